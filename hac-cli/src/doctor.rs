@@ -0,0 +1,87 @@
+use crate::DoctorArgs;
+
+use hac_core::doctor::{self, Checkup};
+
+use std::path::Path;
+
+/// checks the collections directory, printing what was found. Returns the
+/// exit code, 1 when there is any problem
+pub fn run(args: DoctorArgs) -> anyhow::Result<i32> {
+    let dir = hac_config::get_collections_dir();
+    if !dir.is_dir() {
+        anyhow::bail!(
+            "there is no collections directory at {}, it is created once hac runs",
+            dir.to_string_lossy()
+        );
+    }
+
+    let checkup = doctor::check_dir(&dir)?;
+    match args.json {
+        true => println!(
+            "{}",
+            serde_json::to_string_pretty(&as_json(&checkup, &dir))?
+        ),
+        false => print!("{}", format_checkup(&checkup, &dir)),
+    }
+
+    Ok(match checkup.problems.is_empty() {
+        true => 0,
+        false => 1,
+    })
+}
+
+fn format_checkup(checkup: &Checkup, dir: &Path) -> String {
+    let mut output = format!(
+        "checked {} collections on {}\n",
+        checkup.collections.len(),
+        dir.to_string_lossy()
+    );
+
+    match checkup.problems.len() {
+        0 => output.push_str("no problems found\n"),
+        1 => output.push_str("found 1 problem\n"),
+        amount => output.push_str(&format!("found {amount} problems\n")),
+    }
+    for problem in checkup.problems.iter() {
+        output.push_str(&format!("  - {problem}\n"));
+    }
+
+    output
+}
+
+fn as_json(checkup: &Checkup, dir: &Path) -> serde_json::Value {
+    serde_json::json!({
+        "collections_dir": dir,
+        "collections": checkup.collections.len(),
+        "problems": checkup.problems,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problems_are_listed_under_a_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not json").unwrap();
+        let checkup = doctor::check_dir(dir.path()).unwrap();
+
+        let output = format_checkup(&checkup, dir.path());
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            format!("checked 0 collections on {}", dir.path().to_string_lossy())
+        );
+        assert_eq!(lines[1], "found 1 problem");
+        assert!(lines[2].contains("notes.txt: not a collection, at line 1 column 2"));
+
+        let json = as_json(&checkup, dir.path());
+        assert_eq!(json["problems"][0]["kind"], "unparsable");
+        assert_eq!(json["problems"][0]["line"], 1);
+
+        let empty = tempfile::tempdir().unwrap();
+        let checkup = doctor::check_dir(empty.path()).unwrap();
+        assert!(format_checkup(&checkup, empty.path()).ends_with("no problems found\n"));
+    }
+}
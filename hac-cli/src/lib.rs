@@ -1,6 +1,12 @@
-use std::path::Path;
+pub mod doctor;
+pub mod report;
+pub mod runner;
+pub mod transfer;
 
-use clap::Parser;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use report::Report;
 
 /// How the runtime should behave. Dictated by the flags provided to  `Cli`
 #[derive(Debug, PartialEq)]
@@ -15,12 +21,36 @@ pub enum RuntimeBehavior {
     /// will dump the default configuration to stdout instead of running the
     /// application.
     DumpDefaultConfig,
-    /// will run the application with all disk-synchronization disabled. That
-    /// means `HAC` wont't save any files or changes to collection to disk.
-    DryRun,
+    /// prints the version along with the commit it was built from
+    PrintVersion,
     /// the default running behavior of the application, this is the default
     /// behavior for `HAC`.
-    Run,
+    Run(RunOptions),
+    /// runs a request from a collection without starting the TUI, printing
+    /// the response to stdout. Used for scripts and CI
+    Headless(RunArgs),
+    /// writes a collection to a single file without starting the TUI
+    Export(ExportArgs),
+    /// creates a collection from a file without starting the TUI
+    Import(ImportArgs),
+    /// looks for problems on the collections directory and reports them
+    Doctor(DoctorArgs),
+}
+
+/// flags that change how the TUI runs
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct RunOptions {
+    /// will run the application with all disk-synchronization disabled. That
+    /// means `HAC` wont't save any files or changes to collection to disk.
+    pub dry_run: bool,
+    /// starts on the dashboard even when `restore_session` is enabled on the
+    /// config file
+    pub no_restore: bool,
+    /// never touches the network, requests are not sent and nothing is
+    /// looked up on its own, eg: updates
+    pub offline: bool,
+    /// draws without colors, like when `NO_COLOR` is set
+    pub no_color: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -31,33 +61,196 @@ pub struct Cli {
     /// dumps the default configuration to stdout.
     #[arg(long)]
     config_dump: bool,
+    /// reads the config from this file instead, eg: to keep a profile per
+    /// project
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// keeps collections, history, state and logs on this directory
+    #[arg(long, global = true, value_name = "PATH")]
+    data_dir: Option<PathBuf>,
     /// prints the directory in which the collections are being stored
     #[arg(long)]
-    data_dir: bool,
+    show_data_dir: bool,
     /// wether or not we should sync changes to the disk, when --dry-run is
     /// specified, no collection, request, or anything will be saved to disk.
     #[arg(long)]
     dry_run: bool,
+    /// don't reopen the last session, even when `restore_session` is enabled.
+    /// Useful for screenshots and demos
+    #[arg(long)]
+    no_restore: bool,
+    /// never touches the network: requests are not sent and the update
+    /// check is skipped. Same as setting `offline`, `O` toggles it while
+    /// running
+    #[arg(long)]
+    offline: bool,
+    /// draws without colors, telling things apart with bold, dim, underline
+    /// and reverse video instead. Same as setting `NO_COLOR`
+    #[arg(long)]
+    no_color: bool,
+    /// prints the version and the commit it was built from
+    #[arg(long, short = 'V')]
+    version: bool,
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// runs a request from a collection without the TUI
+    Run(RunArgs),
+    /// writes a collection to a single json file that can be imported
+    /// elsewhere, useful for backups and sharing
+    Export(ExportArgs),
+    /// creates a new collection from a file written by `export`, or from a
+    /// `.http` file, either on disk or on a url
+    Import(ImportArgs),
+    /// checks the collections directory for files that can't be read,
+    /// collections sharing a name and variables no environment sets. Exits
+    /// with 1 when anything is found
+    Doctor(DoctorArgs),
+}
+
+#[derive(Args, Debug, PartialEq, Clone)]
+pub struct DoctorArgs {
+    /// prints the problems as json, for tooling
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Clone)]
+pub struct ExportArgs {
+    /// name of the collection, either its display name or its file name
+    /// without the extension
+    pub collection: String,
+    /// file the collection is written to
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// also writes the values of secret variables, which are left out by
+    /// default
+    #[arg(long)]
+    pub include_secrets: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Clone)]
+pub struct ImportArgs {
+    /// file or url to import, either an exported collection, a `.http` file
+    /// or an insomnia export. Which one it is is told by its content
+    pub file: String,
+}
+
+#[derive(Args, Debug, PartialEq, Clone)]
+pub struct RunArgs {
+    /// name of the collection, either its display name or its file name
+    /// without the extension
+    pub collection: String,
+    /// name of the request to run, not needed when running with `--all`,
+    /// `--folder`, `--flow` or `--tag`
+    #[arg(required_unless_present_any = ["all", "folder", "flow", "tag"])]
+    pub request: Option<String>,
+    /// runs every request on the collection, directories are ran depth-first
+    /// in the order they appear
+    #[arg(long, conflicts_with_all = ["request", "folder", "flow"])]
+    pub all: bool,
+    /// runs every request inside the given directory
+    #[arg(long, conflicts_with_all = ["request", "flow"])]
+    pub folder: Option<String>,
+    /// runs the steps of the given flow in order, stopping on the first one
+    /// that fails
+    #[arg(long, conflicts_with = "request")]
+    pub flow: Option<String>,
+    /// only runs the requests tagged with it, out of the whole collection or
+    /// of `--folder`. Given more than once, requests with any of the tags run
+    #[arg(long, conflicts_with_all = ["request", "flow"])]
+    pub tag: Vec<String>,
+    /// milliseconds to wait between requests when running more than one
+    #[arg(long, default_value_t = 0)]
+    pub delay_ms: u64,
+    /// most requests sent on any second when running more than one, short
+    /// bursts up to this many requests are allowed
+    #[arg(long)]
+    pub max_rps: Option<f64>,
+    /// how many requests can be in flight at once when running more than
+    /// one. Requests using values captured by earlier ones still wait for them
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub parallel: u16,
+    /// waits as long as a `429` response asks on its `Retry-After` header,
+    /// then sends the request once more
+    #[arg(long)]
+    pub respect_retry_after: bool,
+    /// stops running on the first failing request
+    #[arg(long)]
+    pub bail: bool,
+    /// environment used to resolve `{{variables}}` on the request
+    #[arg(long, short)]
+    pub env: Option<String>,
+    /// includes the response headers on the output
+    #[arg(short, long)]
+    pub include: bool,
+    /// format in which the response is printed
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+    /// makes 4xx responses count as passing. They exit with a non-zero
+    /// code otherwise, as 5xx responses and network errors always do
+    #[arg(long)]
+    pub allow_4xx: bool,
+    /// writes a report of the run once it is over, as `junit=<path>` or
+    /// `json=<path>`. Can be given more than once
+    #[arg(long, conflicts_with = "request")]
+    pub report: Vec<Report>,
+}
+
+#[derive(ValueEnum, Debug, PartialEq, Clone, Copy)]
+pub enum OutputFormat {
+    /// status line, optional headers and the body, like curl would print
+    Text,
+    /// a json envelope with status, timing, headers and body
+    Json,
 }
 
 impl Cli {
     pub fn parse_args() -> RuntimeBehavior {
-        let args = Cli::parse();
+        let cli = Cli::parse();
+        hac_config::paths::set_overrides(cli.path_overrides());
+        cli.into_behavior()
+    }
+
+    /// where `--config` and `--data-dir` point to, which every command
+    /// reads and writes through
+    fn path_overrides(&self) -> hac_config::paths::Overrides {
+        hac_config::paths::Overrides {
+            config_file: self.config.clone(),
+            data_dir: self.data_dir.clone(),
+        }
+    }
 
-        if args.config_dir {
+    fn into_behavior(self) -> RuntimeBehavior {
+        match self.command {
+            Some(Commands::Run(run_args)) => return RuntimeBehavior::Headless(run_args),
+            Some(Commands::Export(args)) => return RuntimeBehavior::Export(args),
+            Some(Commands::Import(args)) => return RuntimeBehavior::Import(args),
+            Some(Commands::Doctor(args)) => return RuntimeBehavior::Doctor(args),
+            None => {}
+        }
+        if self.config_dir {
             return RuntimeBehavior::PrintConfigPath;
         }
-        if args.data_dir {
+        if self.show_data_dir {
             return RuntimeBehavior::PrintDataPath;
         }
-        if args.config_dump {
+        if self.config_dump {
             return RuntimeBehavior::DumpDefaultConfig;
         }
-        if args.dry_run {
-            return RuntimeBehavior::DryRun;
+        if self.version {
+            return RuntimeBehavior::PrintVersion;
         }
 
-        RuntimeBehavior::Run
+        RuntimeBehavior::Run(RunOptions {
+            dry_run: self.dry_run,
+            no_restore: self.no_restore,
+            offline: self.offline,
+            no_color: self.no_color,
+        })
     }
 
     pub fn print_data_path<P>(data_path: P)
@@ -93,4 +286,270 @@ impl Cli {
     pub fn print_default_config(config_as_str: &str) {
         println!("{}", config_as_str)
     }
+
+    /// the binary knows its own version and commit, so they are given here
+    /// instead of using the ones of this crate
+    pub fn print_version(version: &str, commit: &str, target: &str) {
+        println!("hac {version}");
+        println!("commit: {commit}");
+        println!("target: {target}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_run_subcommand() {
+        let args = Cli::try_parse_from([
+            "hac",
+            "run",
+            "my_api",
+            "login",
+            "--env",
+            "staging",
+            "-i",
+            "--output",
+            "json",
+            "--allow-4xx",
+        ])
+        .unwrap();
+
+        let Some(Commands::Run(run_args)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+
+        assert_eq!(
+            run_args,
+            RunArgs {
+                collection: "my_api".into(),
+                request: Some("login".into()),
+                all: false,
+                folder: None,
+                flow: None,
+                tag: vec![],
+                delay_ms: 0,
+                max_rps: None,
+                parallel: 1,
+                respect_retry_after: false,
+                bail: false,
+                env: Some("staging".into()),
+                include: true,
+                output: OutputFormat::Json,
+                allow_4xx: true,
+                report: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_subcommand_defaults() {
+        let args = Cli::try_parse_from(["hac", "run", "my_api", "login"]).unwrap();
+
+        let Some(Commands::Run(run_args)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+
+        assert_eq!(run_args.output, OutputFormat::Text);
+        assert!(!run_args.allow_4xx);
+        assert!(!run_args.include);
+    }
+
+    #[test]
+    fn test_parsing_run_all_subcommand() {
+        let args = Cli::try_parse_from([
+            "hac",
+            "run",
+            "my_api",
+            "--all",
+            "--delay-ms",
+            "250",
+            "--bail",
+            "--max-rps",
+            "2.5",
+            "--parallel",
+            "4",
+            "--respect-retry-after",
+        ])
+        .unwrap();
+
+        let Some(Commands::Run(run_args)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+
+        assert!(run_args.all);
+        assert!(run_args.bail);
+        assert_eq!(run_args.request, None);
+        assert_eq!(run_args.delay_ms, 250);
+        assert_eq!(run_args.max_rps, Some(2.5));
+        assert_eq!(run_args.parallel, 4);
+        assert!(run_args.respect_retry_after);
+        assert!(Cli::try_parse_from(["hac", "run", "my_api", "--all", "--parallel", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parsing_tui_flags() {
+        let behavior = Cli::try_parse_from(["hac"]).unwrap().into_behavior();
+        assert_eq!(behavior, RuntimeBehavior::Run(RunOptions::default()));
+
+        let behavior = Cli::try_parse_from([
+            "hac",
+            "--dry-run",
+            "--no-restore",
+            "--offline",
+            "--no-color",
+        ])
+        .unwrap()
+        .into_behavior();
+        assert_eq!(
+            behavior,
+            RuntimeBehavior::Run(RunOptions {
+                dry_run: true,
+                no_restore: true,
+                offline: true,
+                no_color: true,
+            })
+        );
+
+        let behavior = Cli::try_parse_from(["hac", "-V"]).unwrap().into_behavior();
+        assert_eq!(behavior, RuntimeBehavior::PrintVersion);
+    }
+
+    #[test]
+    fn test_parsing_export_and_import_subcommands() {
+        let behavior = Cli::try_parse_from(["hac", "export", "my_api", "-o", "api.json"])
+            .unwrap()
+            .into_behavior();
+        assert_eq!(
+            behavior,
+            RuntimeBehavior::Export(ExportArgs {
+                collection: "my_api".into(),
+                output: "api.json".into(),
+                include_secrets: false,
+            })
+        );
+
+        assert!(Cli::try_parse_from(["hac", "export", "my_api"]).is_err());
+
+        let behavior = Cli::try_parse_from([
+            "hac",
+            "export",
+            "my_api",
+            "-o",
+            "api.json",
+            "--include-secrets",
+        ])
+        .unwrap()
+        .into_behavior();
+        assert!(matches!(
+            behavior,
+            RuntimeBehavior::Export(ExportArgs {
+                include_secrets: true,
+                ..
+            })
+        ));
+
+        let behavior = Cli::try_parse_from(["hac", "import", "api.json"])
+            .unwrap()
+            .into_behavior();
+        assert_eq!(
+            behavior,
+            RuntimeBehavior::Import(ImportArgs {
+                file: "api.json".into(),
+            })
+        );
+
+        let behavior = Cli::try_parse_from(["hac", "import", "https://example.com/api.http"])
+            .unwrap()
+            .into_behavior();
+        assert_eq!(
+            behavior,
+            RuntimeBehavior::Import(ImportArgs {
+                file: "https://example.com/api.http".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parsing_doctor_subcommand() {
+        let behavior = Cli::try_parse_from(["hac", "doctor"])
+            .unwrap()
+            .into_behavior();
+        assert_eq!(
+            behavior,
+            RuntimeBehavior::Doctor(DoctorArgs { json: false })
+        );
+
+        let behavior = Cli::try_parse_from(["hac", "doctor", "--json"])
+            .unwrap()
+            .into_behavior();
+        assert_eq!(behavior, RuntimeBehavior::Doctor(DoctorArgs { json: true }));
+    }
+
+    #[test]
+    fn test_run_requires_a_request_or_a_scope() {
+        assert!(Cli::try_parse_from(["hac", "run", "my_api"]).is_err());
+        assert!(Cli::try_parse_from(["hac", "run", "my_api", "login", "--all"]).is_err());
+        assert!(Cli::try_parse_from(["hac", "run", "my_api", "--folder", "auth"]).is_ok());
+        assert!(Cli::try_parse_from(["hac", "run", "my_api", "--flow", "smoke"]).is_ok());
+        assert!(Cli::try_parse_from(["hac", "run", "my_api", "--flow", "smoke", "--all"]).is_err());
+        assert!(
+            Cli::try_parse_from(["hac", "run", "my_api", "--tag", "smoke", "--flow", "a"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parsing_run_by_tags() {
+        let args = Cli::try_parse_from([
+            "hac", "run", "my_api", "--tag", "smoke", "--tag", "admin", "--folder", "auth",
+        ])
+        .unwrap();
+
+        let Some(Commands::Run(run_args)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+
+        assert_eq!(run_args.request, None);
+        assert_eq!(run_args.folder, Some("auth".into()));
+        assert_eq!(run_args.tag, vec!["smoke", "admin"]);
+    }
+
+    #[test]
+    fn test_parsing_path_overrides() {
+        let cli = Cli::try_parse_from(["hac", "--show-data-dir"]).unwrap();
+        assert_eq!(
+            cli.path_overrides(),
+            hac_config::paths::Overrides::default()
+        );
+        assert_eq!(cli.into_behavior(), RuntimeBehavior::PrintDataPath);
+
+        // a directory has to be given, the next word is never taken for it
+        assert!(Cli::try_parse_from(["hac", "--data-dir"]).is_err());
+        let cli = Cli::try_parse_from(["hac", "--data-dir", "run", "doctor"]).unwrap();
+        assert_eq!(cli.path_overrides().data_dir, Some(PathBuf::from("run")));
+        assert!(matches!(cli.into_behavior(), RuntimeBehavior::Doctor(_)));
+
+        let cli = Cli::try_parse_from([
+            "hac",
+            "--config",
+            "work.toml",
+            "--data-dir",
+            "./work",
+            "--offline",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.path_overrides(),
+            hac_config::paths::Overrides {
+                config_file: Some("work.toml".into()),
+                data_dir: Some("./work".into()),
+            }
+        );
+        assert!(matches!(cli.into_behavior(), RuntimeBehavior::Run(_)));
+
+        // they apply to every subcommand as well
+        let cli = Cli::try_parse_from(["hac", "doctor", "--data-dir", "./work"]).unwrap();
+        assert_eq!(cli.path_overrides().data_dir, Some(PathBuf::from("./work")));
+    }
 }
@@ -0,0 +1,460 @@
+use hac_core::collection::types::RequestMethod;
+use hac_core::runner::{RunResult, RunSummary};
+
+use crate::runner::format_assertion;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// a report to write once the run is over, given as `<format>=<path>`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Report {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl FromStr for Report {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or("expected <format>=<path>, eg: junit=report.xml")?;
+
+        let format = match format.trim() {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            format => {
+                return Err(format!(
+                    "unknown report format {format:?}, use junit or json"
+                ))
+            }
+        };
+
+        if path.trim().is_empty() {
+            return Err("the report needs a path to be written to".into());
+        }
+
+        Ok(Report {
+            format,
+            path: PathBuf::from(path.trim()),
+        })
+    }
+}
+
+/// a request that was meant to run, with its result when it did
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReportEntry {
+    pub name: String,
+    /// directories the request is in, joined by `/`
+    pub folder: String,
+    pub method: RequestMethod,
+    pub tags: Vec<String>,
+    pub result: Option<RunResult>,
+}
+
+impl ReportEntry {
+    fn outcome(&self) -> &'static str {
+        match self.result.as_ref() {
+            Some(result) if result.passed => "passed",
+            Some(_) => "failed",
+            None => "skipped",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RunReport {
+    pub collection: String,
+    pub entries: Vec<ReportEntry>,
+    pub summary: RunSummary,
+}
+
+impl RunReport {
+    fn duration(&self) -> Duration {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.result.as_ref())
+            .map(|result| result.duration)
+            .sum()
+    }
+
+    /// writes every report, even when some fail to be written, returning the
+    /// first error
+    pub fn write(&self, reports: &[Report]) -> anyhow::Result<()> {
+        let mut first_error = None;
+
+        for report in reports {
+            let content = match report.format {
+                ReportFormat::Junit => self.to_junit(),
+                ReportFormat::Json => serde_json::to_string_pretty(&self.to_json())?,
+            };
+
+            let written = report
+                .path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&report.path, content))
+                .with_context(|| format!("failed to write report to {:?}", report.path));
+
+            if let Err(e) = written {
+                eprintln!("{e:#}");
+                first_error.get_or_insert(e);
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// the report has this shape:
+    ///
+    /// ```json
+    /// {
+    ///   "collection": "my api",
+    ///   "summary": { "total": 3, "passed": 1, "failed": 1, "skipped": 1, "duration_ms": 62 },
+    ///   "requests": [
+    ///     {
+    ///       "name": "login",
+    ///       "folder": "auth/admin",
+    ///       "method": "POST",
+    ///       "tags": ["smoke"],
+    ///       "outcome": "passed",
+    ///       "status": 201,
+    ///       "duration_ms": 42,
+    ///       "size": 128,
+    ///       "error": null,
+    ///       "assertions": [
+    ///         { "assertion": "status 2xx", "passed": true, "actual": "201", "message": "PASS status 2xx" }
+    ///       ]
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// `outcome` is one of `passed`, `failed` or `skipped`. `folder` is empty
+    /// for requests at the root of the collection. `status`, `duration_ms`,
+    /// `size` and `error` are `null` when they don't apply, which for skipped
+    /// requests is all of them
+    pub fn to_json(&self) -> serde_json::Value {
+        let requests = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let result = entry.result.as_ref();
+                let assertions = result
+                    .map(|result| result.assertions.as_slice())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|assertion| {
+                        serde_json::json!({
+                            "assertion": assertion.assertion.to_string(),
+                            "passed": assertion.passed,
+                            "actual": assertion.actual,
+                            "message": format_assertion(assertion).trim(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                serde_json::json!({
+                    "name": entry.name,
+                    "folder": entry.folder,
+                    "method": entry.method.to_string(),
+                    "tags": entry.tags,
+                    "outcome": entry.outcome(),
+                    "status": result.and_then(|result| result.status),
+                    "duration_ms": result.map(|result| result.duration.as_millis() as u64),
+                    "size": result.and_then(|result| result.size),
+                    "error": result.and_then(|result| result.error.clone()),
+                    "assertions": assertions,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "collection": self.collection,
+            "summary": {
+                "total": self.entries.len(),
+                "passed": self.summary.passed,
+                "failed": self.summary.failed,
+                "skipped": self.summary.skipped,
+                "duration_ms": self.duration().as_millis() as u64,
+            },
+            "requests": requests,
+        })
+    }
+
+    /// one `testsuite` for the collection with a `testcase` per request, the
+    /// folder of the request being its `classname` and its tags going on its
+    /// `properties`. Failed assertions and
+    /// unexpected statuses are `failure`s, requests that couldn't be sent are
+    /// `error`s and requests that never ran are `skipped`
+    pub fn to_junit(&self) -> String {
+        let errors = self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.result.as_ref())
+            .filter(|result| !result.passed && result.status.is_none())
+            .count();
+        let failures = self.summary.failed.saturating_sub(errors);
+        let time = seconds(self.duration());
+        let tests = self.entries.len();
+        let skipped = self.summary.skipped;
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"hac\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\" time=\"{time}\">\n"
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\" time=\"{time}\">\n",
+            escape(&self.collection)
+        ));
+
+        for entry in self.entries.iter() {
+            xml.push_str(&self.junit_testcase(entry));
+        }
+
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    fn junit_testcase(&self, entry: &ReportEntry) -> String {
+        let classname = match entry.folder.is_empty() {
+            true => self.collection.clone(),
+            false => format!("{}.{}", self.collection, entry.folder.replace('/', ".")),
+        };
+        let name = format!("{} {}", entry.method, entry.name);
+        let time = entry
+            .result
+            .as_ref()
+            .map(|result| seconds(result.duration))
+            .unwrap_or_else(|| seconds(Duration::ZERO));
+
+        let mut testcase = format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{time}\">\n",
+            escape(&name),
+            escape(&classname),
+        );
+
+        if !entry.tags.is_empty() {
+            testcase.push_str("      <properties>\n");
+            for tag in entry.tags.iter() {
+                testcase.push_str(&format!(
+                    "        <property name=\"tag\" value=\"{}\"/>\n",
+                    escape(tag)
+                ));
+            }
+            testcase.push_str("      </properties>\n");
+        }
+
+        match entry.result.as_ref() {
+            None => testcase.push_str(
+                "      <skipped message=\"not run, the run stopped on an earlier failure\"/>\n",
+            ),
+            Some(result) if !result.passed && result.status.is_none() => {
+                let error = result.error.as_deref().unwrap_or("request failed");
+                testcase.push_str(&format!(
+                    "      <error message=\"{}\" type=\"error\"/>\n",
+                    escape(error)
+                ));
+            }
+            Some(result) if !result.passed => {
+                let failed = result.assertions.iter().filter(|a| !a.passed).count();
+                let message = match failed {
+                    0 => format!("unexpected status {}", status_text(result)),
+                    _ => format!(
+                        "{} of {} assertions failed",
+                        failed,
+                        result.assertions.len()
+                    ),
+                };
+                let details = result
+                    .assertions
+                    .iter()
+                    .filter(|assertion| !assertion.passed)
+                    .map(|assertion| escape(format_assertion(assertion).trim()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                testcase.push_str(&format!(
+                    "      <failure message=\"{}\" type=\"assertion\">{details}</failure>\n",
+                    escape(&message)
+                ));
+            }
+            Some(_) => {}
+        }
+
+        if let Some(result) = entry
+            .result
+            .as_ref()
+            .filter(|result| result.status.is_some())
+        {
+            let size = result
+                .size
+                .map(|size| format!(", {size} bytes"))
+                .unwrap_or_default();
+            testcase.push_str(&format!(
+                "      <system-out>{}{size}</system-out>\n",
+                status_text(result)
+            ));
+        }
+
+        testcase.push_str("    </testcase>\n");
+        testcase
+    }
+}
+
+fn status_text(result: &RunResult) -> String {
+    result
+        .status
+        .map(|status| status.to_string())
+        .unwrap_or("---".into())
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hac_core::assertions::AssertionResult;
+
+    fn result(name: &str, status: Option<u16>, passed: bool) -> RunResult {
+        RunResult {
+            name: name.into(),
+            method: RequestMethod::Get,
+            status,
+            duration: Duration::from_millis(20),
+            passed,
+            error: None,
+            assertions: vec![],
+            captures: vec![],
+            size: status.map(|_| 128),
+        }
+    }
+
+    fn fixture_report() -> RunReport {
+        let mut login = result("login", Some(201), true);
+        login.assertions = vec![AssertionResult {
+            assertion: "status 2xx".parse().unwrap(),
+            passed: true,
+            actual: "201".into(),
+        }];
+
+        let mut me = result("me", Some(200), false);
+        me.assertions = vec![
+            AssertionResult {
+                assertion: "status 2xx".parse().unwrap(),
+                passed: true,
+                actual: "200".into(),
+            },
+            AssertionResult {
+                assertion: "$.name == \"admin\"".parse().unwrap(),
+                passed: false,
+                actual: "\"guest\"".into(),
+            },
+        ];
+
+        let mut health = result("health", None, false);
+        health.error = Some("connection refused".into());
+
+        let entry = |name: &str, folder: &str, result: Option<RunResult>| ReportEntry {
+            name: name.into(),
+            folder: folder.into(),
+            method: RequestMethod::Get,
+            tags: vec![],
+            result,
+        };
+        let tagged = |entry: ReportEntry| ReportEntry {
+            tags: vec!["smoke".into(), "admin".into()],
+            ..entry
+        };
+
+        RunReport {
+            collection: "users & roles".into(),
+            entries: vec![
+                tagged(entry("login", "auth/admin", Some(login))),
+                entry("me", "auth", Some(me)),
+                entry("health", "", Some(health)),
+                entry("logout", "auth", None),
+            ],
+            summary: RunSummary {
+                passed: 1,
+                failed: 2,
+                skipped: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_parsing_reports() {
+        assert_eq!(
+            "junit=out/report.xml".parse::<Report>(),
+            Ok(Report {
+                format: ReportFormat::Junit,
+                path: "out/report.xml".into()
+            })
+        );
+        assert_eq!(
+            "json=report.json".parse::<Report>().unwrap().format,
+            ReportFormat::Json
+        );
+        assert!("xml=report.xml".parse::<Report>().is_err());
+        assert!("junit".parse::<Report>().is_err());
+        assert!("junit=".parse::<Report>().is_err());
+    }
+
+    #[test]
+    fn test_junit_report_matches_the_fixture() {
+        assert_eq!(
+            fixture_report().to_junit(),
+            include_str!("fixtures/run_report.xml")
+        );
+    }
+
+    #[test]
+    fn test_json_report_marks_skipped_requests() {
+        let json = fixture_report().to_json();
+
+        assert_eq!(json["summary"]["total"], 4);
+        assert_eq!(json["summary"]["duration_ms"], 60);
+        assert_eq!(json["requests"][0]["folder"], "auth/admin");
+        assert_eq!(json["requests"][0]["size"], 128);
+        assert_eq!(
+            json["requests"][0]["tags"],
+            serde_json::json!(["smoke", "admin"])
+        );
+        assert_eq!(json["requests"][1]["tags"], serde_json::json!([]));
+        assert_eq!(json["requests"][1]["outcome"], "failed");
+        assert_eq!(
+            json["requests"][1]["assertions"][1]["message"],
+            "FAIL $.name == admin (got \"guest\")"
+        );
+        assert_eq!(json["requests"][3]["outcome"], "skipped");
+        assert_eq!(json["requests"][3]["status"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,744 @@
+use crate::report::{ReportEntry, RunReport};
+use crate::{OutputFormat, RunArgs};
+
+use hac_core::assertions::{self, AssertionResult};
+use hac_core::captures::CaptureResult;
+use hac_core::collection::{collection, format_names, types::Request, Collection};
+use hac_core::hooks::{self, TrustedHooks};
+use hac_core::net::request_client::ClientOptions;
+use hac_core::net::request_manager::{version_name, Response};
+use hac_core::net::send_request;
+use hac_core::net::uri;
+use hac_core::runner::{self, PacingReason, RunEvent, RunOptions, RunResult, RunSummary};
+use hac_core::secrets::Secrets;
+use hac_core::variables;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// runs the requests selected by the arguments and prints the responses
+/// according to the chosen output format, returning the exit code the process
+/// should finish with
+pub async fn run(args: RunArgs) -> anyhow::Result<i32> {
+    let config = hac_config::try_load_config()?;
+    hac_config::set_read_only_dirs(config.read_only_dirs.clone());
+    let collections = collection::get_collections_from_config()?;
+    let collection = find_collection(&collections, &args.collection)?;
+    let client = ClientOptions::new(&config, collection);
+
+    let secrets = Secrets::load(hac_config::secrets_file());
+    secrets.fill_auth(collection);
+    let variables = collection.layers(args.env.as_deref(), &secrets)?.merged();
+
+    // hooks are trusted from the app, the same way they are for sending
+    let run_hooks = TrustedHooks::load(hac_config::trusted_hooks_file()).is_trusted(collection);
+    let options = run_options(&args, run_hooks, client);
+
+    match args.request.as_ref() {
+        Some(name) => run_single(&args, collection, name, variables, options).await,
+        None => run_many(&args, collection, variables, options).await,
+    }
+}
+
+/// the defaults every run starts from, 4xx responses fail unless the
+/// arguments allow them
+fn run_options(args: &RunArgs, run_hooks: bool, client: ClientOptions) -> RunOptions {
+    let options = RunOptions {
+        run_hooks,
+        client,
+        ..Default::default()
+    };
+    RunOptions {
+        fail_on_4xx: options.fail_on_4xx && !args.allow_4xx,
+        ..options
+    }
+}
+
+async fn run_single(
+    args: &RunArgs,
+    collection: &Collection,
+    name: &str,
+    mut variables: BTreeMap<String, String>,
+    options: RunOptions,
+) -> anyhow::Result<i32> {
+    let request = collection.request(name)?;
+    let request_hooks = match request.hooks.clone() {
+        Some(request_hooks) if options.run_hooks => request_hooks,
+        Some(_) => {
+            eprintln!(
+                "warning: skipped the hooks of {name}, the hooks of the collection aren't trusted"
+            );
+            Default::default()
+        }
+        None => Default::default(),
+    };
+    if let Some(hook) = request_hooks.pre_request.as_ref() {
+        variables.extend(hooks::run_pre_request(hook, &request).await?);
+    }
+
+    let client = options.client;
+    let mut request = variables::resolve_request(&request, &variables)?;
+    let normalized = uri::normalize(&request.uri)?;
+    for warning in normalized.warnings {
+        eprintln!("warning: {warning}");
+    }
+    request.uri = normalized.uri;
+    if let Some(oversized) = client.oversized_body(&request) {
+        eprintln!("warning: {oversized}");
+    }
+    let response = send_request(request.clone(), client).await;
+    if let Some(hook) = request_hooks.post_response.as_ref() {
+        let summary = hooks::response_summary(&request, &response);
+        if let Err(e) = hooks::run_post_response(hook, &request, &summary).await {
+            eprintln!("warning: {e:#}");
+        }
+    }
+    let results =
+        assertions::evaluate_all(request.assertions.as_deref().unwrap_or_default(), &response);
+
+    match args.output {
+        OutputFormat::Text => print_text(&response, &results, args.include),
+        OutputFormat::Json => print_json(&request, &response, &results)?,
+    }
+
+    // requests with assertions are judged by them, just like on collection runs
+    if !results.is_empty() {
+        let passed = !response.is_error && results.iter().all(|result| result.passed);
+        return Ok(if passed { 0 } else { 1 });
+    }
+
+    Ok(exit_code(
+        response.status.map(|status| status.as_u16()),
+        options.fail_on_4xx,
+    ))
+}
+
+async fn run_many(
+    args: &RunArgs,
+    collection: &Collection,
+    variables: BTreeMap<String, String>,
+    options: RunOptions,
+) -> anyhow::Result<i32> {
+    let requests = match (args.folder.as_ref(), args.flow.as_ref()) {
+        (Some(folder), _) => {
+            let directory = collection.find_directory(folder).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "directory {:?} not found on collection {:?}",
+                    folder,
+                    collection.info.name
+                )
+            })?;
+            with_defaults(collection, directory.flatten_requests())
+        }
+        (None, Some(flow)) => collection.flow(flow)?,
+        (None, None) => with_defaults(collection, collection.flatten_requests()),
+    };
+    let requests = with_tags(collection, requests, &args.tag)?;
+    let planned = requests
+        .iter()
+        .map(|request| ReportEntry {
+            name: request.name.clone(),
+            folder: collection.directory_path(&request.id).join("/"),
+            method: request.method.clone(),
+            tags: request.tags.clone(),
+            result: None,
+        })
+        .collect::<Vec<_>>();
+
+    let options = RunOptions {
+        delay: Duration::from_millis(args.delay_ms),
+        // later steps of a flow expect the earlier ones to have worked
+        bail: args.bail || args.flow.is_some(),
+        max_rps: args.max_rps,
+        parallel: args.parallel.into(),
+        respect_retry_after: args.respect_retry_after,
+        ..options
+    };
+
+    let mut results = vec![];
+    let output = args.output;
+    let summary = runner::run_requests(requests, variables, options, |event| match event {
+        RunEvent::RequestFinished(result) => {
+            if output.eq(&OutputFormat::Text) {
+                println!("{}", format_result(&result));
+            }
+            warn_unresolved_captures(&result.name, &result.captures);
+            results.push(result);
+        }
+        RunEvent::Warning(warning) => eprintln!("warning: {warning}"),
+        // the delay between requests was asked for, so it isn't reported
+        RunEvent::Waiting(pacing) if pacing.reason.ne(&PacingReason::Delay) => {
+            eprintln!("{pacing}")
+        }
+        _ => {}
+    })
+    .await;
+
+    // results come in the order the requests were given, whatever is left
+    // after them never ran
+    let entries = planned
+        .into_iter()
+        .zip(
+            results
+                .iter()
+                .cloned()
+                .map(Some)
+                .chain(std::iter::repeat(None)),
+        )
+        .map(|(entry, result)| ReportEntry { result, ..entry })
+        .collect();
+    let report = RunReport {
+        collection: collection.info.name.clone(),
+        entries,
+        summary: summary.clone(),
+    };
+    // reports are written before anything else can fail, so CI always gets one
+    let written = report.write(&args.report);
+
+    let captured = results
+        .iter()
+        .flat_map(|result| result.captures.clone())
+        .collect::<Vec<_>>();
+    persist_captures(args, collection, &captured).await?;
+
+    match args.output {
+        OutputFormat::Text => println!(
+            "\n{} passed, {} failed, {} skipped",
+            summary.passed, summary.failed, summary.skipped
+        ),
+        OutputFormat::Json => print_json_summary(&results, &summary)?,
+    }
+    written?;
+
+    Ok(match summary.is_success() {
+        true => 0,
+        false => 1,
+    })
+}
+
+/// the requests as they are sent, with the collection defaults and hooks
+fn with_defaults(collection: &Collection, requests: Vec<Arc<RwLock<Request>>>) -> Vec<Request> {
+    requests
+        .iter()
+        .map(|req| collection.runnable(&req.read().unwrap()))
+        .collect()
+}
+
+/// keeps the requests carrying any of `tags`, or all of them when no tag was
+/// given. Running nothing is most likely a typo on the tag, so it errors
+fn with_tags(
+    collection: &Collection,
+    requests: Vec<Request>,
+    tags: &[String],
+) -> anyhow::Result<Vec<Request>> {
+    if tags.is_empty() {
+        return Ok(requests);
+    }
+
+    let requests = requests
+        .into_iter()
+        .filter(|request| tags.iter().any(|tag| request.has_tag(tag)))
+        .collect::<Vec<_>>();
+    if requests.is_empty() {
+        anyhow::bail!(
+            "no request to run is tagged {}, tags on collection {:?} are:\n{}",
+            tags.join(" or "),
+            collection.info.name,
+            format_names(&collection.tags().into_iter().collect::<Vec<_>>())
+        );
+    }
+
+    Ok(requests)
+}
+
+/// 2xx and 3xx responses are always successful, 4xx responses are failures
+/// unless `fail_on_4xx` is disabled, and anything else, including requests
+/// that never got a response, is a failure
+pub fn exit_code(status: Option<u16>, fail_on_4xx: bool) -> i32 {
+    match runner::is_passing_status(status, fail_on_4xx) {
+        true => 0,
+        false => 1,
+    }
+}
+
+fn format_result(result: &RunResult) -> String {
+    let outcome = if result.passed { "PASS" } else { "FAIL" };
+    let status = result
+        .status
+        .map(|status| status.to_string())
+        .unwrap_or("---".into());
+
+    let mut line = format!(
+        "{} {:<7} {:<30} {} {}ms",
+        outcome,
+        result.method.to_string(),
+        result.name,
+        status,
+        result.duration.as_millis()
+    );
+
+    if let Some(error) = result.error.as_ref() {
+        line.push_str(&format!(" ({})", error));
+    }
+
+    for assertion in result.assertions.iter().filter(|a| !a.passed) {
+        line.push('\n');
+        line.push_str(&format_assertion(assertion));
+    }
+
+    line
+}
+
+/// unresolved captures don't fail the request, but the variables they would
+/// set are probably needed by later requests, so we let the user know
+fn warn_unresolved_captures(request: &str, results: &[CaptureResult]) {
+    for result in results.iter().filter(|result| result.value.is_none()) {
+        eprintln!(
+            "warning: capture `{}` on {:?} didn't match anything on the response",
+            result.capture, request
+        );
+    }
+}
+
+/// writes captures marked as persisted into the selected environment and
+/// saves the collection, captured secrets go to the secrets file instead.
+/// When no environment was selected there is nowhere to persist them to, so
+/// they are only used during this run
+async fn persist_captures(
+    args: &RunArgs,
+    collection: &Collection,
+    results: &[CaptureResult],
+) -> anyhow::Result<()> {
+    let persisted = results
+        .iter()
+        .filter(|result| result.capture.persist)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if persisted.iter().all(|result| result.value.is_none()) {
+        return Ok(());
+    }
+
+    let Some(env) = args.env.as_ref() else {
+        eprintln!("warning: no environment selected, persisted captures were not saved");
+        return Ok(());
+    };
+
+    let mut collection = collection.clone();
+    let key = Secrets::key(&collection);
+    if let Some(environment) = collection
+        .environments
+        .iter_mut()
+        .flatten()
+        .find(|environment| environment.name.eq(env))
+    {
+        let mut secrets = Secrets::load(hac_config::secrets_file());
+        if secrets.apply_captures(&key, environment, &persisted) {
+            secrets.save()?;
+        }
+    }
+
+    // secrets live apart from the collection, so they are kept either way
+    if collection.is_read_only() {
+        eprintln!(
+            "warning: {} is read-only, only the captures into secrets were saved",
+            collection.info.name
+        );
+        return Ok(());
+    }
+
+    hac_core::fs::sync_collection(collection)
+        .await
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub(crate) fn format_assertion(result: &AssertionResult) -> String {
+    match result.passed {
+        true => format!("  PASS {}", result.assertion),
+        false => format!("  FAIL {} (got {})", result.assertion, result.actual),
+    }
+}
+
+fn assertions_as_json(results: &[AssertionResult]) -> serde_json::Value {
+    results
+        .iter()
+        .map(|result| {
+            serde_json::json!({
+                "assertion": result.assertion.to_string(),
+                "passed": result.passed,
+                "actual": result.actual,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn find_collection<'a>(
+    collections: &'a [Collection],
+    name: &str,
+) -> anyhow::Result<&'a Collection> {
+    collections
+        .iter()
+        .find(|collection| {
+            collection.info.name.eq(name)
+                || collection
+                    .path
+                    .file_stem()
+                    .is_some_and(|stem| stem.to_string_lossy() == name)
+        })
+        .ok_or_else(|| {
+            let names = collections
+                .iter()
+                .map(|collection| collection.info.name.clone())
+                .collect::<Vec<_>>();
+            anyhow::anyhow!(
+                "collection {:?} not found, available collections are:\n{}",
+                name,
+                format_names(&names)
+            )
+        })
+}
+
+fn print_text(response: &Response, results: &[AssertionResult], include: bool) {
+    if response.is_error {
+        eprintln!(
+            "request failed: {}",
+            response.cause.as_deref().unwrap_or("unknown error")
+        );
+        return;
+    }
+    // the body is printed as received when it couldn't be decompressed
+    if let Some(cause) = response.cause.as_ref() {
+        eprintln!("warning: {cause}");
+    }
+
+    if let Some(status) = response.status {
+        let version = response.version.map(version_name).unwrap_or("HTTP");
+        match response.proxy.as_ref() {
+            Some(proxy) => println!(
+                "{} {} ({}ms, via proxy {})",
+                version,
+                status,
+                response.duration.as_millis(),
+                proxy
+            ),
+            None => println!(
+                "{} {} ({}ms)",
+                version,
+                status,
+                response.duration.as_millis()
+            ),
+        }
+    }
+
+    if include {
+        for (name, value) in response.headers.iter().flatten() {
+            println!("{}: {}", name, value.to_str().unwrap_or_default());
+        }
+        println!();
+    }
+
+    // bodies too big for memory only have their start on the response, the
+    // whole body is copied from the file it was written to
+    if let Some(spilled) = response.spilled.as_ref() {
+        let copied = std::fs::File::open(&spilled.path)
+            .and_then(|mut file| std::io::copy(&mut file, &mut std::io::stdout()));
+        if let Err(e) = copied {
+            eprintln!("failed to read the response body: {e}");
+        }
+    } else if let Some(body) = response.body.as_ref() {
+        println!("{}", body);
+    }
+
+    // binary bodies are written as they came, so they can be piped into a file
+    if let Some(bytes) = response
+        .binary_body
+        .as_ref()
+        .filter(|_| response.spilled.is_none())
+    {
+        use std::io::Write;
+        let mut stdout = std::io::stdout();
+        _ = stdout.write_all(bytes);
+        _ = stdout.flush();
+    }
+
+    if !results.is_empty() {
+        println!();
+        results
+            .iter()
+            .for_each(|result| println!("{}", format_assertion(result)));
+    }
+}
+
+fn print_json(
+    request: &Request,
+    response: &Response,
+    results: &[AssertionResult],
+) -> anyhow::Result<()> {
+    let headers = response
+        .headers
+        .iter()
+        .flatten()
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or_default().to_string();
+            (name.to_string(), serde_json::Value::String(value))
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    // bodies that are valid json are embedded as is so they can be queried
+    // directly, anything else goes in as a string
+    let body = response
+        .body
+        .as_ref()
+        .map(|body| serde_json::from_str(body).unwrap_or(serde_json::Value::String(body.clone())))
+        .unwrap_or_default();
+
+    let envelope = serde_json::json!({
+        "request": request.name,
+        "method": request.method.to_string(),
+        "uri": request.uri,
+        "status": response.status.map(|status| status.as_u16()),
+        "http_version": response.version.map(version_name),
+        "duration_ms": response.duration.as_millis() as u64,
+        "headers": headers,
+        "body": body,
+        // only the start of bodies that were too big to keep in memory
+        "truncated": response.spilled.is_some(),
+        "error": response.cause,
+        "proxy": response.proxy,
+        "remote_addr": response.remote_addr.map(|addr| addr.to_string()),
+        "compression": response.compression.as_ref().map(|compression| serde_json::json!({
+            "encoding": compression.encoding.to_string(),
+            "wire_size": compression.wire_size,
+            "decompressed": compression.decompressed,
+        })),
+        "assertions": assertions_as_json(results),
+    });
+
+    println!("{}", serde_json::to_string_pretty(&envelope)?);
+    Ok(())
+}
+
+fn print_json_summary(results: &[RunResult], summary: &RunSummary) -> anyhow::Result<()> {
+    let results = results
+        .iter()
+        .map(|result| {
+            serde_json::json!({
+                "request": result.name,
+                "method": result.method.to_string(),
+                "status": result.status,
+                "duration_ms": result.duration.as_millis() as u64,
+                "passed": result.passed,
+                "error": result.error,
+                "assertions": assertions_as_json(&result.assertions),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let envelope = serde_json::json!({
+        "results": results,
+        "passed": summary.passed,
+        "failed": summary.failed,
+        "skipped": summary.skipped,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&envelope)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_by_status_class() {
+        assert_eq!(exit_code(Some(200), true), 0);
+        assert_eq!(exit_code(Some(302), true), 0);
+        assert_eq!(exit_code(Some(404), true), 1);
+        assert_eq!(exit_code(Some(404), false), 0);
+        assert_eq!(exit_code(Some(500), false), 1);
+        assert_eq!(exit_code(None, false), 1);
+    }
+
+    #[test]
+    fn test_format_result_line() {
+        let result = RunResult {
+            name: "login".into(),
+            method: hac_core::collection::types::RequestMethod::Post,
+            status: Some(201),
+            duration: Duration::from_millis(42),
+            passed: true,
+            error: None,
+            assertions: vec![],
+            captures: vec![],
+            size: None,
+        };
+
+        assert_eq!(
+            format_result(&result),
+            format!("PASS POST    {:<30} 201 42ms", "login")
+        );
+    }
+
+    #[test]
+    fn test_format_result_lists_failed_assertions() {
+        let result = RunResult {
+            name: "login".into(),
+            method: hac_core::collection::types::RequestMethod::Post,
+            status: Some(201),
+            duration: Duration::from_millis(42),
+            passed: false,
+            error: None,
+            assertions: vec![
+                AssertionResult {
+                    assertion: "status 2xx".parse().unwrap(),
+                    passed: true,
+                    actual: "201".into(),
+                },
+                AssertionResult {
+                    assertion: "$.token exists".parse().unwrap(),
+                    passed: false,
+                    actual: "not found".into(),
+                },
+            ],
+            captures: vec![],
+            size: None,
+        };
+
+        assert_eq!(
+            format_result(&result),
+            format!(
+                "FAIL POST    {:<30} 201 42ms\n  FAIL $.token exists (got not found)",
+                "login"
+            )
+        );
+    }
+
+    #[test]
+    fn test_missing_collection_lists_available_ones() {
+        let collections = vec![
+            collection::create_from_form("first".into(), String::new()),
+            collection::create_from_form("second".into(), String::new()),
+        ];
+
+        let err = find_collection(&collections, "third")
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("\"third\" not found"));
+        assert!(err.contains("  first\n  second"));
+    }
+
+    #[test]
+    fn test_requests_are_narrowed_down_by_tags() {
+        let collection = collection::create_from_form("api".into(), String::new());
+        let request = |name: &str, tags: &str| -> Request {
+            serde_json::from_value(serde_json::json!({
+                "id": name,
+                "method": "GET",
+                "name": name,
+                "uri": "/",
+                "tags": tags.split(' ').collect::<Vec<_>>(),
+            }))
+            .unwrap()
+        };
+        let requests = vec![
+            request("login", "smoke"),
+            request("users", "admin"),
+            request("health", "smoke monitoring"),
+        ];
+
+        let names = |requests: Vec<Request>| {
+            requests
+                .into_iter()
+                .map(|request| request.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(with_tags(&collection, requests.clone(), &[]).unwrap()),
+            vec!["login", "users", "health"]
+        );
+        assert_eq!(
+            names(with_tags(&collection, requests.clone(), &["SMOKE".into()]).unwrap()),
+            vec!["login", "health"]
+        );
+        assert_eq!(
+            names(
+                with_tags(
+                    &collection,
+                    requests.clone(),
+                    &["admin".into(), "monitoring".into()]
+                )
+                .unwrap()
+            ),
+            vec!["users", "health"]
+        );
+        assert!(with_tags(&collection, requests, &["nightly".into()])
+            .unwrap_err()
+            .to_string()
+            .starts_with("no request to run is tagged nightly"));
+    }
+
+    #[test]
+    fn test_collection_can_be_found_by_file_name() {
+        let collections = vec![collection::create_from_form("My Api".into(), String::new())];
+
+        assert!(find_collection(&collections, "my_api").is_ok());
+        assert!(find_collection(&collections, "My Api").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_4xx_responses_fail_unless_allowed() {
+        use clap::Parser;
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w.eq(b"\r\n\r\n")) {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let response =
+                    "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let mut collection = collection::create_from_form("api".into(), String::new());
+        let request: Request = serde_json::from_value(serde_json::json!({
+            "id": "users",
+            "method": "GET",
+            "name": "users",
+            "uri": format!("http://{addr}/users"),
+        }))
+        .unwrap();
+        collection.requests = Some(Arc::new(RwLock::new(vec![
+            hac_core::collection::types::RequestKind::Single(Arc::new(RwLock::new(request))),
+        ])));
+
+        let exit_code = |flags: &[&str]| {
+            let cli =
+                crate::Cli::try_parse_from(["hac", "run", "api", "users"].iter().chain(flags))
+                    .unwrap();
+            let Some(crate::Commands::Run(args)) = cli.command else {
+                panic!("expected the run subcommand");
+            };
+            let options = run_options(&args, false, ClientOptions::default());
+            let collection = &collection;
+            async move {
+                run_single(&args, collection, "users", BTreeMap::new(), options)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(exit_code(&[]).await, 1);
+        assert_eq!(exit_code(&["--allow-4xx"]).await, 0);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+use crate::runner::find_collection;
+use crate::{ExportArgs, ImportArgs};
+
+use hac_core::collection::collection;
+use hac_core::import::remote;
+use hac_core::net::request_client::ClientOptions;
+use hac_core::secrets::Secrets;
+
+/// writes a collection to a single file, so it can be backed up or handed to
+/// someone who doesn't share our collections directory
+pub fn export(args: ExportArgs) -> anyhow::Result<()> {
+    let collections = collection::get_collections_from_config()?;
+    let collection = find_collection(&collections, &args.collection)?;
+
+    hac_core::fs::export_collection(collection, &args.output, args.include_secrets)?;
+    println!(
+        "exported {} to {}",
+        collection.info.name,
+        args.output.to_string_lossy()
+    );
+
+    Ok(())
+}
+
+/// creates new collections from a file written by `export`, a `.http` file
+/// or an insomnia export, read from disk or fetched from a url
+pub async fn import(args: ImportArgs) -> anyhow::Result<()> {
+    // importing may be the first thing done on a new machine
+    hac_config::get_or_create_data_dir();
+    hac_config::get_or_create_collections_dir();
+    let imported = match remote::is_url(&args.file) {
+        true => {
+            let options = ClientOptions::from_config(&hac_config::try_load_config()?);
+            hac_core::fs::import_from_url(&args.file, &options, false).await?
+        }
+        false => hac_core::fs::import_collections(args.file.into(), false).await?,
+    };
+
+    // the TUI moves secrets out when the collection is opened, here there is
+    // nothing to open it so they are moved right away
+    let mut secrets = Secrets::load(hac_config::secrets_file());
+    for mut collection in imported.collections {
+        if secrets.take_from(&mut collection) {
+            secrets.save()?;
+            hac_core::fs::sync_collection(collection.clone()).await?;
+        }
+
+        println!(
+            "imported {} requests as {}",
+            collection.flatten_requests().len(),
+            collection.info.name
+        );
+    }
+
+    if !imported.skipped.is_empty() {
+        println!("left out, as there is nothing like them here:");
+        for skipped in imported.skipped {
+            println!("  - {skipped}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use hac_core::collection::types::Info;
+use hac_core::collection::Collection;
+
+use hac_client::pages::collection_dashboard::CollectionDashboard;
+use hac_client::pages::{Eventful, Renderable};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+
+fn main() {
+    divan::main();
+}
+
+fn create_sample_collections(amount: usize) -> Vec<Collection> {
+    (0..amount)
+        .map(|i| Collection {
+            info: Info {
+                name: format!("collection_{}", i),
+                description: Some(format!("description of collection {}", i)),
+                ..Default::default()
+            },
+            path: format!("collection_{}.json", i).into(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn feed_keys(dashboard: &mut CollectionDashboard, key_codes: &[KeyCode]) {
+    for code in key_codes {
+        dashboard
+            .handle_key_event(KeyEvent::new(*code, KeyModifiers::NONE))
+            .unwrap();
+    }
+}
+
+#[divan::bench]
+fn filtering_a_thousand_collections(bencher: divan::Bencher) {
+    let colors = hac_colors::Colors::default();
+    let size = Rect::new(0, 0, 80, 24);
+    let mut dashboard =
+        CollectionDashboard::new(size, &colors, create_sample_collections(1000), true).unwrap();
+
+    bencher.bench_local(|| {
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyCode::Char('/'),
+                KeyCode::Char('9'),
+                KeyCode::Char('9'),
+                KeyCode::Esc,
+            ],
+        );
+    });
+}
+
+#[divan::bench]
+fn drawing_a_thousand_collections(bencher: divan::Bencher) {
+    let colors = hac_colors::Colors::default();
+    let size = Rect::new(0, 0, 80, 24);
+    let mut dashboard =
+        CollectionDashboard::new(size, &colors, create_sample_collections(1000), true).unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(size.width, size.height)).unwrap();
+
+    bencher.bench_local(|| {
+        let mut frame = terminal.get_frame();
+        dashboard.draw(&mut frame, size).unwrap();
+    });
+}
@@ -0,0 +1,22 @@
+use std::process::Command;
+
+/// exposes the commit and target being built to `hac --version`. Builds from
+/// a source tarball have no git repository, so the commit is `unknown` there
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".into());
+
+    println!("cargo:rustc-env=HAC_GIT_HASH={commit}");
+    println!(
+        "cargo:rustc-env=HAC_BUILD_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+}
@@ -1,77 +1,180 @@
-use hac_core::{collection::Collection, command::Command};
+use hac_core::command::{Command, ErrorReport};
 
 use crate::event_pool::{Event, EventPool};
+use crate::external_editor;
+use crate::graphics;
+use crate::log_buffer::LogBuffer;
+use crate::notifications::{self, Notifier};
 use crate::pages::{Eventful, Renderable};
 use crate::screen_manager::ScreenManager;
 
 use std::io::Stdout;
+use std::time::{Duration, Instant};
 
 use ratatui::{backend::CrosstermBackend, Terminal};
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// how long quitting waits on writes that are still running before giving up
+/// on them
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
 
 pub struct App<'app> {
     event_pool: EventPool,
     terminal: Terminal<CrosstermBackend<Stdout>>,
     should_quit: bool,
     screen_manager: ScreenManager<'app>,
+    notifier: Notifier,
+    /// span covering the startup, along with when it started, closed once
+    /// the first frame is drawn
+    startup: Option<(tracing::Span, Instant)>,
 }
 
 impl<'app> App<'app> {
     pub fn new(
         colors: &'app hac_colors::Colors,
-        collections: Vec<Collection>,
         config: &'app hac_config::Config,
         dry_run: bool,
+        restore_session: bool,
     ) -> anyhow::Result<Self> {
         let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+        let screen_manager = ScreenManager::new(terminal.size()?, colors, vec![], config, dry_run)?
+            .with_session_restore(restore_session);
         Ok(Self {
-            screen_manager: ScreenManager::new(
-                terminal.size()?,
-                colors,
-                collections,
-                config,
-                dry_run,
-            )?,
+            screen_manager,
             event_pool: EventPool::new(60f64, 30f64),
             should_quit: false,
+            notifier: Notifier::new(config.notifications.clone()),
             terminal,
+            startup: None,
         })
     }
 
+    /// the buffer the tracing subscriber writes to, shown by the log viewer
+    pub fn with_log_buffer(self, buffer: LogBuffer) -> Self {
+        App {
+            screen_manager: self.screen_manager.with_log_buffer(buffer),
+            ..self
+        }
+    }
+
+    /// whether to look for a newer release once the application starts
+    pub fn with_update_check(self, check_updates: bool) -> Self {
+        App {
+            screen_manager: self.screen_manager.with_update_check(check_updates),
+            ..self
+        }
+    }
+
+    /// starts with offline mode on, it can still be toggled while running
+    pub fn with_offline(self, offline: bool) -> Self {
+        App {
+            screen_manager: self.screen_manager.with_offline(offline),
+            ..self
+        }
+    }
+
+    /// records on `span` how long it took from `started` to the first frame,
+    /// as `first_frame_ms`
+    pub fn with_startup(self, span: tracing::Span, started: Instant) -> Self {
+        App {
+            startup: Some((span, started)),
+            ..self
+        }
+    }
+
     /// this is the main method which starts the event loop task, listen for events and commands
     /// to pass them down the chain, and render the terminal screen
     pub async fn run(&mut self) -> anyhow::Result<()> {
-        let (command_tx, mut command_rx) = mpsc::unbounded_channel();
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+
+        let owns_title = self.notifier.owns_title();
+        install_panic_hook(owns_title);
+        startup(owns_title)?;
+        // the terminal answers on the input, before events are read from it
+        graphics::detect();
         self.event_pool.start();
 
-        startup()?;
+        // the terminal has to be restored no matter how the loop ended, so
+        // its result is only returned after shutting down
+        let result = self.event_loop(command_tx, command_rx).await;
+
+        self.event_pool.stop();
+        let tasks = self.screen_manager.shutdown();
+        if !tasks.wait(SHUTDOWN_TIMEOUT).await {
+            tracing::error!("gave up waiting on writes after {SHUTDOWN_TIMEOUT:?}");
+        }
+
+        shutdown(owns_title)?;
+        result
+    }
 
+    async fn event_loop(
+        &mut self,
+        command_tx: UnboundedSender<Command>,
+        mut command_rx: UnboundedReceiver<Command>,
+    ) -> anyhow::Result<()> {
         self.screen_manager
             .register_command_handler(command_tx.clone())?;
+        self.screen_manager.load_collections(command_tx.clone());
+        self.screen_manager.check_for_updates(command_tx.clone());
 
         loop {
             {
                 while let Ok(command) = command_rx.try_recv() {
                     match command {
                         Command::Quit => self.should_quit = true,
+                        Command::EditExternally {
+                            viewer_id,
+                            content,
+                            extension,
+                        } => {
+                            let result = self
+                                .suspended(|| external_editor::edit(&content, &extension))
+                                .await?
+                                .map_err(ErrorReport::from);
+                            self.screen_manager
+                                .handle_command(Command::ExternalEditFinished {
+                                    viewer_id,
+                                    result,
+                                });
+                        }
                         _ => self.screen_manager.handle_command(command),
                     }
                 }
             }
 
+            if let Some(finished) = self.screen_manager.take_finished_request() {
+                self.notifier.announce(&mut std::io::stdout(), &finished)?;
+            }
+
             if let Some(event) = self.event_pool.next().await {
                 match event {
                     Event::Tick => self.screen_manager.handle_tick()?,
-                    Event::Resize(new_size) => self.screen_manager.resize(new_size),
+                    Event::Resize(new_size) => {
+                        graphics::forget();
+                        self.screen_manager.resize(new_size);
+                    }
                     Event::Render => {
                         self.terminal.draw(|f| {
                             let result = self.screen_manager.draw(f, f.size());
                             if let Err(e) = result {
                                 command_tx
-                                    .send(Command::Error(format!("Failed to draw: {:?}", e)))
+                                    .send(Command::Error(format!("Failed to draw: {:?}", e).into()))
                                     .expect("failed to send command through channel");
                             }
                         })?;
+                        if graphics::flush(&mut std::io::stdout())? {
+                            self.terminal.clear()?;
+                        }
+                        self.notifier.set_title(
+                            &mut std::io::stdout(),
+                            &self.screen_manager.window_title(),
+                        )?;
+                        if let Some((span, started)) = self.startup.take() {
+                            let elapsed = started.elapsed();
+                            span.record("first_frame_ms", elapsed.as_millis() as u64);
+                            span.in_scope(|| tracing::info!("first frame drawn after {elapsed:?}"));
+                        }
                     }
                     event => {
                         if let Some(command) =
@@ -90,23 +193,69 @@ impl<'app> App<'app> {
             }
         }
 
-        shutdown()?;
         Ok(())
     }
+
+    /// gives the terminal back to the user while `f` runs, eg: for an
+    /// external editor. The application is restored even when `f` fails,
+    /// anything typed meanwhile belongs to whatever `f` runs
+    async fn suspended<T>(&mut self, f: impl FnOnce() -> T) -> anyhow::Result<T> {
+        let owns_title = self.notifier.owns_title();
+        self.event_pool.pause().await;
+        shutdown(owns_title)?;
+
+        let result = f();
+
+        startup(owns_title)?;
+        self.notifier.forget_title();
+        self.terminal.clear()?;
+        graphics::forget();
+        self.event_pool.start();
+
+        Ok(result)
+    }
+}
+
+/// a panic skips the regular shutdown, which would leave the terminal in raw
+/// mode with the panic message scrambled on the alternate screen. Restoring
+/// an already restored terminal is harmless, so this also covers panics
+/// while the application is suspended
+fn install_panic_hook(owns_title: bool) {
+    let panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        _ = shutdown(owns_title);
+        panic_hook(info);
+    }));
 }
 
 /// before initializing the app, we must setup the terminal to enable all the features
-/// we need, such as raw mode and entering the alternate screen
-fn startup() -> anyhow::Result<()> {
+/// we need, such as raw mode and entering the alternate screen. When we are going to
+/// change the window title, the one the user had is saved first
+fn startup(owns_title: bool) -> anyhow::Result<()> {
     crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+    crossterm::execute!(
+        std::io::stdout(),
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableBracketedPaste
+    )?;
+    if owns_title {
+        notifications::save_title(&mut std::io::stdout())?;
+    }
     Ok(())
 }
 
 /// before shutting down we must reverse the changes we made to the users terminal, allowing
-/// them have a usable terminal
-fn shutdown() -> anyhow::Result<()> {
+/// them have a usable terminal, with the window title they had
+fn shutdown(owns_title: bool) -> anyhow::Result<()> {
     crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
+    if owns_title {
+        notifications::restore_title(&mut std::io::stdout())?;
+    }
+    crossterm::execute!(
+        std::io::stdout(),
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::event::DisableBracketedPaste,
+        crossterm::cursor::Show
+    )?;
     Ok(())
 }
@@ -6,6 +6,9 @@ use std::ops::Div;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Event {
     Key(crossterm::event::KeyEvent),
+    /// text pasted into the terminal, it comes in a single event instead of
+    /// one key event for every character, newlines included
+    Paste(String),
     Resize(Rect),
     Tick,
     Render,
@@ -19,6 +22,7 @@ pub struct EventPool {
     event_tx: tokio::sync::mpsc::UnboundedSender<Event>,
     frame_rate: f64,
     tick_rate: f64,
+    task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl EventPool {
@@ -30,6 +34,7 @@ impl EventPool {
             event_tx,
             frame_rate,
             tick_rate,
+            task: None,
         }
     }
 
@@ -39,7 +44,7 @@ impl EventPool {
         let tick_delay = std::time::Duration::from_secs_f64(1.0.div(self.tick_rate));
 
         let event_tx = self.event_tx.clone();
-        tokio::spawn(async move {
+        self.task = Some(tokio::spawn(async move {
             let mut reader = crossterm::event::EventStream::new();
             let mut render_interval = tokio::time::interval(render_delay);
             let mut tick_interval = tokio::time::interval(tick_delay);
@@ -57,6 +62,9 @@ impl EventPool {
                                     event_tx.send(Event::Key(key_event)).expect("failed to send event through channel");
                                 }
                             }
+                            Some(Ok(CrosstermEvent::Paste(text))) => event_tx
+                                .send(Event::Paste(text))
+                                .expect("failed to send event through channel"),
                             Some(Ok(CrosstermEvent::Resize(width, height))) => event_tx
                                 .send(Event::Resize(Rect::new(0, 0, width, height)))
                                 .expect("failed to send event through channel"),
@@ -71,7 +79,28 @@ impl EventPool {
                     },
                 }
             }
-        });
+        }));
+    }
+
+    /// stops reading events from the terminal until `start` is called again.
+    /// The reader is dropped before this returns, so it won't steal input
+    /// from other programs using the terminal meanwhile
+    #[cfg_attr(test, mutants::skip)]
+    pub async fn pause(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+            _ = task.await;
+        }
+    }
+
+    /// stops reading events from the terminal, events that were already
+    /// read are dropped
+    #[cfg_attr(test, mutants::skip)]
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.event_rx.close();
     }
 
     #[cfg_attr(test, mutants::skip)]
@@ -0,0 +1,83 @@
+use std::process::Command;
+
+use anyhow::Context;
+
+/// the command to open files with, `$VISUAL` takes precedence over
+/// `$EDITOR` and `vi` is used when neither is set. Both can carry arguments,
+/// eg: `code --wait`
+pub fn editor_command(visual: Option<String>, editor: Option<String>) -> Vec<String> {
+    let command = [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| "vi".into());
+
+    command.split_whitespace().map(String::from).collect()
+}
+
+/// writes `content` to a temporary file, opens it on the editor from the
+/// environment and returns what the file has once the editor exits
+pub fn edit(content: &str, extension: &str) -> anyhow::Result<String> {
+    let command = editor_command(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok());
+    edit_with(&command, content, extension)
+}
+
+/// the temporary file is removed whether the edit worked or not. An editor
+/// that exits with an error is taken as the user giving up on the edit
+fn edit_with(command: &[String], content: &str, extension: &str) -> anyhow::Result<String> {
+    let Some((program, args)) = command.split_first() else {
+        anyhow::bail!("no editor to open the body with");
+    };
+
+    let path = std::env::temp_dir().join(format!("hac-body-{}.{extension}", uuid::Uuid::new_v4()));
+    std::fs::write(&path, content)
+        .with_context(|| format!("failed to write body to {}", path.display()))?;
+
+    let result = Command::new(program)
+        .args(args)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("failed to launch {program}"))
+        .and_then(|status| match status.success() {
+            true => std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read body back from {}", path.display())),
+            false => Err(anyhow::anyhow!("{program} exited with {status}")),
+        });
+
+    _ = std::fs::remove_file(&path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_command_precedence() {
+        assert_eq!(
+            editor_command(Some("code --wait".into()), Some("nvim".into())),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(
+            editor_command(Some(" ".into()), Some("nvim".into())),
+            vec!["nvim"]
+        );
+        assert_eq!(editor_command(None, None), vec!["vi"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_reads_the_file_back() {
+        // the path is given as the last argument, which is `$0` for `sh -c`
+        let command = ["sh", "-c", r#"printf '{"id": 1}' > "$0""#].map(String::from);
+        let edited = edit_with(&command, "{}", "json").unwrap();
+        assert_eq!(edited, r#"{"id": 1}"#);
+
+        let command = ["sh", "-c", "exit 1"].map(String::from);
+        let error = edit_with(&command, "{}", "json").unwrap_err();
+        assert!(error.to_string().starts_with("sh exited with"));
+
+        let command = ["hac-editor-that-does-not-exist".to_string()];
+        assert!(edit_with(&command, "{}", "json").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "graphics")]
+mod kitty;
+#[cfg(all(unix, feature = "graphics"))]
+mod query;
+#[cfg(feature = "graphics")]
+mod sixel;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use hac_core::net::image::Pixels;
+use ratatui::layout::Rect;
+
+/// the ways a terminal can draw real pixels, instead of half blocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(feature = "graphics"), allow(dead_code))]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+/// what the terminal told us it can draw, and how many pixels a cell takes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Graphics {
+    pub protocol: Protocol,
+    pub cell_width: u32,
+    pub cell_height: u32,
+}
+
+static GRAPHICS: OnceLock<Option<Graphics>> = OnceLock::new();
+
+/// an image to be drawn over `area` after the frame is flushed
+#[derive(Clone)]
+struct Placement {
+    area: Rect,
+    pixels: Rc<Pixels>,
+}
+
+impl PartialEq for Placement {
+    fn eq(&self, other: &Self) -> bool {
+        self.area.eq(&other.area) && Rc::ptr_eq(&self.pixels, &other.pixels)
+    }
+}
+
+thread_local! {
+    /// placed while drawing the current frame
+    static PENDING: RefCell<Option<Placement>> = const { RefCell::new(None) };
+    /// what is on the screen right now
+    static SHOWN: RefCell<Option<Placement>> = const { RefCell::new(None) };
+}
+
+/// asks the terminal which graphics protocol it speaks. Has to run in raw
+/// mode and before anything else reads the input, as the answers arrive
+/// there. Without the `graphics` feature, or when the terminal is quiet,
+/// images are drawn with half blocks
+pub fn detect() {
+    #[cfg(all(unix, feature = "graphics"))]
+    let graphics = query::query();
+    #[cfg(not(all(unix, feature = "graphics")))]
+    let graphics = None;
+
+    tracing::debug!("terminal graphics: {graphics:?}");
+    _ = GRAPHICS.set(graphics);
+}
+
+/// the graphics protocol of the terminal, when it has one
+pub fn graphics() -> Option<Graphics> {
+    GRAPHICS.get().copied().flatten()
+}
+
+/// draws `pixels` over `area` once the current frame is on the screen. The
+/// cells under it should be left blank
+pub fn place(area: Rect, pixels: Rc<Pixels>) {
+    PENDING.with_borrow_mut(|pending| *pending = Some(Placement { area, pixels }));
+}
+
+/// the screen was cleared, whatever was shown has to be drawn again
+pub fn forget() {
+    SHOWN.with_borrow_mut(|shown| *shown = None);
+}
+
+/// writes the image placed on the last frame, if it isn't already on the
+/// screen, and removes the one that was there. Returns whether the screen
+/// has to be redrawn, as sixels can only be removed by drawing over them
+pub fn flush(out: &mut impl std::io::Write) -> std::io::Result<bool> {
+    let pending = PENDING.with_borrow_mut(Option::take);
+    let shown = SHOWN.with_borrow(Clone::clone);
+    if pending.eq(&shown) {
+        return Ok(false);
+    }
+    SHOWN.with_borrow_mut(|shown| *shown = pending.clone());
+
+    let Some(graphics) = graphics() else {
+        return Ok(false);
+    };
+    draw(out, graphics, shown.is_some(), pending)
+}
+
+#[cfg(feature = "graphics")]
+fn draw(
+    out: &mut impl std::io::Write,
+    graphics: Graphics,
+    was_shown: bool,
+    placement: Option<Placement>,
+) -> std::io::Result<bool> {
+    let redraw = match graphics.protocol {
+        // also after the screen was cleared, in case it was kept
+        Protocol::Kitty => {
+            out.write_all(kitty::DELETE.as_bytes())?;
+            false
+        }
+        Protocol::Sixel => was_shown,
+    };
+
+    // a sixel drawn now would be wiped by the redraw, it is placed again
+    // on the next frame
+    if redraw {
+        forget();
+        out.flush()?;
+        return Ok(true);
+    }
+
+    if let Some(Placement { area, pixels }) = placement {
+        let width = u32::from(area.width) * graphics.cell_width;
+        let height = u32::from(area.height) * graphics.cell_height;
+        let pixels = fit(&pixels, width, height);
+        // the cursor is put back where the frame left it
+        write!(out, "\x1b7\x1b[{};{}H", area.y + 1, area.x + 1)?;
+        match graphics.protocol {
+            Protocol::Kitty => kitty::write(out, &pixels, area)?,
+            Protocol::Sixel => sixel::write(out, &pixels)?,
+        }
+        out.write_all(b"\x1b8")?;
+    }
+    out.flush()?;
+    Ok(false)
+}
+
+#[cfg(not(feature = "graphics"))]
+fn draw(
+    _: &mut impl std::io::Write,
+    _: Graphics,
+    _: bool,
+    _: Option<Placement>,
+) -> std::io::Result<bool> {
+    Ok(false)
+}
+
+/// `pixels` scaled down to fit `width` by `height`, keeping their aspect,
+/// sampling the nearest pixel
+#[cfg(feature = "graphics")]
+fn fit(pixels: &Pixels, width: u32, height: u32) -> Pixels {
+    let scale = f64::min(
+        f64::from(width) / f64::from(pixels.width),
+        f64::from(height) / f64::from(pixels.height),
+    )
+    .min(1.0);
+    let width = ((f64::from(pixels.width) * scale) as u32).max(1);
+    let height = ((f64::from(pixels.height) * scale) as u32).max(1);
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let x = (u64::from(x) * u64::from(pixels.width) / u64::from(width)) as u32;
+            let y = (u64::from(y) * u64::from(pixels.height) / u64::from(height)) as u32;
+            rgba.extend(pixels.get(x, y));
+        }
+    }
+    Pixels {
+        width,
+        height,
+        rgba,
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_images_are_scaled_to_fit() {
+        let pixels = Pixels {
+            width: 4,
+            height: 2,
+            rgba: (0..8u8).flat_map(|idx| [idx, 0, 0, 255]).collect(),
+        };
+
+        let fitted = fit(&pixels, 2, 100);
+        assert_eq!((fitted.width, fitted.height), (2, 1));
+        assert_eq!(fitted.get(0, 0), [0, 0, 0, 255]);
+        assert_eq!(fitted.get(1, 0), [2, 0, 0, 255]);
+
+        // never scaled up
+        let fitted = fit(&pixels, 40, 40);
+        assert_eq!(fitted, pixels);
+    }
+}
@@ -0,0 +1,64 @@
+use hac_core::net::image::Pixels;
+use ratatui::layout::Rect;
+
+use crate::clipboard::base64_encode;
+
+/// every image we draw uses this id, there is never more than one
+const IMAGE_ID: u32 = 4242;
+/// the terminal takes the payload in chunks of at most this many bytes
+const CHUNK_SIZE: usize = 4096;
+
+/// removes our image, freeing its data
+pub const DELETE: &str = "\x1b_Ga=d,d=I,i=4242,q=2\x1b\\";
+
+/// sends `pixels` and shows them at the cursor. The image sits under the
+/// text, so popups drawn over it hide it. Replies are turned off, they
+/// would arrive as key presses
+pub fn write(out: &mut impl std::io::Write, pixels: &Pixels, area: Rect) -> std::io::Result<()> {
+    let payload = base64_encode(&pixels.rgba);
+    let chunks = payload.as_bytes().chunks(CHUNK_SIZE).collect::<Vec<_>>();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(idx + 1 < chunks.len());
+        match idx {
+            0 => write!(
+                out,
+                "\x1b_Ga=T,f=32,i={IMAGE_ID},s={},v={},c={},r={},C=1,z=-1,q=2,m={more};",
+                pixels.width, pixels.height, area.width, area.height,
+            )?,
+            _ => write!(out, "\x1b_Gm={more};")?,
+        }
+        out.write_all(chunk)?;
+        out.write_all(b"\x1b\\")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_images_are_sent_in_chunks() {
+        let pixels = Pixels {
+            width: 40,
+            height: 40,
+            rgba: vec![255; 40 * 40 * 4],
+        };
+        let mut out = vec![];
+        write(&mut out, &pixels, Rect::new(0, 0, 5, 3)).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let commands = out.split_terminator("\x1b\\").collect::<Vec<_>>();
+        // 6400 bytes take 8536 characters of base64
+        assert_eq!(commands.len(), 3);
+        assert!(commands[0]
+            .starts_with("\x1b_Ga=T,f=32,i=4242,s=40,v=40,c=5,r=3,C=1,z=-1,q=2,m=1;////"));
+        assert!(commands[1].starts_with("\x1b_Gm=1;"));
+        assert!(commands[2].starts_with("\x1b_Gm=0;"));
+        let payload = commands
+            .iter()
+            .map(|command| command.split_once(';').unwrap().1.len())
+            .sum::<usize>();
+        assert_eq!(payload, 8536);
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use super::{Graphics, Protocol};
+
+/// terminals answer right away, waiting longer would only delay the start
+/// on the ones that never do
+const TIMEOUT: Duration = Duration::from_millis(250);
+/// asks to load a 1x1 image without showing it, only kitty answers
+const KITTY_QUERY: &str = "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\";
+/// the primary device attributes, every terminal answers it and lists
+/// sixels as attribute 4
+const ATTRIBUTES_QUERY: &str = "\x1b[c";
+/// used when the terminal doesn't tell the size of its cells
+const DEFAULT_CELL: (u32, u32) = (8, 16);
+
+pub fn query() -> Option<Graphics> {
+    // SAFETY: isatty only looks at the descriptors
+    let is_tty =
+        unsafe { libc::isatty(libc::STDIN_FILENO) == 1 && libc::isatty(libc::STDOUT_FILENO) == 1 };
+    if !is_tty {
+        return None;
+    }
+
+    let mut stdout = std::io::stdout();
+    write!(stdout, "{KITTY_QUERY}{ATTRIBUTES_QUERY}").ok()?;
+    stdout.flush().ok()?;
+
+    let protocol = parse_reply(&read_reply()?)?;
+    let (cell_width, cell_height) = crossterm::terminal::window_size()
+        .ok()
+        .filter(|size| size.width.gt(&0) && size.columns.gt(&0) && size.rows.gt(&0))
+        .map(|size| {
+            (
+                u32::from(size.width / size.columns),
+                u32::from(size.height / size.rows),
+            )
+        })
+        .filter(|(width, height)| width.gt(&0) && height.gt(&0))
+        .unwrap_or(DEFAULT_CELL);
+
+    Some(Graphics {
+        protocol,
+        cell_width,
+        cell_height,
+    })
+}
+
+/// reads the input until the device attributes arrive, as they are the last
+/// answer. Nothing is read once the time is up, what is typed after that is
+/// left to the application
+fn read_reply() -> Option<Vec<u8>> {
+    let started = Instant::now();
+    let mut reply = vec![];
+    while !attributes_end(&reply) {
+        let left = TIMEOUT.checked_sub(started.elapsed())?;
+        let mut poll = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: a single descriptor that lives through the call
+        if unsafe { libc::poll(&mut poll, 1, left.as_millis() as libc::c_int) } <= 0 {
+            return None;
+        }
+
+        let mut buf = [0u8; 256];
+        // SAFETY: the buffer is valid for as many bytes as we ask for
+        let read = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+        if read <= 0 {
+            return None;
+        }
+        reply.extend_from_slice(&buf[..read as usize]);
+    }
+    Some(reply)
+}
+
+/// whether the answer to the device attributes, `ESC [ ? ... c`, is in
+fn attributes_end(reply: &[u8]) -> bool {
+    find(reply, b"\x1b[?").is_some_and(|start| reply[start..].contains(&b'c'))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq(needle))
+}
+
+/// kitty wins over sixels, it draws with every color and under the text
+fn parse_reply(reply: &[u8]) -> Option<Protocol> {
+    if find(reply, b"\x1b_Gi=31;OK").is_some() {
+        return Some(Protocol::Kitty);
+    }
+
+    let start = find(reply, b"\x1b[?")? + 3;
+    let end = start + reply[start..].iter().position(|byte| b'c'.eq(byte))?;
+    String::from_utf8_lossy(&reply[start..end])
+        .split(';')
+        .any(|attribute| attribute.eq("4"))
+        .then_some(Protocol::Sixel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replies_tell_the_protocol() {
+        assert_eq!(
+            parse_reply(b"\x1b_Gi=31;OK\x1b\\\x1b[?62;22c"),
+            Some(Protocol::Kitty)
+        );
+        // an error still means kitty didn't draw it
+        assert_eq!(
+            parse_reply(b"\x1b_Gi=31;ENOTSUPPORTED\x1b\\\x1b[?62;22c"),
+            None
+        );
+        assert_eq!(parse_reply(b"\x1b[?62;4;6;22c"), Some(Protocol::Sixel));
+        assert_eq!(parse_reply(b"\x1b[?64;1;2;6;9;15;18;21;22c"), None);
+        assert_eq!(parse_reply(b"\x1b[?4c"), Some(Protocol::Sixel));
+
+        assert!(!attributes_end(b"\x1b_Gi=31;OK\x1b\\"));
+        assert!(!attributes_end(b"\x1b[?62;4"));
+        assert!(attributes_end(b"\x1b_Gi=31;OK\x1b\\\x1b[?62;4c"));
+    }
+}
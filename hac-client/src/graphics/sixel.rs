@@ -0,0 +1,137 @@
+use hac_core::net::image::Pixels;
+
+/// levels of each channel on the palette, a 6x6x6 cube every terminal with
+/// sixels can hold
+const LEVELS: u8 = 6;
+/// pixels more transparent than this are left undrawn
+const OPAQUE: u8 = 128;
+
+/// the palette index of a pixel, or nothing when it is transparent
+fn index([r, g, b, a]: [u8; 4]) -> Option<u8> {
+    let level = |c: u8| (u16::from(c) * u16::from(LEVELS - 1) + 127) / 255;
+    a.ge(&OPAQUE)
+        .then(|| (level(r) * 36 + level(g) * 6 + level(b)) as u8)
+}
+
+/// draws `pixels` at the cursor. Each band of six rows is written once per
+/// color it has, with the sixels of that color set
+pub fn write(out: &mut impl std::io::Write, pixels: &Pixels) -> std::io::Result<()> {
+    let (width, height) = (pixels.width as usize, pixels.height as usize);
+    let indexes = (0..pixels.height)
+        .flat_map(|y| (0..pixels.width).map(move |x| (x, y)))
+        .map(|(x, y)| index(pixels.get(x, y)))
+        .collect::<Vec<_>>();
+
+    // transparent pixels keep whatever is behind them
+    write!(out, "\x1bP0;1;0q\"1;1;{width};{height}")?;
+    let mut used = indexes.iter().flatten().copied().collect::<Vec<_>>();
+    used.sort_unstable();
+    used.dedup();
+    for idx in used {
+        let percent = |level: u8| u16::from(level) * 100 / u16::from(LEVELS - 1);
+        write!(
+            out,
+            "#{idx};2;{};{};{}",
+            percent(idx / 36),
+            percent(idx / 6 % 6),
+            percent(idx % 6)
+        )?;
+    }
+
+    for top in (0..height).step_by(6) {
+        let rows = top..(top + 6).min(height);
+        let mut colors = rows
+            .clone()
+            .flat_map(|y| {
+                indexes[y * width..(y + 1) * width]
+                    .iter()
+                    .flatten()
+                    .copied()
+            })
+            .collect::<Vec<_>>();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for color in colors {
+            write!(out, "#{color}")?;
+            let sixels = (0..width).map(|x| {
+                rows.clone().fold(0u8, |bits, y| {
+                    match indexes[y * width + x].eq(&Some(color)) {
+                        true => bits | 1 << (y - top),
+                        false => bits,
+                    }
+                })
+            });
+            write_runs(out, sixels)?;
+            // back to the start of the band for the next color
+            out.write_all(b"$")?;
+        }
+        out.write_all(b"-")?;
+    }
+
+    out.write_all(b"\x1b\\")
+}
+
+/// repeated sixels are written once, with how many times they repeat
+fn write_runs(
+    out: &mut impl std::io::Write,
+    sixels: impl Iterator<Item = u8>,
+) -> std::io::Result<()> {
+    let mut write_run = |sixel: u8, count: usize| {
+        let sixel = char::from(sixel + 63);
+        match count {
+            0 => Ok(()),
+            1..=3 => write!(out, "{}", sixel.to_string().repeat(count)),
+            _ => write!(out, "!{count}{sixel}"),
+        }
+    };
+
+    let mut run = (0, 0);
+    for sixel in sixels {
+        match run {
+            (current, count) if current.eq(&sixel) => run = (current, count + 1),
+            (current, count) => {
+                write_run(current, count)?;
+                run = (sixel, 1);
+            }
+        }
+    }
+    write_run(run.0, run.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sixels_are_written_per_color() {
+        // a red row over a transparent one, then a blue pixel on the right
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let clear = [0, 0, 0, 0];
+        let pixels = Pixels {
+            width: 6,
+            height: 2,
+            rgba: [
+                red, red, red, red, red, red, clear, clear, clear, clear, clear, blue,
+            ]
+            .concat(),
+        };
+
+        let mut out = vec![];
+        write(&mut out, &pixels).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            // red only sets the top sixel, blue the second of the last column
+            "\x1bP0;1;0q\"1;1;6;2#5;2;0;0;100#180;2;100;0;0#5!5?A$#180!6@$-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_colors_are_mapped_to_the_palette() {
+        assert_eq!(index([0, 0, 0, 255]), Some(0));
+        assert_eq!(index([255, 255, 255, 255]), Some(215));
+        assert_eq!(index([255, 0, 0, 255]), Some(180));
+        assert_eq!(index([255, 0, 0, 100]), None);
+    }
+}
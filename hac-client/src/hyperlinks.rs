@@ -0,0 +1,155 @@
+use hac_core::net::uri;
+
+use std::ops::Add;
+use std::sync::OnceLock;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+
+/// terminals that are known to understand OSC 8 hyperlinks, by the value
+/// they set on `TERM_PROGRAM`
+const TERM_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"];
+
+/// whether the terminal we are running on turns OSC 8 escapes into links,
+/// `FORCE_HYPERLINK=1` or `0` overrides whatever we tell from the
+/// environment. Terminals that don't know the escape would print it, so
+/// anything we are not sure about counts as not supported
+pub fn supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| supported_by(|name| std::env::var(name).ok()))
+}
+
+fn supported_by(env: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(forced) = env("FORCE_HYPERLINK") {
+        return forced.ne("0");
+    }
+
+    // vte added hyperlinks on 0.50, which it reports as 5000
+    let vte = env("VTE_VERSION")
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version.ge(&5000));
+
+    vte || env("KITTY_WINDOW_ID").is_some()
+        || env("WT_SESSION").is_some()
+        || env("TERM_PROGRAM").is_some_and(|program| TERM_PROGRAMS.contains(&program.as_str()))
+}
+
+/// wraps the urls drawn on each row of `area` in OSC 8 escapes, so the
+/// terminal lets them be clicked. Urls touching the right edge of the area
+/// might go on past it, so they are left alone instead of linking to half
+/// of them
+pub fn mark(buf: &mut Buffer, area: Rect) {
+    for y in area.top()..area.bottom() {
+        let mut text = String::new();
+        let mut columns = vec![];
+        for x in area.left()..area.right() {
+            columns.push((text.len(), x));
+            text.push_str(buf.get(x, y).symbol());
+        }
+
+        for range in uri::find_urls(&text) {
+            let url = &text[range.clone()];
+            let cells = columns
+                .iter()
+                .filter(|(offset, _)| range.contains(offset))
+                .map(|(_, x)| *x)
+                .collect::<Vec<_>>();
+            if cells.last().is_some_and(|x| x.add(1).eq(&area.right())) {
+                continue;
+            }
+
+            // the escape goes on every other cell, taking the symbol of the
+            // cell after it along, which is then skipped. The escape has no
+            // width, so this keeps the cells aligned with the screen
+            for pair in cells.chunks(2) {
+                let symbols = pair
+                    .iter()
+                    .map(|x| buf.get(*x, y).symbol().to_string())
+                    .collect::<String>();
+                buf.get_mut(pair[0], y)
+                    .set_symbol(&format!("\x1b]8;;{url}\x07{symbols}\x1b]8;;\x07"));
+                if let Some(x) = pair.get(1) {
+                    buf.get_mut(*x, y).set_skip(true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Style;
+    use std::collections::HashMap;
+    use std::ops::Sub;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        move |name| vars.get(name).cloned()
+    }
+
+    /// cells of `area` whose symbol starts a hyperlink, with the url they link
+    /// to
+    fn linked_cells(buf: &Buffer, area: Rect) -> Vec<(u16, u16, String)> {
+        let mut linked = vec![];
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let symbol = buf.get(x, y).symbol();
+                if let Some(rest) = symbol.strip_prefix("\x1b]8;;") {
+                    let url = rest.split('\x07').next().unwrap_or_default();
+                    linked.push((x.sub(area.left()), y.sub(area.top()), url.to_string()));
+                }
+            }
+        }
+        linked
+    }
+
+    #[test]
+    fn test_telling_whether_the_terminal_has_hyperlinks() {
+        assert!(!supported_by(env(&[])));
+        assert!(!supported_by(env(&[("TERM_PROGRAM", "Apple_Terminal")])));
+        assert!(!supported_by(env(&[("VTE_VERSION", "4802")])));
+        assert!(supported_by(env(&[("VTE_VERSION", "6800")])));
+        assert!(supported_by(env(&[("TERM_PROGRAM", "WezTerm")])));
+        assert!(supported_by(env(&[("KITTY_WINDOW_ID", "1")])));
+        assert!(supported_by(env(&[("FORCE_HYPERLINK", "1")])));
+        assert!(!supported_by(env(&[
+            ("FORCE_HYPERLINK", "0"),
+            ("TERM_PROGRAM", "WezTerm")
+        ])));
+    }
+
+    #[test]
+    fn test_urls_on_view_are_linked() {
+        let area = Rect::new(2, 1, 24, 3);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 5));
+        buf.set_string(2, 1, r#""a": "https://a.dev/x","#, Style::default());
+        // this one might go on past the area
+        buf.set_string(2, 2, "see https://b.dev/longer", Style::default());
+        buf.set_string(2, 3, "(http://c.dev)", Style::default());
+        mark(&mut buf, area);
+
+        let linked = linked_cells(&buf, area);
+        let url_cells = |x: u16, y: u16, url: &str, len: u16| {
+            (0..len.div_ceil(2))
+                .map(|pair| (x.add(pair * 2), y, url.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let expected = [
+            url_cells(6, 0, "https://a.dev/x", 15),
+            url_cells(1, 2, "http://c.dev", 12),
+        ]
+        .concat();
+        assert_eq!(linked, expected);
+
+        // the cell taken along by the escape is not drawn on its own, and
+        // the last cell of an odd length url has no pair
+        assert!(buf.get(9, 1).skip);
+        assert!(!buf.get(22, 1).skip);
+        assert!(buf.get(8, 1).symbol().ends_with("ht\x1b]8;;\x07"));
+        assert!(buf.get(22, 1).symbol().ends_with("x\x1b]8;;\x07"));
+    }
+}
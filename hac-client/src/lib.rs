@@ -1,6 +1,23 @@
 pub mod app;
 mod ascii;
+mod clipboard;
 pub mod event_pool;
+mod external_editor;
+mod graphics;
+mod hyperlinks;
+pub mod log_buffer;
+mod notifications;
 pub mod pages;
 pub mod screen_manager;
+pub mod task_tracker;
+#[cfg(test)]
+mod test_utils;
 pub mod utils;
+
+/// version of the running binary, shown on the help overlay and by
+/// `--version`
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// short hash of the commit the binary was built from, set by the build
+/// script
+pub const GIT_HASH: &str = env!("HAC_GIT_HASH");
+pub const BUILD_TARGET: &str = env!("HAC_BUILD_TARGET");
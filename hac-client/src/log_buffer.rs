@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// how many entries are kept around, older entries are dropped as new ones
+/// come in
+pub const LOG_BUFFER_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// keeps the last few log entries in memory so they can be shown inside the
+/// app. Clones share the same entries
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    capacity: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        LogBuffer::new(LOG_BUFFER_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        LogBuffer {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: usize::max(capacity, 1),
+        }
+    }
+
+    pub fn push(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len().ge(&self.capacity) {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// copies the entries out, so the lock isn't held while drawing
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// a tracing layer that feeds this buffer
+    pub fn layer(&self) -> LogBufferLayer {
+        LogBufferLayer {
+            buffer: self.clone(),
+        }
+    }
+}
+
+/// collects every event above `TRACE` into a `LogBuffer`, trace events are
+/// way too noisy to be useful on screen
+#[derive(Debug)]
+pub struct LogBufferLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if metadata.level().eq(&Level::TRACE) {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        self.buffer.push(LogEntry {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        });
+    }
+}
+
+/// formats the `message` field first and every other field after it as
+/// `name=value`, just like the fmt layer does
+#[derive(Debug, Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name().eq("message") {
+            let fields = std::mem::take(&mut self.message);
+            _ = write!(self.message, "{value:?}");
+            if !fields.is_empty() {
+                _ = write!(self.message, " {fields}");
+            }
+            return;
+        }
+
+        if !self.message.is_empty() {
+            self.message.push(' ');
+        }
+        _ = write!(self.message, "{}={value:?}", field.name());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn make_entry(message: &str) -> LogEntry {
+        LogEntry {
+            level: Level::INFO,
+            target: String::from("hac"),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn test_oldest_entries_are_dropped_past_the_capacity() {
+        let buffer = LogBuffer::new(3);
+
+        for idx in 0..5 {
+            buffer.push(make_entry(&idx.to_string()));
+        }
+
+        let messages = buffer
+            .entries()
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_events_are_captured_without_trace() {
+        let buffer = LogBuffer::default();
+        let subscriber = tracing_subscriber::registry().with(buffer.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::trace!("too noisy");
+            tracing::warn!(status = 500, "request failed");
+            tracing::error!("something broke");
+        });
+
+        let entries = buffer.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].level, Level::WARN);
+        assert_eq!(entries[0].message, "request failed status=500");
+        assert_eq!(entries[1].level, Level::ERROR);
+        assert_eq!(entries[1].message, "something broke");
+    }
+}
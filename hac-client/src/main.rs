@@ -1,16 +1,26 @@
-use hac_cli::RuntimeBehavior;
+use hac_cli::{RunOptions, RuntimeBehavior};
 use hac_client::app;
-use hac_core::collection::collection;
+use hac_client::log_buffer::LogBuffer;
+use tracing_subscriber::layer::SubscriberExt;
 
-fn setup_tracing() -> anyhow::Result<tracing_appender::non_blocking::WorkerGuard> {
+use std::time::Instant;
+
+/// logs go both to the log file and to an in memory buffer that is shown by
+/// the log viewer
+fn setup_tracing(
+    buffer: &LogBuffer,
+) -> anyhow::Result<tracing_appender::non_blocking::WorkerGuard> {
     let (data_dir, logfile) = hac_config::log_file();
     let appender = tracing_appender::rolling::never(data_dir, logfile);
     let (writer, guard) = tracing_appender::non_blocking(appender);
-    let subscriber = tracing_subscriber::FmtSubscriber::builder()
-        .with_max_level(tracing::Level::TRACE)
-        .with_writer(writer)
-        .with_ansi(false)
-        .finish();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_subscriber::filter::LevelFilter::TRACE)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false),
+        )
+        .with(buffer.layer());
 
     tracing::subscriber::set_global_default(subscriber)?;
 
@@ -19,6 +29,7 @@ fn setup_tracing() -> anyhow::Result<tracing_appender::non_blocking::WorkerGuard
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let started = Instant::now();
     let runtime_behavior = hac_cli::Cli::parse_args();
 
     match runtime_behavior {
@@ -32,19 +43,55 @@ async fn main() -> anyhow::Result<()> {
         RuntimeBehavior::DumpDefaultConfig => {
             hac_cli::Cli::print_default_config(hac_config::default_as_str())
         }
+        RuntimeBehavior::PrintVersion => hac_cli::Cli::print_version(
+            hac_client::VERSION,
+            hac_client::GIT_HASH,
+            hac_client::BUILD_TARGET,
+        ),
+        RuntimeBehavior::Headless(args) => {
+            let exit_code = hac_cli::runner::run(args).await?;
+            std::process::exit(exit_code);
+        }
+        RuntimeBehavior::Export(args) => return hac_cli::transfer::export(args),
+        RuntimeBehavior::Import(args) => return hac_cli::transfer::import(args).await,
+        RuntimeBehavior::Doctor(args) => {
+            let exit_code = hac_cli::doctor::run(args)?;
+            std::process::exit(exit_code);
+        }
         _ => {}
     }
 
-    let dry_run = runtime_behavior.eq(&RuntimeBehavior::DryRun);
+    let options = match runtime_behavior {
+        RuntimeBehavior::Run(options) => options,
+        _ => RunOptions::default(),
+    };
 
-    let _guard = setup_tracing()?;
+    let log_buffer = LogBuffer::default();
+    let _guard = setup_tracing(&log_buffer)?;
+    hac_config::paths::log_locations();
+    let startup = tracing::info_span!("startup", first_frame_ms = tracing::field::Empty);
     hac_config::get_or_create_data_dir();
-    let config = hac_config::load_config();
+    let config = hac_config::try_load_config()?;
+    hac_config::set_read_only_dirs(config.read_only_dirs.clone());
 
-    let colors = hac_colors::Colors::default();
-    let mut collections = collection::get_collections_from_config()?;
-    collections.sort_by_key(|key| key.info.name.clone());
-    let mut app = app::App::new(&colors, collections, &config, dry_run)?;
+    // NO_COLOR set to anything but an empty string asks for no colors, as
+    // described on no-color.org
+    let no_color = options.no_color
+        || config.no_color
+        || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let colors = match no_color {
+        true => hac_colors::Colors::monochrome(),
+        false => hac_colors::Colors::default(),
+    };
+    let restore_session = config.restore_session && !options.no_restore;
+    let offline = config.offline || options.offline;
+    let check_updates = config.check_updates && !offline;
+    let mut app = startup
+        .in_scope(|| app::App::new(&colors, &config, options.dry_run, restore_session))?
+        .with_log_buffer(log_buffer)
+        .with_update_check(check_updates)
+        .with_offline(offline)
+        .with_startup(startup, started);
     app.run().await?;
 
     Ok(())
@@ -0,0 +1,158 @@
+use crate::pages::collection_viewer::FinishedRequest;
+
+use std::io::{self, Write};
+
+/// pushes the window title onto the title stack of the terminal, so the one
+/// the user had can be popped back later. Terminals without a title stack
+/// ignore both
+const SAVE_TITLE: &str = "\x1b[22;0t";
+const RESTORE_TITLE: &str = "\x1b[23;0t";
+const BELL: &str = "\x07";
+
+/// tells the terminal about requests through the window title, the bell and
+/// desktop notifications, as picked on the config
+#[derive(Debug)]
+pub struct Notifier {
+    config: hac_config::NotificationsConfig,
+    /// the title written last, it is only written again when it changes
+    title: Option<String>,
+}
+
+impl Notifier {
+    pub fn new(config: hac_config::NotificationsConfig) -> Self {
+        Notifier {
+            config,
+            title: None,
+        }
+    }
+
+    /// whether we change the window title, and so have to put it back
+    pub fn owns_title(&self) -> bool {
+        self.config.title
+    }
+
+    pub fn set_title(&mut self, out: &mut impl Write, title: &str) -> io::Result<()> {
+        if !self.config.title || self.title.as_deref().eq(&Some(title)) {
+            return Ok(());
+        }
+
+        crossterm::queue!(out, crossterm::terminal::SetTitle(printable(title)))?;
+        out.flush()?;
+        self.title = Some(title.to_string());
+        Ok(())
+    }
+
+    /// the title the user had was put back, eg: while suspended, so ours is
+    /// written again next time
+    pub fn forget_title(&mut self) {
+        self.title = None;
+    }
+
+    /// rings the bell and sends a desktop notification, whichever are
+    /// enabled, when the request took longer than the threshold
+    pub fn announce(&self, out: &mut impl Write, finished: &FinishedRequest) -> io::Result<()> {
+        if finished.took.as_millis().lt(&self.config.after_ms.into()) {
+            return Ok(());
+        }
+
+        if self.config.bell {
+            out.write_all(BELL.as_bytes())?;
+        }
+        if self.config.desktop {
+            let message = format!("{} {}", finished.request, finished.outcome);
+            write!(out, "\x1b]9;{}\x07", printable(&message))?;
+        }
+        out.flush()
+    }
+}
+
+pub fn save_title(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(SAVE_TITLE.as_bytes())?;
+    out.flush()
+}
+
+pub fn restore_title(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(RESTORE_TITLE.as_bytes())?;
+    out.flush()
+}
+
+/// control characters would end the escape early, or start another one
+fn printable(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hac_config::NotificationsConfig;
+    use std::time::Duration;
+
+    fn finished(took: Duration) -> FinishedRequest {
+        FinishedRequest {
+            request: "GET /reports".into(),
+            outcome: "✓ 200 in 14.2s".into(),
+            took,
+        }
+    }
+
+    #[test]
+    fn test_titles_are_written_when_they_change() {
+        let mut out = vec![];
+        let mut notifier = Notifier::new(NotificationsConfig::default());
+        notifier.set_title(&mut out, "hac").unwrap();
+        notifier.set_title(&mut out, "hac").unwrap();
+        assert_eq!(String::from_utf8_lossy(&out), "\x1b]0;hac\x07");
+
+        out.clear();
+        notifier
+            .set_title(&mut out, "hac — waiting on GET /\x1b[2J")
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "\x1b]0;hac — waiting on GET /[2J\x07"
+        );
+
+        out.clear();
+        notifier.forget_title();
+        notifier.set_title(&mut out, "hac").unwrap();
+        assert!(!out.is_empty());
+
+        let mut out = vec![];
+        let mut notifier = Notifier::new(NotificationsConfig {
+            title: false,
+            ..Default::default()
+        });
+        notifier.set_title(&mut out, "hac").unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_only_slow_requests_are_announced() {
+        let mut out = vec![];
+        let notifier = Notifier::new(NotificationsConfig::default());
+        notifier
+            .announce(&mut out, &finished(Duration::from_secs(20)))
+            .unwrap();
+        // the defaults only update the title
+        assert!(out.is_empty());
+
+        let notifier = Notifier::new(NotificationsConfig {
+            bell: true,
+            desktop: true,
+            after_ms: 5_000,
+            ..Default::default()
+        });
+        notifier
+            .announce(&mut out, &finished(Duration::from_secs(4)))
+            .unwrap();
+        assert!(out.is_empty());
+
+        notifier
+            .announce(&mut out, &finished(Duration::from_secs(5)))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "\x07\x1b]9;GET /reports ✓ 200 in 14.2s\x07"
+        );
+    }
+}
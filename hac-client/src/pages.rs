@@ -1,10 +1,17 @@
+pub mod activity_panel;
 pub mod collection_dashboard;
 pub mod collection_viewer;
 pub mod confirm_popup;
 pub mod error_popup;
+pub mod help_overlay;
 pub mod input;
+pub mod log_viewer;
+pub mod markdown;
 pub mod overlay;
+pub mod path_prompt;
+pub mod scrollbar;
 mod spinner;
+pub mod status_bar;
 pub mod terminal_too_small;
 mod under_construction;
 
@@ -47,13 +54,40 @@ pub trait Eventful {
     fn handle_event(&mut self, event: Option<Event>) -> anyhow::Result<Option<Self::Result>> {
         match event {
             Some(Event::Key(key_event)) => self.handle_key_event(key_event),
+            Some(Event::Paste(text)) => self.handle_paste(text),
             _ => Ok(None),
         }
     }
 
+    /// pasted text arrives all at once, components with text inputs insert
+    /// it where the cursor is, everything else ignores it
+    #[allow(unused_variables)]
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        Ok(None)
+    }
+
     /// when we get a key_event, this will be called for the eventful component to handle it
     #[allow(unused_variables)]
     fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
         Ok(None)
     }
 }
+
+/// An `Overlay` is a popup opened over a page, like a prompt or the help. The page
+/// decides where it goes and keeps a stack of them, only the one on top gets the keys.
+///
+/// unlike `Eventful`, every result is final: once an overlay reports one it is done
+/// and the page closes it, going back to whatever was below
+pub trait Overlay {
+    type Result;
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> anyhow::Result<()>;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>>;
+
+    /// overlays without text inputs ignore pasted text
+    #[allow(unused_variables)]
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        Ok(None)
+    }
+}
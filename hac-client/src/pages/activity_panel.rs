@@ -0,0 +1,260 @@
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use hac_core::activity::{Activity, ActivityEntry, ActivityState};
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph};
+use ratatui::Frame;
+
+#[derive(Debug, PartialEq)]
+pub enum ActivityPanelEvent {
+    Close,
+    Quit,
+}
+
+/// lists what is running on the background and what finished a moment ago,
+/// on top of whatever screen is open. Anything still running can be
+/// cancelled from here
+#[derive(Debug)]
+pub struct ActivityPanel<'a> {
+    colors: &'a hac_colors::Colors,
+    activity: Activity,
+    /// id of the selected entry, so the selection stays on it as entries
+    /// come and go
+    selected: Option<u64>,
+}
+
+impl<'a> ActivityPanel<'a> {
+    pub fn new(colors: &'a hac_colors::Colors, activity: Activity) -> Self {
+        ActivityPanel {
+            colors,
+            activity,
+            selected: None,
+        }
+    }
+
+    /// newest first, as that is usually what we are looking for
+    fn entries(&self) -> Vec<ActivityEntry> {
+        let mut entries = self.activity.entries();
+        entries.reverse();
+        entries
+    }
+
+    /// the index of the selected entry on `entries`, falling back to the
+    /// first one when it is gone
+    fn selected_index(&self, entries: &[ActivityEntry]) -> usize {
+        self.selected
+            .and_then(|id| entries.iter().position(|entry| entry.id.eq(&id)))
+            .unwrap_or_default()
+    }
+
+    fn select_offset(&mut self, offset: isize) {
+        let entries = self.entries();
+        if entries.is_empty() {
+            return;
+        }
+        let idx = self
+            .selected_index(&entries)
+            .saturating_add_signed(offset)
+            .min(entries.len() - 1);
+        self.selected = Some(entries[idx].id);
+    }
+
+    fn cancel_selected(&mut self) {
+        let entries = self.entries();
+        if let Some(entry) = entries.get(self.selected_index(&entries)) {
+            self.activity.cancel(entry.id);
+        }
+    }
+
+    fn state_span(&self, state: &ActivityState) -> (String, Color) {
+        match state {
+            ActivityState::Running => (String::from("running"), self.colors.normal.blue),
+            ActivityState::Done(outcome) => (outcome.clone(), self.colors.normal.green),
+            ActivityState::Failed(reason) => (reason.clone(), self.colors.normal.red),
+            ActivityState::Cancelled => (String::from("cancelled"), self.colors.normal.yellow),
+        }
+    }
+
+    fn build_line(&self, entry: &ActivityEntry, selected: bool, now: Instant) -> Line<'static> {
+        let (state, state_color) = self.state_span(&entry.state);
+        let mut spans = vec![
+            format!("{:<9}", entry.kind).fg(self.colors.bright.black),
+            format!("{:>6} ", format_elapsed(entry.elapsed(now))).fg(self.colors.bright.black),
+            entry.label.clone().fg(self.colors.normal.white),
+        ];
+        if let Some(detail) = entry.detail.as_ref() {
+            spans.push(format!(" · {detail}").fg(self.colors.bright.black));
+        }
+        spans.push(format!(" {state}").fg(state_color));
+
+        let line = Line::from(spans);
+        match selected {
+            true => line.bg(self.colors.primary.hover),
+            false => line,
+        }
+    }
+
+    fn build_footer(&self) -> Line<'static> {
+        Line::from("j/k select · x cancel · esc to go back".fg(self.colors.normal.magenta))
+            .centered()
+    }
+}
+
+/// `850ms`, `12.4s` or `3m05s`, short enough to line up
+fn format_elapsed(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0 => format!("{}ms", elapsed.as_millis()),
+        secs if secs.lt(&60) => format!("{:.1}s", elapsed.as_secs_f64()),
+        secs => format!("{}m{:02}s", secs / 60, secs % 60),
+    }
+}
+
+impl Renderable for ActivityPanel<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
+
+        let popup = Rect::new(
+            size.x.saturating_add(2),
+            size.y.saturating_add(1),
+            size.width.saturating_sub(4),
+            size.height.saturating_sub(2),
+        )
+        .clamp(size);
+
+        let entries = self.entries();
+        let running = entries.iter().filter(|entry| entry.is_running()).count();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .title(format!(" activity · {running} active ").fg(self.colors.normal.white))
+            .padding(Padding::horizontal(1))
+            .bg(self.colors.normal.black);
+
+        let [content_pane, footer_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(block.inner(popup));
+
+        let now = Instant::now();
+        let selected = self.selected_index(&entries);
+        // keeps the selection on screen when there are more entries than
+        // lines to show them
+        let start = selected.saturating_sub(usize::from(content_pane.height).saturating_sub(1));
+        let lines = match entries.is_empty() {
+            true => vec![Line::from(
+                "nothing is running".fg(self.colors.bright.black),
+            )],
+            false => entries
+                .iter()
+                .enumerate()
+                .skip(start)
+                .map(|(idx, entry)| self.build_line(entry, idx.eq(&selected), now))
+                .collect(),
+        };
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+        frame.render_widget(Paragraph::new(lines), content_pane);
+        frame.render_widget(self.build_footer(), footer_pane);
+
+        Ok(())
+    }
+}
+
+impl Eventful for ActivityPanel<'_> {
+    type Result = ActivityPanelEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(ActivityPanelEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => self.select_offset(1),
+            KeyCode::Char('k') | KeyCode::Up => self.select_offset(-1),
+            KeyCode::Char('x') => self.cancel_selected(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(9) => {
+                return Ok(Some(ActivityPanelEvent::Close))
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hac_core::activity::ActivityKind;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn rendered(terminal: &Terminal<TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_the_selected_entry() {
+        let colors = hac_colors::Colors::default();
+        let activity = Activity::default();
+        let run = activity.start(ActivityKind::Run, "users");
+        let run_task = tokio::spawn(tokio::time::sleep(Duration::from_secs(60)));
+        activity.attach(run, run_task.abort_handle());
+        let send = activity.start(ActivityKind::Request, "GET /users");
+        let send_task = tokio::spawn(tokio::time::sleep(Duration::from_secs(60)));
+        activity.attach(send, send_task.abort_handle());
+        activity.progress(send, "1.2 MB");
+
+        let mut panel = ActivityPanel::new(&colors, activity.clone());
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+        let size = terminal.size().unwrap();
+        terminal.draw(|f| panel.draw(f, size).unwrap()).unwrap();
+        let screen = rendered(&terminal);
+        assert!(screen.contains("2 active"));
+        assert!(screen.contains("GET /users · 1.2 MB running"));
+
+        // the newest is selected first, so moving down reaches the run
+        panel
+            .handle_key_event(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE))
+            .unwrap();
+        panel
+            .handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(run_task.await.unwrap_err().is_cancelled());
+        assert!(activity.is_cancelled(run));
+        assert!(!activity.is_cancelled(send));
+
+        terminal.draw(|f| panel.draw(f, size).unwrap()).unwrap();
+        let screen = rendered(&terminal);
+        assert!(screen.contains("1 active"));
+        assert!(screen.contains("users cancelled"));
+
+        assert_eq!(
+            panel
+                .handle_key_event(KeyEvent::new(KeyCode::F(9), KeyModifiers::NONE))
+                .unwrap(),
+            Some(ActivityPanelEvent::Close)
+        );
+    }
+
+    #[test]
+    fn test_formatting_elapsed_time() {
+        assert_eq!(format_elapsed(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_elapsed(Duration::from_millis(12_400)), "12.4s");
+        assert_eq!(format_elapsed(Duration::from_secs(185)), "3m05s");
+    }
+}
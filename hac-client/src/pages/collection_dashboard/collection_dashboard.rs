@@ -1,21 +1,41 @@
-use hac_core::{collection::types::Collection, command::Command};
-
+use hac_config::LayoutOrientation;
+use hac_core::activity::{Activity, ActivityKind};
+use hac_core::app_state::{AppState, CollectionSort, Session};
+use hac_core::collection::types::Collection;
+use hac_core::command::{Command, ErrorReport};
+use hac_core::fs::error::FsError;
+use hac_core::fs::Imported;
+use hac_core::import::remote;
+use hac_core::net::request_client::ClientOptions;
+
+use crate::clipboard;
+use crate::pages::collection_dashboard::collection_details::CollectionDetails;
 use crate::pages::collection_dashboard::collection_list::{CollectionList, CollectionListState};
+use crate::pages::collection_dashboard::import_summary::{ImportReport, ImportSummary};
 use crate::pages::collection_dashboard::new_collection_form::{
     FormFocus, FormState, NewCollectionForm,
 };
-use crate::pages::confirm_popup::ConfirmPopup;
-use crate::pages::error_popup::ErrorPopup;
+use crate::pages::collection_dashboard::request_search::{RequestSearch, RequestSearchEvent};
+use crate::pages::confirm_popup::{ConfirmButton, ConfirmPopup, ConfirmPopupEvent};
+use crate::pages::error_popup::{ErrorPopup, ErrorPopupState};
+use crate::pages::help_overlay::{HelpOverlay, HelpOverlayEvent, HelpSection};
+use crate::pages::input;
 use crate::pages::overlay::{draw_overlay, make_overlay};
-use crate::pages::{Eventful, Renderable};
+use crate::pages::path_prompt::{PathPrompt, PathPromptEvent};
+use crate::pages::status_bar::{self, Segment, StatusBar};
+use crate::pages::{Eventful, Overlay, Renderable};
+use crate::task_tracker::TaskTracker;
 
 use std::ops::{Add, Div, Not, Sub};
+use std::path::{Path, PathBuf};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Alignment, Constraint, Direction, Flex, Layout, Rect};
 use ratatui::style::{Style, Stylize};
 use ratatui::text::Line;
-use ratatui::widgets::{Block, Clear, Padding, Paragraph, StatefulWidget, Widget, Wrap};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, Padding, Paragraph, StatefulWidget, Wrap,
+};
 use ratatui::Frame;
 use tokio::sync::mpsc::UnboundedSender;
 use tui_big_text::{BigText, PixelSize};
@@ -29,6 +49,8 @@ struct DashboardLayout {
     confirm_popup: Rect,
     form_popup: Rect,
     error_popup: Rect,
+    details_popup: Rect,
+    search_popup: Rect,
 }
 
 #[derive(Debug)]
@@ -41,13 +63,49 @@ pub struct CollectionDashboard<'a> {
     form_state: FormState,
     colors: &'a hac_colors::Colors,
     filter: String,
-    pane_focus: PaneFocus,
+    /// what is open over the list, the last one takes the keys. Closing it
+    /// gives them back to the one below, eg: dismissing an error raised by
+    /// the form goes back to the form
+    focus_stack: Vec<PaneFocus>,
     pub command_sender: Option<UnboundedSender<Command>>,
-    error_message: String,
+    error: ErrorReport,
+    error_popup_state: ErrorPopupState,
+    delete_prompt: Option<ConfirmPopup<'a, bool>>,
+    /// asks whether fetching a collection again from its url replaces it
+    reimport_prompt: Option<ConfirmPopup<'a, Reimport>>,
+    search: Option<RequestSearch<'a>>,
+    help: HelpOverlay<'a>,
+    import_prompt: PathPrompt<'a>,
+    export_prompt: PathPrompt<'a>,
+    /// what the last import brought in, shown on a popup when it isn't just
+    /// a single collection
+    import_report: ImportReport,
+    import_scroll: usize,
+    /// result of the last action that has nothing else to show, eg: an
+    /// export. Shown on the status bar until the next key press
+    notice: Option<String>,
+    /// newer release found by the update check, mentioned on the status bar
+    /// until it is dismissed
+    available_update: Option<String>,
+    /// whether requests are kept from being sent, only shown here as the
+    /// dashboard sends nothing
+    offline: bool,
+    /// proxy and global headers used to fetch collections imported from a
+    /// url
+    client: ClientOptions,
+    /// whether collections are still being read from disk
+    loading: bool,
+    app_state: AppState,
+    /// shown on the onboarding screen, so people know where collections live
+    collections_dir: String,
+    /// writes to the collections directory, so quitting can wait on them
+    tasks: TaskTracker,
+    /// where imports are listed while they are fetched and parsed
+    activity: Activity,
     dry_run: bool,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum PaneFocus {
     List,
     Form,
@@ -55,6 +113,20 @@ enum PaneFocus {
     Prompt,
     Help,
     Filter,
+    Details,
+    Search,
+    Import,
+    ImportSummary,
+    Export,
+    Reimport,
+}
+
+/// the answers to fetching a collection again from the url it came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reimport {
+    Replace,
+    Copy,
+    Cancel,
 }
 
 impl<'a> CollectionDashboard<'a> {
@@ -64,7 +136,7 @@ impl<'a> CollectionDashboard<'a> {
         collections: Vec<Collection>,
         dry_run: bool,
     ) -> anyhow::Result<Self> {
-        let mut list_state = CollectionListState::new(collections.clone());
+        let mut list_state = CollectionListState::new(&collections);
         collections
             .is_empty()
             .not()
@@ -79,44 +151,276 @@ impl<'a> CollectionDashboard<'a> {
             list: CollectionList::new(colors),
             filter: String::new(),
             command_sender: None,
-            error_message: String::default(),
-            pane_focus: PaneFocus::List,
+            error: ErrorReport::default(),
+            error_popup_state: ErrorPopupState::default(),
+            delete_prompt: None,
+            reimport_prompt: None,
+            search: None,
+            help: HelpOverlay::new(colors, help_sections()),
+            import_prompt: PathPrompt::new(colors),
+            export_prompt: PathPrompt::new(colors),
+            import_report: ImportReport::default(),
+            import_scroll: 0,
+            notice: None,
+            available_update: None,
+            offline: false,
+            client: ClientOptions::default(),
+            loading: false,
+            app_state: AppState::default(),
+            collections_dir: shorten_home(
+                &hac_config::get_collections_dir(),
+                std::env::var_os("HOME").map(PathBuf::from).as_deref(),
+            ),
+            focus_stack: vec![],
+            tasks: TaskTracker::default(),
+            activity: Activity::default(),
             dry_run,
         })
     }
 
-    pub fn display_error(&mut self, message: String) {
-        self.pane_focus = PaneFocus::Error;
-        self.error_message = message;
+    pub fn set_task_tracker(&mut self, tasks: TaskTracker) {
+        self.tasks = tasks;
     }
 
-    fn filter_list(&mut self) {
-        self.list_state.set_items(
-            self.collections
-                .clone()
-                .into_iter()
-                .filter(|s| s.info.name.contains(&self.filter))
+    pub fn set_activity(&mut self, activity: Activity) {
+        self.activity = activity;
+    }
+
+    /// shows a loading message in place of the list until `set_collections`
+    /// is called
+    pub fn set_loading(&mut self) {
+        self.loading = true;
+    }
+
+    pub fn set_collections(&mut self, collections: Vec<Collection>) {
+        self.loading = false;
+        self.collections = collections;
+        self.sort_collections();
+    }
+
+    /// replaces the state, sorting the collections the way it was last used
+    pub fn set_app_state(&mut self, app_state: AppState) {
+        self.app_state = app_state;
+        self.sort_collections();
+    }
+
+    /// a collection created during this session, it goes where the current
+    /// sorting puts it instead of the end of the list
+    pub fn add_collection(&mut self, collection: Collection) {
+        self.app_state.mark_opened(&collection.path);
+        self.collections.push(collection);
+        self.sort_collections();
+        self.save_app_state();
+    }
+
+    /// the collection asked for on the form was written, which is the only
+    /// thing that closes the form after submitting it
+    pub fn collection_created(&mut self, collection: Collection) {
+        self.add_collection(collection);
+        if self.form_state.submitting {
+            self.form_state.reset();
+            if self.focus().eq(&PaneFocus::Form) {
+                self.pop_focus();
+            }
+        }
+    }
+
+    /// a collection edited on the dashboard, it keeps its place on the list
+    /// unless the edit changed what the list is sorted by
+    pub fn update_collection(&mut self, collection: Collection) {
+        let Some(index) = self
+            .collections
+            .iter()
+            .position(|existing| existing.path.eq(&collection.path))
+        else {
+            return;
+        };
+
+        let sort_key_changed = match self.app_state.collection_sort {
+            CollectionSort::Name => self.collections[index].info.name.ne(&collection.info.name),
+            CollectionSort::RecentlyOpened => false,
+            // the file was just written, so it is now the most recent one
+            CollectionSort::RecentlyModified => !self.dry_run,
+        };
+
+        self.collections[index] = collection;
+        match sort_key_changed {
+            true => self.sort_collections(),
+            false => self
+                .list_state
+                .refresh_item(index, &self.collections[index], &self.app_state),
+        }
+    }
+
+    /// re-sorts the list, the selected collection stays selected wherever
+    /// it ends up
+    fn sort_collections(&mut self) {
+        let selected = self
+            .list_state
+            .selected_item()
+            .map(|item| item.path.clone());
+        self.app_state.sort_collections(&mut self.collections);
+        self.list_state
+            .set_items(&self.collections, &self.app_state);
+        self.list_state.filter(&self.filter);
+
+        let position = selected.and_then(|path| self.list_state.position_of(&path));
+        self.list_state
+            .select(position.or(self.list_state.is_empty().not().then_some(0)));
+    }
+
+    /// the collection the last session was left on along with the session
+    /// itself, when there is one to go back to
+    pub fn last_session(&self) -> Option<(Collection, Session)> {
+        let session = &self.app_state.session;
+        session
+            .find_collection(&self.collections)
+            .map(|collection| (collection.clone(), session.clone()))
+    }
+
+    pub fn set_session(&mut self, session: Session) {
+        if self.app_state.session.eq(&session) {
+            return;
+        }
+
+        self.app_state.session = session;
+        self.save_app_state();
+    }
+
+    /// the orientation the viewer was last switched to, if it ever was
+    pub fn layout(&self) -> Option<LayoutOrientation> {
+        self.app_state.layout
+    }
+
+    pub fn set_layout(&mut self, layout: LayoutOrientation) {
+        self.app_state.layout = Some(layout);
+        self.save_app_state();
+    }
+
+    pub fn set_available_update(&mut self, version: String) {
+        self.available_update = Some(version);
+    }
+
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// the pane taking the keys, the list when nothing is open over it
+    fn focus(&self) -> PaneFocus {
+        self.focus_stack.last().copied().unwrap_or(PaneFocus::List)
+    }
+
+    /// opens `pane` over what is focused, opening what is already on top
+    /// again does nothing, so it isn't closed twice
+    fn push_focus(&mut self, pane: PaneFocus) {
+        if self.focus().ne(&pane) {
+            self.focus_stack.push(pane);
+        }
+    }
+
+    /// closes what is on top, giving the keys back to what is below
+    fn pop_focus(&mut self) {
+        self.focus_stack.pop();
+    }
+
+    /// hands the key to the overlay on top, closing it once it is done.
+    /// One that is already gone is just taken off the stack
+    fn overlay_key_event<O: Overlay>(
+        &mut self,
+        key_event: KeyEvent,
+        overlay: fn(&mut Self) -> Option<&mut O>,
+    ) -> anyhow::Result<Option<O::Result>> {
+        let Some(overlay) = overlay(self) else {
+            self.pop_focus();
+            return Ok(None);
+        };
+
+        let result = overlay.handle_key_event(key_event)?;
+        if result.is_some() {
+            self.pop_focus();
+        }
+        Ok(result)
+    }
+
+    pub fn set_client_options(&mut self, client: ClientOptions) {
+        self.client = client;
+    }
+
+    /// offline mode can be toggled after the options were given
+    fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            offline: self.offline,
+            ..self.client.clone()
+        }
+    }
+
+    /// the release to mention on the status bar, if it wasn't dismissed
+    fn update_notice(&self) -> Option<&str> {
+        self.available_update
+            .as_deref()
+            .filter(|version| !self.app_state.is_update_dismissed(version))
+    }
+
+    fn save_app_state(&self) {
+        if self.dry_run {
+            return;
+        }
+
+        if let Err(e) = self.app_state.save() {
+            tracing::error!("failed to save application state: {e:?}");
+        }
+    }
+
+    /// shows what an import created and what it had to leave out, the
+    /// collections themselves are added with `add_collection`
+    pub fn display_import_summary(&mut self, collections: &[Collection], skipped: Vec<String>) {
+        self.import_report = ImportReport {
+            imported: collections
+                .iter()
+                .map(|collection| {
+                    (
+                        collection.info.name.clone(),
+                        collection.flatten_requests().len(),
+                    )
+                })
                 .collect(),
-        );
+            skipped,
+        };
+        self.import_scroll = 0;
+        self.push_focus(PaneFocus::ImportSummary);
+    }
+
+    pub fn display_error(&mut self, error: ErrorReport) {
+        self.loading = false;
+        // the error opens over the form, so what was typed is kept to be
+        // fixed and submitted again
+        self.form_state.submitting = false;
+        self.push_focus(PaneFocus::Error);
+        self.error = error;
+        self.error_popup_state = ErrorPopupState::default();
+    }
+
+    fn filter_list(&mut self) {
+        self.list_state.filter(&self.filter);
         self.list_state.select(None);
     }
 
     fn handle_filter_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Command>> {
         match (key_event.code, key_event.modifiers) {
             (KeyCode::Char('c'), KeyModifiers::CONTROL) | (KeyCode::Esc, _) => {
-                self.pane_focus = PaneFocus::List;
+                self.pop_focus();
                 self.filter = String::new();
                 self.filter_list();
             }
             (KeyCode::Backspace, _) => {
                 if self.filter.is_empty() {
-                    self.pane_focus = PaneFocus::List;
+                    self.pop_focus();
                 }
                 self.filter.pop();
                 self.filter_list();
             }
             (KeyCode::Enter, _) => {
-                self.pane_focus = PaneFocus::List;
+                self.pop_focus();
                 self.filter_list();
             }
             (KeyCode::Char(c), _) => {
@@ -130,91 +434,207 @@ impl<'a> CollectionDashboard<'a> {
     }
 
     fn handle_list_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Command>> {
+        self.notice = None;
+
+        if let (KeyCode::Char('f'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            self.search = Some(RequestSearch::new(self.colors, self.collections.clone()));
+            self.push_focus(PaneFocus::Search);
+            return Ok(None);
+        }
+
         match key_event.code {
             KeyCode::Enter => {
-                return Ok(self
+                // nothing is selected while collections are loading or when
+                // a filter matches nothing
+                let Some(collection) = self
                     .list_state
-                    .items
-                    .is_empty()
-                    .not()
-                    .then(|| {
-                        self.list_state
-                            .selected()
-                            .and_then(|i| self.collections.get(i))
-                            .expect(
-                                "user should never be allowed to select a non existing collection",
-                            )
-                    })
-                    .map(|collection| {
-                        tracing::debug!("selected collection: {}", collection.info.name);
-                        Command::SelectCollection(collection.clone())
-                    }));
+                    .selected_index()
+                    .and_then(|i| self.collections.get(i))
+                    .cloned()
+                else {
+                    return Ok(None);
+                };
+
+                tracing::debug!("selected collection: {}", collection.info.name);
+                self.app_state.mark_opened(&collection.path);
+                self.save_app_state();
+                return Ok(Some(Command::SelectCollection(collection)));
+            }
+            KeyCode::Char('d') if self.list_state.marked_count().gt(&0) => {
+                if let Some(item) = self
+                    .list_state
+                    .marked_items()
+                    .into_iter()
+                    .find(|item| item.read_only)
+                {
+                    self.notice = Some(read_only_notice(&item.name));
+                    return Ok(None);
+                }
+                let message = format!(
+                    "Delete {} collections?{}",
+                    self.list_state.marked_count(),
+                    self.hidden_marks_note()
+                );
+                self.delete_prompt = Some(ConfirmPopup::yes_no(message, self.colors));
+                self.push_focus(PaneFocus::Prompt);
             }
             KeyCode::Char('d') => {
-                if self.list_state.selected().is_some() {
-                    self.pane_focus = PaneFocus::Prompt;
+                if let Some(item) = self.list_state.selected_item() {
+                    if item.read_only {
+                        self.notice = Some(read_only_notice(&item.name));
+                        return Ok(None);
+                    }
+                    let name = &item.name;
+                    self.delete_prompt = Some(ConfirmPopup::yes_no(
+                        format!("You really want to delete collection {}?", name),
+                        self.colors,
+                    ));
+                    self.push_focus(PaneFocus::Prompt);
                 }
             }
             KeyCode::Char('n') | KeyCode::Char('c') => {
-                self.pane_focus = PaneFocus::Form;
+                self.push_focus(PaneFocus::Form);
             }
-            KeyCode::Char('h') | KeyCode::Left => {
-                if !self.list_state.items.is_empty() {
-                    self.list_state.select(
-                        self.list_state
-                            .selected()
-                            .map(|i| i.saturating_sub(1))
-                            .or(Some(0)),
+            KeyCode::Char('i') => {
+                self.import_prompt.open(
+                    "Import a .http, insomnia or collection file, or a url".into(),
+                    vec![],
+                );
+                self.push_focus(PaneFocus::Import);
+            }
+            KeyCode::Char('E') if self.list_state.marked_count().gt(&0) => {
+                let path = String::from("./collections");
+                self.export_prompt.open(
+                    format!(
+                        "Export {} collections to a directory{}",
+                        self.list_state.marked_count(),
+                        self.hidden_marks_note()
+                    ),
+                    vec![
+                        ("without secrets".into(), path.clone()),
+                        ("with secrets".into(), path),
+                    ],
+                );
+                self.push_focus(PaneFocus::Export);
+            }
+            KeyCode::Char('E') => {
+                if let Some(item) = self.list_state.selected_item() {
+                    let path =
+                        format!("./{}.json", item.name.replace(std::path::is_separator, "-"));
+                    self.export_prompt.open(
+                        format!("Export {}", item.name),
+                        vec![
+                            ("without secrets".into(), path.clone()),
+                            ("with secrets".into(), path),
+                        ],
                     );
+                    self.push_focus(PaneFocus::Export);
                 }
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                if !self.list_state.items.is_empty() {
-                    self.list_state.select(
-                        self.list_state
-                            .selected()
-                            .map(|i| {
-                                usize::min(
-                                    self.list_state.items.len() - 1,
-                                    i + self.list.items_per_row(&self.layout.collections_pane),
-                                )
-                            })
-                            .or(Some(0)),
+            KeyCode::Char('e') => {
+                if let Some(item) = self.list_state.selected_item() {
+                    if item.read_only {
+                        self.notice = Some(read_only_notice(&item.name));
+                        return Ok(None);
+                    }
+                    self.form_state.edit(
+                        item.name.clone(),
+                        item.description.clone(),
+                        item.path.clone(),
                     );
+                    self.push_focus(PaneFocus::Form);
                 }
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if !self.list_state.items.is_empty() {
+            KeyCode::Char('r') => self.open_reimport_prompt(),
+            KeyCode::Char('y') => self.duplicate_collection(),
+            KeyCode::Char('v') => {
+                if self.list_state.selected_item().is_some() {
+                    self.push_focus(PaneFocus::Details);
+                }
+            }
+            KeyCode::Char(' ') => self.list_state.toggle_mark(),
+            KeyCode::Char('h') | KeyCode::Left => {
+                if !self.list_state.is_empty() {
                     self.list_state.select(
                         self.list_state
                             .selected()
-                            .map(|i| {
-                                i.saturating_sub(
-                                    self.list.items_per_row(&self.layout.collections_pane),
-                                )
-                            })
+                            .map(|i| i.saturating_sub(1))
                             .or(Some(0)),
                     );
                 }
             }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let per_row = self.list.items_per_row(&self.layout.collections_pane);
+                self.list_state.select_row(1, per_row);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let per_row = self.list.items_per_row(&self.layout.collections_pane);
+                self.list_state.select_row(-1, per_row);
+            }
             KeyCode::Char('l') | KeyCode::Right => {
-                if !self.list_state.items.is_empty() {
+                if !self.list_state.is_empty() {
                     self.list_state.select(
                         self.list_state
                             .selected()
-                            .map(|i| usize::min(self.list_state.items.len().sub(1), i.add(1)))
+                            .map(|i| usize::min(self.list_state.len().sub(1), i.add(1)))
                             .or(Some(0)),
                     );
                 }
             }
-            KeyCode::Char('?') => self.pane_focus = PaneFocus::Help,
-            KeyCode::Char('/') => self.pane_focus = PaneFocus::Filter,
+            KeyCode::Esc if self.list_state.marked_count().gt(&0) => self.list_state.clear_marks(),
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.filter_list();
+                self.list_state
+                    .select(self.list_state.is_empty().not().then_some(0));
+            }
+            KeyCode::Char('s') => {
+                self.app_state.collection_sort = self.app_state.collection_sort.next();
+                self.sort_collections();
+                self.save_app_state();
+            }
+            KeyCode::Char('p') => {
+                if let Some(path) = self
+                    .list_state
+                    .selected_item()
+                    .map(|item| item.path.clone())
+                {
+                    self.app_state.toggle_pin(&path);
+                    self.sort_collections();
+                    self.save_app_state();
+                }
+            }
+            KeyCode::Char('u') => {
+                if let Some(version) = self.update_notice().map(String::from) {
+                    self.app_state.dismissed_update = Some(version);
+                    self.save_app_state();
+                }
+            }
+            KeyCode::Char('O') => {
+                self.offline = !self.offline;
+                self.notice = Some(String::from(match self.offline {
+                    true => "offline mode, requests are not sent",
+                    false => "back online",
+                }));
+                if let Some(sender) = self.command_sender.as_ref() {
+                    _ = sender.send(Command::OfflineChanged(self.offline));
+                }
+            }
+            KeyCode::Char('?') => {
+                self.help.reset();
+                self.push_focus(PaneFocus::Help);
+            }
+            KeyCode::Char('/') => self.push_focus(PaneFocus::Filter),
             _ => {}
         };
         Ok(None)
     }
 
     fn handle_form_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Command>> {
+        if self.form_state.submitting {
+            return Ok(None);
+        }
+
         match (key_event.code, key_event.modifiers) {
             (KeyCode::Tab, _) => match self.form_state.focused_field {
                 FormFocus::Name => self.form_state.focused_field = FormFocus::Description,
@@ -228,7 +648,11 @@ impl<'a> CollectionDashboard<'a> {
                 _ => {}
             },
             (KeyCode::Enter, _) => match self.form_state.focused_field {
+                FormFocus::Confirm if self.form_state.editing.is_some() => {
+                    self.save_edited_collection()
+                }
                 FormFocus::Confirm => {
+                    self.form_state.submitting = true;
                     let name = self.form_state.name.clone();
                     let description = self.form_state.description.clone();
 
@@ -239,28 +663,23 @@ impl<'a> CollectionDashboard<'a> {
 
                     let dry_run = self.dry_run;
 
-                    tokio::spawn(async move {
-                        match hac_core::fs::create_collection(name, description, dry_run).await {
-                            Ok(collection) => {
-                                if sender_copy
-                                    .send(Command::CreateCollection(collection))
-                                    .is_err()
-                                {
-                                    tracing::error!("failed to send command through channel");
-                                    std::process::abort();
-                                }
-                            }
-                            Err(e) => {
-                                if sender_copy.send(Command::Error(e.to_string())).is_err() {
-                                    tracing::error!("failed to send error command through channel");
-                                    std::process::abort();
-                                }
-                            }
+                    self.tasks.spawn(async move {
+                        let command =
+                            match hac_core::fs::create_collection(name, description, dry_run).await
+                            {
+                                Ok(collection) => Command::CreateCollection(collection),
+                                Err(e) => Command::Error(ErrorReport::new(&e)),
+                            };
+
+                        // the receiver is only gone when the application is
+                        // shutting down, the file is written either way
+                        if sender_copy.send(command).is_err() {
+                            tracing::error!("failed to send command through channel");
                         }
                     });
                 }
                 FormFocus::Cancel => {
-                    self.pane_focus = PaneFocus::List;
+                    self.pop_focus();
                     self.form_state.reset();
                 }
                 _ => {}
@@ -279,225 +698,659 @@ impl<'a> CollectionDashboard<'a> {
         Ok(None)
     }
 
-    #[tracing::instrument(skip_all)]
-    fn handle_confirm_popup_key_event(
-        &mut self,
-        key_event: KeyEvent,
-    ) -> anyhow::Result<Option<Command>> {
-        match key_event.code {
-            KeyCode::Char('y') => {
-                let selected = self
-                    .list_state
-                    .selected()
-                    .expect("deleting when nothing is selected should never happen");
-                let collection = self
-                    .collections
-                    .get(selected)
-                    .expect("should never attempt to delete a non existing item");
-                let path = collection.path.clone();
-
-                if !self.dry_run {
-                    tokio::spawn(async move {
-                        tracing::debug!("attempting to delete collection: {:?}", path);
-                        hac_core::fs::delete_collection(&path)
-                            .await
-                            .expect("failed to delete collection from filesystem");
-                    });
-                }
+    /// writes the info on the form back to the collection being edited, the
+    /// list is only updated once the file is saved
+    fn save_edited_collection(&mut self) {
+        let path = self.form_state.editing.take();
+        let Some(mut collection) = path
+            .and_then(|path| self.collections.iter().find(|c| c.path.eq(&path)))
+            .cloned()
+        else {
+            return;
+        };
 
-                self.collections.remove(selected);
-                self.list_state.set_items(self.collections.clone());
-                self.list_state.select(None);
-                self.pane_focus = PaneFocus::List;
-            }
-            KeyCode::Char('n') => {
-                self.pane_focus = PaneFocus::List;
+        // an empty name would leave the card blank, so we keep the old one
+        if !self.form_state.name.trim().is_empty() {
+            collection.info.name = self.form_state.name.clone();
+        }
+        collection.info.description = Some(self.form_state.description.clone());
+
+        let sender = self
+            .command_sender
+            .clone()
+            .expect("should always have a sender at this point");
+        let dry_run = self.dry_run;
+
+        self.tasks.spawn(async move {
+            let command = match dry_run {
+                true => Command::UpdateCollection(collection),
+                false => match hac_core::fs::sync_collection(collection.clone()).await {
+                    Ok(_) => Command::UpdateCollection(collection),
+                    Err(e) => Command::Error(ErrorReport::new(&e)),
+                },
+            };
+
+            if sender.send(command).is_err() {
+                tracing::error!("failed to send command through channel");
             }
-            _ => {}
-        };
+        });
 
-        Ok(None)
+        self.form_state.reset();
+        self.pop_focus();
     }
 
-    fn handle_error_popup_key_event(
-        &mut self,
-        key_event: KeyEvent,
-    ) -> anyhow::Result<Option<Command>> {
-        match key_event.code {
-            KeyCode::Char('o') | KeyCode::Esc | KeyCode::Enter => {
-                self.pane_focus = PaneFocus::List;
+    fn handle_search_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Command>> {
+        let event = self.overlay_key_event(key_event, |dashboard| dashboard.search.as_mut())?;
+        if event.is_some() {
+            self.search = None;
+        }
+
+        match event {
+            Some(RequestSearchEvent::Open(result)) => {
+                let Some(collection) = self
+                    .collections
+                    .iter()
+                    .find(|collection| collection.path.eq(&result.collection_path))
+                    .cloned()
+                else {
+                    return Ok(None);
+                };
+
+                self.app_state.mark_opened(&collection.path);
+                self.save_app_state();
+                return Ok(Some(Command::OpenRequest {
+                    collection,
+                    request_id: result.request_id,
+                }));
             }
-            _ => {}
-        };
+            Some(RequestSearchEvent::Quit) => return Ok(Some(Command::Quit)),
+            Some(RequestSearchEvent::Close) | None => {}
+        }
 
         Ok(None)
     }
 
-    fn draw_hint_text(&self, frame: &mut Frame) {
-        let hint =
-            "[h/j/k/l to move] [n -> new] [enter -> select item] [? -> help] [<C-c> -> quit]"
-                .fg(self.colors.normal.magenta)
-                .into_centered_line();
+    #[tracing::instrument(skip_all)]
+    fn handle_import_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Command>> {
+        match self.overlay_key_event(key_event, |dashboard| Some(&mut dashboard.import_prompt))? {
+            Some(PathPromptEvent::Submit { path, .. }) => self.import_collection(path),
+            Some(PathPromptEvent::Quit) => return Ok(Some(Command::Quit)),
+            Some(PathPromptEvent::Close) | None => {}
+        }
 
-        frame.render_widget(hint, self.layout.hint_pane);
+        Ok(None)
     }
 
-    fn draw_help_popup(&self, frame: &mut Frame) {
-        make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
-
-        let lines = vec![
-            Line::from(vec![
-                "h/<left>".fg(self.colors.bright.magenta),
-                "    - select left item".into(),
-            ]),
-            Line::from(vec![
-                "j/<down>".fg(self.colors.bright.magenta),
-                "    - select item below".into(),
-            ]),
-            Line::from(vec![
-                "k/<up>".fg(self.colors.bright.magenta),
-                "      - select item above".into(),
-            ]),
-            Line::from(vec![
-                "l/<right>".fg(self.colors.bright.magenta),
-                "   - select right item".into(),
-            ]),
-            Line::from(vec![
-                "n/c".fg(self.colors.bright.magenta),
-                "         - creates a new collection".into(),
-            ]),
-            Line::from(vec![
-                "d".fg(self.colors.bright.magenta),
-                "           - deletes the selected collection".into(),
-            ]),
-            Line::from(vec![
-                "?".fg(self.colors.bright.magenta),
-                "           - toggle this help window".into(),
-            ]),
-            Line::from(vec![
-                "enter".fg(self.colors.bright.magenta),
-                "       - select item under cursor".into(),
-            ]),
-            Line::from(vec![
-                "/".fg(self.colors.bright.magenta),
-                "           - enter filter mode".into(),
-            ]),
-            Line::from(vec![
-                "<C-c>".fg(self.colors.bright.magenta),
-                "       - quits the application".into(),
-            ]),
-            Line::from(""),
-            Line::from("press any key to go back".fg(self.colors.normal.magenta)).centered(),
-        ];
-
-        frame.render_widget(
-            Paragraph::new(lines)
-                .wrap(Wrap { trim: true })
-                .block(Block::default().padding(Padding::new(2, 2, 1, 1))),
-            self.layout.help_popup,
-        );
+    /// creates collections out of the requests on the file, or on the url
+    /// it points to. A single one is opened like a newly created one,
+    /// otherwise they are listed on a summary along with what couldn't be
+    /// imported
+    fn import_collection(&mut self, source: String) {
+        let sender_copy = self
+            .command_sender
+            .clone()
+            .expect("should always have a sender at this point");
+        let dry_run = self.dry_run;
+        let options = self.client_options();
+        let activity = self.activity.clone();
+        let id = activity.start(ActivityKind::Import, source.clone());
+
+        let abort = self.tasks.spawn(async move {
+            let imported = match remote::is_url(&source) {
+                true => hac_core::fs::import_from_url(&source, &options, dry_run).await,
+                false => hac_core::fs::import_collections(PathBuf::from(source), dry_run).await,
+            };
+            activity.finish(id, import_outcome(&imported));
+            let command = match imported {
+                Ok(mut imported)
+                    if imported.collections.len().eq(&1) && imported.skipped.is_empty() =>
+                {
+                    Command::CreateCollection(imported.collections.remove(0))
+                }
+                Ok(imported) => Command::CollectionsImported {
+                    collections: imported.collections,
+                    skipped: imported.skipped,
+                },
+                Err(e) => Command::Error(ErrorReport::new(&e)),
+            };
+
+            if sender_copy.send(command).is_err() {
+                tracing::error!("failed to send command through channel");
+            }
+        });
+        self.activity.attach(id, abort);
     }
 
-    fn draw_filter_prompt(&self, frame: &mut Frame) {
-        let filter = Line::from(format!("/{}", self.filter));
-        frame.render_widget(filter, self.layout.hint_pane);
-    }
+    /// asks how to bring the selected collection up to date with the url
+    /// it was imported from, as replacing it loses what was changed since
+    fn open_reimport_prompt(&mut self) {
+        let Some(collection) = self
+            .list_state
+            .selected_index()
+            .and_then(|i| self.collections.get(i))
+        else {
+            return;
+        };
+        let Some(source) = collection.info.source.as_deref() else {
+            self.notice = Some(format!(
+                "{} was not imported from a url",
+                collection.info.name
+            ));
+            return;
+        };
 
-    fn draw_collection_list(&mut self, frame: &mut Frame) {
-        frame.render_stateful_widget(
-            self.list.clone(),
-            self.layout.collections_pane,
-            &mut self.list_state,
-        );
+        // read-only collections can only be brought up to date as a copy
+        let (buttons, message, selected) = match collection.is_read_only() {
+            true => (
+                vec![
+                    ConfirmButton::new("(c)opy", 'c', self.colors.normal.green, Reimport::Copy),
+                    ConfirmButton::new("ca(n)cel", 'n', self.colors.normal.blue, Reimport::Cancel),
+                ],
+                format!(
+                    "Re-import {} from {source}? It is read-only, so it can only be copied",
+                    collection.info.name
+                ),
+                0,
+            ),
+            false => (
+                vec![
+                    ConfirmButton::new("(r)eplace", 'r', self.colors.normal.red, Reimport::Replace),
+                    ConfirmButton::new("(c)opy", 'c', self.colors.normal.green, Reimport::Copy),
+                    ConfirmButton::new("ca(n)cel", 'n', self.colors.normal.blue, Reimport::Cancel),
+                ],
+                format!(
+                    "Re-import {} from {source}? Replacing it discards the changes made since",
+                    collection.info.name
+                ),
+                1,
+            ),
+        };
+        self.reimport_prompt =
+            Some(ConfirmPopup::new(message, self.colors, buttons).with_selected(selected));
+        self.push_focus(PaneFocus::Reimport);
     }
 
-    fn draw_no_matches_text(&self, frame: &mut Frame) -> anyhow::Result<()> {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Fill(1),
-                Constraint::Length(8),
-                Constraint::Fill(1),
-            ])
-            .split(self.layout.collections_pane)[1];
+    fn handle_reimport_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> anyhow::Result<Option<Command>> {
+        let event =
+            self.overlay_key_event(key_event, |dashboard| dashboard.reimport_prompt.as_mut())?;
+        if event.is_some() {
+            self.reimport_prompt = None;
+        }
 
-        let no_matches = BigText::builder()
-            .pixel_size(PixelSize::Quadrant)
-            .style(Style::default().fg(self.colors.normal.magenta))
-            .lines(vec!["No matches".into()])
-            .alignment(Alignment::Center)
-            .build()?;
+        let replace = match event {
+            Some(ConfirmPopupEvent::Confirm(Reimport::Replace)) => true,
+            Some(ConfirmPopupEvent::Confirm(Reimport::Copy)) => false,
+            Some(ConfirmPopupEvent::Quit) => return Ok(Some(Command::Quit)),
+            _ => return Ok(None),
+        };
 
-        frame.render_widget(no_matches, layout);
+        if let Some(collection) = self
+            .list_state
+            .selected_index()
+            .and_then(|i| self.collections.get(i))
+            .cloned()
+        {
+            self.reimport_collection(collection, replace);
+        }
 
-        Ok(())
+        Ok(None)
     }
 
-    fn draw_empty_message(&self, frame: &mut Frame) -> anyhow::Result<()> {
-        let size = Layout::default()
-            .direction(Direction::Vertical)
-            .flex(Flex::Center)
-            .constraints([
-                Constraint::Fill(1),
-                Constraint::Length(8),
-                Constraint::Fill(1),
-            ])
-            .split(self.layout.collections_pane)[1];
-
-        let empty_message = BigText::builder()
-            .pixel_size(PixelSize::Quadrant)
-            .style(Style::default().fg(self.colors.normal.magenta))
-            .lines(vec!["No collections".into()])
-            .alignment(Alignment::Center)
-            .build()?;
-
-        frame.render_widget(empty_message, size);
-
-        Ok(())
+    /// fetches `collection` again, replacing it on the list or adding the
+    /// copy like a newly created collection
+    fn reimport_collection(&mut self, collection: Collection, replace: bool) {
+        let sender = self
+            .command_sender
+            .clone()
+            .expect("should always have a sender at this point");
+        let dry_run = self.dry_run;
+        let options = self.client_options();
+        let activity = self.activity.clone();
+        let label = collection.info.source.clone().unwrap_or_default();
+        let id = activity.start(ActivityKind::Import, label);
+
+        let abort = self.tasks.spawn(async move {
+            let reimported =
+                hac_core::fs::reimport_collection(&collection, &options, replace, dry_run).await;
+            activity.finish(
+                id,
+                reimported
+                    .as_ref()
+                    .map(|fresh| fresh.info.name.clone())
+                    .map_err(|e| e.to_string()),
+            );
+            let command = match reimported {
+                Ok(fresh) if replace => Command::UpdateCollection(fresh),
+                Ok(fresh) => Command::CreateCollection(fresh),
+                Err(e) => Command::Error(ErrorReport::new(&e)),
+            };
+
+            if sender.send(command).is_err() {
+                tracing::error!("failed to send command through channel");
+            }
+        });
+        self.activity.attach(id, abort);
     }
 
-    fn draw_background(&self, size: Rect, frame: &mut Frame) {
-        frame.render_widget(Clear, size);
-        frame.render_widget(Block::default().bg(self.colors.primary.background), size);
+    /// writes a copy of the selected collection that can be changed, the
+    /// way to edit a read-only one. It is added like a newly created one
+    fn duplicate_collection(&mut self) {
+        let Some(collection) = self
+            .list_state
+            .selected_index()
+            .and_then(|i| self.collections.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let sender = self
+            .command_sender
+            .clone()
+            .expect("should always have a sender at this point");
+        let dry_run = self.dry_run;
+
+        self.tasks.spawn(async move {
+            let command = match hac_core::fs::duplicate_collection(&collection, dry_run).await {
+                Ok(copy) => Command::CreateCollection(copy),
+                Err(e) => Command::Error(ErrorReport::new(&e)),
+            };
+
+            if sender.send(command).is_err() {
+                tracing::error!("failed to send command through channel");
+            }
+        });
     }
 
-    fn draw_error_popup(&self, frame: &mut Frame) {
-        let popup = ErrorPopup::new(self.error_message.clone(), self.colors);
-        popup.render(self.layout.error_popup, frame.buffer_mut());
+    fn handle_import_summary_key_event(&mut self, key_event: KeyEvent) -> Option<Command> {
+        match key_event.code {
+            KeyCode::Char('o') | KeyCode::Esc | KeyCode::Enter => {
+                self.pop_focus();
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.import_scroll = self.import_scroll.add(1),
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.import_scroll = self.import_scroll.saturating_sub(1)
+            }
+            _ => {}
+        }
+
+        None
     }
 
-    fn draw_form_popup(&mut self, size: Rect, frame: &mut Frame) {
-        self.draw_background(size, frame);
-        draw_overlay(self.colors, size, "新", frame);
+    fn handle_export_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Command>> {
+        match self.overlay_key_event(key_event, |dashboard| Some(&mut dashboard.export_prompt))? {
+            Some(PathPromptEvent::Submit { path, choice }) => {
+                match self.list_state.marked_count() {
+                    0 => self.export_collection(PathBuf::from(path), choice.eq(&1)),
+                    _ => self.export_marked(PathBuf::from(path), choice.eq(&1)),
+                }
+            }
+            Some(PathPromptEvent::Quit) => return Ok(Some(Command::Quit)),
+            Some(PathPromptEvent::Close) | None => {}
+        }
 
-        let form = NewCollectionForm::new(self.colors);
-        form.render(
-            self.layout.form_popup,
-            frame.buffer_mut(),
-            &mut self.form_state,
-        );
+        Ok(None)
     }
 
-    fn draw_delete_prompt(&self, frame: &mut Frame) {
-        let selected_index = self
+    fn export_collection(&mut self, path: PathBuf, include_secrets: bool) {
+        let Some(collection) = self
             .list_state
-            .selected()
-            .expect("attempted to open confirm popup without an item selected");
-        let selected_item_name = &self
-            .collections
-            .get(selected_index)
-            .expect("should never be able to have an out of bounds selection")
-            .info
-            .name;
-
-        let confirm_popup = ConfirmPopup::new(
-            format!(
-                "You really want to delete collection {}?",
-                selected_item_name
-            ),
-            self.colors,
-        );
-        confirm_popup.render(self.layout.confirm_popup, frame.buffer_mut());
+            .selected_item()
+            .and_then(|item| self.collections.iter().find(|c| c.path.eq(&item.path)))
+        else {
+            return;
+        };
+
+        match hac_core::fs::export_collection(collection, &path, include_secrets) {
+            Ok(_) => self.notice = Some(format!("exported to {}", path.to_string_lossy())),
+            Err(e) => self.display_error(ErrorReport::new(&e)),
+        }
+    }
+
+    /// every marked collection to a file of its own under `dir`, marks are
+    /// kept so the same selection can be acted upon again
+    fn export_marked(&mut self, dir: PathBuf, include_secrets: bool) {
+        let marked = self
+            .list_state
+            .marked_items()
+            .into_iter()
+            .filter_map(|item| self.collections.iter().find(|c| c.path.eq(&item.path)))
+            .collect::<Vec<_>>();
+
+        match hac_core::fs::export_collections(&marked, &dir, include_secrets) {
+            Ok(written) => {
+                self.notice = Some(format!(
+                    "exported {} collections to {}",
+                    written.len(),
+                    dir.to_string_lossy()
+                ))
+            }
+            Err(e) => self.display_error(ErrorReport::new(&e)),
+        }
+    }
+
+    /// bulk actions include marked collections the filter is hiding, so the
+    /// prompts asking for them say how many of those there are
+    fn hidden_marks_note(&self) -> String {
+        match self.list_state.hidden_marks() {
+            0 => String::new(),
+            1 => String::from(" 1 of them is hidden by the filter"),
+            hidden => format!(" {hidden} of them are hidden by the filter"),
+        }
+    }
+
+    /// moves the collection files to the trash in the background, the list
+    /// is expected to be updated by the caller
+    fn trash_collections(&self, paths: Vec<PathBuf>) {
+        if self.dry_run {
+            return;
+        }
+
+        let sender = self.command_sender.clone();
+        self.tasks.spawn(async move {
+            let trash_dir = hac_config::trash_dir();
+            for path in paths {
+                tracing::debug!("attempting to delete collection: {:?}", path);
+                if let Err(e) = hac_core::fs::trash_collection(&path, &trash_dir).await {
+                    tracing::error!("failed to delete collection: {e:?}");
+                    if let Some(sender) = sender.as_ref() {
+                        _ = sender.send(Command::Error(ErrorReport::new(&e)));
+                    }
+                }
+            }
+        });
+    }
+
+    fn handle_confirm_popup_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> anyhow::Result<Option<Command>> {
+        let event =
+            self.overlay_key_event(key_event, |dashboard| dashboard.delete_prompt.as_mut())?;
+        if event.is_some() {
+            self.delete_prompt = None;
+        }
+
+        match event {
+            Some(ConfirmPopupEvent::Confirm(true)) if self.list_state.marked_count().gt(&0) => {
+                let marked = self
+                    .list_state
+                    .marked_items()
+                    .into_iter()
+                    .map(|item| item.path.clone())
+                    .collect::<Vec<_>>();
+
+                self.collections
+                    .retain(|collection| !marked.contains(&collection.path));
+                self.trash_collections(marked);
+                self.list_state.clear_marks();
+                self.sort_collections();
+            }
+            Some(ConfirmPopupEvent::Confirm(true)) => {
+                let (Some(position), Some(selected)) =
+                    (self.list_state.selected(), self.list_state.selected_index())
+                else {
+                    tracing::warn!("confirmed a deletion without a collection selected");
+                    return Ok(None);
+                };
+                let path = self.collections[selected].path.clone();
+                self.trash_collections(vec![path]);
+
+                self.collections.remove(selected);
+                self.list_state
+                    .set_items(&self.collections, &self.app_state);
+                self.list_state.filter(&self.filter);
+                // the next collection takes the place of the deleted one,
+                // or the previous one when the last was deleted
+                self.list_state.select(match self.list_state.len() {
+                    0 => None,
+                    len => Some(position.min(len.sub(1))),
+                });
+            }
+            Some(ConfirmPopupEvent::Quit) => return Ok(Some(Command::Quit)),
+            _ => {}
+        };
+
+        Ok(None)
+    }
+
+    fn handle_error_popup_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> anyhow::Result<Option<Command>> {
+        match key_event.code {
+            KeyCode::Char('o') | KeyCode::Esc | KeyCode::Enter => {
+                self.pop_focus();
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.error_popup_state.scroll_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.error_popup_state.scroll_up(),
+            KeyCode::Char('d') => self.error_popup_state.toggle_details(),
+            KeyCode::Char('y') => {
+                if let Err(e) = clipboard::copy(&self.error.to_string()) {
+                    tracing::error!("failed to copy error to the clipboard: {e:?}");
+                }
+            }
+            _ => {}
+        };
+
+        Ok(None)
+    }
+
+    /// the hints of the help sections the focused part of the dashboard
+    /// responds to
+    fn hint_segments(&self, titles: &[&str]) -> Vec<Segment> {
+        titles
+            .iter()
+            .flat_map(|title| self.help.hints(title))
+            .map(|hint| match hint.keys() {
+                "s" => hint
+                    .clone()
+                    .with_value(self.app_state.collection_sort.label()),
+                _ => hint.clone(),
+            })
+            .map(|hint| hint.segment(self.colors.normal.magenta))
+            .collect()
+    }
+
+    fn status_segments(&self) -> Vec<Segment> {
+        match self.focus() {
+            PaneFocus::Filter => {
+                let mut segments =
+                    vec![Segment::left(format!("/{}", self.filter)).with_priority(u8::MAX)];
+                segments.extend(self.hint_segments(&["filter"]));
+                segments
+            }
+            _ => {
+                let mut segments =
+                    self.hint_segments(&["navigation", "collections", "list", "general"]);
+                if self.list_state.marked_count().gt(&0) {
+                    segments.push(
+                        Segment::right(
+                            format!(
+                                "{} marked [d -> delete] [E -> export] [esc -> clear]",
+                                self.list_state.marked_count()
+                            )
+                            .fg(self.colors.normal.green),
+                        )
+                        .with_priority(u8::MAX),
+                    );
+                }
+                if let Some(notice) = self.notice.as_ref() {
+                    segments.push(
+                        Segment::right(notice.clone().fg(self.colors.normal.green))
+                            .with_priority(u8::MAX),
+                    );
+                }
+                if self.offline {
+                    segments.push(status_bar::offline_badge(self.colors));
+                }
+                match self.activity.active_count() {
+                    0 => {}
+                    active => segments.push(status_bar::activity_counter(self.colors, active)),
+                }
+                if let Some(version) = self.update_notice() {
+                    segments.push(
+                        Segment::right(
+                            format!("hac {version} is available [u -> dismiss]")
+                                .fg(self.colors.normal.yellow),
+                        )
+                        .with_priority(2),
+                    );
+                }
+                segments
+            }
+        }
+    }
+
+    fn draw_status_bar(&self, frame: &mut Frame) {
+        let status_bar = StatusBar::new(self.status_segments());
+        frame.render_widget(status_bar, self.layout.hint_pane);
+    }
+
+    fn draw_collection_list(&mut self, frame: &mut Frame) {
+        frame.render_stateful_widget(
+            &self.list,
+            self.layout.collections_pane,
+            &mut self.list_state,
+        );
+    }
+
+    fn draw_no_matches_text(&self, frame: &mut Frame) {
+        let size = Layout::default()
+            .direction(Direction::Vertical)
+            .flex(Flex::Center)
+            .constraints([Constraint::Length(3)])
+            .split(self.layout.collections_pane)[0];
+
+        let lines = vec![
+            Line::from(vec![
+                "no collections match ".fg(self.colors.normal.magenta),
+                format!("‹{}›", self.filter).fg(self.colors.bright.magenta),
+            ])
+            .centered(),
+            Line::default(),
+            Line::from(vec![
+                "press ".fg(self.colors.bright.black),
+                "<esc>".fg(self.colors.bright.magenta),
+                " to clear the filter".fg(self.colors.bright.black),
+            ])
+            .centered(),
+        ];
+
+        frame.render_widget(Paragraph::new(lines), size);
+    }
+
+    /// first thing a fresh install sees, explains what collections are and
+    /// how to get the first one
+    fn draw_empty_message(&self, frame: &mut Frame) {
+        let lines = vec![
+            Line::from("Welcome to hac!".fg(self.colors.bright.magenta).bold()).centered(),
+            Line::default(),
+            Line::from(
+                "collections group the requests you send to an api,".fg(self.colors.normal.white),
+            ),
+            Line::from("and you don't have any yet.".fg(self.colors.normal.white)),
+            Line::default(),
+            Line::from(vec![
+                "n".fg(self.colors.bright.magenta),
+                "      -> create a new collection".fg(self.colors.normal.white),
+            ]),
+            Line::from(vec![
+                "i".fg(self.colors.bright.magenta),
+                "      -> import .http, insomnia or collection files".fg(self.colors.normal.white),
+            ]),
+            Line::from("collection files can also be copied into:".fg(self.colors.normal.white)),
+            Line::from(self.collections_dir.clone().fg(self.colors.bright.black)),
+        ];
+
+        let pane = self.layout.collections_pane;
+        let width = u16::min(pane.width, 60);
+        let height = u16::min(pane.height, lines.len().add(4) as u16);
+        let size = Rect::new(
+            pane.x.add(pane.width.sub(width).div(2)),
+            pane.y.add(pane.height.sub(height).div(2)),
+            width,
+            height,
+        );
+
+        let onboarding = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(self.colors.bright.black))
+                .padding(Padding::new(2, 2, 1, 1)),
+        );
+
+        frame.render_widget(onboarding, size);
+    }
+
+    fn draw_loading_message(&self, frame: &mut Frame) {
+        let size = Layout::default()
+            .direction(Direction::Vertical)
+            .flex(Flex::Center)
+            .constraints([Constraint::Length(1)])
+            .split(self.layout.collections_pane)[0];
+
+        let loading_message =
+            Line::from("loading collections…".fg(self.colors.bright.black)).centered();
+
+        frame.render_widget(loading_message, size);
+    }
+
+    fn draw_background(&self, size: Rect, frame: &mut Frame) {
+        frame.render_widget(Clear, size);
+        frame.render_widget(Block::default().bg(self.colors.primary.background), size);
+    }
+
+    fn draw_error_popup(&mut self, frame: &mut Frame) {
+        let popup = ErrorPopup::new(&self.error, self.colors);
+        popup.render(
+            self.layout.error_popup,
+            frame.buffer_mut(),
+            &mut self.error_popup_state,
+        );
+    }
+
+    fn draw_form_popup(&mut self, size: Rect, frame: &mut Frame) {
+        self.draw_background(size, frame);
+        draw_overlay(self.colors, size, "新", frame);
+
+        let form = NewCollectionForm::new(self.colors);
+        form.render(
+            self.layout.form_popup,
+            frame.buffer_mut(),
+            &mut self.form_state,
+        );
+    }
+
+    fn draw_import_summary(&self, frame: &mut Frame) {
+        make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
+        frame.render_widget(
+            ImportSummary::new(&self.import_report, self.colors, self.import_scroll),
+            self.layout.details_popup,
+        );
+    }
+
+    fn draw_details_popup(&self, frame: &mut Frame) {
+        if let Some(item) = self.list_state.selected_item() {
+            make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
+            frame.render_widget(
+                CollectionDetails::new(item, self.colors),
+                self.layout.details_popup,
+            );
+        }
+    }
+
+    fn draw_search(&mut self, frame: &mut Frame) -> anyhow::Result<()> {
+        match self.search.as_mut() {
+            Some(search) => {
+                make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
+                search.draw(frame, self.layout.search_popup)
+            }
+            None => Ok(()),
+        }
     }
 
     fn draw_title(&self, frame: &mut Frame) -> anyhow::Result<()> {
@@ -519,23 +1372,42 @@ impl Renderable for CollectionDashboard<'_> {
         self.draw_background(size, frame);
         self.draw_title(frame)?;
 
-        match (
-            self.collections.is_empty(),
-            self.list_state.items.is_empty(),
-        ) {
+        match (self.collections.is_empty(), self.list_state.is_empty()) {
             (false, false) => self.draw_collection_list(frame),
-            (false, true) => self.draw_no_matches_text(frame)?,
-            (true, true) => self.draw_empty_message(frame)?,
+            (false, true) => self.draw_no_matches_text(frame),
+            (true, true) if self.loading => self.draw_loading_message(frame),
+            (true, true) => self.draw_empty_message(frame),
             (true, false) => unreachable!(),
         }
 
-        match self.pane_focus {
-            PaneFocus::Error => self.draw_error_popup(frame),
-            PaneFocus::Form => self.draw_form_popup(size, frame),
-            PaneFocus::Filter => self.draw_filter_prompt(frame),
-            PaneFocus::Help => self.draw_help_popup(frame),
-            PaneFocus::Prompt => self.draw_delete_prompt(frame),
-            PaneFocus::List => self.draw_hint_text(frame),
+        if matches!(self.focus(), PaneFocus::List | PaneFocus::Filter) {
+            self.draw_status_bar(frame);
+        }
+
+        // popups are drawn from the bottom of the stack up, so the one taking
+        // the keys is always the one on top
+        for idx in 0..self.focus_stack.len() {
+            match self.focus_stack[idx] {
+                PaneFocus::Error => self.draw_error_popup(frame),
+                PaneFocus::Form => self.draw_form_popup(size, frame),
+                PaneFocus::Help => self.help.draw(frame, self.layout.help_popup)?,
+                PaneFocus::Details => self.draw_details_popup(frame),
+                PaneFocus::Search => self.draw_search(frame)?,
+                PaneFocus::Prompt => draw_over(
+                    self.delete_prompt.as_mut(),
+                    frame,
+                    self.layout.confirm_popup,
+                )?,
+                PaneFocus::Reimport => draw_over(
+                    self.reimport_prompt.as_mut(),
+                    frame,
+                    self.layout.confirm_popup,
+                )?,
+                PaneFocus::Import => self.import_prompt.draw(frame, size)?,
+                PaneFocus::ImportSummary => self.draw_import_summary(frame),
+                PaneFocus::Export => self.export_prompt.draw(frame, size)?,
+                PaneFocus::List | PaneFocus::Filter => {}
+            }
         }
 
         Ok(())
@@ -559,22 +1431,149 @@ impl Eventful for CollectionDashboard<'_> {
             return Ok(Some(Command::Quit));
         };
 
-        match self.pane_focus {
+        match self.focus() {
             PaneFocus::List => self.handle_list_key_event(key_event),
             PaneFocus::Form => self.handle_form_key_event(key_event),
             PaneFocus::Error => self.handle_error_popup_key_event(key_event),
             PaneFocus::Prompt => self.handle_confirm_popup_key_event(key_event),
+            PaneFocus::Reimport => self.handle_reimport_key_event(key_event),
             PaneFocus::Filter => self.handle_filter_key_event(key_event),
+            PaneFocus::Search => self.handle_search_key_event(key_event),
+            PaneFocus::Import => self.handle_import_key_event(key_event),
+            PaneFocus::ImportSummary => Ok(self.handle_import_summary_key_event(key_event)),
+            PaneFocus::Export => self.handle_export_key_event(key_event),
             PaneFocus::Help => {
-                self.pane_focus = PaneFocus::List;
+                match self.overlay_key_event(key_event, |dashboard| Some(&mut dashboard.help))? {
+                    Some(HelpOverlayEvent::Quit) => Ok(Some(Command::Quit)),
+                    _ => Ok(None),
+                }
+            }
+            PaneFocus::Details => {
+                self.pop_focus();
                 Ok(None)
             }
         }
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Command>> {
+        match self.focus() {
+            PaneFocus::Filter => {
+                self.filter.push_str(&input::single_line(&text));
+                self.filter_list();
+            }
+            PaneFocus::Form => match self.form_state.focused_field {
+                FormFocus::Name => self.form_state.name.push_str(&input::single_line(&text)),
+                FormFocus::Description => self
+                    .form_state
+                    .description
+                    .push_str(&input::single_line(&text)),
+                _ => {}
+            },
+            PaneFocus::Search => {
+                if let Some(search) = self.search.as_mut() {
+                    search.handle_paste(text)?;
+                }
+            }
+            PaneFocus::Import => _ = self.import_prompt.handle_paste(text)?,
+            PaneFocus::Export => _ = self.export_prompt.handle_paste(text)?,
+            // nothing else takes text, and pasting while a popup is open
+            // must not reach the list behind it
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+/// draws `overlay` over `area`, prompts that were already answered are gone
+/// and draw nothing
+fn draw_over<O: Overlay>(
+    overlay: Option<&mut O>,
+    frame: &mut Frame,
+    area: Rect,
+) -> anyhow::Result<()> {
+    overlay.map_or(Ok(()), |overlay| overlay.draw(frame, area))
+}
+
+fn help_sections() -> Vec<HelpSection> {
+    vec![
+        HelpSection::new("navigation")
+            .entry("h/<left>", "select left item")
+            .entry("j/<down>", "select item below")
+            .entry("k/<up>", "select item above")
+            .entry("l/<right>", "select right item")
+            .entry("enter", "opens the selected collection")
+            .entry("v", "shows the collection details")
+            .hint("h/j/k/l", "move", 1),
+        HelpSection::new("collections")
+            .entry("n/c", "creates a new collection")
+            .hint("n", "new", 2)
+            .entry("i", "imports .http, insomnia or collection files, or urls")
+            .entry("r", "re-imports the collection from its url")
+            .entry("E", "exports the collection to share it")
+            .entry("e", "edits the selected collection")
+            .entry("d", "deletes the selected collection")
+            .entry(
+                "y",
+                "duplicates the collection, the way to edit read-only ones",
+            )
+            .entry("p", "pins or unpins the collection"),
+        HelpSection::new("marks")
+            .entry("<space>", "marks or unmarks the collection")
+            .entry("d", "deletes every marked collection")
+            .entry("E", "exports every marked collection to a directory")
+            .entry("<esc>", "clears the marks"),
+        HelpSection::new("list")
+            .entry("s", "changes the sorting")
+            .entry("/", "enter filter mode")
+            .entry("<C-f>", "searches every request")
+            .hint("s", "sort", 1),
+        HelpSection::new("filter")
+            .entry("enter", "applies the filter")
+            .entry("<esc>", "clears the filter")
+            .hint("enter", "apply", 1)
+            .hint("esc", "clear", 1),
+        HelpSection::new("general")
+            .entry("?", "toggle this help window")
+            .entry("u", "dismisses the update notice")
+            .entry("O", "toggles offline mode, nothing is sent while it is on")
+            .entry("<F9>", "lists what is running on the background")
+            .entry("<F12>", "shows the debug logs")
+            .entry("<C-c>", "quits the application")
+            .hint("?", "help", 3)
+            .hint("<C-c>", "quit", 3),
+    ]
+}
+
+/// replaces the home directory on the start of `path` with `~`
+fn shorten_home(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(relative) => format!("~/{}", relative.to_string_lossy()),
+        None => path.to_string_lossy().to_string(),
+    }
+}
+
+/// shown when trying to change a collection that is read-only
+fn read_only_notice(name: &str) -> String {
+    format!("{name} is read-only [y -> duplicate to edit]")
+}
+
+/// how an import ended, as listed on the activity panel
+fn import_outcome(imported: &Result<Imported, FsError>) -> Result<String, String> {
+    match imported {
+        Ok(imported) if imported.collections.len().eq(&1) => Ok(String::from("1 collection")),
+        Ok(imported) => Ok(format!("{} collections", imported.collections.len())),
+        Err(e) => Err(e.to_string()),
+    }
 }
 
 fn build_layout(size: Rect) -> DashboardLayout {
-    let size = Rect::new(size.x + 1, size.y, size.width - 1, size.height);
+    let size = Rect::new(
+        size.x + 1,
+        size.y,
+        size.width.saturating_sub(1),
+        size.height,
+    );
     let [top, help_pane] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Fill(1), Constraint::Length(1)])
@@ -589,29 +1588,42 @@ fn build_layout(size: Rect) -> DashboardLayout {
         ])
         .areas(top);
 
-    let help_popup = Rect::new(
-        size.width.div(2).saturating_sub(25),
-        size.height.div(2).saturating_sub(7),
-        50,
-        14,
-    );
     let confirm_popup = Rect::new(
         size.width.div(4),
         size.height.div(2).saturating_sub(4),
         size.width.div(2),
         8,
-    );
+    )
+    .clamp(size);
     let form_popup = Rect::new(
         size.width.div(4),
         size.height.div(2).saturating_sub(7),
         size.width.div(2),
         14,
-    );
+    )
+    .clamp(size);
     let error_popup = Rect::new(
         size.width.div(4),
         size.height.div(2).saturating_sub(10),
         size.width.div(2),
         20,
+    )
+    .clamp(size);
+    let details_width = u16::min(size.width.saturating_sub(4), 60);
+    let details_popup = Rect::new(
+        size.width.saturating_sub(details_width).div(2),
+        2,
+        details_width,
+        size.height.saturating_sub(4),
+    );
+    // both popups are tall lists of text, so they take the same spot
+    let help_popup = details_popup;
+    let search_width = u16::min(size.width.saturating_sub(4), 70);
+    let search_popup = Rect::new(
+        size.width.saturating_sub(search_width).div(2),
+        2,
+        search_width,
+        size.height.saturating_sub(4),
     );
 
     DashboardLayout {
@@ -622,6 +1634,8 @@ fn build_layout(size: Rect) -> DashboardLayout {
         confirm_popup,
         form_popup,
         error_popup,
+        details_popup,
+        search_popup,
     }
 }
 
@@ -632,6 +1646,7 @@ mod tests {
     use std::{
         fs::{create_dir, File},
         io::Write,
+        time::Duration,
     };
     use tempfile::{tempdir, TempDir};
 
@@ -672,10 +1687,12 @@ mod tests {
             collections_pane: Rect::new(1, 6, 79, 17),
             hint_pane: Rect::new(1, 23, 79, 1),
             title_pane: Rect::new(1, 1, 79, 5),
-            help_popup: Rect::new(14, 5, 50, 14),
+            help_popup: Rect::new(9, 2, 60, 20),
             confirm_popup: Rect::new(19, 8, 39, 8),
             form_popup: Rect::new(19, 5, 39, 14),
             error_popup: Rect::new(19, 2, 39, 20),
+            details_popup: Rect::new(9, 2, 60, 20),
+            search_popup: Rect::new(4, 2, 70, 20),
         };
 
         let layout = build_layout(size);
@@ -683,6 +1700,26 @@ mod tests {
         assert_eq!(layout, expected);
     }
 
+    #[test]
+    fn test_build_layout_with_degenerate_sizes() {
+        for size in [Rect::new(0, 0, 1, 1), Rect::new(0, 0, 200, 5)] {
+            let layout = build_layout(size);
+            for popup in [
+                layout.confirm_popup,
+                layout.form_popup,
+                layout.error_popup,
+                layout.details_popup,
+                layout.search_popup,
+            ] {
+                assert_eq!(
+                    popup.intersection(size),
+                    popup,
+                    "{popup:?} outside {size:?}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_open_close_help() {
         let size = Rect::new(0, 0, 80, 24);
@@ -695,21 +1732,21 @@ mod tests {
         assert_eq!(dashboard.collections.len(), 1);
         assert_eq!(dashboard.list_state.selected(), Some(0));
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
 
         feed_keys(
             &mut dashboard,
             &[KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::Help);
+        assert_eq!(dashboard.focus(), PaneFocus::Help);
 
         feed_keys(
             &mut dashboard,
             &[KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
     }
 
     #[test]
@@ -736,6 +1773,31 @@ mod tests {
         assert_eq!(dashboard.list_state.selected(), None);
     }
 
+    #[test]
+    fn test_pasting_into_the_filter() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(10);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, false).unwrap();
+
+        // nothing takes text on the list
+        dashboard.handle_paste("test_collection_3".into()).unwrap();
+        assert!(dashboard.filter.is_empty());
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE)],
+        );
+        dashboard
+            .handle_paste("test_collection_3\n".into())
+            .unwrap();
+
+        assert_eq!(dashboard.focus(), PaneFocus::Filter);
+        assert_eq!(dashboard.filter, "test_collection_3");
+        assert_eq!(dashboard.list_state.len(), 1);
+    }
+
     #[test]
     fn test_filtering_list() {
         let size = Rect::new(0, 0, 80, 24);
@@ -759,8 +1821,8 @@ mod tests {
             ],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::Filter);
-        assert_eq!(dashboard.list_state.items.len(), 0);
+        assert_eq!(dashboard.focus(), PaneFocus::Filter);
+        assert_eq!(dashboard.list_state.len(), 0);
 
         feed_keys(
             &mut dashboard,
@@ -773,8 +1835,8 @@ mod tests {
             ],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
-        assert_eq!(dashboard.list_state.items.len(), 10);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert_eq!(dashboard.list_state.len(), 10);
 
         feed_keys(
             &mut dashboard,
@@ -787,8 +1849,8 @@ mod tests {
             ],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
-        assert_eq!(dashboard.list_state.items.len(), 10);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert_eq!(dashboard.list_state.len(), 10);
 
         feed_keys(
             &mut dashboard,
@@ -800,8 +1862,8 @@ mod tests {
             ],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
-        assert_eq!(dashboard.list_state.items.len(), 1);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert_eq!(dashboard.list_state.len(), 1);
     }
 
     #[test]
@@ -856,7 +1918,7 @@ mod tests {
             &[KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::Form);
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
 
         feed_keys(
             &mut dashboard,
@@ -875,14 +1937,14 @@ mod tests {
             ],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
 
         feed_keys(
             &mut dashboard,
             &[KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::Form);
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
 
         feed_keys(
             &mut dashboard,
@@ -919,6 +1981,64 @@ mod tests {
         assert_eq!(dashboard.form_state.description, "World");
     }
 
+    #[tokio::test]
+    async fn test_the_form_waits_for_the_collection_to_be_created() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], true).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dashboard.register_command_handler(tx).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let submit = [key(KeyCode::Enter), key(KeyCode::Enter)];
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                key(KeyCode::Char('n')),
+                key(KeyCode::Char('a')),
+                key(KeyCode::Char('p')),
+                key(KeyCode::Char('i')),
+                key(KeyCode::Tab),
+                key(KeyCode::Tab),
+            ],
+        );
+        // pressing enter again while submitting doesn't create another one
+        feed_keys(&mut dashboard, &submit);
+        assert!(dashboard.form_state.submitting);
+        feed_keys(&mut dashboard, &[key(KeyCode::Char('x'))]);
+        assert_eq!(dashboard.form_state.name, "api");
+
+        dashboard.tasks.wait(Duration::from_secs(5)).await;
+        assert!(matches!(rx.try_recv(), Ok(Command::CreateCollection(_))));
+        assert!(rx.try_recv().is_err());
+
+        // a failure leaves the form as it was once the error is dismissed
+        dashboard.display_error("failed to write collection".into());
+        assert!(!dashboard.form_state.submitting);
+        feed_keys(&mut dashboard, &[key(KeyCode::Char('o'))]);
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
+        assert_eq!(dashboard.form_state.name, "api");
+
+        // and only creating it closes the form
+        feed_keys(&mut dashboard, &submit);
+        dashboard.tasks.wait(Duration::from_secs(5)).await;
+        let Ok(Command::CreateCollection(collection)) = rx.try_recv() else {
+            panic!("expected the collection to be created");
+        };
+        assert!(rx.try_recv().is_err());
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
+        dashboard.collection_created(collection);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert!(!dashboard.form_state.submitting);
+        assert!(dashboard.form_state.name.is_empty());
+        assert_eq!(dashboard.collections[0].info.name, "api");
+
+        // errors of anything else still go back to the list
+        dashboard.display_error("failed to delete collection".into());
+        feed_keys(&mut dashboard, &[key(KeyCode::Char('o'))]);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+    }
+
     #[test]
     fn test_prompt_delete_collections() {
         let size = Rect::new(0, 0, 80, 24);
@@ -932,26 +2052,973 @@ mod tests {
             &[KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::Prompt);
+        assert_eq!(dashboard.focus(), PaneFocus::Prompt);
 
         feed_keys(
             &mut dashboard,
             &[KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
     }
 
     #[test]
-    fn test_display_error() {
+    fn test_delete_prompt_buttons() {
         let size = Rect::new(0, 0, 80, 24);
         let colors = hac_colors::Colors::default();
-        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+
+        // `no` is focused by default, so enter and esc both keep the collection
+        for key in [KeyCode::Enter, KeyCode::Esc] {
+            feed_keys(
+                &mut dashboard,
+                &[
+                    KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+                    KeyEvent::new(key, KeyModifiers::NONE),
+                ],
+            );
+            assert_eq!(dashboard.focus(), PaneFocus::List);
+            assert_eq!(dashboard.collections.len(), 3);
+        }
 
-        dashboard.display_error("any error message".into());
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            ],
+        );
+
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert_eq!(dashboard.collections.len(), 2);
+        assert!(dashboard.delete_prompt.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reimporting_asks_how_first() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(1);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dashboard.register_command_handler(tx).unwrap();
+
+        let reimport = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE);
+        feed_keys(&mut dashboard, &[reimport]);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert!(dashboard
+            .notice
+            .as_deref()
+            .is_some_and(|notice| notice.ends_with("was not imported from a url")));
+
+        dashboard.collections[0].info.source = Some("http://127.0.0.1:9/users.http".into());
+        feed_keys(
+            &mut dashboard,
+            &[
+                reimport,
+                KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert!(dashboard.reimport_prompt.is_none());
+
+        // nothing is fetched while offline, which is told like any failure
+        dashboard.set_offline(true);
+        feed_keys(&mut dashboard, &[reimport]);
+        assert_eq!(dashboard.focus(), PaneFocus::Reimport);
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        let Some(Command::Error(report)) = rx.recv().await else {
+            panic!("expected the fetch to fail");
+        };
+        assert!(report.message.contains("offline mode"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_collections_are_duplicated_to_edit() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(0);
+        std::fs::write(
+            PathBuf::from(&path).join("shared.json"),
+            r#"{"info": { "name": "shared", "source": "http://127.0.0.1:9/shared.http" }, "read_only": true}"#,
+        )
+        .unwrap();
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dashboard.register_command_handler(tx).unwrap();
+        assert!(dashboard.list_state.selected_item().unwrap().read_only);
+
+        for key in ['e', 'd'] {
+            feed_keys(
+                &mut dashboard,
+                &[KeyEvent::new(KeyCode::Char(key), KeyModifiers::NONE)],
+            );
+            assert_eq!(dashboard.focus(), PaneFocus::List);
+            assert_eq!(
+                dashboard.notice.as_deref(),
+                Some("shared is read-only [y -> duplicate to edit]")
+            );
+        }
+
+        // marking it doesn't get around it either
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert!(dashboard.delete_prompt.is_none());
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)],
+        );
+
+        // it can still be re-imported, but only as a copy
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Reimport);
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
+            ],
+        );
+        let Some(Command::CreateCollection(copy)) = rx.recv().await else {
+            panic!("expected the duplicated collection");
+        };
+        assert!(copy.info.name.starts_with("shared (copy"));
+        assert!(!copy.is_read_only());
+
+        dashboard.collection_created(copy);
+        assert_eq!(dashboard.collections.len(), 2);
+    }
+
+    #[test]
+    fn test_deleting_keeps_a_collection_selected() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let delete = [
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
+        ];
+
+        // the next collection takes the place of the deleted one
+        let second = dashboard.collections[1].path.clone();
+        feed_keys(&mut dashboard, &delete);
+        assert_eq!(dashboard.collections.len(), 2);
+        assert_eq!(dashboard.list_state.selected(), Some(0));
+        assert_eq!(dashboard.list_state.selected_item().unwrap().path, second);
+
+        // deleting the last one selects the one before it
+        dashboard.list_state.select(Some(1));
+        feed_keys(&mut dashboard, &delete);
+        assert_eq!(dashboard.collections.len(), 1);
+        assert_eq!(dashboard.list_state.selected_item().unwrap().path, second);
+
+        // and deleting the only one leaves nothing to select
+        feed_keys(&mut dashboard, &delete);
+        assert!(dashboard.collections.is_empty());
+        assert_eq!(dashboard.list_state.selected(), None);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+
+        // on an empty list there is nothing to ask about
+        feed_keys(&mut dashboard, &delete);
+        assert!(dashboard.delete_prompt.is_none());
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+    }
+
+    #[test]
+    fn test_delete_without_a_selection_does_nothing() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(2);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+
+        // a filter matching nothing leaves nothing to delete
+        dashboard.filter = "nothing matches this".into();
+        dashboard.filter_list();
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)],
+        );
+        assert!(dashboard.delete_prompt.is_none());
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+
+        // a prompt left open after the selection went away is just closed
+        dashboard.filter = String::new();
+        dashboard.filter_list();
+        dashboard.delete_prompt = Some(ConfirmPopup::yes_no("delete?".into(), &colors));
+        dashboard.push_focus(PaneFocus::Prompt);
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.collections.len(), 2);
+        assert!(dashboard.delete_prompt.is_none());
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+    }
+
+    #[test]
+    fn test_deleting_marked_collections_hidden_by_the_filter() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(4);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        // marks the first and the third collections
+        feed_keys(
+            &mut dashboard,
+            &[
+                key(KeyCode::Char(' ')),
+                key(KeyCode::Char('l')),
+                key(KeyCode::Char('l')),
+                key(KeyCode::Char(' ')),
+            ],
+        );
+        assert_eq!(dashboard.list_state.marked_count(), 2);
+
+        // and the second while the others are filtered out
+        feed_keys(
+            &mut dashboard,
+            &[
+                key(KeyCode::Char('/')),
+                key(KeyCode::Char('1')),
+                key(KeyCode::Enter),
+                key(KeyCode::Char('l')),
+                key(KeyCode::Char(' ')),
+            ],
+        );
+        assert_eq!(dashboard.list_state.len(), 1);
+        assert_eq!(dashboard.list_state.marked_count(), 3);
+        // hidden marks are deleted as well, and the prompt says so
+        assert_eq!(
+            dashboard.hidden_marks_note(),
+            " 2 of them are hidden by the filter"
+        );
+
+        feed_keys(&mut dashboard, &[key(KeyCode::Char('d'))]);
+        assert_eq!(dashboard.focus(), PaneFocus::Prompt);
+        feed_keys(&mut dashboard, &[key(KeyCode::Char('y'))]);
+
+        let names = dashboard
+            .collections
+            .iter()
+            .map(|collection| collection.info.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["test_collection_3"]);
+        assert_eq!(dashboard.list_state.marked_count(), 0);
+        // the filter is kept, and nothing left matches it
+        assert!(dashboard.list_state.is_empty());
+        assert_eq!(dashboard.list_state.selected(), None);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+    }
+
+    #[test]
+    fn test_esc_clears_marks_before_the_filter() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                key(KeyCode::Char(' ')),
+                key(KeyCode::Char('/')),
+                key(KeyCode::Char('2')),
+                key(KeyCode::Enter),
+                key(KeyCode::Esc),
+            ],
+        );
+        assert_eq!(dashboard.list_state.marked_count(), 0);
+        assert_eq!(dashboard.filter, "2");
+
+        // without marks, a single collection is asked about
+        feed_keys(
+            &mut dashboard,
+            &[key(KeyCode::Char('l')), key(KeyCode::Char('d'))],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Prompt);
+        feed_keys(&mut dashboard, &[key(KeyCode::Char('y'))]);
+        assert_eq!(dashboard.collections.len(), 2);
+
+        // unmarking leaves nothing marked
+        feed_keys(
+            &mut dashboard,
+            &[
+                key(KeyCode::Esc),
+                key(KeyCode::Char(' ')),
+                key(KeyCode::Char(' ')),
+            ],
+        );
+        assert!(dashboard.filter.is_empty());
+        assert_eq!(dashboard.list_state.marked_count(), 0);
+    }
+
+    #[test]
+    fn test_keys_while_loading() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
+        dashboard.set_loading();
+
+        for code in [
+            KeyCode::Enter,
+            KeyCode::Char('j'),
+            KeyCode::Char('l'),
+            KeyCode::Char('d'),
+        ] {
+            let result = dashboard.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+            assert!(result.unwrap().is_none());
+            assert_eq!(dashboard.focus(), PaneFocus::List);
+        }
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
+    }
+
+    #[test]
+    fn test_set_collections_after_loading() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
+        dashboard.set_loading();
+
+        dashboard.set_collections(collections);
+
+        assert!(!dashboard.loading);
+        assert_eq!(dashboard.list_state.len(), 3);
+        assert_eq!(dashboard.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_shorten_home() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            shorten_home(Path::new("/home/user/.local/share/hac"), Some(home)),
+            "~/.local/share/hac"
+        );
+        assert_eq!(
+            shorten_home(Path::new("/data/hac"), Some(home)),
+            "/data/hac"
+        );
+        assert_eq!(shorten_home(Path::new("/data/hac"), None), "/data/hac");
+    }
+
+    #[test]
+    fn test_esc_clears_the_filter() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, false).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            ],
+        );
+        assert!(dashboard.list_state.is_empty());
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.filter, "");
+        assert_eq!(dashboard.list_state.len(), 3);
+        assert_eq!(dashboard.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_sorting_is_cycled_and_applied_to_new_collections() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE),
+            ],
+        );
+
+        assert_eq!(
+            dashboard.app_state.collection_sort,
+            CollectionSort::RecentlyOpened
+        );
+        assert_eq!(dashboard.collections[0].info.name, "test_collection_2");
+
+        let mut collection = dashboard.collections[0].clone();
+        collection.info.name = "a_new_collection".into();
+        collection.path = "a_new_collection.json".into();
+        dashboard.add_collection(collection);
+
+        let names = dashboard
+            .collections
+            .iter()
+            .map(|collection| collection.info.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "a_new_collection",
+                "test_collection_2",
+                "test_collection_0",
+                "test_collection_1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dismissing_the_update_notice() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], true).unwrap();
+        assert_eq!(dashboard.update_notice(), None);
+
+        dashboard.set_available_update("0.3.0".into());
+        assert_eq!(dashboard.update_notice(), Some("0.3.0"));
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.update_notice(), None);
+        assert_eq!(
+            dashboard.app_state.dismissed_update.as_deref(),
+            Some("0.3.0")
+        );
+
+        // only the dismissed release is hidden
+        dashboard.set_available_update("0.4.0".into());
+        assert_eq!(dashboard.update_notice(), Some("0.4.0"));
+    }
+
+    #[test]
+    fn test_pinning_keeps_the_selection() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        dashboard.sort_collections();
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE),
+            ],
+        );
+
+        let selected = dashboard.list_state.selected_item().unwrap();
+        assert_eq!(dashboard.list_state.selected(), Some(0));
+        assert_eq!(selected.name, "test_collection_2");
+        assert!(selected.pinned);
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)],
+        );
+
+        let selected = dashboard.list_state.selected_item().unwrap();
+        assert_eq!(dashboard.list_state.selected(), Some(2));
+        assert_eq!(selected.name, "test_collection_2");
+        assert!(!selected.pinned);
+    }
+
+    #[tokio::test]
+    async fn test_editing_a_collection() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, false).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dashboard.register_command_handler(tx).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE),
+            ],
+        );
+
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
+        assert_eq!(dashboard.form_state.name, "test_collection_1");
+        assert_eq!(dashboard.form_state.description, "test_description_1!");
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert_eq!(dashboard.form_state.editing, None);
+
+        let Some(Command::UpdateCollection(collection)) = rx.recv().await else {
+            panic!("expected the edited collection");
+        };
+        let on_disk = std::fs::read_to_string(&collection.path).unwrap();
+        assert!(on_disk.contains("test_description_1!"));
+
+        dashboard.update_collection(collection);
+        let item = dashboard.list_state.selected_item().unwrap();
+        assert_eq!(dashboard.list_state.selected(), Some(1));
+        assert_eq!(item.description, "test_description_1!");
+    }
+
+    #[tokio::test]
+    async fn test_importing_a_http_file() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("users.http");
+        std::fs::write(
+            &file,
+            "GET https://example.com/users\n\n###\nDELETE https://example.com/users/1\n",
+        )
+        .unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], true).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dashboard.register_command_handler(tx).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Import);
+
+        dashboard
+            .handle_paste(file.to_string_lossy().to_string())
+            .unwrap();
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+
+        let Some(Command::CreateCollection(collection)) = rx.recv().await else {
+            panic!("expected the imported collection");
+        };
+        // an existing `users` collection would get the imported suffix
+        assert!(collection.info.name.starts_with("users"));
+        assert_eq!(collection.flatten_requests().len(), 2);
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE)],
+        );
+        dashboard
+            .handle_paste(
+                dir.path()
+                    .join("missing.http")
+                    .to_string_lossy()
+                    .to_string(),
+            )
+            .unwrap();
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)],
+        );
+        assert!(matches!(rx.recv().await, Some(Command::Error(_))));
+    }
+
+    #[tokio::test]
+    async fn test_importing_an_insomnia_export_shows_a_summary() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("export.json");
+        std::fs::write(
+            &file,
+            r#"{
+                "_type": "export",
+                "__export_format": 4,
+                "resources": [
+                    { "_id": "wrk_1", "_type": "workspace", "name": "insomnia_api" },
+                    {
+                        "_id": "req_1",
+                        "_type": "request",
+                        "parentId": "wrk_1",
+                        "name": "users",
+                        "method": "GET",
+                        "url": "{{ _.host }}/users"
+                    },
+                    { "_id": "jar_1", "_type": "cookie_jar", "parentId": "wrk_1", "name": "Jar" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], true).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dashboard.register_command_handler(tx).unwrap();
+
+        dashboard.import_collection(file.to_string_lossy().to_string());
+        let Some(Command::CollectionsImported {
+            collections,
+            skipped,
+        }) = rx.recv().await
+        else {
+            panic!("expected the imported collections");
+        };
+        assert_eq!(collections.len(), 1);
+        assert_eq!(
+            collections[0].flatten_requests()[0].read().unwrap().uri,
+            "{{host}}/users"
+        );
+        assert_eq!(skipped, vec!["cookie jar \"Jar\""]);
+
+        dashboard.display_import_summary(&collections, skipped);
+        assert_eq!(dashboard.focus(), PaneFocus::ImportSummary);
+        assert_eq!(dashboard.import_report.imported[0].1, 1);
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+    }
+
+    #[test]
+    fn test_exporting_a_collection() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (guard, path) = setup_temp_collections(2);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, false).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('E'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Export);
+
+        let file = guard.path().join("shared.json");
+        dashboard.export_collection(file.clone(), false);
+        assert_eq!(
+            dashboard.notice,
+            Some(format!("exported to {}", file.to_string_lossy()))
+        );
+        let exported =
+            hac_core::import::collection_file::parse(&std::fs::read_to_string(&file).unwrap())
+                .unwrap();
+        assert_eq!(exported.info.name, "test_collection_0");
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert_eq!(dashboard.notice, None);
+
+        dashboard.export_collection(guard.path().join("missing/shared.json"), false);
+        assert_eq!(dashboard.focus(), PaneFocus::Error);
+    }
+
+    #[test]
+    fn test_exporting_marked_collections() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, false).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                key(KeyCode::Char(' ')),
+                key(KeyCode::Char('l')),
+                key(KeyCode::Char('l')),
+                key(KeyCode::Char(' ')),
+                key(KeyCode::Char('E')),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Export);
+
+        let dir = guard.path().join("shared");
+        dashboard.export_marked(dir.clone(), false);
+        assert_eq!(
+            dashboard.notice,
+            Some(format!(
+                "exported 2 collections to {}",
+                dir.to_string_lossy()
+            ))
+        );
+        let mut exported = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        exported.sort();
+        assert_eq!(
+            exported,
+            ["test_collection_0.json", "test_collection_2.json"]
+        );
+        // the marks are kept for whatever comes next
+        assert_eq!(dashboard.list_state.marked_count(), 2);
+    }
+
+    #[test]
+    fn test_edits_only_reorder_when_the_sort_key_changes() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let names = |dashboard: &CollectionDashboard| {
+            dashboard
+                .collections
+                .iter()
+                .map(|collection| collection.info.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut collection = dashboard.collections[0].clone();
+        collection.info.description = Some("first\nsecond".into());
+        dashboard.update_collection(collection.clone());
+        assert_eq!(
+            names(&dashboard),
+            [
+                "test_collection_0",
+                "test_collection_1",
+                "test_collection_2"
+            ]
+        );
+
+        collection.info.name = "z_collection".into();
+        dashboard.update_collection(collection);
+        assert_eq!(
+            names(&dashboard),
+            ["test_collection_1", "test_collection_2", "z_collection"]
+        );
+        assert_eq!(dashboard.list_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_searching_opens_the_matched_request() {
+        use hac_core::collection::types::{Info, Request, RequestKind, RequestMethod};
+        use std::sync::{Arc, RwLock};
+
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let request = Request {
+            id: "request_id".into(),
+            method: RequestMethod::Get,
+            name: "list users".into(),
+            uri: "http://localhost/users".into(),
+            ..Default::default()
+        };
+        let collection = Collection {
+            info: Info {
+                name: "users".into(),
+                ..Default::default()
+            },
+            requests: Some(Arc::new(RwLock::new(vec![RequestKind::Single(Arc::new(
+                RwLock::new(request),
+            ))]))),
+            path: "users.json".into(),
+            ..Default::default()
+        };
+        let mut dashboard =
+            CollectionDashboard::new(size, &colors, vec![collection], true).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[
+                KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE),
+            ],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Search);
+
+        let command = dashboard
+            .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+        let Some(Command::OpenRequest {
+            collection,
+            request_id,
+        }) = command
+        else {
+            panic!("expected the request to be opened");
+        };
+        assert_eq!(collection.info.name, "users");
+        assert_eq!(request_id, "request_id");
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert!(dashboard.search.is_none());
+    }
+
+    #[test]
+    fn test_details_popup() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+
+        let (_guard, path) = setup_temp_collections(1);
+        dashboard.set_collections(collection::collection::get_collections(path).unwrap());
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Details);
+
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+    }
+
+    #[test]
+    fn test_display_error() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
+
+        dashboard.display_error("any error message".into());
+
+        assert_eq!(dashboard.focus(), PaneFocus::Error);
+        assert_eq!(dashboard.error, ErrorReport::from("any error message"));
+    }
+
+    #[test]
+    fn test_popups_stack_over_what_is_focused() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(1);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        // keys only reach the filter once the error over it is dismissed
+        feed_keys(&mut dashboard, &[key('/'), key('a')]);
+        dashboard.display_error("any error message".into());
+        feed_keys(&mut dashboard, &[key('b')]);
+        assert_eq!(dashboard.focus(), PaneFocus::Error);
+        feed_keys(&mut dashboard, &[key('o'), key('c')]);
+        assert_eq!(dashboard.focus(), PaneFocus::Filter);
+        assert_eq!(dashboard.filter, "ac");
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+
+        // a failed submit leaves the form open under the error
+        feed_keys(&mut dashboard, &[key('n')]);
+        dashboard.form_state.submitting = true;
+        dashboard.display_error("already exists".into());
+        assert_eq!(dashboard.focus_stack, [PaneFocus::Form, PaneFocus::Error]);
+        feed_keys(
+            &mut dashboard,
+            &[KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)],
+        );
+        assert_eq!(dashboard.focus(), PaneFocus::Form);
+        assert!(!dashboard.form_state.submitting);
+        dashboard.pop_focus();
+
+        // whatever finishes in the background opens over the help
+        feed_keys(&mut dashboard, &[key('?')]);
+        dashboard.display_import_summary(&[], vec!["cookie jar".into()]);
+        dashboard.display_import_summary(&[], vec!["cookie jar".into()]);
+        assert_eq!(
+            dashboard.focus_stack,
+            [PaneFocus::Help, PaneFocus::ImportSummary]
+        );
+        feed_keys(&mut dashboard, &[key('o')]);
+        assert_eq!(dashboard.focus(), PaneFocus::Help);
+        feed_keys(&mut dashboard, &[key('q')]);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
+        assert!(dashboard.focus_stack.is_empty());
+    }
+
+    #[test]
+    fn test_help_takes_the_keys_over_a_prompt_over_the_filter() {
+        let size = Rect::new(0, 0, 80, 24);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(2);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let mut dashboard = CollectionDashboard::new(size, &colors, collections, true).unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        feed_keys(&mut dashboard, &[key('/'), key('c')]);
+        dashboard.delete_prompt = Some(ConfirmPopup::yes_no("delete?".into(), &colors));
+        dashboard.push_focus(PaneFocus::Prompt);
+        dashboard.help.reset();
+        dashboard.push_focus(PaneFocus::Help);
+        assert_eq!(
+            dashboard.focus_stack,
+            [PaneFocus::Filter, PaneFocus::Prompt, PaneFocus::Help]
+        );
+
+        // the answer only closes the help, nothing is deleted
+        feed_keys(&mut dashboard, &[key('y')]);
+        assert_eq!(dashboard.focus(), PaneFocus::Prompt);
+        assert!(dashboard.delete_prompt.is_some());
+        assert_eq!(dashboard.collections.len(), 2);
+
+        feed_keys(&mut dashboard, &[key('n')]);
+        assert_eq!(dashboard.focus(), PaneFocus::Filter);
+        assert!(dashboard.delete_prompt.is_none());
+        assert_eq!(dashboard.collections.len(), 2);
+        assert_eq!(dashboard.filter, "c");
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::Error);
-        assert_eq!(dashboard.error_message, "any error message");
+        feed_keys(&mut dashboard, &[key('y')]);
+        assert_eq!(dashboard.filter, "cy");
     }
 
     #[test]
@@ -983,13 +3050,13 @@ mod tests {
         let mut dashboard = CollectionDashboard::new(size, &colors, collections, false).unwrap();
 
         dashboard.display_error("any_error_message".into());
-        assert_eq!(dashboard.pane_focus, PaneFocus::Error);
+        assert_eq!(dashboard.focus(), PaneFocus::Error);
         feed_keys(
             &mut dashboard,
             &[KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE)],
         );
 
-        assert_eq!(dashboard.pane_focus, PaneFocus::List);
+        assert_eq!(dashboard.focus(), PaneFocus::List);
     }
 
     #[test]
@@ -1004,10 +3071,12 @@ mod tests {
             collections_pane: Rect::new(1, 6, 79, 17),
             hint_pane: Rect::new(1, 23, 79, 1),
             title_pane: Rect::new(1, 1, 79, 5),
-            help_popup: Rect::new(14, 5, 50, 14),
+            help_popup: Rect::new(9, 2, 60, 20),
             confirm_popup: Rect::new(19, 8, 39, 8),
             form_popup: Rect::new(19, 5, 39, 14),
             error_popup: Rect::new(19, 2, 39, 20),
+            details_popup: Rect::new(9, 2, 60, 20),
+            search_popup: Rect::new(4, 2, 70, 20),
         };
 
         dashboard.resize(new_size);
@@ -0,0 +1,208 @@
+use crate::pages::collection_dashboard::collection_list::{format_time_ago, CollectionListItem};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Widget, Wrap};
+
+/// everything we know about a collection without opening it, for when the
+/// card is too small to tell
+pub struct CollectionDetails<'a> {
+    item: &'a CollectionListItem,
+    colors: &'a hac_colors::Colors,
+}
+
+impl<'a> CollectionDetails<'a> {
+    pub fn new(item: &'a CollectionListItem, colors: &'a hac_colors::Colors) -> Self {
+        CollectionDetails { item, colors }
+    }
+
+    fn build_lines(&self) -> Vec<Line<'a>> {
+        let mut lines = vec![self
+            .item
+            .name
+            .as_str()
+            .fg(self.colors.normal.white)
+            .bold()
+            .into()];
+        lines.push(Line::default());
+
+        match self.item.description.is_empty() {
+            true => lines.push("no description".fg(self.colors.bright.black).into()),
+            false => lines.extend(
+                self.item
+                    .description
+                    .lines()
+                    .map(|line| Line::from(line.fg(self.colors.bright.yellow))),
+            ),
+        }
+        lines.push(Line::default());
+
+        let now = SystemTime::now();
+        let timestamp = |time: Option<SystemTime>| match time {
+            Some(time) => format!(
+                "{} ({})",
+                format_timestamp(time),
+                format_time_ago(time, now)
+            ),
+            None => String::from("unknown"),
+        };
+        let source = self.item.source.clone().map(|url| ("source    ", url));
+        let read_only = self.item.read_only.then(|| {
+            (
+                "access    ",
+                String::from("read-only, duplicate it to edit"),
+            )
+        });
+        let fields = [
+            ("path      ", self.item.path.to_string_lossy().to_string()),
+            ("requests  ", self.item.requests.to_string()),
+            ("created   ", timestamp(self.item.created_at)),
+            ("modified  ", timestamp(self.item.modified_at)),
+        ];
+        lines.extend(
+            fields
+                .into_iter()
+                .chain(source)
+                .chain(read_only)
+                .map(|(label, value)| {
+                    Line::from(vec![
+                        label.fg(self.colors.bright.black),
+                        value.fg(self.colors.normal.white),
+                    ])
+                }),
+        );
+
+        let hint = Line::from("press any key to go back".fg(self.colors.normal.magenta));
+        lines.push(Line::default());
+        lines.push(hint.centered());
+        lines
+    }
+}
+
+impl Widget for CollectionDetails<'_> {
+    fn render(self, size: Rect, buf: &mut Buffer) {
+        Clear.render(size, buf);
+        Paragraph::new(self.build_lines())
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(self.colors.bright.black))
+                    .padding(Padding::new(2, 2, 1, 1))
+                    .bg(self.colors.normal.black),
+            )
+            .render(size, buf);
+    }
+}
+
+/// formats `time` as `YYYY-MM-DD HH:MM UTC`, there is no timezone database
+/// around so we don't try to be local
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // days since the epoch to a civil date, following howard hinnant's
+    // `civil_from_days`
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn make_item(description: &str) -> CollectionListItem {
+        CollectionListItem {
+            name: "users api".into(),
+            description: description.into(),
+            requests: 4,
+            modified: None,
+            created_at: None,
+            modified_at: None,
+            path: "/collections/users_api.json".into(),
+            source: None,
+            pinned: false,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let at = |secs: u64| format_timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+
+        assert_eq!(at(0), "1970-01-01 00:00 UTC");
+        assert_eq!(at(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(at(1_700_000_000), "2023-11-14 22:13 UTC");
+    }
+
+    #[test]
+    fn test_description_lines_are_kept() {
+        let colors = hac_colors::Colors::default();
+        let item = make_item("first line\nsecond line");
+        let lines = CollectionDetails::new(&item, &colors).build_lines();
+        let lines = lines.iter().map(Line::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            [
+                "users api",
+                "",
+                "first line",
+                "second line",
+                "",
+                "path      /collections/users_api.json",
+                "requests  4",
+                "created   unknown",
+                "modified  unknown",
+                "",
+                "press any key to go back",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_description() {
+        let colors = hac_colors::Colors::default();
+        let item = make_item("");
+        let lines = CollectionDetails::new(&item, &colors).build_lines();
+
+        assert_eq!(lines[2].to_string(), "no description");
+    }
+
+    #[test]
+    fn test_imported_collections_show_their_source() {
+        let colors = hac_colors::Colors::default();
+        let item = CollectionListItem {
+            source: Some("https://example.com/users.http".into()),
+            ..make_item("")
+        };
+        let lines = CollectionDetails::new(&item, &colors).build_lines();
+
+        assert_eq!(
+            lines[8].to_string(),
+            "source    https://example.com/users.http"
+        );
+    }
+}
@@ -1,30 +1,92 @@
+use crate::pages::scrollbar::Scrollbar;
+
+use hac_core::app_state::AppState;
 use hac_core::collection::Collection;
+use hac_core::search;
 
-use std::collections::VecDeque;
-use std::ops::{Add, Div, Mul};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::{Div, Mul, Range};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use ratatui::buffer::Buffer;
-use ratatui::layout::{Constraint, Direction, Flex, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Flex, Layout, Rect};
 use ratatui::style::{Style, Stylize};
-use ratatui::widgets::{
-    Block, BorderType, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
-    StatefulWidget, Widget,
-};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph, StatefulWidget, Widget};
+
+/// below this inner width a card only shows the name and the metadata
+const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+/// what a card on the list needs to be drawn, built once per collection so
+/// drawing and filtering never touch the requests of a collection
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionListItem {
+    pub name: String,
+    pub description: String,
+    pub requests: usize,
+    /// how long ago the collection file was changed, eg: `2 days ago`
+    pub modified: Option<String>,
+    /// not every filesystem keeps a creation time, so either of these can
+    /// be missing
+    pub created_at: Option<SystemTime>,
+    pub modified_at: Option<SystemTime>,
+    pub path: PathBuf,
+    /// the url the collection was imported from
+    pub source: Option<String>,
+    pub pinned: bool,
+    /// either flagged on the file or living on a read-only directory
+    pub read_only: bool,
+}
+
+impl From<&Collection> for CollectionListItem {
+    fn from(collection: &Collection) -> Self {
+        let metadata = std::fs::metadata(&collection.path).ok();
+        let created_at = metadata.as_ref().and_then(|m| m.created().ok());
+        let modified_at = metadata.as_ref().and_then(|m| m.modified().ok());
+
+        CollectionListItem {
+            name: collection.info.name.clone(),
+            description: collection.info.description.clone().unwrap_or_default(),
+            requests: collection.flatten_requests().len(),
+            modified: modified_at.map(|modified| format_time_ago(modified, SystemTime::now())),
+            created_at,
+            modified_at,
+            path: collection.path.clone(),
+            source: collection.info.source.clone(),
+            pinned: false,
+            read_only: collection.is_read_only(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct CollectionListState {
+    /// index into `visible`, not into `items`
     selected: Option<usize>,
-    pub(super) items: Vec<Collection>,
+    items: Vec<CollectionListItem>,
+    /// indexes into `items` of the collections matching the current filter
+    visible: Vec<usize>,
+    /// first row on display, rows are counted as laid out by `row_range`
     scroll: usize,
+    /// paths of the collections marked for a bulk action, kept by path so
+    /// marks survive filtering and sorting
+    marked: HashSet<PathBuf>,
 }
 
 impl CollectionListState {
-    pub fn new(items: Vec<Collection>) -> Self {
-        CollectionListState {
+    pub fn new(collections: &[Collection]) -> Self {
+        let mut state = CollectionListState {
             selected: None,
-            items,
+            items: vec![],
+            visible: vec![],
             scroll: 0,
-        }
+            marked: HashSet::new(),
+        };
+        state.set_items(collections, &AppState::default());
+        state
     }
 
     pub fn select(&mut self, index: Option<usize>) {
@@ -35,8 +97,170 @@ impl CollectionListState {
         self.selected
     }
 
-    pub fn set_items(&mut self, items: Vec<Collection>) {
-        self.items = items;
+    /// index of the selected collection on the list it was built from,
+    /// regardless of any filter being applied
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected.and_then(|idx| self.visible.get(idx).copied())
+    }
+
+    pub fn selected_item(&self) -> Option<&CollectionListItem> {
+        self.selected_index().and_then(|idx| self.items.get(idx))
+    }
+
+    /// amount of collections currently displayed
+    pub fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visible.is_empty()
+    }
+
+    /// `collections` are expected to be sorted by `app_state`, so pinned
+    /// collections come first
+    pub fn set_items(&mut self, collections: &[Collection], app_state: &AppState) {
+        self.items = collections
+            .iter()
+            .map(|collection| CollectionListItem {
+                pinned: app_state.is_pinned(&collection.path),
+                ..CollectionListItem::from(collection)
+            })
+            .collect();
+        self.visible = (0..self.items.len()).collect();
+        self.scroll = 0;
+        // collections that went away can't be acted upon anymore
+        let items = &self.items;
+        self.marked
+            .retain(|path| items.iter().any(|item| item.path.eq(path)));
+    }
+
+    /// rebuilds the item at `index` on the list it was built from, keeping its
+    /// place and every filter as they are
+    pub fn refresh_item(&mut self, index: usize, collection: &Collection, app_state: &AppState) {
+        if let Some(item) = self.items.get_mut(index) {
+            *item = CollectionListItem {
+                pinned: app_state.is_pinned(&collection.path),
+                ..CollectionListItem::from(collection)
+            };
+        }
+    }
+
+    /// position on the visible list of the collection at `path`
+    pub fn position_of(&self, path: &Path) -> Option<usize> {
+        self.visible
+            .iter()
+            .position(|idx| self.items[*idx].path.eq(path))
+    }
+
+    fn pinned_count(&self) -> usize {
+        self.visible
+            .iter()
+            .take_while(|idx| self.items[**idx].pinned)
+            .count()
+    }
+
+    /// pinned collections fill the first rows, and the rest of the list
+    /// starts on a row of its own
+    fn total_rows(&self, per_row: usize) -> usize {
+        let pinned = self.pinned_count();
+        pinned.div_ceil(per_row) + (self.len() - pinned).div_ceil(per_row)
+    }
+
+    /// the visible positions laid out on `row`, empty past the last row
+    fn row_range(&self, row: usize, per_row: usize) -> Range<usize> {
+        let pinned = self.pinned_count();
+        let pinned_rows = pinned.div_ceil(per_row);
+        let (start, end) = match row.lt(&pinned_rows) {
+            true => (row * per_row, pinned),
+            false => (pinned + (row - pinned_rows) * per_row, self.len()),
+        };
+        let start = usize::min(start, end);
+        start..usize::min(start + per_row, end)
+    }
+
+    fn row_of(&self, index: usize, per_row: usize) -> usize {
+        let pinned = self.pinned_count();
+        match index.lt(&pinned) {
+            true => index / per_row,
+            false => pinned.div_ceil(per_row) + (index - pinned) / per_row,
+        }
+    }
+
+    /// moves the selection `rows` rows up or down keeping the column when
+    /// possible, so moving across the pinned section never gets stuck on a
+    /// shorter row
+    pub fn select_row(&mut self, rows: isize, per_row: usize) {
+        if self.is_empty() {
+            return;
+        }
+
+        let Some(selected) = self.selected else {
+            self.selected = Some(0);
+            return;
+        };
+
+        let per_row = usize::max(per_row, 1);
+        let row = self.row_of(selected, per_row);
+        let column = selected - self.row_range(row, per_row).start;
+        let target = row
+            .saturating_add_signed(rows)
+            .min(self.total_rows(per_row) - 1);
+        let range = self.row_range(target, per_row);
+        self.selected = Some(usize::min(range.start + column, range.end - 1));
+    }
+
+    /// marks the selected collection, or unmarks it when it already was
+    pub fn toggle_mark(&mut self) {
+        let Some(path) = self.selected_item().map(|item| item.path.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+    }
+
+    pub fn is_marked(&self, item: &CollectionListItem) -> bool {
+        self.marked.contains(&item.path)
+    }
+
+    pub fn marked_count(&self) -> usize {
+        self.marked.len()
+    }
+
+    /// every marked collection in the order they are listed, the ones the
+    /// current filter hides included
+    pub fn marked_items(&self) -> Vec<&CollectionListItem> {
+        self.items
+            .iter()
+            .filter(|item| self.is_marked(item))
+            .collect()
+    }
+
+    /// how many of the marked collections the current filter hides
+    pub fn hidden_marks(&self) -> usize {
+        self.marked_count()
+            - self
+                .visible
+                .iter()
+                .filter(|idx| self.is_marked(&self.items[**idx]))
+                .count()
+    }
+
+    /// only displays collections whose name contains `filter`, ignoring case
+    pub fn filter(&mut self, filter: &str) {
+        self.visible.clear();
+        self.visible.extend(
+            self.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| search::matches(&item.name, filter))
+                .map(|(idx, _)| idx),
+        );
+        self.scroll = 0;
     }
 }
 
@@ -64,9 +288,10 @@ impl<'a> CollectionList<'a> {
         (size.height.div(self.row_height)).into()
     }
 
-    fn build_layout(&self, size: &Rect) -> VecDeque<Rect> {
+    fn build_layout(&self, size: &Rect) -> Vec<Rect> {
         let total_rows = self.total_rows(size);
-        let items_per_row = self.items_per_row(size);
+        // a single card still fits screens narrower than a column
+        let items_per_row = usize::max(self.items_per_row(size), 1);
 
         (0..total_rows)
             .flat_map(|row| {
@@ -82,93 +307,176 @@ impl<'a> CollectionList<'a> {
                     ))
                     .to_vec()
             })
-            .collect::<VecDeque<_>>()
+            .collect::<Vec<_>>()
     }
 
-    fn build_card(
+    /// cards narrower than their content drop the description first and the
+    /// metadata after, names and descriptions are cut with an ellipsis
+    fn build_card<'b>(
         &self,
-        state: &CollectionListState,
-        collection: &Collection,
-        index: usize,
-    ) -> Paragraph<'_> {
-        let lines = vec![
-            collection
-                .info
-                .name
-                .clone()
-                .fg(self.colors.normal.white)
-                .into(),
-            collection
-                .info
-                .description
-                .clone()
-                .unwrap_or_default()
-                .fg(self.colors.bright.yellow)
-                .into(),
-        ];
+        item: &'b CollectionListItem,
+        selected: bool,
+        marked: bool,
+        width: u16,
+    ) -> Paragraph<'b> {
+        let inner_width = usize::from(width.saturating_sub(2));
+        let name = match marked {
+            true => Line::from(vec![
+                Span::styled("● ", Style::default().fg(self.colors.normal.green)),
+                Span::styled(
+                    truncate(&item.name, inner_width.saturating_sub(2)),
+                    Style::default().fg(self.colors.normal.white),
+                ),
+            ]),
+            false => Line::from(Span::styled(
+                truncate(&item.name, inner_width),
+                Style::default().fg(self.colors.normal.white),
+            )),
+        };
+        let mut lines = vec![name];
+        if inner_width.ge(&MIN_DESCRIPTION_WIDTH) {
+            lines.push(Line::from(Span::styled(
+                description_preview(&item.description, inner_width),
+                Style::default().fg(self.colors.bright.yellow),
+            )));
+        }
 
-        let border_color = if state
-            .selected
-            .is_some_and(|selected| selected.eq(&(index.add(state.scroll))))
-        {
-            self.colors.bright.magenta
-        } else {
-            self.colors.primary.hover
+        let border_style = match (selected, marked) {
+            (true, _) => self.colors.focus_border(self.colors.bright.magenta),
+            (false, true) => Style::default().fg(self.colors.normal.green),
+            (false, false) => Style::default().fg(self.colors.primary.hover),
         };
 
-        Paragraph::new(lines).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(border_color)),
-        )
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_style);
+
+        let badges = [(item.pinned, "📌"), (item.read_only, "🔒")]
+            .into_iter()
+            .filter_map(|(shown, badge)| shown.then_some(badge))
+            .collect::<Vec<_>>();
+        if !badges.is_empty() {
+            block = block.title(
+                Title::from(format!(" {} ", badges.join(" ")).fg(self.colors.normal.red))
+                    .alignment(Alignment::Right),
+            );
+        }
+
+        let requests = match item.requests {
+            1 => String::from("1 request"),
+            amount => format!("{amount} requests"),
+        };
+        // the modified time goes away before the request count does
+        let metadata = item
+            .modified
+            .as_ref()
+            .map(|modified| format!(" {requests} · {modified} "))
+            .into_iter()
+            .chain(std::iter::once(format!(" {requests} ")))
+            .find(|metadata| metadata.chars().count().le(&inner_width));
+        if let Some(metadata) = metadata {
+            block = block.title(
+                Title::from(metadata.fg(self.colors.bright.black))
+                    .position(Position::Bottom)
+                    .alignment(Alignment::Right),
+            );
+        }
+
+        Paragraph::new(lines).block(block)
     }
 }
 
-impl StatefulWidget for CollectionList<'_> {
+/// implemented on a reference so the dashboard can draw the list without
+/// cloning it every frame
+impl StatefulWidget for &CollectionList<'_> {
     type State = CollectionListState;
 
     fn render(self, size: Rect, buf: &mut Buffer, state: &mut Self::State) {
         let list_size = Rect::new(size.x, size.y, size.width.saturating_sub(3), size.height);
         let scrollbar_size = Rect::new(size.width.saturating_sub(1), size.y, 1, size.height);
-        let mut rects = self.build_layout(&list_size);
+        let rects = self.build_layout(&list_size);
+        let per_row = usize::max(self.items_per_row(&list_size), 1);
+        let rows_on_display = self.total_rows(&list_size);
 
-        let mut scrollbar_state =
-            ScrollbarState::new(state.items.len().div(self.items_per_row(&size)))
-                .position(state.scroll);
-
-        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-            .style(Style::default().fg(self.colors.normal.magenta))
-            .begin_symbol(Some("↑"))
-            .end_symbol(Some("↓"));
-
-        let items_on_display = self
-            .items_per_row(&list_size)
-            .mul(self.total_rows(&list_size));
         if let Some(index) = state.selected {
-            index
-                .gt(&items_on_display.saturating_sub(1).add(state.scroll))
-                .then(|| state.scroll = state.scroll.add(self.items_per_row(&list_size)));
-
-            state.scroll.gt(&0).then(|| {
-                index
-                    .add(1)
-                    .saturating_sub(state.scroll)
-                    .eq(&0)
-                    .then(|| state.scroll = state.scroll.saturating_sub(self.items_per_row(&size)));
-            });
+            let row = state.row_of(index, per_row);
+            if row.ge(&(state.scroll + rows_on_display)) {
+                state.scroll = (row + 1).saturating_sub(rows_on_display);
+            }
+            if row.lt(&state.scroll) {
+                state.scroll = row;
+            }
         };
 
-        state
-            .items
-            .iter()
-            .skip(state.scroll)
-            .take(rects.len())
-            .enumerate()
-            .map(|(i, collection)| self.build_card(state, collection, i))
-            .for_each(|card| card.render(rects.pop_front().unwrap(), buf));
+        let scrollbar = Scrollbar::new(
+            self.colors,
+            state.total_rows(per_row),
+            rows_on_display,
+            state.scroll,
+        );
+
+        // only the collections that fit on the screen are ever looked at
+        for (i, row) in (state.scroll..state.scroll + rows_on_display).enumerate() {
+            for (column, index) in state.row_range(row, per_row).enumerate() {
+                let Some(rect) = rects.get(i * per_row + column) else {
+                    continue;
+                };
+                let item = &state.items[state.visible[index]];
+                let selected = state.selected.is_some_and(|selected| selected.eq(&index));
+                self.build_card(item, selected, state.is_marked(item), rect.width)
+                    .render(*rect, buf);
+            }
+        }
+
+        scrollbar.render(scrollbar_size, buf);
+    }
+}
+
+/// cuts `text` to `width` characters, ending it with an ellipsis when it
+/// didn't fit
+fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.chars().count().le(&width) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut truncated = text
+        .chars()
+        .take(width.saturating_sub(1))
+        .collect::<String>();
+    if width.gt(&0) {
+        truncated.push('…');
+    }
+    Cow::Owned(truncated)
+}
+
+/// the first line of a description, with an ellipsis when there is more to
+/// it than what fits the card
+fn description_preview(description: &str, width: usize) -> Cow<'_, str> {
+    let mut lines = description.lines();
+    let first = lines.next().unwrap_or_default();
+    match lines.next() {
+        Some(_) => Cow::Owned(truncate(&format!("{first}…"), width).into_owned()),
+        None => truncate(first, width),
+    }
+}
 
-        scrollbar.render(scrollbar_size, buf, &mut scrollbar_state);
+/// a rough, human friendly distance from `time` to `now`, times in the
+/// future are treated as `just now`
+pub fn format_time_ago(time: SystemTime, now: SystemTime) -> String {
+    let elapsed = now.duration_since(time).unwrap_or(Duration::ZERO).as_secs();
+    let (amount, unit) = match elapsed {
+        0..=59 => return String::from("just now"),
+        60..=3599 => (elapsed / 60, "minute"),
+        3600..=86_399 => (elapsed / 3600, "hour"),
+        86_400..=2_591_999 => (elapsed / 86_400, "day"),
+        2_592_000..=31_535_999 => (elapsed / 2_592_000, "month"),
+        _ => (elapsed / 31_536_000, "year"),
+    };
+
+    match amount {
+        1 => format!("1 {unit} ago"),
+        _ => format!("{amount} {unit}s ago"),
     }
 }
 
@@ -179,15 +487,16 @@ mod tests {
     use super::*;
     use hac_core::collection::types::*;
     use ratatui::{backend::TestBackend, buffer::Cell, Terminal};
+    use std::sync::{Arc, RwLock};
 
     fn sample_collection() -> Collection {
         Collection {
             info: Info {
                 name: String::from("any_name"),
-                description: None,
+                ..Default::default()
             },
             path: "any_path".into(),
-            requests: None,
+            ..Default::default()
         }
     }
 
@@ -224,12 +533,12 @@ mod tests {
         let collections = vec![Collection {
             info: Info {
                 name: String::from("any_name"),
-                description: None,
+                ..Default::default()
             },
             path: "any_path".into(),
-            requests: None,
+            ..Default::default()
         }];
-        let state = CollectionListState::new(collections.clone());
+        let state = CollectionListState::new(&collections);
 
         let lines = vec![
             "any_name".fg(colors.normal.white).into(),
@@ -239,10 +548,15 @@ mod tests {
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(colors.primary.hover)),
+                .border_style(Style::default().fg(colors.primary.hover))
+                .title(
+                    Title::from(" 0 requests ".fg(colors.bright.black))
+                        .position(Position::Bottom)
+                        .alignment(Alignment::Right),
+                ),
         );
 
-        let card = collection_list.build_card(&state, &collections[0], 0);
+        let card = collection_list.build_card(&state.items[0], false, false, 30);
 
         assert_eq!(card, expected);
     }
@@ -257,14 +571,14 @@ mod tests {
         let size = terminal.size().unwrap();
         let mut frame = terminal.get_frame();
 
-        let mut state = CollectionListState::new(collections.clone());
+        let mut state = CollectionListState::new(&collections);
         let collection_list = CollectionList::new(&colors);
 
         for cell in &frame.buffer_mut().content {
             assert_eq!(cell, &Cell::default());
         }
 
-        collection_list.render(size, frame.buffer_mut(), &mut state);
+        (&collection_list).render(size, frame.buffer_mut(), &mut state);
 
         for cell in frame
             .buffer_mut()
@@ -276,4 +590,263 @@ mod tests {
             assert!(cell.symbol().ne(" "));
         }
     }
+
+    #[test]
+    fn test_filter_keeps_indexes_into_the_original_list() {
+        let collections = ["users", "billing", "users_v2"]
+            .into_iter()
+            .map(|name| Collection {
+                info: Info {
+                    name: name.into(),
+                    ..Default::default()
+                },
+                ..sample_collection()
+            })
+            .collect::<Vec<_>>();
+        let mut state = CollectionListState::new(&collections);
+
+        state.filter("users");
+        state.select(Some(1));
+
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.selected_index(), Some(2));
+        assert_eq!(state.selected_item().unwrap().name, "users_v2");
+
+        state.filter("nothing");
+        assert!(state.is_empty());
+        assert_eq!(state.selected_item(), None);
+    }
+
+    #[test]
+    fn test_requests_are_counted_through_directories() {
+        let request = |id: &str| {
+            RequestKind::Single(Arc::new(RwLock::new(Request {
+                id: id.into(),
+                method: RequestMethod::Get,
+                name: id.into(),
+                uri: "http://localhost".into(),
+                ..Default::default()
+            })))
+        };
+        let collection = Collection {
+            requests: Some(Arc::new(RwLock::new(vec![
+                request("a"),
+                RequestKind::Nested(Directory {
+                    id: "dir".into(),
+                    name: "dir".into(),
+                    requests: Arc::new(RwLock::new(vec![request("b"), request("c")])),
+                }),
+            ]))),
+            ..sample_collection()
+        };
+
+        assert_eq!(CollectionListItem::from(&collection).requests, 3);
+    }
+
+    fn pinned_state(names: &[&str], pinned: &[&str]) -> CollectionListState {
+        let collections = names
+            .iter()
+            .map(|name| Collection {
+                info: Info {
+                    name: name.to_string(),
+                    ..Default::default()
+                },
+                path: format!("{name}.json").into(),
+                ..sample_collection()
+            })
+            .collect::<Vec<_>>();
+        let mut app_state = AppState::default();
+        for name in pinned {
+            app_state.toggle_pin(Path::new(&format!("{name}.json")));
+        }
+        let mut state = CollectionListState::new(&[]);
+        state.set_items(&collections, &app_state);
+        state
+    }
+
+    #[test]
+    fn test_pinned_section_starts_its_own_rows() {
+        let state = pinned_state(&["a", "b", "c", "d", "e", "f"], &["a", "b", "c"]);
+
+        assert_eq!(state.total_rows(2), 4);
+        assert_eq!(state.row_range(0, 2), 0..2);
+        assert_eq!(state.row_range(1, 2), 2..3);
+        assert_eq!(state.row_range(2, 2), 3..5);
+        assert_eq!(state.row_range(3, 2), 5..6);
+        assert_eq!(state.row_range(4, 2), 6..6);
+        assert_eq!(state.row_of(3, 2), 2);
+    }
+
+    #[test]
+    fn test_moving_across_the_pinned_section() {
+        let mut state = pinned_state(&["a", "b", "c", "d", "e", "f"], &["a", "b", "c"]);
+        state.select(Some(1));
+
+        // the last pinned row only has one item, the column is clamped to it
+        state.select_row(1, 2);
+        assert_eq!(state.selected(), Some(2));
+        state.select_row(1, 2);
+        assert_eq!(state.selected(), Some(3));
+        state.select_row(5, 2);
+        assert_eq!(state.selected(), Some(5));
+        state.select_row(-1, 2);
+        assert_eq!(state.selected(), Some(3));
+        state.select_row(-1, 2);
+        assert_eq!(state.selected(), Some(2));
+        state.select_row(-1, 2);
+        assert_eq!(state.selected(), Some(0));
+        state.select_row(-1, 2);
+        assert_eq!(state.selected(), Some(0));
+
+        state.filter("e");
+        state.select(Some(0));
+        state.select_row(1, 2);
+        assert_eq!(state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_marks_survive_filtering_and_sorting() {
+        let collections = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| Collection {
+                    info: Info {
+                        name: name.to_string(),
+                        ..Default::default()
+                    },
+                    path: format!("{name}.json").into(),
+                    ..sample_collection()
+                })
+                .collect::<Vec<_>>()
+        };
+        let names = |state: &CollectionListState| {
+            state
+                .marked_items()
+                .into_iter()
+                .map(|item| item.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let mut state = pinned_state(&["users", "billing", "users_v2"], &[]);
+        state.select(Some(0));
+        state.toggle_mark();
+        state.select(Some(2));
+        state.toggle_mark();
+        assert_eq!(names(&state), ["users", "users_v2"]);
+
+        // hidden marks are kept and counted
+        state.filter("billing");
+        assert_eq!(state.hidden_marks(), 2);
+        state.select(Some(0));
+        state.toggle_mark();
+        assert_eq!(names(&state), ["users", "billing", "users_v2"]);
+        assert_eq!(state.hidden_marks(), 2);
+        state.toggle_mark();
+        assert_eq!(names(&state), ["users", "users_v2"]);
+
+        // sorting rebuilds the list, and marks follow their collection
+        state.set_items(
+            &collections(&["users_v2", "billing", "users"]),
+            &AppState::default(),
+        );
+        assert_eq!(names(&state), ["users_v2", "users"]);
+        assert_eq!(state.hidden_marks(), 0);
+
+        // a collection that went away takes its mark with it
+        state.set_items(&collections(&["billing", "users"]), &AppState::default());
+        assert_eq!(names(&state), ["users"]);
+        state.clear_marks();
+        assert_eq!(state.marked_count(), 0);
+    }
+
+    #[test]
+    fn test_narrow_cards_degrade() {
+        let colors = hac_colors::Colors::default();
+        let collection_list = CollectionList::new(&colors);
+        let item = CollectionListItem {
+            name: "a collection with a long name".into(),
+            description: "and an even longer description".into(),
+            requests: 12,
+            modified: Some("2 days ago".into()),
+            created_at: None,
+            modified_at: None,
+            path: "any_path".into(),
+            source: None,
+            pinned: false,
+            read_only: false,
+        };
+        let render = |width: u16| {
+            let mut buffer = Buffer::empty(Rect::new(0, 0, width, 4));
+            collection_list
+                .build_card(&item, false, false, width)
+                .render(buffer.area, &mut buffer);
+            buffer
+                .content
+                .chunks(width.into())
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            render(34),
+            [
+                "╭────────────────────────────────╮",
+                "│a collection with a long name   │",
+                "│and an even longer description  │",
+                "╰────── 12 requests · 2 days ago ╯",
+            ]
+        );
+        assert_eq!(
+            render(24),
+            [
+                "╭──────────────────────╮",
+                "│a collection with a l…│",
+                "│and an even longer de…│",
+                "╰───────── 12 requests ╯",
+            ]
+        );
+        assert_eq!(
+            render(14),
+            [
+                "╭────────────╮",
+                "│a collectio…│",
+                "│            │",
+                "╰────────────╯",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_time_ago() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100_000_000);
+        let ago = |secs: u64| format_time_ago(now - Duration::from_secs(secs), now);
+
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(3 * 3600), "3 hours ago");
+        assert_eq!(ago(2 * 86_400), "2 days ago");
+        assert_eq!(ago(400 * 86_400), "1 year ago");
+        assert_eq!(
+            format_time_ago(now + Duration::from_secs(10), now),
+            "just now"
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly", 7), "exactly");
+        assert_eq!(truncate("a bit too long", 8), "a bit t…");
+        assert_eq!(truncate("anything", 0), "");
+    }
+
+    #[test]
+    fn test_multiline_descriptions_only_preview_the_first_line() {
+        assert_eq!(description_preview("first\nsecond", 20), "first…");
+        assert_eq!(
+            description_preview("a long first line\nsecond", 10),
+            "a long fi…"
+        );
+        assert_eq!(description_preview("single line", 20), "single line");
+        assert_eq!(description_preview("", 20), "");
+    }
 }
@@ -0,0 +1,129 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Widget, Wrap};
+
+/// what an import brought in, kept around to be shown once it is done
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ImportReport {
+    /// name and amount of requests of every collection created
+    pub imported: Vec<(String, usize)>,
+    /// what on the file had no equivalent and was left out
+    pub skipped: Vec<String>,
+}
+
+/// popup shown after importing a file that held more than a collection or
+/// had anything we couldn't import
+pub struct ImportSummary<'a> {
+    report: &'a ImportReport,
+    colors: &'a hac_colors::Colors,
+    scroll: usize,
+}
+
+impl<'a> ImportSummary<'a> {
+    pub fn new(report: &'a ImportReport, colors: &'a hac_colors::Colors, scroll: usize) -> Self {
+        ImportSummary {
+            report,
+            colors,
+            scroll,
+        }
+    }
+
+    fn build_lines(&self) -> Vec<Line<'static>> {
+        let plural = |count: usize, word: &str| match count {
+            1 => format!("{count} {word}"),
+            _ => format!("{count} {word}s"),
+        };
+
+        let mut lines = vec![Line::from(
+            format!(
+                "imported {}",
+                plural(self.report.imported.len(), "collection")
+            )
+            .fg(self.colors.normal.white)
+            .bold(),
+        )];
+        for (name, requests) in self.report.imported.iter() {
+            lines.push(Line::from(vec![
+                format!("  {name} ").fg(self.colors.normal.green),
+                format!("({})", plural(*requests, "request")).fg(self.colors.bright.black),
+            ]));
+        }
+
+        if !self.report.skipped.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from(
+                "left out, as there is nothing like them here"
+                    .fg(self.colors.normal.white)
+                    .bold(),
+            ));
+            lines.extend(
+                self.report.skipped.iter().map(|skipped| {
+                    Line::from(format!("  - {skipped}").fg(self.colors.normal.yellow))
+                }),
+            );
+        }
+
+        lines
+    }
+}
+
+impl Widget for ImportSummary<'_> {
+    fn render(self, size: Rect, buf: &mut Buffer) {
+        Clear.render(size, buf);
+        let lines = self.build_lines();
+        let scroll = self.scroll.min(lines.len().saturating_sub(1));
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll as u16, 0))
+            .block(
+                Block::default()
+                    .title(" Import summary ".fg(self.colors.normal.white))
+                    .title(
+                        Title::from(" j/k to scroll, (o)k to close ".fg(self.colors.bright.black))
+                            .position(Position::Bottom)
+                            .alignment(Alignment::Right),
+                    )
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(self.colors.bright.black))
+                    .padding(Padding::new(2, 2, 1, 1))
+                    .bg(self.colors.normal.black),
+            )
+            .render(size, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_lists_collections_and_what_was_left_out() {
+        let colors = hac_colors::Colors::default();
+        let report = ImportReport {
+            imported: vec![("Shop API".into(), 5), ("Auth".into(), 1)],
+            skipped: vec!["cookie jar \"Default Jar\"".into()],
+        };
+
+        let lines = ImportSummary::new(&report, &colors, 0)
+            .build_lines()
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "imported 2 collections",
+                "  Shop API (5 requests)",
+                "  Auth (1 request)",
+                "",
+                "left out, as there is nothing like them here",
+                "  - cookie jar \"Default Jar\"",
+            ]
+        );
+    }
+}
@@ -1,6 +1,9 @@
 #[allow(clippy::module_inception)]
 mod collection_dashboard;
+mod collection_details;
 mod collection_list;
+mod import_summary;
 mod new_collection_form;
+mod request_search;
 
 pub use collection_dashboard::CollectionDashboard;
@@ -1,9 +1,12 @@
 use crate::pages::input::Input;
+use crate::pages::spinner::Spinner;
+
+use std::path::PathBuf;
 
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Direction, Flex, Layout, Rect};
 use ratatui::style::{Style, Stylize};
-use ratatui::text::Line;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{
     Block, BorderType, Borders, Clear, Padding, Paragraph, StatefulWidget, Widget,
 };
@@ -30,6 +33,12 @@ pub struct FormState {
     pub name: String,
     pub description: String,
     pub focused_field: FormFocus,
+    /// path of the collection being edited, the form creates a new collection
+    /// when this is `None`
+    pub editing: Option<PathBuf>,
+    /// the collection is being written, the form takes no input until it
+    /// either was or failed to
+    pub submitting: bool,
 }
 
 impl FormState {
@@ -37,6 +46,16 @@ impl FormState {
         self.name = String::default();
         self.description = String::default();
         self.focused_field = FormFocus::Name;
+        self.editing = None;
+        self.submitting = false;
+    }
+
+    /// fills the form with the current info of the collection at `path`
+    pub fn edit(&mut self, name: String, description: String, path: PathBuf) {
+        self.name = name;
+        self.description = description;
+        self.focused_field = FormFocus::Description;
+        self.editing = Some(path);
     }
 }
 
@@ -109,9 +128,11 @@ impl StatefulWidget for NewCollectionForm<'_> {
         };
 
         let cancel_text = if state.focused_field.eq(&FormFocus::Cancel) {
-            "Cancel"
-                .fg(self.colors.normal.white)
-                .bg(self.colors.normal.red)
+            Span::styled(
+                "Cancel",
+                self.colors
+                    .highlight(self.colors.normal.white, self.colors.normal.red),
+            )
         } else {
             "Cancel".fg(self.colors.normal.white)
         };
@@ -123,15 +144,24 @@ impl StatefulWidget for NewCollectionForm<'_> {
                 .border_type(BorderType::Rounded),
         );
 
-        let confirm_text = if state.focused_field.eq(&FormFocus::Confirm) {
-            "Create"
-                .fg(self.colors.normal.white)
-                .bg(self.colors.normal.magenta)
-        } else {
-            "Create".fg(self.colors.normal.white)
+        let confirm_label = match state.editing {
+            Some(_) => "Save",
+            None => "Create",
+        };
+        let confirm_text = match (state.submitting, &state.focused_field) {
+            (true, _) => Spinner::default()
+                .with_style(Style::default().fg(self.colors.bright.magenta))
+                .into_centered_line(),
+            (false, FormFocus::Confirm) => Line::from(Span::styled(
+                confirm_label,
+                self.colors
+                    .highlight(self.colors.normal.white, self.colors.normal.magenta),
+            ))
+            .centered(),
+            (false, _) => Line::from(confirm_label.fg(self.colors.normal.white)).centered(),
         };
 
-        let confirm_button = Paragraph::new(Line::from(confirm_text).centered()).block(
+        let confirm_button = Paragraph::new(confirm_text).block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(self.colors.bright.magenta))
@@ -142,9 +172,12 @@ impl StatefulWidget for NewCollectionForm<'_> {
             .padding(Padding::uniform(1))
             .style(Style::default().bg(self.colors.primary.background));
 
-        let hint = Paragraph::new("[Tab] to switch focus [Enter] to select a button")
-            .centered()
-            .fg(self.colors.normal.magenta);
+        let hint = match state.submitting {
+            true => Paragraph::new("creating the collection…").fg(self.colors.bright.black),
+            false => Paragraph::new("[Tab] to switch focus [Enter] to select a button")
+                .fg(self.colors.normal.magenta),
+        }
+        .centered();
 
         full_block.render(size, buf);
         name_input.render(layout.name_input, buf, &mut state.name);
@@ -0,0 +1,308 @@
+use hac_core::collection::Collection;
+use hac_core::search::{self, MatchField, SearchMatch};
+
+use crate::pages::input;
+use crate::pages::Overlay;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph};
+use ratatui::Frame;
+
+#[derive(Debug, PartialEq)]
+pub enum RequestSearchEvent {
+    /// the user picked one of the results
+    Open(SearchMatch),
+    Close,
+    Quit,
+}
+
+/// searches every request of every collection as the user types, the
+/// collections are cloned when the search opens, which is cheap as requests
+/// are shared
+#[derive(Debug)]
+pub struct RequestSearch<'a> {
+    colors: &'a hac_colors::Colors,
+    collections: Vec<Collection>,
+    query: String,
+    matches: Vec<SearchMatch>,
+    selected: usize,
+    scroll: usize,
+}
+
+impl<'a> RequestSearch<'a> {
+    pub fn new(colors: &'a hac_colors::Colors, collections: Vec<Collection>) -> Self {
+        RequestSearch {
+            colors,
+            collections,
+            query: String::default(),
+            matches: vec![],
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = search::search(&self.collections, &self.query);
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    fn select_next(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = usize::min(self.selected + 1, self.matches.len() - 1);
+        }
+    }
+
+    fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn build_match_line(&self, result: &SearchMatch, selected: bool) -> Line<'static> {
+        let mut location = vec![result.collection_name.as_str()];
+        location.extend(result.folders.iter().map(String::as_str));
+        location.push(result.request_name.as_str());
+
+        // headers also show which header matched, names and uris speak for
+        // themselves
+        let label = match &result.field {
+            MatchField::Name | MatchField::Uri => String::default(),
+            MatchField::Header(name) => format!("{name}: "),
+        };
+
+        let text = &result.text;
+        let mut spans = vec![
+            Span::from(format!("{}: ", location.join(" ▸ "))).fg(self.colors.normal.white),
+            Span::from(label).fg(self.colors.bright.black),
+            Span::from(text[..result.range.start].to_string()).fg(self.colors.bright.black),
+            Span::styled(
+                text[result.range.clone()].to_string(),
+                self.colors
+                    .highlight(self.colors.normal.black, self.colors.normal.yellow),
+            ),
+            Span::from(text[result.range.end..].to_string()).fg(self.colors.bright.black),
+        ];
+
+        if selected {
+            spans.insert(0, Span::from("> ").fg(self.colors.bright.magenta));
+        } else {
+            spans.insert(0, Span::from("  "));
+        }
+
+        Line::from(spans)
+    }
+
+    fn build_results(&mut self, height: usize) -> Vec<Line<'static>> {
+        if self.query.is_empty() {
+            return vec![Line::from(
+                "search request names, uris and header values".fg(self.colors.bright.black),
+            )];
+        }
+
+        if self.matches.is_empty() {
+            return vec![Line::from(
+                format!("no requests match {}", self.query).fg(self.colors.bright.black),
+            )];
+        }
+
+        if self.selected.ge(&(self.scroll + height)) {
+            self.scroll = self.selected + 1 - height;
+        }
+        if self.selected.lt(&self.scroll) {
+            self.scroll = self.selected;
+        }
+
+        self.matches
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .map(|(idx, result)| self.build_match_line(result, idx.eq(&self.selected)))
+            .collect()
+    }
+}
+
+impl Overlay for RequestSearch<'_> {
+    type Result = RequestSearchEvent;
+
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .title(" search requests ".fg(self.colors.normal.white))
+            .padding(Padding::new(1, 1, 0, 0))
+            .bg(self.colors.normal.black);
+        let inner = block.inner(size);
+
+        let [prompt_pane, _, results_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .areas(inner);
+
+        let prompt = Line::from(vec![
+            "> ".fg(self.colors.bright.magenta),
+            self.query.clone().fg(self.colors.normal.white),
+        ]);
+        let results = self.build_results(results_pane.height.into());
+
+        frame.render_widget(Clear, size);
+        frame.render_widget(block, size);
+        frame.render_widget(prompt, prompt_pane);
+        frame.render_widget(Paragraph::new(results), results_pane);
+
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        match (key_event.code, key_event.modifiers) {
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                return Ok(Some(RequestSearchEvent::Quit))
+            }
+            (KeyCode::Esc, _) => return Ok(Some(RequestSearchEvent::Close)),
+            (KeyCode::Enter, _) => {
+                return Ok(self
+                    .matches
+                    .get(self.selected)
+                    .cloned()
+                    .map(RequestSearchEvent::Open))
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => self.select_next(),
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => self.select_prev(),
+            (KeyCode::Backspace, _) => {
+                self.query.pop();
+                self.update_matches();
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        self.query.push_str(&input::single_line(&text));
+        self.update_matches();
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_collection, make_request};
+    use hac_core::collection::types::Request;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn searchable(name: &str, requests: &[&str]) -> Collection {
+        let requests = requests
+            .iter()
+            .map(|name| Request {
+                id: format!("{name}_id"),
+                ..make_request(name, &format!("http://localhost/{name}"))
+            })
+            .collect();
+
+        Collection {
+            path: format!("{name}.json").into(),
+            ..make_collection(name, requests)
+        }
+    }
+
+    #[test]
+    fn test_search_is_incremental() {
+        let colors = hac_colors::Colors::default();
+        let collections = vec![
+            searchable("users", &["list users", "create user"]),
+            searchable("billing", &["list invoices"]),
+        ];
+        let mut search = RequestSearch::new(&colors, collections);
+
+        search.handle_key_event(key(KeyCode::Char('l'))).unwrap();
+        search.handle_key_event(key(KeyCode::Char('i'))).unwrap();
+        assert_eq!(search.matches.len(), 2);
+
+        search.handle_key_event(key(KeyCode::Char('s'))).unwrap();
+        search.handle_key_event(key(KeyCode::Char('t'))).unwrap();
+        search.handle_key_event(key(KeyCode::Char(' '))).unwrap();
+        search.handle_key_event(key(KeyCode::Char('i'))).unwrap();
+        assert_eq!(search.matches.len(), 1);
+
+        search.handle_key_event(key(KeyCode::Backspace)).unwrap();
+        search.handle_key_event(key(KeyCode::Backspace)).unwrap();
+        assert_eq!(search.query, "list");
+        assert_eq!(search.matches.len(), 2);
+    }
+
+    #[test]
+    fn test_pasting_into_the_query() {
+        let colors = hac_colors::Colors::default();
+        let collections = vec![
+            searchable("users", &["list users"]),
+            searchable("billing", &["list invoices"]),
+        ];
+        let mut search = RequestSearch::new(&colors, collections);
+
+        search.handle_paste("invoices\n".into()).unwrap();
+        assert_eq!(search.query, "invoices");
+        assert_eq!(search.matches.len(), 1);
+    }
+
+    #[test]
+    fn test_picking_a_result() {
+        let colors = hac_colors::Colors::default();
+        let collections = vec![
+            searchable("users", &["list users"]),
+            searchable("billing", &["list invoices"]),
+        ];
+        let mut search = RequestSearch::new(&colors, collections);
+
+        assert_eq!(search.handle_key_event(key(KeyCode::Enter)).unwrap(), None);
+
+        for c in "list".chars() {
+            search.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        search.handle_key_event(key(KeyCode::Down)).unwrap();
+        search.handle_key_event(key(KeyCode::Down)).unwrap();
+
+        let Some(RequestSearchEvent::Open(result)) =
+            search.handle_key_event(key(KeyCode::Enter)).unwrap()
+        else {
+            panic!("expected a result to be opened");
+        };
+        assert_eq!(result.collection_name, "billing");
+        assert_eq!(result.request_id, "list invoices_id");
+
+        assert_eq!(
+            search.handle_key_event(key(KeyCode::Esc)).unwrap(),
+            Some(RequestSearchEvent::Close)
+        );
+    }
+
+    #[test]
+    fn test_match_is_highlighted() {
+        let colors = hac_colors::Colors::default();
+        let collections = vec![searchable("users", &["list users"])];
+        let mut search = RequestSearch::new(&colors, collections);
+        for c in "users".chars() {
+            search.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+
+        let line = search.build_match_line(&search.matches[0], true);
+        assert_eq!(line.to_string(), "> users ▸ list users: list users");
+        assert_eq!(line.spans[4].content, "users");
+        assert_eq!(line.spans[4].style.bg, Some(colors.normal.yellow));
+    }
+}
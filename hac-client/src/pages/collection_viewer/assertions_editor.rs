@@ -0,0 +1,434 @@
+use hac_core::collection::types::Assertion;
+
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::input::{self, Input};
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Sub};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// set of events `AssertionsEditor` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AssertionsEditorEvent {
+    /// user closed the editor, assertions are saved on the request as they
+    /// are confirmed, so there is nothing left to do but to hide it
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// text currently being typed on the editor input
+#[derive(Debug, PartialEq)]
+struct EditingAssertion {
+    /// index of the assertion being edited, `None` when creating a new one
+    idx: Option<usize>,
+    value: String,
+}
+
+/// popup to list, create, edit and delete the assertions of the selected
+/// request. Assertions are typed in their short textual form, eg:
+/// `$.items length >= 1`, and are validated before being saved
+#[derive(Debug)]
+pub struct AssertionsEditor<'ae> {
+    colors: &'ae hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    editing: Option<EditingAssertion>,
+    error: Option<String>,
+    /// why some assertions made from a response check less than they could,
+    /// by their index. Only kept until the editor is closed
+    notes: Vec<(usize, String)>,
+}
+
+impl<'ae> AssertionsEditor<'ae> {
+    pub fn new(
+        colors: &'ae hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        AssertionsEditor {
+            colors,
+            collection_store,
+            selected: 0,
+            editing: None,
+            error: None,
+            notes: vec![],
+        }
+    }
+
+    /// selects the first of the assertions that were just added, for the
+    /// user to go over them along with their notes
+    pub fn review(&mut self, added: Vec<(usize, Option<String>)>) {
+        self.selected = added
+            .first()
+            .map(|(idx, _)| *idx)
+            .unwrap_or(self.assertions().len().saturating_sub(1));
+        self.notes = added
+            .into_iter()
+            .filter_map(|(idx, note)| note.map(|note| (idx, note)))
+            .collect();
+        self.editing = None;
+        self.error = None;
+    }
+
+    fn assertions(&self) -> Vec<Assertion> {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .and_then(|req| req.read().unwrap().assertions.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_assertions(&mut self, assertions: Vec<Assertion>) {
+        if let Some(request) = self.collection_store.borrow().get_selected_request() {
+            // we don't want to store an empty list on the collection file
+            request.write().unwrap().assertions = (!assertions.is_empty()).then_some(assertions);
+        }
+    }
+
+    fn confirm_edit(&mut self) {
+        let Some(editing) = self.editing.as_ref() else {
+            return;
+        };
+
+        let assertion = match editing.value.parse::<Assertion>() {
+            Ok(assertion) => assertion,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let mut assertions = self.assertions();
+        match editing.idx {
+            Some(idx) if idx.lt(&assertions.len()) => {
+                // the note was about what the assertion used to be
+                self.notes.retain(|(noted, _)| noted.ne(&idx));
+                assertions[idx] = assertion;
+            }
+            _ => {
+                assertions.push(assertion);
+                self.selected = assertions.len().sub(1);
+            }
+        }
+
+        self.set_assertions(assertions);
+        self.editing = None;
+        self.error = None;
+    }
+
+    fn delete_selected(&mut self) {
+        let mut assertions = self.assertions();
+        if self.selected.lt(&assertions.len()) {
+            assertions.remove(self.selected);
+            let removed = self.selected;
+            self.notes.retain(|(idx, _)| idx.ne(&removed));
+            for (idx, _) in self.notes.iter_mut().filter(|(idx, _)| idx.gt(&removed)) {
+                *idx = idx.sub(1);
+            }
+            self.selected = self.selected.min(assertions.len().saturating_sub(1));
+            self.set_assertions(assertions);
+        }
+    }
+
+    fn handle_editing_key_event(&mut self, key_event: KeyEvent) {
+        let Some(editing) = self.editing.as_mut() else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Char(c) => editing.value.push(c),
+            KeyCode::Backspace => _ = editing.value.pop(),
+            KeyCode::Enter => self.confirm_edit(),
+            KeyCode::Esc => {
+                self.editing = None;
+                self.error = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn make_assertion_lines(&self, assertions: &[Assertion]) -> Vec<Line<'static>> {
+        if assertions.is_empty() {
+            return vec![Line::from(
+                "no assertions yet, press n to add one".fg(self.colors.bright.black),
+            )
+            .centered()];
+        }
+
+        assertions
+            .iter()
+            .enumerate()
+            .map(|(idx, assertion)| {
+                let line = format!(" {} ", assertion);
+                let mut line = match idx.eq(&self.selected) && self.editing.is_none() {
+                    true => Line::from(Span::styled(
+                        line,
+                        self.colors
+                            .highlight(self.colors.normal.white, self.colors.normal.blue),
+                    )),
+                    false => Line::from(line.fg(self.colors.normal.white)),
+                };
+                if let Some((_, note)) = self.notes.iter().find(|(noted, _)| noted.eq(&idx)) {
+                    line.spans
+                        .push(format!(" # {note}").fg(self.colors.bright.black));
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+impl Renderable for AssertionsEditor<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let popup = build_layout(size);
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Assertions ".fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let [list_pane, input_pane, error_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(inner);
+
+        let assertions = self.assertions();
+        let lines = self
+            .make_assertion_lines(&assertions)
+            .into_iter()
+            // keep the selected assertion in view on long lists
+            .skip(
+                self.selected
+                    .saturating_sub(list_pane.height.saturating_sub(1).into()),
+            )
+            .collect::<Vec<_>>();
+        frame.render_widget(Paragraph::new(lines), list_pane);
+
+        let mut input = Input::new(self.colors, "Assertion".into())
+            .placeholder("status 2xx, header content-type contains json, $.id exists".into());
+        let mut value = self
+            .editing
+            .as_ref()
+            .map(|editing| editing.value.clone())
+            .unwrap_or_default();
+        if self.editing.is_some() {
+            input.focus();
+            frame.set_cursor(
+                input_pane.x.add(value.chars().count() as u16).add(1),
+                input_pane.y.add(1),
+            );
+        }
+        frame.render_stateful_widget(input, input_pane, &mut value);
+
+        if let Some(error) = self.error.as_ref() {
+            frame.render_widget(
+                Line::from(error.clone().fg(self.colors.normal.red)),
+                error_pane,
+            );
+        }
+
+        let hint = match self.editing.is_some() {
+            true => "[enter -> confirm] [esc -> cancel]",
+            false => "[n -> new] [enter -> edit] [d -> delete] [esc -> close]",
+        };
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for AssertionsEditor<'_> {
+    type Result = AssertionsEditorEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(AssertionsEditorEvent::Quit));
+        }
+
+        if self.editing.is_some() {
+            self.handle_editing_key_event(key_event);
+            return Ok(None);
+        }
+
+        let total = self.assertions().len();
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.selected = 0;
+                self.notes.clear();
+                return Ok(Some(AssertionsEditorEvent::Close));
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = usize::min(self.selected.add(1), total.saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('n') => {
+                self.editing = Some(EditingAssertion {
+                    idx: None,
+                    value: String::default(),
+                })
+            }
+            KeyCode::Enter | KeyCode::Char('i') if self.selected.lt(&total) => {
+                self.editing = Some(EditingAssertion {
+                    idx: Some(self.selected),
+                    value: self.assertions()[self.selected].to_string(),
+                })
+            }
+            KeyCode::Char('d') => self.delete_selected(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let Some(editing) = self.editing.as_mut() {
+            editing.value.push_str(&input::single_line(&text));
+        }
+        Ok(None)
+    }
+}
+
+fn build_layout(size: Rect) -> Rect {
+    let width = u16::min(size.width.saturating_sub(4), 80);
+    let height = u16::min(size.height.saturating_sub(4), 20);
+
+    Rect::new(
+        size.x.add(size.width.sub(width).div(2)),
+        size.y.add(size.height.sub(height).div(2)),
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+
+    fn type_text(editor: &mut AssertionsEditor, text: &str) {
+        for c in text.chars() {
+            editor
+                .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap();
+        }
+    }
+
+    fn press(editor: &mut AssertionsEditor, code: KeyCode) {
+        editor
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_creating_and_deleting_assertions() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) =
+            store_with_request(make_request("users", "https://example.com/users"));
+        let mut editor = AssertionsEditor::new(&colors, store);
+
+        press(&mut editor, KeyCode::Char('n'));
+        type_text(&mut editor, "status 2xx");
+        press(&mut editor, KeyCode::Enter);
+
+        assert_eq!(
+            request.read().unwrap().assertions,
+            Some(vec![Assertion::StatusClass { class: 2 }])
+        );
+
+        press(&mut editor, KeyCode::Char('d'));
+        assert_eq!(request.read().unwrap().assertions, None);
+    }
+
+    #[test]
+    fn test_invalid_assertions_are_not_saved() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) =
+            store_with_request(make_request("users", "https://example.com/users"));
+        let mut editor = AssertionsEditor::new(&colors, store);
+
+        press(&mut editor, KeyCode::Char('n'));
+        type_text(&mut editor, "status abc");
+        press(&mut editor, KeyCode::Enter);
+
+        assert!(editor.error.is_some());
+        assert!(editor.editing.is_some());
+        assert_eq!(request.read().unwrap().assertions, None);
+
+        press(&mut editor, KeyCode::Esc);
+        assert!(editor.error.is_none());
+        assert!(editor.editing.is_none());
+    }
+
+    #[test]
+    fn test_reviewing_scaffolded_assertions_keeps_their_notes() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) =
+            store_with_request(make_request("users", "https://example.com/users"));
+        request.write().unwrap().assertions = Some(vec![
+            "status 200".parse().unwrap(),
+            "$.id exists".parse().unwrap(),
+            "$.created_at exists".parse().unwrap(),
+        ]);
+        let mut editor = AssertionsEditor::new(&colors, store);
+
+        editor.review(vec![
+            (1, Some("looks like a {{$uuid}}".into())),
+            (2, Some("looks like a {{$isoTimestamp}}".into())),
+        ]);
+        assert_eq!(editor.selected, 1);
+        let lines = editor
+            .make_assertion_lines(&editor.assertions())
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                " status 200 ",
+                " $.id exists  # looks like a {{$uuid}}",
+                " $.created_at exists  # looks like a {{$isoTimestamp}}",
+            ]
+        );
+
+        // notes follow their assertion around
+        press(&mut editor, KeyCode::Char('d'));
+        assert_eq!(
+            editor.notes,
+            vec![(1, "looks like a {{$isoTimestamp}}".into())]
+        );
+        press(&mut editor, KeyCode::Enter);
+        for _ in 0.."exists".len() {
+            press(&mut editor, KeyCode::Backspace);
+        }
+        type_text(&mut editor, "== 2024-05-01");
+        press(&mut editor, KeyCode::Enter);
+        assert!(editor.notes.is_empty());
+
+        press(&mut editor, KeyCode::Esc);
+        editor.review(vec![]);
+        assert_eq!(editor.selected, 1);
+    }
+}
@@ -0,0 +1,945 @@
+use hac_core::collection::types::HostOverride;
+use hac_core::collection::Collection;
+use hac_core::net::download::format_size;
+use hac_core::secrets::MASK;
+
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::input;
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Sub};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// width of the column with the name of each field
+const LABEL_WIDTH: usize = 16;
+
+/// set of events `CollectionSettings` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CollectionSettingsEvent {
+    /// a setting was changed and the collection should be saved
+    Changed,
+    /// user pressed `Esc` while no field was being edited
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    BaseUrl,
+    Timeout,
+    Proxy,
+    UnixSocket,
+    Resolve,
+    MaxRequestBody,
+    MaxBodyInMemory,
+    ConfirmDestructive,
+}
+
+/// every field in the order they are shown, along with the group they are
+/// listed under
+const FIELDS: [(&str, Field); 8] = [
+    ("Requests", Field::BaseUrl),
+    ("Requests", Field::Timeout),
+    ("Connection", Field::Proxy),
+    ("Connection", Field::UnixSocket),
+    ("Connection", Field::Resolve),
+    ("Limits", Field::MaxRequestBody),
+    ("Limits", Field::MaxBodyInMemory),
+    ("Safety", Field::ConfirmDestructive),
+];
+
+impl Field {
+    fn label(&self) -> &'static str {
+        match self {
+            Field::BaseUrl => "Base url",
+            Field::Timeout => "Timeout (ms)",
+            Field::Proxy => "Proxy",
+            Field::UnixSocket => "Unix socket",
+            Field::Resolve => "Resolve",
+            Field::MaxRequestBody => "Request body",
+            Field::MaxBodyInMemory => "Response body",
+            Field::ConfirmDestructive => "Confirm sends",
+        }
+    }
+
+    /// what is written on the collection, `None` when the field is not set
+    fn value(&self, collection: &Collection) -> Option<String> {
+        match self {
+            Field::BaseUrl => collection.base_url.clone(),
+            Field::Timeout => collection.timeout_ms.map(|ms| ms.to_string()),
+            Field::Proxy => collection.proxy.clone(),
+            Field::UnixSocket => collection
+                .unix_socket
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string()),
+            Field::Resolve => collection.resolve.as_ref().map(|entries| {
+                entries
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+            Field::MaxRequestBody => collection.max_request_body.map(|max| max.to_string()),
+            Field::MaxBodyInMemory => collection.max_body_in_memory.map(|max| max.to_string()),
+            Field::ConfirmDestructive => collection.confirm_destructive.map(on_off),
+        }
+    }
+
+    /// checks what was typed on the field, an empty value unsets it
+    fn validate(&self, value: &str) -> Result<Option<String>, String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        match self {
+            // placeholders are only resolved when sending, so there is no
+            // telling what the url will look like
+            Field::BaseUrl if value.contains("{{") => {}
+            Field::BaseUrl => match reqwest::Url::parse(value) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => return Err("must be an http:// or https:// url".into()),
+            },
+            Field::Timeout => match value.parse::<u64>() {
+                Ok(ms) if ms > 0 => {}
+                _ => return Err("must be a whole number of milliseconds".into()),
+            },
+            Field::Proxy => match reqwest::Url::parse(value) {
+                Ok(url) if matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") => {}
+                _ => return Err("must be an http://, https:// or socks5:// url".into()),
+            },
+            // zero is a valid limit, it turns the one on the config file off
+            Field::MaxRequestBody | Field::MaxBodyInMemory => {
+                if value.parse::<u64>().is_err() {
+                    return Err("must be a whole number of bytes, 0 for no limit".into());
+                }
+            }
+            Field::Resolve => {
+                for entry in host_overrides(value) {
+                    entry.map_err(|e| format!("{e}, separated by commas"))?;
+                }
+            }
+            Field::UnixSocket if !Path::new(value).is_absolute() => {
+                return Err("must be an absolute path".into())
+            }
+            Field::UnixSocket | Field::ConfirmDestructive => {}
+        }
+
+        Ok(Some(value.to_string()))
+    }
+
+    /// writes an already validated value to the collection
+    fn apply(&self, collection: &mut Collection, value: Option<String>) {
+        match self {
+            Field::BaseUrl => collection.base_url = value,
+            Field::Timeout => collection.timeout_ms = value.and_then(|ms| ms.parse().ok()),
+            Field::Proxy => collection.proxy = value,
+            Field::UnixSocket => collection.unix_socket = value.map(PathBuf::from),
+            Field::Resolve => {
+                collection.resolve = value.map(|value| host_overrides(&value).flatten().collect())
+            }
+            Field::MaxRequestBody => {
+                collection.max_request_body = value.and_then(|max| max.parse().ok())
+            }
+            Field::MaxBodyInMemory => {
+                collection.max_body_in_memory = value.and_then(|max| max.parse().ok())
+            }
+            Field::ConfirmDestructive => {
+                collection.confirm_destructive = value.map(|value| value.eq("on"))
+            }
+        }
+    }
+}
+
+/// a line of the popup that can be selected
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    Setting(Field),
+    /// a variable of the collection, by name
+    Variable(String),
+    /// where new variables are typed
+    NewVariable,
+    /// a variable of the active environment, by name, which can be marked
+    /// as secret
+    EnvVariable(String),
+}
+
+/// checks a variable typed as `name=value`, an empty one is removed
+fn parse_variable(typed: &str) -> Result<Option<(String, String)>, String> {
+    let typed = typed.trim();
+    if typed.is_empty() {
+        return Ok(None);
+    }
+
+    let Some((name, value)) = typed.split_once('=') else {
+        return Err("must be written as name=value".into());
+    };
+    let name = name.trim();
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c.eq(&'{') || c.eq(&'}')) {
+        return Err("names can't be empty or have spaces or braces".into());
+    }
+    if name.starts_with('$') {
+        return Err("names starting with $ are built-in variables".into());
+    }
+
+    Ok(Some((name.to_string(), value.trim().to_string())))
+}
+
+/// address overrides typed like curl takes them, separated by commas
+fn host_overrides(typed: &str) -> impl Iterator<Item = Result<HostOverride, String>> + '_ {
+    typed
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::parse)
+}
+
+/// a size limit from the config file, where `0` turns it off
+fn limit(max: u64) -> String {
+    match max {
+        0 => "no limit (config)".into(),
+        max => format!("{} (config)", format_size(max)),
+    }
+}
+
+fn on_off(enabled: bool) -> String {
+    match enabled {
+        true => "on".into(),
+        false => "off".into(),
+    }
+}
+
+/// settings shared by every request on the collection, along with its
+/// variables and which ones of the active environment are secret. Text
+/// fields are checked when confirmed, and nothing is written until they are
+/// valid
+#[derive(Debug)]
+pub struct CollectionSettings<'cs> {
+    colors: &'cs hac_colors::Colors,
+    config: &'cs hac_config::Config,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    /// what is being typed on the selected field, if it is being edited
+    editing: Option<String>,
+    /// why the last value confirmed on the selected field was refused
+    error: Option<String>,
+}
+
+impl<'cs> CollectionSettings<'cs> {
+    pub fn new(
+        colors: &'cs hac_colors::Colors,
+        config: &'cs hac_config::Config,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        CollectionSettings {
+            colors,
+            config,
+            collection_store,
+            selected: 0,
+            editing: None,
+            error: None,
+        }
+    }
+
+    /// the settings followed by the variables of the collection, sorted by
+    /// name, a row to add new ones and the variables of the active
+    /// environment
+    fn rows(&self) -> Vec<Row> {
+        let store = self.collection_store.borrow();
+        let variables = store
+            .get_collection()
+            .and_then(|collection| collection.borrow().variables.clone())
+            .unwrap_or_default();
+        let environment = store.environment_variables();
+
+        FIELDS
+            .iter()
+            .map(|(_, field)| Row::Setting(*field))
+            .chain(variables.into_keys().map(Row::Variable))
+            .chain(std::iter::once(Row::NewVariable))
+            .chain(
+                environment
+                    .into_iter()
+                    .map(|(name, _)| Row::EnvVariable(name)),
+            )
+            .collect()
+    }
+
+    /// what is shown for a variable of the active environment, secrets are
+    /// masked and the ones without a value say so
+    fn env_variable_span(&self, name: &str) -> Span<'static> {
+        let store = self.collection_store.borrow();
+        let value = store.get_layers().environment.get(name).cloned();
+        match (store.is_secret(name), value) {
+            (true, Some(_)) => MASK.fg(self.colors.normal.white),
+            (true, None) => "secret, no value yet".fg(self.colors.bright.black),
+            (false, value) => value.unwrap_or_default().fg(self.colors.normal.white),
+        }
+    }
+
+    fn row(&self) -> Row {
+        let mut rows = self.rows();
+        rows.swap_remove(self.selected.min(rows.len().sub(1)))
+    }
+
+    fn variable(&self, name: &str) -> Option<String> {
+        self.collection_store
+            .borrow()
+            .get_collection()
+            .and_then(|collection| collection.borrow().variables.as_ref()?.get(name).cloned())
+    }
+
+    /// replaces the variable `previous`, if any, with `variable`, selecting
+    /// it afterwards
+    fn set_variable(&mut self, previous: Option<&str>, variable: Option<(String, String)>) {
+        if let Some(collection) = self.collection_store.borrow().get_collection() {
+            let mut collection = collection.borrow_mut();
+            let mut variables = collection.variables.take().unwrap_or_default();
+            if let Some(previous) = previous {
+                variables.remove(previous);
+            }
+            if let Some((name, value)) = variable.as_ref() {
+                variables.insert(name.clone(), value.clone());
+            }
+            collection.variables = (!variables.is_empty()).then_some(variables);
+        }
+
+        if let Some((name, _)) = variable {
+            let row = Row::Variable(name);
+            if let Some(idx) = self.rows().iter().position(|r| r.eq(&row)) {
+                self.selected = idx;
+            }
+        }
+    }
+
+    fn current_value(&self, field: Field) -> Option<String> {
+        self.collection_store
+            .borrow()
+            .get_collection()
+            .and_then(|collection| field.value(&collection.borrow()))
+    }
+
+    fn set_value(&mut self, field: Field, value: Option<String>) {
+        if let Some(collection) = self.collection_store.borrow().get_collection() {
+            field.apply(&mut collection.borrow_mut(), value);
+        }
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.rows().len().sub(1));
+        self.error = None;
+    }
+
+    /// text fields start editing with what they have, variables as
+    /// `name=value`. The toggle goes from following the config file to on,
+    /// then off
+    fn activate(&mut self) -> Option<CollectionSettingsEvent> {
+        let field = match self.row() {
+            Row::Setting(field) => field,
+            Row::Variable(name) => {
+                let value = self.variable(&name).unwrap_or_default();
+                self.editing = Some(format!("{name}={value}"));
+                return None;
+            }
+            Row::NewVariable => {
+                self.editing = Some(String::new());
+                return None;
+            }
+            Row::EnvVariable(_) => return None,
+        };
+        if field.ne(&Field::ConfirmDestructive) {
+            self.editing = Some(self.current_value(field).unwrap_or_default());
+            return None;
+        }
+
+        let next = match self.current_value(field).as_deref() {
+            None => Some("on".to_string()),
+            Some("on") => Some("off".to_string()),
+            _ => None,
+        };
+        self.set_value(field, next);
+        Some(CollectionSettingsEvent::Changed)
+    }
+
+    /// writes the value being typed when it is valid, otherwise the field
+    /// keeps being edited with the reason shown below it
+    fn confirm(&mut self) -> Option<CollectionSettingsEvent> {
+        let field = match self.row() {
+            Row::Setting(field) => field,
+            row => return self.confirm_variable(row),
+        };
+        let typed = self.editing.as_deref().unwrap_or_default();
+        match field.validate(typed) {
+            Ok(value) => {
+                self.editing = None;
+                self.error = None;
+                match self.current_value(field).ne(&value) {
+                    true => {
+                        self.set_value(field, value);
+                        Some(CollectionSettingsEvent::Changed)
+                    }
+                    false => None,
+                }
+            }
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        }
+    }
+
+    fn confirm_variable(&mut self, row: Row) -> Option<CollectionSettingsEvent> {
+        let typed = self.editing.as_deref().unwrap_or_default();
+        let variable = match parse_variable(typed) {
+            Ok(variable) => variable,
+            Err(error) => {
+                self.error = Some(error);
+                return None;
+            }
+        };
+        self.editing = None;
+        self.error = None;
+
+        let previous = match row {
+            Row::Variable(name) => Some(name),
+            _ => None,
+        };
+        let current = previous
+            .as_ref()
+            .and_then(|name| self.variable(name).map(|value| (name.clone(), value)));
+        if current.eq(&variable) {
+            return None;
+        }
+
+        self.set_variable(previous.as_deref(), variable);
+        Some(CollectionSettingsEvent::Changed)
+    }
+
+    /// the value shown for `field`. Unset fields show what is used instead,
+    /// dimmed
+    fn value_span(&self, field: Field) -> Span<'static> {
+        if let Some(value) = self.current_value(field) {
+            return value.fg(self.colors.normal.white);
+        }
+
+        let inherited = match field {
+            Field::Proxy => match self.config.proxy.as_ref() {
+                Some(proxy) => format!("{} (config)", hac_core::net::proxy::redact(proxy)),
+                None => "from the environment".into(),
+            },
+            Field::ConfirmDestructive => {
+                format!("{} (config)", on_off(self.config.confirm_destructive))
+            }
+            Field::Timeout => "no limit".into(),
+            Field::MaxRequestBody => limit(self.config.max_request_body),
+            Field::MaxBodyInMemory => limit(self.config.max_body_in_memory),
+            Field::BaseUrl | Field::UnixSocket | Field::Resolve => "not set".into(),
+        };
+        inherited.fg(self.colors.bright.black)
+    }
+
+    fn lines(&self) -> (Vec<Line<'static>>, Option<(u16, u16)>) {
+        let mut lines = vec![];
+        let mut cursor = None;
+        let mut group = "";
+        let environment = format!(
+            "Environment {}",
+            self.collection_store
+                .borrow()
+                .get_active_environment()
+                .unwrap_or_default()
+        );
+
+        for (idx, row) in self.rows().into_iter().enumerate() {
+            let row_group = match row {
+                Row::Setting(field) => FIELDS
+                    .iter()
+                    .find(|(_, f)| f.eq(&field))
+                    .map(|(group, _)| *group)
+                    .unwrap_or_default(),
+                Row::Variable(_) | Row::NewVariable => "Variables",
+                Row::EnvVariable(_) => environment.as_str(),
+            };
+            if group.ne(row_group) {
+                if !lines.is_empty() {
+                    lines.push(Line::default());
+                }
+                lines.push(Line::from(
+                    row_group.to_string().fg(self.colors.normal.yellow).bold(),
+                ));
+                group = row_group;
+            }
+
+            let selected = idx.eq(&self.selected);
+            let label = match &row {
+                Row::Setting(field) => field.label(),
+                Row::Variable(name) | Row::EnvVariable(name) => name.as_str(),
+                Row::NewVariable => "New variable",
+            };
+            let label = format!(" {label:<LABEL_WIDTH$}");
+            let label_width = label.chars().count();
+            let label = match selected {
+                true => label.fg(self.colors.normal.white).bold(),
+                false => label.fg(self.colors.bright.black),
+            };
+            let value = match (selected, self.editing.as_ref(), &row) {
+                (true, Some(typed), _) => {
+                    let col = label_width.add(typed.chars().count());
+                    cursor = Some((col as u16, lines.len() as u16));
+                    typed.clone().fg(self.colors.normal.white)
+                }
+                (_, _, Row::Setting(field)) => self.value_span(*field),
+                (_, _, Row::Variable(name)) => self
+                    .variable(name)
+                    .unwrap_or_default()
+                    .fg(self.colors.normal.white),
+                (_, _, Row::NewVariable) => "name=value".fg(self.colors.bright.black),
+                (_, _, Row::EnvVariable(name)) => self.env_variable_span(name),
+            };
+
+            let mut line = Line::from(vec![label, value]);
+            if selected {
+                line = line.patch_style(
+                    self.colors
+                        .highlight(self.colors.normal.white, self.colors.primary.hover),
+                );
+            }
+            lines.push(line);
+
+            if let (true, Some(error)) = (selected, self.error.as_ref()) {
+                lines.push(Line::from(
+                    format!(" {:<LABEL_WIDTH$}{error}", "").fg(self.colors.normal.red),
+                ));
+            }
+        }
+
+        (lines, cursor)
+    }
+}
+
+impl Renderable for CollectionSettings<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let (lines, cursor) = self.lines();
+        let width = u16::min(size.width.saturating_sub(4), 64);
+        let height = u16::min(size.height, (lines.len() as u16).add(4));
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.sub(height).div(2)),
+            width,
+            height,
+        );
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Collection settings ".fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let list_size = Rect::new(
+            inner.x,
+            inner.y,
+            inner.width,
+            inner.height.saturating_sub(1),
+        );
+        frame.render_widget(Paragraph::new(lines), list_size);
+        if let Some((col, row)) = cursor {
+            frame.set_cursor(list_size.x.add(col), list_size.y.add(row));
+        }
+
+        let hint = match (self.editing.as_ref(), self.row()) {
+            (Some(_), _) => "[enter -> confirm] [esc -> cancel]",
+            (None, Row::EnvVariable(_)) => "[s -> toggle secret] [j/k -> move] [esc -> close]",
+            (None, _) => "[enter -> edit] [j/k -> move] [esc -> close]",
+        };
+        let hint_size = Rect::new(inner.x, inner.bottom().saturating_sub(1), inner.width, 1);
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_size,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for CollectionSettings<'_> {
+    type Result = CollectionSettingsEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(CollectionSettingsEvent::Quit));
+        }
+
+        if let Some(typed) = self.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Enter => return Ok(self.confirm()),
+                // leaving the field checks it the same as confirming, and
+                // only moves on when it is valid
+                KeyCode::Tab | KeyCode::Down | KeyCode::BackTab | KeyCode::Up => {
+                    let event = self.confirm();
+                    if self.editing.is_none() {
+                        match key_event.code {
+                            KeyCode::Tab | KeyCode::Down => self.select(self.selected.add(1)),
+                            _ => self.select(self.selected.saturating_sub(1)),
+                        }
+                    }
+                    return Ok(event);
+                }
+                KeyCode::Esc => {
+                    self.editing = None;
+                    self.error = None;
+                }
+                KeyCode::Backspace => _ = typed.pop(),
+                KeyCode::Char(c) => typed.push(c),
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(CollectionSettingsEvent::Close)),
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Tab => self.select(self.selected.add(1)),
+            KeyCode::Char('k') | KeyCode::Up | KeyCode::BackTab => {
+                self.select(self.selected.saturating_sub(1))
+            }
+            KeyCode::Enter => return Ok(self.activate()),
+            KeyCode::Char('s') => {
+                if let Row::EnvVariable(name) = self.row() {
+                    if self.collection_store.borrow_mut().toggle_secret(&name) {
+                        return Ok(Some(CollectionSettingsEvent::Changed));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let Some(typed) = self.editing.as_mut() {
+            typed.push_str(&input::single_line(&text));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn make_store() -> Rc<RefCell<CollectionStore>> {
+        let collection: Collection =
+            serde_json::from_str(r#"{ "info": { "name": "users" }, "base_url": "{{host}}" }"#)
+                .unwrap();
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        Rc::new(RefCell::new(store))
+    }
+
+    fn press(settings: &mut CollectionSettings, code: KeyCode) -> Option<CollectionSettingsEvent> {
+        settings
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    fn collection(store: &Rc<RefCell<CollectionStore>>) -> Collection {
+        store.borrow().get_collection().unwrap().borrow().clone()
+    }
+
+    #[test]
+    fn test_invalid_values_are_refused_when_confirmed() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = make_store();
+        let mut settings = CollectionSettings::new(&colors, &config, store.clone());
+
+        // moving to the timeout and typing something that isn't a number
+        press(&mut settings, KeyCode::Char('j'));
+        press(&mut settings, KeyCode::Enter);
+        settings.handle_paste("3s".into()).unwrap();
+        assert_eq!(press(&mut settings, KeyCode::Enter), None);
+        assert_eq!(
+            settings.error.as_deref(),
+            Some("must be a whole number of milliseconds")
+        );
+        // leaving the field is refused the same way
+        assert_eq!(press(&mut settings, KeyCode::Tab), None);
+        assert!(settings.editing.is_some());
+        assert_eq!(collection(&store).timeout_ms, None);
+
+        press(&mut settings, KeyCode::Backspace);
+        press(&mut settings, KeyCode::Char('0'));
+        press(&mut settings, KeyCode::Char('0'));
+        press(&mut settings, KeyCode::Char('0'));
+        assert_eq!(
+            press(&mut settings, KeyCode::Tab),
+            Some(CollectionSettingsEvent::Changed)
+        );
+        assert_eq!(settings.error, None);
+        assert_eq!(settings.row(), Row::Setting(Field::Proxy));
+        assert_eq!(collection(&store).timeout_ms, Some(3000));
+
+        press(&mut settings, KeyCode::Enter);
+        settings.handle_paste("localhost:3128".into()).unwrap();
+        press(&mut settings, KeyCode::Enter);
+        assert!(settings.error.is_some());
+        // esc gives up on the edit, leaving the proxy as it was
+        press(&mut settings, KeyCode::Esc);
+        assert_eq!(collection(&store).proxy, None);
+        assert_eq!(
+            press(&mut settings, KeyCode::Esc),
+            Some(CollectionSettingsEvent::Close)
+        );
+    }
+
+    #[test]
+    fn test_clearing_and_toggling_settings() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = make_store();
+        let mut settings = CollectionSettings::new(&colors, &config, store.clone());
+
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(settings.editing.as_deref(), Some("{{host}}"));
+        for _ in 0.."{{host}}".len() {
+            press(&mut settings, KeyCode::Backspace);
+        }
+        assert_eq!(
+            press(&mut settings, KeyCode::Enter),
+            Some(CollectionSettingsEvent::Changed)
+        );
+        assert_eq!(collection(&store).base_url, None);
+
+        for _ in 0..FIELDS.len().sub(1) {
+            press(&mut settings, KeyCode::Char('j'));
+        }
+        assert_eq!(settings.row(), Row::Setting(Field::ConfirmDestructive));
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(collection(&store).confirm_destructive, Some(true));
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(collection(&store).confirm_destructive, Some(false));
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(collection(&store).confirm_destructive, None);
+    }
+
+    #[test]
+    fn test_size_limits_fall_back_to_the_config() {
+        let colors = hac_colors::Colors::default();
+        let mut config = hac_config::load_config();
+        config.max_request_body = 5 * 1024 * 1024;
+        config.max_body_in_memory = 0;
+        let store = make_store();
+        let mut settings = CollectionSettings::new(&colors, &config, store.clone());
+
+        assert_eq!(
+            settings.value_span(Field::MaxRequestBody).content,
+            "5.0 MiB (config)"
+        );
+        assert_eq!(
+            settings.value_span(Field::MaxBodyInMemory).content,
+            "no limit (config)"
+        );
+
+        for _ in 0..5 {
+            press(&mut settings, KeyCode::Char('j'));
+        }
+        assert_eq!(settings.row(), Row::Setting(Field::MaxRequestBody));
+        press(&mut settings, KeyCode::Enter);
+        settings.handle_paste("5MB".into()).unwrap();
+        assert_eq!(press(&mut settings, KeyCode::Enter), None);
+        assert_eq!(
+            settings.error.as_deref(),
+            Some("must be a whole number of bytes, 0 for no limit")
+        );
+
+        settings.editing = Some("0".into());
+        assert_eq!(
+            press(&mut settings, KeyCode::Enter),
+            Some(CollectionSettingsEvent::Changed)
+        );
+        assert_eq!(collection(&store).max_request_body, Some(0));
+        assert_eq!(settings.value_span(Field::MaxRequestBody).content, "0");
+    }
+
+    #[test]
+    fn test_editing_host_overrides() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = make_store();
+        let mut settings = CollectionSettings::new(&colors, &config, store.clone());
+
+        for _ in 0..4 {
+            press(&mut settings, KeyCode::Char('j'));
+        }
+        assert_eq!(settings.row(), Row::Setting(Field::Resolve));
+        assert_eq!(settings.value_span(Field::Resolve).content, "not set");
+
+        press(&mut settings, KeyCode::Enter);
+        settings
+            .handle_paste("api.example.com:443:10.0.0.7, api.example.com:80".into())
+            .unwrap();
+        assert_eq!(press(&mut settings, KeyCode::Enter), None);
+        assert_eq!(
+            settings.error.as_deref(),
+            Some("must be written as host:port:address, separated by commas")
+        );
+        assert_eq!(collection(&store).resolve, None);
+
+        settings.handle_paste(":[::1]".into()).unwrap();
+        assert_eq!(
+            press(&mut settings, KeyCode::Enter),
+            Some(CollectionSettingsEvent::Changed)
+        );
+        assert_eq!(
+            collection(&store).resolve,
+            Some(vec![
+                "api.example.com:443:10.0.0.7".parse().unwrap(),
+                "api.example.com:80:::1".parse().unwrap(),
+            ])
+        );
+        assert_eq!(
+            settings.value_span(Field::Resolve).content,
+            "api.example.com:443:10.0.0.7, api.example.com:80:[::1]"
+        );
+
+        // editing starts with what is there, and emptying it unsets them
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(
+            settings.editing.as_deref(),
+            Some("api.example.com:443:10.0.0.7, api.example.com:80:[::1]")
+        );
+        settings.editing = Some(String::new());
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(collection(&store).resolve, None);
+    }
+
+    #[test]
+    fn test_adding_renaming_and_removing_variables() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = make_store();
+        let mut settings = CollectionSettings::new(&colors, &config, store.clone());
+
+        // the row to add variables comes right after the settings
+        for _ in 0..FIELDS.len().add(3) {
+            press(&mut settings, KeyCode::Char('j'));
+        }
+        assert_eq!(settings.row(), Row::NewVariable);
+
+        press(&mut settings, KeyCode::Enter);
+        settings.handle_paste("tenant acme".into()).unwrap();
+        assert_eq!(press(&mut settings, KeyCode::Enter), None);
+        assert_eq!(
+            settings.error.as_deref(),
+            Some("must be written as name=value")
+        );
+        press(&mut settings, KeyCode::Esc);
+
+        press(&mut settings, KeyCode::Enter);
+        settings.handle_paste(" version = v2 ".into()).unwrap();
+        assert_eq!(
+            press(&mut settings, KeyCode::Enter),
+            Some(CollectionSettingsEvent::Changed)
+        );
+        press(&mut settings, KeyCode::Char('j'));
+        press(&mut settings, KeyCode::Enter);
+        settings.handle_paste("tenant=acme".into()).unwrap();
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(settings.row(), Row::Variable("tenant".into()));
+        assert_eq!(
+            collection(&store).variables,
+            Some(BTreeMap::from([
+                ("tenant".to_string(), "acme".to_string()),
+                ("version".to_string(), "v2".to_string()),
+            ]))
+        );
+        // they take part in resolving requests below the environment
+        assert_eq!(
+            store.borrow().get_variables().get("version"),
+            Some(&"v2".to_string())
+        );
+
+        // renaming keeps the value where it was typed
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(settings.editing.as_deref(), Some("tenant=acme"));
+        settings.editing = Some("$tenant=acme".into());
+        press(&mut settings, KeyCode::Enter);
+        assert!(settings.error.is_some());
+        settings.editing = Some("tenant_id=acme".into());
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(settings.row(), Row::Variable("tenant_id".into()));
+
+        // and clearing it removes the variable, the next one is selected
+        press(&mut settings, KeyCode::Enter);
+        settings.editing = Some(String::new());
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(settings.row(), Row::Variable("version".into()));
+        press(&mut settings, KeyCode::Enter);
+        settings.editing = Some(String::new());
+        press(&mut settings, KeyCode::Enter);
+        assert_eq!(collection(&store).variables, None);
+        assert_eq!(settings.row(), Row::NewVariable);
+    }
+
+    #[test]
+    fn test_marking_environment_variables_as_secret() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let with_environment: Collection = serde_json::from_str(
+            r#"{
+                "info": { "name": "users" },
+                "environments": [{ "name": "prod", "variables": { "token": "hunter2" } }]
+            }"#,
+        )
+        .unwrap();
+        let mut store = CollectionStore::default();
+        store.set_state(with_environment);
+        let store = Rc::new(RefCell::new(store));
+        let mut settings = CollectionSettings::new(&colors, &config, store.clone());
+
+        for _ in 0..FIELDS.len().add(1) {
+            press(&mut settings, KeyCode::Char('j'));
+        }
+        assert_eq!(settings.row(), Row::EnvVariable("token".into()));
+        assert_eq!(settings.env_variable_span("token").content, "hunter2");
+
+        assert_eq!(
+            press(&mut settings, KeyCode::Char('s')),
+            Some(CollectionSettingsEvent::Changed)
+        );
+        let environment = collection(&store).environments.unwrap().remove(0);
+        assert!(environment.secrets.contains("token"));
+        assert!(!environment.variables.contains_key("token"));
+        // the value is still used for requests, it just isn't shown
+        assert_eq!(settings.env_variable_span("token").content, MASK);
+        assert_eq!(
+            store.borrow().get_variables().get("token"),
+            Some(&"hunter2".to_string())
+        );
+
+        press(&mut settings, KeyCode::Char('s'));
+        let environment = collection(&store).environments.unwrap().remove(0);
+        assert!(environment.secrets.is_empty());
+        assert_eq!(
+            environment.variables.get("token"),
+            Some(&"hunter2".to_string())
+        );
+    }
+}
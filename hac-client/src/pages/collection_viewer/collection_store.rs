@@ -1,14 +1,24 @@
-use hac_core::collection::types::{Request, RequestKind};
+use hac_core::captures::{self, CaptureResult};
+use hac_core::collection::types::{parse_tags, Request, RequestKind};
 use hac_core::collection::Collection;
+use hac_core::hooks::{self, Hooks, TrustedHooks};
+use hac_core::search;
+use hac_core::secrets::Secrets;
+use hac_core::templates::{RequestTemplate, Templates};
+use hac_core::url_history::UrlHistory;
+use hac_core::variables::Layers;
 
 use crate::pages::collection_viewer::collection_viewer::CollectionViewerOverlay;
 use crate::pages::collection_viewer::collection_viewer::PaneFocus;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
+/// how many requests are remembered besides the one open
+const MAX_RECENT_REQUESTS: usize = 9;
+
 #[derive(Debug)]
 pub struct CollectionState {
     collection: Rc<RefCell<Collection>>,
@@ -19,11 +29,34 @@ pub struct CollectionState {
     focused_pane: PaneFocus,
     has_pending_request: bool,
     overlay_stack: Vec<CollectionViewerOverlay>,
+    /// name of the environment used to resolve variables, if any
+    active_environment: Option<String>,
+    /// everything captured since the collection was opened, shadowing the
+    /// active environment. Captures that are not persisted only live here
+    captured: BTreeMap<String, String>,
+    /// last captures of each request, keyed by the request id
+    capture_results: HashMap<String, Vec<CaptureResult>>,
+    /// narrows the sidebar down to the matching requests, empty when not
+    /// filtering
+    sidebar_filter: String,
+    /// ids of the requests opened on the collection, the one open first
+    recent_requests: Vec<String>,
 }
 
 #[derive(Debug, Default)]
 pub struct CollectionStore {
     state: Option<Rc<RefCell<CollectionState>>>,
+    /// outlives the state, as secrets of every collection share a file
+    secrets: Secrets,
+    /// request templates, shared by every collection as well
+    templates: Templates,
+    /// collections allowed to run their hook commands
+    trusted_hooks: TrustedHooks,
+    /// urls sent from the uri bar of any collection
+    url_history: UrlHistory,
+    /// nothing is sent while offline, it outlives the state as it is
+    /// toggled for the whole application
+    offline: bool,
 }
 
 #[derive(Debug)]
@@ -37,10 +70,43 @@ pub enum CollectionStoreAction {
     SetFocusedPane(PaneFocus),
     SetSelectedPane(Option<PaneFocus>),
     SetPendingRequest(bool),
+    /// applies the captures of the request with the given id to the
+    /// variables, writing persisted ones to the active environment
+    ApplyCaptures(String, Vec<CaptureResult>),
 }
 
 impl CollectionStore {
-    pub fn set_state(&mut self, collection: Collection) {
+    pub fn set_secrets(&mut self, secrets: Secrets) {
+        self.secrets = secrets;
+    }
+
+    pub fn set_templates(&mut self, templates: Templates) {
+        self.templates = templates;
+    }
+
+    pub fn set_trusted_hooks(&mut self, trusted_hooks: TrustedHooks) {
+        self.trusted_hooks = trusted_hooks;
+    }
+
+    pub fn set_url_history(&mut self, url_history: UrlHistory) {
+        self.url_history = url_history;
+    }
+
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn set_state(&mut self, mut collection: Collection) {
+        // secrets written straight into the file are moved out of it, they
+        // are gone from the collection the next time it is synced
+        if self.secrets.take_from(&mut collection) {
+            self.save_secrets();
+        }
+
         let selected_request = collection.requests.as_ref().and_then(|requests| {
             requests.read().unwrap().first().and_then(|req| {
                 if let RequestKind::Single(req) = req {
@@ -56,7 +122,19 @@ impl CollectionStore {
             .as_ref()
             .and_then(|items| items.read().unwrap().first().map(|item| item.get_id()));
 
+        let active_environment = collection
+            .environments
+            .as_ref()
+            .and_then(|envs| envs.first())
+            .map(|env| env.name.clone());
+
+        let recent_requests = selected_request
+            .iter()
+            .map(|request| request.read().unwrap().id.clone())
+            .collect();
+
         let state = CollectionState {
+            recent_requests,
             selected_request,
             hovered_request,
             dirs_expanded: Rc::new(RefCell::new(HashMap::default())),
@@ -65,6 +143,10 @@ impl CollectionStore {
             selected_pane: None,
             has_pending_request: false,
             overlay_stack: vec![],
+            active_environment,
+            captured: BTreeMap::default(),
+            capture_results: HashMap::default(),
+            sidebar_filter: String::new(),
         };
 
         self.state = Some(Rc::new(RefCell::new(state)));
@@ -74,7 +156,14 @@ impl CollectionStore {
         if let Some(ref state) = self.state {
             match action {
                 CollectionStoreAction::SetSelectedRequest(maybe_req) => {
-                    state.borrow_mut().selected_request = maybe_req
+                    let mut state = state.borrow_mut();
+                    if let Some(request) = maybe_req.as_ref() {
+                        let id = request.read().unwrap().id.clone();
+                        state.recent_requests.retain(|recent| recent.ne(&id));
+                        state.recent_requests.insert(0, id);
+                        state.recent_requests.truncate(MAX_RECENT_REQUESTS + 1);
+                    }
+                    state.selected_request = maybe_req
                 }
                 CollectionStoreAction::SetHoveredRequest(maybe_req_id) => {
                     state.borrow_mut().hovered_request = maybe_req_id
@@ -107,6 +196,35 @@ impl CollectionStore {
                 CollectionStoreAction::SetPendingRequest(is_pending) => {
                     state.borrow_mut().has_pending_request = is_pending;
                 }
+                CollectionStoreAction::ApplyCaptures(request_id, results) => {
+                    let mut state = state.borrow_mut();
+                    captures::apply(&results, &mut state.captured);
+
+                    let persisted = results
+                        .iter()
+                        .filter(|result| result.capture.persist)
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    if let Some(env_name) = state.active_environment.clone() {
+                        let mut collection = state.collection.borrow_mut();
+                        let key = Secrets::key(&collection);
+                        if let Some(environment) = collection
+                            .environments
+                            .iter_mut()
+                            .flatten()
+                            .find(|env| env.name.eq(&env_name))
+                        {
+                            // secrets go to the secrets file instead, so they
+                            // never end up on the collection
+                            if self.secrets.apply_captures(&key, environment, &persisted) {
+                                self.save_secrets();
+                            }
+                        }
+                    }
+
+                    state.capture_results.insert(request_id, results);
+                }
             }
         }
     }
@@ -117,6 +235,145 @@ impl CollectionStore {
             .and_then(|state| state.borrow().selected_request.clone())
     }
 
+    pub fn get_request_by_id(&self, id: &str) -> Option<Arc<RwLock<Request>>> {
+        self.get_collection()?
+            .borrow()
+            .flatten_requests()
+            .into_iter()
+            .find(|req| req.read().unwrap().id.eq(id))
+    }
+
+    /// the requests opened last, most recent first and without the one
+    /// open. Requests deleted since are skipped
+    pub fn get_recent_requests(&self) -> Vec<Arc<RwLock<Request>>> {
+        let Some(state) = self.state.as_ref() else {
+            return vec![];
+        };
+        let selected = self
+            .get_selected_request()
+            .map(|request| request.read().unwrap().id.clone());
+
+        let recent_requests = state.borrow().recent_requests.clone();
+        recent_requests
+            .iter()
+            .filter(|id| selected.as_ref().ne(&Some(id)))
+            .filter_map(|id| self.get_request_by_id(id))
+            .take(MAX_RECENT_REQUESTS)
+            .collect()
+    }
+
+    /// selects and hovers the request with the given id, expanding the
+    /// directories it lives in so it shows up on the sidebar
+    pub fn select_request(&mut self, id: &str) {
+        let Some(request) = self.get_request_by_id(id) else {
+            return;
+        };
+
+        self.reveal(id);
+        self.dispatch(CollectionStoreAction::SetHoveredRequest(Some(id.into())));
+        self.dispatch(CollectionStoreAction::SetSelectedRequest(Some(request)));
+    }
+
+    /// expands every directory the item with the given id lives in
+    fn reveal(&mut self, id: &str) {
+        if let (Some(requests), Some(dirs_expanded)) =
+            (self.get_requests(), self.get_dirs_expanded())
+        {
+            let mut dirs_expanded = dirs_expanded.borrow_mut();
+            for kind in requests.read().unwrap().iter() {
+                if let RequestKind::Nested(dir) = kind {
+                    if dir
+                        .flatten_requests()
+                        .iter()
+                        .any(|req| req.read().unwrap().id.eq(id))
+                    {
+                        dirs_expanded.insert(dir.id.clone(), true);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn get_sidebar_filter(&self) -> String {
+        self.state
+            .as_ref()
+            .map(|state| state.borrow().sidebar_filter.clone())
+            .unwrap_or_default()
+    }
+
+    /// narrows the sidebar down to `filter`, moving the hover to the first
+    /// visible item when the hovered one gets filtered out. Clearing the
+    /// filter expands the directories of the hovered item, as it may have
+    /// been found inside of a collapsed one
+    pub fn set_sidebar_filter(&mut self, filter: &str) {
+        let Some(state) = self.state.as_ref() else {
+            return;
+        };
+        state.borrow_mut().sidebar_filter = filter.to_string();
+
+        let hovered = self.get_hovered_request();
+        match self.get_filtered_items() {
+            Some(items) => {
+                let is_visible = hovered
+                    .as_ref()
+                    .is_some_and(|id| items.iter().any(|item| item.get_id().eq(id)));
+                if !is_visible {
+                    let first = items
+                        .iter()
+                        .find(|item| matches!(item, RequestKind::Single(_)))
+                        .or(items.first())
+                        .map(|item| item.get_id());
+                    self.dispatch(CollectionStoreAction::SetHoveredRequest(first));
+                }
+            }
+            None => match hovered {
+                Some(id) => self.reveal(&id),
+                None => self.dispatch(CollectionStoreAction::SetHoveredRequest(
+                    self.get_requests()
+                        .and_then(|items| items.read().unwrap().first().map(|item| item.get_id())),
+                )),
+            },
+        }
+    }
+
+    /// items the sidebar shows while filtering, in the order they are drawn,
+    /// or `None` when there is no filter
+    pub fn get_filtered_items(&self) -> Option<Vec<RequestKind>> {
+        let filter = self.get_sidebar_filter();
+        if filter.is_empty() {
+            return None;
+        }
+
+        let (tags, text) = split_filter(&filter);
+        let mut items = vec![];
+        if let Some(requests) = self.get_requests() {
+            filter_items(&requests.read().unwrap(), &tags, &text, &mut items);
+        }
+        Some(items)
+    }
+
+    /// makes the environment with the given name the active one, dropping
+    /// what was captured on the previous one. Unknown names are ignored
+    pub fn select_environment(&mut self, name: &str) {
+        let Some(state) = self.state.as_ref() else {
+            return;
+        };
+
+        let mut state = state.borrow_mut();
+        if state.collection.borrow().find_environment(name).is_none() {
+            return;
+        }
+
+        state.active_environment = Some(name.into());
+        state.captured.clear();
+    }
+
+    pub fn get_active_environment(&self) -> Option<String> {
+        self.state
+            .as_ref()
+            .and_then(|state| state.borrow().active_environment.clone())
+    }
+
     pub fn get_focused_pane(&self) -> PaneFocus {
         self.state
             .as_ref()
@@ -137,12 +394,33 @@ impl CollectionStore {
             .and_then(|state| state.borrow().hovered_request.clone())
     }
 
+    /// nothing on a read-only collection is written back to its file
+    pub fn is_read_only(&self) -> bool {
+        self.get_collection()
+            .is_some_and(|collection| collection.borrow().is_read_only())
+    }
+
     pub fn get_collection(&self) -> Option<Rc<RefCell<Collection>>> {
         self.state
             .as_ref()
             .map(|state| state.borrow().collection.clone())
     }
 
+    /// `request` with the collection base url and default headers applied,
+    /// everything that sends or shows the final request goes through here
+    pub fn with_defaults(&self, request: &Request) -> Request {
+        match self.get_collection() {
+            Some(collection) => collection.borrow().apply_defaults(request),
+            None => request.clone(),
+        }
+    }
+
+    pub fn hooks_for(&self, request: &Request) -> Hooks {
+        self.get_collection()
+            .map(|collection| collection.borrow().hooks_for(request))
+            .unwrap_or_default()
+    }
+
     pub fn get_dirs_expanded(&mut self) -> Option<Rc<RefCell<HashMap<String, bool>>>> {
         self.state
             .as_mut()
@@ -193,13 +471,296 @@ impl CollectionStore {
         })
     }
 
+    /// the first of `names` that is a secret of the active environment, used
+    /// to ask for secrets that have no value yet
+    pub fn find_secret(&self, names: &[String]) -> Option<String> {
+        let state = self.state.as_ref()?.borrow();
+        let env_name = state.active_environment.as_ref()?;
+        let collection = state.collection.borrow();
+        let environment = collection.find_environment(env_name)?;
+        names
+            .iter()
+            .find(|name| environment.secrets.contains(*name))
+            .cloned()
+    }
+
+    /// whether `name` is a secret of the active environment
+    pub fn is_secret(&self, name: &str) -> bool {
+        self.find_secret(&[name.to_string()]).is_some()
+    }
+
+    /// values of the secrets of the active environment, so they can be
+    /// masked wherever they end up
+    pub fn secret_values(&self) -> Vec<String> {
+        let variables = self.get_variables();
+        let Some(state) = self.state.as_ref().map(|state| state.borrow()) else {
+            return vec![];
+        };
+        let Some(env_name) = state.active_environment.as_ref() else {
+            return vec![];
+        };
+        let collection = state.collection.borrow();
+        let Some(environment) = collection.find_environment(env_name) else {
+            return vec![];
+        };
+
+        environment
+            .secrets
+            .iter()
+            .filter_map(|name| variables.get(name))
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// sets the value of a secret of the active environment, saving it to
+    /// the secrets file right away
+    pub fn set_secret(&mut self, name: &str, value: String) {
+        let Some(state) = self.state.as_ref() else {
+            return;
+        };
+
+        let mut state = state.borrow_mut();
+        let Some(env_name) = state.active_environment.clone() else {
+            return;
+        };
+        let key = Secrets::key(&state.collection.borrow());
+        self.secrets.insert(&key, &env_name, name, value);
+        // a value captured before would keep shadowing the new one
+        state.captured.remove(name);
+        drop(state);
+
+        self.save_secrets();
+    }
+
+    /// every variable of the active environment by name, secrets included,
+    /// along with whether it is a secret
+    pub fn environment_variables(&self) -> Vec<(String, bool)> {
+        let Some(state) = self.state.as_ref().map(|state| state.borrow()) else {
+            return vec![];
+        };
+        let Some(env_name) = state.active_environment.as_ref() else {
+            return vec![];
+        };
+        let collection = state.collection.borrow();
+        let Some(environment) = collection.find_environment(env_name) else {
+            return vec![];
+        };
+
+        let mut names = environment
+            .variables
+            .keys()
+            .map(|name| (name.clone(), false))
+            .collect::<BTreeMap<_, _>>();
+        names.extend(environment.secrets.iter().map(|name| (name.clone(), true)));
+        names.into_iter().collect()
+    }
+
+    /// marks a variable of the active environment as secret, moving its
+    /// value to the secrets file, or turns a secret back into a variable
+    /// written on the collection. Returns whether anything changed
+    pub fn toggle_secret(&mut self, name: &str) -> bool {
+        let Some(state) = self.state.as_ref() else {
+            return false;
+        };
+
+        let state = state.borrow();
+        let Some(env_name) = state.active_environment.clone() else {
+            return false;
+        };
+        let collection = state.collection.clone();
+        let mut collection = collection.borrow_mut();
+        let key = Secrets::key(&collection);
+        let Some(environment) = collection
+            .environments
+            .iter_mut()
+            .flatten()
+            .find(|env| env.name.eq(&env_name))
+        else {
+            return false;
+        };
+
+        if environment.secrets.remove(name) {
+            // a secret that never got a value becomes an empty variable, so
+            // it isn't lost along the way
+            let value = self
+                .secrets
+                .remove(&key, &env_name, name)
+                .unwrap_or_default();
+            environment.variables.insert(name.into(), value);
+        } else {
+            environment.secrets.insert(name.into());
+            if let Some(value) = environment.variables.remove(name) {
+                self.secrets.insert(&key, &env_name, name, value);
+            }
+        }
+        drop(collection);
+        drop(state);
+
+        self.save_secrets();
+        true
+    }
+
+    /// keeps the values of secret auth fields on the secrets file, called
+    /// before `collection` is written as they are left out of it
+    pub fn keep_auth_secrets(&mut self, collection: &Collection) {
+        if self.secrets.keep_auth(collection) {
+            self.save_secrets();
+        }
+    }
+
+    pub fn get_templates(&self) -> Vec<RequestTemplate> {
+        self.templates.list().to_vec()
+    }
+
+    /// templates are saved to the templates file on every change, as they
+    /// are not tied to the collection being synced
+    pub fn save_template(&mut self, template: RequestTemplate) {
+        self.templates.add(template);
+        self.save_templates();
+    }
+
+    pub fn rename_template(&mut self, idx: usize, name: String) {
+        self.templates.rename(idx, name);
+        self.save_templates();
+    }
+
+    pub fn remove_template(&mut self, idx: usize) {
+        self.templates.remove(idx);
+        self.save_templates();
+    }
+
+    /// whether the hooks of the collection, as they are now, were trusted
+    pub fn hooks_trusted(&self) -> bool {
+        self.get_collection()
+            .is_some_and(|collection| self.trusted_hooks.is_trusted(&collection.borrow()))
+    }
+
+    /// every hook command trusting the collection allows to run
+    pub fn hook_commands(&self) -> Vec<String> {
+        self.get_collection()
+            .map(|collection| hooks::commands(&collection.borrow()))
+            .unwrap_or_default()
+    }
+
+    pub fn trust_hooks(&mut self) {
+        let Some(collection) = self.get_collection() else {
+            return;
+        };
+        self.trusted_hooks.trust(&collection.borrow());
+        if let Err(e) = self.trusted_hooks.save() {
+            tracing::error!("failed to save trusted hooks: {e:?}");
+        }
+    }
+
+    /// remembers a url sent from the collection, saving the history right
+    /// away like templates
+    pub fn record_url(&mut self, url: &str) {
+        let Some(collection) = self.get_collection() else {
+            return;
+        };
+        let path = collection.borrow().path.clone();
+        let secrets = self.secret_values();
+        self.url_history.record(path, url, &secrets);
+        if let Err(e) = self.url_history.save() {
+            tracing::error!("failed to save url history: {e:?}");
+        }
+    }
+
+    /// urls starting with `typed`, the ones sent most often and most
+    /// recently first, followed by the uris of the other requests on the
+    /// collection. `global` suggests urls sent from any collection
+    pub fn url_suggestions(&self, typed: &str, global: bool) -> Vec<String> {
+        let Some(collection) = self.get_collection() else {
+            return vec![];
+        };
+        let collection = collection.borrow();
+        let scope = (!global).then_some(&collection.path);
+
+        let mut urls = self.url_history.suggestions(typed, scope);
+        let typed_lowercase = typed.to_lowercase();
+        for request in collection.flatten_requests() {
+            let uri = request.read().unwrap().uri.clone();
+            if uri.to_lowercase().starts_with(&typed_lowercase) && !urls.contains(&uri) {
+                urls.push(uri);
+            }
+        }
+        urls.retain(|url| url.ne(typed));
+        urls
+    }
+
+    fn save_templates(&self) {
+        if let Err(e) = self.templates.save() {
+            tracing::error!("failed to save templates: {e:?}");
+        }
+    }
+
+    fn save_secrets(&self) {
+        if let Err(e) = self.secrets.save() {
+            tracing::error!("failed to save secrets: {e:?}");
+        }
+    }
+
+    /// every variable requests are resolved with, the ones of the active
+    /// environment shadow the ones of the collection
+    pub fn get_variables(&self) -> BTreeMap<String, String> {
+        self.get_layers().merged()
+    }
+
+    /// the variables of the collection and of the active environment, kept
+    /// apart so it can be told where each value comes from. Built the way
+    /// runs build them, with the captures of the session on top of the
+    /// environment
+    pub fn get_layers(&self) -> Layers {
+        let Some(state) = self.state.as_ref().map(|state| state.borrow()) else {
+            return Layers::default();
+        };
+
+        let collection = state.collection.borrow();
+        let mut layers = collection
+            .layers(state.active_environment.as_deref(), &self.secrets)
+            .or_else(|_| collection.layers(None, &self.secrets))
+            .unwrap_or_default();
+        layers.environment.extend(state.captured.clone());
+        layers
+    }
+
+    pub fn get_capture_results(&self, request_id: &str) -> Vec<CaptureResult> {
+        self.state
+            .as_ref()
+            .and_then(|state| state.borrow().capture_results.get(request_id).cloned())
+            .unwrap_or_default()
+    }
+
     pub fn has_pending_request(&self) -> bool {
         self.state
             .as_ref()
             .is_some_and(|state| state.borrow().has_pending_request)
     }
 
+    /// while filtering, the hover walks the filtered items instead of the tree
+    fn hover_filtered(&mut self, items: Vec<RequestKind>, visit: VisitNode) {
+        let position = self
+            .get_hovered_request()
+            .and_then(|id| items.iter().position(|item| item.get_id().eq(&id)));
+        let next = match (position, visit) {
+            (None, _) => items.first(),
+            (Some(idx), VisitNode::Prev) => idx.checked_sub(1).and_then(|idx| items.get(idx)),
+            (Some(idx), _) => items.get(idx + 1),
+        };
+
+        if let Some(next) = next {
+            self.dispatch(CollectionStoreAction::SetHoveredRequest(Some(
+                next.get_id(),
+            )));
+        }
+    }
+
     fn maybe_hover_prev(&mut self) {
+        if let Some(items) = self.get_filtered_items() {
+            return self.hover_filtered(items, VisitNode::Prev);
+        }
+
         if self.get_requests().is_some() {
             let requests = self.get_requests().unwrap();
 
@@ -226,6 +787,10 @@ impl CollectionStore {
     }
 
     fn maybe_hover_next(&mut self) {
+        if let Some(items) = self.get_filtered_items() {
+            return self.hover_filtered(items, VisitNode::Next);
+        }
+
         if self.get_requests().is_some() {
             let requests = self.get_requests().unwrap();
 
@@ -250,6 +815,15 @@ impl CollectionStore {
     }
 
     pub fn find_hovered_request(&mut self) -> RequestKind {
+        // filtered items are visible even inside of collapsed directories
+        if let Some(items) = self.get_filtered_items() {
+            let id = self.get_hovered_request().unwrap();
+            return items
+                .into_iter()
+                .find(|item| item.get_id().eq(&id))
+                .expect("attempting to find an unexisting request");
+        }
+
         get_request_by_id(
             &self.get_requests().as_ref().unwrap().read().unwrap(),
             &self.get_dirs_expanded().unwrap().borrow(),
@@ -278,6 +852,191 @@ impl CollectionStore {
             requests.first().map(|req| req.get_id()),
         ));
     }
+
+    /// moves the item around the tree, keeping it hovered and selected.
+    /// Returns whether anything moved, so the caller knows to sync the
+    /// collection
+    pub fn move_item(&mut self, item_id: &str, item_move: ItemMove) -> bool {
+        let Some(requests) = self.get_requests() else {
+            return false;
+        };
+        let mut requests = requests.write().unwrap();
+
+        match item_move {
+            ItemMove::Up => swap_with_sibling(&mut requests, item_id, true),
+            ItemMove::Down => swap_with_sibling(&mut requests, item_id, false),
+            ItemMove::Indent => match indent(&mut requests, item_id) {
+                // the item would be hidden inside of a collapsed directory,
+                // and hovering hidden items is not a thing
+                Some(dir_id) => {
+                    drop(requests);
+                    self.get_dirs_expanded()
+                        .unwrap()
+                        .borrow_mut()
+                        .insert(dir_id, true);
+                    true
+                }
+                None => false,
+            },
+            ItemMove::Unindent => unindent(&mut requests, item_id),
+        }
+    }
+}
+
+/// swaps the item with the sibling above or below it, on the root or inside
+/// of its directory
+fn swap_with_sibling(items: &mut [RequestKind], item_id: &str, up: bool) -> bool {
+    if let Some(idx) = items.iter().position(|item| item.get_id().eq(item_id)) {
+        let other = match up {
+            true => idx.checked_sub(1),
+            false => Some(idx + 1).filter(|other| other.lt(&items.len())),
+        };
+        return match other {
+            Some(other) => {
+                items.swap(idx, other);
+                true
+            }
+            None => false,
+        };
+    }
+
+    items.iter().any(|item| match item {
+        RequestKind::Nested(dir) => {
+            swap_with_sibling(&mut dir.requests.write().unwrap(), item_id, up)
+        }
+        RequestKind::Single(_) => false,
+    })
+}
+
+/// moves a request on the root into the directory above it, as its last
+/// child, or into the directory below it, as its first child. Returns the id
+/// of the directory it went into
+fn indent(items: &mut Vec<RequestKind>, item_id: &str) -> Option<String> {
+    let idx = items.iter().position(|item| item.get_id().eq(item_id))?;
+    let RequestKind::Single(request) = &items[idx] else {
+        return None;
+    };
+    let request = request.clone();
+
+    let above = idx.checked_sub(1).and_then(|above| match &items[above] {
+        RequestKind::Nested(dir) => Some(dir.clone()),
+        RequestKind::Single(_) => None,
+    });
+    let below = items.get(idx + 1).and_then(|below| match below {
+        RequestKind::Nested(dir) => Some(dir.clone()),
+        RequestKind::Single(_) => None,
+    });
+
+    let dir_id = match (above, below) {
+        (Some(dir), _) => {
+            dir.requests
+                .write()
+                .unwrap()
+                .push(RequestKind::Single(request.clone()));
+            dir.id
+        }
+        (None, Some(dir)) => {
+            dir.requests
+                .write()
+                .unwrap()
+                .insert(0, RequestKind::Single(request.clone()));
+            dir.id
+        }
+        (None, None) => return None,
+    };
+
+    items.remove(idx);
+    request.write().unwrap().parent = Some(dir_id.clone());
+    Some(dir_id)
+}
+
+/// moves a request out of its directory, the first child goes right above
+/// the directory and any other right below it, so it undoes `indent`
+fn unindent(items: &mut Vec<RequestKind>, item_id: &str) -> bool {
+    let found = items.iter().enumerate().find_map(|(dir_idx, item)| {
+        let RequestKind::Nested(dir) = item else {
+            return None;
+        };
+        let mut children = dir.requests.write().unwrap();
+        let idx = children
+            .iter()
+            .position(|child| child.get_id().eq(item_id))?;
+        let is_first = idx.eq(&0);
+        Some((dir_idx, is_first, children.remove(idx)))
+    });
+
+    let Some((dir_idx, is_first, item)) = found else {
+        return false;
+    };
+
+    if let RequestKind::Single(request) = &item {
+        request.write().unwrap().parent = None;
+    }
+    match is_first {
+        true => items.insert(dir_idx, item),
+        false => items.insert(dir_idx + 1, item),
+    }
+    true
+}
+
+/// how an item is moved around the sidebar tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemMove {
+    Up,
+    Down,
+    /// moves a request into the directory right next to it
+    Indent,
+    /// moves a request out of its directory, right next to it
+    Unindent,
+}
+
+/// words of the filter starting with `#` are tags the requests must carry,
+/// the rest is the text looked up on their names and uris
+fn split_filter(filter: &str) -> (Vec<String>, String) {
+    let (tags, words): (Vec<_>, Vec<_>) = filter
+        .split_whitespace()
+        .partition(|word| word.starts_with('#'));
+    (parse_tags(&tags.join(" ")), words.join(" "))
+}
+
+/// pushes the items carrying every one of `tags` and matching `text` by name
+/// or uri into `out`, along with the directories they live in so they keep
+/// their context. Returns whether anything matched
+fn filter_items(
+    items: &[RequestKind],
+    tags: &[String],
+    text: &str,
+    out: &mut Vec<RequestKind>,
+) -> bool {
+    let mut found = false;
+
+    for item in items {
+        match item {
+            RequestKind::Single(req) => {
+                let req = req.read().unwrap();
+                let has_tags = tags.iter().all(|tag| req.has_tag(tag));
+                if has_tags && (search::matches(&req.name, text) || search::matches(&req.uri, text))
+                {
+                    out.push(item.clone());
+                    found = true;
+                }
+            }
+            RequestKind::Nested(dir) => {
+                let mut children = vec![];
+                // directories have no tags, so they only show up for the
+                // requests in them when filtering by tags
+                if filter_items(&dir.requests.read().unwrap(), tags, text, &mut children)
+                    || (tags.is_empty() && search::matches(&dir.name, text))
+                {
+                    out.push(item.clone());
+                    out.extend(children);
+                    found = true;
+                }
+            }
+        }
+    }
+
+    found
 }
 
 #[derive(PartialEq)]
@@ -397,26 +1156,19 @@ mod tests {
             id: "root".to_string(),
             method: RequestMethod::Get,
             name: "Root1".to_string(),
-            auth_method: None,
-            parent: None,
-            headers: None,
             uri: "/root1".to_string(),
-            body_type: None,
-            body: None,
+            ..Default::default()
         })))
     }
 
     fn create_child_one() -> RequestKind {
         RequestKind::Single(Arc::new(RwLock::new(Request {
             id: "child_one".to_string(),
-            auth_method: None,
             parent: Some(String::from("dir")),
             method: RequestMethod::Post,
             name: "Child1".to_string(),
             uri: "/nested1/child1".to_string(),
-            headers: None,
-            body_type: None,
-            body: None,
+            ..Default::default()
         })))
     }
 
@@ -424,13 +1176,10 @@ mod tests {
         RequestKind::Single(Arc::new(RwLock::new(Request {
             id: "child_two".to_string(),
             method: RequestMethod::Put,
-            auth_method: None,
             name: "Child2".to_string(),
-            headers: None,
             parent: Some(String::from("dir")),
             uri: "/nested1/child2".to_string(),
-            body_type: None,
-            body: None,
+            ..Default::default()
         })))
     }
 
@@ -439,12 +1188,8 @@ mod tests {
             id: "not_used".to_string(),
             method: RequestMethod::Put,
             name: "NotUsed".to_string(),
-            parent: None,
-            auth_method: None,
-            headers: None,
             uri: "/not/used".to_string(),
-            body_type: None,
-            body: None,
+            ..Default::default()
         })))
     }
 
@@ -464,13 +1209,9 @@ mod tests {
         RequestKind::Single(Arc::new(RwLock::new(Request {
             id: "root_two".to_string(),
             method: RequestMethod::Delete,
-            auth_method: None,
-            headers: None,
-            parent: None,
             name: "Root2".to_string(),
             uri: "/root2".to_string(),
-            body_type: None,
-            body: None,
+            ..Default::default()
         })))
     }
 
@@ -556,4 +1297,335 @@ mod tests {
 
         assert!(next.is_none());
     }
+
+    #[test]
+    fn test_select_request_expands_its_directory() {
+        use hac_core::collection::types::Info;
+
+        let collection = Collection {
+            info: Info {
+                name: "tree".into(),
+                ..Default::default()
+            },
+            requests: Some(Arc::new(RwLock::new(create_test_tree()))),
+            ..Default::default()
+        };
+
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        store.select_request("child_two");
+
+        let selected = store.get_selected_request().unwrap();
+        assert_eq!(selected.read().unwrap().id, "child_two");
+        assert_eq!(store.get_hovered_request(), Some("child_two".into()));
+        assert_eq!(
+            store.get_dirs_expanded().unwrap().borrow().get("dir"),
+            Some(&true)
+        );
+
+        store.select_request("missing");
+        let selected = store.get_selected_request().unwrap();
+        assert_eq!(selected.read().unwrap().id, "child_two");
+    }
+
+    #[test]
+    fn test_recent_requests_are_most_recent_first() {
+        use hac_core::collection::types::Info;
+
+        let collection = Collection {
+            info: Info {
+                name: "tree".into(),
+                ..Default::default()
+            },
+            requests: Some(Arc::new(RwLock::new(create_test_tree()))),
+            ..Default::default()
+        };
+
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        let recent = |store: &CollectionStore| {
+            store
+                .get_recent_requests()
+                .iter()
+                .map(|request| request.read().unwrap().id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert!(recent(&store).is_empty());
+
+        store.select_request("child_one");
+        store.select_request("root_two");
+        store.select_request("root");
+        assert_eq!(recent(&store), vec!["root_two", "child_one"]);
+
+        store.select_request("child_one");
+        assert_eq!(recent(&store), vec!["root", "root_two"]);
+
+        store.remove_item("root_two".into());
+        assert_eq!(recent(&store), vec!["root"]);
+    }
+
+    #[test]
+    fn test_select_environment() {
+        use hac_core::collection::types::{Environment, Info};
+
+        let environment = |name: &str| Environment {
+            name: name.into(),
+            variables: BTreeMap::from([("host".to_string(), format!("{name}.api"))]),
+            secrets: Default::default(),
+        };
+        let collection = Collection {
+            info: Info {
+                name: "envs".into(),
+                ..Default::default()
+            },
+            environments: Some(vec![environment("dev"), environment("staging")]),
+            ..Default::default()
+        };
+
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        assert_eq!(store.get_active_environment(), Some("dev".into()));
+
+        store.select_environment("staging");
+        assert_eq!(store.get_active_environment(), Some("staging".into()));
+        assert_eq!(
+            store.get_variables().get("host"),
+            Some(&"staging.api".to_string())
+        );
+
+        store.select_environment("missing");
+        assert_eq!(store.get_active_environment(), Some("staging".into()));
+
+        // captures shadow the environment until another one is picked
+        let results = vec![CaptureResult {
+            capture: "host = $.host".parse().unwrap(),
+            value: Some("captured.api".into()),
+        }];
+        store.dispatch(CollectionStoreAction::ApplyCaptures("id".into(), results));
+        assert_eq!(
+            store.get_variables().get("host"),
+            Some(&"captured.api".to_string())
+        );
+        store.select_environment("dev");
+        let collection = store.get_collection().unwrap();
+        let expected = collection
+            .borrow()
+            .layers(Some("dev"), &Secrets::default())
+            .unwrap();
+        assert_eq!(store.get_layers(), expected);
+    }
+
+    #[test]
+    fn test_apply_captures_only_persists_marked_captures() {
+        use hac_core::collection::types::{Capture, Environment, Info};
+
+        let collection = Collection {
+            info: Info {
+                name: "auth".into(),
+                ..Default::default()
+            },
+            environments: Some(vec![Environment {
+                name: "dev".into(),
+                variables: BTreeMap::default(),
+                secrets: Default::default(),
+            }]),
+            ..Default::default()
+        };
+
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+
+        let mut persisted = "token = $.token".parse::<Capture>().unwrap();
+        persisted.persist = true;
+        let results = vec![
+            CaptureResult {
+                capture: persisted,
+                value: Some("secret".into()),
+            },
+            CaptureResult {
+                capture: "user = $.user".parse().unwrap(),
+                value: Some("hac".into()),
+            },
+        ];
+
+        store.dispatch(CollectionStoreAction::ApplyCaptures("id".into(), results));
+
+        let variables = store.get_variables();
+        assert_eq!(variables.get("token"), Some(&"secret".to_string()));
+        assert_eq!(variables.get("user"), Some(&"hac".to_string()));
+
+        let collection = store.get_collection().unwrap();
+        let collection = collection.borrow();
+        let environment = &collection.environments.as_ref().unwrap()[0];
+        assert_eq!(
+            environment.variables,
+            BTreeMap::from([("token".to_string(), "secret".to_string())])
+        );
+        assert_eq!(store.get_capture_results("id").len(), 2);
+    }
+
+    fn make_tree_store() -> CollectionStore {
+        use hac_core::collection::types::Info;
+
+        let collection = Collection {
+            info: Info {
+                name: "tree".into(),
+                ..Default::default()
+            },
+            requests: Some(Arc::new(RwLock::new(create_test_tree()))),
+            ..Default::default()
+        };
+
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        store
+    }
+
+    fn root_ids(store: &CollectionStore) -> Vec<String> {
+        let requests = store.get_requests().unwrap();
+        let requests = requests.read().unwrap();
+        requests.iter().map(RequestKind::get_id).collect()
+    }
+
+    fn dir_ids(store: &CollectionStore) -> Vec<String> {
+        let requests = store.get_requests().unwrap();
+        let requests = requests.read().unwrap();
+        let dir = requests
+            .iter()
+            .find_map(|item| match item {
+                RequestKind::Nested(dir) => Some(dir.clone()),
+                RequestKind::Single(_) => None,
+            })
+            .unwrap();
+        let children = dir.requests.read().unwrap();
+        children.iter().map(RequestKind::get_id).collect()
+    }
+
+    #[test]
+    fn test_moving_items_within_their_parent() {
+        let mut store = make_tree_store();
+
+        assert!(store.move_item("root_two", ItemMove::Up));
+        assert_eq!(root_ids(&store), vec!["root", "root_two", "dir"]);
+
+        assert!(store.move_item("child_one", ItemMove::Down));
+        assert_eq!(dir_ids(&store), vec!["child_two", "child_one"]);
+
+        // the ends of a parent don't move any further
+        assert!(!store.move_item("child_one", ItemMove::Down));
+        assert!(!store.move_item("root", ItemMove::Up));
+        assert_eq!(root_ids(&store), vec!["root", "root_two", "dir"]);
+    }
+
+    #[test]
+    fn test_indenting_and_unindenting_requests() {
+        let mut store = make_tree_store();
+        store.select_request("root_two");
+        store
+            .get_dirs_expanded()
+            .unwrap()
+            .borrow_mut()
+            .insert("dir".into(), false);
+
+        assert!(store.move_item("root_two", ItemMove::Indent));
+        assert_eq!(root_ids(&store), vec!["root", "dir"]);
+        assert_eq!(dir_ids(&store), vec!["child_one", "child_two", "root_two"]);
+        let selected = store.get_selected_request().unwrap();
+        assert_eq!(selected.read().unwrap().id, "root_two");
+        assert_eq!(selected.read().unwrap().parent, Some("dir".into()));
+        assert_eq!(store.get_hovered_request(), Some("root_two".into()));
+        // the request would be hidden otherwise
+        assert_eq!(
+            store.get_dirs_expanded().unwrap().borrow().get("dir"),
+            Some(&true)
+        );
+        assert_eq!(store.find_hovered_request().get_id(), "root_two");
+
+        assert!(store.move_item("root_two", ItemMove::Unindent));
+        assert_eq!(root_ids(&store), vec!["root", "dir", "root_two"]);
+        assert_eq!(selected.read().unwrap().parent, None);
+
+        assert!(store.move_item("root", ItemMove::Indent));
+        assert_eq!(dir_ids(&store), vec!["root", "child_one", "child_two"]);
+        assert!(store.move_item("root", ItemMove::Unindent));
+        assert_eq!(root_ids(&store), vec!["root", "dir", "root_two"]);
+
+        // directories can't be nested, and root requests can't be unindented
+        assert!(!store.move_item("dir", ItemMove::Indent));
+        assert!(!store.move_item("root_two", ItemMove::Unindent));
+    }
+
+    #[test]
+    fn test_filtering_keeps_the_hover_on_visible_items() {
+        let mut store = make_tree_store();
+        let ids = |store: &CollectionStore| {
+            store
+                .get_filtered_items()
+                .unwrap()
+                .iter()
+                .map(RequestKind::get_id)
+                .collect::<Vec<_>>()
+        };
+
+        // matches show up with the directory they live in
+        store.set_sidebar_filter("child2");
+        assert_eq!(ids(&store), vec!["dir", "child_two"]);
+        assert_eq!(store.get_hovered_request(), Some("child_two".into()));
+        // the directory is collapsed, but the request is still found
+        assert_eq!(store.find_hovered_request().get_id(), "child_two");
+
+        store.dispatch(CollectionStoreAction::HoverPrev);
+        store.dispatch(CollectionStoreAction::HoverPrev);
+        assert_eq!(store.get_hovered_request(), Some("dir".into()));
+
+        store.set_sidebar_filter("/NESTED1");
+        assert_eq!(ids(&store), vec!["dir", "child_one", "child_two"]);
+        assert_eq!(store.get_hovered_request(), Some("dir".into()));
+        store.dispatch(CollectionStoreAction::HoverNext);
+        store.dispatch(CollectionStoreAction::HoverNext);
+        store.dispatch(CollectionStoreAction::HoverNext);
+        assert_eq!(store.get_hovered_request(), Some("child_two".into()));
+
+        store.set_sidebar_filter("nothing");
+        assert!(ids(&store).is_empty());
+        assert_eq!(store.get_hovered_request(), None);
+
+        store.set_sidebar_filter("child1");
+        store.set_sidebar_filter("");
+        assert!(store.get_filtered_items().is_none());
+        // clearing the filter keeps the hovered request in view
+        assert_eq!(
+            store.get_dirs_expanded().unwrap().borrow().get("dir"),
+            Some(&true)
+        );
+        assert_eq!(store.find_hovered_request().get_id(), "child_one");
+    }
+
+    #[test]
+    fn test_filtering_by_tags() {
+        let mut store = make_tree_store();
+        let tag = |store: &CollectionStore, id: &str, tags: &str| {
+            store.get_request_by_id(id).unwrap().write().unwrap().tags = parse_tags(tags);
+        };
+        tag(&store, "root", "smoke");
+        tag(&store, "child_two", "smoke admin");
+        let ids = |store: &CollectionStore| {
+            store
+                .get_filtered_items()
+                .unwrap()
+                .iter()
+                .map(RequestKind::get_id)
+                .collect::<Vec<_>>()
+        };
+
+        store.set_sidebar_filter("#smoke");
+        assert_eq!(ids(&store), vec!["root", "dir", "child_two"]);
+        store.set_sidebar_filter("#SMOKE #admin");
+        assert_eq!(ids(&store), vec!["dir", "child_two"]);
+        store.set_sidebar_filter("#smoke root");
+        assert_eq!(ids(&store), vec!["root"]);
+        store.set_sidebar_filter("#deprecated");
+        assert!(ids(&store).is_empty());
+        assert_eq!(store.get_hovered_request(), None);
+    }
 }
@@ -1,25 +1,82 @@
+mod activity;
+mod popups;
+mod runners;
+
+use activity::send_outcome;
+use popups::{ConflictChoice, HookChoice, LinkChoice, SendChoice};
+
+use hac_core::activity::{Activity, ActivityKind};
+use hac_core::builtins;
+use hac_core::captures;
 use hac_core::collection::types::*;
-use hac_core::command::Command;
+use hac_core::command::{Command, ErrorReport};
+use hac_core::hooks::{Hook, Hooks};
+use hac_core::net::client_pool;
+use hac_core::net::download::format_size;
+use hac_core::net::request_client::ClientOptions;
 use hac_core::net::request_manager::Response;
+use hac_core::net::uri;
+use hac_core::safety::SafetyOptions;
+use hac_core::variables::{self, Generated, VariableError};
 
+use hac_config::{Action, EditorMode, LayoutOrientation};
+
+use crate::pages::collection_viewer::assertions_editor::{AssertionsEditor, AssertionsEditorEvent};
+use crate::pages::collection_viewer::collection_settings::{
+    CollectionSettings, CollectionSettingsEvent,
+};
 use crate::pages::collection_viewer::collection_store::{CollectionStore, CollectionStoreAction};
-use crate::pages::collection_viewer::request_editor::{RequestEditor, RequestEditorEvent};
+use crate::pages::collection_viewer::copy_as::{CopyAs, CopyAsEvent};
+use crate::pages::collection_viewer::flow_manager::{FlowManager, FlowManagerEvent};
+use crate::pages::collection_viewer::request_editor::{
+    EditorViewState, RequestEditor, RequestEditorEvent,
+};
+use crate::pages::collection_viewer::request_preview::{RequestPreview, RequestPreviewEvent};
+use crate::pages::collection_viewer::request_status::{FinishedRequest, RequestStatus};
 use crate::pages::collection_viewer::request_uri::{RequestUri, RequestUriEvent};
-use crate::pages::collection_viewer::response_viewer::{ResponseViewer, ResponseViewerEvent};
-use crate::pages::collection_viewer::sidebar::{self, Sidebar, SidebarEvent};
-use crate::pages::{Eventful, Renderable};
+use crate::pages::collection_viewer::response_history::ResponseHistory;
+use crate::pages::collection_viewer::response_viewer::{
+    ResponseViewState, ResponseViewer, ResponseViewerEvent,
+};
+use crate::pages::collection_viewer::run_timings::{RunTimings, RunTimingsEvent};
+use crate::pages::collection_viewer::runner_panel::{RunnerPanel, RunnerPanelEvent};
+use crate::pages::collection_viewer::secret_prompt::{SecretPrompt, SecretPromptEvent};
+use crate::pages::collection_viewer::sidebar::{self, Sidebar, SidebarEvent, SyncState};
+use crate::pages::collection_viewer::template_manager::{TemplateManager, TemplateManagerEvent};
+use crate::pages::collection_viewer::view_states::ViewStates;
+use crate::pages::collection_viewer::watch::Watch;
+use crate::pages::confirm_popup::ConfirmPopup;
+use crate::pages::help_overlay::{HelpOverlay, HelpOverlayEvent, HelpSection};
+use crate::pages::path_prompt::{PathPrompt, PathPromptEvent};
+use crate::pages::status_bar::{self, Segment, StatusBar};
+use crate::pages::{Eventful, Overlay, Renderable};
+use crate::task_tracker::TaskTracker;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
-use std::ops::{Add, Div};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::{Add, Div, Mul};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Clear};
 use ratatui::Frame;
-use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// ids of the requests sent by any viewer, a viewer is rebuilt whenever a
+/// collection is opened so the ids can't restart along with it
+static NEXT_SEND_ID: AtomicU64 = AtomicU64::new(0);
+/// every viewer gets its own id, which goes along every command it spawns
+/// so results arriving after another collection was opened can be dropped
+static NEXT_VIEWER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// recent requests listed on the status bar, the rest can still be jumped to
+const RECENT_REQUESTS_SHOWN: usize = 3;
 
 #[derive(Debug, PartialEq)]
 pub struct ExplorerLayout {
@@ -29,6 +86,7 @@ pub struct ExplorerLayout {
     pub req_editor: Rect,
     pub response_preview: Rect,
     pub create_req_form: Rect,
+    pub help_popup: Rect,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -36,6 +94,8 @@ pub enum CollectionViewerOverlay {
     None,
     CreateRequest,
     SelectParentDir,
+    /// picking the template a new request starts from
+    SelectTemplate,
     EditRequest,
     EditDirectory,
     CreateDirectory,
@@ -43,6 +103,76 @@ pub enum CollectionViewerOverlay {
     HeadersDelete,
     HeadersForm(usize),
     DeleteSidebarItem(String),
+    RunnerProgress,
+    EditAssertions,
+    CopyAs,
+    RunTimings,
+    RequestPreview,
+    /// a destructive request to a protected host is waiting for the user to
+    /// confirm it, holds the host
+    ConfirmSend(String),
+    /// the request has hook commands on a collection that wasn't trusted
+    /// to run them yet
+    ConfirmHooks,
+    /// a secret used by the request has no value yet, holds its name
+    SecretPrompt(String),
+    /// asking where to export the request or the collection to
+    Export,
+    /// listing the request templates
+    Templates,
+    /// listing the flows of the collection
+    Flows,
+    /// editing the settings shared by every request on the collection
+    CollectionSettings,
+    /// asking how often, or on which files, to send the request again
+    Watch,
+    /// asking where to save a binary body to
+    SaveBody,
+    /// the collection file was changed by something else while there were
+    /// edits that weren't written to it yet
+    ConfirmConflict,
+    /// asking what to do with a link picked on the response, holds its url
+    OpenLink(String),
+    Help,
+}
+
+/// what would be lost by quitting right now
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PendingWork {
+    pub unsaved_requests: usize,
+    pub requests_in_flight: usize,
+    pub collection_run: bool,
+}
+
+impl PendingWork {
+    /// eg: `1 unsaved request, 1 request in flight`, `None` when there is
+    /// nothing to lose
+    pub fn summary(&self) -> Option<String> {
+        let plural = |count: usize| if count.eq(&1) { "" } else { "s" };
+        let mut pieces = vec![];
+        if self.unsaved_requests.gt(&0) {
+            pieces.push(format!(
+                "{} unsaved request{}",
+                self.unsaved_requests,
+                plural(self.unsaved_requests)
+            ));
+        }
+        if self.requests_in_flight.gt(&0) {
+            pieces.push(format!(
+                "{} request{} in flight",
+                self.requests_in_flight,
+                plural(self.requests_in_flight)
+            ));
+        }
+        if self.collection_run {
+            pieces.push(String::from("a collection run going on"));
+        }
+
+        match pieces.is_empty() {
+            true => None,
+            false => Some(pieces.join(", ")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -78,20 +208,88 @@ pub struct CollectionViewer<'cv> {
     response_viewer: ResponseViewer<'cv>,
     request_editor: RequestEditor<'cv>,
     request_uri: RequestUri<'cv>,
+    request_status: RequestStatus<'cv>,
     sidebar: Sidebar<'cv>,
+    runner_panel: RunnerPanel<'cv>,
+    assertions_editor: AssertionsEditor<'cv>,
+    copy_as: CopyAs<'cv>,
+    run_timings: RunTimings<'cv>,
+    request_preview: RequestPreview<'cv>,
+    secret_prompt: SecretPrompt<'cv>,
+    export_prompt: PathPrompt<'cv>,
+    watch_prompt: PathPrompt<'cv>,
+    save_prompt: PathPrompt<'cv>,
+    template_manager: TemplateManager<'cv>,
+    flow_manager: FlowManager<'cv>,
+    collection_settings: CollectionSettings<'cv>,
+    help: HelpOverlay<'cv>,
 
     colors: &'cv hac_colors::Colors,
     config: &'cv hac_config::Config,
+    size: Rect,
     layout: ExplorerLayout,
+    /// whether the focused pane takes the whole area of the panes
+    maximized: bool,
+    /// how the request editor and the response pane are split
+    orientation: LayoutOrientation,
     global_command_sender: Option<UnboundedSender<Command>>,
-    collection_sync_timer: std::time::Instant,
+    collection_sync_timer: Instant,
+    /// every request as it was last written to disk, to tell which ones
+    /// were edited since
+    saved_requests: HashMap<String, Request>,
+    /// stamp of the collection file as we last read or wrote it, anything
+    /// else on it was written by someone else
+    disk_stamp: Option<u64>,
+    /// stamp of the collection as we last serialized it, telling whether
+    /// there is anything left to write
+    saved_stamp: Option<u64>,
+    /// the file and our version both changed, nothing is written until the
+    /// user picks which one to keep
+    conflicted: bool,
+    confirm_conflict: Option<ConfirmPopup<'cv, ConflictChoice>>,
     collection_store: Rc<RefCell<CollectionStore>>,
 
-    responses_map: HashMap<String, Rc<RefCell<Response>>>,
-    response_rx: UnboundedReceiver<Response>,
-    request_tx: UnboundedSender<Response>,
+    responses_map: HashMap<String, ResponseHistory>,
+    /// request the editor and the response panes were built for
+    panes_request_id: Option<String>,
+    view_states: ViewStates<(EditorViewState, ResponseViewState)>,
+    /// request the viewer is currently showing the history of
+    shown_request_id: Option<String>,
+    /// the send whose response we are waiting for, responses of any other
+    /// send are stale
+    pending_send: Option<u64>,
+    /// resolved request waiting on the destructive request confirmation
+    awaiting_confirmation: Option<Request>,
+    /// what the builtins generated for the request being sent, kept on its
+    /// response once it arrives
+    generated: Generated,
+    confirm_send: Option<ConfirmPopup<'cv, SendChoice>>,
+    /// hosts the user asked not to be asked about again, only for as long as
+    /// the application is open
+    trusted_hosts: HashSet<String>,
+    confirm_hooks: Option<ConfirmPopup<'cv, HookChoice>>,
+    confirm_link: Option<ConfirmPopup<'cv, LinkChoice>>,
+    /// the next send goes out without hooks, as the user chose not to trust
+    /// the collection just yet
+    skip_hooks: bool,
+    /// variables printed by the pre-request hook, for the request with the
+    /// id, used by the send that comes after the hook
+    hook_variables: Option<(String, BTreeMap<String, String>)>,
+    /// runs once the response of the send in flight arrives
+    post_response_hook: Option<Hook>,
+    /// the selected request is being sent over and over, until `W` is
+    /// pressed again or another request is selected
+    watch: Option<Watch>,
+    /// collection syncs to disk, so quitting can wait on them
+    tasks: TaskTracker,
+    /// where sends and runs are listed, they can be cancelled from there
+    activity: Activity,
+    /// entries of the pending send and of the run going on
+    send_activity: Option<u64>,
+    run_activity: Option<u64>,
 
     dry_run: bool,
+    id: u64,
 }
 
 impl<'cv> CollectionViewer<'cv> {
@@ -102,8 +300,7 @@ impl<'cv> CollectionViewer<'cv> {
         config: &'cv hac_config::Config,
         dry_run: bool,
     ) -> Self {
-        let layout = build_layout(size);
-        let (request_tx, response_rx) = unbounded_channel::<Response>();
+        let layout = build_layout(size, config.layout, None);
 
         let sidebar = sidebar::Sidebar::new(colors, collection_store.clone());
 
@@ -118,26 +315,122 @@ impl<'cv> CollectionViewer<'cv> {
         );
 
         let request_uri = RequestUri::new(colors, collection_store.clone(), layout.req_uri);
+        let panes_request_id = collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|request| request.read().unwrap().id.clone());
+        let saved_requests = collection_store
+            .borrow()
+            .get_collection()
+            .map(|collection| snapshot_requests(&collection.borrow()))
+            .unwrap_or_default();
+        let (disk_stamp, saved_stamp) = collection_store
+            .borrow()
+            .get_collection()
+            .map(|collection| {
+                let collection = collection.borrow();
+                (
+                    hac_core::fs::file_stamp(&collection.path),
+                    serialized_stamp(&collection),
+                )
+            })
+            .unwrap_or_default();
 
         CollectionViewer {
             request_editor,
             response_viewer,
             sidebar,
+            runner_panel: RunnerPanel::new(colors),
+            assertions_editor: AssertionsEditor::new(colors, collection_store.clone()),
+            copy_as: CopyAs::new(colors, collection_store.clone()),
+            run_timings: RunTimings::new(colors),
+            request_preview: RequestPreview::new(colors),
+            secret_prompt: SecretPrompt::new(colors),
+            export_prompt: PathPrompt::new(colors),
+            watch_prompt: PathPrompt::new(colors),
+            save_prompt: PathPrompt::new(colors),
+            template_manager: TemplateManager::new(colors, collection_store.clone()),
+            flow_manager: FlowManager::new(colors, collection_store.clone()),
+            collection_settings: CollectionSettings::new(colors, config, collection_store.clone()),
+            help: HelpOverlay::new(colors, help_sections(config)),
             request_uri,
+            request_status: RequestStatus::new(colors),
             colors,
+            size,
             layout,
+            maximized: false,
+            orientation: config.layout,
             config,
             global_command_sender: None,
-            collection_sync_timer: std::time::Instant::now(),
+            collection_sync_timer: Instant::now(),
+            saved_requests,
+            disk_stamp,
+            saved_stamp,
+            conflicted: false,
+            confirm_conflict: None,
             responses_map: HashMap::default(),
-            response_rx,
-            request_tx,
+            panes_request_id,
+            view_states: ViewStates::default(),
+            shown_request_id: None,
+            pending_send: None,
+            awaiting_confirmation: None,
+            generated: vec![],
+            confirm_send: None,
+            trusted_hosts: HashSet::default(),
+            confirm_hooks: None,
+            confirm_link: None,
+            skip_hooks: false,
+            hook_variables: None,
+            post_response_hook: None,
+            watch: None,
+            tasks: TaskTracker::default(),
+            activity: Activity::default(),
+            send_activity: None,
+            run_activity: None,
             dry_run,
+            id: NEXT_VIEWER_ID.fetch_add(1, Ordering::Relaxed),
             collection_store,
         }
     }
 
+    pub fn set_task_tracker(&mut self, tasks: TaskTracker) {
+        self.tasks = tasks;
+    }
+
+    pub fn set_activity(&mut self, activity: Activity) {
+        self.activity = activity;
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// what the window title says about the request in flight or the last
+    /// one that finished
+    pub fn window_title(&self) -> String {
+        self.request_status.window_title()
+    }
+
+    pub fn take_finished_request(&mut self) -> Option<FinishedRequest> {
+        self.request_status.take_finished()
+    }
+
     fn rebuild_everything(&mut self) {
+        if let Some(request_id) = self.panes_request_id.take() {
+            self.view_states.insert(
+                request_id,
+                (
+                    self.request_editor.view_state(),
+                    self.response_viewer.view_state(),
+                ),
+            );
+        }
+
+        // wrapping is kept for the whole session, even across collections,
+        // until a request that was visited before brings back its own
+        let body_wrap = self.request_editor.body_wrap();
+        let response_wrap = self.response_viewer.wrap();
+
         self.sidebar = sidebar::Sidebar::new(self.colors, self.collection_store.clone());
         self.request_editor = RequestEditor::new(
             self.colors,
@@ -145,49 +438,609 @@ impl<'cv> CollectionViewer<'cv> {
             self.collection_store.clone(),
             self.layout.req_editor,
         );
+        self.request_editor.set_body_wrap(body_wrap);
         self.response_viewer = ResponseViewer::new(
             self.colors,
             self.collection_store.clone(),
             None,
             self.layout.response_preview,
         );
+        self.response_viewer.set_wrap(response_wrap);
+        self.shown_request_id = None;
         self.request_uri = RequestUri::new(
             self.colors,
             self.collection_store.clone(),
             self.layout.req_uri,
         );
+
+        self.panes_request_id = self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|request| request.read().unwrap().id.clone());
+        if let Some((editor, response)) = self
+            .panes_request_id
+            .as_deref()
+            .and_then(|request_id| self.view_states.get(request_id))
+            .cloned()
+        {
+            self.request_editor.restore_view_state(editor);
+            self.response_viewer.restore_view_state(response);
+        }
     }
 
-    // collect all pending responses from the channel. Here, I don't see a way we
-    // may have more than one response on this channel at any point, but it shouldn't matter
-    // if we have, so we can drain all the responses and update accordingly
-    fn drain_responses_channel(&mut self) {
-        while let Ok(res) = self.response_rx.try_recv() {
-            let res = Rc::new(RefCell::new(res));
-            self.collection_store
-                .borrow()
-                .get_selected_request()
-                .as_ref()
-                .and_then(|req| {
-                    self.responses_map
-                        .insert(req.read().unwrap().id.to_string(), Rc::clone(&res))
-                });
-            self.response_viewer.update(Some(Rc::clone(&res)));
-            self.response_rx.is_empty().then(|| {
+    /// the request in flight or its outcome on the left, the active
+    /// environment on the right
+    fn status_segments(&self) -> Vec<Segment> {
+        let mut segments = self.request_status.segments();
+        segments.extend(self.watch_segment());
+        segments.extend(self.hint_segments());
+        segments.extend(self.recent_requests_segment());
+        if self.collection_store.borrow().is_offline() {
+            segments.push(status_bar::offline_badge(self.colors));
+        }
+        match self.activity.active_count() {
+            0 => {}
+            active => segments.push(status_bar::activity_counter(self.colors, active)),
+        }
+        if let Some(environment) = self.collection_store.borrow().get_active_environment() {
+            segments.push(
+                Segment::right(format!("env: {environment}").fg(self.colors.normal.blue))
+                    .with_priority(2),
+            );
+        }
+        segments
+    }
+
+    /// the help hint, followed by the few keys that matter the most on the
+    /// selected pane, or by how to move around when no pane is selected
+    fn hint_segments(&self) -> Vec<Segment> {
+        let general = self.help.hints("general");
+        let hints = match self.hinted_section() {
+            Some(title) => general
+                .iter()
+                .filter(|hint| hint.keys().eq("?"))
+                .chain(self.help.hints(title))
+                .collect::<Vec<_>>(),
+            None => general.iter().collect(),
+        };
+
+        hints
+            .into_iter()
+            .map(|hint| hint.segment(self.colors.normal.magenta))
+            .collect()
+    }
+
+    /// title of the help section of the selected pane
+    fn hinted_section(&self) -> Option<&'static str> {
+        let title = match self.collection_store.borrow().get_selected_pane()? {
+            PaneFocus::Sidebar => "sidebar",
+            PaneFocus::ReqUri => "request uri",
+            PaneFocus::Preview => "response",
+            PaneFocus::Editor => match self.request_editor.body_mode() {
+                Some(EditorMode::Normal) => "body editor (normal)",
+                Some(EditorMode::Insert) => "body editor (insert)",
+                None => "request editor",
+            },
+        };
+        Some(title)
+    }
+
+    /// how the request is being watched, and whether its body changed on the
+    /// last send
+    fn watch_segment(&self) -> Option<Segment> {
+        let watch = self.watch.as_ref()?;
+        let mut spans = vec![watch.label(Instant::now()).fg(self.colors.normal.blue)];
+        match watch.body_changed {
+            Some(true) => spans.push(" body changed".fg(self.colors.normal.orange).bold()),
+            Some(false) => spans.push(" same body".fg(self.colors.bright.black)),
+            None => {}
+        }
+        Some(Segment::left(Line::from(spans)).with_priority(3))
+    }
+
+    /// the keys of the last few requests opened, eg: `A-1 login A-2 users`
+    fn recent_requests_segment(&self) -> Option<Segment> {
+        let recent = self.collection_store.borrow().get_recent_requests();
+        let mut spans: Vec<Span> = vec![];
+        for (key, request) in self
+            .config
+            .recent_request_keys
+            .iter()
+            .zip(recent.iter())
+            .take(RECENT_REQUESTS_SHOWN)
+        {
+            if !spans.is_empty() {
+                spans.push(" ".into());
+            }
+            let name = request
+                .read()
+                .unwrap()
+                .name
+                .chars()
+                .take(12)
+                .collect::<String>();
+            spans.push(key.clone().fg(self.colors.normal.yellow));
+            spans.push(format!(" {name}").fg(self.colors.bright.black));
+        }
+
+        match spans.is_empty() {
+            true => None,
+            false => Some(Segment::right(Line::from(spans))),
+        }
+    }
+
+    fn draw_status_bar(&self, frame: &mut Frame) {
+        let status_bar = StatusBar::new(self.status_segments());
+        frame.render_widget(status_bar, self.layout.hint_pane);
+    }
+
+    /// takes the response of a send, which is only shown when it answers
+    /// the latest send. Anything else was cancelled or superseded, and would
+    /// overwrite the response of the request we are waiting on
+    pub fn handle_response(&mut self, send_id: u64, request_id: String, mut res: Response) {
+        if self.pending_send.ne(&Some(send_id)) {
+            tracing::debug!("discarding stale response of send {send_id} for {request_id}");
+            return;
+        }
+        self.pending_send = None;
+        if let Some(id) = self.send_activity.take() {
+            self.activity.finish(id, send_outcome(&res));
+        }
+        self.request_status.finish(&res);
+        if let Some(spilled) = res.spilled.as_ref() {
+            let limit = self.client_options().max_in_memory.unwrap_or_default();
+            self.request_status.notify(format!(
+                "response body is {}, over the {} display limit, only its start is shown",
+                format_size(spilled.size),
+                format_size(limit)
+            ));
+        }
+        res.generated = std::mem::take(&mut self.generated);
+
+        // responses belong to the request that was sent, which might not be
+        // the selected one anymore
+        let request = self
+            .collection_store
+            .borrow()
+            .get_request_by_id(&request_id);
+        if let Some(watch) = self.watch.as_mut().filter(|w| w.request_id.eq(&request_id)) {
+            let body = res.body.as_deref().map(str::as_bytes);
+            watch.record_body(body.or(res.binary_body.as_deref()).unwrap_or_default());
+        }
+
+        if let Some(req) = request.as_ref() {
+            let req = req.read().unwrap();
+
+            // captures are only applied on requests that got a response,
+            // unresolved captures are displayed by the editor as warnings
+            if !res.is_error {
+                let results =
+                    captures::extract_all(req.captures.as_deref().unwrap_or_default(), &res);
                 self.collection_store
                     .borrow_mut()
-                    .dispatch(CollectionStoreAction::SetPendingRequest(false));
-            });
+                    .dispatch(CollectionStoreAction::ApplyCaptures(
+                        req.id.clone(),
+                        results,
+                    ));
+            }
+
+            if let Some(hook) = self.post_response_hook.take() {
+                self.run_post_response_hook(hook, &req, &res);
+            }
+        }
+
+        self.responses_map.entry(request_id).or_default().push(res);
+        // forces the viewer to pick up the new response
+        self.shown_request_id = None;
+
+        self.collection_store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetPendingRequest(false));
+    }
+
+    /// keeps the response viewer showing the history of the selected
+    /// request, swapping it whenever the selection changes
+    fn sync_response_history(&mut self) {
+        let selected_id = self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|req| req.read().unwrap().id.clone());
+
+        if selected_id.eq(&self.shown_request_id) {
+            return;
+        }
+
+        let history = selected_id
+            .as_ref()
+            .and_then(|id| self.responses_map.get(id));
+        self.response_viewer
+            .update(history.and_then(|history| history.selected()));
+        self.response_viewer.set_history(
+            history.map(|history| history.tabs()).unwrap_or_default(),
+            history
+                .map(|history| history.selected_idx())
+                .unwrap_or_default(),
+        );
+        self.response_viewer
+            .set_runs(history.map(|history| history.runs()).unwrap_or_default());
+        self.shown_request_id = selected_id;
+    }
+
+    fn select_response(&mut self, idx: usize) {
+        if let Some(history) = self
+            .shown_request_id
+            .as_ref()
+            .and_then(|id| self.responses_map.get_mut(id))
+        {
+            history.select(idx);
+            self.shown_request_id = None;
+        }
+    }
+
+    /// resolves the variables of the selected request and sends it, when any
+    /// variable is unresolved the request is not sent and an error is shown,
+    /// or the value is asked for when it is a secret
+    fn send_selected_request(&mut self) {
+        let Some(request) = self.collection_store.borrow().get_selected_request() else {
+            return;
+        };
+
+        // not even the hooks run, as they could reach out on their own
+        if self.collection_store.borrow().is_offline() {
+            self.collection_store
+                .borrow_mut()
+                .dispatch(CollectionStoreAction::SetPendingRequest(false));
+            self.request_status
+                .notify("offline mode, the request was not sent [O -> go online]");
+            return;
+        }
+
+        let mut layers = self.collection_store.borrow().get_layers();
+        // the uri is remembered as it was typed, before the base url and
+        // variables get into it
+        let typed_uri = request.read().unwrap().uri.clone();
+        let request = self
+            .collection_store
+            .borrow()
+            .with_defaults(&request.read().unwrap());
+
+        let hooks = match std::mem::take(&mut self.skip_hooks) {
+            true => Hooks::default(),
+            false => self.collection_store.borrow().hooks_for(&request),
+        };
+        if !hooks.is_empty() && !self.collection_store.borrow().hooks_trusted() {
+            self.confirm_hooks = Some(self.build_confirm_hooks());
+            self.collection_store
+                .borrow_mut()
+                .push_overlay(CollectionViewerOverlay::ConfirmHooks);
+            return;
+        }
+
+        // the hook runs first and sends the request again once it is done,
+        // this time with what it printed
+        let hook_variables = self
+            .hook_variables
+            .take()
+            .filter(|(id, _)| id.eq(&request.id));
+        match (hooks.pre_request, hook_variables) {
+            (_, Some((_, hook_variables))) => layers.request = hook_variables,
+            (Some(hook), None) => return self.run_pre_request_hook(hook, request),
+            (None, None) => {}
+        }
+        self.post_response_hook = hooks.post_response;
+
+        let resolved = variables::resolve_request_with_generated(&request, &layers.merged());
+
+        match resolved {
+            Ok((mut request, generated)) => {
+                // only the request going out is cleaned up, the collection
+                // keeps the uri as it was typed
+                let normalized = match uri::normalize(&request.uri) {
+                    Ok(normalized) => normalized,
+                    Err(e) => return self.fail_send(&e),
+                };
+                request.uri = normalized.uri;
+                let mut warnings = normalized.warnings;
+                if let Some(oversized) = self.client_options().oversized_body(&request) {
+                    tracing::warn!("{oversized}, sending {}", request.name);
+                    warnings.push(oversized.to_string());
+                }
+                if !warnings.is_empty() {
+                    self.request_status.notify(warnings.join(", "));
+                }
+
+                self.generated = generated;
+                self.collection_store.borrow_mut().record_url(&typed_uri);
+
+                let protected_host = self
+                    .safety_options()
+                    .protected_host(&request)
+                    .filter(|host| !self.trusted_hosts.contains(host));
+
+                match protected_host {
+                    Some(host) => {
+                        self.confirm_send = Some(self.build_confirm_send(&request, &host));
+                        self.awaiting_confirmation = Some(request);
+                        self.collection_store
+                            .borrow_mut()
+                            .push_overlay(CollectionViewerOverlay::ConfirmSend(host));
+                    }
+                    None => self.dispatch_request(request),
+                }
+            }
+            Err(e) => {
+                // secrets without a value are asked for, sending would leave
+                // the placeholder on the request
+                let secret = match &e {
+                    VariableError::Unresolved(names) => {
+                        self.collection_store.borrow().find_secret(names)
+                    }
+                    VariableError::Builtin(_) => None,
+                };
+                if let Some(name) = secret {
+                    self.collection_store
+                        .borrow_mut()
+                        .dispatch(CollectionStoreAction::SetPendingRequest(false));
+                    self.secret_prompt.open(name.clone());
+                    self.collection_store
+                        .borrow_mut()
+                        .push_overlay(CollectionViewerOverlay::SecretPrompt(name));
+                    return;
+                }
+
+                self.fail_send(&e);
+            }
         }
     }
 
-    fn sync_collection_changes(&mut self) {
+    /// gives up on a send that couldn't get the request ready, showing why
+    fn fail_send(&mut self, error: &dyn std::error::Error) {
+        self.collection_store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetPendingRequest(false));
+
         let sender = self
             .global_command_sender
             .as_ref()
-            .expect("should have a sender at this point")
-            .clone();
+            .expect("should have a sender at this point");
+        if sender
+            .send(Command::Error(ErrorReport::new(error)))
+            .is_err()
+        {
+            tracing::error!("failed to send error command through channel");
+        }
+    }
+
+    /// sends an already resolved request, skipping any confirmation
+    fn dispatch_request(&mut self, request: Request) {
+        let Some(sender) = self.global_command_sender.clone() else {
+            tracing::error!("attempted to send a request without a command sender");
+            return;
+        };
+
+        // a send still in flight is replaced by this one
+        if let Some(id) = self.send_activity.take() {
+            self.activity.cancel(id);
+        }
+
+        let (method, uri) = (request.method.clone(), request.uri.clone());
+        let send_id = NEXT_SEND_ID.fetch_add(1, Ordering::Relaxed);
+        self.pending_send = Some(send_id);
+        let handle = hac_core::net::handle_request(
+            &Arc::new(RwLock::new(request)),
+            self.id,
+            send_id,
+            self.client_options(),
+            sender,
+        );
+        let id = self
+            .activity
+            .start(ActivityKind::Request, format!("{method} {uri}"));
+        self.activity.attach(id, handle.abort_handle());
+        self.send_activity = Some(id);
+        self.request_status.start(method, uri, handle);
+
+        if self.config.focus_response_on_send {
+            self.update_focus(PaneFocus::Preview);
+            if self.collection_store.borrow().get_selected_pane().is_some() {
+                self.update_selection(Some(PaneFocus::Preview));
+            }
+        }
+    }
+
+    fn safety_options(&self) -> SafetyOptions {
+        self.collection_store
+            .borrow()
+            .get_collection()
+            .map(|collection| SafetyOptions::new(self.config, &collection.borrow()))
+            .unwrap_or_default()
+    }
+
+    /// drops the clients kept for the collection, so their idle connections
+    /// are closed and the next send builds one with the current settings
+    pub fn forget_connections(&self) {
+        if let Some(path) = self.client_options().collection {
+            client_pool::forget(&path);
+        }
+    }
+
+    fn client_options(&self) -> ClientOptions {
+        let store = self.collection_store.borrow();
+        let options = store
+            .get_collection()
+            .map(|collection| ClientOptions::new(self.config, &collection.borrow()))
+            .unwrap_or_default();
+        // offline mode is toggled while running, so the store knows better
+        // than the config
+        ClientOptions {
+            offline: store.is_offline(),
+            ..options
+        }
+    }
+
+    /// turns offline mode on or off for every screen
+    fn toggle_offline(&mut self) {
+        let offline = !self.collection_store.borrow().is_offline();
+        self.collection_store.borrow_mut().set_offline(offline);
+        self.request_status.notify(match offline {
+            true => "offline mode, requests are not sent",
+            false => "back online",
+        });
+
+        let Some(sender) = self.global_command_sender.as_ref() else {
+            return;
+        };
+        if sender.send(Command::OfflineChanged(offline)).is_err() {
+            tracing::error!("failed to send offline command through channel");
+        }
+    }
+
+    /// writes what is on the body editor into the selected request
+    fn apply_body_edits(&mut self) {
+        if let Some(request) = self.collection_store.borrow().get_selected_request() {
+            let body = self.request_editor.body().to_string();
+            if !body.is_empty() {
+                let mut request = request.write().unwrap();
+                request.body = Some(body);
+                request.body_type.get_or_insert(BodyType::Json);
+            }
+        }
+    }
+
+    /// asks the application to open the body on the user's editor, the file
+    /// gets the extension of the body type so editors highlight it
+    fn edit_body_externally(&self) -> Option<Command> {
+        let request = self.collection_store.borrow().get_selected_request()?;
+        let extension = request
+            .read()
+            .unwrap()
+            .body_type
+            .as_ref()
+            .unwrap_or(&BodyType::Json)
+            .extension()
+            .to_string();
+
+        Some(Command::EditExternally {
+            viewer_id: self.id,
+            content: self.request_editor.body().to_string(),
+            extension,
+        })
+    }
+
+    /// takes the body back from the external editor. When the editor failed
+    /// the body is left as it was
+    pub fn handle_external_edit(&mut self, result: Result<String, ErrorReport>) {
+        match result {
+            Ok(body) => {
+                self.request_editor.set_body(&body);
+                self.apply_body_edits();
+                self.sync_collection_changes();
+            }
+            Err(report) => self
+                .request_status
+                .notify(format!("body left unchanged: {}", report.message)),
+        }
+    }
+
+    /// sends the selected request again from whatever pane is focused,
+    /// including body edits that weren't synced yet. With a request in
+    /// flight it either does nothing or cancels it first, depending on the
+    /// config
+    fn resend_request(&mut self) {
+        if self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .is_none()
+        {
+            return;
+        }
+
+        if self.collection_store.borrow().has_pending_request() {
+            if !self.config.resend_cancels_pending {
+                self.request_status
+                    .notify("A request is already in flight, <C-x> cancels it");
+                return;
+            }
+            self.cancel_request();
+        }
+
+        self.apply_body_edits();
+        self.collection_store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetPendingRequest(true));
+        self.send_selected_request();
+    }
+
+    /// writes the collection to disk on a background task, which happens on
+    /// every few ticks and once more when quitting. When its file was changed
+    /// by something else since, it is reloaded if there is nothing of ours to
+    /// lose, otherwise the user is asked which version to keep
+    pub fn sync_collection_changes(&mut self) {
+        let collection = self.collection_with_edits();
+        self.collection_sync_timer = std::time::Instant::now();
+
+        if self.dry_run {
+            self.saved_requests = snapshot_requests(&collection);
+            return;
+        }
+
+        if self.conflicted {
+            self.ask_about_conflict();
+            return;
+        }
+
+        let read_only = self.collection_store.borrow().is_read_only();
+        if self.changed_on_disk(&collection.path) {
+            match self.has_local_edits(&collection) {
+                // our edits can't be written anyway, so they are kept until
+                // there is nothing of ours to lose
+                true if read_only => {}
+                true => {
+                    self.conflicted = true;
+                    self.ask_about_conflict();
+                }
+                // reloading drops whatever overlay is open, so it waits for
+                // the user to be done with it
+                false if self.collection_store.borrow().has_overlay() => {}
+                false => {
+                    self.reload_from_disk();
+                    self.request_status.notify(format!(
+                        "reloaded {}, it was changed on disk",
+                        collection.info.name
+                    ));
+                }
+            }
+            return;
+        }
+
+        if !read_only {
+            self.write_collection(&collection, false);
+        }
+    }
+
+    /// the last sync before quitting. There is no one left to ask about a
+    /// conflict, so our version goes to a new collection instead
+    pub fn sync_before_quit(&mut self) {
+        if self.dry_run || self.collection_store.borrow().is_read_only() {
+            self.sync_collection_changes();
+            return;
+        }
 
+        let collection = self.collection_with_edits();
+        let conflicted = self.conflicted
+            || self.changed_on_disk(&collection.path) && self.has_local_edits(&collection);
+        match conflicted {
+            true => _ = self.write_conflicted_copy(&collection),
+            false => self.sync_collection_changes(),
+        }
+    }
+
+    /// the collection with the edits on the body editor and the selected
+    /// request applied, as it would be written
+    fn collection_with_edits(&mut self) -> Collection {
         let mut collection = self
             .collection_store
             .borrow()
@@ -196,16 +1049,8 @@ impl<'cv> CollectionViewer<'cv> {
             .expect("tried to sync collection to disk without having a collection")
             .borrow()
             .clone();
+        self.apply_body_edits();
         if let Some(request) = self.collection_store.borrow().get_selected_request() {
-            let request = request.clone();
-            let body = self.request_editor.body().to_string();
-            // this is not the best idea for when we start implementing other kinds of
-            // body types like GraphQL
-            if !body.is_empty() {
-                request.write().unwrap().body = Some(body);
-                request.write().unwrap().body_type = Some(BodyType::Json)
-            }
-
             // we might later on decide to keep track of the actual dir/request index
             // so we dont have to go over all the possible requests, this might be a
             // problem for huge collections, but I haven't tested
@@ -234,37 +1079,262 @@ impl<'cv> CollectionViewer<'cv> {
                 });
         }
 
-        self.collection_sync_timer = std::time::Instant::now();
-
-        if self.dry_run {
-            return;
-        }
+        collection
+    }
 
-        tokio::spawn(async move {
-            match hac_core::fs::sync_collection(collection).await {
-                Ok(_) => {}
-                Err(e) => {
-                    if sender.send(Command::Error(e.to_string())).is_err() {
-                        tracing::error!("failed to send error command through channel");
-                        std::process::abort();
-                    }
-                }
-            }
-        });
+    fn changed_on_disk(&self, path: &std::path::Path) -> bool {
+        let on_disk = hac_core::fs::file_stamp(path);
+        on_disk.is_some() && on_disk.ne(&self.disk_stamp)
     }
 
-    fn update_selection(&mut self, pane_to_select: Option<PaneFocus>) {
-        self.collection_store
-            .borrow_mut()
-            .dispatch(CollectionStoreAction::SetSelectedPane(pane_to_select));
+    fn has_local_edits(&self, collection: &Collection) -> bool {
+        serialized_stamp(collection).ne(&self.saved_stamp)
     }
 
-    fn update_focus(&mut self, pane_to_focus: PaneFocus) {
+    /// writes `collection` to its file on a background task, moving what was
+    /// there to the trash first when `backup` is set
+    fn write_collection(&mut self, collection: &Collection, backup: bool) {
         self.collection_store
             .borrow_mut()
-            .dispatch(CollectionStoreAction::SetFocusedPane(pane_to_focus));
-    }
-}
+            .keep_auth_secrets(collection);
+        let sender = self
+            .global_command_sender
+            .as_ref()
+            .expect("should have a sender at this point")
+            .clone();
+        let contents = match hac_core::fs::collection_contents(collection) {
+            Ok(contents) => contents,
+            Err(e) => {
+                if sender.send(Command::Error(ErrorReport::new(&e))).is_err() {
+                    tracing::error!("failed to sync collection: {e:?}");
+                }
+                return;
+            }
+        };
+
+        let stamp = Some(hac_core::fs::stamp(contents.as_bytes()));
+        self.disk_stamp = stamp;
+        self.saved_stamp = stamp;
+        self.saved_requests = snapshot_requests(collection);
+
+        let path = collection.path.clone();
+        self.tasks.spawn(async move {
+            let written = async {
+                if backup && path.exists() {
+                    hac_core::fs::backup_to_trash(&path, &hac_config::trash_dir()).await?;
+                }
+                hac_core::fs::write_atomically(&path, contents).await
+            };
+            if let Err(e) = written.await {
+                if sender.send(Command::Error(ErrorReport::new(&e))).is_err() {
+                    tracing::error!("failed to sync collection: {e:?}");
+                }
+            }
+        });
+    }
+
+    /// writes our version of the collection as a new collection next to it,
+    /// leaving the file of this one alone
+    fn write_conflicted_copy(&mut self, collection: &Collection) -> String {
+        let copy = hac_core::fs::conflicted_copy(collection);
+        let sender = self
+            .global_command_sender
+            .as_ref()
+            .expect("should have a sender at this point")
+            .clone();
+
+        let name = copy.info.name.clone();
+        self.tasks.spawn(async move {
+            if let Err(e) = hac_core::fs::sync_collection(copy).await {
+                if sender.send(Command::Error(ErrorReport::new(&e))).is_err() {
+                    tracing::error!("failed to write conflicted copy: {e:?}");
+                }
+            }
+        });
+        name
+    }
+
+    /// replaces the collection with what is on its file, keeping the same
+    /// request and environment selected when they are still there
+    fn reload_from_disk(&mut self) {
+        let Some(path) = self
+            .collection_store
+            .borrow()
+            .get_collection()
+            .map(|collection| collection.borrow().path.clone())
+        else {
+            return;
+        };
+
+        let collection = match Collection::load(&path) {
+            Ok(collection) => collection,
+            Err(e) => {
+                self.request_status
+                    .notify(format!("failed to reload {}: {e}", path.to_string_lossy()));
+                return;
+            }
+        };
+
+        {
+            let mut store = self.collection_store.borrow_mut();
+            let request_id = store
+                .get_selected_request()
+                .map(|request| request.read().unwrap().id.clone());
+            let environment = store.get_active_environment();
+            store.set_state(collection);
+            if let Some(request_id) = request_id {
+                store.select_request(&request_id);
+            }
+            if let Some(environment) = environment {
+                store.select_environment(&environment);
+            }
+        }
+
+        let collection = self.collection_store.borrow().get_collection();
+        if let Some(collection) = collection {
+            let collection = collection.borrow();
+            self.saved_requests = snapshot_requests(&collection);
+            self.saved_stamp = serialized_stamp(&collection);
+        }
+        self.disk_stamp = hac_core::fs::file_stamp(&path);
+        self.conflicted = false;
+        self.rebuild_everything();
+    }
+
+    /// nothing was changed as the collection is read-only, the way to change
+    /// it is working on a copy
+    fn notify_read_only(&mut self) {
+        let name = self
+            .collection_store
+            .borrow()
+            .get_collection()
+            .map(|collection| collection.borrow().info.name.clone())
+            .unwrap_or_default();
+        self.request_status.notify(format!(
+            "{name} is read-only, duplicate it from the dashboard with y to edit"
+        ));
+    }
+
+    fn sync_state(&self) -> SyncState {
+        if self.collection_store.borrow().is_read_only() {
+            return SyncState::ReadOnly;
+        }
+        match (self.conflicted, self.unsaved_requests()) {
+            (true, _) => SyncState::Conflicted,
+            (false, 0) => SyncState::Clean,
+            (false, _) => SyncState::Dirty,
+        }
+    }
+
+    /// opens the request at `slot` of the ones opened last. Edits on the
+    /// body editor are applied to the request being left, so they are kept
+    fn open_recent_request(&mut self, slot: usize) {
+        let Some(request) = self
+            .collection_store
+            .borrow()
+            .get_recent_requests()
+            .get(slot)
+            .cloned()
+        else {
+            return;
+        };
+
+        self.apply_body_edits();
+        let id = request.read().unwrap().id.clone();
+        self.collection_store.borrow_mut().select_request(&id);
+        self.rebuild_everything();
+    }
+
+    /// what quitting right now would lose, edits on the body editor count
+    /// as edits to the request
+    pub fn pending_work(&mut self) -> PendingWork {
+        self.apply_body_edits();
+        PendingWork {
+            unsaved_requests: self.unsaved_requests(),
+            requests_in_flight: usize::from(self.pending_send.is_some()),
+            collection_run: self.runner_panel.is_running(),
+        }
+    }
+
+    /// requests edited since they were last written to disk, edits on a
+    /// read-only collection are never written so they don't count
+    fn unsaved_requests(&self) -> usize {
+        let store = self.collection_store.borrow();
+        match store.get_collection() {
+            Some(_) if store.is_read_only() => 0,
+            Some(collection) => collection
+                .borrow()
+                .flatten_requests()
+                .iter()
+                .filter(|request| {
+                    let request = request.read().unwrap();
+                    self.saved_requests.get(&request.id).ne(&Some(&*request))
+                })
+                .count(),
+            None => 0,
+        }
+    }
+
+    fn update_selection(&mut self, pane_to_select: Option<PaneFocus>) {
+        self.collection_store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetSelectedPane(pane_to_select));
+    }
+
+    fn update_focus(&mut self, pane_to_focus: PaneFocus) {
+        self.collection_store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetFocusedPane(pane_to_focus));
+
+        // the maximized pane follows the focus around
+        if self.maximized {
+            self.relayout();
+        }
+    }
+
+    fn toggle_maximized(&mut self) {
+        self.maximized = !self.maximized;
+        self.relayout();
+    }
+
+    /// uses the orientation remembered from an earlier run instead of the
+    /// one on the config
+    pub fn set_orientation(&mut self, orientation: LayoutOrientation) {
+        self.orientation = orientation;
+        self.relayout();
+    }
+
+    /// switches to the next orientation. Only the rects change, so focus,
+    /// scrolling and any open overlay stay as they were
+    fn cycle_orientation(&mut self) {
+        self.set_orientation(self.orientation.next());
+        self.request_status
+            .notify(format!("layout: {}", self.orientation.label()));
+
+        let Some(sender) = self.global_command_sender.as_ref() else {
+            return;
+        };
+        if sender
+            .send(Command::LayoutChanged(self.orientation))
+            .is_err()
+        {
+            tracing::error!("failed to send layout command through channel");
+        }
+    }
+
+    fn maximized_pane(&self) -> Option<PaneFocus> {
+        self.maximized
+            .then(|| self.collection_store.borrow().get_focused_pane())
+    }
+
+    fn relayout(&mut self) {
+        let new_layout = build_layout(self.size, self.orientation, self.maximized_pane());
+        self.request_editor.resize(new_layout.req_editor);
+        self.response_viewer.resize(new_layout.response_preview);
+        self.request_uri.resize(new_layout.req_uri);
+        self.layout = new_layout;
+    }
+}
 
 impl Renderable for CollectionViewer<'_> {
     #[tracing::instrument(skip_all)]
@@ -274,13 +1344,27 @@ impl Renderable for CollectionViewer<'_> {
         frame.render_widget(Clear, size);
         frame.render_widget(Block::default().bg(self.colors.primary.background), size);
 
-        self.drain_responses_channel();
+        self.sync_response_history();
+        self.runner_panel.drain_events();
+        self.sync_activity();
+        self.sidebar.set_sync_state(self.sync_state());
 
-        self.sidebar.draw(frame, self.layout.sidebar)?;
-        self.response_viewer
-            .draw(frame, self.layout.response_preview)?;
-        self.request_editor.draw(frame, self.layout.req_editor)?;
-        self.request_uri.draw(frame, self.layout.req_uri)?;
+        match self.maximized_pane() {
+            Some(PaneFocus::Sidebar) => self.sidebar.draw(frame, self.layout.sidebar)?,
+            Some(PaneFocus::ReqUri) => self.request_uri.draw(frame, self.layout.req_uri)?,
+            Some(PaneFocus::Editor) => self.request_editor.draw(frame, self.layout.req_editor)?,
+            Some(PaneFocus::Preview) => self
+                .response_viewer
+                .draw(frame, self.layout.response_preview)?,
+            None => {
+                self.sidebar.draw(frame, self.layout.sidebar)?;
+                self.response_viewer
+                    .draw(frame, self.layout.response_preview)?;
+                self.request_editor.draw(frame, self.layout.req_editor)?;
+                self.request_uri.draw(frame, self.layout.req_uri)?;
+            }
+        }
+        self.draw_status_bar(frame);
 
         let overlay = self.collection_store.borrow().peek_overlay();
         match overlay {
@@ -293,6 +1377,9 @@ impl Renderable for CollectionViewer<'_> {
             CollectionViewerOverlay::SelectParentDir => {
                 self.sidebar.draw_overlay(frame, overlay)?;
             }
+            CollectionViewerOverlay::SelectTemplate => {
+                self.sidebar.draw_overlay(frame, overlay)?;
+            }
             CollectionViewerOverlay::EditRequest => {
                 self.sidebar.draw_overlay(frame, overlay)?;
             }
@@ -311,6 +1398,57 @@ impl Renderable for CollectionViewer<'_> {
             CollectionViewerOverlay::HeadersForm(_) => {
                 self.request_editor.draw_overlay(frame, overlay)?;
             }
+            CollectionViewerOverlay::RunnerProgress => {
+                self.runner_panel.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::EditAssertions => {
+                self.assertions_editor.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::CopyAs => {
+                self.copy_as.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::RunTimings => {
+                self.run_timings.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::RequestPreview => {
+                self.request_preview.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::ConfirmSend(_) => {
+                self.draw_confirm_send(frame, size)?;
+            }
+            CollectionViewerOverlay::ConfirmHooks => {
+                self.draw_confirm_hooks(frame, size)?;
+            }
+            CollectionViewerOverlay::ConfirmConflict => {
+                self.draw_confirm_conflict(frame, size)?;
+            }
+            CollectionViewerOverlay::OpenLink(_) => {
+                self.draw_confirm_link(frame, size)?;
+            }
+            CollectionViewerOverlay::SecretPrompt(_) => {
+                self.secret_prompt.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::Export => {
+                self.export_prompt.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::Watch => {
+                self.watch_prompt.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::SaveBody => {
+                self.save_prompt.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::Templates => {
+                self.template_manager.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::Flows => {
+                self.flow_manager.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::CollectionSettings => {
+                self.collection_settings.draw(frame, size)?;
+            }
+            CollectionViewerOverlay::Help => {
+                self.help.draw(frame, self.layout.help_popup)?;
+            }
             CollectionViewerOverlay::None => {}
         }
 
@@ -352,9 +1490,11 @@ impl Renderable for CollectionViewer<'_> {
     }
 
     fn handle_tick(&mut self) -> anyhow::Result<()> {
-        if self.collection_sync_timer.elapsed().as_secs().ge(&5) {
+        // while in conflict, writes wait on the user to resolve it
+        if !self.conflicted && self.collection_sync_timer.elapsed().as_secs().ge(&5) {
             self.sync_collection_changes();
         }
+        self.tick_watch();
         Ok(())
     }
 
@@ -364,10 +1504,8 @@ impl Renderable for CollectionViewer<'_> {
     }
 
     fn resize(&mut self, new_size: Rect) {
-        let new_layout = build_layout(new_size);
-        self.request_editor.resize(new_layout.req_editor);
-        self.response_viewer.resize(new_layout.response_preview);
-        self.layout = new_layout;
+        self.size = new_size;
+        self.relayout();
     }
 }
 
@@ -389,6 +1527,289 @@ impl Eventful for CollectionViewer<'_> {
             return Ok(Some(Command::Quit));
         }
 
+        if let (KeyCode::Char('x'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            if self.request_status.is_sending() {
+                self.cancel_request();
+                return Ok(None);
+            }
+        }
+
+        let overlay = self.collection_store.borrow().peek_overlay();
+        if overlay.eq(&CollectionViewerOverlay::None) {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Char('f'), KeyModifiers::CONTROL) => {
+                    self.toggle_maximized();
+                    return Ok(None);
+                }
+                // control chords never end up as text, so resending wins
+                // over whatever input is selected
+                (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                    self.resend_request();
+                    return Ok(None);
+                }
+                _ => {}
+            }
+
+            if let Some(slot) = key_chord(key_event).and_then(|chord| {
+                self.config
+                    .recent_request_keys
+                    .iter()
+                    .position(|key| key.eq(&chord))
+            }) {
+                self.open_recent_request(slot);
+                return Ok(None);
+            }
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::RunnerProgress)
+        {
+            match self.runner_panel.handle_key_event(key_event)? {
+                Some(RunnerPanelEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(RunnerPanelEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::EditAssertions)
+        {
+            match self.assertions_editor.handle_key_event(key_event)? {
+                Some(AssertionsEditorEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                    self.response_viewer.evaluate_assertions();
+                }
+                Some(AssertionsEditorEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::CopyAs)
+        {
+            match self.copy_as.handle_key_event(key_event)? {
+                Some(CopyAsEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(CopyAsEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::RunTimings)
+        {
+            match self.run_timings.handle_key_event(key_event)? {
+                Some(RunTimingsEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(RunTimingsEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::RequestPreview)
+        {
+            match self.request_preview.handle_key_event(key_event)? {
+                Some(RequestPreviewEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(RequestPreviewEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::Help)
+        {
+            match self.help.handle_key_event(key_event)? {
+                Some(HelpOverlayEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(HelpOverlayEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        let overlay = self.collection_store.borrow().peek_overlay();
+        if let CollectionViewerOverlay::ConfirmSend(host) = overlay {
+            return self.handle_confirm_send_key_event(key_event, host);
+        }
+
+        if overlay.eq(&CollectionViewerOverlay::ConfirmHooks) {
+            return self.handle_confirm_hooks_key_event(key_event);
+        }
+
+        if overlay.eq(&CollectionViewerOverlay::ConfirmConflict) {
+            return self.handle_confirm_conflict_key_event(key_event);
+        }
+
+        if let CollectionViewerOverlay::OpenLink(url) = overlay {
+            return self.handle_confirm_link_key_event(key_event, url);
+        }
+
+        if let CollectionViewerOverlay::SecretPrompt(name) = overlay {
+            match self.secret_prompt.handle_key_event(key_event)? {
+                Some(SecretPromptEvent::Submit(value)) => {
+                    let mut store = self.collection_store.borrow_mut();
+                    store.pop_overlay();
+                    store.set_secret(&name, value);
+                    drop(store);
+                    self.send_selected_request();
+                }
+                Some(SecretPromptEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(SecretPromptEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::Export)
+        {
+            match self.export_prompt.handle_key_event(key_event)? {
+                Some(PathPromptEvent::Submit { path, choice }) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                    self.export(path, choice);
+                }
+                Some(PathPromptEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(PathPromptEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::Watch)
+        {
+            match self.watch_prompt.handle_key_event(key_event)? {
+                Some(PathPromptEvent::Submit { path, choice }) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                    self.start_watch(path, choice);
+                }
+                Some(PathPromptEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(PathPromptEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::SaveBody)
+        {
+            match self.save_prompt.handle_key_event(key_event)? {
+                Some(PathPromptEvent::Submit { path, .. }) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                    if let Some(message) = self.response_viewer.save_body(&path) {
+                        self.request_status.notify(message);
+                    }
+                }
+                Some(PathPromptEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(PathPromptEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::Templates)
+        {
+            match self.template_manager.handle_key_event(key_event)? {
+                Some(TemplateManagerEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(TemplateManagerEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::Flows)
+        {
+            match self.flow_manager.handle_key_event(key_event)? {
+                Some(FlowManagerEvent::Run(name)) => self.run_flow(&name),
+                Some(FlowManagerEvent::Changed) => self.sync_collection_changes(),
+                Some(FlowManagerEvent::ReadOnly) => self.notify_read_only(),
+                Some(FlowManagerEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(FlowManagerEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if self
+            .collection_store
+            .borrow()
+            .peek_overlay()
+            .eq(&CollectionViewerOverlay::CollectionSettings)
+        {
+            match self.collection_settings.handle_key_event(key_event)? {
+                Some(CollectionSettingsEvent::Changed) => {
+                    self.sync_collection_changes();
+                    self.forget_connections();
+                }
+                Some(CollectionSettingsEvent::Close) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(CollectionSettingsEvent::Quit) => return Ok(Some(Command::Quit)),
+                None => {}
+            }
+            return Ok(None);
+        }
+
         if self.collection_store.borrow().get_selected_pane().is_none() {
             match key_event.code {
                 KeyCode::Char('r') => {
@@ -407,8 +1828,40 @@ impl Eventful for CollectionViewer<'_> {
                     self.update_focus(PaneFocus::Editor);
                     self.update_selection(Some(PaneFocus::Editor));
                 }
-                KeyCode::Tab => {
-                    let next_pane = self.collection_store.borrow().get_focused_pane().next();
+                KeyCode::Char('R') => self.run_collection(),
+                KeyCode::Char('z') => self.toggle_maximized(),
+                KeyCode::Char('L') => self.cycle_orientation(),
+                KeyCode::Char('O') => self.toggle_offline(),
+                KeyCode::Char('y') => self
+                    .collection_store
+                    .borrow_mut()
+                    .push_overlay(CollectionViewerOverlay::CopyAs),
+                KeyCode::Char('P') => self.show_request_preview(),
+                KeyCode::Char('E') => self.show_export_prompt(),
+                KeyCode::Char('W') => self.toggle_watch(),
+                KeyCode::Char('T') => self
+                    .collection_store
+                    .borrow_mut()
+                    .push_overlay(CollectionViewerOverlay::Templates),
+                KeyCode::Char('F') => self
+                    .collection_store
+                    .borrow_mut()
+                    .push_overlay(CollectionViewerOverlay::Flows),
+                KeyCode::Char(',') if self.collection_store.borrow().is_read_only() => {
+                    self.notify_read_only()
+                }
+                KeyCode::Char(',') => self
+                    .collection_store
+                    .borrow_mut()
+                    .push_overlay(CollectionViewerOverlay::CollectionSettings),
+                KeyCode::Char('?') => {
+                    self.help.reset();
+                    self.collection_store
+                        .borrow_mut()
+                        .push_overlay(CollectionViewerOverlay::Help);
+                }
+                KeyCode::Tab => {
+                    let next_pane = self.collection_store.borrow().get_focused_pane().next();
                     self.update_focus(next_pane);
                 }
                 KeyCode::BackTab => {
@@ -432,6 +1885,11 @@ impl Eventful for CollectionViewer<'_> {
                         .collection_store
                         .borrow_mut()
                         .push_overlay(CollectionViewerOverlay::CreateRequest),
+                    Some(SidebarEvent::CreateRequestFromTemplate) => {
+                        let mut store = self.collection_store.borrow_mut();
+                        store.push_overlay(CollectionViewerOverlay::CreateRequest);
+                        store.push_overlay(CollectionViewerOverlay::SelectTemplate);
+                    }
                     Some(SidebarEvent::EditRequest) => self
                         .collection_store
                         .borrow_mut()
@@ -450,6 +1908,7 @@ impl Eventful for CollectionViewer<'_> {
                         .push_overlay(CollectionViewerOverlay::DeleteSidebarItem(item_id)),
                     Some(SidebarEvent::RemoveSelection) => self.update_selection(None),
                     Some(SidebarEvent::SyncCollection) => self.sync_collection_changes(),
+                    Some(SidebarEvent::ReadOnly) => self.notify_read_only(),
                     Some(SidebarEvent::Quit) => return Ok(Some(Command::Quit)),
                     Some(SidebarEvent::RebuildView) => self.rebuild_everything(),
                     // when theres no event we do nothing
@@ -457,20 +1916,40 @@ impl Eventful for CollectionViewer<'_> {
                 },
                 PaneFocus::ReqUri => match self.request_uri.handle_key_event(key_event)? {
                     Some(RequestUriEvent::Quit) => return Ok(Some(Command::Quit)),
-                    Some(RequestUriEvent::SendRequest) => hac_core::net::handle_request(
-                        self.collection_store
-                            .borrow()
-                            .get_selected_request()
-                            .as_ref()
-                            .unwrap(),
-                        self.request_tx.clone(),
-                    ),
+                    Some(RequestUriEvent::SendRequest) => self.send_selected_request(),
                     Some(RequestUriEvent::RemoveSelection) => self.update_selection(None),
                     // when theres no event we do nothing
                     None => {}
                 },
                 PaneFocus::Preview => match self.response_viewer.handle_key_event(key_event)? {
                     Some(ResponseViewerEvent::RemoveSelection) => self.update_selection(None),
+                    Some(ResponseViewerEvent::EditAssertions) => self
+                        .collection_store
+                        .borrow_mut()
+                        .push_overlay(CollectionViewerOverlay::EditAssertions),
+                    Some(ResponseViewerEvent::ReviewAssertions(notes)) => {
+                        let message = match notes.len() {
+                            0 => "the request already has these assertions".to_string(),
+                            1 => "added 1 assertion from the response".to_string(),
+                            n => format!("added {n} assertions from the response"),
+                        };
+                        self.request_status.notify(message);
+                        self.assertions_editor.review(notes);
+                        self.collection_store
+                            .borrow_mut()
+                            .push_overlay(CollectionViewerOverlay::EditAssertions);
+                    }
+                    Some(ResponseViewerEvent::SelectResponse(idx)) => self.select_response(idx),
+                    Some(ResponseViewerEvent::Notify(message)) => {
+                        self.request_status.notify(message)
+                    }
+                    Some(ResponseViewerEvent::RequestChanged(message)) => {
+                        self.request_status.notify(message);
+                        self.sync_collection_changes();
+                    }
+                    Some(ResponseViewerEvent::ShowRuns) => self.show_run_timings(),
+                    Some(ResponseViewerEvent::OpenLink(url)) => self.ask_about_link(url),
+                    Some(ResponseViewerEvent::SaveBody(path)) => self.show_save_prompt(path),
                     Some(ResponseViewerEvent::Quit) => return Ok(Some(Command::Quit)),
                     // when theres no event we do nothing
                     None => {}
@@ -478,6 +1957,12 @@ impl Eventful for CollectionViewer<'_> {
                 PaneFocus::Editor => match self.request_editor.handle_key_event(key_event)? {
                     Some(RequestEditorEvent::RemoveSelection) => self.update_selection(None),
                     Some(RequestEditorEvent::Quit) => return Ok(Some(Command::Quit)),
+                    Some(RequestEditorEvent::EditBodyExternally) => {
+                        return Ok(self.edit_body_externally())
+                    }
+                    Some(RequestEditorEvent::Notify(message)) => {
+                        self.request_status.notify(message)
+                    }
                     // when theres no event we do nothing
                     None => {}
                 },
@@ -486,41 +1971,147 @@ impl Eventful for CollectionViewer<'_> {
 
         Ok(None)
     }
+
+    /// pastes go to the input of the open popup, or to the selected pane when
+    /// there is no popup. Popups without inputs drop them, so nothing leaks
+    /// to the panes behind it
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Command>> {
+        let overlay = self.collection_store.borrow().peek_overlay();
+        let selected_pane = self.collection_store.borrow().get_selected_pane();
+
+        match overlay {
+            CollectionViewerOverlay::CreateRequest
+            | CollectionViewerOverlay::EditRequest
+            | CollectionViewerOverlay::CreateDirectory
+            | CollectionViewerOverlay::EditDirectory => _ = self.sidebar.handle_paste(text)?,
+            CollectionViewerOverlay::HeadersForm(_) => {
+                _ = self.request_editor.handle_paste(text)?
+            }
+            CollectionViewerOverlay::EditAssertions => {
+                _ = self.assertions_editor.handle_paste(text)?
+            }
+            CollectionViewerOverlay::SecretPrompt(_) => {
+                _ = self.secret_prompt.handle_paste(text)?
+            }
+            CollectionViewerOverlay::Export => _ = self.export_prompt.handle_paste(text)?,
+            CollectionViewerOverlay::Watch => _ = self.watch_prompt.handle_paste(text)?,
+            CollectionViewerOverlay::SaveBody => _ = self.save_prompt.handle_paste(text)?,
+            CollectionViewerOverlay::Templates => _ = self.template_manager.handle_paste(text)?,
+            CollectionViewerOverlay::Flows => _ = self.flow_manager.handle_paste(text)?,
+            CollectionViewerOverlay::CollectionSettings => {
+                _ = self.collection_settings.handle_paste(text)?
+            }
+            CollectionViewerOverlay::None => match selected_pane {
+                Some(PaneFocus::ReqUri) => _ = self.request_uri.handle_paste(text)?,
+                Some(PaneFocus::Editor) => _ = self.request_editor.handle_paste(text)?,
+                Some(PaneFocus::Sidebar) => _ = self.sidebar.handle_paste(text)?,
+                Some(PaneFocus::Preview) | None => {}
+            },
+            _ => {}
+        }
+
+        Ok(None)
+    }
 }
 
-pub fn build_layout(size: Rect) -> ExplorerLayout {
+/// the key as written on the config, eg: `A-1`. Only chords with alt or
+/// control are given, as anything else is typed into inputs
+fn key_chord(key_event: KeyEvent) -> Option<String> {
+    let KeyCode::Char(c) = key_event.code else {
+        return None;
+    };
+    match key_event.modifiers {
+        KeyModifiers::ALT => Some(format!("A-{c}")),
+        KeyModifiers::CONTROL => Some(format!("C-{c}")),
+        _ => None,
+    }
+}
+
+/// stamp of the collection as it would be written to its file
+fn serialized_stamp(collection: &Collection) -> Option<u64> {
+    hac_core::fs::collection_contents(collection)
+        .ok()
+        .map(|contents| hac_core::fs::stamp(contents.as_bytes()))
+}
+
+/// copies of every request of the collection by id, the collection shares
+/// its requests with the store, so they have to be cloned out of it
+fn snapshot_requests(collection: &Collection) -> HashMap<String, Request> {
+    collection
+        .flatten_requests()
+        .iter()
+        .map(|request| {
+            let request = request.read().unwrap();
+            (request.id.clone(), request.clone())
+        })
+        .collect()
+}
+
+/// in `auto`, the editor and the response go side by side only when the area
+/// looks wider than tall, cells being about twice as tall as they are wide,
+/// and each of them still gets this many columns
+const MIN_SIDE_BY_SIDE_WIDTH: u16 = 45;
+
+/// builds the layout of the viewer, a `maximized` pane takes the area every
+/// pane would share
+pub fn build_layout(
+    size: Rect,
+    orientation: LayoutOrientation,
+    maximized: Option<PaneFocus>,
+) -> ExplorerLayout {
     let [top_pane, hint_pane] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Fill(1), Constraint::Length(1)])
         .areas(size);
 
-    let [sidebar, right_pane] = Layout::default()
+    let [mut sidebar, right_pane] = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Length(30), Constraint::Fill(1)])
         .areas(top_pane);
 
-    let [req_uri, req_builder] = Layout::default()
+    let [mut req_uri, req_builder] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Fill(1)])
         .areas(right_pane);
 
-    let [req_editor, response_preview] = if size.width < 120 {
-        Layout::default()
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .direction(Direction::Vertical)
-            .areas(req_builder)
-    } else {
-        Layout::default()
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .direction(Direction::Horizontal)
-            .areas(req_builder)
+    let direction = match orientation {
+        LayoutOrientation::Horizontal => Direction::Horizontal,
+        LayoutOrientation::Vertical => Direction::Vertical,
+        LayoutOrientation::Auto => {
+            let wide = req_builder.width.ge(&req_builder.height.saturating_mul(2));
+            match wide && req_builder.width.ge(&MIN_SIDE_BY_SIDE_WIDTH.mul(2)) {
+                true => Direction::Horizontal,
+                false => Direction::Vertical,
+            }
+        }
     };
+    let [mut req_editor, mut response_preview] = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .direction(direction)
+        .areas(req_builder);
+
+    match maximized {
+        Some(PaneFocus::Sidebar) => sidebar = top_pane,
+        Some(PaneFocus::ReqUri) => req_uri = Rect::new(top_pane.x, top_pane.y, top_pane.width, 3),
+        Some(PaneFocus::Editor) => req_editor = top_pane,
+        Some(PaneFocus::Preview) => response_preview = top_pane,
+        None => {}
+    }
 
     let create_req_form = Rect::new(
         size.width.div(4),
         size.height.div(2).saturating_sub(6),
         size.width.div(2),
         11,
+    )
+    .clamp(size);
+
+    let help_width = size.width.saturating_sub(4).min(70);
+    let help_popup = Rect::new(
+        size.width.saturating_sub(help_width).div(2),
+        2,
+        help_width,
+        size.height.saturating_sub(4),
     );
 
     ExplorerLayout {
@@ -530,5 +2121,1534 @@ pub fn build_layout(size: Rect) -> ExplorerLayout {
         req_editor,
         response_preview,
         create_req_form,
+        help_popup,
+    }
+}
+
+/// eg: `<A-1>..<A-9>`
+fn recent_keys_label(keys: &[String]) -> String {
+    match (keys.first(), keys.last()) {
+        (Some(first), Some(last)) if first.ne(last) => format!("<{first}>..<{last}>"),
+        (Some(first), _) => format!("<{first}>"),
+        _ => String::from("unbound"),
+    }
+}
+
+fn help_sections(config: &hac_config::Config) -> Vec<HelpSection> {
+    vec![
+        HelpSection::new("general")
+            .entry("r", "selects the requests sidebar")
+            .entry("u", "selects the request uri")
+            .entry("e", "selects the request editor")
+            .entry("p", "selects the response preview")
+            .entry("tab/<S-tab>", "moves focus between panes")
+            .entry("enter", "selects the focused pane")
+            .hint("?", "help", 1)
+            .hint("enter", "select", 1)
+            .hint("tab", "next pane", 0)
+            .entry("z/<C-f>", "maximizes or restores the focused pane")
+            .entry(
+                "L",
+                "switches the layout between auto, side by side and stacked",
+            )
+            .entry("R", "runs every request in the collection")
+            .entry("F", "lists the flows, to run them or pick their steps")
+            .entry("y", "copies the request as another format")
+            .entry("P", "previews the request exactly as it will be sent")
+            .entry("E", "exports the request or collection as a .http file")
+            .entry(
+                "W",
+                "sends the request every few seconds or on file changes, again to stop",
+            )
+            .entry("T", "manages request templates, s saves the request as one")
+            .entry(
+                ",",
+                "edits the collection settings, like its proxy or timeout",
+            )
+            .entry("<C-r>", "resends the request from any pane")
+            .entry(
+                recent_keys_label(&config.recent_request_keys),
+                "opens the requests opened last, most recent first",
+            )
+            .entry("<C-x>", "cancels the running request")
+            .entry("O", "toggles offline mode, nothing is sent while it is on")
+            .entry("?", "toggle this help window")
+            .entry("<F9>", "lists what is running on the background")
+            .entry("<F12>", "shows the debug logs")
+            .entry("<C-c>", "quits the application"),
+        builtins::BUILTINS.iter().fold(
+            HelpSection::new("built-in variables"),
+            |section, builtin| section.entry(builtin.usage, builtin.description),
+        ),
+        HelpSection::new("sidebar")
+            .entry("j/<down>", "select item below")
+            .entry("k/<up>", "select item above")
+            .entry("enter", "opens the request or toggles the directory")
+            .entry(
+                "n",
+                "creates a new request, from a template if there are any",
+            )
+            .entry("d", "creates a new directory")
+            .entry("e", "edits the selected item, request tags included")
+            .entry("D", "deletes the selected item")
+            .hint("n", "new", 1)
+            .hint("e", "rename", 1)
+            .hint("D", "delete", 0)
+            .entry("J/K", "moves the selected item down or up")
+            .entry("</>", "moves the request out of or into a directory")
+            .entry(
+                "/",
+                "filters the requests by name or uri, words like #smoke by tag",
+            )
+            .entry("esc", "clears the filter or leaves the sidebar")
+            .hint("/", "filter", 0),
+        HelpSection::new("request uri")
+            .entry("enter", "sends the request")
+            .entry("tab", "completes the uri with the selected suggestion")
+            .entry("up/down", "selects a suggestion")
+            .entry(
+                "<C-g>",
+                "suggests urls sent from any collection, or this one",
+            )
+            .entry("esc", "dismisses the suggestions or leaves the uri")
+            .hint("enter", "send", 1)
+            .hint("tab", "complete", 1)
+            .hint("<C-g>", "history", 0),
+        HelpSection::new("request editor")
+            .entry("tab/<S-tab>", "changes the editor tab")
+            .entry("?", "shows the headers help on the headers tab")
+            .entry("enter", "edits the notes on the notes tab, esc saves them")
+            .hint("tab", "next tab", 1)
+            .hint("<S-tab>", "previous tab", 0),
+        HelpSection::from_keymap("body editor (normal)", &config.editor_keys.normal)
+            .hint_action(&Action::EnterMode(EditorMode::Insert), "insert", 1)
+            .hint_action(&Action::EditExternally, "external editor", 0)
+            .hint_action(&Action::ToggleWrap, "wrap", 0),
+        HelpSection::from_keymap("body editor (insert)", &config.editor_keys.insert).hint_action(
+            &Action::EnterMode(EditorMode::Normal),
+            "normal",
+            1,
+        ),
+        HelpSection::new("response")
+            .entry("tab/<S-tab>", "changes the response tab")
+            .entry("j/k", "scrolls the response")
+            .entry(
+                "w",
+                "toggles wrapping long lines of the preview and raw tabs",
+            )
+            .entry("h/l", "scrolls the headers or unwrapped lines sideways")
+            .entry("0/$", "jumps to the start or end of the lines")
+            .entry("[/]", "moves through the response history")
+            .entry("1-9", "picks a response from the history")
+            .entry("z", "toggles decompression")
+            .entry(
+                "F",
+                "toggles sending on a fresh connection instead of reusing one",
+            )
+            .entry(
+                "D",
+                "displays the body as json, xml, html, yaml or plain text, kept per request",
+            )
+            .entry("s", "saves a binary body, like an image, asking where to")
+            .entry(
+                "H",
+                "cycles the http version: negotiated, HTTP/1.1 or HTTP/2",
+            )
+            .entry(
+                "v/V",
+                "selects a block or whole lines of the preview, y copies them",
+            )
+            .entry(
+                "yp/yv",
+                "copies the json path or the value shown on the preview breadcrumb",
+            )
+            .entry(
+                "C",
+                "captures the value on the breadcrumb into a variable of the request",
+            )
+            .entry(
+                "o",
+                "moves between the links of the preview with tab, enter opens one",
+            )
+            .entry("t", "lists the timings of past runs of the request")
+            .entry("e", "edits the assertions on the assertions tab")
+            .entry(
+                "A",
+                "adds assertions on the status, content type and the value on the breadcrumb",
+            )
+            .entry(
+                "y/Y",
+                "copies the sha-256 or md5 of the body on the bytes tab",
+            )
+            .entry("esc", "leaves the response")
+            .hint("s", "save", 1)
+            .hint("yv", "copy value", 1)
+            .hint("v", "select", 0)
+            .hint("D", "display as", 0)
+            .hint("z", "decompress", 0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::popups::export_path;
+    use super::*;
+    use crate::test_utils::{make_collection, make_request, make_response, make_store};
+    use hac_core::activity::ActivityState;
+    use hac_core::net::download::Progress;
+    use ratatui::style::{Color, Modifier};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn users_store() -> Rc<RefCell<CollectionStore>> {
+        let request = Request {
+            id: "list".into(),
+            ..make_request("list users", "http://localhost:3000")
+        };
+        let store = make_store(make_collection("users", vec![request]));
+        store.borrow_mut().select_request("list");
+        store
+    }
+
+    #[test]
+    fn test_build_layout_with_degenerate_sizes() {
+        let maximized = [
+            None,
+            Some(PaneFocus::Sidebar),
+            Some(PaneFocus::ReqUri),
+            Some(PaneFocus::Editor),
+            Some(PaneFocus::Preview),
+        ];
+
+        for size in [Rect::new(0, 0, 1, 1), Rect::new(0, 0, 200, 5)] {
+            for pane in maximized {
+                let layout = build_layout(size, LayoutOrientation::Auto, pane);
+                for popup in [layout.create_req_form, layout.help_popup] {
+                    assert_eq!(
+                        popup.intersection(size),
+                        popup,
+                        "{popup:?} outside {size:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_maximized_pane_takes_the_panes_area() {
+        let size = Rect::new(0, 0, 80, 22);
+        let regular = build_layout(size, LayoutOrientation::Auto, None);
+        let maximized = build_layout(size, LayoutOrientation::Auto, Some(PaneFocus::Preview));
+
+        assert_eq!(maximized.response_preview, Rect::new(0, 0, 80, 21));
+        assert_eq!(maximized.req_editor, regular.req_editor);
+        assert_eq!(maximized.sidebar, regular.sidebar);
+        assert_eq!(maximized.hint_pane, regular.hint_pane);
+    }
+
+    #[test]
+    fn test_maximizing_survives_resizing_and_follows_focus() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, users_store(), &colors, &config, false);
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(viewer.layout.sidebar, Rect::new(0, 0, 80, 21));
+
+        let bigger = Rect::new(0, 0, 120, 30);
+        viewer.resize(bigger);
+        assert_eq!(viewer.layout.sidebar, Rect::new(0, 0, 120, 29));
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(viewer.layout.req_uri, Rect::new(0, 0, 120, 3));
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(
+            viewer.layout,
+            build_layout(bigger, LayoutOrientation::Auto, None)
+        );
+    }
+
+    #[test]
+    fn test_auto_orientation_follows_the_aspect_ratio() {
+        let side_by_side =
+            |layout: &ExplorerLayout| layout.req_editor.y.eq(&layout.response_preview.y);
+
+        let wide = Rect::new(0, 0, 120, 30);
+        assert!(side_by_side(&build_layout(
+            wide,
+            LayoutOrientation::Auto,
+            None
+        )));
+        assert!(!side_by_side(&build_layout(
+            wide,
+            LayoutOrientation::Vertical,
+            None
+        )));
+
+        // a pane rotated to be taller than wide, and one too narrow to split
+        let tall = Rect::new(0, 0, 120, 60);
+        assert!(!side_by_side(&build_layout(
+            tall,
+            LayoutOrientation::Auto,
+            None
+        )));
+        let narrow = Rect::new(0, 0, 80, 22);
+        assert!(!side_by_side(&build_layout(
+            narrow,
+            LayoutOrientation::Auto,
+            None
+        )));
+        assert!(side_by_side(&build_layout(
+            narrow,
+            LayoutOrientation::Horizontal,
+            None
+        )));
+    }
+
+    #[test]
+    fn test_switching_orientation_keeps_focus_and_overlays() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, false);
+        viewer.register_command_handler(tx).unwrap();
+        viewer.set_orientation(LayoutOrientation::Auto);
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .unwrap();
+        let focused = store.borrow().get_focused_pane();
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::NONE))
+            .unwrap();
+
+        assert_eq!(viewer.orientation, LayoutOrientation::Horizontal);
+        assert_eq!(
+            viewer.layout,
+            build_layout(size, LayoutOrientation::Horizontal, None)
+        );
+        assert_eq!(store.borrow().get_focused_pane(), focused);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Command::LayoutChanged(LayoutOrientation::Horizontal))
+        ));
+
+        // an open overlay stays open while auto picks a new split on resize
+        viewer.set_orientation(LayoutOrientation::Auto);
+        store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::Help);
+        let wide = Rect::new(0, 0, 120, 30);
+        viewer.resize(wide);
+        assert_eq!(
+            viewer.layout,
+            build_layout(wide, LayoutOrientation::Auto, None)
+        );
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::Help);
+    }
+
+    #[test]
+    fn test_pastes_only_reach_the_selected_input() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, false);
+        let uri = || {
+            let request = store.borrow().get_selected_request().unwrap();
+            let uri = request.read().unwrap().uri.clone();
+            uri
+        };
+
+        // nothing is selected, so nothing takes the text
+        viewer.handle_paste("/ignored".into()).unwrap();
+        assert_eq!(uri(), "http://localhost:3000");
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE))
+            .unwrap();
+        viewer.handle_paste("/users\n".into()).unwrap();
+        assert_eq!(uri(), "http://localhost:3000/users");
+        assert!(!viewer.request_status.is_sending());
+
+        // a popup without inputs swallows the paste
+        store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::CopyAs);
+        viewer.handle_paste("/ignored".into()).unwrap();
+        assert_eq!(uri(), "http://localhost:3000/users");
+    }
+
+    #[test]
+    fn test_jumping_to_recent_requests_keeps_body_edits() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let list = store.borrow().get_selected_request().unwrap();
+        let mut other = list.read().unwrap().clone();
+        other.id = "other".into();
+        other.name = "other".into();
+        store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::InsertRequest(RequestKind::Single(
+                Arc::new(RwLock::new(other)),
+            )));
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, true);
+        let jump = KeyEvent::new(KeyCode::Char('1'), KeyModifiers::ALT);
+        let selected = || {
+            let request = store.borrow().get_selected_request().unwrap();
+            let id = request.read().unwrap().id.clone();
+            id
+        };
+
+        // nothing was opened before
+        viewer.handle_key_event(jump).unwrap();
+        assert_eq!(selected(), "list");
+
+        store.borrow_mut().select_request("other");
+        viewer.rebuild_everything();
+        viewer.request_editor.set_body("{}");
+        assert!(viewer
+            .recent_requests_segment()
+            .is_some_and(|segment| segment.eq(&Segment::right(Line::from(vec![
+                "A-1".fg(colors.normal.yellow),
+                " list users".fg(colors.bright.black),
+            ])))));
+
+        viewer.handle_key_event(jump).unwrap();
+        assert_eq!(selected(), "list");
+        let other = store.borrow().get_request_by_id("other").unwrap();
+        assert_eq!(other.read().unwrap().body.as_deref(), Some("{}"));
+
+        viewer.handle_key_event(jump).unwrap();
+        assert_eq!(selected(), "other");
+    }
+
+    #[test]
+    fn test_opening_links_from_the_response() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, true);
+        let press = |viewer: &mut CollectionViewer, code: KeyCode| {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        };
+        let selected = || {
+            let request = store.borrow().get_selected_request().unwrap();
+            let request = request.read().unwrap();
+            (
+                request.name.clone(),
+                request.method.clone(),
+                request.uri.clone(),
+            )
+        };
+
+        viewer.ask_about_link("https://a.dev/users/2".into());
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::OpenLink("https://a.dev/users/2".into())
+        );
+        press(&mut viewer, KeyCode::Esc);
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+        assert_eq!(selected().2, "http://localhost:3000");
+
+        viewer.ask_about_link("https://a.dev/users/2".into());
+        press(&mut viewer, KeyCode::Char('n'));
+        assert_eq!(
+            selected(),
+            (
+                "a.dev/users/2".into(),
+                RequestMethod::Get,
+                "https://a.dev/users/2".into()
+            )
+        );
+        assert!(store.borrow().get_request_by_id("list").is_some());
+
+        store.borrow_mut().select_request("list");
+        viewer.rebuild_everything();
+        viewer.ask_about_link("https://a.dev/users/3".into());
+        press(&mut viewer, KeyCode::Char('r'));
+        assert_eq!(
+            selected(),
+            (
+                "list users".into(),
+                RequestMethod::Get,
+                "https://a.dev/users/3".into()
+            )
+        );
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+    }
+
+    #[test]
+    fn test_wrapping_is_kept_for_the_session() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, true);
+        assert!(!viewer.request_editor.body_wrap());
+        assert!(viewer.response_viewer.wrap());
+
+        viewer
+            .response_viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE))
+            .unwrap();
+        viewer.request_editor.set_body_wrap(true);
+        viewer.rebuild_everything();
+
+        assert!(viewer.request_editor.body_wrap());
+        assert!(!viewer.response_viewer.wrap());
+    }
+
+    #[test]
+    fn test_panes_are_left_as_they_were_on_each_request() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let list = store.borrow().get_selected_request().unwrap();
+        list.write().unwrap().method = RequestMethod::Post;
+        list.write().unwrap().body = Some("{\n  \"a\": 1,\n  \"b\": 2\n}".into());
+        let mut other = list.read().unwrap().clone();
+        other.id = "other".into();
+        store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::InsertRequest(RequestKind::Single(
+                Arc::new(RwLock::new(other)),
+            )));
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, true);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        viewer.update_selection(Some(PaneFocus::Editor));
+        for code in [KeyCode::Char('j'), KeyCode::Char('j'), KeyCode::Char('l')] {
+            viewer.request_editor.handle_key_event(key(code)).unwrap();
+        }
+        viewer.update_selection(None);
+        for code in [KeyCode::Tab, KeyCode::Char('j'), KeyCode::Char('w')] {
+            viewer.response_viewer.handle_key_event(key(code)).unwrap();
+        }
+        let editor = viewer.request_editor.view_state();
+        let response = viewer.response_viewer.view_state();
+
+        // a request that wasn't visited yet starts from the top
+        store.borrow_mut().select_request("other");
+        viewer.rebuild_everything();
+        assert_ne!(viewer.request_editor.view_state(), editor);
+        assert_ne!(viewer.response_viewer.view_state(), response);
+
+        // and switching back finds everything where it was left
+        store.borrow_mut().select_request("list");
+        viewer.rebuild_everything();
+        assert_eq!(viewer.request_editor.view_state(), editor);
+        assert_eq!(viewer.response_viewer.view_state(), response);
+    }
+
+    #[tokio::test]
+    async fn test_pending_work_is_what_quitting_would_lose() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let size = Rect::new(0, 0, 80, 22);
+        let mut viewer = CollectionViewer::new(size, store.clone(), &colors, &config, true);
+        viewer.register_command_handler(tx).unwrap();
+        assert_eq!(viewer.pending_work(), PendingWork::default());
+        assert_eq!(viewer.pending_work().summary(), None);
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE))
+            .unwrap();
+        viewer.handle_paste("/users".into()).unwrap();
+        let request = store.borrow().get_selected_request().unwrap();
+        let request = request.read().unwrap().clone();
+        viewer.dispatch_request(request);
+        assert_eq!(
+            viewer.pending_work().summary().as_deref(),
+            Some("1 unsaved request, 1 request in flight")
+        );
+
+        viewer.sync_collection_changes();
+        assert_eq!(viewer.pending_work().unsaved_requests, 0);
+    }
+
+    /// rewrites the collection file as some other program would, renaming
+    /// the collection
+    fn rename_on_disk(path: &std::path::Path, name: &str) {
+        let mut json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        json["info"]["name"] = name.into();
+        std::fs::write(path, serde_json::to_string_pretty(&json).unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_changes_on_disk_are_reloaded_or_asked_about() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        let store = users_store();
+        {
+            let collection = store.borrow().get_collection().unwrap();
+            let mut collection = collection.borrow_mut();
+            collection.path = path.clone();
+            std::fs::write(
+                &path,
+                hac_core::fs::collection_contents(&collection).unwrap(),
+            )
+            .unwrap();
+        }
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let collection_name = |store: &Rc<RefCell<CollectionStore>>| {
+            store
+                .borrow()
+                .get_collection()
+                .unwrap()
+                .borrow()
+                .info
+                .name
+                .clone()
+        };
+
+        // nothing of ours would be lost, so the file is just reloaded
+        rename_on_disk(&path, "renamed");
+        viewer.sync_collection_changes();
+        assert_eq!(collection_name(&store), "renamed");
+        assert_eq!(viewer.sync_state(), SyncState::Clean);
+        let selected = store.borrow().get_selected_request().unwrap();
+        assert_eq!(selected.read().unwrap().id, "list");
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE))
+            .unwrap();
+        viewer.handle_paste("/users".into()).unwrap();
+        assert_eq!(viewer.sync_state(), SyncState::Dirty);
+
+        // both changed, so nothing is written until the user picks a side
+        rename_on_disk(&path, "renamed again");
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        viewer.sync_collection_changes();
+        assert_eq!(viewer.sync_state(), SyncState::Conflicted);
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::ConfirmConflict
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), on_disk);
+
+        // closing the prompt keeps the conflict, the next sync asks again
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+        assert_eq!(viewer.sync_state(), SyncState::Conflicted);
+        viewer.sync_collection_changes();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), on_disk);
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(viewer.tasks.wait(std::time::Duration::from_secs(5)).await);
+        assert_eq!(collection_name(&store), "renamed again");
+        assert_eq!(viewer.sync_state(), SyncState::Clean);
+        let selected = store.borrow().get_selected_request().unwrap();
+        assert_eq!(selected.read().unwrap().uri, "http://localhost:3000");
+
+        let copy = dir.path().join("renamed_(conflicted_copy).json");
+        let copy = Collection::load(copy).unwrap();
+        assert_eq!(copy.info.name, "renamed (conflicted copy)");
+        assert_eq!(
+            copy.request("list users").unwrap().uri,
+            "http://localhost:3000/users"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_collections_are_never_written() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        let store = users_store();
+        {
+            let collection = store.borrow().get_collection().unwrap();
+            let mut collection = collection.borrow_mut();
+            collection.path = path.clone();
+            collection.read_only = true;
+            std::fs::write(
+                &path,
+                hac_core::fs::collection_contents(&collection).unwrap(),
+            )
+            .unwrap();
+        }
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        assert_eq!(viewer.sync_state(), SyncState::ReadOnly);
+
+        // requests can still be tweaked to be sent, it just stays in memory
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE))
+            .unwrap();
+        viewer.handle_paste("/users".into()).unwrap();
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        viewer.sync_collection_changes();
+        viewer.sync_before_quit();
+        assert!(viewer.tasks.wait(std::time::Duration::from_secs(5)).await);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), on_disk);
+        assert_eq!(viewer.pending_work().unsaved_requests, 0);
+        let selected = store.borrow().get_selected_request().unwrap();
+        assert_eq!(selected.read().unwrap().uri, "http://localhost:3000/users");
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char(','), KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+    }
+
+    #[tokio::test]
+    async fn test_only_the_latest_send_is_kept_when_responses_arrive_out_of_order() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let request = store.borrow().get_selected_request().unwrap();
+        let request = request.read().unwrap().clone();
+
+        viewer.dispatch_request(request.clone());
+        let first = viewer.pending_send.unwrap();
+        viewer.dispatch_request(request);
+        let second = viewer.pending_send.unwrap();
+        assert_ne!(first, second);
+
+        let progress = Progress {
+            received: 2048,
+            total: None,
+            elapsed: std::time::Duration::from_secs(1),
+        };
+        viewer.handle_progress(second, progress);
+        viewer.handle_response(second, "list".into(), make_response(200));
+        viewer.handle_response(first, "list".into(), make_response(500));
+
+        let history = viewer.responses_map.get("list").unwrap();
+        assert_eq!(history.tabs().len(), 1);
+        let shown = history.selected().unwrap();
+        assert_eq!(shown.borrow().status, Some(reqwest::StatusCode::OK));
+        assert!(!viewer.request_status.is_sending());
+
+        // the replaced send is listed as cancelled, the one with a body
+        // slow enough to report progress as a download
+        let entries = viewer.activity.entries();
+        assert_eq!(entries[0].state, ActivityState::Cancelled);
+        assert_eq!(entries[1].kind, ActivityKind::Download);
+        assert_eq!(entries[1].state, ActivityState::Done("200 OK".into()));
+        assert_eq!(send_outcome(&make_response(302)), Ok("302 Found".into()));
+        assert!(send_outcome(&make_response(404)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generated_values_are_kept_on_the_response() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let request = store.borrow().get_selected_request().unwrap();
+        request
+            .write()
+            .unwrap()
+            .uri
+            .push_str("?page={{$randomInt 4 4}}");
+
+        viewer.send_selected_request();
+        let send_id = viewer.pending_send.unwrap();
+        viewer.handle_response(send_id, "list".into(), make_response(200));
+
+        let history = viewer.responses_map.get("list").unwrap();
+        assert_eq!(
+            history.selected().unwrap().borrow().generated,
+            vec![("$randomInt 4 4".to_string(), "4".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uris_are_cleaned_up_only_on_what_is_sent() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let request = store.borrow().get_selected_request().unwrap();
+        let typed = "  http://localhost:3000/search?q=a b\n";
+        request.write().unwrap().uri = typed.into();
+
+        viewer.send_selected_request();
+        assert!(viewer.pending_send.is_some());
+        assert_eq!(request.read().unwrap().uri, typed);
+        // the warning about the space, the spinner and the cancel hint
+        assert_eq!(viewer.request_status.segments().len(), 3);
+        viewer.cancel_request();
+
+        request.write().unwrap().uri = "http://localhost:3000/\nusers".into();
+        viewer.send_selected_request();
+        assert!(viewer.pending_send.is_none());
+        let Some(Command::Error(report)) = rx.recv().await else {
+            panic!("expected the uri to be refused");
+        };
+        assert_eq!(report.message, "URL contains a newline at position 23");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_are_warned_about_but_sent() {
+        let colors = hac_colors::Colors::default();
+        let mut config = hac_config::load_config();
+        config.max_request_body = 4;
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let request = store.borrow().get_selected_request().unwrap();
+        request.write().unwrap().body = Some("{}".into());
+
+        viewer.send_selected_request();
+        // the spinner and the cancel hint
+        assert_eq!(viewer.request_status.segments().len(), 2);
+        viewer.cancel_request();
+
+        request.write().unwrap().body = Some("{\"id\": 1}".into());
+        viewer.send_selected_request();
+        assert!(viewer.pending_send.is_some());
+        // the warning joins them
+        assert_eq!(viewer.request_status.segments().len(), 3);
+        viewer.cancel_request();
+    }
+
+    #[tokio::test]
+    async fn test_watching_resends_until_stopped() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let press = |viewer: &mut CollectionViewer, code: KeyCode| {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        };
+
+        press(&mut viewer, KeyCode::Char('W'));
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::Watch
+        );
+        // the interval from the config is suggested, and sending starts
+        // right away
+        press(&mut viewer, KeyCode::Enter);
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+        let send_id = viewer.pending_send.unwrap();
+        viewer.handle_response(send_id, "list".into(), make_response(200));
+        assert!(viewer.watch_segment().is_some());
+
+        // nothing is due until the interval runs out
+        viewer.handle_tick().unwrap();
+        assert!(viewer.pending_send.is_none());
+
+        // every response is compared to the one before
+        let request = store.borrow().get_selected_request().unwrap();
+        let request = request.read().unwrap().clone();
+        viewer.dispatch_request(request);
+        let send_id = viewer.pending_send.unwrap();
+        let mut response = make_response(200);
+        response.body = Some("{\"count\": 2}".into());
+        viewer.handle_response(send_id, "list".into(), response);
+        assert_eq!(viewer.watch.as_ref().unwrap().body_changed, Some(true));
+
+        press(&mut viewer, KeyCode::Char('W'));
+        assert!(viewer.watch.is_none());
+
+        // selecting another request stops watching the previous one
+        press(&mut viewer, KeyCode::Char('W'));
+        press(&mut viewer, KeyCode::Enter);
+        viewer.cancel_request();
+        viewer.watch.as_mut().unwrap().request_id = "another".into();
+        viewer.handle_tick().unwrap();
+        assert!(viewer.watch.is_none());
+
+        // what can't be watched is refused without starting anything
+        press(&mut viewer, KeyCode::Char('W'));
+        press(&mut viewer, KeyCode::Tab);
+        for _ in 0..".".len() {
+            press(&mut viewer, KeyCode::Backspace);
+        }
+        viewer.handle_paste("/nowhere/to/be/found".into()).unwrap();
+        press(&mut viewer, KeyCode::Enter);
+        assert!(viewer.watch.is_none());
+        assert!(viewer.pending_send.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_responses_of_cancelled_sends_are_discarded() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let request = store.borrow().get_selected_request().unwrap();
+        let request = request.read().unwrap().clone();
+
+        viewer.dispatch_request(request);
+        let send_id = viewer.pending_send.unwrap();
+        viewer.cancel_request();
+        viewer.handle_response(send_id, "list".into(), make_response(200));
+
+        assert!(!viewer.responses_map.contains_key("list"));
+    }
+
+    #[tokio::test]
+    async fn test_resending_from_any_pane() {
+        let colors = hac_colors::Colors::default();
+        let mut config = hac_config::load_config();
+        config.resend_cancels_pending = false;
+        let store = users_store();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx.clone()).unwrap();
+        let resend = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE))
+            .unwrap();
+        viewer.handle_key_event(resend).unwrap();
+        let send_id = viewer.pending_send.unwrap();
+        assert!(viewer.request_status.is_sending());
+
+        // the request in flight is kept, and the user is told why
+        viewer.handle_key_event(resend).unwrap();
+        assert_eq!(viewer.pending_send, Some(send_id));
+        // the notice, the spinner and the cancel hint
+        assert_eq!(viewer.request_status.segments().len(), 3);
+
+        let mut config = config.clone();
+        config.resend_cancels_pending = true;
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetPendingRequest(false));
+
+        viewer.handle_key_event(resend).unwrap();
+        let send_id = viewer.pending_send.unwrap();
+        viewer.handle_key_event(resend).unwrap();
+        assert!(viewer.pending_send.is_some_and(|id| id.ne(&send_id)));
+        assert!(viewer.request_status.is_sending());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hooks_only_run_once_the_collection_is_trusted() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let set_hook = |command: &str| {
+            let collection = store.borrow().get_collection().unwrap();
+            collection.borrow_mut().hooks = Some(Hooks {
+                pre_request: Some(Hook {
+                    command: command.into(),
+                    timeout_ms: None,
+                }),
+                post_response: None,
+            });
+        };
+        set_hook("echo TOKEN=abc");
+        let request = store.borrow().get_selected_request().unwrap();
+        request.write().unwrap().uri.push_str("?token={{TOKEN}}");
+        let press = |viewer: &mut CollectionViewer, c: char| {
+            viewer
+                .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap();
+        };
+
+        viewer.send_selected_request();
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::ConfirmHooks
+        );
+        press(&mut viewer, 'n');
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+        assert!(viewer.pending_send.is_none());
+
+        // without the hook the variable it prints is missing
+        viewer.send_selected_request();
+        press(&mut viewer, 's');
+        assert!(viewer.pending_send.is_none());
+        assert!(matches!(rx.recv().await, Some(Command::Error(_))));
+
+        viewer.send_selected_request();
+        press(&mut viewer, 't');
+        let hook_send = viewer.pending_send.unwrap();
+        let Some(Command::PreRequestHookFinished {
+            viewer_id,
+            send_id,
+            request_id,
+            result,
+        }) = rx.recv().await
+        else {
+            panic!("expected the hook to finish");
+        };
+        assert_eq!(viewer_id, viewer.id());
+        assert_eq!(send_id, hook_send);
+        viewer.handle_pre_request_hook(send_id, request_id, result);
+        assert!(viewer.pending_send.is_some_and(|id| id.ne(&hook_send)));
+        viewer.cancel_request();
+
+        // trusted until the hooks change
+        viewer.send_selected_request();
+        assert_eq!(store.borrow().peek_overlay(), CollectionViewerOverlay::None);
+        viewer.cancel_request();
+
+        set_hook("echo 'vault is sealed' >&2; exit 1");
+        viewer.send_selected_request();
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::ConfirmHooks
+        );
+        press(&mut viewer, 't');
+        let Some(Command::PreRequestHookFinished {
+            send_id,
+            request_id,
+            result,
+            ..
+        }) = rx.recv().await
+        else {
+            panic!("expected the hook to finish");
+        };
+        viewer.handle_pre_request_hook(send_id, request_id, result);
+        assert!(viewer.pending_send.is_none());
+        let Some(Command::Error(report)) = rx.recv().await else {
+            panic!("expected the hook failure to be reported");
+        };
+        assert_eq!(report.message, "pre-request hook failed");
+        assert!(report.to_string().contains("vault is sealed"));
+    }
+
+    #[test]
+    fn test_status_bar_shows_the_active_environment() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        store
+            .borrow()
+            .get_collection()
+            .unwrap()
+            .borrow_mut()
+            .environments = Some(vec![Environment {
+            name: "staging".into(),
+            variables: Default::default(),
+            secrets: Default::default(),
+        }]);
+        store.borrow_mut().select_environment("staging");
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+
+        terminal
+            .draw(|f| viewer.draw(f, f.size()).unwrap())
+            .unwrap();
+        let status_line = terminal.backend().buffer().content[80 * 21..]
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+
+        assert!(status_line.starts_with("[? -> help]"));
+        assert!(status_line.ends_with("env: staging"));
+    }
+
+    #[test]
+    fn test_hint_line_follows_the_selected_pane() {
+        let colors = hac_colors::Colors::default();
+        let mut config = hac_config::load_config();
+        config.editor_keys.normal.insert(
+            "a".into(),
+            hac_config::KeyAction::Simple(Action::EnterMode(EditorMode::Insert)),
+        );
+        let store = users_store();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        let status_line = |viewer: &mut CollectionViewer, width: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(width, 22)).unwrap();
+            viewer.resize(Rect::new(0, 0, width, 22));
+            terminal
+                .draw(|f| viewer.draw(f, f.size()).unwrap())
+                .unwrap();
+            let width = usize::from(width);
+            terminal.backend().buffer().content[width * 21..]
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+
+        assert_eq!(
+            status_line(&mut viewer, 80),
+            "[? -> help] [enter -> select] [tab -> next pane]"
+        );
+
+        let select = |pane| {
+            store
+                .borrow_mut()
+                .dispatch(CollectionStoreAction::SetSelectedPane(Some(pane)));
+        };
+        select(PaneFocus::Sidebar);
+        assert_eq!(
+            status_line(&mut viewer, 80),
+            "[? -> help] [n -> new] [e -> rename] [D -> delete] [/ -> filter]"
+        );
+        // the least important hints are dropped instead of wrapping
+        assert_eq!(
+            status_line(&mut viewer, 40),
+            "[? -> help] [n -> new] [e -> rename]"
+        );
+
+        // requests without a body open the editor on the headers
+        select(PaneFocus::Editor);
+        assert_eq!(
+            status_line(&mut viewer, 80),
+            "[? -> help] [tab -> next tab] [<S-tab> -> previous tab]"
+        );
+
+        // keys come from the keymap, so rebinding shows up on the hints
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT))
+            .unwrap();
+        assert_eq!(
+            status_line(&mut viewer, 80),
+            "[? -> help] [a/i -> insert] [<C-e> -> external editor] [<C-w> -> wrap]"
+        );
+
+        select(PaneFocus::Preview);
+        assert_eq!(
+            status_line(&mut viewer, 80),
+            "[? -> help] [s -> save] [yv -> copy value] [v -> select] [D -> display as]"
+        );
+    }
+
+    #[test]
+    fn test_nothing_is_sent_while_offline() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        viewer.register_command_handler(tx).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('O'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(store.borrow().is_offline());
+        assert!(viewer.client_options().offline);
+        assert!(matches!(rx.try_recv(), Ok(Command::OfflineChanged(true))));
+
+        store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetPendingRequest(true));
+        viewer.send_selected_request();
+        assert!(viewer.pending_send.is_none());
+        assert!(!store.borrow().has_pending_request());
+
+        terminal
+            .draw(|f| viewer.draw(f, f.size()).unwrap())
+            .unwrap();
+        let status_line = terminal.backend().buffer().content[80 * 21..]
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(status_line.starts_with("offline mode, the request was not sent"));
+        assert!(status_line.ends_with(" OFFLINE "));
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('O'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(!viewer.client_options().offline);
+        assert!(matches!(rx.try_recv(), Ok(Command::OfflineChanged(false))));
+    }
+
+    #[test]
+    fn test_monochrome_draws_without_colors() {
+        let colors = hac_colors::Colors::monochrome();
+        let config = hac_config::load_config();
+        let store = users_store();
+        store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::SetFocusedPane(PaneFocus::Sidebar));
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+
+        terminal
+            .draw(|f| viewer.draw(f, f.size()).unwrap())
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        assert!(buffer
+            .content
+            .iter()
+            .all(|cell| cell.fg.eq(&Color::Reset) && cell.bg.eq(&Color::Reset)));
+
+        // focus is told by reverse video, as there is no color to tell it
+        let sidebar = viewer.layout.sidebar;
+        let corner = buffer.get(sidebar.x, sidebar.y);
+        assert!(corner.modifier.contains(Modifier::REVERSED));
+        let other = viewer.layout.req_uri;
+        let corner = buffer.get(other.x, other.y);
+        assert!(!corner.modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_creating_a_request_from_a_template() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let mut template = {
+            let request = store.borrow().get_selected_request().unwrap();
+            let request = request.read().unwrap();
+            hac_core::templates::RequestTemplate::from_request("json post".into(), &request)
+        };
+        template.method = RequestMethod::Post;
+        template.headers = Some(vec![HeaderMap {
+            pair: ("Accept".into(), "application/json".into()),
+            enabled: true,
+        }]);
+        store.borrow_mut().save_template(template);
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            true,
+        );
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        viewer.register_command_handler(tx).unwrap();
+
+        for code in [KeyCode::Char('r'), KeyCode::Char('n')] {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        }
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::SelectTemplate
+        );
+
+        for code in [KeyCode::Char('j'), KeyCode::Enter, KeyCode::Char('x')] {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        }
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::CreateRequest
+        );
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+
+        let created = store
+            .borrow()
+            .get_collection()
+            .unwrap()
+            .borrow()
+            .find_request("x");
+        let created = created.unwrap().read().unwrap().clone();
+        assert_eq!(created.method, RequestMethod::Post);
+        assert_eq!(created.uri, "");
+        assert_eq!(created.headers.unwrap()[0].pair.0, "Accept");
+
+        // the template stays as it was saved
+        let template = store.borrow().get_templates()[0].clone();
+        assert_eq!(template.name, "json post");
+    }
+
+    #[test]
+    fn test_exporting_the_selected_request() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        store
+            .borrow()
+            .get_collection()
+            .unwrap()
+            .borrow_mut()
+            .environments = Some(vec![Environment {
+            name: "local".into(),
+            variables: [("port".to_string(), "3000".to_string())].into(),
+            secrets: Default::default(),
+        }]);
+        store.borrow_mut().select_environment("local");
+        store
+            .borrow()
+            .get_selected_request()
+            .unwrap()
+            .write()
+            .unwrap()
+            .uri = "http://localhost:{{port}}".into();
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('E'), KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(
+            store.borrow().peek_overlay(),
+            CollectionViewerOverlay::Export
+        );
+        assert_eq!(viewer.export_prompt.choice(), 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("list.http");
+        viewer.export(path.to_string_lossy().to_string(), 0);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "@port = 3000\n\n### list users\nGET http://localhost:{{port}}\n"
+        );
+
+        viewer.export(path.to_string_lossy().to_string(), 1);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("### list users"));
+        assert_eq!(export_path("users/admin"), "./users-admin.http");
+    }
+
+    #[test]
+    fn test_filtering_the_sidebar_and_opening_a_match() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        let mut post = store
+            .borrow()
+            .get_request_by_id("list")
+            .unwrap()
+            .read()
+            .unwrap()
+            .clone();
+        post.id = "post".into();
+        post.name = "create post".into();
+        store
+            .borrow_mut()
+            .dispatch(CollectionStoreAction::InsertRequest(RequestKind::Single(
+                Arc::new(RwLock::new(post)),
+            )));
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            false,
+        );
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+        let selected = || {
+            let request = store.borrow().get_selected_request().unwrap();
+            let id = request.read().unwrap().id.clone();
+            id
+        };
+
+        for code in [KeyCode::Char('r'), KeyCode::Char('/')] {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        }
+        viewer.handle_paste("POST\n".into()).unwrap();
+        assert_eq!(store.borrow().get_sidebar_filter(), "POST");
+        assert_eq!(store.borrow().get_hovered_request(), Some("post".into()));
+
+        terminal
+            .draw(|f| viewer.draw(f, f.size()).unwrap())
+            .unwrap();
+        let sidebar = terminal
+            .backend()
+            .buffer()
+            .content
+            .chunks(80)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(sidebar.contains("create post"));
+        assert!(!sidebar.contains("list users"));
+        assert!(sidebar.contains("/POST"));
+
+        // enter applies the filter, and then opens the match
+        for code in [KeyCode::Enter, KeyCode::Enter] {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        }
+        assert_eq!(selected(), "post");
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(store.borrow().get_sidebar_filter(), "");
+        assert_eq!(selected(), "post");
+        assert_eq!(store.borrow().get_selected_pane(), Some(PaneFocus::Sidebar));
+    }
+
+    #[test]
+    fn test_editing_the_body_externally() {
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let store = users_store();
+        // `GET` requests open on the headers tab
+        let request = store.borrow().get_selected_request().unwrap();
+        request.write().unwrap().method = RequestMethod::Post;
+        request.write().unwrap().body = Some("{}".into());
+        let mut viewer = CollectionViewer::new(
+            Rect::new(0, 0, 80, 22),
+            store.clone(),
+            &colors,
+            &config,
+            true,
+        );
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        viewer.register_command_handler(tx).unwrap();
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE))
+            .unwrap();
+        let command = viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert!(matches!(
+            command,
+            Some(Command::EditExternally { content, extension, .. })
+                if content.eq("{}") && extension.eq("json")
+        ));
+
+        let body = || request.read().unwrap().body.clone();
+
+        viewer.handle_external_edit(Ok("{\"id\": 1}".into()));
+        assert_eq!(viewer.request_editor.body().to_string(), "{\"id\": 1}");
+        assert_eq!(body().as_deref(), Some("{\"id\": 1}"));
+
+        // a failed edit keeps the body and tells the user why
+        viewer.handle_external_edit(Err("vi exited with exit status: 1".into()));
+        assert_eq!(body().as_deref(), Some("{\"id\": 1}"));
+        assert_eq!(viewer.request_status.segments().len(), 1);
     }
 }
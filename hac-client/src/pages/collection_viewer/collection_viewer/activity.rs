@@ -0,0 +1,93 @@
+use hac_core::activity::ActivityKind;
+use hac_core::net::download::{format_size, Progress};
+use hac_core::net::request_manager::Response;
+
+use crate::pages::collection_viewer::collection_store::CollectionStoreAction;
+use crate::pages::collection_viewer::collection_viewer::CollectionViewer;
+
+impl<'cv> CollectionViewer<'cv> {
+    /// progress of sends other than the pending one is stale and ignored.
+    /// Progress is only reported for bodies that take a while to arrive, so
+    /// the send is listed as a download from then on
+    pub fn handle_progress(&mut self, send_id: u64, progress: Progress) {
+        if self.pending_send.eq(&Some(send_id)) {
+            if let Some(id) = self.send_activity {
+                self.activity.set_kind(id, ActivityKind::Download);
+                self.activity.progress(id, progress_detail(&progress));
+            }
+            self.request_status.progress(progress);
+        }
+    }
+
+    /// aborts the request in flight, if any. The response viewer goes back to
+    /// whatever it was showing before the request was sent
+    pub(super) fn cancel_request(&mut self) {
+        if let Some(id) = self.send_activity.take() {
+            self.activity.cancel(id);
+        }
+        if self.request_status.cancel() {
+            self.pending_send = None;
+            self.collection_store
+                .borrow_mut()
+                .dispatch(CollectionStoreAction::SetPendingRequest(false));
+        }
+    }
+
+    /// catches up with the activity panel, which can cancel the send or the
+    /// run from under us, and tells it how the run ended
+    pub(super) fn sync_activity(&mut self) {
+        if let Some(id) = self.send_activity {
+            if self.activity.is_cancelled(id) {
+                self.cancel_request();
+                self.request_status
+                    .notify("request cancelled from the activity panel");
+            }
+        }
+
+        if self.runner_panel.is_running() {
+            return;
+        }
+        let Some(id) = self.run_activity.take() else {
+            return;
+        };
+        // runs that were cancelled have no summary, and are already
+        // listed as such
+        if let Some(summary) = self.runner_panel.summary() {
+            let outcome = format!("{} passed, {} failed", summary.passed, summary.failed);
+            self.activity.finish(
+                id,
+                match summary.failed {
+                    0 => Ok(outcome),
+                    _ => Err(outcome),
+                },
+            );
+        }
+    }
+}
+
+/// how a send ended, as listed on the activity panel
+pub(super) fn send_outcome(response: &Response) -> Result<String, String> {
+    match (response.is_error, response.status) {
+        (false, Some(status)) if status.is_client_error() || status.is_server_error() => {
+            Err(status.to_string())
+        }
+        (false, Some(status)) => Ok(status.to_string()),
+        (false, None) => Ok(String::from("done")),
+        (true, _) => Err(response
+            .cause
+            .clone()
+            .unwrap_or_else(|| String::from("failed"))),
+    }
+}
+
+/// how much of the body arrived, eg: `1.2 MB of 4 MB`
+pub(super) fn progress_detail(progress: &Progress) -> String {
+    match progress.total {
+        Some(total) => format!(
+            "{} of {}",
+            format_size(progress.received),
+            format_size(total)
+        ),
+        None => format_size(progress.received),
+    }
+}
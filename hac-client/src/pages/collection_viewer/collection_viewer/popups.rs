@@ -0,0 +1,580 @@
+use hac_core::collection::types::*;
+use hac_core::command::Command;
+use hac_core::export::http_file;
+use hac_core::net::uri;
+use hac_core::net::wire::WireRequest;
+use hac_core::secrets::Secrets;
+use hac_core::variables;
+
+use crate::pages::collection_viewer::collection_store::CollectionStoreAction;
+use crate::pages::collection_viewer::collection_viewer::{
+    CollectionViewer, CollectionViewerOverlay,
+};
+use crate::pages::collection_viewer::request_uri::RequestUri;
+use crate::pages::confirm_popup::{ConfirmButton, ConfirmPopup, ConfirmPopupEvent};
+use crate::pages::Overlay;
+
+use std::ops::{Add, Div, Sub};
+use std::sync::{Arc, RwLock};
+
+use crossterm::event::KeyEvent;
+use ratatui::layout::Rect;
+use ratatui::Frame;
+
+/// the answers to the confirmation shown before sending destructive requests
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SendChoice {
+    Send,
+    Always,
+    Cancel,
+}
+
+/// the answers to the confirmation shown before running hooks of a
+/// collection that isn't trusted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum HookChoice {
+    Trust,
+    /// sends the request without running any hook, asking again next time
+    Skip,
+    Cancel,
+}
+
+/// what to do with a link picked on the response
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum LinkChoice {
+    /// adds a GET request to the url to the collection
+    NewRequest,
+    /// points the selected request to the url
+    ReplaceUri,
+}
+
+/// what to keep when the collection file changed under our own edits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum ConflictChoice {
+    /// drops our edits in favor of what is on disk
+    Reload,
+    /// writes our version, moving the one on disk to the trash
+    Overwrite,
+    /// writes our version to a new collection and reloads this one
+    SaveCopy,
+}
+
+impl<'cv> CollectionViewer<'cv> {
+    pub(super) fn show_run_timings(&mut self) {
+        if let Some(history) = self
+            .shown_request_id
+            .as_ref()
+            .and_then(|id| self.responses_map.get(id))
+        {
+            self.run_timings.set_runs(history.runs());
+            self.collection_store
+                .borrow_mut()
+                .push_overlay(CollectionViewerOverlay::RunTimings);
+        }
+    }
+
+    /// resolves the selected request like sending it would, but keeps going
+    /// when variables are missing so they can be pointed out on the preview
+    pub(super) fn show_request_preview(&mut self) {
+        let store = self.collection_store.borrow();
+        let Some(request) = store.get_selected_request() else {
+            return;
+        };
+
+        let request = store.with_defaults(&request.read().unwrap());
+        let layers = store.get_layers();
+        let sources = variables::sources(&request, &layers);
+        let (mut request, unresolved) =
+            variables::resolve_request_partially(&request, &layers.merged());
+        let secrets = store.secret_values();
+        drop(store);
+
+        // the preview shows the uri as it goes out, what was typed is kept
+        let options = self.client_options();
+        let (wire, mut warnings) = match uri::normalize(&request.uri) {
+            Ok(normalized) => {
+                request.uri = normalized.uri;
+                let wire = WireRequest::new(&request, &options);
+                (wire, normalized.warnings)
+            }
+            Err(e) => (Err(e.to_string()), vec![]),
+        };
+        let global_headers = options
+            .global_headers(&request)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        if request.raw_body {
+            warnings.push("the body is raw, its placeholders are sent as written".into());
+        }
+        self.request_preview.set_request(
+            wire,
+            unresolved,
+            sources,
+            global_headers,
+            secrets,
+            warnings,
+        );
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::RequestPreview);
+    }
+
+    /// the selected request is offered first, then the whole collection
+    pub(super) fn show_export_prompt(&mut self) {
+        let store = self.collection_store.borrow();
+        let Some(collection) = store.get_collection() else {
+            return;
+        };
+
+        let mut choices = vec![];
+        if let Some(request) = store.get_selected_request() {
+            let name = request.read().unwrap().name.clone();
+            choices.push(("request".into(), export_path(&name)));
+        }
+        choices.push((
+            "collection".into(),
+            export_path(&collection.borrow().info.name),
+        ));
+        drop(store);
+
+        self.export_prompt
+            .open("Export as a .http file".into(), choices);
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::Export);
+    }
+
+    pub(super) fn show_save_prompt(&mut self, path: String) {
+        self.save_prompt.open("Save the body".into(), vec![]);
+        self.save_prompt.set_path(path);
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::SaveBody);
+    }
+
+    /// variables of the collection and the active environment used by the
+    /// requests go along, so the file can be sent as is by other tools
+    pub(super) fn export(&mut self, path: String, choice: usize) {
+        let store = self.collection_store.borrow();
+        let Some(collection) = store.get_collection() else {
+            return;
+        };
+        let collection = collection.borrow();
+        let active = store.get_active_environment();
+        // resolved without the secrets of the store, so their values never
+        // end up on the export
+        let layers = collection
+            .layers(active.as_deref(), &Secrets::default())
+            .or_else(|_| collection.layers(None, &Secrets::default()))
+            .unwrap_or_default();
+        let environment = Environment {
+            name: active.unwrap_or_default(),
+            variables: layers.merged(),
+            secrets: Default::default(),
+        };
+        let environment = Some(&environment);
+
+        // the request is only offered, and so the first choice, when one is
+        // selected
+        let content = match store.get_selected_request().filter(|_| choice.eq(&0)) {
+            Some(request) => {
+                let request = collection.apply_defaults(&request.read().unwrap());
+                http_file::request(&request, environment)
+            }
+            None => http_file::collection(&collection, environment),
+        };
+        drop(collection);
+        drop(store);
+
+        match std::fs::write(&path, content) {
+            Ok(_) => self.request_status.notify(format!("exported to {path}")),
+            Err(e) => self
+                .request_status
+                .notify(format!("failed to export to {path}: {e}")),
+        }
+    }
+
+    /// every command of the collection is listed, as trusting it allows
+    /// all of them to run, not only the ones of this request
+    pub(super) fn build_confirm_hooks(&self) -> ConfirmPopup<'cv, HookChoice> {
+        let buttons = vec![
+            ConfirmButton::new("(t)rust", 't', self.colors.normal.green, HookChoice::Trust),
+            ConfirmButton::new(
+                "(s)end without hooks",
+                's',
+                self.colors.normal.blue,
+                HookChoice::Skip,
+            ),
+            ConfirmButton::new("(n)o", 'n', self.colors.normal.red, HookChoice::Cancel),
+        ];
+        let commands = self
+            .collection_store
+            .borrow()
+            .hook_commands()
+            .into_iter()
+            .map(|command| format!("$ {command}"))
+            .collect();
+        let message =
+            String::from("This collection runs these commands on your machine. Trust its hooks?");
+        ConfirmPopup::new(message, self.colors, buttons)
+            .with_details(commands)
+            .with_selected(2)
+    }
+
+    pub(super) fn handle_confirm_hooks_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> anyhow::Result<Option<Command>> {
+        let Some(popup) = self.confirm_hooks.as_mut() else {
+            return Ok(None);
+        };
+
+        let choice = match popup.handle_key_event(key_event)? {
+            Some(ConfirmPopupEvent::Confirm(choice)) => choice,
+            Some(ConfirmPopupEvent::Close) => HookChoice::Cancel,
+            Some(ConfirmPopupEvent::Quit) => return Ok(Some(Command::Quit)),
+            None => return Ok(None),
+        };
+
+        self.confirm_hooks = None;
+        self.collection_store.borrow_mut().pop_overlay();
+        match choice {
+            HookChoice::Trust => {
+                self.collection_store.borrow_mut().trust_hooks();
+                self.send_selected_request();
+            }
+            HookChoice::Skip => {
+                self.skip_hooks = true;
+                self.send_selected_request();
+            }
+            HookChoice::Cancel => self
+                .collection_store
+                .borrow_mut()
+                .dispatch(CollectionStoreAction::SetPendingRequest(false)),
+        }
+
+        Ok(None)
+    }
+
+    pub(super) fn draw_confirm_hooks(
+        &mut self,
+        frame: &mut Frame,
+        size: Rect,
+    ) -> anyhow::Result<()> {
+        let Some(confirm_hooks) = self.confirm_hooks.as_mut() else {
+            return Ok(());
+        };
+
+        let width = u16::min(size.width, 60);
+        let height = u16::min(size.height, 9 + confirm_hooks.details_height());
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(height).div(2)),
+            width,
+            height,
+        );
+
+        confirm_hooks.draw(frame, popup)
+    }
+
+    pub(super) fn build_confirm_send(
+        &self,
+        request: &Request,
+        host: &str,
+    ) -> ConfirmPopup<'cv, SendChoice> {
+        let buttons = vec![
+            ConfirmButton::new("(y)es", 'y', self.colors.normal.green, SendChoice::Send),
+            ConfirmButton::new(
+                "(a)lways for this host",
+                'a',
+                self.colors.normal.blue,
+                SendChoice::Always,
+            ),
+            ConfirmButton::new("(n)o", 'n', self.colors.normal.red, SendChoice::Cancel),
+        ];
+        let message = format!("Really send {} to {}?", request.method, host);
+        ConfirmPopup::new(message, self.colors, buttons).with_selected(2)
+    }
+
+    pub(super) fn handle_confirm_send_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        host: String,
+    ) -> anyhow::Result<Option<Command>> {
+        let Some(popup) = self.confirm_send.as_mut() else {
+            return Ok(None);
+        };
+
+        let confirmed = match popup.handle_key_event(key_event)? {
+            Some(ConfirmPopupEvent::Confirm(SendChoice::Send)) => true,
+            Some(ConfirmPopupEvent::Confirm(SendChoice::Always)) => {
+                self.trusted_hosts.insert(host);
+                true
+            }
+            Some(ConfirmPopupEvent::Confirm(SendChoice::Cancel))
+            | Some(ConfirmPopupEvent::Close) => false,
+            Some(ConfirmPopupEvent::Quit) => return Ok(Some(Command::Quit)),
+            None => return Ok(None),
+        };
+
+        self.confirm_send = None;
+        self.collection_store.borrow_mut().pop_overlay();
+        match self.awaiting_confirmation.take() {
+            Some(request) if confirmed => self.dispatch_request(request),
+            _ => self
+                .collection_store
+                .borrow_mut()
+                .dispatch(CollectionStoreAction::SetPendingRequest(false)),
+        }
+
+        Ok(None)
+    }
+
+    pub(super) fn draw_confirm_send(
+        &mut self,
+        frame: &mut Frame,
+        size: Rect,
+    ) -> anyhow::Result<()> {
+        let width = u16::min(size.width, 60);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(7).div(2)),
+            width,
+            u16::min(size.height, 7),
+        );
+
+        match self.confirm_send.as_mut() {
+            Some(confirm_send) => confirm_send.draw(frame, popup),
+            None => Ok(()),
+        }
+    }
+
+    pub(super) fn ask_about_conflict(&mut self) {
+        if self.confirm_conflict.is_some() {
+            return;
+        }
+
+        let name = self
+            .collection_store
+            .borrow()
+            .get_collection()
+            .map(|collection| collection.borrow().info.name.clone())
+            .unwrap_or_default();
+        let buttons = vec![
+            ConfirmButton::new(
+                "(r)eload",
+                'r',
+                self.colors.normal.red,
+                ConflictChoice::Reload,
+            ),
+            ConfirmButton::new(
+                "(o)verwrite",
+                'o',
+                self.colors.normal.yellow,
+                ConflictChoice::Overwrite,
+            ),
+            ConfirmButton::new(
+                "(s)ave as copy",
+                's',
+                self.colors.normal.green,
+                ConflictChoice::SaveCopy,
+            ),
+        ];
+        let message = format!(
+            "{name} was changed on disk while you had unsaved edits. Which version do you want to keep?"
+        );
+        self.confirm_conflict =
+            Some(ConfirmPopup::new(message, self.colors, buttons).with_selected(2));
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::ConfirmConflict);
+    }
+
+    pub(super) fn handle_confirm_conflict_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> anyhow::Result<Option<Command>> {
+        let Some(popup) = self.confirm_conflict.as_mut() else {
+            return Ok(None);
+        };
+
+        let choice = match popup.handle_key_event(key_event)? {
+            Some(ConfirmPopupEvent::Confirm(choice)) => Some(choice),
+            Some(ConfirmPopupEvent::Close) => None,
+            Some(ConfirmPopupEvent::Quit) => return Ok(Some(Command::Quit)),
+            None => return Ok(None),
+        };
+
+        self.confirm_conflict = None;
+        self.collection_store.borrow_mut().pop_overlay();
+        match choice {
+            Some(ConflictChoice::Reload) => {
+                self.reload_from_disk();
+                self.request_status
+                    .notify("reloaded from disk, your edits were dropped");
+            }
+            Some(ConflictChoice::Overwrite) => {
+                let collection = self.collection_with_edits();
+                self.conflicted = false;
+                self.write_collection(&collection, true);
+                self.request_status
+                    .notify("overwritten, the version on disk was moved to the trash");
+            }
+            Some(ConflictChoice::SaveCopy) => {
+                let collection = self.collection_with_edits();
+                let name = self.write_conflicted_copy(&collection);
+                self.reload_from_disk();
+                self.request_status
+                    .notify(format!("your version was saved as {name}"));
+            }
+            None => self
+                .request_status
+                .notify("nothing is saved until the conflict with the file is resolved"),
+        }
+
+        Ok(None)
+    }
+
+    pub(super) fn draw_confirm_conflict(
+        &mut self,
+        frame: &mut Frame,
+        size: Rect,
+    ) -> anyhow::Result<()> {
+        let width = u16::min(size.width, 60);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(9).div(2)),
+            width,
+            u16::min(size.height, 9),
+        );
+
+        match self.confirm_conflict.as_mut() {
+            Some(confirm_conflict) => confirm_conflict.draw(frame, popup),
+            None => Ok(()),
+        }
+    }
+
+    pub(super) fn ask_about_link(&mut self, url: String) {
+        let buttons = vec![
+            ConfirmButton::new(
+                "(n)ew request",
+                'n',
+                self.colors.normal.green,
+                LinkChoice::NewRequest,
+            ),
+            ConfirmButton::new(
+                "(r)eplace url",
+                'r',
+                self.colors.normal.yellow,
+                LinkChoice::ReplaceUri,
+            ),
+        ];
+        let message = format!(
+            "Open {url} as a new request in this collection, or as the url of this request?"
+        );
+        self.confirm_link = Some(ConfirmPopup::new(message, self.colors, buttons));
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::OpenLink(url));
+    }
+
+    pub(super) fn handle_confirm_link_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        url: String,
+    ) -> anyhow::Result<Option<Command>> {
+        let Some(popup) = self.confirm_link.as_mut() else {
+            return Ok(None);
+        };
+
+        let choice = match popup.handle_key_event(key_event)? {
+            Some(ConfirmPopupEvent::Confirm(choice)) => Some(choice),
+            Some(ConfirmPopupEvent::Close) => None,
+            Some(ConfirmPopupEvent::Quit) => return Ok(Some(Command::Quit)),
+            None => return Ok(None),
+        };
+
+        self.confirm_link = None;
+        self.collection_store.borrow_mut().pop_overlay();
+        match choice {
+            Some(LinkChoice::NewRequest) if self.collection_store.borrow().is_read_only() => {
+                self.notify_read_only()
+            }
+            Some(LinkChoice::NewRequest) => self.open_link_as_request(url),
+            Some(LinkChoice::ReplaceUri) => self.replace_uri_with_link(url),
+            None => {}
+        }
+
+        Ok(None)
+    }
+
+    pub(super) fn draw_confirm_link(
+        &mut self,
+        frame: &mut Frame,
+        size: Rect,
+    ) -> anyhow::Result<()> {
+        let width = u16::min(size.width, 60);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(9).div(2)),
+            width,
+            u16::min(size.height, 9),
+        );
+
+        match self.confirm_link.as_mut() {
+            Some(confirm_link) => confirm_link.draw(frame, popup),
+            None => Ok(()),
+        }
+    }
+
+    /// adds a GET request to `url` at the top of the collection, named after
+    /// the url, and switches to it
+    pub(super) fn open_link_as_request(&mut self, url: String) {
+        self.apply_body_edits();
+        let name = url
+            .split_once("://")
+            .map(|(_, rest)| rest.to_string())
+            .unwrap_or(url.clone());
+        let request = Request {
+            id: uuid::Uuid::new_v4().to_string(),
+            method: RequestMethod::Get,
+            name: name.clone(),
+            uri: url,
+            ..Default::default()
+        };
+        let id = request.id.clone();
+
+        let mut store = self.collection_store.borrow_mut();
+        store.dispatch(CollectionStoreAction::InsertRequest(RequestKind::Single(
+            Arc::new(RwLock::new(request)),
+        )));
+        store.select_request(&id);
+        drop(store);
+        self.rebuild_everything();
+        self.sync_collection_changes();
+        self.request_status
+            .notify(format!("created the request {name}"));
+    }
+
+    pub(super) fn replace_uri_with_link(&mut self, url: String) {
+        let Some(request) = self.collection_store.borrow().get_selected_request() else {
+            return;
+        };
+        request.write().unwrap().uri = url;
+        self.request_uri = RequestUri::new(
+            self.colors,
+            self.collection_store.clone(),
+            self.layout.req_uri,
+        );
+        self.sync_collection_changes();
+        self.request_status
+            .notify("replaced the url of the request, send it to see the new response");
+    }
+}
+
+/// suggested path for exporting something named `name`, on the directory hac
+/// was started from
+pub(super) fn export_path(name: &str) -> String {
+    format!("./{}.http", name.replace(std::path::is_separator, "-"))
+}
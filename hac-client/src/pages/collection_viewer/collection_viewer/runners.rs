@@ -0,0 +1,270 @@
+use hac_core::activity::ActivityKind;
+use hac_core::collection::types::Request;
+use hac_core::command::{Command, ErrorReport};
+use hac_core::hooks::{self, Hook};
+use hac_core::net::request_manager::Response;
+use hac_core::runner::RunOptions;
+
+use crate::pages::collection_viewer::collection_store::CollectionStoreAction;
+use crate::pages::collection_viewer::collection_viewer::{
+    CollectionViewer, CollectionViewerOverlay, NEXT_SEND_ID,
+};
+use crate::pages::collection_viewer::watch::{Watch, WatchTrigger};
+
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+impl<'cv> CollectionViewer<'cv> {
+    /// runs on the background as any send, so it shows up on the status bar
+    /// and can be cancelled the same way
+    pub(super) fn run_pre_request_hook(&mut self, hook: Hook, request: Request) {
+        let Some(sender) = self.global_command_sender.clone() else {
+            tracing::error!("attempted to run a hook without a command sender");
+            return;
+        };
+
+        let (method, uri) = (request.method.clone(), request.uri.clone());
+        let send_id = NEXT_SEND_ID.fetch_add(1, Ordering::Relaxed);
+        self.pending_send = Some(send_id);
+        let viewer_id = self.id;
+        let handle = tokio::spawn(async move {
+            let result = hooks::run_pre_request(&hook, &request)
+                .await
+                .map_err(ErrorReport::from);
+            let command = Command::PreRequestHookFinished {
+                viewer_id,
+                send_id,
+                request_id: request.id,
+                result,
+            };
+            if sender.send(command).is_err() {
+                tracing::error!("failed to send hook result through channel");
+            }
+        });
+        self.request_status.start(method, uri, handle);
+    }
+
+    /// a failing hook aborts the send, showing what the hook wrote to stderr
+    pub fn handle_pre_request_hook(
+        &mut self,
+        send_id: u64,
+        request_id: String,
+        result: Result<BTreeMap<String, String>, ErrorReport>,
+    ) {
+        if self.pending_send.ne(&Some(send_id)) {
+            tracing::debug!("discarding stale hook of send {send_id} for {request_id}");
+            return;
+        }
+
+        let selected_id = self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|request| request.read().unwrap().id.clone());
+
+        match result {
+            Ok(variables) if selected_id.eq(&Some(request_id.clone())) => {
+                self.hook_variables = Some((request_id, variables));
+                self.send_selected_request();
+            }
+            // another request was selected while the hook ran
+            Ok(_) => self.cancel_request(),
+            Err(report) => {
+                self.pending_send = None;
+                self.request_status.fail("pre-request hook failed");
+                self.collection_store
+                    .borrow_mut()
+                    .dispatch(CollectionStoreAction::SetPendingRequest(false));
+
+                let sender = self
+                    .global_command_sender
+                    .as_ref()
+                    .expect("should have a sender at this point");
+                if sender.send(Command::Error(report)).is_err() {
+                    tracing::error!("failed to send error command through channel");
+                }
+            }
+        }
+    }
+
+    /// the response is already shown by the time the hook runs, so a failure
+    /// only shows up as an error
+    pub(super) fn run_post_response_hook(
+        &mut self,
+        hook: Hook,
+        request: &Request,
+        response: &Response,
+    ) {
+        let Some(sender) = self.global_command_sender.clone() else {
+            return;
+        };
+
+        let summary = hooks::response_summary(request, response);
+        let request = request.clone();
+        self.tasks.spawn(async move {
+            if let Err(e) = hooks::run_post_response(&hook, &request, &summary).await {
+                if sender.send(Command::Error(ErrorReport::from(e))).is_err() {
+                    tracing::error!("failed to send error command through channel");
+                }
+            }
+        });
+    }
+
+    /// `W` asks how to watch the selected request, or stops watching it
+    pub(super) fn toggle_watch(&mut self) {
+        if self.watch.take().is_some() {
+            self.request_status.notify("stopped watching");
+            return;
+        }
+        if self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .is_none()
+        {
+            return;
+        }
+
+        self.watch_prompt.open(
+            "Watch the request".into(),
+            vec![
+                (
+                    "every few seconds".into(),
+                    self.config.watch_interval_secs.to_string(),
+                ),
+                ("on file changes".into(), ".".into()),
+            ],
+        );
+        self.watch_prompt
+            .set_input("Watch", "seconds between sends, or a path to watch");
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::Watch);
+    }
+
+    /// starts watching the selected request, sending it right away
+    pub(super) fn start_watch(&mut self, typed: String, choice: usize) {
+        let trigger = match WatchTrigger::parse(&typed, choice) {
+            Ok(trigger) => trigger,
+            Err(e) => return self.request_status.notify(e),
+        };
+        let Some(request) = self.collection_store.borrow().get_selected_request() else {
+            return;
+        };
+        let request_id = request.read().unwrap().id.clone();
+
+        self.watch = Some(Watch::new(request_id, trigger, Instant::now()));
+        self.resend_request();
+    }
+
+    /// sends the watched request once it is due. A send still in flight, or
+    /// anything waiting on the user, holds it back until it is done
+    pub(super) fn tick_watch(&mut self) {
+        let Some(watch) = self.watch.as_mut() else {
+            return;
+        };
+
+        let selected = self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|request| request.read().unwrap().id.clone());
+        if selected.ne(&Some(watch.request_id.clone())) {
+            self.watch = None;
+            self.request_status
+                .notify("stopped watching, another request was selected");
+            return;
+        }
+
+        let store = self.collection_store.borrow();
+        let busy =
+            store.has_pending_request() || store.peek_overlay().ne(&CollectionViewerOverlay::None);
+        drop(store);
+        if !busy && watch.is_due(Instant::now()) {
+            self.resend_request();
+        }
+    }
+
+    /// hooks run along with the requests only once the collection was
+    /// trusted, which is asked when sending a request on its own
+    fn run_options(&self) -> RunOptions {
+        RunOptions {
+            run_hooks: self.collection_store.borrow().hooks_trusted(),
+            ..RunOptions::new(&self.config.runner, self.client_options())
+        }
+    }
+
+    /// runs every request on the collection in order, opening the runner panel
+    /// so results can be followed as they arrive. If a run is already going on
+    /// we just show its progress again
+    pub(super) fn run_collection(&mut self) {
+        if !self.runner_panel.is_running() {
+            let variables = self.collection_store.borrow().get_variables();
+            let options = self.run_options();
+            let Some(collection) = self.collection_store.borrow().get_collection() else {
+                return;
+            };
+            let collection = collection.borrow();
+            let requests = collection
+                .flatten_requests()
+                .iter()
+                .map(|req| collection.runnable(&req.read().unwrap()))
+                .collect::<Vec<_>>();
+
+            let abort =
+                self.runner_panel
+                    .start(collection.info.name.clone(), requests, variables, options);
+            let id = self
+                .activity
+                .start(ActivityKind::Run, collection.info.name.clone());
+            self.activity.attach(id, abort);
+            self.run_activity = Some(id);
+        }
+
+        self.collection_store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::RunnerProgress);
+    }
+
+    /// runs the steps of the flow named `name`, stopping on the first one to
+    /// fail as the ones after it usually depend on it
+    pub(super) fn run_flow(&mut self, name: &str) {
+        if self.runner_panel.is_running() {
+            self.request_status
+                .notify("wait for the current run to finish first");
+            return;
+        }
+
+        let variables = self.collection_store.borrow().get_variables();
+        let options = self.run_options();
+        let Some(collection) = self.collection_store.borrow().get_collection() else {
+            return;
+        };
+        let requests = match collection.borrow().flow(name) {
+            Ok(requests) => requests,
+            Err(e) => {
+                self.request_status.notify(e.to_string());
+                return;
+            }
+        };
+
+        let abort = self.runner_panel.start(
+            format!("flow {name}"),
+            requests,
+            variables,
+            RunOptions {
+                bail: true,
+                ..options
+            },
+        );
+        let id = self
+            .activity
+            .start(ActivityKind::Run, format!("flow {name}"));
+        self.activity.attach(id, abort);
+        self.run_activity = Some(id);
+        let mut store = self.collection_store.borrow_mut();
+        store.pop_overlay();
+        store.push_overlay(CollectionViewerOverlay::RunnerProgress);
+    }
+}
@@ -0,0 +1,251 @@
+use hac_core::codegen::{self, Target};
+use hac_core::variables;
+
+use crate::clipboard;
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Sub};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// set of events `CopyAs` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CopyAsEvent {
+    /// user either copied a snippet or pressed `Esc`, the popup should be
+    /// hidden
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// popup listing every codegen target with a preview of the snippet for the
+/// selected request, pressing enter copies the snippet to the clipboard
+#[derive(Debug)]
+pub struct CopyAs<'ca> {
+    colors: &'ca hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    error: Option<String>,
+}
+
+impl<'ca> CopyAs<'ca> {
+    pub fn new(
+        colors: &'ca hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        CopyAs {
+            colors,
+            collection_store,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    fn target(&self) -> Target {
+        *Target::iter()
+            .nth(self.selected)
+            .expect("selected should always be a valid target")
+    }
+
+    /// generates the snippet for the selected target from the resolved
+    /// request, so snippets never contain `{{variables}}`
+    fn snippet(&self) -> anyhow::Result<String> {
+        let store = self.collection_store.borrow();
+        let Some(request) = store.get_selected_request() else {
+            anyhow::bail!("select a request to generate a snippet");
+        };
+
+        let request = store.with_defaults(&request.read().unwrap());
+        let request = variables::resolve_request(&request, &store.get_variables())?;
+        Ok(codegen::generate(self.target(), &request))
+    }
+
+    fn copy_selected(&mut self) -> Option<CopyAsEvent> {
+        match self.snippet().and_then(|snippet| clipboard::copy(&snippet)) {
+            Ok(_) => {
+                self.error = None;
+                Some(CopyAsEvent::Close)
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                None
+            }
+        }
+    }
+}
+
+impl Renderable for CopyAs<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let popup = build_layout(size);
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Copy as ".fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let [targets_pane, _, preview_pane, error_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(Target::iter().len() as u16),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(inner);
+
+        let targets = Target::iter()
+            .enumerate()
+            .map(|(idx, target)| match idx.eq(&self.selected) {
+                true => Line::from(Span::styled(
+                    format!(" {} ", target),
+                    self.colors
+                        .highlight(self.colors.normal.white, self.colors.normal.blue),
+                )),
+                false => Line::from(format!(" {} ", target).fg(self.colors.normal.white)),
+            })
+            .collect::<Vec<_>>();
+        frame.render_widget(Paragraph::new(targets), targets_pane);
+
+        let preview = match self.snippet() {
+            Ok(snippet) => snippet
+                .lines()
+                // tabs are rendered as a single cell, which makes go snippets
+                // look flat on the preview
+                .map(|line| Line::from(line.replace('\t', "    ").fg(self.colors.bright.black)))
+                .collect::<Vec<_>>(),
+            Err(e) => vec![Line::from(e.to_string().fg(self.colors.normal.red))],
+        };
+        frame.render_widget(Paragraph::new(preview), preview_pane);
+
+        if let Some(error) = self.error.as_ref() {
+            frame.render_widget(
+                Line::from(error.clone().fg(self.colors.normal.red)),
+                error_pane,
+            );
+        }
+
+        frame.render_widget(
+            Line::from(
+                "[j/k -> select] [enter -> copy] [esc -> close]".fg(self.colors.bright.black),
+            )
+            .centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for CopyAs<'_> {
+    type Result = CopyAsEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(CopyAsEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.error = None;
+                return Ok(Some(CopyAsEvent::Close));
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = usize::min(self.selected.add(1), Target::iter().len().sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Enter => return Ok(self.copy_selected()),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+fn build_layout(size: Rect) -> Rect {
+    let width = u16::min(size.width.saturating_sub(4), 100);
+    let height = u16::min(size.height.saturating_sub(4), 30);
+
+    Rect::new(
+        size.x.add(size.width.sub(width).div(2)),
+        size.y.add(size.height.sub(height).div(2)),
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_collection, make_request, make_store};
+    use hac_core::collection::types::*;
+    use std::collections::BTreeMap;
+
+    fn local_store() -> Rc<RefCell<CollectionStore>> {
+        make_store(Collection {
+            environments: Some(vec![Environment {
+                name: "local".into(),
+                variables: BTreeMap::from([(
+                    "base_url".to_string(),
+                    "http://localhost:3000".to_string(),
+                )]),
+                secrets: Default::default(),
+            }]),
+            ..make_collection("users", vec![make_request("users", "{{base_url}}/users")])
+        })
+    }
+
+    #[test]
+    fn test_snippets_use_the_resolved_request() {
+        let colors = hac_colors::Colors::default();
+        let mut copy_as = CopyAs::new(&colors, local_store());
+
+        for _ in 0..5 {
+            copy_as
+                .handle_key_event(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE))
+                .unwrap();
+        }
+
+        assert_eq!(copy_as.target(), Target::Go);
+        let snippet = copy_as.snippet().unwrap();
+        assert!(snippet.contains("\"http://localhost:3000/users\""));
+    }
+
+    #[test]
+    fn test_snippets_include_the_collection_defaults() {
+        let colors = hac_colors::Colors::default();
+        let store = local_store();
+        {
+            let store = store.borrow();
+            let collection = store.get_collection().unwrap();
+            let mut collection = collection.borrow_mut();
+            collection.base_url = Some("{{base_url}}/v1".into());
+            collection.default_headers = Some(vec![HeaderMap {
+                pair: ("X-Api-Key".into(), "abc".into()),
+                enabled: true,
+            }]);
+            store.get_selected_request().unwrap().write().unwrap().uri = "/users".into();
+        }
+        let copy_as = CopyAs::new(&colors, store);
+
+        assert_eq!(copy_as.target(), Target::JavaScript);
+        let snippet = copy_as.snippet().unwrap();
+        assert!(snippet.contains("\"http://localhost:3000/v1/users\""));
+        assert!(snippet.contains("\"X-Api-Key\": \"abc\""));
+    }
+}
@@ -0,0 +1,637 @@
+use hac_core::collection::types::{Flow, Request};
+use hac_core::collection::Collection;
+
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::input::{self, Input};
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Sub};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear};
+use ratatui::Frame;
+
+/// set of events `FlowManager` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FlowManagerEvent {
+    /// user pressed `Enter` on a flow, holds its name
+    Run(String),
+    /// the flows of the collection were edited and should be written
+    Changed,
+    /// user tried to change the flows of a read-only collection
+    ReadOnly,
+    /// user pressed `Esc` while browsing the flows
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// what the name being typed is for
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Naming {
+    New,
+    /// renaming the flow at the index
+    Rename(usize),
+}
+
+/// lists the flows of the collection, where they can be ran, created and
+/// have their steps picked and reordered
+#[derive(Debug)]
+pub struct FlowManager<'fm> {
+    colors: &'fm hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    /// the steps of the selected flow are shown instead of the flows
+    editing: bool,
+    selected_step: usize,
+    /// the requests of the collection are shown to add one as a step
+    picking: bool,
+    selected_request: usize,
+    naming: Option<Naming>,
+    name: String,
+}
+
+impl<'fm> FlowManager<'fm> {
+    pub fn new(
+        colors: &'fm hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        FlowManager {
+            colors,
+            collection_store,
+            selected: 0,
+            editing: false,
+            selected_step: 0,
+            picking: false,
+            selected_request: 0,
+            naming: None,
+            name: String::default(),
+        }
+    }
+
+    fn collection(&self) -> Option<Rc<RefCell<Collection>>> {
+        self.collection_store.borrow().get_collection()
+    }
+
+    fn flows(&self) -> Vec<Flow> {
+        self.collection()
+            .and_then(|collection| collection.borrow().flows.clone())
+            .unwrap_or_default()
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.collection()
+            .map(|collection| {
+                collection
+                    .borrow()
+                    .flatten_requests()
+                    .iter()
+                    .map(|request| request.read().unwrap().clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// runs `f` on the flows of the collection, creating them when there are
+    /// none yet
+    fn edit_flows<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Vec<Flow>),
+    {
+        if let Some(collection) = self.collection() {
+            f(collection.borrow_mut().flows.get_or_insert_with(Vec::new));
+        }
+    }
+
+    fn edit_steps<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Vec<String>),
+    {
+        let selected = self.selected;
+        self.edit_flows(|flows| {
+            if let Some(flow) = flows.get_mut(selected) {
+                f(&mut flow.steps);
+            }
+        });
+    }
+
+    fn start_naming(&mut self, naming: Naming) {
+        self.name = match naming {
+            Naming::New => String::default(),
+            Naming::Rename(idx) => match self.flows().get(idx) {
+                Some(flow) => flow.name.clone(),
+                None => return,
+            },
+        };
+        self.naming = Some(naming);
+    }
+
+    /// flow names have to be unique, as flows are ran by name
+    fn finish_naming(&mut self, naming: Naming) -> Option<FlowManagerEvent> {
+        let name = self.name.trim().to_string();
+        let flows = self.flows();
+        let taken = flows
+            .iter()
+            .enumerate()
+            .any(|(idx, flow)| flow.name.eq(&name) && naming.ne(&Naming::Rename(idx)));
+        if name.is_empty() || taken {
+            return None;
+        }
+
+        match naming {
+            Naming::New => {
+                self.edit_flows(|flows| {
+                    flows.push(Flow {
+                        name,
+                        steps: vec![],
+                    })
+                });
+                self.selected = flows.len();
+                self.selected_step = 0;
+                self.editing = true;
+            }
+            Naming::Rename(idx) => self.edit_flows(|flows| flows[idx].name = name),
+        }
+        self.naming = None;
+        Some(FlowManagerEvent::Changed)
+    }
+
+    fn handle_naming_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        naming: Naming,
+    ) -> Option<FlowManagerEvent> {
+        match key_event.code {
+            KeyCode::Esc => self.naming = None,
+            KeyCode::Enter => return self.finish_naming(naming),
+            KeyCode::Backspace => _ = self.name.pop(),
+            KeyCode::Char(c) => self.name.push(c),
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_flows_key_event(&mut self, key_event: KeyEvent) -> Option<FlowManagerEvent> {
+        let flows = self.flows();
+        if let KeyCode::Char('n' | 'r' | 'e' | 'l' | 'd') = key_event.code {
+            if self.collection_store.borrow().is_read_only() {
+                return Some(FlowManagerEvent::ReadOnly);
+            }
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return Some(FlowManagerEvent::Close),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = usize::min(self.selected.add(1), flows.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Enter => {
+                return flows
+                    .get(self.selected)
+                    .map(|flow| FlowManagerEvent::Run(flow.name.clone()))
+            }
+            KeyCode::Char('n') => self.start_naming(Naming::New),
+            KeyCode::Char('r') => self.start_naming(Naming::Rename(self.selected)),
+            KeyCode::Char('e') | KeyCode::Char('l') if self.selected.lt(&flows.len()) => {
+                self.editing = true;
+                self.selected_step = 0;
+            }
+            KeyCode::Char('d') if self.selected.lt(&flows.len()) => {
+                let selected = self.selected;
+                self.edit_flows(|flows| _ = flows.remove(selected));
+                self.selected = self.selected.min(flows.len().saturating_sub(2));
+                return Some(FlowManagerEvent::Changed);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_steps_key_event(&mut self, key_event: KeyEvent) -> Option<FlowManagerEvent> {
+        let total = self
+            .flows()
+            .get(self.selected)
+            .map(|flow| flow.steps.len())
+            .unwrap_or_default();
+        let step = self.selected_step;
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('h') => self.editing = false,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected_step = usize::min(step.add(1), total.saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected_step = step.saturating_sub(1),
+            KeyCode::Char('a') => {
+                self.picking = true;
+                self.selected_request = 0;
+            }
+            KeyCode::Char('J') if step.add(1).lt(&total) => {
+                self.edit_steps(|steps| steps.swap(step, step.add(1)));
+                self.selected_step = step.add(1);
+                return Some(FlowManagerEvent::Changed);
+            }
+            KeyCode::Char('K') if step.gt(&0) && step.lt(&total) => {
+                self.edit_steps(|steps| steps.swap(step, step.sub(1)));
+                self.selected_step = step.sub(1);
+                return Some(FlowManagerEvent::Changed);
+            }
+            KeyCode::Char('d') if step.lt(&total) => {
+                self.edit_steps(|steps| _ = steps.remove(step));
+                self.selected_step = step.min(total.saturating_sub(2));
+                return Some(FlowManagerEvent::Changed);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// steps are added after the selected one
+    fn handle_picking_key_event(&mut self, key_event: KeyEvent) -> Option<FlowManagerEvent> {
+        let requests = self.requests();
+        match key_event.code {
+            KeyCode::Esc => self.picking = false,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected_request = usize::min(
+                    self.selected_request.add(1),
+                    requests.len().saturating_sub(1),
+                );
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.selected_request = self.selected_request.saturating_sub(1)
+            }
+            KeyCode::Enter => {
+                let request = requests.get(self.selected_request)?;
+                let id = request.id.clone();
+                let at = match self.flows().get(self.selected) {
+                    Some(flow) if !flow.steps.is_empty() => {
+                        self.selected_step.add(1).min(flow.steps.len())
+                    }
+                    _ => 0,
+                };
+                self.edit_steps(|steps| steps.insert(at, id));
+                self.selected_step = at;
+                self.picking = false;
+                return Some(FlowManagerEvent::Changed);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn chevron(&self, selected: bool) -> (&'static str, ratatui::style::Color) {
+        match selected {
+            true => ("> ", self.colors.normal.red),
+            false => ("  ", self.colors.normal.white),
+        }
+    }
+
+    fn flow_lines(&self) -> Vec<Line<'static>> {
+        let Some(collection) = self.collection() else {
+            return vec![];
+        };
+        let collection = collection.borrow();
+
+        collection
+            .flows
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(idx, flow)| {
+                let (chevron, name_fg) = self.chevron(idx.eq(&self.selected));
+                let mut spans = vec![
+                    chevron.fg(self.colors.normal.red),
+                    flow.name.clone().fg(name_fg),
+                    format!(" {} steps", flow.steps.len()).fg(self.colors.bright.black),
+                ];
+                let broken = collection.broken_steps(flow).len();
+                if broken.gt(&0) {
+                    spans.push(format!(" {broken} broken").fg(self.colors.normal.red));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    /// steps whose request was deleted are kept, showing the id they had
+    fn step_lines(&self) -> Vec<Line<'static>> {
+        let Some(flow) = self.flows().into_iter().nth(self.selected) else {
+            return vec![];
+        };
+        let requests = self.requests();
+
+        flow.steps
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| {
+                let (chevron, name_fg) = self.chevron(idx.eq(&self.selected_step));
+                let mut spans = vec![
+                    chevron.fg(self.colors.normal.red),
+                    format!("{}. ", idx.add(1)).fg(self.colors.bright.black),
+                ];
+                match requests.iter().find(|request| request.id.eq(id)) {
+                    Some(request) => {
+                        spans.push(format!("{} ", request.method).fg(self.colors.normal.blue));
+                        spans.push(request.name.clone().fg(name_fg));
+                    }
+                    None => spans.push(
+                        format!("broken, request {id} no longer exists").fg(self.colors.normal.red),
+                    ),
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    fn request_lines(&self) -> Vec<Line<'static>> {
+        self.requests()
+            .iter()
+            .enumerate()
+            .map(|(idx, request)| {
+                let (chevron, name_fg) = self.chevron(idx.eq(&self.selected_request));
+                Line::from(vec![
+                    chevron.fg(self.colors.normal.red),
+                    format!("{} ", request.method).fg(self.colors.normal.blue),
+                    request.name.clone().fg(name_fg),
+                ])
+            })
+            .collect()
+    }
+}
+
+impl Renderable for FlowManager<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let width = u16::min(size.width.saturating_sub(4), 60);
+        let height = u16::min(size.height, 16);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.sub(height).div(2)),
+            width,
+            height,
+        );
+        frame.render_widget(Clear, popup);
+
+        let flow_name = self
+            .flows()
+            .get(self.selected)
+            .map(|flow| flow.name.clone())
+            .unwrap_or_default();
+        let (title, lines, selected, empty, hint) = match (self.editing, self.picking) {
+            (_, true) => (
+                format!(" Add a step to {flow_name} "),
+                self.request_lines(),
+                self.selected_request,
+                "the collection has no requests yet",
+                "[enter -> add] [esc -> back]",
+            ),
+            (true, false) => (
+                format!(" Steps of {flow_name} "),
+                self.step_lines(),
+                self.selected_step,
+                "no steps yet, a adds one",
+                "[a -> add] [J/K -> move] [d -> remove] [esc -> back]",
+            ),
+            (false, false) => (
+                " Flows ".to_string(),
+                self.flow_lines(),
+                self.selected,
+                "no flows yet, n creates one",
+                "[enter -> run] [n -> new] [e -> steps] [r -> rename] [d -> delete]",
+            ),
+        };
+        let hint = match self.naming {
+            Some(_) => "[enter -> confirm] [esc -> cancel]",
+            None => hint,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let input_height = if self.naming.is_some() { 3 } else { 0 };
+        let list_height = inner.height.saturating_sub(input_height).saturating_sub(1);
+
+        if lines.is_empty() {
+            let empty = Line::from(empty.fg(self.colors.bright.black)).centered();
+            frame.render_widget(
+                empty,
+                Rect::new(inner.x, inner.y, inner.width, 1).clamp(inner),
+            );
+        }
+
+        let scroll = selected.saturating_sub(usize::from(list_height).saturating_sub(1));
+        for (row, line) in lines
+            .into_iter()
+            .skip(scroll)
+            .take(list_height.into())
+            .enumerate()
+        {
+            let row_size = Rect::new(inner.x, inner.y.add(row as u16), inner.width, 1);
+            frame.render_widget(line, row_size);
+        }
+
+        if self.naming.is_some() {
+            let input_size = Rect::new(inner.x, inner.y.add(list_height), inner.width, 3);
+            let mut input = Input::new(self.colors, "Flow name".into());
+            input.focus();
+            let mut name = self.name.clone();
+            frame.render_stateful_widget(input, input_size.clamp(inner), &mut name);
+            frame.set_cursor(
+                input_size.x.add(self.name.chars().count().add(1) as u16),
+                input_size.y.add(1),
+            );
+        }
+
+        let hint_size = Rect::new(inner.x, inner.bottom().saturating_sub(1), inner.width, 1);
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_size,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for FlowManager<'_> {
+    type Result = FlowManagerEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(FlowManagerEvent::Quit));
+        }
+
+        if let Some(naming) = self.naming {
+            return Ok(self.handle_naming_key_event(key_event, naming));
+        }
+
+        Ok(match (self.editing, self.picking) {
+            (_, true) => self.handle_picking_key_event(key_event),
+            (true, false) => self.handle_steps_key_event(key_event),
+            (false, false) => self.handle_flows_key_event(key_event),
+        })
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if self.naming.is_some() {
+            self.name.push_str(&input::single_line(&text));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_collection, make_request, make_store};
+    use hac_core::collection::types::RequestMethod;
+
+    fn flows_store() -> Rc<RefCell<CollectionStore>> {
+        let request = |id: &str, method: RequestMethod, name: &str, uri: &str| Request {
+            id: id.into(),
+            method,
+            ..make_request(name, uri)
+        };
+        make_store(make_collection(
+            "users",
+            vec![
+                request("login", RequestMethod::Post, "login", "/login"),
+                request("create", RequestMethod::Post, "create user", "/users"),
+                request("cleanup", RequestMethod::Delete, "cleanup", "/users/1"),
+            ],
+        ))
+    }
+
+    fn press(manager: &mut FlowManager, code: KeyCode) -> Option<FlowManagerEvent> {
+        manager
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    fn steps(store: &Rc<RefCell<CollectionStore>>) -> Vec<String> {
+        let collection = store.borrow().get_collection().unwrap();
+        let collection = collection.borrow();
+        collection.flows.as_ref().unwrap()[0].steps.clone()
+    }
+
+    #[test]
+    fn test_creating_a_flow_and_ordering_its_steps() {
+        let colors = hac_colors::Colors::default();
+        let store = flows_store();
+        let mut manager = FlowManager::new(&colors, store.clone());
+
+        press(&mut manager, KeyCode::Char('n'));
+        manager.handle_paste("smoke".into()).unwrap();
+        assert_eq!(
+            press(&mut manager, KeyCode::Enter),
+            Some(FlowManagerEvent::Changed)
+        );
+        // a new flow goes straight to its steps
+        assert!(manager.editing);
+
+        // cleanup, then login before it
+        press(&mut manager, KeyCode::Char('a'));
+        press(&mut manager, KeyCode::Char('j'));
+        press(&mut manager, KeyCode::Char('j'));
+        press(&mut manager, KeyCode::Enter);
+        press(&mut manager, KeyCode::Char('a'));
+        press(&mut manager, KeyCode::Enter);
+        assert_eq!(steps(&store), vec!["cleanup", "login"]);
+        assert_eq!(manager.selected_step, 1);
+
+        press(&mut manager, KeyCode::Char('K'));
+        assert_eq!(steps(&store), vec!["login", "cleanup"]);
+        press(&mut manager, KeyCode::Char('a'));
+        press(&mut manager, KeyCode::Char('j'));
+        press(&mut manager, KeyCode::Enter);
+        assert_eq!(steps(&store), vec!["login", "create", "cleanup"]);
+
+        press(&mut manager, KeyCode::Esc);
+        assert_eq!(
+            press(&mut manager, KeyCode::Enter),
+            Some(FlowManagerEvent::Run("smoke".into()))
+        );
+
+        // names are unique, so a taken one isn't accepted
+        press(&mut manager, KeyCode::Char('n'));
+        manager.handle_paste("smoke".into()).unwrap();
+        assert_eq!(press(&mut manager, KeyCode::Enter), None);
+        assert_eq!(manager.naming, Some(Naming::New));
+    }
+
+    #[test]
+    fn test_steps_of_deleted_requests_are_shown_as_broken() {
+        let colors = hac_colors::Colors::default();
+        let store = flows_store();
+        {
+            let collection = store.borrow().get_collection().unwrap();
+            let mut collection = collection.borrow_mut();
+            collection.flows = Some(vec![Flow {
+                name: "smoke".into(),
+                steps: vec!["login".into(), "gone".into()],
+            }]);
+        }
+        let mut manager = FlowManager::new(&colors, store.clone());
+
+        let flows = manager.flow_lines();
+        assert!(flows[0].to_string().ends_with("smoke 2 steps 1 broken"));
+
+        press(&mut manager, KeyCode::Char('e'));
+        let lines = manager
+            .step_lines()
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "> 1. POST login",
+                "  2. broken, request gone no longer exists"
+            ]
+        );
+
+        // removing the broken step fixes the flow
+        press(&mut manager, KeyCode::Char('j'));
+        press(&mut manager, KeyCode::Char('d'));
+        assert_eq!(steps(&store), vec!["login"]);
+    }
+
+    #[test]
+    fn test_flows_of_read_only_collections_can_only_run() {
+        let colors = hac_colors::Colors::default();
+        let store = flows_store();
+        {
+            let collection = store.borrow().get_collection().unwrap();
+            let mut collection = collection.borrow_mut();
+            collection.read_only = true;
+            collection.flows = Some(vec![Flow {
+                name: "smoke".into(),
+                steps: vec!["login".into()],
+            }]);
+        }
+        let mut manager = FlowManager::new(&colors, store.clone());
+
+        for key in ['n', 'r', 'e', 'd'] {
+            assert_eq!(
+                press(&mut manager, KeyCode::Char(key)),
+                Some(FlowManagerEvent::ReadOnly)
+            );
+        }
+        assert_eq!(manager.naming, None);
+        assert_eq!(steps(&store), vec!["login"]);
+        assert_eq!(
+            press(&mut manager, KeyCode::Enter),
+            Some(FlowManagerEvent::Run("smoke".into()))
+        );
+    }
+}
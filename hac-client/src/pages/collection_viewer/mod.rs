@@ -1,9 +1,23 @@
+mod assertions_editor;
+mod collection_settings;
 pub mod collection_store;
 #[allow(clippy::module_inception)]
 pub mod collection_viewer;
+mod copy_as;
+mod flow_manager;
 mod request_editor;
+mod request_preview;
+mod request_status;
 mod request_uri;
+mod response_history;
 mod response_viewer;
+mod run_timings;
+mod runner_panel;
+mod secret_prompt;
 mod sidebar;
+mod template_manager;
+mod view_states;
+mod watch;
 
 pub use collection_viewer::CollectionViewer;
+pub use request_status::{FinishedRequest, APP_TITLE};
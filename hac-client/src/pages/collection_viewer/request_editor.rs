@@ -1,13 +1,17 @@
 mod auth_editor;
 mod body_editor;
+mod captures_editor;
 mod headers_editor;
+mod notes_editor;
 
-use auth_editor::AuthEditor;
-use body_editor::{BodyEditor, BodyEditorEvent};
+use auth_editor::{AuthEditor, AuthEditorEvent};
+use body_editor::{BodyEditor, BodyEditorEvent, BodyViewState};
+use captures_editor::{CapturesEditor, CapturesEditorEvent};
 use hac_config::EditorMode;
 use hac_core::collection::types::{Request, RequestMethod};
 use hac_core::text_object::{TextObject, Write};
 use headers_editor::{HeadersEditor, HeadersEditorEvent};
+use notes_editor::{NotesEditor, NotesEditorEvent};
 
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::collection_viewer::{CollectionViewerOverlay, PaneFocus};
@@ -36,6 +40,19 @@ pub enum RequestEditorEvent {
     /// user pressed `Esc` so we bubble a remove selection event for the
     /// parent to handle
     RemoveSelection,
+    /// user wants to edit the body on an external editor, which the parent
+    /// has to suspend the application for
+    EditBodyExternally,
+    /// something to tell the user on the status line
+    Notify(String),
+}
+
+/// the tab the editor was on and how its body was left, kept for each
+/// request
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorViewState {
+    tab: ReqEditorTabs,
+    body: BodyViewState,
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -45,15 +62,19 @@ pub enum ReqEditorTabs {
     Headers,
     Query,
     Auth,
+    Captures,
+    Notes,
 }
 
 impl ReqEditorTabs {
     pub fn prev(&self) -> Self {
         match self {
-            ReqEditorTabs::Body => ReqEditorTabs::Auth,
+            ReqEditorTabs::Body => ReqEditorTabs::Notes,
             ReqEditorTabs::Headers => ReqEditorTabs::Body,
             ReqEditorTabs::Query => ReqEditorTabs::Headers,
             ReqEditorTabs::Auth => ReqEditorTabs::Query,
+            ReqEditorTabs::Captures => ReqEditorTabs::Auth,
+            ReqEditorTabs::Notes => ReqEditorTabs::Captures,
         }
     }
 
@@ -62,7 +83,9 @@ impl ReqEditorTabs {
             ReqEditorTabs::Body => ReqEditorTabs::Headers,
             ReqEditorTabs::Headers => ReqEditorTabs::Query,
             ReqEditorTabs::Query => ReqEditorTabs::Auth,
-            ReqEditorTabs::Auth => ReqEditorTabs::Body,
+            ReqEditorTabs::Auth => ReqEditorTabs::Captures,
+            ReqEditorTabs::Captures => ReqEditorTabs::Notes,
+            ReqEditorTabs::Notes => ReqEditorTabs::Body,
         }
     }
 }
@@ -80,6 +103,8 @@ impl Display for ReqEditorTabs {
             ReqEditorTabs::Headers => f.write_str("Headers"),
             ReqEditorTabs::Query => f.write_str("Query"),
             ReqEditorTabs::Auth => f.write_str("Auth"),
+            ReqEditorTabs::Captures => f.write_str("Captures"),
+            ReqEditorTabs::Notes => f.write_str("Notes"),
         }
     }
 }
@@ -97,6 +122,8 @@ pub struct RequestEditor<'re> {
     body_editor: BodyEditor<'re>,
     headers_editor: HeadersEditor<'re>,
     auth_editor: AuthEditor<'re>,
+    captures_editor: CapturesEditor<'re>,
+    notes_editor: NotesEditor<'re>,
     layout: ReqEditorLayout,
     curr_tab: ReqEditorTabs,
 }
@@ -133,6 +160,8 @@ impl<'re> RequestEditor<'re> {
                 layout.content_pane,
             ),
             auth_editor: AuthEditor::new(colors, collection_store.clone()),
+            captures_editor: CapturesEditor::new(colors, collection_store.clone()),
+            notes_editor: NotesEditor::new(colors, collection_store.clone()),
             layout,
             curr_tab,
             collection_store,
@@ -145,34 +174,80 @@ impl<'re> RequestEditor<'re> {
         }
     }
 
+    /// the mode of the body editor, while the body is the tab shown
+    pub fn body_mode(&self) -> Option<&EditorMode> {
+        self.curr_tab
+            .eq(&ReqEditorTabs::Body)
+            .then(|| self.body_editor.mode())
+    }
+
     pub fn body(&self) -> &TextObject<Write> {
         self.body_editor.body()
     }
 
+    pub fn set_body(&mut self, body: &str) {
+        self.body_editor.set_body(body);
+    }
+
+    pub fn body_wrap(&self) -> bool {
+        self.body_editor.wrap()
+    }
+
+    pub fn set_body_wrap(&mut self, wrap: bool) {
+        self.body_editor.set_wrap(wrap);
+    }
+
+    pub fn view_state(&self) -> EditorViewState {
+        EditorViewState {
+            tab: self.curr_tab.clone(),
+            body: self.body_editor.view_state(),
+        }
+    }
+
+    pub fn restore_view_state(&mut self, state: EditorViewState) {
+        self.curr_tab = state.tab;
+        self.body_editor.restore_view_state(state.body);
+    }
+
     pub fn resize(&mut self, new_size: Rect) {
         self.layout = build_layout(new_size);
         self.headers_editor.resize(self.layout.content_pane);
         self.body_editor.resize(self.layout.content_pane);
     }
 
+    /// whether the current tab is taking text, so `Tab` belongs to it
+    fn is_typing(&self) -> bool {
+        match self.curr_tab {
+            ReqEditorTabs::Body => self.body_editor.mode().eq(&EditorMode::Insert),
+            ReqEditorTabs::Auth => self.auth_editor.is_editing(),
+            ReqEditorTabs::Captures => self.captures_editor.is_editing(),
+            ReqEditorTabs::Notes => self.notes_editor.is_editing(),
+            _ => false,
+        }
+    }
+
     fn draw_current_tab(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
         match self.curr_tab {
             ReqEditorTabs::Body => self.body_editor.draw(frame, size)?,
             ReqEditorTabs::Headers => self.headers_editor.draw(frame, size)?,
             ReqEditorTabs::Query => UnderConstruction::new(self.colors).draw(frame, size)?,
-            ReqEditorTabs::Auth => UnderConstruction::new(self.colors).draw(frame, size)?,
+            ReqEditorTabs::Auth => self.auth_editor.draw(frame, size)?,
+            ReqEditorTabs::Captures => self.captures_editor.draw(frame, size)?,
+            ReqEditorTabs::Notes => self.notes_editor.draw(frame, size)?,
         }
 
         Ok(())
     }
 
     fn draw_tabs(&self, frame: &mut Frame, size: Rect) {
-        let tabs = vec!["Body", "Headers", "Query", "Auth"];
+        let tabs = vec!["Body", "Headers", "Query", "Auth", "Captures", "Notes"];
         let active = match self.curr_tab {
             ReqEditorTabs::Body => 0,
             ReqEditorTabs::Headers => 1,
             ReqEditorTabs::Query => 2,
             ReqEditorTabs::Auth => 3,
+            ReqEditorTabs::Captures => 4,
+            ReqEditorTabs::Notes => 5,
         };
 
         frame.render_widget(
@@ -180,9 +255,8 @@ impl<'re> RequestEditor<'re> {
                 .style(Style::default().fg(self.colors.bright.black))
                 .select(active)
                 .highlight_style(
-                    Style::default()
-                        .fg(self.colors.normal.white)
-                        .bg(self.colors.normal.blue),
+                    self.colors
+                        .highlight(self.colors.normal.white, self.colors.normal.blue),
                 ),
             size,
         );
@@ -195,11 +269,7 @@ impl<'re> RequestEditor<'re> {
             .get_selected_pane()
             .is_some_and(|pane| pane.eq(&PaneFocus::Editor));
 
-        let block_border = match (is_focused, is_selected) {
-            (true, false) => Style::default().fg(self.colors.bright.blue),
-            (true, true) => Style::default().fg(self.colors.normal.red),
-            (_, _) => Style::default().fg(self.colors.bright.black),
-        };
+        let block_border = self.colors.pane_border(is_focused, is_selected);
 
         let block = Block::default()
             .borders(Borders::ALL)
@@ -221,7 +291,9 @@ impl<'re> RequestEditor<'re> {
             ReqEditorTabs::Body => todo!(),
             ReqEditorTabs::Headers => self.headers_editor.draw_overlay(frame, overlay),
             ReqEditorTabs::Query => todo!(),
-            ReqEditorTabs::Auth => todo!(),
+            // auth, captures and notes are edited inline, there are no
+            // overlays to draw
+            ReqEditorTabs::Auth | ReqEditorTabs::Captures | ReqEditorTabs::Notes => Ok(()),
         }
     }
 }
@@ -250,9 +322,7 @@ impl Eventful for RequestEditor<'_> {
 
         if let KeyCode::Tab = key_event.code {
             let store = self.collection_store.borrow_mut();
-            if self.curr_tab.eq(&ReqEditorTabs::Body)
-                && self.body_editor.mode().eq(&EditorMode::Insert)
-            {
+            if self.is_typing() {
                 return Ok(None);
             }
             if !store.has_overlay() {
@@ -263,9 +333,7 @@ impl Eventful for RequestEditor<'_> {
 
         if let KeyCode::BackTab = key_event.code {
             let store = self.collection_store.borrow_mut();
-            if self.curr_tab.eq(&ReqEditorTabs::Body)
-                && self.body_editor.mode().eq(&EditorMode::Insert)
-            {
+            if self.is_typing() {
                 return Ok(None);
             }
             if !store.has_overlay() {
@@ -279,6 +347,12 @@ impl Eventful for RequestEditor<'_> {
                     return Ok(Some(RequestEditorEvent::RemoveSelection))
                 }
                 Some(BodyEditorEvent::Quit) => return Ok(Some(RequestEditorEvent::Quit)),
+                Some(BodyEditorEvent::EditExternally) => {
+                    return Ok(Some(RequestEditorEvent::EditBodyExternally))
+                }
+                Some(BodyEditorEvent::Notify(message)) => {
+                    return Ok(Some(RequestEditorEvent::Notify(message)))
+                }
                 None => {}
             },
             ReqEditorTabs::Headers => match self.headers_editor.handle_key_event(key_event)? {
@@ -289,15 +363,43 @@ impl Eventful for RequestEditor<'_> {
                 None => {}
             },
             ReqEditorTabs::Query => {}
-            ReqEditorTabs::Auth => {
-                if (self.auth_editor.handle_key_event(key_event)?).is_some() {
-                    todo!()
+            ReqEditorTabs::Auth => match self.auth_editor.handle_key_event(key_event)? {
+                Some(AuthEditorEvent::Quit) => return Ok(Some(RequestEditorEvent::Quit)),
+                Some(AuthEditorEvent::RemoveSelection) => {
+                    return Ok(Some(RequestEditorEvent::RemoveSelection))
                 }
-            }
+                None => {}
+            },
+            ReqEditorTabs::Captures => match self.captures_editor.handle_key_event(key_event)? {
+                Some(CapturesEditorEvent::Quit) => return Ok(Some(RequestEditorEvent::Quit)),
+                Some(CapturesEditorEvent::RemoveSelection) => {
+                    return Ok(Some(RequestEditorEvent::RemoveSelection))
+                }
+                None => {}
+            },
+            ReqEditorTabs::Notes => match self.notes_editor.handle_key_event(key_event)? {
+                Some(NotesEditorEvent::Quit) => return Ok(Some(RequestEditorEvent::Quit)),
+                Some(NotesEditorEvent::RemoveSelection) => {
+                    return Ok(Some(RequestEditorEvent::RemoveSelection))
+                }
+                None => {}
+            },
         }
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        match self.curr_tab {
+            ReqEditorTabs::Body => _ = self.body_editor.handle_paste(text)?,
+            ReqEditorTabs::Headers => _ = self.headers_editor.handle_paste(text)?,
+            ReqEditorTabs::Auth => _ = self.auth_editor.handle_paste(text)?,
+            ReqEditorTabs::Captures => _ = self.captures_editor.handle_paste(text)?,
+            ReqEditorTabs::Notes => _ = self.notes_editor.handle_paste(text)?,
+            ReqEditorTabs::Query => {}
+        }
+        Ok(None)
+    }
 }
 
 fn build_layout(size: Rect) -> ReqEditorLayout {
@@ -1,52 +1,369 @@
+use hac_core::collection::types::AuthMethod;
+use hac_core::secrets::MASK;
+
 use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::input::{self, Input};
 use crate::pages::{Eventful, Renderable};
 
 use std::cell::RefCell;
+use std::ops::Add;
 use std::rc::Rc;
 
-use crossterm::event::KeyEvent;
-use ratatui::{layout::Rect, widgets::Paragraph, Frame};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AuthEditorEvent {
+    Quit,
+    RemoveSelection,
+}
+
+/// the methods `m` goes through, starting over from no auth at all
+fn methods() -> [Option<AuthMethod>; 3] {
+    [
+        None,
+        Some(AuthMethod::AwsSigV4 {
+            access_key: String::default(),
+            secret_key: String::default(),
+            region: String::default(),
+            service: String::default(),
+            session_token: None,
+        }),
+        Some(AuthMethod::Digest {
+            username: String::default(),
+            password: String::default(),
+        }),
+    ]
+}
 
+fn method_name(method: Option<&AuthMethod>) -> &'static str {
+    match method {
+        None => "No auth",
+        Some(AuthMethod::Bearer) => "Bearer",
+        Some(AuthMethod::Basic) => "Basic",
+        Some(AuthMethod::AwsSigV4 { .. }) => "AWS Signature V4",
+        Some(AuthMethod::Digest { .. }) => "Digest",
+    }
+}
+
+/// picks the auth method of the selected request and edits its fields.
+/// Values of secret fields are masked unless revealed, and are kept on the
+/// secrets file when the collection is saved
 #[derive(Debug)]
 pub struct AuthEditor<'ae> {
-    _colors: &'ae hac_colors::colors::Colors,
+    colors: &'ae hac_colors::Colors,
     collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    /// text being typed on the selected field, written to the request on
+    /// confirm
+    editing: Option<String>,
+    revealed: bool,
 }
 
 impl<'ae> AuthEditor<'ae> {
     pub fn new(
-        colors: &'ae hac_colors::colors::Colors,
+        colors: &'ae hac_colors::Colors,
         collection_store: Rc<RefCell<CollectionStore>>,
     ) -> Self {
         AuthEditor {
-            _colors: colors,
+            colors,
             collection_store,
+            selected: 0,
+            editing: None,
+            revealed: false,
+        }
+    }
+
+    /// while typing, keys like `Tab` shouldn't switch the editor tabs
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn auth_method(&self) -> Option<AuthMethod> {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .and_then(|req| req.read().unwrap().auth_method.clone())
+    }
+
+    fn set_auth_method(&mut self, auth_method: Option<AuthMethod>) {
+        if let Some(request) = self.collection_store.borrow().get_selected_request() {
+            request.write().unwrap().auth_method = auth_method;
+        }
+    }
+
+    /// switching methods starts the new one blank, values of one method
+    /// mean nothing to the others
+    fn next_method(&mut self) {
+        let current = self.auth_method();
+        let methods = methods();
+        let idx = methods
+            .iter()
+            .position(|method| {
+                method.as_ref().map(std::mem::discriminant)
+                    == current.as_ref().map(std::mem::discriminant)
+            })
+            .map(|idx| idx.add(1) % methods.len())
+            .unwrap_or_default();
+
+        self.set_auth_method(methods[idx].clone());
+        self.selected = 0;
+    }
+
+    fn start_editing(&mut self) {
+        let Some(auth) = self.auth_method() else {
+            return;
+        };
+        if let Some(field) = auth.fields().get(self.selected) {
+            self.editing = Some(auth.get(field.name).unwrap_or_default().into());
+        }
+    }
+
+    fn confirm_edit(&mut self) {
+        let (Some(value), Some(mut auth)) = (self.editing.take(), self.auth_method()) else {
+            return;
+        };
+        if let Some(field) = auth.fields().get(self.selected) {
+            auth.set(field.name, value.trim().into());
+            self.set_auth_method(Some(auth));
+        }
+    }
+
+    fn mask(&self, value: &str, secret: bool) -> String {
+        match secret && !self.revealed {
+            true if !value.is_empty() => MASK.into(),
+            _ => value.into(),
+        }
+    }
+
+    fn draw_fields(&self, frame: &mut Frame, size: Rect, auth: Option<&AuthMethod>) {
+        let mut lines = vec![
+            Line::from(vec![
+                " Method        ".fg(self.colors.bright.black),
+                method_name(auth).fg(self.colors.normal.white).bold(),
+            ]),
+            Line::default(),
+        ];
+
+        let fields = auth.map(AuthMethod::fields).unwrap_or_default();
+        if fields.is_empty() {
+            lines.push(
+                Line::from("nothing to set, press m to pick a method".fg(self.colors.bright.black))
+                    .centered(),
+            );
+        }
+
+        for (idx, field) in fields.iter().enumerate() {
+            let value = auth
+                .and_then(|auth| auth.get(field.name))
+                .unwrap_or_default();
+            let label = format!(" {:<14}", field.label);
+            let value = Span::from(self.mask(value, field.secret));
+            let value = match idx.eq(&self.selected) && self.editing.is_none() {
+                true => value.style(
+                    self.colors
+                        .highlight(self.colors.normal.white, self.colors.normal.blue),
+                ),
+                false => value.fg(self.colors.normal.white),
+            };
+
+            let mut spans = vec![label.fg(self.colors.bright.black), value];
+            if field.secret {
+                spans.push(" secret".fg(self.colors.normal.magenta));
+            }
+            lines.push(Line::from(spans));
         }
+
+        frame.render_widget(Paragraph::new(lines), size);
     }
 }
 
 impl Renderable for AuthEditor<'_> {
     fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
-        frame.render_widget(Paragraph::new("hello from auth editor").centered(), size);
-        let store = self.collection_store.borrow();
+        let [fields_pane, input_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(if self.editing.is_some() { 3 } else { 0 }),
+                Constraint::Length(1),
+            ])
+            .areas(size);
 
-        let Some(request) = store.get_selected_request() else {
-            return Ok(());
-        };
+        let auth = self.auth_method();
+        self.draw_fields(frame, fields_pane, auth.as_ref());
 
-        let request = request.read().unwrap();
-        if request.auth_method.is_none() {
-            return Ok(());
+        let field = auth
+            .as_ref()
+            .and_then(|auth| auth.fields().get(self.selected));
+        if let (Some(editing), Some(field)) = (self.editing.as_ref(), field) {
+            let mut shown = match field.secret && !self.revealed {
+                true => "•".repeat(editing.chars().count()),
+                false => editing.clone(),
+            };
+            let mut input = Input::new(self.colors, field.label.into());
+            input.focus();
+            frame.render_stateful_widget(input, input_pane, &mut shown);
+            frame.set_cursor(
+                input_pane.x.add(editing.chars().count() as u16).add(1),
+                input_pane.y.add(1),
+            );
         }
 
+        let hint = match self.editing.is_some() {
+            true => "[enter -> confirm] [esc -> cancel]",
+            false => "[m -> change method] [enter -> edit] [r -> reveal]",
+        };
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_pane,
+        );
+
         Ok(())
     }
 }
 
 impl Eventful for AuthEditor<'_> {
-    type Result = ();
+    type Result = AuthEditorEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(AuthEditorEvent::Quit));
+        }
+
+        if let Some(editing) = self.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) => editing.push(c),
+                KeyCode::Backspace => _ = editing.pop(),
+                KeyCode::Enter => self.confirm_edit(),
+                KeyCode::Esc => self.editing = None,
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        let total = self
+            .auth_method()
+            .map(|auth| auth.fields().len())
+            .unwrap_or_default();
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(AuthEditorEvent::RemoveSelection)),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = usize::min(self.selected.add(1), total.saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('m') => self.next_method(),
+            KeyCode::Enter | KeyCode::Char('i') => self.start_editing(),
+            KeyCode::Char('r') => self.revealed = !self.revealed,
+            _ => {}
+        }
 
-    fn handle_key_event(&mut self, _key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let Some(editing) = self.editing.as_mut() {
+            editing.push_str(&input::single_line(&text));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+
+    fn feed(editor: &mut AuthEditor, codes: &[KeyCode]) {
+        for code in codes {
+            editor
+                .handle_key_event(KeyEvent::new(*code, KeyModifiers::NONE))
+                .unwrap();
+        }
+    }
+
+    fn type_text(editor: &mut AuthEditor, text: &str) {
+        feed(editor, &text.chars().map(KeyCode::Char).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_filling_in_aws_sigv4() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = store_with_request(make_request("upload", "/upload"));
+        let mut editor = AuthEditor::new(&colors, store);
+
+        feed(&mut editor, &[KeyCode::Char('m'), KeyCode::Enter]);
+        assert!(editor.is_editing());
+        type_text(&mut editor, "AKIDEXAMPLE");
+        feed(
+            &mut editor,
+            &[KeyCode::Enter, KeyCode::Char('j'), KeyCode::Enter],
+        );
+        editor.handle_paste("wJalrXUtnFEMI\n".into()).unwrap();
+        feed(&mut editor, &[KeyCode::Enter]);
+        feed(&mut editor, &[KeyCode::Char('j'), KeyCode::Char('i')]);
+        type_text(&mut editor, "us-east-1");
+        // cancelling leaves the field as it was
+        feed(&mut editor, &[KeyCode::Esc]);
+        assert!(!editor.is_editing());
+
+        assert_eq!(
+            request.read().unwrap().auth_method,
+            Some(AuthMethod::AwsSigV4 {
+                access_key: "AKIDEXAMPLE".into(),
+                secret_key: "wJalrXUtnFEMI".into(),
+                region: String::default(),
+                service: String::default(),
+                session_token: None,
+            })
+        );
+
+        // going through every method comes back to no auth
+        feed(&mut editor, &[KeyCode::Char('m'), KeyCode::Char('m')]);
+        assert_eq!(request.read().unwrap().auth_method, None);
+    }
+
+    #[test]
+    fn test_filling_in_digest() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = store_with_request(make_request("login", "/login"));
+        let mut editor = AuthEditor::new(&colors, store);
+
+        feed(&mut editor, &[KeyCode::Char('m'), KeyCode::Char('m')]);
+        let auth = request.read().unwrap().auth_method.clone().unwrap();
+        assert!(auth.fields().iter().any(|field| field.secret));
+
+        feed(&mut editor, &[KeyCode::Enter]);
+        type_text(&mut editor, "admin");
+        feed(
+            &mut editor,
+            &[KeyCode::Enter, KeyCode::Down, KeyCode::Enter],
+        );
+        type_text(&mut editor, "hunter2");
+        feed(&mut editor, &[KeyCode::Enter]);
+
+        assert_eq!(
+            request.read().unwrap().auth_method,
+            Some(AuthMethod::Digest {
+                username: "admin".into(),
+                password: "hunter2".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_secret_fields_are_masked_until_revealed() {
+        let colors = hac_colors::Colors::default();
+        let (store, _) = store_with_request(make_request("upload", "/upload"));
+        let mut editor = AuthEditor::new(&colors, store);
+
+        assert_eq!(editor.mask("wJalrXUtnFEMI", true), MASK);
+        assert_eq!(editor.mask("", true), "");
+        assert_eq!(editor.mask("us-east-1", false), "us-east-1");
+        feed(&mut editor, &[KeyCode::Char('r')]);
+        assert_eq!(editor.mask("wJalrXUtnFEMI", true), "wJalrXUtnFEMI");
+    }
 }
@@ -1,5 +1,4 @@
 #[allow(clippy::module_inception)]
 mod auth_editor;
-mod auth_kind_prompt;
 
-pub use auth_editor::AuthEditor;
+pub use auth_editor::{AuthEditor, AuthEditorEvent};
@@ -1,12 +1,15 @@
 use hac_config::{Action, EditorMode, KeyAction};
+use hac_core::collection::types::{BodyType, PreviewLanguage};
+use hac_core::secrets::MASK;
 use hac_core::syntax::highlighter::HIGHLIGHTER;
 use hac_core::text_object::{cursor::Cursor, TextObject, Write};
+use hac_core::variables;
 
 use crate::pages::{collection_viewer::collection_store::CollectionStore, Eventful, Renderable};
-use crate::utils::build_syntax_highlighted_lines;
+use crate::utils::{build_syntax_highlighted_lines, highlight_placeholders, wrap_line};
 
 use std::cell::RefCell;
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul, Rem, Sub};
 use std::rc::Rc;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -21,6 +24,20 @@ use tree_sitter::Tree;
 pub enum BodyEditorEvent {
     RemoveSelection,
     Quit,
+    /// user asked to edit the body on an external editor
+    EditExternally,
+    /// something to tell the user on the status line
+    Notify(String),
+}
+
+/// where the cursor and the view of the body were, restored when its
+/// request is selected again
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyViewState {
+    cursor: Cursor,
+    row_scroll: usize,
+    col_scroll: usize,
+    wrap: bool,
 }
 
 #[derive(Debug)]
@@ -32,6 +49,8 @@ pub struct BodyEditor<'be> {
     editor_mode: EditorMode,
     row_scroll: usize,
     col_scroll: usize,
+    /// long lines are soft wrapped instead of scrolling sideways
+    wrap: bool,
     colors: &'be hac_colors::Colors,
     config: &'be hac_config::Config,
 
@@ -43,7 +62,7 @@ pub struct BodyEditor<'be> {
     /// Only KeyAction::Complex are stored here as any other kind of key action can be acted upon
     /// instantly
     keymap_buffer: Option<KeyAction>,
-    _collection_store: Rc<RefCell<CollectionStore>>,
+    collection_store: Rc<RefCell<CollectionStore>>,
 }
 
 impl<'be> BodyEditor<'be> {
@@ -60,12 +79,13 @@ impl<'be> BodyEditor<'be> {
         Self {
             body,
             tree,
-            _collection_store: collection_store,
+            collection_store,
             styled_display,
             cursor: Cursor::default(),
             editor_mode: EditorMode::Normal,
             row_scroll: 0,
             col_scroll: 0,
+            wrap: false,
             size,
             colors,
             config,
@@ -81,6 +101,138 @@ impl<'be> BodyEditor<'be> {
         &self.body
     }
 
+    /// replaces the whole body, eg: after it was edited somewhere else. The
+    /// cursor goes back to the start as its position may not exist anymore
+    pub fn set_body(&mut self, body: &str) {
+        self.body = TextObject::from(body).with_write();
+        self.rehighlight();
+        self.cursor = Cursor::default();
+        self.row_scroll = 0;
+        self.col_scroll = 0;
+        self.keymap_buffer = None;
+    }
+
+    /// parses the body again and rebuilds its lines, which are plain text
+    /// until the json grammar is loaded
+    fn rehighlight(&mut self) {
+        let body = self.body.to_string();
+        self.tree = HIGHLIGHTER.write().unwrap().parse(&body);
+        self.styled_display =
+            build_syntax_highlighted_lines(&body, self.tree.as_ref(), self.colors);
+    }
+
+    pub fn wrap(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+        self.maybe_scroll_view();
+    }
+
+    pub fn view_state(&self) -> BodyViewState {
+        BodyViewState {
+            cursor: self.cursor.clone(),
+            row_scroll: self.row_scroll,
+            col_scroll: self.col_scroll,
+            wrap: self.wrap,
+        }
+    }
+
+    /// the body may have changed since the state was taken, so the cursor
+    /// is kept within it
+    pub fn restore_view_state(&mut self, state: BodyViewState) {
+        let row = state
+            .cursor
+            .row()
+            .min(self.body.len_lines().saturating_sub(1));
+        let col = state.cursor.col().min(self.body.line_len(row));
+        self.cursor = Cursor::default();
+        self.cursor.move_to_row(row);
+        self.cursor.move_to_col(col);
+        self.row_scroll = state.row_scroll.min(row);
+        self.col_scroll = state.col_scroll;
+        self.wrap = state.wrap;
+        self.maybe_scroll_view();
+    }
+
+    /// columns left for the text once the gutter is drawn, when wrapping
+    fn wrap_width(&self) -> usize {
+        usize::from(self.size.width).saturating_sub(2).max(1)
+    }
+
+    /// how many rows `line` takes when wrapped
+    fn wrapped_rows(&self, line: usize) -> usize {
+        self.body.line_len(line).div_ceil(self.wrap_width()).max(1)
+    }
+
+    /// how far from the top of the view the cursor is when wrapping, as
+    /// every line above it may take more than a row
+    fn wrapped_cursor_row(&self) -> usize {
+        (self.row_scroll..self.cursor.row())
+            .map(|line| self.wrapped_rows(line))
+            .sum::<usize>()
+            .add(self.cursor.col_with_offset().div(self.wrap_width()))
+    }
+
+    /// whether the selected request sends its body without substituting
+    /// placeholders
+    fn is_raw(&self) -> bool {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .is_some_and(|request| request.read().unwrap().raw_body)
+    }
+
+    fn toggle_raw_body(&mut self) -> Option<String> {
+        let request = self.collection_store.borrow().get_selected_request()?;
+        let mut request = request.write().unwrap();
+        request.raw_body = !request.raw_body;
+        let message = match request.raw_body {
+            true => "the body is sent as written, placeholders are not substituted",
+            false => "placeholders on the body are substituted",
+        };
+        Some(message.into())
+    }
+
+    /// the computed content type follows the new type, so the headers tab
+    /// shows it right away
+    fn cycle_body_type(&mut self) -> Option<String> {
+        let request = self.collection_store.borrow().get_selected_request()?;
+        let mut request = request.write().unwrap();
+        let body_type = request.body_type.as_ref().unwrap_or(&BodyType::Json).next();
+        let message = format!(
+            "the body is sent as {body_type}, with content type {}",
+            body_type.content_type()
+        );
+        request.body_type = Some(body_type);
+        Some(message)
+    }
+
+    /// what the placeholder under the cursor resolves to on the active
+    /// environment, secrets are never shown
+    fn describe_variable(&self) -> String {
+        let line = self.body.current_line(&self.cursor).unwrap_or_default();
+        let Some(name) = variables::placeholder_at(line, self.cursor.col()) else {
+            return "no variable under the cursor".into();
+        };
+        if self.is_raw() {
+            return format!("{{{{{name}}}}} is sent as written, the body is raw");
+        }
+        if name.starts_with('$') {
+            return format!("{{{{{name}}}}} is generated on every send");
+        }
+
+        let store = self.collection_store.borrow();
+        match store.get_variables().get(name) {
+            Some(value) if !value.is_empty() && store.is_secret(name) => {
+                format!("{{{{{name}}}}} = {MASK}")
+            }
+            Some(value) if !store.is_secret(name) => format!("{{{{{name}}}}} = {value}"),
+            _ => format!("{{{{{name}}}}} is not set"),
+        }
+    }
+
     pub fn draw_cursor(&self, frame: &mut Frame) {
         // the editor status bar occupies 1 row, so we have to subtract it to prevent the
         // cursor from going out of the intended spacing, we also subtract the bottom border.
@@ -89,18 +241,28 @@ impl<'be> BodyEditor<'be> {
         let border_size = 1;
         editor_position.height = editor_position.height.sub(statusbar_size).sub(border_size);
 
+        let (row, col) = match self.wrap {
+            // the gutter takes the first two columns
+            true => (
+                self.wrapped_cursor_row(),
+                self.cursor.col_with_offset().rem(self.wrap_width()).add(2),
+            ),
+            false => (
+                self.cursor
+                    .row_with_offset()
+                    .saturating_sub(self.row_scroll),
+                self.cursor
+                    .col_with_offset()
+                    .saturating_sub(self.col_scroll),
+            ),
+        };
+
         let row_with_offset = u16::min(
-            editor_position
-                .y
-                .add(self.cursor.row_with_offset() as u16)
-                .saturating_sub(self.row_scroll as u16),
+            editor_position.y.add(row as u16),
             editor_position.y.add(editor_position.height),
         );
         let col_with_offset = u16::min(
-            editor_position
-                .x
-                .add(self.cursor.col_with_offset() as u16)
-                .saturating_sub(self.col_scroll as u16),
+            editor_position.x.add(col as u16),
             editor_position.x.add(editor_position.width),
         );
         frame.set_cursor(col_with_offset, row_with_offset);
@@ -110,6 +272,10 @@ impl<'be> BodyEditor<'be> {
         let cursor_pos = self.cursor.readable_position();
 
         let mut mode = Span::from(format!(" {} ", self.editor_mode));
+        let raw = match self.is_raw() {
+            true => Span::from(" RAW ").fg(self.colors.normal.orange).bold(),
+            false => Span::default(),
+        };
         let mut cursor = Span::from(format!(" {}:{} ", cursor_pos.1, cursor_pos.0));
 
         let mut percentage = Span::from(format!(
@@ -122,6 +288,7 @@ impl<'be> BodyEditor<'be> {
         let content_len = mode
             .content
             .len()
+            .add(raw.content.len())
             .add(cursor.content.len())
             .add(percentage.content.len());
 
@@ -129,23 +296,27 @@ impl<'be> BodyEditor<'be> {
 
         match self.editor_mode {
             EditorMode::Insert => {
-                mode = mode
-                    .fg(self.colors.normal.black)
-                    .bg(self.colors.normal.green);
-                cursor = cursor
-                    .fg(self.colors.normal.black)
-                    .bg(self.colors.normal.green);
+                mode = mode.patch_style(
+                    self.colors
+                        .highlight(self.colors.normal.black, self.colors.normal.green),
+                );
+                cursor = cursor.patch_style(
+                    self.colors
+                        .highlight(self.colors.normal.black, self.colors.normal.green),
+                );
                 percentage = percentage
                     .fg(self.colors.normal.green)
                     .bg(self.colors.primary.hover);
             }
             EditorMode::Normal => {
-                mode = mode
-                    .fg(self.colors.normal.black)
-                    .bg(self.colors.bright.blue);
-                cursor = cursor
-                    .fg(self.colors.normal.black)
-                    .bg(self.colors.bright.blue);
+                mode = mode.patch_style(
+                    self.colors
+                        .highlight(self.colors.normal.black, self.colors.bright.blue),
+                );
+                cursor = cursor.patch_style(
+                    self.colors
+                        .highlight(self.colors.normal.black, self.colors.bright.blue),
+                );
                 percentage = percentage
                     .fg(self.colors.bright.blue)
                     .bg(self.colors.normal.blue);
@@ -153,7 +324,7 @@ impl<'be> BodyEditor<'be> {
         };
 
         frame.render_widget(
-            Paragraph::new(Line::from(vec![mode, padding, percentage, cursor])),
+            Paragraph::new(Line::from(vec![mode, raw, padding, percentage, cursor])),
             size,
         )
     }
@@ -199,6 +370,12 @@ impl<'be> BodyEditor<'be> {
             Action::FindNext => {}
             Action::FindPrevious => {}
             Action::PasteBelow => {}
+            // it needs the terminal, so it is bubbled up instead
+            Action::EditExternally => {}
+            Action::ShowVariable => {}
+            Action::ToggleWrap => {}
+            Action::ToggleRawBody => {}
+            Action::CycleBodyType => {}
         }
     }
 
@@ -219,6 +396,16 @@ impl<'be> BodyEditor<'be> {
                     .saturating_sub(self.row_scroll.saturating_sub(self.cursor.row()))
             });
 
+        if self.wrap {
+            self.col_scroll = 0;
+            // the lines above may wrap enough to push the cursor out of view
+            let height = usize::from(self.size.height.saturating_sub(1)).max(1);
+            while self.row_scroll.lt(&self.cursor.row()) && self.wrapped_cursor_row().ge(&height) {
+                self.row_scroll = self.row_scroll.add(1);
+            }
+            return;
+        }
+
         self.cursor
             .col()
             .saturating_sub(self.col_scroll)
@@ -510,19 +697,38 @@ impl Renderable for BodyEditor<'_> {
     fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
         let [request_pane, statusline_pane] = build_editor_layout(size);
 
+        if self.tree.is_none() && HIGHLIGHTER.read().unwrap().is_loaded(PreviewLanguage::Json) {
+            self.rehighlight();
+        }
+
         self.draw_statusline(frame, statusline_pane);
 
-        let lines_in_view = self
+        let variables = self.collection_store.borrow().get_variables();
+        let is_raw = self.is_raw();
+        // placeholders on a raw body are just text
+        let lines = self
             .styled_display
             .clone()
             .into_iter()
             .skip(self.row_scroll)
-            .chain(std::iter::repeat(Line::from(
-                "~".fg(self.colors.bright.black),
-            )))
-            .take(size.height.into())
-            .map(|line| get_visible_spans(&line, self.col_scroll))
-            .collect::<Vec<Line>>();
+            .map(|line| match is_raw {
+                true => line,
+                false => highlight_placeholders(line, &variables, self.colors),
+            });
+        let filler = std::iter::repeat(Line::from("~".fg(self.colors.bright.black)));
+
+        let lines_in_view = match self.wrap {
+            true => lines
+                .flat_map(|line| wrap_line(&line, self.wrap_width(), self.colors))
+                .chain(filler)
+                .take(size.height.into())
+                .collect::<Vec<Line>>(),
+            false => lines
+                .chain(filler)
+                .take(size.height.into())
+                .map(|line| get_visible_spans(&line, self.col_scroll))
+                .collect::<Vec<Line>>(),
+        };
 
         frame.render_widget(Paragraph::new(lines_in_view), request_pane);
         Ok(())
@@ -556,12 +762,7 @@ impl Eventful for BodyEditor<'_> {
                 _ => self.keymap_buffer = None,
             }
 
-            self.tree = HIGHLIGHTER.write().unwrap().parse(&self.body.to_string());
-            self.styled_display = build_syntax_highlighted_lines(
-                &self.body.to_string(),
-                self.tree.as_ref(),
-                self.colors,
-            );
+            self.rehighlight();
             return Ok(None);
         }
 
@@ -577,6 +778,30 @@ impl Eventful for BodyEditor<'_> {
 
         match self.editor_mode {
             EditorMode::Normal => match self.config.editor_keys.normal.get(&key_str) {
+                Some(KeyAction::Simple(Action::EditExternally)) => {
+                    return Ok(Some(BodyEditorEvent::EditExternally))
+                }
+                Some(KeyAction::Simple(Action::ShowVariable)) => {
+                    return Ok(Some(BodyEditorEvent::Notify(self.describe_variable())))
+                }
+                Some(KeyAction::Simple(Action::ToggleRawBody)) => {
+                    if let Some(message) = self.toggle_raw_body() {
+                        return Ok(Some(BodyEditorEvent::Notify(message)));
+                    }
+                }
+                Some(KeyAction::Simple(Action::CycleBodyType)) => {
+                    if let Some(message) = self.cycle_body_type() {
+                        return Ok(Some(BodyEditorEvent::Notify(message)));
+                    }
+                }
+                Some(KeyAction::Simple(Action::ToggleWrap)) => {
+                    self.set_wrap(!self.wrap);
+                    let message = match self.wrap {
+                        true => "wrapping long lines",
+                        false => "long lines scroll sideways",
+                    };
+                    return Ok(Some(BodyEditorEvent::Notify(message.into())));
+                }
                 Some(KeyAction::Simple(action)) => self.handle_action(action),
                 Some(KeyAction::Multiple(actions)) => {
                     actions.iter().for_each(|a| self.handle_action(a))
@@ -598,9 +823,24 @@ impl Eventful for BodyEditor<'_> {
             },
         }
 
-        self.tree = HIGHLIGHTER.write().unwrap().parse(&self.body.to_string());
-        self.styled_display =
-            build_syntax_highlighted_lines(&self.body.to_string(), self.tree.as_ref(), self.colors);
+        self.rehighlight();
+
+        Ok(None)
+    }
+
+    /// the body is the only place where pasted newlines are kept, they are
+    /// inserted as is instead of going through the keymaps
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        for c in text.chars() {
+            match c {
+                '\n' => self.insert_newline(),
+                '\r' => {}
+                c => self.insert_char(c),
+            }
+        }
+        self.maybe_scroll_view();
+
+        self.rehighlight();
 
         Ok(None)
     }
@@ -0,0 +1,382 @@
+use hac_core::captures::CaptureResult;
+use hac_core::collection::types::Capture;
+use hac_core::secrets::MASK;
+
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::input::{self, Input};
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Sub};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CapturesEditorEvent {
+    Quit,
+    RemoveSelection,
+}
+
+/// text being typed on the captures input
+#[derive(Debug, PartialEq)]
+struct EditingCapture {
+    /// index of the capture being edited, `None` when creating a new one
+    idx: Option<usize>,
+    value: String,
+}
+
+/// lists the captures of the selected request along with the value each one
+/// got on the last send, and allows creating, editing and deleting them
+#[derive(Debug)]
+pub struct CapturesEditor<'ce> {
+    colors: &'ce hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    editing: Option<EditingCapture>,
+    error: Option<String>,
+    /// values captured into secrets are masked unless revealed
+    revealed: bool,
+}
+
+impl<'ce> CapturesEditor<'ce> {
+    pub fn new(
+        colors: &'ce hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        CapturesEditor {
+            colors,
+            collection_store,
+            selected: 0,
+            editing: None,
+            error: None,
+            revealed: false,
+        }
+    }
+
+    /// while typing, keys like `Tab` shouldn't switch the editor tabs
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn captures(&self) -> Vec<Capture> {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .and_then(|req| req.read().unwrap().captures.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_captures(&mut self, captures: Vec<Capture>) {
+        if let Some(request) = self.collection_store.borrow().get_selected_request() {
+            request.write().unwrap().captures = (!captures.is_empty()).then_some(captures);
+        }
+    }
+
+    fn last_results(&self) -> Vec<CaptureResult> {
+        let store = self.collection_store.borrow();
+        store
+            .get_selected_request()
+            .map(|req| store.get_capture_results(&req.read().unwrap().id))
+            .unwrap_or_default()
+    }
+
+    fn confirm_edit(&mut self) {
+        let Some(editing) = self.editing.as_ref() else {
+            return;
+        };
+
+        let mut capture = match editing.value.parse::<Capture>() {
+            Ok(capture) => capture,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let mut captures = self.captures();
+        match editing.idx {
+            Some(idx) if idx.lt(&captures.len()) => {
+                // the textual form doesn't include persistence, so editing
+                // a capture must not reset it
+                capture.persist = captures[idx].persist;
+                captures[idx] = capture;
+            }
+            _ => {
+                captures.push(capture);
+                self.selected = captures.len().sub(1);
+            }
+        }
+
+        self.set_captures(captures);
+        self.editing = None;
+        self.error = None;
+    }
+
+    fn toggle_persist(&mut self) {
+        let mut captures = self.captures();
+        if let Some(capture) = captures.get_mut(self.selected) {
+            capture.persist = !capture.persist;
+            self.set_captures(captures);
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        let mut captures = self.captures();
+        if self.selected.lt(&captures.len()) {
+            captures.remove(self.selected);
+            self.selected = self.selected.min(captures.len().saturating_sub(1));
+            self.set_captures(captures);
+        }
+    }
+
+    fn make_capture_line(
+        &self,
+        idx: usize,
+        capture: &Capture,
+        results: &[CaptureResult],
+    ) -> Line<'static> {
+        let is_selected = idx.eq(&self.selected) && self.editing.is_none();
+        let name = match is_selected {
+            true => Span::styled(
+                format!(" {} ", capture),
+                self.colors
+                    .highlight(self.colors.normal.white, self.colors.normal.blue),
+            ),
+            false => format!(" {} ", capture).fg(self.colors.normal.white),
+        };
+
+        let mut spans = vec![name];
+
+        if capture.persist {
+            spans.push(Span::from(" persisted").fg(self.colors.normal.magenta));
+        }
+
+        // results are matched by the capture itself, as captures may have
+        // been edited since the last send
+        match results.iter().find(|result| result.capture.eq(capture)) {
+            Some(CaptureResult {
+                value: Some(value), ..
+            }) => {
+                let is_secret = self.collection_store.borrow().is_secret(&capture.name);
+                let value = match is_secret && !self.revealed {
+                    true => MASK,
+                    false => value.as_str(),
+                };
+                spans.push(format!(" -> {}", value).fg(self.colors.normal.green))
+            }
+            Some(CaptureResult { value: None, .. }) => {
+                spans.push(" -> not found on last response".fg(self.colors.normal.yellow))
+            }
+            None => {}
+        }
+
+        Line::from(spans)
+    }
+}
+
+impl Renderable for CapturesEditor<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        let [list_pane, input_pane, error_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(if self.editing.is_some() { 3 } else { 0 }),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(size);
+
+        let captures = self.captures();
+        let results = self.last_results();
+
+        let lines = match captures.is_empty() {
+            true => vec![
+                Line::from("No captures".fg(self.colors.normal.white)).centered(),
+                Line::from(
+                    "captures store values from responses into variables, eg: token = $.token"
+                        .fg(self.colors.bright.black),
+                )
+                .centered(),
+            ],
+            false => captures
+                .iter()
+                .enumerate()
+                .map(|(idx, capture)| self.make_capture_line(idx, capture, &results))
+                // keep the selected capture in view on long lists
+                .skip(
+                    self.selected
+                        .saturating_sub(list_pane.height.saturating_sub(1).into()),
+                )
+                .collect(),
+        };
+        frame.render_widget(Paragraph::new(lines), list_pane);
+
+        if let Some(editing) = self.editing.as_ref() {
+            let mut input = Input::new(self.colors, "Capture".into())
+                .placeholder("token = $.data.token or session = header x-session".into());
+            input.focus();
+            frame.render_stateful_widget(input, input_pane, &mut editing.value.clone());
+            frame.set_cursor(
+                input_pane
+                    .x
+                    .add(editing.value.chars().count() as u16)
+                    .add(1),
+                input_pane.y.add(1),
+            );
+        }
+
+        if let Some(error) = self.error.as_ref() {
+            frame.render_widget(
+                Line::from(error.clone().fg(self.colors.normal.red)),
+                error_pane,
+            );
+        }
+
+        let hint = match self.editing.is_some() {
+            true => "[enter -> confirm] [esc -> cancel]",
+            false => "[n -> new] [enter -> edit] [d -> delete] [p -> toggle persist] [r -> reveal]",
+        };
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for CapturesEditor<'_> {
+    type Result = CapturesEditorEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(CapturesEditorEvent::Quit));
+        }
+
+        if let Some(editing) = self.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) => editing.value.push(c),
+                KeyCode::Backspace => _ = editing.value.pop(),
+                KeyCode::Enter => self.confirm_edit(),
+                KeyCode::Esc => {
+                    self.editing = None;
+                    self.error = None;
+                }
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        let total = self.captures().len();
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(CapturesEditorEvent::RemoveSelection)),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = usize::min(self.selected.add(1), total.saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('n') => {
+                self.editing = Some(EditingCapture {
+                    idx: None,
+                    value: String::default(),
+                })
+            }
+            KeyCode::Enter | KeyCode::Char('i') if self.selected.lt(&total) => {
+                self.editing = Some(EditingCapture {
+                    idx: Some(self.selected),
+                    value: self.captures()[self.selected].to_string(),
+                })
+            }
+            KeyCode::Char('p') => self.toggle_persist(),
+            KeyCode::Char('d') => self.delete_selected(),
+            KeyCode::Char('r') => self.revealed = !self.revealed,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let Some(editing) = self.editing.as_mut() {
+            editing.value.push_str(&input::single_line(&text));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+    use hac_core::collection::types::*;
+    use std::sync::{Arc, RwLock};
+
+    fn make_store() -> (Rc<RefCell<CollectionStore>>, Arc<RwLock<Request>>) {
+        store_with_request(Request {
+            method: RequestMethod::Post,
+            ..make_request("login", "https://example.com/login")
+        })
+    }
+
+    fn press(editor: &mut CapturesEditor, code: KeyCode) {
+        editor
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_editing_keeps_persistence() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = make_store();
+        let mut editor = CapturesEditor::new(&colors, store);
+
+        press(&mut editor, KeyCode::Char('n'));
+        "token = $.token"
+            .chars()
+            .for_each(|c| press(&mut editor, KeyCode::Char(c)));
+        press(&mut editor, KeyCode::Enter);
+        press(&mut editor, KeyCode::Char('p'));
+
+        press(&mut editor, KeyCode::Enter);
+        "Id".chars()
+            .for_each(|c| press(&mut editor, KeyCode::Char(c)));
+        press(&mut editor, KeyCode::Enter);
+
+        assert_eq!(
+            request.read().unwrap().captures,
+            Some(vec![Capture {
+                name: "token".into(),
+                source: CaptureSource::JsonPath {
+                    path: "$.tokenId".into()
+                },
+                persist: true,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_escape_only_leaves_the_tab_when_not_editing() {
+        let colors = hac_colors::Colors::default();
+        let (store, _) = make_store();
+        let mut editor = CapturesEditor::new(&colors, store);
+
+        press(&mut editor, KeyCode::Char('n'));
+        assert!(editor.is_editing());
+
+        let event = editor
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(event, None);
+        assert!(!editor.is_editing());
+
+        let event = editor
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(event, Some(CapturesEditorEvent::RemoveSelection));
+    }
+}
@@ -2,6 +2,7 @@ use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_viewer::CollectionViewerOverlay;
 use crate::pages::overlay::make_overlay;
 use crate::pages::{collection_viewer::collection_store::CollectionStore, Eventful, Renderable};
+use crate::utils::highlight_placeholders;
 
 use std::ops::{Div, Mul, Sub};
 use std::{cell::RefCell, ops::Add, rc::Rc};
@@ -26,6 +27,12 @@ pub enum HeadersEditorEvent {
     RemoveSelection,
 }
 
+enum HeaderRow<'a> {
+    Written(&'a HeaderMap),
+    Inherited(&'a HeaderMap),
+    Computed(&'a (String, String)),
+}
+
 #[derive(Debug)]
 struct HeadersEditorLayout {
     name_header_size: Rect,
@@ -87,10 +94,14 @@ impl<'he> HeadersEditor<'he> {
             (false, false) => disabled,
         };
 
-        let make_paragraph = |text: &str| Paragraph::new(text.to_string()).fg(text_color);
-
-        let name = make_paragraph(&header.pair.0);
-        let value = make_paragraph(&header.pair.1);
+        let name = Paragraph::new(header.pair.0.clone()).fg(text_color);
+        let variables = self.collection_store.borrow().get_variables();
+        let value = highlight_placeholders(
+            Line::from(header.pair.1.clone().fg(text_color)),
+            &variables,
+            self.colors,
+        );
+        let value = Paragraph::new(value);
 
         let decor_fg = if is_selected { selected } else { normal };
         let checkbox = if header.enabled { "[x]" } else { "[ ]" };
@@ -102,6 +113,71 @@ impl<'he> HeadersEditor<'he> {
         frame.render_widget(Paragraph::new(checkbox).fg(decor_fg).centered(), row[3]);
     }
 
+    /// headers inherited from the collection are dimmed, they can only be
+    /// opted out of, which unchecks them, and are struck through when the
+    /// request overrides them
+    fn draw_inherited_row(
+        &self,
+        (row, header): (Vec<Rect>, &HeaderMap),
+        (skipped, overridden): (bool, bool),
+        frame: &mut Frame,
+        row_idx: usize,
+    ) {
+        let is_selected = row_idx.eq(&self.selected_row.saturating_sub(self.scroll));
+        let decor_fg = match is_selected {
+            true => self.colors.normal.red,
+            false => self.colors.bright.black,
+        };
+        let style = match overridden {
+            true => Style::default().fg(decor_fg).crossed_out(),
+            false => Style::default().fg(decor_fg).italic(),
+        };
+
+        let checkbox = if skipped { "[ ]" } else { "[x]" };
+        let chevron = if is_selected { ">" } else { " " };
+
+        frame.render_widget(Paragraph::new(chevron).fg(decor_fg), row[0]);
+        frame.render_widget(Paragraph::new(header.pair.0.clone()).style(style), row[1]);
+        frame.render_widget(Paragraph::new(header.pair.1.clone()).style(style), row[2]);
+        frame.render_widget(Paragraph::new(checkbox).style(style).centered(), row[3]);
+    }
+
+    /// enabled default headers of the collection, including the ones the
+    /// selected request opted out of
+    fn default_headers(&self) -> Vec<HeaderMap> {
+        let Some(collection) = self.collection_store.borrow().get_collection() else {
+            return vec![];
+        };
+        let collection = collection.borrow();
+        collection
+            .default_headers
+            .iter()
+            .flatten()
+            .filter(|header| header.enabled)
+            .cloned()
+            .collect()
+    }
+
+    /// computed headers are not editable, they show in another color with
+    /// `auto` in place of the checkbox, and struck through when a header of
+    /// the same name overrides them
+    fn draw_computed_row(
+        &self,
+        row: Vec<Rect>,
+        (name, value): &(String, String),
+        overridden: bool,
+        frame: &mut Frame,
+    ) {
+        let style = match overridden {
+            true => Style::default().fg(self.colors.bright.black).crossed_out(),
+            false => Style::default().fg(self.colors.normal.blue).italic(),
+        };
+
+        frame.render_widget(Paragraph::new(name.to_string()).style(style), row[1]);
+        frame.render_widget(Paragraph::new(value.to_string()).style(style), row[2]);
+        frame.render_widget(Paragraph::new("auto").style(style).centered(), row[3]);
+    }
+
     fn get_hint_size(&self, frame: &mut Frame) -> Rect {
         let size = frame.size();
         Rect::new(0, size.height.sub(1), size.width, 1)
@@ -228,8 +304,17 @@ impl Renderable for HeadersEditor<'_> {
         };
 
         let request = request.read().expect("failed to read selected request");
-        let Some(headers) = request.headers.as_ref() else {
+        let headers = request.headers.as_deref().unwrap_or_default();
+        let inherited = self.default_headers();
+        let computed = request.computed_headers();
+        if headers.is_empty() && inherited.is_empty() && computed.is_empty() {
             return Ok(());
+        }
+        let total_rows = headers.len().add(inherited.len()).add(computed.len());
+        let is_inherited = |name: &str| {
+            inherited.iter().any(|header| {
+                header.pair.0.eq_ignore_ascii_case(name) && !request.skips_default_header(name)
+            })
         };
 
         let title_name = Paragraph::new("Name").fg(self.colors.normal.yellow).bold();
@@ -238,12 +323,16 @@ impl Renderable for HeadersEditor<'_> {
             .fg(self.colors.normal.yellow)
             .bold();
 
-        for (idx, header) in headers
+        // inherited headers go after the ones the user wrote, and computed
+        // headers go last, they can't be selected so they never get in the
+        // way of the selection
+        let rows = headers
             .iter()
-            .skip(self.scroll)
-            .take(self.amount_on_view)
-            .enumerate()
-        {
+            .map(HeaderRow::Written)
+            .chain(inherited.iter().map(HeaderRow::Inherited))
+            .chain(computed.iter().map(HeaderRow::Computed));
+
+        for (idx, row) in rows.skip(self.scroll).take(self.amount_on_view).enumerate() {
             let size = self.layout.content_size;
             let offset = self.row_height.mul(idx as u16);
             let size = Rect::new(size.x, size.y.add(offset), size.width, self.row_height);
@@ -270,11 +359,21 @@ impl Renderable for HeadersEditor<'_> {
                 .map(|(_, rect)| *rect)
                 .collect::<Vec<_>>();
 
-            let pair = (layout, header);
-            self.draw_row(pair, frame, idx);
+            match row {
+                HeaderRow::Written(header) => self.draw_row((layout, header), frame, idx),
+                HeaderRow::Inherited(header) => {
+                    let skipped = request.skips_default_header(&header.pair.0);
+                    let overridden = request.has_header(&header.pair.0);
+                    self.draw_inherited_row((layout, header), (skipped, overridden), frame, idx);
+                }
+                HeaderRow::Computed(pair) => {
+                    let overridden = request.has_header(&pair.0) || is_inherited(&pair.0);
+                    self.draw_computed_row(layout, pair, overridden, frame);
+                }
+            }
         }
 
-        let mut scrollbar_state = ScrollbarState::new(headers.len())
+        let mut scrollbar_state = ScrollbarState::new(total_rows)
             .content_length(self.row_height.into())
             .position(self.scroll);
 
@@ -370,6 +469,7 @@ impl Eventful for HeadersEditor<'_> {
             return Ok(None);
         };
 
+        let inherited = self.default_headers();
         let mut request = request.write().unwrap();
 
         let total_headers = request
@@ -377,10 +477,19 @@ impl Eventful for HeadersEditor<'_> {
             .as_ref()
             .map(|h| h.len())
             .unwrap_or_default();
+        // inherited headers can be selected to opt out of them, but not
+        // edited or deleted
+        let selected_inherited = self
+            .selected_row
+            .checked_sub(total_headers)
+            .and_then(|idx| inherited.get(idx));
 
         match key_event.code {
             KeyCode::Char('j') | KeyCode::Down => {
-                self.selected_row = usize::min(self.selected_row.add(1), total_headers.sub(1))
+                self.selected_row = usize::min(
+                    self.selected_row.add(1),
+                    total_headers.add(inherited.len()).saturating_sub(1),
+                )
             }
             KeyCode::Char('k') | KeyCode::Up => {
                 self.selected_row = self.selected_row.saturating_sub(1);
@@ -396,6 +505,11 @@ impl Eventful for HeadersEditor<'_> {
                 };
             }
             KeyCode::Char(' ') => {
+                if let Some(header) = selected_inherited {
+                    request.toggle_default_header(&header.pair.0);
+                    return Ok(None);
+                }
+
                 if total_headers.eq(&0) {
                     return Ok(None);
                 }
@@ -413,7 +527,7 @@ impl Eventful for HeadersEditor<'_> {
                 }
             }
             KeyCode::Char('d') => {
-                if total_headers.eq(&0) {
+                if self.selected_row.ge(&total_headers) {
                     return Ok(None);
                 }
 
@@ -430,7 +544,7 @@ impl Eventful for HeadersEditor<'_> {
                 }
             }
             KeyCode::Enter => {
-                if total_headers.eq(&0) {
+                if self.selected_row.ge(&total_headers) {
                     return Ok(None);
                 }
 
@@ -467,9 +581,11 @@ impl Eventful for HeadersEditor<'_> {
         if self
             .selected_row
             .saturating_sub(self.scroll)
-            .ge(&self.amount_on_view.sub(1))
+            .ge(&self.amount_on_view.saturating_sub(1))
         {
-            self.scroll = self.selected_row.saturating_sub(self.amount_on_view.sub(1));
+            self.scroll = self
+                .selected_row
+                .saturating_sub(self.amount_on_view.saturating_sub(1));
         }
 
         if self.selected_row.saturating_sub(self.scroll).eq(&0) {
@@ -480,6 +596,15 @@ impl Eventful for HeadersEditor<'_> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let CollectionViewerOverlay::HeadersForm(_) =
+            self.collection_store.borrow().peek_overlay()
+        {
+            self.header_form.handle_paste(text)?;
+        }
+        Ok(None)
+    }
 }
 
 fn build_layout(size: Rect, row_height: u16) -> HeadersEditorLayout {
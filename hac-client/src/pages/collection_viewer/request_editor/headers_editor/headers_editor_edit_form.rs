@@ -1,7 +1,7 @@
 use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::collection_viewer::CollectionViewerOverlay;
-use crate::pages::input::Input;
+use crate::pages::input::{self, Input};
 use crate::pages::overlay::make_overlay;
 use crate::pages::{Eventful, Renderable};
 
@@ -10,13 +10,18 @@ use std::ops::{Add, Div, Sub};
 use std::rc::Rc;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use hac_core::collection::types::HeaderMap;
+use hac_core::header_suggestions;
 use rand::Rng;
 use ratatui::layout::Rect;
 use ratatui::style::Stylize;
-use ratatui::text::Line;
-use ratatui::widgets::Paragraph;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
 use ratatui::Frame;
 
+/// how many suggestions are visible at once
+const MAX_SUGGESTIONS: usize = 5;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HeadersEditorFormEvent {
     FinishEdit,
@@ -48,6 +53,13 @@ pub struct HeadersEditorForm<'hef> {
     focused_input: HeadersEditorFormInput,
     original_name: String,
     original_value: String,
+    /// header names used anywhere on the collection, offered as suggestions
+    /// along with the standard ones
+    known_names: Vec<String>,
+    /// completions for the focused input, they are only applied when
+    /// accepted with tab
+    suggestions: Vec<String>,
+    selected_suggestion: usize,
 }
 
 impl<'hef> HeadersEditorForm<'hef> {
@@ -65,6 +77,9 @@ impl<'hef> HeadersEditorForm<'hef> {
             focused_input: HeadersEditorFormInput::Name,
             original_name: String::default(),
             original_value: String::default(),
+            known_names: vec![],
+            suggestions: vec![],
+            selected_suggestion: 0,
         }
     }
 
@@ -96,12 +111,92 @@ impl<'hef> HeadersEditorForm<'hef> {
         self.original_name = header.pair.0.to_string();
         self.original_value = header.pair.1.to_string();
 
+        // the selected request is read again while collecting the names
+        drop(request);
+        self.known_names = store
+            .get_collection()
+            .map(|collection| known_header_names(&collection.borrow().flatten_requests()))
+            .unwrap_or_default();
+
         Ok(())
     }
 
     fn reset(&mut self) {
         self.original_name.clear();
         self.original_value.clear();
+        self.known_names.clear();
+        self.suggestions.clear();
+    }
+
+    fn draw_suggestions(&self, frame: &mut Frame, input_size: Rect) {
+        let start = self
+            .selected_suggestion
+            .saturating_sub(MAX_SUGGESTIONS.sub(1));
+        let lines = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(MAX_SUGGESTIONS)
+            .map(
+                |(idx, suggestion)| match idx.eq(&self.selected_suggestion) {
+                    true => Line::from(Span::styled(
+                        format!(" {suggestion} "),
+                        self.colors
+                            .highlight(self.colors.normal.white, self.colors.normal.blue),
+                    )),
+                    false => Line::from(format!(" {suggestion} ").fg(self.colors.normal.white)),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16;
+        let size = Rect::new(
+            input_size.x.add(1),
+            input_size.y.add(input_size.height).sub(1),
+            width,
+            lines.len() as u16,
+        )
+        .clamp(frame.size());
+
+        frame.render_widget(Clear, size);
+        frame.render_widget(Paragraph::new(lines).bg(self.colors.primary.hover), size);
+    }
+}
+
+/// every distinct header name on `requests`, in the order they show up
+fn known_header_names(
+    requests: &[std::sync::Arc<std::sync::RwLock<hac_core::collection::types::Request>>],
+) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for request in requests {
+        let request = request.read().unwrap();
+        for header in request.headers.iter().flatten() {
+            let name = header.pair.0.trim();
+            if !name.is_empty() && !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// completions for whatever is typed on the focused input
+fn suggestions_for(
+    input: HeadersEditorFormInput,
+    header: &HeaderMap,
+    known_names: &[String],
+) -> Vec<String> {
+    match input {
+        HeadersEditorFormInput::Name => {
+            header_suggestions::header_names(&header.pair.0, known_names)
+        }
+        HeadersEditorFormInput::Value => {
+            header_suggestions::header_values(&header.pair.0, &header.pair.1)
+                .into_iter()
+                .map(String::from)
+                .collect()
+        }
     }
 }
 
@@ -135,7 +230,7 @@ impl Renderable for HeadersEditorForm<'_> {
 
         let total_size = logo_size.add(11).add(2);
         let mut size = Rect::new(
-            size.width.div(2).sub(25),
+            size.width.div(2).saturating_sub(25),
             size.height
                 .div(2)
                 .saturating_sub(logo_size.div(2))
@@ -150,13 +245,15 @@ impl Renderable for HeadersEditorForm<'_> {
             size.height = 11;
             size.y = size.height.div(2).saturating_sub(5);
         }
+        let size = size.clamp(frame.size());
 
         let mut name_input = Input::new(self.colors, "Name".into());
         let mut value_input = Input::new(self.colors, "Value".into());
-        let hint = Paragraph::new(
-            "Press enter to confirm, press esc to cancel".fg(self.colors.bright.black),
-        )
-        .centered();
+        let hint = match self.suggestions.is_empty() {
+            true => "Press enter to confirm, press esc to cancel",
+            false => "tab accepts, up/down selects, esc dismisses",
+        };
+        let hint = Paragraph::new(hint.fg(self.colors.bright.black)).centered();
 
         match self.focused_input {
             HeadersEditorFormInput::Name => name_input.focus(),
@@ -198,6 +295,13 @@ impl Renderable for HeadersEditorForm<'_> {
             }
         }
 
+        if !self.suggestions.is_empty() {
+            match self.focused_input {
+                HeadersEditorFormInput::Name => self.draw_suggestions(frame, name_size),
+                HeadersEditorFormInput::Value => self.draw_suggestions(frame, value_size),
+            }
+        }
+
         Ok(())
     }
 }
@@ -233,16 +337,51 @@ impl Eventful for HeadersEditorForm<'_> {
         }
 
         match key_event.code {
-            KeyCode::Tab => self.focused_input = self.focused_input.next(),
-            KeyCode::BackTab => self.focused_input = self.focused_input.next(),
-            KeyCode::Backspace => match self.focused_input {
-                HeadersEditorFormInput::Name => _ = header.pair.0.pop(),
-                HeadersEditorFormInput::Value => _ = header.pair.1.pop(),
-            },
-            KeyCode::Char(c) => match self.focused_input {
-                HeadersEditorFormInput::Name => header.pair.0.push(c),
-                HeadersEditorFormInput::Value => header.pair.1.push(c),
-            },
+            // with suggestions on screen, tab accepts the selected one
+            // instead of moving to the other input
+            KeyCode::Tab if !self.suggestions.is_empty() => {
+                let suggestion = self.suggestions[self.selected_suggestion].clone();
+                match self.focused_input {
+                    HeadersEditorFormInput::Name => header.pair.0 = suggestion,
+                    HeadersEditorFormInput::Value => header.pair.1 = suggestion,
+                }
+                self.suggestions.clear();
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focused_input = self.focused_input.next();
+                // values are offered right away, names only once typing
+                self.suggestions = match self.focused_input {
+                    HeadersEditorFormInput::Name => vec![],
+                    HeadersEditorFormInput::Value => {
+                        suggestions_for(self.focused_input, header, &self.known_names)
+                    }
+                };
+                self.selected_suggestion = 0;
+            }
+            KeyCode::Down if !self.suggestions.is_empty() => {
+                self.selected_suggestion = self
+                    .selected_suggestion
+                    .add(1)
+                    .min(self.suggestions.len().sub(1));
+            }
+            KeyCode::Up => self.selected_suggestion = self.selected_suggestion.saturating_sub(1),
+            KeyCode::Backspace => {
+                match self.focused_input {
+                    HeadersEditorFormInput::Name => _ = header.pair.0.pop(),
+                    HeadersEditorFormInput::Value => _ = header.pair.1.pop(),
+                }
+                self.suggestions = suggestions_for(self.focused_input, header, &self.known_names);
+                self.selected_suggestion = 0;
+            }
+            KeyCode::Char(c) => {
+                match self.focused_input {
+                    HeadersEditorFormInput::Name => header.pair.0.push(c),
+                    HeadersEditorFormInput::Value => header.pair.1.push(c),
+                }
+                self.suggestions = suggestions_for(self.focused_input, header, &self.known_names);
+                self.selected_suggestion = 0;
+            }
+            KeyCode::Esc if !self.suggestions.is_empty() => self.suggestions.clear(),
             KeyCode::Esc => {
                 header.pair = (self.original_name.clone(), self.original_value.clone());
                 drop(store);
@@ -259,4 +398,120 @@ impl Eventful for HeadersEditorForm<'_> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        let store = self.collection_store.borrow();
+        let CollectionViewerOverlay::HeadersForm(idx) = store.peek_overlay() else {
+            return Ok(None);
+        };
+        let Some(request) = store.get_selected_request() else {
+            return Ok(None);
+        };
+
+        let mut request = request.write().unwrap();
+        if let Some(header) = request
+            .headers
+            .as_mut()
+            .and_then(|headers| headers.get_mut(idx))
+        {
+            let text = input::single_line(&text);
+            self.suggestions.clear();
+            match self.focused_input {
+                HeadersEditorFormInput::Name => header.pair.0.push_str(&text),
+                HeadersEditorFormInput::Value => header.pair.1.push_str(&text),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_collection, make_request, make_store};
+    use hac_core::collection::types::*;
+    use std::sync::{Arc, RwLock};
+
+    fn store_with_headers() -> (Rc<RefCell<CollectionStore>>, Arc<RwLock<Request>>) {
+        let with_headers = |id: &str, headers: Vec<(&str, &str)>| Request {
+            headers: Some(
+                headers
+                    .into_iter()
+                    .map(|(name, value)| HeaderMap {
+                        pair: (name.into(), value.into()),
+                        enabled: true,
+                    })
+                    .collect(),
+            ),
+            ..make_request(id, "https://example.com/users")
+        };
+        let collection = make_collection(
+            "users",
+            vec![
+                with_headers("users", vec![("", "")]),
+                with_headers("tenants", vec![("X-Tenant", "acme")]),
+            ],
+        );
+        let request = collection.flatten_requests().remove(0);
+
+        let store = make_store(collection);
+        store
+            .borrow_mut()
+            .push_overlay(CollectionViewerOverlay::HeadersForm(0));
+        (store, request)
+    }
+
+    fn feed(form: &mut HeadersEditorForm, codes: &[KeyCode]) {
+        for code in codes {
+            form.handle_key_event(KeyEvent::new(*code, KeyModifiers::NONE))
+                .unwrap();
+        }
+    }
+
+    fn header(request: &Arc<RwLock<Request>>) -> (String, String) {
+        request.read().unwrap().headers.as_ref().unwrap()[0]
+            .pair
+            .clone()
+    }
+
+    #[test]
+    fn test_suggestions_are_only_applied_when_accepted() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = store_with_headers();
+        let mut form = HeadersEditorForm::new(&colors, store);
+        form.update(0).unwrap();
+
+        feed(
+            &mut form,
+            &[KeyCode::Char('x'), KeyCode::Char('-'), KeyCode::Char('t')],
+        );
+        assert_eq!(form.suggestions, vec!["X-Tenant".to_string()]);
+        assert_eq!(header(&request).0, "x-t");
+
+        // esc only dismisses the suggestions, the form is still open
+        feed(&mut form, &[KeyCode::Esc]);
+        assert!(form.suggestions.is_empty());
+        assert_eq!(header(&request).0, "x-t");
+
+        feed(
+            &mut form,
+            &[KeyCode::Backspace, KeyCode::Char('t'), KeyCode::Tab],
+        );
+        assert_eq!(header(&request).0, "X-Tenant");
+        assert_eq!(form.focused_input, HeadersEditorFormInput::Name);
+
+        // values of well-known headers are offered as soon as the value is
+        // focused
+        feed(&mut form, &[KeyCode::Backspace; 8]);
+        feed(
+            &mut form,
+            &"pragma".chars().map(KeyCode::Char).collect::<Vec<_>>(),
+        );
+        feed(&mut form, &[KeyCode::Esc, KeyCode::Tab]);
+        assert_eq!(form.focused_input, HeadersEditorFormInput::Value);
+        assert_eq!(form.suggestions, vec!["no-cache".to_string()]);
+        feed(&mut form, &[KeyCode::Tab]);
+        assert_eq!(header(&request), ("pragma".into(), "no-cache".into()));
+    }
 }
@@ -0,0 +1,243 @@
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::markdown;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::Add;
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NotesEditorEvent {
+    Quit,
+    RemoveSelection,
+}
+
+/// shows the notes of the selected request rendered as markdown, and edits
+/// them as plain text
+#[derive(Debug)]
+pub struct NotesEditor<'ne> {
+    colors: &'ne hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    scroll: u16,
+    /// text being typed, the notes are only written to the request when
+    /// leaving the edit
+    editing: Option<String>,
+}
+
+impl<'ne> NotesEditor<'ne> {
+    pub fn new(
+        colors: &'ne hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        NotesEditor {
+            colors,
+            collection_store,
+            scroll: 0,
+            editing: None,
+        }
+    }
+
+    /// while typing, keys like `Tab` shouldn't switch the editor tabs
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn notes(&self) -> Option<String> {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .and_then(|req| req.read().unwrap().notes.clone())
+    }
+
+    /// blank notes are removed instead of kept around as whitespace
+    fn save(&mut self) {
+        let Some(notes) = self.editing.take() else {
+            return;
+        };
+
+        if let Some(request) = self.collection_store.borrow().get_selected_request() {
+            let notes = notes.trim_end().to_string();
+            request.write().unwrap().notes = (!notes.trim().is_empty()).then_some(notes);
+        }
+    }
+
+    fn draw_notes(&self, frame: &mut Frame, size: Rect) {
+        let lines = match self.notes() {
+            Some(notes) => markdown::render(&notes, self.colors),
+            None => vec![
+                Line::from("No notes".fg(self.colors.normal.white)).centered(),
+                Line::from(
+                    "notes document the request, eg: required scopes or payload quirks"
+                        .fg(self.colors.bright.black),
+                )
+                .centered(),
+            ],
+        };
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            size,
+        );
+    }
+
+    fn draw_editing(&self, frame: &mut Frame, size: Rect, text: &str) {
+        // `lines` would skip the empty line after a trailing newline, which
+        // is exactly where the cursor is
+        let lines = text.split('\n').collect::<Vec<_>>();
+
+        // typing always happens at the end, so the end is kept in view
+        let offset = lines.len().saturating_sub(size.height.into());
+        let last = lines.last().map(|line| line.chars().count()).unwrap_or(0);
+        let visible = lines[offset..]
+            .iter()
+            .map(|line| Line::from(line.to_string().fg(self.colors.normal.white)))
+            .collect::<Vec<_>>();
+
+        frame.render_widget(Paragraph::new(visible), size);
+        frame.set_cursor(
+            size.x
+                .add(u16::min(last as u16, size.width.saturating_sub(1))),
+            size.y.add((lines.len() - offset).saturating_sub(1) as u16),
+        );
+    }
+}
+
+impl Renderable for NotesEditor<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        let [content_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(size);
+
+        match self.editing.as_ref() {
+            Some(text) => self.draw_editing(frame, content_pane, text),
+            None => self.draw_notes(frame, content_pane),
+        }
+
+        let hint = match self.editing.is_some() {
+            true => "[esc -> save]",
+            false => "[enter -> edit] [j/k -> scroll]",
+        };
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for NotesEditor<'_> {
+    type Result = NotesEditorEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(NotesEditorEvent::Quit));
+        }
+
+        if let Some(editing) = self.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) => editing.push(c),
+                KeyCode::Enter => editing.push('\n'),
+                KeyCode::Backspace => _ = editing.pop(),
+                KeyCode::Esc => self.save(),
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(NotesEditorEvent::RemoveSelection)),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Enter | KeyCode::Char('i') | KeyCode::Char('e') => {
+                self.editing = Some(self.notes().unwrap_or_default());
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let Some(editing) = self.editing.as_mut() {
+            editing.extend(text.chars().filter(|c| c.ne(&'\r')));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+    use hac_core::collection::types::*;
+    use std::sync::{Arc, RwLock};
+
+    fn make_store(notes: &str) -> (Rc<RefCell<CollectionStore>>, Arc<RwLock<Request>>) {
+        store_with_request(Request {
+            notes: Some(notes.into()),
+            ..make_request("users", "https://example.com/users")
+        })
+    }
+
+    fn feed(editor: &mut NotesEditor, codes: &[KeyCode]) {
+        for code in codes {
+            editor
+                .handle_key_event(KeyEvent::new(*code, KeyModifiers::NONE))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_editing_writes_the_notes_on_the_request() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = make_store("needs");
+        let mut editor = NotesEditor::new(&colors, store);
+
+        feed(&mut editor, &[KeyCode::Enter]);
+        assert!(editor.is_editing());
+        feed(
+            &mut editor,
+            &[KeyCode::Char(' '), KeyCode::Char('x'), KeyCode::Enter],
+        );
+        editor.handle_paste("- `read:users`\r\n".into()).unwrap();
+        // nothing is written until leaving the edit
+        assert_eq!(request.read().unwrap().notes, Some("needs".into()));
+
+        feed(&mut editor, &[KeyCode::Esc]);
+        assert!(!editor.is_editing());
+        assert_eq!(
+            request.read().unwrap().notes,
+            Some("needs x\n- `read:users`".into())
+        );
+    }
+
+    #[test]
+    fn test_blank_notes_are_removed() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = make_store("a");
+        let mut editor = NotesEditor::new(&colors, store);
+
+        feed(
+            &mut editor,
+            &[
+                KeyCode::Enter,
+                KeyCode::Backspace,
+                KeyCode::Enter,
+                KeyCode::Esc,
+            ],
+        );
+
+        assert_eq!(request.read().unwrap().notes, None);
+    }
+}
@@ -0,0 +1,351 @@
+use hac_core::net::wire::WireRequest;
+use hac_core::secrets::MASK;
+use hac_core::variables::{self, Layer};
+
+use crate::clipboard;
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::ops::{Add, Div, Sub};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// set of events `RequestPreview` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RequestPreviewEvent {
+    /// user pressed `Esc`, the popup should be hidden
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// read-only popup showing the selected request exactly as it would be sent,
+/// with variables resolved. Placeholders that couldn't be resolved are left
+/// in and highlighted, secrets are masked unless revealed, and the variables
+/// used are listed along with where their values came from, as are the
+/// headers that came from the config file
+#[derive(Debug)]
+pub struct RequestPreview<'rp> {
+    colors: &'rp hac_colors::Colors,
+    wire: Result<WireRequest, String>,
+    unresolved: Vec<String>,
+    sources: Vec<(String, Layer)>,
+    /// names of the headers added by the global config
+    global_headers: Vec<String>,
+    secrets: Vec<String>,
+    warnings: Vec<String>,
+    revealed: bool,
+    scroll: usize,
+    notice: Option<String>,
+}
+
+impl<'rp> RequestPreview<'rp> {
+    pub fn new(colors: &'rp hac_colors::Colors) -> Self {
+        RequestPreview {
+            colors,
+            wire: Err(String::from("no request selected")),
+            unresolved: vec![],
+            sources: vec![],
+            global_headers: vec![],
+            secrets: vec![],
+            warnings: vec![],
+            revealed: false,
+            scroll: 0,
+            notice: None,
+        }
+    }
+
+    /// replaces the request being previewed, `unresolved` are the names of
+    /// the placeholders left on it, `sources` the layer each variable used
+    /// came from, `global_headers` the headers added by the config file,
+    /// `secrets` the values to mask and `warnings` what would be told when
+    /// sending it
+    pub fn set_request(
+        &mut self,
+        wire: Result<WireRequest, String>,
+        unresolved: Vec<String>,
+        sources: Vec<(String, Layer)>,
+        global_headers: Vec<String>,
+        mut secrets: Vec<String>,
+        warnings: Vec<String>,
+    ) {
+        // longer secrets go first, so one containing another is still masked
+        // whole
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        self.wire = wire;
+        self.unresolved = unresolved;
+        self.sources = sources;
+        self.global_headers = global_headers;
+        self.secrets = secrets;
+        self.warnings = warnings;
+        self.revealed = false;
+        self.scroll = 0;
+        self.notice = None;
+    }
+
+    /// the raw http message, as shown
+    fn message(&self) -> Option<String> {
+        let message = self.wire.as_ref().ok()?.to_http();
+        match self.revealed {
+            true => Some(message),
+            false => Some(
+                self.secrets
+                    .iter()
+                    .fold(message, |message, secret| message.replace(secret, MASK)),
+            ),
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let Some(message) = self.message() else {
+            let error = self.wire.as_ref().err().cloned().unwrap_or_default();
+            return vec![Line::from(error.fg(self.colors.normal.red))];
+        };
+
+        let (head, body) = message.split_once("\r\n\r\n").unwrap_or((&message, ""));
+        let mut lines = vec![];
+        for (idx, line) in head.split("\r\n").enumerate() {
+            match line.split_once(": ") {
+                Some((name, value)) if idx.gt(&0) => {
+                    let mut spans =
+                        vec![Span::from(format!("{name}: ")).fg(self.colors.normal.blue)];
+                    spans.extend(
+                        self.highlight(value, Style::default().fg(self.colors.normal.white)),
+                    );
+                    if self
+                        .global_headers
+                        .iter()
+                        .any(|n| n.eq_ignore_ascii_case(name))
+                    {
+                        spans.push("  from global config".fg(self.colors.bright.black));
+                    }
+                    lines.push(Line::from(spans));
+                }
+                _ => lines.push(Line::from(
+                    self.highlight(line, Style::default().fg(self.colors.normal.white).bold()),
+                )),
+            }
+        }
+
+        lines.push(Line::default());
+        for line in body.lines() {
+            lines.push(Line::from(
+                self.highlight(line, Style::default().fg(self.colors.normal.white)),
+            ));
+        }
+
+        if !self.sources.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("variables".fg(self.colors.normal.yellow).bold()));
+        }
+        for (name, layer) in self.sources.iter() {
+            let color = match layer {
+                Layer::Collection => self.colors.normal.blue,
+                Layer::Environment => self.colors.normal.green,
+                Layer::Request => self.colors.normal.magenta,
+            };
+            lines.push(Line::from(vec![
+                format!("  {{{{{name}}}}} ").fg(self.colors.normal.white),
+                format!("from the {layer}").fg(color),
+            ]));
+        }
+
+        lines
+    }
+
+    /// splits `text` around the unresolved placeholders so they stand out
+    fn highlight(&self, text: &str, style: Style) -> Vec<Span<'static>> {
+        let mut spans = vec![];
+        let mut last = 0;
+
+        for (range, name) in variables::placeholder_spans(text) {
+            if !self.unresolved.iter().any(|unresolved| unresolved.eq(name)) {
+                continue;
+            }
+            spans.push(Span::styled(text[last..range.start].to_string(), style));
+            spans.push(
+                Span::from(text[range.clone()].to_string())
+                    .fg(self.colors.normal.red)
+                    .bold(),
+            );
+            last = range.end;
+        }
+
+        spans.push(Span::styled(text[last..].to_string(), style));
+        spans
+    }
+
+    fn copy(&mut self) {
+        let copied = match self.message() {
+            Some(message) => clipboard::copy(&message).map_err(|e| e.to_string()),
+            None => Err(String::from("there is nothing to copy")),
+        };
+
+        self.notice = Some(match copied {
+            Ok(_) => String::from("copied as a raw HTTP message"),
+            Err(e) => format!("failed to copy: {e}"),
+        });
+    }
+}
+
+impl Renderable for RequestPreview<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let popup = build_layout(size);
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Request preview ".fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let [message_pane, notice_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(inner);
+
+        let lines = self.lines();
+        self.scroll = self
+            .scroll
+            .min(lines.len().saturating_sub(message_pane.height.into()));
+        frame.render_widget(
+            Paragraph::new(lines).scroll((self.scroll as u16, 0)),
+            message_pane,
+        );
+
+        let notice = match (self.notice.as_ref(), self.unresolved.is_empty()) {
+            (Some(notice), _) => notice.clone().fg(self.colors.normal.green),
+            (None, false) => {
+                format!("unresolved: {}", self.unresolved.join(", ")).fg(self.colors.normal.red)
+            }
+            (None, true) => self.warnings.join(", ").fg(self.colors.normal.yellow),
+        };
+        frame.render_widget(Line::from(notice), notice_pane);
+
+        let reveal = match self.revealed {
+            true => "hide",
+            false => "reveal",
+        };
+        frame.render_widget(
+            Line::from(
+                format!("[j/k -> scroll] [y -> copy] [r -> {reveal} secrets] [esc -> close]")
+                    .fg(self.colors.bright.black),
+            )
+            .centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for RequestPreview<'_> {
+    type Result = RequestPreviewEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(RequestPreviewEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(Some(RequestPreviewEvent::Close)),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('r') => self.revealed = !self.revealed,
+            KeyCode::Char('y') => self.copy(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+fn build_layout(size: Rect) -> Rect {
+    let width = u16::min(size.width.saturating_sub(4), 100);
+    let height = u16::min(size.height.saturating_sub(4), 30);
+
+    Rect::new(
+        size.x.add(size.width.sub(width).div(2)),
+        size.y.add(size.height.sub(height).div(2)),
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_masks_secrets_and_highlights_unresolved() {
+        let colors = hac_colors::Colors::default();
+        let mut preview = RequestPreview::new(&colors);
+        preview.set_request(
+            Ok(WireRequest {
+                method: "GET".into(),
+                url: "http://localhost/{{tenant}}/users".into(),
+                headers: vec![
+                    ("Host".into(), "localhost".into()),
+                    ("authorization".into(), "Bearer hunter2".into()),
+                    ("user-agent".into(), "my-team-tool".into()),
+                ],
+                body: None,
+            }),
+            vec!["tenant".into()],
+            vec![
+                ("version".into(), Layer::Collection),
+                ("token".into(), Layer::Environment),
+            ],
+            vec!["User-Agent".into()],
+            vec!["hunter2".into()],
+            vec![],
+        );
+
+        let lines = preview
+            .lines()
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0], "GET http://localhost/{{tenant}}/users HTTP/1.1");
+        assert_eq!(lines[2], format!("authorization: Bearer {MASK}"));
+        assert_eq!(lines[3], "user-agent: my-team-tool  from global config");
+        assert_eq!(
+            lines[lines.len() - 3..],
+            [
+                "variables",
+                "  {{version}} from the collection",
+                "  {{token}} from the environment",
+            ]
+        );
+
+        let placeholder = preview.lines()[0].spans[1].clone();
+        assert_eq!(placeholder.content, "{{tenant}}");
+        assert_eq!(placeholder.style.fg, Some(colors.normal.red));
+
+        // escaped braces are sent as written, they are not placeholders
+        let spans = preview.highlight(r"\{{tenant}} {{tenant}}", Style::default());
+        assert_eq!(spans[0].content, r"\{{tenant}} ");
+        assert_eq!(spans[1].content, "{{tenant}}");
+        assert_eq!(spans[1].style.fg, Some(colors.normal.red));
+
+        preview
+            .handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(preview.message().unwrap().contains("Bearer hunter2"));
+        // only what goes on the wire is copied
+        assert!(!preview.message().unwrap().contains("from global config"));
+    }
+}
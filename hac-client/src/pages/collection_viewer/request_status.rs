@@ -0,0 +1,438 @@
+use hac_core::collection::types::RequestMethod;
+use hac_core::net::download::{format_size, Progress};
+use hac_core::net::request_manager::Response;
+
+use crate::pages::spinner::Spinner;
+use crate::pages::status_bar::Segment;
+
+use std::time::{Duration, Instant};
+
+use ratatui::style::{Style, Stylize};
+use tokio::task::JoinHandle;
+
+/// how long each spinner frame is displayed for
+const SPINNER_FRAME: Duration = Duration::from_millis(80);
+/// how long a notice stays on the status bar
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+/// the window title when there is no request to tell about
+pub const APP_TITLE: &str = "hac";
+
+/// a request that got a response or failed, the terminal is told about the
+/// slow ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedRequest {
+    /// method and path of the request, eg: `GET /reports`
+    pub request: String,
+    /// eg: `✓ 200 in 14.2s`
+    pub outcome: String,
+    /// from sending the request until it finished
+    pub took: Duration,
+}
+
+#[derive(Debug, Default)]
+enum RequestState {
+    #[default]
+    Idle,
+    Sending {
+        method: RequestMethod,
+        uri: String,
+        started: Instant,
+        handle: JoinHandle<()>,
+        /// set once the response body starts arriving
+        progress: Option<Progress>,
+    },
+    Finished {
+        summary: String,
+        is_error: bool,
+        /// the short version of the summary, for the window title
+        outcome: String,
+    },
+    Cancelled,
+}
+
+/// status segments on the bottom line of the viewer, showing the request that
+/// is currently in flight with a live elapsed counter, and the outcome of the
+/// last request once it finishes.
+///
+/// there is no tick driving the counter here, the render loop runs on its own
+/// interval, so every frame simply reads the elapsed time again
+#[derive(Debug)]
+pub struct RequestStatus<'rs> {
+    colors: &'rs hac_colors::Colors,
+    state: RequestState,
+    /// short lived message shown before the request segments
+    notice: Option<(String, Instant)>,
+    /// the last finished request, until someone takes it to announce it
+    unannounced: Option<FinishedRequest>,
+}
+
+impl<'rs> RequestStatus<'rs> {
+    pub fn new(colors: &'rs hac_colors::Colors) -> Self {
+        RequestStatus {
+            colors,
+            state: RequestState::Idle,
+            notice: None,
+            unannounced: None,
+        }
+    }
+
+    /// shows `message` for a few seconds, without touching the request
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.notice = Some((message.into(), Instant::now()));
+    }
+
+    /// a send still in flight is aborted, so its response never lands over
+    /// the new one
+    pub fn start(&mut self, method: RequestMethod, uri: String, handle: JoinHandle<()>) {
+        if let RequestState::Sending { handle, .. } = &self.state {
+            handle.abort();
+        }
+        self.state = RequestState::Sending {
+            method,
+            uri,
+            started: Instant::now(),
+            handle,
+            progress: None,
+        };
+    }
+
+    pub fn progress(&mut self, new_progress: Progress) {
+        if let RequestState::Sending { progress, .. } = &mut self.state {
+            *progress = Some(new_progress);
+        }
+    }
+
+    pub fn finish(&mut self, response: &Response) {
+        let took = self.in_flight().map_or(response.duration, |(_, took)| took);
+        let is_error = response.is_error
+            || response
+                .status
+                .is_some_and(|s| s.is_client_error() || s.is_server_error());
+        let outcome = match (response.is_error, response.status) {
+            (true, _) => format!("✗ failed after {}", title_elapsed(took)),
+            (false, Some(status)) if is_error => {
+                format!("✗ {} in {}", status.as_u16(), title_elapsed(took))
+            }
+            (false, Some(status)) => format!("✓ {} in {}", status.as_u16(), title_elapsed(took)),
+            (false, None) => format!("✓ done in {}", title_elapsed(took)),
+        };
+        self.finished(summarize(response), is_error, outcome, took);
+    }
+
+    /// the request never made it out, eg: its pre-request hook failed
+    pub fn fail(&mut self, summary: impl Into<String>) {
+        let summary = summary.into();
+        let took = self.in_flight().map(|(_, took)| took).unwrap_or_default();
+        self.finished(summary.clone(), true, format!("✗ {summary}"), took);
+    }
+
+    fn finished(&mut self, summary: String, is_error: bool, outcome: String, took: Duration) {
+        let request = self
+            .in_flight()
+            .map(|(request, _)| request)
+            .unwrap_or_default();
+        self.unannounced = Some(FinishedRequest {
+            request,
+            outcome: outcome.clone(),
+            took,
+        });
+        self.state = RequestState::Finished {
+            summary,
+            is_error,
+            outcome,
+        };
+    }
+
+    /// the request being sent, and for how long it has been
+    fn in_flight(&self) -> Option<(String, Duration)> {
+        match &self.state {
+            RequestState::Sending {
+                method,
+                uri,
+                started,
+                ..
+            } => Some((format!("{method} {}", uri_path(uri)), started.elapsed())),
+            _ => None,
+        }
+    }
+
+    /// the request that finished last, only once
+    pub fn take_finished(&mut self) -> Option<FinishedRequest> {
+        self.unannounced.take()
+    }
+
+    /// eg: `hac — waiting on GET /reports… 12s`, and `hac — ✓ 200 in 14.2s`
+    /// once it finishes
+    pub fn window_title(&self) -> String {
+        match &self.state {
+            RequestState::Sending {
+                method,
+                uri,
+                started,
+                ..
+            } => format!(
+                "{APP_TITLE} — waiting on {method} {}… {}s",
+                uri_path(uri),
+                started.elapsed().as_secs()
+            ),
+            RequestState::Finished { outcome, .. } => format!("{APP_TITLE} — {outcome}"),
+            RequestState::Idle | RequestState::Cancelled => APP_TITLE.into(),
+        }
+    }
+
+    /// aborts the request in flight, returns whether there was anything to
+    /// cancel
+    pub fn cancel(&mut self) -> bool {
+        match &self.state {
+            RequestState::Sending { handle, .. } => {
+                handle.abort();
+                self.state = RequestState::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_sending(&self) -> bool {
+        matches!(self.state, RequestState::Sending { .. })
+    }
+
+    /// the segments describing the request for the viewer status bar
+    pub fn segments(&self) -> Vec<Segment> {
+        let notice = self
+            .notice
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed().lt(&NOTICE_DURATION))
+            .map(|(message, _)| {
+                Segment::left(message.clone().fg(self.colors.normal.yellow)).with_priority(4)
+            });
+
+        notice.into_iter().chain(self.request_segments()).collect()
+    }
+
+    fn request_segments(&self) -> Vec<Segment> {
+        match &self.state {
+            RequestState::Idle => vec![],
+            RequestState::Sending {
+                method,
+                uri,
+                started,
+                progress,
+                ..
+            } => {
+                let elapsed = started.elapsed();
+                let step = (elapsed.as_millis() / SPINNER_FRAME.as_millis()) as usize;
+                let label = match progress {
+                    Some(progress) => format!(
+                        "Receiving {} {} ({})",
+                        method,
+                        uri,
+                        format_progress(progress)
+                    ),
+                    None => format!("Sending {} {} ({})", method, uri, format_elapsed(elapsed)),
+                };
+                let spinner = Spinner::default()
+                    .with_step(step)
+                    .with_label(label.fg(self.colors.normal.white))
+                    .with_style(Style::default().fg(self.colors.normal.yellow))
+                    .into_line();
+
+                vec![
+                    Segment::left(spinner).with_priority(3),
+                    Segment::right("[<C-x> -> cancel]".fg(self.colors.normal.magenta))
+                        .with_priority(2),
+                ]
+            }
+            RequestState::Finished {
+                summary, is_error, ..
+            } => {
+                let color = match is_error {
+                    true => self.colors.normal.red,
+                    false => self.colors.normal.green,
+                };
+                vec![Segment::left(summary.clone().fg(color)).with_priority(3)]
+            }
+            RequestState::Cancelled => {
+                vec![
+                    Segment::left("Request cancelled".fg(self.colors.bright.black))
+                        .with_priority(3),
+                ]
+            }
+        }
+    }
+}
+
+fn summarize(response: &Response) -> String {
+    if response.is_error {
+        return format!(
+            "Request failed: {}",
+            response.cause.as_deref().unwrap_or("unknown error")
+        );
+    }
+
+    match response.status {
+        Some(status) => format!("{} in {} ms", status, response.duration.as_millis()),
+        None => format!("Done in {} ms", response.duration.as_millis()),
+    }
+}
+
+/// eg: `12.0 MiB of 48.0 MiB, 25%, 4.0 MiB/s`, the total is only known when
+/// the server sent a `Content-Length`
+fn format_progress(progress: &Progress) -> String {
+    let rate = format!("{}/s", format_size(progress.rate()));
+    match (progress.total, progress.percent()) {
+        (Some(total), Some(percent)) => format!(
+            "{} of {}, {}%, {}",
+            format_size(progress.received),
+            format_size(total),
+            percent,
+            rate
+        ),
+        _ => format!("{}, {}", format_size(progress.received), rate),
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    format!("{:.1} s", elapsed.as_secs_f64())
+}
+
+/// like `format_elapsed`, but tighter for the window title
+fn title_elapsed(elapsed: Duration) -> String {
+    format!("{:.1}s", elapsed.as_secs_f64())
+}
+
+/// titles are short, so the scheme and host are left out
+fn uri_path(uri: &str) -> &str {
+    match uri.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |idx| &rest[idx..]),
+        None => uri,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished_with(status: Option<u16>, cause: Option<&str>) -> Response {
+        Response {
+            status: status.map(|status| reqwest::StatusCode::from_u16(status).unwrap()),
+            is_error: cause.is_some(),
+            cause: cause.map(String::from),
+            duration: Duration::from_millis(431),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        assert_eq!(
+            summarize(&finished_with(Some(200), None)),
+            "200 OK in 431 ms"
+        );
+        assert_eq!(
+            summarize(&finished_with(Some(404), None)),
+            "404 Not Found in 431 ms"
+        );
+        assert_eq!(
+            summarize(&finished_with(None, Some("connection refused"))),
+            "Request failed: connection refused"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_window_title_follows_the_request() {
+        let colors = hac_colors::Colors::default();
+        let mut status = RequestStatus::new(&colors);
+        assert_eq!(status.window_title(), "hac");
+
+        let handle = tokio::spawn(async {});
+        status.start(
+            RequestMethod::Get,
+            "https://api.example.com/reports?year=2024".into(),
+            handle,
+        );
+        assert_eq!(
+            status.window_title(),
+            "hac — waiting on GET /reports?year=2024… 0s"
+        );
+        assert_eq!(status.take_finished(), None);
+
+        status.finish(&finished_with(Some(200), None));
+        assert!(status.window_title().starts_with("hac — ✓ 200 in 0.0s"));
+        let finished = status.take_finished().unwrap();
+        assert_eq!(finished.request, "GET /reports?year=2024");
+        assert_eq!(finished.outcome, "✓ 200 in 0.0s");
+        // it is only announced once
+        assert_eq!(status.take_finished(), None);
+
+        status.fail("pre-request hook failed");
+        assert_eq!(status.window_title(), "hac — ✗ pre-request hook failed");
+        assert_eq!(status.take_finished().unwrap().took, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_starting_a_send_aborts_the_one_in_flight() {
+        let colors = hac_colors::Colors::default();
+        let mut status = RequestStatus::new(&colors);
+
+        let stale = tokio::spawn(std::future::pending::<()>());
+        let abort = stale.abort_handle();
+        status.start(RequestMethod::Get, "/stale".into(), stale);
+        status.start(RequestMethod::Get, "/fresh".into(), tokio::spawn(async {}));
+        tokio::task::yield_now().await;
+        assert!(abort.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_only_client_and_server_errors_fail() {
+        let colors = hac_colors::Colors::default();
+        let mut status = RequestStatus::new(&colors);
+        let outcome = |status: &mut RequestStatus, response: Response| {
+            status.start(RequestMethod::Get, "/".into(), tokio::spawn(async {}));
+            status.finish(&response);
+            status.take_finished().unwrap().outcome
+        };
+
+        assert_eq!(
+            outcome(&mut status, finished_with(Some(304), None)),
+            "✓ 304 in 0.0s"
+        );
+        assert_eq!(
+            outcome(&mut status, finished_with(Some(404), None)),
+            "✗ 404 in 0.0s"
+        );
+        assert_eq!(
+            outcome(&mut status, finished_with(Some(503), None)),
+            "✗ 503 in 0.0s"
+        );
+        assert_eq!(
+            outcome(&mut status, finished_with(None, Some("timed out"))),
+            "✗ failed after 0.0s"
+        );
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(1234)), "1.2 s");
+        assert_eq!(format_elapsed(Duration::from_millis(40)), "0.0 s");
+    }
+
+    #[test]
+    fn test_format_progress() {
+        let progress = Progress {
+            received: 12 * 1024 * 1024,
+            total: Some(48 * 1024 * 1024),
+            elapsed: Duration::from_secs(3),
+        };
+        assert_eq!(
+            format_progress(&progress),
+            "12.0 MiB of 48.0 MiB, 25%, 4.0 MiB/s"
+        );
+
+        let progress = Progress {
+            total: None,
+            ..progress
+        };
+        assert_eq!(format_progress(&progress), "12.0 MiB, 4.0 MiB/s");
+    }
+}
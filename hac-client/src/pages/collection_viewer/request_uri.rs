@@ -1,17 +1,24 @@
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::collection_store::CollectionStoreAction;
 use crate::pages::collection_viewer::collection_viewer::PaneFocus;
+use crate::pages::input;
 use crate::pages::{Eventful, Renderable};
+use crate::utils::highlight_placeholders;
 
 use std::cell::RefCell;
+use std::ops::{Add, Sub};
 use std::rc::Rc;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::Rect;
-use ratatui::style::{Style, Stylize};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
+/// how many suggestions are visible at once
+const MAX_SUGGESTIONS: usize = 5;
+
 /// Set of events RequestUri can send back to the caller when handling key_events
 #[derive(Debug)]
 pub enum RequestUriEvent {
@@ -30,6 +37,11 @@ pub struct RequestUri<'ru> {
     colors: &'ru hac_colors::Colors,
     collection_store: Rc<RefCell<CollectionStore>>,
     size: Rect,
+    /// urls completing what was typed, only applied when accepted with tab
+    suggestions: Vec<String>,
+    selected_suggestion: usize,
+    /// suggests urls sent from any collection instead of only this one
+    global_suggestions: bool,
 }
 
 impl<'ru> RequestUri<'ru> {
@@ -42,7 +54,80 @@ impl<'ru> RequestUri<'ru> {
             colors,
             collection_store,
             size,
+            suggestions: vec![],
+            selected_suggestion: 0,
+            global_suggestions: false,
+        }
+    }
+
+    fn uri(&self) -> Option<String> {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|req| req.read().unwrap().uri.clone())
+    }
+
+    /// suggestions are looked up again on every change to the uri, nothing
+    /// is suggested until something is typed
+    fn refresh_suggestions(&mut self) {
+        self.selected_suggestion = 0;
+        self.suggestions = match self.uri() {
+            Some(uri) if !uri.is_empty() => self
+                .collection_store
+                .borrow()
+                .url_suggestions(&uri, self.global_suggestions),
+            _ => vec![],
+        };
+    }
+
+    fn accept_suggestion(&mut self) {
+        let Some(suggestion) = self.suggestions.get(self.selected_suggestion).cloned() else {
+            return;
+        };
+        if let Some(req) = self.collection_store.borrow().get_selected_request() {
+            req.write().unwrap().uri = suggestion;
         }
+        self.suggestions.clear();
+    }
+
+    fn draw_suggestions(&self, frame: &mut Frame, size: Rect) {
+        let start = self
+            .selected_suggestion
+            .saturating_sub(MAX_SUGGESTIONS.sub(1));
+        let mut lines = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(MAX_SUGGESTIONS)
+            .map(
+                |(idx, suggestion)| match idx.eq(&self.selected_suggestion) {
+                    true => Line::from(Span::styled(
+                        format!(" {suggestion} "),
+                        self.colors
+                            .highlight(self.colors.normal.white, self.colors.normal.blue),
+                    )),
+                    false => Line::from(format!(" {suggestion} ").fg(self.colors.normal.white)),
+                },
+            )
+            .collect::<Vec<_>>();
+        let scope = match self.global_suggestions {
+            true => " [tab -> complete] [C-g -> this collection] ",
+            false => " [tab -> complete] [C-g -> all collections] ",
+        };
+        lines.push(Line::from(scope.fg(self.colors.bright.black)));
+
+        let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16;
+        let size = Rect::new(
+            size.x.add(1),
+            size.y.add(size.height).sub(1),
+            width,
+            lines.len() as u16,
+        )
+        .clamp(frame.size());
+
+        frame.render_widget(Clear, size);
+        frame.render_widget(Paragraph::new(lines).bg(self.colors.primary.hover), size);
     }
 }
 
@@ -63,11 +148,7 @@ impl<'a> Renderable for RequestUri<'a> {
             .get_selected_pane()
             .is_some_and(|pane| pane.eq(&PaneFocus::ReqUri));
 
-        let block_border = match (is_focused, is_selected) {
-            (true, false) => Style::default().fg(self.colors.bright.blue),
-            (true, true) => Style::default().fg(self.colors.normal.red),
-            (false, _) => Style::default().fg(self.colors.bright.black),
-        };
+        let block_border = self.colors.pane_border(is_focused, is_selected);
 
         let uri = self
             .collection_store
@@ -76,9 +157,15 @@ impl<'a> Renderable for RequestUri<'a> {
             .as_ref()
             .map(|req| req.read().unwrap().uri.to_string())
             .unwrap_or_default();
+        let variables = self.collection_store.borrow().get_variables();
+        let uri = highlight_placeholders(
+            Line::from(uri.fg(self.colors.normal.white)),
+            &variables,
+            self.colors,
+        );
 
         frame.render_widget(
-            Paragraph::new(uri).fg(self.colors.normal.white).block(
+            Paragraph::new(uri).block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(block_border)
@@ -90,6 +177,10 @@ impl<'a> Renderable for RequestUri<'a> {
             size,
         );
 
+        if is_selected && !self.suggestions.is_empty() {
+            self.draw_suggestions(frame, size);
+        }
+
         Ok(())
     }
 }
@@ -113,8 +204,25 @@ impl Eventful for RequestUri<'_> {
             return Ok(Some(RequestUriEvent::Quit));
         }
 
+        if let (KeyCode::Char('g'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            self.global_suggestions = !self.global_suggestions;
+            self.refresh_suggestions();
+            return Ok(None);
+        }
+
         match key_event.code {
+            // the first esc only dismisses the suggestions, leaving the uri
+            // as it was typed
+            KeyCode::Esc if !self.suggestions.is_empty() => self.suggestions.clear(),
             KeyCode::Esc => return Ok(Some(RequestUriEvent::RemoveSelection)),
+            KeyCode::Tab => self.accept_suggestion(),
+            KeyCode::Down if !self.suggestions.is_empty() => {
+                self.selected_suggestion = self
+                    .selected_suggestion
+                    .add(1)
+                    .min(self.suggestions.len().sub(1));
+            }
+            KeyCode::Up => self.selected_suggestion = self.selected_suggestion.saturating_sub(1),
             KeyCode::Char(c) => {
                 if let Some(req) = self
                     .collection_store
@@ -124,6 +232,7 @@ impl Eventful for RequestUri<'_> {
                 {
                     req.write().unwrap().uri.push(c);
                 }
+                self.refresh_suggestions();
             }
             KeyCode::Backspace => {
                 if let Some(req) = self
@@ -134,8 +243,10 @@ impl Eventful for RequestUri<'_> {
                 {
                     req.write().unwrap().uri.pop();
                 }
+                self.refresh_suggestions();
             }
             KeyCode::Enter => {
+                self.suggestions.clear();
                 let mut store = self.collection_store.borrow_mut();
                 if store
                     .get_selected_request()
@@ -151,4 +262,73 @@ impl Eventful for RequestUri<'_> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if let Some(req) = self.collection_store.borrow().get_selected_request() {
+            req.write()
+                .unwrap()
+                .uri
+                .push_str(&input::single_line(&text));
+        }
+        self.refresh_suggestions();
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hac_core::collection::Collection;
+
+    fn make_store() -> Rc<RefCell<CollectionStore>> {
+        let collection: Collection = serde_json::from_str(
+            r#"{
+                "info": { "name": "users" },
+                "requests": [
+                    { "id": "a", "method": "GET", "name": "list", "uri": "/users" },
+                    { "id": "b", "method": "GET", "name": "one", "uri": "/users/1" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        store.select_request("a");
+        store.dispatch(CollectionStoreAction::SetSelectedPane(Some(
+            PaneFocus::ReqUri,
+        )));
+        Rc::new(RefCell::new(store))
+    }
+
+    fn press(uri: &mut RequestUri, code: KeyCode) -> Option<RequestUriEvent> {
+        uri.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_completing_and_dismissing_suggestions() {
+        let colors = hac_colors::Colors::default();
+        let store = make_store();
+        store.borrow_mut().record_url("/users?page=2&token=abc");
+        let mut uri = RequestUri::new(&colors, store.clone(), Rect::default());
+
+        press(&mut uri, KeyCode::Char('?'));
+        assert_eq!(uri.suggestions, vec!["/users?page=2"]);
+
+        // esc leaves what was typed alone, and only then deselects the uri
+        assert!(press(&mut uri, KeyCode::Esc).is_none());
+        assert!(uri.suggestions.is_empty());
+        assert_eq!(uri.uri().unwrap(), "/users?");
+        assert!(matches!(
+            press(&mut uri, KeyCode::Esc),
+            Some(RequestUriEvent::RemoveSelection)
+        ));
+
+        press(&mut uri, KeyCode::Backspace);
+        assert_eq!(uri.suggestions, vec!["/users?page=2", "/users/1"]);
+        press(&mut uri, KeyCode::Down);
+        press(&mut uri, KeyCode::Tab);
+        assert_eq!(uri.uri().unwrap(), "/users/1");
+        assert!(uri.suggestions.is_empty());
+    }
 }
@@ -0,0 +1,320 @@
+use hac_core::net::request_manager::Response;
+use hac_core::text_object::TextObject;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+/// how many responses we keep for each request
+pub const MAX_ENTRIES: usize = 5;
+/// how many runs of each request are remembered for the timing sparkline,
+/// runs are tiny so we keep a lot more of them than responses
+pub const MAX_RUNS: usize = 30;
+/// only the newest response is kept whole, bodies of older ones are cut to
+/// this size so a session full of big responses stays bounded
+pub const MAX_STORED_BODY: usize = 256 * 1024;
+
+const TAB_NUMBERS: [&str; MAX_ENTRIES] = ["①", "②", "③", "④", "⑤"];
+
+/// what the response viewer needs to render a tab of the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryTab {
+    pub label: String,
+    pub is_error: bool,
+}
+
+/// when a request was sent and how long it took
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    /// `None` when the request failed without a response, eg: the connection
+    /// was refused
+    pub duration: Option<Duration>,
+    pub at: SystemTime,
+    /// whether the response came through a connection kept alive from an
+    /// earlier request, `None` when it can't be told
+    pub reused: Option<bool>,
+}
+
+/// min, median and max of the durations of the successful runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunStats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl RunStats {
+    pub fn new(runs: &[Run]) -> Option<Self> {
+        let mut durations = runs
+            .iter()
+            .filter_map(|run| run.duration)
+            .collect::<Vec<_>>();
+        durations.sort();
+
+        Some(RunStats {
+            min: *durations.first()?,
+            median: durations[durations.len() / 2],
+            max: *durations.last()?,
+        })
+    }
+}
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// one bar for every run, scaled between the fastest and the slowest run.
+/// Failed runs have no bar, so they can be drawn as a gap
+pub fn sparkline(runs: &[Run]) -> Vec<Option<char>> {
+    let Some(stats) = RunStats::new(runs) else {
+        return vec![None; runs.len()];
+    };
+    let range = stats.max.saturating_sub(stats.min).as_secs_f64();
+
+    runs.iter()
+        .map(|run| {
+            let duration = run.duration?;
+            let level = match range {
+                range if range.gt(&0.0) => {
+                    let ratio = duration.saturating_sub(stats.min).as_secs_f64() / range;
+                    (ratio * (BARS.len() - 1) as f64).round() as usize
+                }
+                _ => 0,
+            };
+            Some(BARS[level.min(BARS.len() - 1)])
+        })
+        .collect()
+}
+
+/// the last few responses of a single request, newest first. Responses only
+/// live in memory and are gone once the application closes
+#[derive(Debug, Default)]
+pub struct ResponseHistory {
+    entries: VecDeque<Rc<RefCell<Response>>>,
+    selected: usize,
+    /// oldest first, outlives the responses themselves
+    runs: VecDeque<Run>,
+}
+
+impl ResponseHistory {
+    /// adds a new response to the history and selects it, evicting the oldest
+    /// one when the history is full
+    pub fn push(&mut self, response: Response) {
+        if let Some(previous) = self.entries.front() {
+            trim_body(&mut previous.borrow_mut());
+        }
+
+        self.runs.push_back(Run {
+            duration: (!response.is_error).then_some(response.duration),
+            at: SystemTime::now(),
+            reused: response.connection_reused,
+        });
+        if self.runs.len().gt(&MAX_RUNS) {
+            self.runs.pop_front();
+        }
+
+        self.entries.push_front(Rc::new(RefCell::new(response)));
+        self.entries.truncate(MAX_ENTRIES);
+        self.selected = 0;
+    }
+
+    pub fn runs(&self) -> Vec<Run> {
+        self.runs.iter().copied().collect()
+    }
+
+    pub fn selected(&self) -> Option<Rc<RefCell<Response>>> {
+        self.entries.get(self.selected).cloned()
+    }
+
+    pub fn selected_idx(&self) -> usize {
+        self.selected
+    }
+
+    /// selects the response at `idx`, out of bounds indexes are ignored
+    pub fn select(&mut self, idx: usize) {
+        if idx.lt(&self.entries.len()) {
+            self.selected = idx;
+        }
+    }
+
+    pub fn tabs(&self) -> Vec<HistoryTab> {
+        self.entries
+            .iter()
+            .zip(TAB_NUMBERS)
+            .map(|(response, number)| {
+                let response = response.borrow();
+                let status = match response.status {
+                    Some(status) => status.as_u16().to_string(),
+                    None => "ERR".into(),
+                };
+                HistoryTab {
+                    label: format!("{} {} {}ms", number, status, response.duration.as_millis()),
+                    is_error: response.is_error || response.status.is_some_and(|s| !s.is_success()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn trim_body(response: &mut Response) {
+    if let Some(body) = response.body.as_mut() {
+        truncate_at_char_boundary(body, MAX_STORED_BODY);
+    }
+
+    if let Some(pretty_body) = response.pretty_body.as_ref() {
+        let mut pretty_body = pretty_body.to_string();
+        if pretty_body.len().gt(&MAX_STORED_BODY) {
+            truncate_at_char_boundary(&mut pretty_body, MAX_STORED_BODY);
+            response.pretty_body = Some(TextObject::from(&pretty_body));
+        }
+    }
+
+    for bytes in [response.binary_body.as_mut(), response.raw_body.as_mut()]
+        .into_iter()
+        .flatten()
+    {
+        bytes.truncate(MAX_STORED_BODY);
+        bytes.shrink_to_fit();
+    }
+}
+
+fn truncate_at_char_boundary(value: &mut String, max: usize) {
+    if value.len().le(&max) {
+        return;
+    }
+
+    let end = (0..=max)
+        .rev()
+        .find(|idx| value.is_char_boundary(*idx))
+        .unwrap_or_default();
+    value.truncate(end);
+    value.shrink_to_fit();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_response;
+    use std::time::Instant;
+
+    /// the status doubles as the duration, so every response tells apart
+    fn response_with_body(status: u16, body: &str) -> Response {
+        Response {
+            duration: Duration::from_millis(status as u64),
+            body: Some(body.to_string()),
+            ..make_response(status)
+        }
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_newest_first() {
+        let mut history = ResponseHistory::default();
+        for status in [200, 201, 202, 203, 204, 500] {
+            history.push(response_with_body(status, "{}"));
+        }
+
+        let tabs = history.tabs();
+        assert_eq!(tabs.len(), MAX_ENTRIES);
+        assert_eq!(
+            tabs[0],
+            HistoryTab {
+                label: "① 500 500ms".into(),
+                is_error: true
+            }
+        );
+        assert_eq!(tabs[4].label, "⑤ 201 201ms");
+    }
+
+    #[test]
+    fn test_select_ignores_out_of_bounds() {
+        let mut history = ResponseHistory::default();
+        history.push(response_with_body(200, "first"));
+        history.push(response_with_body(200, "second"));
+
+        history.select(1);
+        assert_eq!(
+            history.selected().unwrap().borrow().body.as_deref(),
+            Some("first")
+        );
+
+        history.select(4);
+        assert_eq!(history.selected_idx(), 1);
+
+        history.push(response_with_body(200, "third"));
+        assert_eq!(history.selected_idx(), 0);
+    }
+
+    #[test]
+    fn test_older_bodies_are_trimmed() {
+        let mut history = ResponseHistory::default();
+        let big = "é".repeat(MAX_STORED_BODY);
+
+        history.push(response_with_body(200, &big));
+        assert_eq!(
+            history
+                .selected()
+                .unwrap()
+                .borrow()
+                .body
+                .as_ref()
+                .unwrap()
+                .len(),
+            big.len()
+        );
+
+        history.push(response_with_body(200, "{}"));
+        history.select(1);
+        let body = history.selected().unwrap().borrow().body.clone().unwrap();
+        assert!(body.len().le(&MAX_STORED_BODY));
+        assert!(body.chars().all(|c| c.eq(&'é')));
+    }
+
+    #[test]
+    fn test_runs_outlive_responses_and_keep_failures() {
+        let mut history = ResponseHistory::default();
+        history.push(Response::error("refused".into(), Instant::now()));
+        for status in 200..200 + MAX_RUNS as u16 {
+            history.push(response_with_body(status, "{}"));
+        }
+
+        let runs = history.runs();
+        assert_eq!(runs.len(), MAX_RUNS);
+        assert_eq!(runs[0].duration, Some(Duration::from_millis(200)));
+        assert!(runs.windows(2).all(|pair| pair[0].at.le(&pair[1].at)));
+
+        history.push(Response::error("refused".into(), Instant::now()));
+        assert_eq!(history.runs().last().unwrap().duration, None);
+    }
+
+    #[test]
+    fn test_sparkline_scales_and_skips_failures() {
+        let run = |millis: Option<u64>| Run {
+            duration: millis.map(Duration::from_millis),
+            at: SystemTime::UNIX_EPOCH,
+            reused: None,
+        };
+        let runs = [
+            run(Some(10)),
+            run(None),
+            run(Some(80)),
+            run(Some(45)),
+            run(Some(20)),
+        ];
+
+        assert_eq!(
+            sparkline(&runs),
+            vec![Some('▁'), None, Some('█'), Some('▅'), Some('▂')]
+        );
+        assert_eq!(
+            RunStats::new(&runs),
+            Some(RunStats {
+                min: Duration::from_millis(10),
+                median: Duration::from_millis(45),
+                max: Duration::from_millis(80),
+            })
+        );
+
+        assert_eq!(sparkline(&[run(Some(5)), run(Some(5))]), vec![Some('▁'); 2]);
+        assert_eq!(sparkline(&[run(None)]), vec![None]);
+        assert_eq!(RunStats::new(&[run(None)]), None);
+    }
+}
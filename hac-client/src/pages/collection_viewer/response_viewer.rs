@@ -1,23 +1,41 @@
-use hac_core::net::request_manager::Response;
+use hac_core::assertions::json_path::Segment;
+use hac_core::assertions::{self, AssertionResult};
+use hac_core::collection::types::{Capture, CaptureSource, HttpVersion, PreviewLanguage};
+use hac_core::net::certificate::{self, Validity};
+use hac_core::net::checksum::{BodyHashes, HashSource};
+use hac_core::net::download::{self, format_size, PREVIEW_SIZE};
+use hac_core::net::image::{self, ImageError, ImageInfo, Pixels};
+use hac_core::net::request_manager::{version_name, Response};
+use hac_core::net::uri;
 use hac_core::syntax::highlighter::HIGHLIGHTER;
+use hac_core::syntax::json_tree::{self, JsonNode};
 
 use crate::ascii::{BIG_ERROR_ARTS, LOGO_ASCII, SMALL_ERROR_ARTS};
+use crate::clipboard;
+use crate::graphics::{self, Graphics};
+use crate::hyperlinks;
 use crate::pages::collection_viewer::collection_viewer::PaneFocus;
+use crate::pages::collection_viewer::response_history::{self, HistoryTab, Run, RunStats};
+use crate::pages::scrollbar::{self, Scrollbar};
 use crate::pages::under_construction::UnderConstruction;
 use crate::pages::{spinner::Spinner, Eventful, Renderable};
-use crate::utils::build_syntax_highlighted_lines;
+use crate::utils::{
+    blend_colors_multiply, build_syntax_highlighted_lines, restyle_ranges, slice_line, wrap_line,
+};
 
 use std::cell::RefCell;
 use std::iter;
-use std::ops::{Add, Sub};
+use std::ops::{Add, Range, RangeInclusive, Rem, Sub};
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use rand::Rng;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Style, Stylize};
+use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Scrollbar};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph};
 use ratatui::widgets::{ScrollbarOrientation, ScrollbarState, Tabs};
 use ratatui::Frame;
 use tree_sitter::Tree;
@@ -27,6 +45,26 @@ use super::collection_store::CollectionStore;
 #[derive(Debug)]
 pub enum ResponseViewerEvent {
     RemoveSelection,
+    /// user asked to edit the assertions of the selected request
+    EditAssertions,
+    /// assertions were made from the response and appended to the request,
+    /// holds the notes of the new ones by their index, for the user to
+    /// review them
+    ReviewAssertions(Vec<(usize, Option<String>)>),
+    /// user wants to see another response from the history of the selected
+    /// request, holds the index of the tab
+    SelectResponse(usize),
+    /// something happened that is worth a short message on the status bar
+    Notify(String),
+    /// a setting of the selected request was toggled, which has to be saved
+    /// with the collection. Holds a message telling what it does now
+    RequestChanged(String),
+    /// user wants to see the timings of the past runs of the selected request
+    ShowRuns,
+    /// user picked a link on the preview, holding its url
+    OpenLink(String),
+    /// user wants to save a binary body, holds the path suggested for it
+    SaveBody(String),
     Quit,
 }
 
@@ -36,6 +74,81 @@ pub enum ResViewerTabs {
     Raw,
     Cookies,
     Headers,
+    Assertions,
+    Connection,
+    Bytes,
+}
+
+/// the tab and scroll positions of the viewer, kept for each request so
+/// switching back to one shows it the way it was left
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseViewState {
+    active_tab: ResViewerTabs,
+    raw_scroll: usize,
+    headers_scroll_y: usize,
+    headers_scroll_x: usize,
+    pretty_scroll: usize,
+    body_scroll_x: usize,
+    assertions_scroll: usize,
+    connection_scroll: usize,
+    wrap: bool,
+}
+
+/// where a hashing task leaves its result for the viewer to pick up
+type HashSlot = Arc<Mutex<Option<Result<BodyHashes, String>>>>;
+
+/// a url on the preview, by the line it is on and its byte range there
+#[derive(Debug, Clone, PartialEq)]
+struct BodyLink {
+    row: usize,
+    range: Range<usize>,
+    url: String,
+}
+
+/// a region of the preview being selected to be copied. Positions are rows
+/// and columns of the body, not of the screen, so it stays in place while
+/// scrolling
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Selection {
+    anchor: (usize, usize),
+    cursor: (usize, usize),
+    /// whole lines are selected instead of a rectangle
+    linewise: bool,
+}
+
+impl Selection {
+    fn rows(&self) -> RangeInclusive<usize> {
+        usize::min(self.anchor.0, self.cursor.0)..=usize::max(self.anchor.0, self.cursor.0)
+    }
+
+    /// the selected part of `text`, which is the line on one of the
+    /// selected rows, as a byte range
+    fn byte_range(&self, text: &str) -> Range<usize> {
+        if self.linewise {
+            return 0..text.len();
+        }
+
+        let first = usize::min(self.anchor.1, self.cursor.1);
+        let last = usize::max(self.anchor.1, self.cursor.1);
+        let byte_at = |col: usize| {
+            text.char_indices()
+                .nth(col)
+                .map(|(byte, _)| byte)
+                .unwrap_or(text.len())
+        };
+        byte_at(first)..byte_at(last.add(1))
+    }
+
+    /// what is copied, the selected part of every selected row
+    fn text(&self, lines: &[String]) -> String {
+        lines
+            .iter()
+            .skip(*self.rows().start())
+            .take(self.rows().count())
+            .map(|line| &line[self.byte_range(line)])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 impl ResViewerTabs {
@@ -44,16 +157,22 @@ impl ResViewerTabs {
             Self::Preview => ResViewerTabs::Raw,
             Self::Raw => ResViewerTabs::Headers,
             Self::Headers => ResViewerTabs::Cookies,
-            Self::Cookies => ResViewerTabs::Preview,
+            Self::Cookies => ResViewerTabs::Assertions,
+            Self::Assertions => ResViewerTabs::Connection,
+            Self::Connection => ResViewerTabs::Bytes,
+            Self::Bytes => ResViewerTabs::Preview,
         }
     }
 
     pub fn prev(tab: &ResViewerTabs) -> Self {
         match tab {
-            Self::Preview => ResViewerTabs::Cookies,
+            Self::Preview => ResViewerTabs::Bytes,
             Self::Raw => ResViewerTabs::Preview,
             Self::Headers => ResViewerTabs::Raw,
             Self::Cookies => ResViewerTabs::Headers,
+            Self::Assertions => ResViewerTabs::Cookies,
+            Self::Connection => ResViewerTabs::Assertions,
+            Self::Bytes => ResViewerTabs::Connection,
         }
     }
 }
@@ -65,6 +184,9 @@ impl From<ResViewerTabs> for usize {
             ResViewerTabs::Raw => 1,
             ResViewerTabs::Headers => 2,
             ResViewerTabs::Cookies => 3,
+            ResViewerTabs::Assertions => 4,
+            ResViewerTabs::Connection => 5,
+            ResViewerTabs::Bytes => 6,
         }
     }
 }
@@ -72,6 +194,7 @@ impl From<ResViewerTabs> for usize {
 #[derive(Debug, Clone)]
 pub struct ResViewerLayout {
     tabs_pane: Rect,
+    history_pane: Rect,
     content_pane: Rect,
     summary_pane: Rect,
 }
@@ -82,23 +205,73 @@ struct PreviewLayout {
     scrollbar: Rect,
 }
 
+/// characters from the faintest to the densest, drawing images when there
+/// are no colors
+const ASCII_RAMP: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// an image body, decoded once as it arrives
+#[derive(Debug)]
+struct ImagePreview {
+    info: ImageInfo,
+    size: u64,
+    pixels: Result<Rc<Pixels>, ImageError>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ResponseViewer<'a> {
     colors: &'a hac_colors::Colors,
     response: Option<Rc<RefCell<Response>>>,
     tree: Option<Tree>,
+    /// the text `tree` was parsed from, to tell where its nodes are
+    tree_source: String,
     lines: Vec<Line<'static>>,
+    /// set when the body is an image, its lines depend on the pane size
+    image: Option<Rc<ImagePreview>>,
+    /// how the terminal draws images, when it can do better than half blocks
+    graphics: Option<Graphics>,
     error_lines: Option<Vec<Line<'static>>>,
     empty_lines: Vec<Line<'static>>,
     preview_layout: PreviewLayout,
     layout: ResViewerLayout,
     collection_store: Rc<RefCell<CollectionStore>>,
+    assertion_results: Vec<AssertionResult>,
+    history: Vec<HistoryTab>,
+    selected_history: usize,
+    runs: Vec<Run>,
+    /// the response whose body is being hashed, along with where the result
+    /// will show up
+    hashing: Option<(Rc<RefCell<Response>>, HashSlot)>,
+    selection: Option<Selection>,
+    /// the urls on the preview, in the order they show up
+    links: Vec<BodyLink>,
+    /// the link focused while moving between them with tab, set while on
+    /// link mode
+    link_mode: Option<usize>,
+    /// urls on the preview are drawn as terminal hyperlinks when set
+    hyperlinks: bool,
+    /// how far through the current tab the view is scrolled, eg: `(42%)`,
+    /// set while drawing the tab so the summary can show it
+    scroll_label: Option<String>,
+    /// language the body is displayed as, and whether the user picked it
+    display: Option<(PreviewLanguage, bool)>,
+    /// the body is json drawn as plain text as its grammar wasn't loaded
+    /// yet, it is highlighted on the first draw after it is
+    highlight_pending: bool,
+    /// long lines of the preview and raw tabs are soft wrapped when set,
+    /// otherwise they are cut at the pane and scroll sideways
+    wrap: bool,
+    /// set after `y` on the preview, the next key tells what to copy
+    pending_yank: bool,
 
     active_tab: ResViewerTabs,
     raw_scroll: usize,
     headers_scroll_y: usize,
     headers_scroll_x: usize,
     pretty_scroll: usize,
+    /// sideways scroll of the preview and raw tabs while not wrapping
+    body_scroll_x: usize,
+    assertions_scroll: usize,
+    connection_scroll: usize,
 }
 
 impl<'a> ResponseViewer<'a> {
@@ -108,15 +281,17 @@ impl<'a> ResponseViewer<'a> {
         response: Option<Rc<RefCell<Response>>>,
         size: Rect,
     ) -> Self {
-        let tree = response.as_ref().and_then(|response| {
-            if let Some(ref pretty_body) = response.borrow().pretty_body {
-                let pretty_body = pretty_body.to_string();
-                let mut highlighter = HIGHLIGHTER.write().unwrap();
-                highlighter.parse(&pretty_body)
-            } else {
-                None
-            }
-        });
+        let tree_source = response
+            .as_ref()
+            .and_then(|response| {
+                let response = response.borrow();
+                response.pretty_body.as_ref().map(|body| body.to_string())
+            })
+            .unwrap_or_default();
+        let tree = match tree_source.is_empty() {
+            true => None,
+            false => HIGHLIGHTER.write().unwrap().parse(&tree_source),
+        };
 
         let layout = build_layout(size);
         let preview_layout = build_preview_layout(layout.content_pane);
@@ -127,7 +302,10 @@ impl<'a> ResponseViewer<'a> {
             colors,
             response,
             tree,
+            tree_source,
             lines: vec![],
+            image: None,
+            graphics: graphics::graphics(),
             error_lines: None,
             empty_lines,
             preview_layout,
@@ -137,33 +315,184 @@ impl<'a> ResponseViewer<'a> {
             headers_scroll_y: 0,
             headers_scroll_x: 0,
             pretty_scroll: 0,
+            body_scroll_x: 0,
+            assertions_scroll: 0,
+            connection_scroll: 0,
+            assertion_results: vec![],
+            history: vec![],
+            selected_history: 0,
+            runs: vec![],
+            hashing: None,
+            selection: None,
+            links: vec![],
+            link_mode: None,
+            hyperlinks: hyperlinks::supported(),
+            scroll_label: None,
+            display: None,
+            highlight_pending: false,
+            wrap: true,
+            pending_yank: false,
             collection_store,
         }
     }
 
+    pub fn wrap(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    pub fn view_state(&self) -> ResponseViewState {
+        ResponseViewState {
+            active_tab: self.active_tab.clone(),
+            raw_scroll: self.raw_scroll,
+            headers_scroll_y: self.headers_scroll_y,
+            headers_scroll_x: self.headers_scroll_x,
+            pretty_scroll: self.pretty_scroll,
+            body_scroll_x: self.body_scroll_x,
+            assertions_scroll: self.assertions_scroll,
+            connection_scroll: self.connection_scroll,
+            wrap: self.wrap,
+        }
+    }
+
+    /// scroll positions past the end of a response that got shorter are
+    /// clamped once the tab is drawn
+    pub fn restore_view_state(&mut self, state: ResponseViewState) {
+        self.active_tab = state.active_tab;
+        self.raw_scroll = state.raw_scroll;
+        self.headers_scroll_y = state.headers_scroll_y;
+        self.headers_scroll_x = state.headers_scroll_x;
+        self.pretty_scroll = state.pretty_scroll;
+        self.body_scroll_x = state.body_scroll_x;
+        self.assertions_scroll = state.assertions_scroll;
+        self.connection_scroll = state.connection_scroll;
+        self.wrap = state.wrap;
+    }
+
+    /// the language the body of `response` is displayed as, and whether it
+    /// was picked by the user instead of told from the response
+    fn display_language(&self, response: &Response) -> Option<(PreviewLanguage, bool)> {
+        let detected = detect_language(response)?;
+        let selected = self.collection_store.borrow().get_selected_request();
+        match selected.and_then(|request| request.read().unwrap().preview_language) {
+            Some(language) => Some((language, true)),
+            None => Some((detected, false)),
+        }
+    }
+
+    /// displays the body as the next language, going back to the detected
+    /// one clears the override. It is kept on the request, so every later
+    /// response is displayed the same way
+    fn cycle_display_language(&mut self) -> Option<String> {
+        let response = self.response.clone()?;
+        let (current, _) = self.display?;
+        let detected = detect_language(&response.borrow())?;
+        let request = self.collection_store.borrow().get_selected_request()?;
+
+        let next = current.next();
+        request.write().unwrap().preview_language = next.ne(&detected).then_some(next);
+        self.update(Some(response));
+        self.pretty_scroll = 0;
+
+        Some(match next.eq(&detected) {
+            true => format!("displaying the body as {next}, as detected"),
+            false => format!("displaying the body as {next}"),
+        })
+    }
+
     pub fn resize(&mut self, new_size: Rect) {
         self.layout = build_layout(new_size);
         self.preview_layout = build_preview_layout(self.layout.content_pane);
+        if let Some(preview) = self.image.clone() {
+            self.lines = self.image_lines(&preview);
+        }
     }
 
     pub fn update(&mut self, response: Option<Rc<RefCell<Response>>>) {
+        self.display = response
+            .as_ref()
+            .and_then(|res| self.display_language(&res.borrow()));
         let body_str = response
             .as_ref()
             .and_then(|res| {
-                res.borrow()
-                    .pretty_body
-                    .as_ref()
-                    .map(|body| body.to_string())
+                let res = res.borrow();
+                match self.display {
+                    // only json is pretty printed, anything else is shown
+                    // as it was received
+                    Some((PreviewLanguage::Json, _)) => res
+                        .pretty_body
+                        .as_ref()
+                        .map(|body| body.to_string())
+                        .filter(|body| !body.is_empty())
+                        .or(res.body.clone()),
+                    _ => res.body.clone(),
+                }
             })
             .unwrap_or_default();
 
+        let binary_size = response.as_ref().and_then(|res| {
+            res.borrow()
+                .binary_body
+                .as_ref()
+                .map(|bytes| bytes.len() as u64)
+        });
+
+        self.image = response.as_ref().and_then(|res| {
+            let res = res.borrow();
+            let bytes = res.binary_body.as_ref()?;
+            let info = image::probe(bytes)?;
+            // a spilled body only has its start in memory, never the whole image
+            let pixels = match res.spilled.as_ref() {
+                Some(_) => Err(ImageError::TooBig {
+                    width: info.width,
+                    height: info.height,
+                }),
+                None => image::decode(bytes).map(Rc::new),
+            };
+            let size = res
+                .spilled
+                .as_ref()
+                .map_or(bytes.len() as u64, |spilled| spilled.size);
+            Some(Rc::new(ImagePreview { info, size, pixels }))
+        });
+
         if body_str.len().gt(&0) {
-            self.tree = HIGHLIGHTER.write().unwrap().parse(&body_str);
+            // json is the only grammar we have, other languages are plain
+            self.tree = match self.display {
+                Some((PreviewLanguage::Json, _)) => HIGHLIGHTER.write().unwrap().parse(&body_str),
+                _ => None,
+            };
             self.lines = build_syntax_highlighted_lines(&body_str, self.tree.as_ref(), self.colors);
+        } else if let Some(preview) = self.image.clone() {
+            self.tree = None;
+            self.lines = self.image_lines(&preview);
+        } else if let Some(binary_size) = binary_size {
+            self.tree = None;
+            self.lines = vec![
+                Line::from(format!("Binary body ({})", format_size(binary_size))).centered(),
+                Line::from("see the raw tab for a hex view".fg(self.colors.bright.black))
+                    .centered(),
+            ];
         } else {
             self.tree = None;
             self.lines = vec![];
         }
+        self.links = match body_str.is_empty() {
+            true => vec![],
+            false => find_links(&self.lines),
+        };
+        self.link_mode = None;
+        self.highlight_pending = !body_str.is_empty()
+            && self.tree.is_none()
+            && matches!(self.display, Some((PreviewLanguage::Json, _)))
+            && !HIGHLIGHTER.read().unwrap().is_loaded(PreviewLanguage::Json);
+        self.tree_source = match self.tree {
+            Some(_) => body_str,
+            None => String::default(),
+        };
 
         if let Some(res) = response.as_ref() {
             let cause: String = res
@@ -196,7 +525,64 @@ impl<'a> ResponseViewer<'a> {
         };
 
         self.empty_lines = make_empty_ascii_art(self.colors);
+        self.selection = None;
         self.response = response;
+        self.evaluate_assertions();
+    }
+
+    /// sets the tabs of the response history of the selected request, the
+    /// response itself is still set through `update`
+    pub fn set_history(&mut self, history: Vec<HistoryTab>, selected: usize) {
+        self.history = history;
+        self.selected_history = selected;
+    }
+
+    /// sets the past runs of the selected request, oldest first
+    pub fn set_runs(&mut self, runs: Vec<Run>) {
+        self.runs = runs;
+    }
+
+    /// a sparkline of the past runs followed by their min/median/max, failed
+    /// runs show up as a red `×`. Nothing is shown until there is something
+    /// to compare
+    fn run_trend(&self) -> Vec<Span<'static>> {
+        let Some(stats) = RunStats::new(&self.runs).filter(|_| self.runs.len().gt(&1)) else {
+            return vec![];
+        };
+
+        let mut pieces = response_history::sparkline(&self.runs)
+            .into_iter()
+            .map(|bar| match bar {
+                Some(bar) => bar.to_string().fg(self.colors.normal.blue),
+                None => "×".fg(self.colors.normal.red),
+            })
+            .collect::<Vec<_>>();
+        pieces.push(
+            format!(
+                " {}/{}/{}ms",
+                stats.min.as_millis(),
+                stats.median.as_millis(),
+                stats.max.as_millis()
+            )
+            .fg(self.colors.bright.black),
+        );
+        pieces
+    }
+
+    /// checks the assertions of the selected request against the current
+    /// response, this has to be called again whenever assertions change
+    pub fn evaluate_assertions(&mut self) {
+        let assertions = self
+            .collection_store
+            .borrow()
+            .get_selected_request()
+            .and_then(|req| req.read().unwrap().assertions.clone())
+            .unwrap_or_default();
+
+        self.assertion_results = match self.response.as_ref() {
+            Some(response) => assertions::evaluate_all(&assertions, &response.borrow()),
+            None => vec![],
+        };
     }
 
     fn draw_container(&self, size: Rect, frame: &mut Frame) {
@@ -211,13 +597,9 @@ impl<'a> ResponseViewer<'a> {
             .get_selected_pane()
             .is_some_and(|pane| pane.eq(&PaneFocus::Preview));
 
-        let block_border = match (is_focused, is_selected) {
-            (true, false) => Style::default().fg(self.colors.bright.blue),
-            (true, true) => Style::default().fg(self.colors.normal.red),
-            (_, _) => Style::default().fg(self.colors.bright.black),
-        };
+        let block_border = self.colors.pane_border(is_focused, is_selected);
 
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .title(vec![
                 "P".fg(self.colors.normal.red).bold(),
@@ -225,21 +607,72 @@ impl<'a> ResponseViewer<'a> {
             ])
             .border_style(block_border);
 
+        let node = match self.active_tab {
+            ResViewerTabs::Preview => self.inspected_node(),
+            _ => None,
+        };
+        if let Some(node) = node {
+            let breadcrumb = breadcrumb(&node.path(), usize::from(size.width.saturating_sub(4)));
+            block = block.title_bottom(
+                Line::from(format!(" {breadcrumb} "))
+                    .fg(self.colors.normal.white)
+                    .right_aligned(),
+            );
+        }
+
         frame.render_widget(block, size);
     }
 
     fn draw_tabs(&self, frame: &mut Frame, size: Rect) {
-        let tabs = Tabs::new(["Pretty", "Raw", "Headers", "Cookies"])
-            .style(Style::default().fg(self.colors.bright.black))
-            .select(self.active_tab.clone().into())
-            .highlight_style(
-                Style::default()
-                    .fg(self.colors.normal.white)
-                    .bg(self.colors.normal.blue),
-            );
+        let tabs = Tabs::new([
+            "Pretty",
+            "Raw",
+            "Headers",
+            "Cookies",
+            "Assertions",
+            "Connection",
+            "Bytes",
+        ])
+        .style(Style::default().fg(self.colors.bright.black))
+        .select(self.active_tab.clone().into())
+        .highlight_style(
+            self.colors
+                .highlight(self.colors.normal.white, self.colors.normal.blue),
+        );
         frame.render_widget(tabs, size);
     }
 
+    fn draw_history(&self, frame: &mut Frame, size: Rect) {
+        // a single response has nothing to flip between
+        if self.history.len().lt(&2) {
+            return;
+        }
+
+        let tabs = self
+            .history
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, tab)| {
+                let color = match tab.is_error {
+                    true => self.colors.normal.red,
+                    false => self.colors.normal.green,
+                };
+                let label = format!(" {} ", tab.label);
+                let label = match idx.eq(&self.selected_history) {
+                    true => Span::styled(
+                        label,
+                        self.colors
+                            .highlight(self.colors.normal.white, self.colors.normal.blue),
+                    ),
+                    false => label.fg(color),
+                };
+                [label, " ".into()]
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_widget(Line::from(tabs), size);
+    }
+
     fn draw_spinner(&self, frame: &mut Frame) {
         let request_pane = self.preview_layout.content_pane;
         let center = request_pane.y.add(request_pane.height.div_ceil(2));
@@ -270,14 +703,16 @@ impl<'a> ResponseViewer<'a> {
             let center = request_pane
                 .y
                 .add(request_pane.height.div_ceil(2))
-                .sub(self.error_lines.as_ref().unwrap().len().div_ceil(2) as u16);
+                .saturating_sub(self.error_lines.as_ref().unwrap().len().div_ceil(2) as u16);
 
+            // messages taller than the pane are cut instead of drawn outside of it
             let size = Rect::new(
                 request_pane.x.add(1),
                 center,
                 request_pane.width,
                 self.error_lines.as_ref().unwrap().len() as u16,
-            );
+            )
+            .intersection(request_pane);
 
             frame.render_widget(
                 Paragraph::new(self.error_lines.clone().unwrap()).fg(self.colors.bright.black),
@@ -297,14 +732,15 @@ impl<'a> ResponseViewer<'a> {
         let center = request_pane
             .y
             .add(request_pane.height.div_ceil(2))
-            .sub(self.empty_lines.len().div_ceil(2) as u16);
+            .saturating_sub(self.empty_lines.len().div_ceil(2) as u16);
 
         let size = Rect::new(
             request_pane.x.add(1),
             center,
             request_pane.width,
             self.empty_lines.len() as u16,
-        );
+        )
+        .intersection(request_pane);
 
         frame.render_widget(
             Paragraph::new(self.empty_lines.clone())
@@ -337,6 +773,9 @@ impl<'a> ResponseViewer<'a> {
                 ResViewerTabs::Raw => self.draw_raw_response(frame, size),
                 ResViewerTabs::Headers => self.draw_response_headers(frame),
                 ResViewerTabs::Cookies => UnderConstruction::new(self.colors).draw(frame, size)?,
+                ResViewerTabs::Assertions => self.draw_assertions(frame, size),
+                ResViewerTabs::Connection => self.draw_connection(frame, size),
+                ResViewerTabs::Bytes => self.draw_bytes(frame),
             }
         }
 
@@ -348,7 +787,7 @@ impl<'a> ResponseViewer<'a> {
     }
 
     fn draw_response_headers(&mut self, frame: &mut Frame) {
-        if let Some(response) = self.response.as_ref() {
+        if let Some(response) = self.response.clone() {
             if let Some(headers) = response.borrow().headers.as_ref() {
                 let mut longest_line: usize = 0;
 
@@ -368,7 +807,7 @@ impl<'a> ResponseViewer<'a> {
                                 .skip(self.headers_scroll_x)
                                 .collect::<String>()
                                 .bold()
-                                .yellow(),
+                                .fg(self.colors.normal.yellow),
                         ));
                         lines.push(Line::from(
                             value
@@ -436,17 +875,14 @@ impl<'a> ResponseViewer<'a> {
 
     fn draw_raw_response(&mut self, frame: &mut Frame, size: Rect) {
         if let Some(response) = self.response.as_ref() {
-            let lines = if response.borrow().body.is_some() {
-                response
-                    .borrow()
-                    .body
-                    .as_ref()
-                    .unwrap()
-                    .chars()
+            let lines = if let Some(bytes) = response.borrow().binary_body.as_ref() {
+                hex_dump(bytes)
+                    .into_iter()
+                    .map(Line::from)
                     .collect::<Vec<_>>()
-                    // accounting for the scrollbar width when splitting the lines
-                    .chunks(size.width.saturating_sub(2).into())
-                    .map(|row| Line::from(row.iter().collect::<String>()))
+            } else if let Some(body) = response.borrow().body.as_ref() {
+                body.lines()
+                    .map(|line| Line::from(line.to_string()))
                     .collect::<Vec<_>>()
             } else {
                 vec![Line::from("No body").centered()]
@@ -463,33 +899,544 @@ impl<'a> ResponseViewer<'a> {
                 self.preview_layout.scrollbar,
             );
 
-            let lines_in_view = lines
-                .into_iter()
-                .skip(self.raw_scroll)
-                .chain(iter::repeat(Line::from("~".fg(self.colors.bright.black))))
-                .take(size.height.into())
-                .collect::<Vec<_>>();
+            let lines_in_view = self.fit_to_pane(&lines, self.raw_scroll, size.height.into());
 
             let raw_response = Paragraph::new(lines_in_view);
             frame.render_widget(raw_response, self.preview_layout.content_pane);
         }
     }
 
+    fn draw_assertions(&mut self, frame: &mut Frame, size: Rect) {
+        let lines = match self.assertion_results.is_empty() {
+            true => vec![
+                Line::from("No assertions").centered(),
+                Line::from("press e to add one".fg(self.colors.bright.black)).centered(),
+            ],
+            false => self
+                .assertion_results
+                .iter()
+                .flat_map(|result| match result.passed {
+                    true => vec![Line::from(vec![
+                        "PASS ".fg(self.colors.normal.green).bold(),
+                        result.assertion.to_string().into(),
+                    ])],
+                    false => vec![
+                        Line::from(vec![
+                            "FAIL ".fg(self.colors.normal.red).bold(),
+                            result.assertion.to_string().into(),
+                        ]),
+                        Line::from(
+                            format!("     got: {}", result.actual).fg(self.colors.bright.black),
+                        ),
+                    ],
+                })
+                .collect::<Vec<_>>(),
+        };
+
+        if self.assertions_scroll.ge(&lines.len().saturating_sub(1)) {
+            self.assertions_scroll = lines.len().saturating_sub(1);
+        }
+
+        self.draw_scrollbar(
+            lines.len(),
+            self.assertions_scroll,
+            frame,
+            self.preview_layout.scrollbar,
+        );
+
+        let lines_in_view = lines
+            .into_iter()
+            .skip(self.assertions_scroll)
+            .chain(iter::repeat(Line::from("~".fg(self.colors.bright.black))))
+            .take(size.height.into())
+            .collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(lines_in_view),
+            self.preview_layout.content_pane,
+        );
+    }
+
+    /// where a binary body is offered to be saved, next to where hac was
+    /// started from and named after the request url. Text bodies are copied
+    /// instead, so they have nothing to save
+    fn suggested_save_path(&self) -> Option<String> {
+        self.response.as_ref()?.borrow().binary_body.as_ref()?;
+        Some(format!(
+            "./{}",
+            download::file_name_for(&self.request_uri())
+        ))
+    }
+
+    fn request_uri(&self) -> String {
+        self.collection_store
+            .borrow()
+            .get_selected_request()
+            .map(|request| request.read().unwrap().uri.clone())
+            .unwrap_or_default()
+    }
+
+    /// saves a binary body to `path`, relative to where hac was started
+    /// from. Directories get the name taken from the url, and existing files
+    /// are never overwritten
+    pub fn save_body(&self, path: &str) -> Option<String> {
+        let response = self.response.as_ref()?.borrow();
+        let bytes = response.binary_body.as_ref()?;
+
+        let path = std::path::Path::new(path);
+        let (dir, name) = match path.file_name() {
+            Some(name) if !path.is_dir() => (
+                path.parent().unwrap_or(path).to_path_buf(),
+                name.to_string_lossy().to_string(),
+            ),
+            _ => (
+                path.to_path_buf(),
+                download::file_name_for(&self.request_uri()),
+            ),
+        };
+        let saved = std::env::current_dir()
+            .map(|cwd| cwd.join(dir))
+            .and_then(|dir| match response.spilled.as_ref() {
+                Some(spilled) => spilled.save_into(&dir, &name),
+                None => download::save_bytes_into(&dir, &name, bytes),
+            });
+        Some(match saved {
+            Ok(path) => format!("Saved the body to {}", path.display()),
+            Err(e) => format!("Failed to save the body: {e}"),
+        })
+    }
+
+    /// an image body scaled down to fit the pane. Each cell is a half block
+    /// showing two pixels, one over the other, or a character as dense as
+    /// the pixels are bright when there are no colors. Terminals with kitty
+    /// graphics or sixels get blank lines, the image is drawn over them
+    fn image_lines(&self, preview: &ImagePreview) -> Vec<Line<'static>> {
+        let info = preview.info;
+        let mut lines = vec![Line::from(format!(
+            "{} image, {}x{} ({})",
+            info.format,
+            info.width,
+            info.height,
+            format_size(preview.size)
+        ))
+        .centered()];
+
+        let pixels = match preview.pixels.as_ref() {
+            Ok(pixels) if pixels.width.gt(&0) && pixels.height.gt(&0) => pixels,
+            Ok(_) => return lines,
+            Err(e) => {
+                lines.push(Line::from(e.to_string().fg(self.colors.bright.black)).centered());
+                lines.push(
+                    Line::from("press s to save it, the raw tab has a hex view")
+                        .fg(self.colors.bright.black)
+                        .centered(),
+                );
+                return lines;
+            }
+        };
+
+        // the terminal draws the image over the blank lines once the frame
+        // is on the screen
+        if let Some((_, rows)) = self.graphic_cells(pixels) {
+            lines.extend(iter::repeat_n(Line::default(), rows.into()));
+            lines.push(
+                Line::from("press s to save it, the raw tab has a hex view")
+                    .fg(self.colors.bright.black)
+                    .centered(),
+            );
+            return lines;
+        }
+
+        // the gutter of wrapped lines, and the lines around the image
+        let pane = self.preview_layout.content_pane;
+        let columns = f64::from(pane.width.saturating_sub(2).max(1));
+        let rows = f64::from(pane.height.saturating_sub(2).max(1));
+        let scale = f64::min(
+            columns / f64::from(pixels.width),
+            rows * 2.0 / f64::from(pixels.height),
+        )
+        .min(1.0);
+        let width = ((f64::from(pixels.width) * scale) as u64).max(1);
+        let height = ((f64::from(pixels.height) * scale) as u64).max(1);
+        let at = |x: u64, y: u64| {
+            pixels.get(
+                (x * u64::from(pixels.width) / width) as u32,
+                (y * u64::from(pixels.height) / height) as u32,
+            )
+        };
+
+        for y in (0..height).step_by(2) {
+            let cells = (0..width)
+                .map(|x| self.image_cell(at(x, y), (y + 1).lt(&height).then(|| at(x, y + 1))))
+                .collect::<Vec<_>>();
+            lines.push(Line::from(cells));
+        }
+
+        lines.push(
+            Line::from("press s to save it, the raw tab has a hex view")
+                .fg(self.colors.bright.black)
+                .centered(),
+        );
+        lines
+    }
+
+    /// the columns and rows an image takes when the terminal draws it, scaled
+    /// down to fit the pane like the half blocks are
+    fn graphic_cells(&self, pixels: &Pixels) -> Option<(u16, u16)> {
+        let graphics = self.graphics?;
+        let pane = self.preview_layout.content_pane;
+        let columns = u32::from(pane.width.saturating_sub(2).max(1));
+        let rows = u32::from(pane.height.saturating_sub(2).max(1));
+        let scale = f64::min(
+            f64::from(columns * graphics.cell_width) / f64::from(pixels.width),
+            f64::from(rows * graphics.cell_height) / f64::from(pixels.height),
+        )
+        .min(1.0);
+        let width = (f64::from(pixels.width) * scale).max(1.0) as u32;
+        let height = (f64::from(pixels.height) * scale).max(1.0) as u32;
+        Some((
+            width.div_ceil(graphics.cell_width).min(columns) as u16,
+            height.div_ceil(graphics.cell_height).min(rows) as u16,
+        ))
+    }
+
+    /// hands the image to the terminal when it is in view, right under the
+    /// line telling its format. Popups would be drawn over by it
+    fn place_graphic(&self) {
+        let Some(Ok(pixels)) = self.image.as_ref().map(|preview| preview.pixels.as_ref()) else {
+            return;
+        };
+        let Some((columns, rows)) = self.graphic_cells(pixels) else {
+            return;
+        };
+        if self.pretty_scroll.ne(&0) || self.collection_store.borrow().has_overlay() {
+            return;
+        }
+
+        let pane = self.preview_layout.content_pane;
+        let area = Rect::new(
+            pane.x + (pane.width.saturating_sub(columns)) / 2,
+            pane.y + 1,
+            columns,
+            rows,
+        );
+        graphics::place(area, pixels.clone());
+    }
+
+    fn image_cell(&self, top: [u8; 4], bottom: Option<[u8; 4]>) -> Span<'static> {
+        if self.colors.monochrome {
+            // transparent pixels are as faint as the background
+            let brightness = |[r, g, b, a]: [u8; 4]| {
+                (0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b)) * f32::from(a)
+                    / 255.0
+            };
+            let brightness = match bottom {
+                Some(bottom) => (brightness(top) + brightness(bottom)) / 2.0,
+                None => brightness(top),
+            };
+            let idx = (brightness / 256.0 * ASCII_RAMP.len() as f32) as usize;
+            return Span::from(ASCII_RAMP[idx.min(ASCII_RAMP.len() - 1)].to_string());
+        }
+
+        let color = |[r, g, b, a]: [u8; 4]| {
+            blend_colors_multiply(
+                Color::Rgb(r, g, b),
+                self.colors.primary.background,
+                f32::from(a) / 255.0,
+            )
+        };
+        match bottom {
+            Some(bottom) => "▀".fg(color(top)).bg(color(bottom)),
+            None => "▀".fg(color(top)),
+        }
+    }
+
+    fn connection_lines(&self, response: &Response) -> Vec<Line<'static>> {
+        let field = |name: &str, value: String, color: Color| {
+            Line::from(vec![
+                format!("{:<13}", name).fg(self.colors.bright.black),
+                value.fg(color),
+            ])
+        };
+        let white = self.colors.normal.white;
+
+        let mut lines = vec![];
+        if let Some(version) = response.version {
+            lines.push(field("Protocol", version_name(version).into(), white));
+        }
+        if let Some(addr) = response.remote_addr {
+            lines.push(field("Address", addr.to_string(), white));
+        }
+        if let Some(proxy) = response.proxy.as_ref() {
+            lines.push(field("Proxy", proxy.clone(), self.colors.normal.yellow));
+        }
+        match response.connection_reused {
+            Some(true) => lines.push(field(
+                "Connection",
+                "reused".into(),
+                self.colors.normal.green,
+            )),
+            Some(false) => {
+                let connection = match response.handshake {
+                    Some(handshake) => format!(
+                        "new, {}, {}ms handshake",
+                        handshake.version,
+                        handshake.took.as_millis()
+                    ),
+                    None => "new".into(),
+                };
+                lines.push(field("Connection", connection, white));
+            }
+            None => {}
+        }
+
+        // handshakes like digest auth take more than one exchange, which is
+        // what explains a response to a request that was answered with 401
+        if !response.attempts.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Attempts".fg(white).bold()));
+            for (idx, attempt) in response.attempts.iter().enumerate() {
+                let (status, color) = match attempt.status {
+                    Some(status) if status.is_success() => {
+                        (status.to_string(), self.colors.normal.green)
+                    }
+                    Some(status) => (status.to_string(), self.colors.normal.red),
+                    None => ("failed".into(), self.colors.normal.red),
+                };
+                lines.push(Line::from(vec![
+                    format!("{}. ", idx.add(1)).fg(self.colors.bright.black),
+                    status.fg(color),
+                ]));
+                if let Some((name, value)) = attempt.header.as_ref() {
+                    lines.push(Line::from(vec![
+                        format!("   {name}: ").fg(self.colors.bright.black),
+                        value.clone().fg(white),
+                    ]));
+                }
+            }
+        }
+
+        // builtins are generated anew on every send, this is the only place
+        // telling which values this response was sent with
+        if !response.generated.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Generated".fg(white).bold()));
+            for (expression, value) in response.generated.iter() {
+                lines.push(Line::from(vec![
+                    format!("{{{{{expression}}}}} ").fg(self.colors.bright.black),
+                    value.clone().fg(white),
+                ]));
+            }
+        }
+
+        let Some(certificate) = response.certificate.as_ref() else {
+            return lines;
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+        // the date that makes the certificate invalid, or about to be, is
+        // the one highlighted
+        let (before_color, after_color, expiry) = match certificate.validity(now) {
+            Validity::Valid => (white, white, String::new()),
+            Validity::ExpiresSoon => {
+                let days = (certificate.not_after - now) / (24 * 60 * 60);
+                let expiry = format!(" (expires in {days} days)");
+                (white, self.colors.normal.yellow, expiry)
+            }
+            Validity::Expired => (white, self.colors.normal.red, " (expired)".into()),
+            Validity::NotYetValid => (self.colors.normal.red, white, String::new()),
+        };
+
+        lines.push(Line::default());
+        lines.push(Line::from("Certificate".fg(white).bold()));
+        lines.push(field("Subject", certificate.subject.clone(), white));
+        lines.push(field("Issuer", certificate.issuer.clone(), white));
+        for (idx, name) in certificate.san.iter().enumerate() {
+            let label = if idx.eq(&0) { "Names" } else { "" };
+            lines.push(field(label, name.clone(), white));
+        }
+        lines.push(field(
+            "Not before",
+            certificate::format_timestamp(certificate.not_before),
+            before_color,
+        ));
+        lines.push(field(
+            "Not after",
+            format!(
+                "{}{}",
+                certificate::format_timestamp(certificate.not_after),
+                expiry
+            ),
+            after_color,
+        ));
+        lines.push(field("SHA-256", certificate.fingerprint.clone(), white));
+
+        lines
+    }
+
+    fn draw_connection(&mut self, frame: &mut Frame, size: Rect) {
+        let Some(response) = self.response.clone() else {
+            return;
+        };
+        let lines = self.connection_lines(&response.borrow());
+
+        if self.connection_scroll.ge(&lines.len().saturating_sub(1)) {
+            self.connection_scroll = lines.len().saturating_sub(1);
+        }
+
+        let lines_in_view = lines
+            .into_iter()
+            .skip(self.connection_scroll)
+            .take(size.height.into())
+            .collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(lines_in_view),
+            self.preview_layout.content_pane,
+        );
+    }
+
+    /// bodies are hashed on a blocking task the first time the tab is shown
+    /// for a response, the draws that follow pick the result up and keep it
+    /// on the response
+    fn ensure_hashes(&mut self) {
+        let Some(response) = self.response.clone() else {
+            return;
+        };
+        if response.borrow().hashes.is_some() {
+            return;
+        }
+
+        let finished = match self.hashing.as_ref() {
+            Some((hashed, slot)) if Rc::ptr_eq(hashed, &response) => slot.lock().unwrap().take(),
+            _ => {
+                let source = HashSource::of(&response.borrow());
+                let slot = HashSlot::default();
+                let task_slot = slot.clone();
+                tokio::task::spawn_blocking(move || {
+                    let result = source.hash().map_err(|e| e.to_string());
+                    *task_slot.lock().unwrap() = Some(result);
+                });
+                self.hashing = Some((response, slot));
+                return;
+            }
+        };
+
+        if let Some(result) = finished {
+            response.borrow_mut().hashes = Some(result);
+            self.hashing = None;
+        }
+    }
+
+    fn bytes_lines(&self, response: &Response) -> Vec<Line<'static>> {
+        let field = |name: &str, value: String, color: Color| {
+            Line::from(vec![
+                format!("{:<13}", name).fg(self.colors.bright.black),
+                value.fg(color),
+            ])
+        };
+        let exact = |bytes: u64| match bytes.lt(&1024) {
+            true => format!("{bytes} bytes"),
+            false => format!("{bytes} bytes ({})", format_size(bytes)),
+        };
+        let white = self.colors.normal.white;
+        let body_size = response.body_size.unwrap_or_default();
+
+        let mut lines = vec![];
+        match response.compression.as_ref() {
+            Some(compression) => {
+                let received = format!("{} {}", exact(compression.wire_size), compression.encoding);
+                lines.push(field("Received", received, white));
+                let (decompressed, color) = match (compression.decompressed, &response.cause) {
+                    (true, _) => (exact(body_size), white),
+                    (false, Some(cause)) => (cause.clone(), self.colors.normal.red),
+                    (false, None) => ("no, kept as received".into(), white),
+                };
+                lines.push(field("Decompressed", decompressed, color));
+            }
+            None => lines.push(field("Received", exact(body_size), white)),
+        }
+
+        match (response.raw_body.as_ref(), response.charset.as_ref()) {
+            (Some(_), Some(charset)) => {
+                let text_size = response.body.as_ref().map(String::len).unwrap_or_default();
+                let transcoded = format!(
+                    "from {} to {} of utf-8",
+                    charset.name,
+                    exact(text_size as u64)
+                );
+                lines.push(field("Transcoded", transcoded, self.colors.normal.yellow));
+            }
+            _ => lines.push(field("Transcoded", "no".into(), white)),
+        }
+
+        if let Some(spilled) = response.spilled.as_ref() {
+            let path = spilled.path.to_string_lossy().to_string();
+            lines.push(field("On disk", path, white));
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("Checksums".fg(white).bold()));
+        match response.hashes.as_ref() {
+            Some(Ok(hashes)) => {
+                lines.push(field("SHA-256", hashes.sha256.clone(), white));
+                lines.push(field("MD5", hashes.md5.clone(), white));
+                lines.push(Line::default());
+                lines.push(Line::from(
+                    "[y -> copy sha-256] [Y -> copy md5]".fg(self.colors.normal.magenta),
+                ));
+            }
+            Some(Err(e)) => {
+                lines.push(field("Failed", e.clone(), self.colors.normal.red));
+            }
+            None => lines.push(field(
+                "SHA-256",
+                "computing...".into(),
+                self.colors.bright.black,
+            )),
+        }
+
+        lines
+    }
+
+    fn draw_bytes(&mut self, frame: &mut Frame) {
+        self.ensure_hashes();
+        let Some(response) = self.response.clone() else {
+            return;
+        };
+        let lines = self.bytes_lines(&response.borrow());
+        frame.render_widget(Paragraph::new(lines), self.preview_layout.content_pane);
+    }
+
+    /// copies one of the checksums of the body, once they were computed
+    fn copy_hash(&self, pick: fn(&BodyHashes) -> &String, name: &str) -> Option<String> {
+        let response = self.response.as_ref()?.borrow();
+        let Some(Ok(hashes)) = response.hashes.as_ref() else {
+            return None;
+        };
+
+        Some(match clipboard::copy(pick(hashes)) {
+            Ok(_) => format!("Copied the {name} of the body"),
+            Err(e) => format!("Failed to copy the {name}: {e}"),
+        })
+    }
+
     fn draw_scrollbar(
-        &self,
+        &mut self,
         total_lines: usize,
         current_scroll: usize,
         frame: &mut Frame,
         size: Rect,
     ) {
-        let mut scrollbar_state = ScrollbarState::new(total_lines).position(current_scroll);
-
-        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-            .style(Style::default().fg(self.colors.normal.red))
-            .begin_symbol(Some("↑"))
-            .end_symbol(Some("↓"));
-
-        frame.render_stateful_widget(scrollbar, size, &mut scrollbar_state);
+        let viewport = self.preview_layout.content_pane.height as usize;
+        self.scroll_label = scrollbar::percent_label(total_lines, viewport, current_scroll);
+        frame.render_widget(
+            Scrollbar::new(self.colors, total_lines, viewport, current_scroll),
+            size,
+        );
     }
 
     fn draw_horizontal_scrollbar(
@@ -501,7 +1448,7 @@ impl<'a> ResponseViewer<'a> {
     ) {
         let mut scrollbar_state = ScrollbarState::new(total_columns).position(current_scroll);
 
-        let scrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
+        let scrollbar = ratatui::widgets::Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
             .style(Style::default().fg(self.colors.normal.red))
             .begin_symbol(Some("←"))
             .end_symbol(Some("→"));
@@ -510,6 +1457,10 @@ impl<'a> ResponseViewer<'a> {
     }
 
     fn draw_pretty_response(&mut self, frame: &mut Frame, size: Rect) {
+        if self.highlight_pending && HIGHLIGHTER.read().unwrap().is_loaded(PreviewLanguage::Json) {
+            self.update(self.response.clone());
+        }
+
         if self.response.as_ref().is_some() {
             if self.pretty_scroll.ge(&self.lines.len().saturating_sub(1)) {
                 self.pretty_scroll = self.lines.len().saturating_sub(1);
@@ -517,7 +1468,7 @@ impl<'a> ResponseViewer<'a> {
 
             self.draw_scrollbar(
                 self.lines.len(),
-                self.raw_scroll,
+                self.pretty_scroll,
                 frame,
                 self.preview_layout.scrollbar,
             );
@@ -528,44 +1479,438 @@ impl<'a> ResponseViewer<'a> {
                 vec![Line::from("No body").centered()]
             };
 
-            let lines_in_view = lines
+            let lines = lines
                 .into_iter()
-                .skip(self.pretty_scroll)
-                .chain(iter::repeat(Line::from("~".fg(self.colors.bright.black))))
-                .take(size.height.into())
+                .enumerate()
+                .map(|(row, line)| self.highlight_selection(row, self.highlight_links(row, line)))
                 .collect::<Vec<_>>();
+            let lines_in_view = self.fit_to_pane(&lines, self.pretty_scroll, size.height.into());
 
             let pretty_response = Paragraph::new(lines_in_view);
             frame.render_widget(pretty_response, self.preview_layout.content_pane);
+            self.place_graphic();
+            if self.hyperlinks && !self.links.is_empty() {
+                hyperlinks::mark(frame.buffer_mut(), self.preview_layout.content_pane);
+            }
         }
     }
 
-    fn draw_summary(&self, frame: &mut Frame, size: Rect) {
-        if let Some(ref response) = self.response {
-            let status_color = match response
-                .borrow()
-                .status
-                .map(|status| status.as_u16())
-                .unwrap_or_default()
-            {
-                s if s < 400 => self.colors.normal.green,
-                _ => self.colors.normal.red,
+    /// the lines from `scroll` onwards that fit the content pane, soft wrapped
+    /// or cut at the sideways scroll. Scrolling always counts whole lines,
+    /// however many rows they take when wrapped
+    fn fit_to_pane(
+        &mut self,
+        lines: &[Line<'static>],
+        scroll: usize,
+        height: usize,
+    ) -> Vec<Line<'static>> {
+        let width = usize::from(self.preview_layout.content_pane.width);
+        let filler = Line::from("~".fg(self.colors.bright.black));
+
+        if self.wrap {
+            // the two columns of the gutter are taken from the pane
+            return lines
+                .iter()
+                .skip(scroll)
+                .flat_map(|line| wrap_line(line, width.saturating_sub(2), self.colors))
+                .chain(iter::repeat(filler))
+                .take(height)
+                .collect();
+        }
+
+        let longest = lines
+            .iter()
+            .map(|line| line_text(line).chars().count())
+            .max()
+            .unwrap_or_default();
+        self.body_scroll_x = self.body_scroll_x.min(longest.saturating_sub(1));
+
+        lines
+            .iter()
+            .skip(scroll)
+            .map(|line| slice_line(line, self.body_scroll_x, width))
+            .chain(iter::repeat(filler))
+            .take(height)
+            .collect()
+    }
+
+    fn highlight_selection(&self, row: usize, line: Line<'static>) -> Line<'static> {
+        let Some(selection) = self.selection.filter(|s| s.rows().contains(&row)) else {
+            return line;
+        };
+
+        let text = line_text(&line);
+        let range = selection.byte_range(&text);
+        // empty selections would be invisible otherwise
+        if range.is_empty() {
+            let mut line = line;
+            line.spans.push(" ".reversed());
+            return line;
+        }
+        restyle_ranges(
+            line,
+            &[(range, Style::default().add_modifier(Modifier::REVERSED))],
+        )
+    }
+
+    /// underlines the links on `row`, the one focused on link mode is also
+    /// reversed
+    fn highlight_links(&self, row: usize, line: Line<'static>) -> Line<'static> {
+        let first = self.links.partition_point(|link| link.row.lt(&row));
+        let ranges = self.links[first..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, link)| link.row.eq(&row))
+            .map(|(idx, link)| {
+                let modifier = match self.link_mode.eq(&Some(first.add(idx))) {
+                    true => Modifier::UNDERLINED | Modifier::REVERSED,
+                    false => Modifier::UNDERLINED,
+                };
+                (link.range.clone(), Style::default().add_modifier(modifier))
+            })
+            .collect::<Vec<_>>();
+        restyle_ranges(line, &ranges)
+    }
+
+    /// focuses the first link on view, or the first one at all when there
+    /// is none on view
+    fn start_link_mode(&mut self) -> Option<String> {
+        if self.links.is_empty() {
+            return Some("there are no links on the response".into());
+        }
+        let focused = self
+            .links
+            .iter()
+            .position(|link| link.row.ge(&self.pretty_scroll))
+            .unwrap_or_default();
+        self.link_mode = Some(focused);
+        self.scroll_to_link(focused);
+        None
+    }
+
+    /// moves between the links of the preview, going around at either end
+    fn handle_link_key(&mut self, key_event: KeyEvent) -> Option<ResponseViewerEvent> {
+        let focused = self.link_mode?;
+        let count = self.links.len();
+        let next = match key_event.code {
+            KeyCode::Esc => {
+                self.link_mode = None;
+                return None;
+            }
+            KeyCode::Enter => {
+                return self
+                    .links
+                    .get(focused)
+                    .map(|link| ResponseViewerEvent::OpenLink(link.url.clone()))
+            }
+            KeyCode::Tab => focused.add(1).rem(count),
+            KeyCode::BackTab => focused.add(count).sub(1).rem(count),
+            _ => return None,
+        };
+
+        self.link_mode = Some(next);
+        self.scroll_to_link(next);
+        None
+    }
+
+    /// scrolls just enough for the link to be on view, the start of it when
+    /// it is too long to fit sideways
+    fn scroll_to_link(&mut self, idx: usize) {
+        let Some(link) = self.links.get(idx) else {
+            return;
+        };
+
+        let height = usize::from(self.preview_layout.content_pane.height).max(1);
+        if link.row.lt(&self.pretty_scroll) {
+            self.pretty_scroll = link.row;
+        } else if link.row.ge(&self.pretty_scroll.add(height)) {
+            self.pretty_scroll = link.row.add(1).sub(height);
+        }
+
+        if self.wrap {
+            return;
+        }
+        let width = usize::from(self.preview_layout.content_pane.width).max(1);
+        let text = line_text(&self.lines[link.row]);
+        let start = text[..link.range.start].chars().count();
+        let end = start.add(link.url.chars().count());
+        if start.lt(&self.body_scroll_x) || end.gt(&self.body_scroll_x.add(width)) {
+            self.body_scroll_x = start;
+        }
+    }
+
+    /// starts selecting from the top left of what is on view
+    fn start_selection(&mut self, linewise: bool) {
+        if self.lines.is_empty() {
+            return;
+        }
+        let start = (self.pretty_scroll.min(self.lines.len().sub(1)), 0);
+        self.selection = Some(Selection {
+            anchor: start,
+            cursor: start,
+            linewise,
+        });
+    }
+
+    /// moves the end of the selection around, scrolling along so it is
+    /// always on view
+    fn handle_selection_key(&mut self, key_event: KeyEvent) -> Option<ResponseViewerEvent> {
+        let mut selection = self.selection?;
+        let last_row = self.lines.len().saturating_sub(1);
+        let (row, col) = selection.cursor;
+
+        let cursor = match key_event.code {
+            KeyCode::Esc => {
+                self.selection = None;
+                return None;
+            }
+            KeyCode::Char('y') => return Some(self.copy_selection(selection)),
+            // the breadcrumb follows the cursor, so these act on it
+            KeyCode::Char('p') => {
+                let event = self.yank_node(KeyCode::Char('p'));
+                self.selection = None;
+                return event;
+            }
+            KeyCode::Char('C') => {
+                let message = self.capture_node();
+                self.selection = None;
+                return message.map(ResponseViewerEvent::Notify);
+            }
+            KeyCode::Char('v') if !selection.linewise => {
+                self.selection = None;
+                return None;
+            }
+            KeyCode::Char('V') if selection.linewise => {
+                self.selection = None;
+                return None;
+            }
+            KeyCode::Char('v') | KeyCode::Char('V') => {
+                selection.linewise = !selection.linewise;
+                (row, col)
+            }
+            KeyCode::Char('j') | KeyCode::Down => (row.add(1).min(last_row), col),
+            KeyCode::Char('k') | KeyCode::Up => (row.saturating_sub(1), col),
+            KeyCode::Char('h') | KeyCode::Left => (row, col.saturating_sub(1)),
+            KeyCode::Char('l') | KeyCode::Right => (row, col.add(1)),
+            KeyCode::Char('0') => (row, 0),
+            KeyCode::Char('$') => (row, usize::MAX),
+            KeyCode::Char('g') => (0, col),
+            KeyCode::Char('G') => (last_row, col),
+            _ => return None,
+        };
+
+        let line_len = self
+            .lines
+            .get(cursor.0)
+            .map(|line| line_text(line).chars().count())
+            .unwrap_or_default();
+        selection.cursor = (cursor.0, cursor.1.min(line_len.saturating_sub(1)));
+        self.selection = Some(selection);
+
+        let height = usize::from(self.preview_layout.content_pane.height).max(1);
+        if selection.cursor.0.lt(&self.pretty_scroll) {
+            self.pretty_scroll = selection.cursor.0;
+        } else if selection.cursor.0.ge(&self.pretty_scroll.add(height)) {
+            self.pretty_scroll = selection.cursor.0.add(1).sub(height);
+        }
+
+        let width = usize::from(self.preview_layout.content_pane.width).max(1);
+        if selection.cursor.1.lt(&self.body_scroll_x) {
+            self.body_scroll_x = selection.cursor.1;
+        } else if selection.cursor.1.ge(&self.body_scroll_x.add(width)) {
+            self.body_scroll_x = selection.cursor.1.add(1).sub(width);
+        }
+
+        None
+    }
+
+    /// the json value the breadcrumb points to, the one under the cursor
+    /// while selecting, otherwise the first one on the top line in view
+    fn inspected_node(&self) -> Option<JsonNode> {
+        let tree = self.tree.as_ref()?;
+        let cursor = match self.selection {
+            Some(selection) => selection.cursor,
+            None => {
+                let text = line_text(self.lines.get(self.pretty_scroll)?);
+                let indent = text.chars().take_while(|c| c.is_whitespace()).count();
+                (self.pretty_scroll, indent)
+            }
+        };
+        let byte = byte_offset(&self.tree_source, cursor)?;
+        json_tree::node_at(tree, &self.tree_source, byte)
+    }
+
+    /// the second key of `yp` and `yv`, copying either the path to the
+    /// inspected value or the value itself
+    fn yank_node(&self, code: KeyCode) -> Option<ResponseViewerEvent> {
+        let node = self.inspected_node()?;
+        let path = node.path();
+        let (text, message) = match code {
+            KeyCode::Char('p') => (path.clone(), format!("Copied {path}")),
+            KeyCode::Char('v') => (
+                node.value(&self.tree_source),
+                format!("Copied the value of {path}"),
+            ),
+            _ => return None,
+        };
+
+        Some(ResponseViewerEvent::Notify(match clipboard::copy(&text) {
+            Ok(_) => message,
+            Err(e) => format!("Failed to copy {path}: {e}"),
+        }))
+    }
+
+    /// captures the inspected value on the selected request, named after the
+    /// closest key on its path, so it can be used by other requests right
+    /// after the next send
+    fn capture_node(&self) -> Option<String> {
+        let node = self.inspected_node()?;
+        let path = node.path();
+        let request = self.collection_store.borrow().get_selected_request()?;
+        let mut request = request.write().unwrap();
+        let captures = request.captures.get_or_insert_with(Vec::new);
+
+        if let Some(existing) = captures.iter().find(|capture| {
+            matches!(&capture.source, CaptureSource::JsonPath { path: captured } if captured.eq(&path))
+        }) {
+            return Some(format!("{path} is already captured as {}", existing.name));
+        }
+
+        let base = node
+            .path
+            .iter()
+            .rev()
+            .find_map(|segment| match segment {
+                Segment::Key(key) => Some(capture_name(key)),
+                Segment::Index(_) => None,
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or("value".into());
+        let name = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base}_{n}"),
+            })
+            .find(|name| !captures.iter().any(|capture| capture.name.eq(name)))?;
+
+        captures.push(Capture {
+            name: name.clone(),
+            source: CaptureSource::JsonPath { path: path.clone() },
+            persist: false,
+        });
+
+        Some(format!("capturing {path} as {{{{{name}}}}}"))
+    }
+
+    /// appends assertions pinning down the response to the selected request,
+    /// the value on the breadcrumb included when on the preview. The ones
+    /// the request already has are skipped
+    fn scaffold_assertions(&self) -> Option<ResponseViewerEvent> {
+        let response = self.response.as_ref()?.borrow();
+        let node = match self.active_tab {
+            ResViewerTabs::Preview => self.inspected_node(),
+            _ => None,
+        };
+        // pinning the whole body is rarely what anyone wants
+        let path = node
+            .filter(|node| !node.path.is_empty())
+            .map(|node| node.path());
+        let scaffolded = assertions::scaffold(&response, path.as_deref());
+
+        let request = self.collection_store.borrow().get_selected_request()?;
+        let mut request = request.write().unwrap();
+        let existing = request.assertions.get_or_insert_with(Vec::new);
+        let mut review = vec![];
+        for scaffolded in scaffolded {
+            if !existing.contains(&scaffolded.assertion) {
+                review.push((existing.len(), scaffolded.note));
+                existing.push(scaffolded.assertion);
+            }
+        }
+        if existing.is_empty() {
+            request.assertions = None;
+        }
+
+        Some(ResponseViewerEvent::ReviewAssertions(review))
+    }
+
+    fn copy_selection(&mut self, selection: Selection) -> ResponseViewerEvent {
+        self.selection = None;
+        let lines = self.lines.iter().map(line_text).collect::<Vec<_>>();
+        let rows = selection.rows().count();
+
+        ResponseViewerEvent::Notify(match clipboard::copy(&selection.text(&lines)) {
+            Ok(_) if rows.eq(&1) => "Copied the selection".into(),
+            Ok(_) => format!("Copied {rows} lines"),
+            Err(e) => format!("Failed to copy the selection: {e}"),
+        })
+    }
+
+    fn draw_summary(&self, frame: &mut Frame, size: Rect) {
+        if let Some(ref response) = self.response {
+            let status_color = match response
+                .borrow()
+                .status
+                .map(|status| status.as_u16())
+                .unwrap_or_default()
+            {
+                s if s < 400 => self.colors.normal.green,
+                _ => self.colors.normal.red,
             };
 
+            // shown like the status line, eg: `HTTP/2 200`, so it is clear
+            // which version was negotiated
+            let version = response
+                .borrow()
+                .version
+                .map(|version| format!("{} ", version_name(version)))
+                .unwrap_or_default();
             let status = match response.borrow().status {
                 Some(status) if size.width.gt(&50) => format!(
-                    "{} ({})",
+                    "{}{} ({})",
+                    version,
                     status.as_str(),
                     status
                         .canonical_reason()
                         .expect("tried to get a canonical_reason from a invalid status code")
                 )
                 .fg(status_color),
-                Some(status) => status.as_str().to_string().fg(status_color),
+                Some(status) => format!("{}{}", version, status.as_str()).fg(status_color),
                 None => "Error".fg(self.colors.normal.red),
             };
 
-            let mut pieces: Vec<Span> = vec![
+            let mut pieces: Vec<Span> = vec![];
+            match self.selection {
+                Some(selection) if selection.linewise => {
+                    pieces.push(Span::styled(
+                        " V-LINE ",
+                        self.colors
+                            .highlight(self.colors.normal.black, self.colors.normal.blue),
+                    ));
+                    pieces.push(" ".into());
+                }
+                Some(_) => {
+                    pieces.push(Span::styled(
+                        " VISUAL ",
+                        self.colors
+                            .highlight(self.colors.normal.black, self.colors.normal.blue),
+                    ));
+                    pieces.push(" ".into());
+                }
+                None => {}
+            }
+            if let Some(focused) = self.link_mode {
+                pieces.push(Span::styled(
+                    format!(" LINK {}/{} ", focused.add(1), self.links.len()),
+                    self.colors
+                        .highlight(self.colors.normal.black, self.colors.normal.blue),
+                ));
+                pieces.push(" ".into());
+            }
+            if let Some(label) = self.scroll_label.as_ref() {
+                pieces.push(format!("{label} ").fg(self.colors.bright.black));
+            }
+            pieces.extend([
                 "Status: ".fg(self.colors.bright.black),
                 status,
                 " ".into(),
@@ -573,11 +1918,104 @@ impl<'a> ResponseViewer<'a> {
                 format!("{}ms", response.borrow().duration.as_millis())
                     .fg(self.colors.normal.green),
                 " ".into(),
-            ];
+            ]);
 
             if let Some(size) = response.borrow().size {
+                let body_size = response.borrow().body_size.unwrap_or_default();
+                let size = match response.borrow().compression.as_ref() {
+                    Some(compression) if compression.decompressed => format!(
+                        "{} ({}) → {}",
+                        format_size(compression.wire_size),
+                        compression.encoding,
+                        format_size(body_size)
+                    ),
+                    Some(compression) => format!(
+                        "{} ({}, not decompressed)",
+                        format_size(compression.wire_size),
+                        compression.encoding
+                    ),
+                    None => format_size(size),
+                };
                 pieces.push("Size: ".fg(self.colors.bright.black));
-                pieces.push(format!("{} B", size).fg(self.colors.normal.green))
+                pieces.push(size.fg(self.colors.normal.green));
+                // the body was kept as received as it couldn't be decompressed
+                if let Some(cause) = response.borrow().cause.as_ref() {
+                    pieces.push(format!(" {cause}").fg(self.colors.normal.red));
+                }
+                if response.borrow().spilled.is_some() {
+                    let shown = format!(
+                        " (first {} shown, s saves it)",
+                        format_size(PREVIEW_SIZE as u64)
+                    );
+                    pieces.push(shown.fg(self.colors.normal.yellow));
+                }
+                pieces.push(" ".into());
+            };
+
+            if !self.assertion_results.is_empty() {
+                let total = self.assertion_results.len();
+                let passed = self.assertion_results.iter().filter(|a| a.passed).count();
+                let color = match passed.eq(&total) {
+                    true => self.colors.normal.green,
+                    false => self.colors.normal.red,
+                };
+                pieces.push("Assertions: ".fg(self.colors.bright.black));
+                pieces.push(format!("{}/{}", passed, total).fg(color));
+                pieces.push(" ".into());
+            }
+
+            if let Some((language, overridden)) = self.display {
+                pieces.push("Display: ".fg(self.colors.bright.black));
+                pieces.push(language.to_string().fg(self.colors.normal.green));
+                if overridden {
+                    pieces.push(" (overridden)".fg(self.colors.normal.yellow));
+                }
+                pieces.push(" ".into());
+            }
+
+            if let Some(charset) = response.borrow().charset.as_ref() {
+                pieces.push("Charset: ".fg(self.colors.bright.black));
+                pieces.push(charset.name.clone().fg(self.colors.normal.green));
+                if let Some(warning) = charset.warning.as_ref() {
+                    pieces.push(format!(" ({warning})").fg(self.colors.normal.red));
+                }
+                pieces.push(" ".into());
+            }
+
+            // through a proxy the address would be the proxy's, which is
+            // already shown on its own
+            match (
+                response.borrow().proxy.as_ref(),
+                response.borrow().remote_addr,
+            ) {
+                (Some(proxy), _) => {
+                    pieces.push("Proxy: ".fg(self.colors.bright.black));
+                    pieces.push(proxy.clone().fg(self.colors.normal.yellow));
+                }
+                (None, Some(addr)) => {
+                    pieces.push("Address: ".fg(self.colors.bright.black));
+                    pieces.push(addr.to_string().fg(self.colors.normal.green));
+                }
+                (None, None) => {}
+            }
+
+            // the trend goes on the right, but only when it leaves enough
+            // room for the rest of the summary
+            let trend = Line::from(self.run_trend());
+            let size = match trend.width() as u16 {
+                width if width.gt(&0) && size.width.gt(&width.add(40)) => {
+                    let [summary, _, trend_pane] = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([
+                            Constraint::Fill(1),
+                            Constraint::Length(1),
+                            Constraint::Length(width),
+                        ])
+                        .areas(size);
+                    frame.render_widget(trend, trend_pane);
+                    summary
+                }
+                _ => size,
             };
 
             frame.render_widget(Line::from(pieces), size);
@@ -588,6 +2026,8 @@ impl<'a> ResponseViewer<'a> {
 impl<'a> Renderable for ResponseViewer<'a> {
     fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
         self.draw_tabs(frame, self.layout.tabs_pane);
+        self.draw_history(frame, self.layout.history_pane);
+        self.scroll_label = None;
         self.draw_current_tab(frame, self.layout.content_pane)?;
         self.draw_summary(frame, self.layout.summary_pane);
         self.draw_container(size, frame);
@@ -606,6 +2046,18 @@ impl<'a> Eventful for ResponseViewer<'a> {
             return Ok(Some(ResponseViewerEvent::Quit));
         }
 
+        if self.selection.is_some() {
+            return Ok(self.handle_selection_key(key_event));
+        }
+
+        if self.link_mode.is_some() {
+            return Ok(self.handle_link_key(key_event));
+        }
+
+        if std::mem::take(&mut self.pending_yank) {
+            return Ok(self.yank_node(key_event.code));
+        }
+
         if let KeyCode::Esc = key_event.code {
             return Ok(Some(ResponseViewerEvent::RemoveSelection));
         }
@@ -619,22 +2071,152 @@ impl<'a> Eventful for ResponseViewer<'a> {
         }
 
         match key_event.code {
-            KeyCode::Char('0') if self.active_tab.eq(&ResViewerTabs::Headers) => {
-                self.headers_scroll_x = 0;
+            KeyCode::Char('e') if self.active_tab.eq(&ResViewerTabs::Assertions) => {
+                if self
+                    .collection_store
+                    .borrow()
+                    .get_selected_request()
+                    .is_some()
+                {
+                    return Ok(Some(ResponseViewerEvent::EditAssertions));
+                }
             }
-            KeyCode::Char('$') if self.active_tab.eq(&ResViewerTabs::Headers) => {
-                self.headers_scroll_x = usize::MAX;
+            // decompression is a setting of the request, so it only takes
+            // effect on the next time it is sent
+            KeyCode::Char('z') => {
+                if let Some(request) = self.collection_store.borrow().get_selected_request() {
+                    let mut request = request.write().unwrap();
+                    request.disable_decompression = !request.disable_decompression;
+                    let message = match request.disable_decompression {
+                        true => "keeping compressed bodies as they arrive",
+                        false => "decompressing compressed bodies",
+                    };
+                    return Ok(Some(ResponseViewerEvent::RequestChanged(message.into())));
+                }
             }
-            KeyCode::Char('h') => {
-                if let ResViewerTabs::Headers = self.active_tab {
-                    self.headers_scroll_x = self.headers_scroll_x.saturating_sub(1)
+            // like decompression, this only applies to the next sends
+            KeyCode::Char('F') => {
+                if let Some(request) = self.collection_store.borrow().get_selected_request() {
+                    let mut request = request.write().unwrap();
+                    request.fresh_connection = !request.fresh_connection;
+                    let message = match request.fresh_connection {
+                        true => "sending on a fresh connection every time",
+                        false => "reusing kept alive connections",
+                    };
+                    return Ok(Some(ResponseViewerEvent::RequestChanged(message.into())));
+                }
+            }
+            KeyCode::Char('D') => {
+                if let Some(message) = self.cycle_display_language() {
+                    return Ok(Some(ResponseViewerEvent::Notify(message)));
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(path) = self.suggested_save_path() {
+                    return Ok(Some(ResponseViewerEvent::SaveBody(path)));
+                }
+            }
+            KeyCode::Char('v') if self.active_tab.eq(&ResViewerTabs::Preview) => {
+                self.start_selection(false)
+            }
+            KeyCode::Char('V') if self.active_tab.eq(&ResViewerTabs::Preview) => {
+                self.start_selection(true)
+            }
+            KeyCode::Char('y') if self.active_tab.eq(&ResViewerTabs::Preview) => {
+                self.pending_yank = self.tree.is_some();
+            }
+            KeyCode::Char('o') if self.active_tab.eq(&ResViewerTabs::Preview) => {
+                if let Some(message) = self.start_link_mode() {
+                    return Ok(Some(ResponseViewerEvent::Notify(message)));
+                }
+            }
+            KeyCode::Char('C') if self.active_tab.eq(&ResViewerTabs::Preview) => {
+                if let Some(message) = self.capture_node() {
+                    return Ok(Some(ResponseViewerEvent::Notify(message)));
+                }
+            }
+            KeyCode::Char('A') => {
+                if let Some(event) = self.scaffold_assertions() {
+                    return Ok(Some(event));
                 }
             }
+            KeyCode::Char('H') => {
+                if let Some(request) = self.collection_store.borrow().get_selected_request() {
+                    let mut request = request.write().unwrap();
+                    request.http_version = HttpVersion::cycle(request.http_version);
+                    let message = match request.http_version {
+                        Some(version) => format!("sending over {version} only"),
+                        None => "negotiating the http version with the server".into(),
+                    };
+                    return Ok(Some(ResponseViewerEvent::RequestChanged(message)));
+                }
+            }
+            KeyCode::Char('y') if self.active_tab.eq(&ResViewerTabs::Bytes) => {
+                if let Some(message) = self.copy_hash(|hashes| &hashes.sha256, "sha-256") {
+                    return Ok(Some(ResponseViewerEvent::Notify(message)));
+                }
+            }
+            KeyCode::Char('Y') if self.active_tab.eq(&ResViewerTabs::Bytes) => {
+                if let Some(message) = self.copy_hash(|hashes| &hashes.md5, "md5") {
+                    return Ok(Some(ResponseViewerEvent::Notify(message)));
+                }
+            }
+            KeyCode::Char('t') if !self.runs.is_empty() => {
+                return Ok(Some(ResponseViewerEvent::ShowRuns));
+            }
+            KeyCode::Char('[') if self.selected_history.gt(&0) => {
+                return Ok(Some(ResponseViewerEvent::SelectResponse(
+                    self.selected_history.sub(1),
+                )));
+            }
+            KeyCode::Char(']') if self.selected_history.add(1).lt(&self.history.len()) => {
+                return Ok(Some(ResponseViewerEvent::SelectResponse(
+                    self.selected_history.add(1),
+                )));
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let idx = c.to_digit(10).unwrap_or_default() as usize - 1;
+                if idx.lt(&self.history.len()) {
+                    return Ok(Some(ResponseViewerEvent::SelectResponse(idx)));
+                }
+            }
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+                self.body_scroll_x = 0;
+                let message = match self.wrap {
+                    true => "wrapping long lines",
+                    false => "long lines scroll sideways",
+                };
+                return Ok(Some(ResponseViewerEvent::Notify(message.into())));
+            }
+            KeyCode::Char('0') => match self.active_tab {
+                ResViewerTabs::Headers => self.headers_scroll_x = 0,
+                ResViewerTabs::Preview | ResViewerTabs::Raw => self.body_scroll_x = 0,
+                _ => {}
+            },
+            KeyCode::Char('$') => match self.active_tab {
+                ResViewerTabs::Headers => self.headers_scroll_x = usize::MAX,
+                ResViewerTabs::Preview | ResViewerTabs::Raw if !self.wrap => {
+                    self.body_scroll_x = usize::MAX
+                }
+                _ => {}
+            },
+            KeyCode::Char('h') | KeyCode::Left => match self.active_tab {
+                ResViewerTabs::Headers => {
+                    self.headers_scroll_x = self.headers_scroll_x.saturating_sub(1)
+                }
+                ResViewerTabs::Preview | ResViewerTabs::Raw => {
+                    self.body_scroll_x = self.body_scroll_x.saturating_sub(1)
+                }
+                _ => {}
+            },
             KeyCode::Char('j') => match self.active_tab {
                 ResViewerTabs::Preview => self.pretty_scroll = self.pretty_scroll.add(1),
                 ResViewerTabs::Raw => self.raw_scroll = self.raw_scroll.add(1),
                 ResViewerTabs::Headers => self.headers_scroll_y = self.headers_scroll_y.add(1),
-                ResViewerTabs::Cookies => {}
+                ResViewerTabs::Assertions => self.assertions_scroll = self.assertions_scroll.add(1),
+                ResViewerTabs::Connection => self.connection_scroll = self.connection_scroll.add(1),
+                ResViewerTabs::Cookies | ResViewerTabs::Bytes => {}
             },
             KeyCode::Char('k') => match self.active_tab {
                 ResViewerTabs::Preview => self.pretty_scroll = self.pretty_scroll.saturating_sub(1),
@@ -642,13 +2224,21 @@ impl<'a> Eventful for ResponseViewer<'a> {
                 ResViewerTabs::Headers => {
                     self.headers_scroll_y = self.headers_scroll_y.saturating_sub(1)
                 }
-                ResViewerTabs::Cookies => {}
+                ResViewerTabs::Assertions => {
+                    self.assertions_scroll = self.assertions_scroll.saturating_sub(1)
+                }
+                ResViewerTabs::Connection => {
+                    self.connection_scroll = self.connection_scroll.saturating_sub(1)
+                }
+                ResViewerTabs::Cookies | ResViewerTabs::Bytes => {}
             },
-            KeyCode::Char('l') => {
-                if let ResViewerTabs::Headers = self.active_tab {
-                    self.headers_scroll_x = self.headers_scroll_x.add(1)
+            KeyCode::Char('l') | KeyCode::Right => match self.active_tab {
+                ResViewerTabs::Headers => self.headers_scroll_x = self.headers_scroll_x.add(1),
+                ResViewerTabs::Preview | ResViewerTabs::Raw if !self.wrap => {
+                    self.body_scroll_x = self.body_scroll_x.add(1)
                 }
-            }
+                _ => {}
+            },
             _ => {}
         }
 
@@ -656,6 +2246,89 @@ impl<'a> Eventful for ResponseViewer<'a> {
     }
 }
 
+/// the language the body of `response` looks like, bodies that aren't text
+/// have none
+fn detect_language(response: &Response) -> Option<PreviewLanguage> {
+    let body = response.body.as_ref()?;
+    let content_type = response
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.get("content-type"))
+        .and_then(|value| value.to_str().ok());
+    Some(PreviewLanguage::detect(content_type, body))
+}
+
+/// where the character at `(row, col)` of the preview is on `text`. Lines
+/// are split the way the preview splits them, a line break followed by
+/// another one counts as a single break
+fn byte_offset(text: &str, (row, col): (usize, usize)) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    let mut current = 0;
+    while current.lt(&row) {
+        let (_, c) = chars.next()?;
+        if matches!(c, '\n' | '\r') {
+            chars.next_if(|(_, next)| matches!(next, '\n' | '\r'));
+            current += 1;
+        }
+    }
+
+    let start = chars.peek().map_or(text.len(), |(idx, _)| *idx);
+    let line = text[start..].split(['\n', '\r']).next().unwrap_or_default();
+    let col = line
+        .char_indices()
+        .nth(col)
+        .map_or(line.len(), |(idx, _)| idx);
+    Some(start.add(col))
+}
+
+/// cuts the start of long paths, the end is what tells where the cursor is
+fn breadcrumb(path: &str, width: usize) -> String {
+    let len = path.chars().count();
+    match len.gt(&width) {
+        true => iter::once('…')
+            .chain(path.chars().skip(len.add(1).saturating_sub(width)))
+            .collect(),
+        false => path.to_string(),
+    }
+}
+
+/// capture names can't have whitespace or `=`, anything but letters,
+/// digits and underscores is swapped for an underscore
+fn capture_name(key: &str) -> String {
+    key.chars()
+        .map(|c| match c.is_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+/// every url on `lines`, in the order they show up
+fn find_links(lines: &[Line<'static>]) -> Vec<BodyLink> {
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            let text = line_text(line);
+            uri::find_urls(&text)
+                .into_iter()
+                .map(|range| BodyLink {
+                    row,
+                    url: text[range.clone()].to_string(),
+                    range,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn line_text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
 fn build_layout(size: Rect) -> ResViewerLayout {
     let size = Rect::new(
         size.x.add(1),
@@ -664,7 +2337,7 @@ fn build_layout(size: Rect) -> ResViewerLayout {
         size.height.saturating_sub(2),
     );
 
-    let [tabs_pane, _, content_pane, summary_pane] = Layout::default()
+    let [tabs_pane, history_pane, content_pane, summary_pane] = Layout::default()
         .constraints([
             Constraint::Length(1),
             Constraint::Length(1),
@@ -676,6 +2349,7 @@ fn build_layout(size: Rect) -> ResViewerLayout {
 
     ResViewerLayout {
         tabs_pane,
+        history_pane,
         content_pane,
         summary_pane,
     }
@@ -710,6 +2384,31 @@ fn build_preview_layout(size: Rect) -> PreviewLayout {
     }
 }
 
+/// renders bytes like `hexdump -C` does, 16 bytes per line with the offset
+/// on the left and printable ascii on the right
+fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|byte| match byte.is_ascii_graphic() || byte.eq(&b' ') {
+                    true => *byte as char,
+                    false => '.',
+                })
+                .collect::<String>();
+
+            format!("{:08x}  {:<47}  |{}|", idx * 16, hex, ascii)
+        })
+        .collect()
+}
+
 fn get_error_ascii_art<R>(width: u16, rng: &mut R) -> &'static [&'static str]
 where
     R: Rng,
@@ -751,6 +2450,28 @@ mod tests {
     use rand::{rngs::StdRng, SeedableRng};
 
     use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+    use hac_core::net::handshake::Handshake;
+    use hac_core::net::request_manager::Attempt;
+    use hac_core::syntax::highlighter;
+    use hac_core::text_object::TextObject;
+
+    #[test]
+    fn test_hex_dump() {
+        let mut bytes = b"{\"id\": 1}".to_vec();
+        bytes.extend([0x1f, 0x8b, 0x08, 0x00, 0xff, 0x0a, 0x41]);
+
+        assert_eq!(
+            hex_dump(&bytes),
+            vec![
+                "00000000  7b 22 69 64 22 3a 20 31 7d 1f 8b 08 00 ff 0a 41  |{\"id\": 1}......A|"
+                    .to_string(),
+            ]
+        );
+        assert_eq!(hex_dump(&[0; 17]).len(), 2);
+        assert!(hex_dump(&[0; 17])[1].starts_with("00000010  00 "));
+    }
+
     #[test]
     fn test_ascii_with_size() {
         let seed = [0u8; 32];
@@ -781,4 +2502,734 @@ mod tests {
 
         assert_eq!(art, expected);
     }
+
+    #[test]
+    fn test_selecting_regions_of_the_preview() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 10));
+        viewer.lines = (0..30)
+            .map(|idx| Line::from(vec![format!("{idx:02}").fg(Color::Red), " = café".into()]))
+            .collect();
+        let press = |viewer: &mut ResponseViewer, c: char| {
+            viewer
+                .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap();
+        };
+        let viewer_text = |viewer: &ResponseViewer| {
+            let lines = viewer.lines.iter().map(line_text).collect::<Vec<_>>();
+            viewer.selection.unwrap().text(&lines)
+        };
+
+        viewer.pretty_scroll = 2;
+        press(&mut viewer, 'v');
+        assert_eq!(viewer.selection.unwrap().anchor, (2, 0));
+
+        // a rectangle from the first column of the number to the accent
+        press(&mut viewer, '$');
+        press(&mut viewer, 'j');
+        press(&mut viewer, 'h');
+        assert_eq!(viewer_text(&viewer), "02 = caf\n03 = caf");
+
+        // moving past the bottom scrolls, leaving the selection where it was
+        for _ in 0..12 {
+            press(&mut viewer, 'j');
+        }
+        assert!(viewer.pretty_scroll.gt(&2));
+        assert_eq!(viewer.selection.unwrap().rows(), 2..=15);
+        let styled = viewer.highlight_selection(15, viewer.lines[15].clone());
+        assert_eq!(line_text(&styled), "15 = café");
+        assert!(styled.spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::REVERSED));
+        assert_eq!(styled.spans[0].style.fg, Some(Color::Red));
+        assert!(!styled
+            .spans
+            .last()
+            .unwrap()
+            .style
+            .add_modifier
+            .contains(Modifier::REVERSED));
+
+        press(&mut viewer, 'V');
+        press(&mut viewer, 'g');
+        assert_eq!(viewer_text(&viewer), "00 = café\n01 = café\n02 = café");
+
+        press(&mut viewer, 'V');
+        assert_eq!(viewer.selection, None);
+    }
+
+    #[test]
+    fn test_moving_between_links_of_the_preview() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 10));
+        let key = |viewer: &mut ResponseViewer, code: KeyCode| {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap()
+        };
+
+        assert!(matches!(
+            key(&mut viewer, KeyCode::Char('o')),
+            Some(ResponseViewerEvent::Notify(_))
+        ));
+        assert_eq!(viewer.link_mode, None);
+
+        viewer.lines = (0..30)
+            .map(|idx| match idx {
+                3 | 20 => Line::from(vec![
+                    "  \"next\": ".fg(Color::Red),
+                    format!("\"https://a.dev/{idx}\",").into(),
+                ]),
+                _ => Line::from(format!("  \"line\": {idx},")),
+            })
+            .collect();
+        viewer.links = find_links(&viewer.lines);
+        let focused_url = |viewer: &ResponseViewer| {
+            viewer
+                .link_mode
+                .map(|focused| viewer.links[focused].url.clone())
+        };
+
+        // starts from the first one on view
+        viewer.pretty_scroll = 10;
+        key(&mut viewer, KeyCode::Char('o'));
+        assert_eq!(focused_url(&viewer).as_deref(), Some("https://a.dev/20"));
+        assert!(viewer.pretty_scroll.le(&20));
+
+        // going around scrolls back up to it
+        key(&mut viewer, KeyCode::Tab);
+        assert_eq!(focused_url(&viewer).as_deref(), Some("https://a.dev/3"));
+        assert_eq!(viewer.pretty_scroll, 3);
+        key(&mut viewer, KeyCode::BackTab);
+        assert_eq!(focused_url(&viewer).as_deref(), Some("https://a.dev/20"));
+
+        let styled = viewer.highlight_links(20, viewer.lines[20].clone());
+        assert_eq!(line_text(&styled), "  \"next\": \"https://a.dev/20\",");
+        let link = styled
+            .spans
+            .iter()
+            .find(|span| span.content.eq("https://a.dev/20"))
+            .unwrap();
+        assert!(link
+            .style
+            .add_modifier
+            .contains(Modifier::UNDERLINED | Modifier::REVERSED));
+        let styled = viewer.highlight_links(3, viewer.lines[3].clone());
+        let link = styled
+            .spans
+            .iter()
+            .find(|span| span.content.eq("https://a.dev/3"))
+            .unwrap();
+        assert!(link.style.add_modifier.contains(Modifier::UNDERLINED));
+        assert!(!link.style.add_modifier.contains(Modifier::REVERSED));
+
+        // other keys are swallowed while on link mode
+        assert!(key(&mut viewer, KeyCode::Char('w')).is_none());
+        assert!(viewer.wrap);
+        assert!(matches!(
+            key(&mut viewer, KeyCode::Enter),
+            Some(ResponseViewerEvent::OpenLink(url)) if url.eq("https://a.dev/20")
+        ));
+        key(&mut viewer, KeyCode::Esc);
+        assert_eq!(viewer.link_mode, None);
+    }
+
+    #[test]
+    fn test_long_lines_wrap_or_scroll_sideways() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 10));
+        let width = usize::from(viewer.preview_layout.content_pane.width);
+        let long = (0..width * 2)
+            .map(|idx| idx.to_string())
+            .collect::<String>();
+        let lines = vec![Line::from(long.clone()), Line::from("{}")];
+        let press = |viewer: &mut ResponseViewer, code: KeyCode| {
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap()
+        };
+
+        // the long line takes several rows, but scrolling one line past it
+        // lands on the next line
+        let rows = viewer.fit_to_pane(&lines, 0, 10);
+        let wrapped_rows = long.len().div_ceil(width - 2);
+        assert!(rows[1].to_string().starts_with("↪ "));
+        assert_eq!(rows[wrapped_rows].to_string(), "  {}");
+        assert_eq!(viewer.fit_to_pane(&lines, 1, 10)[0].to_string(), "  {}");
+        // nothing scrolls sideways while wrapping
+        press(&mut viewer, KeyCode::Char('l'));
+        assert_eq!(viewer.body_scroll_x, 0);
+
+        assert!(matches!(
+            press(&mut viewer, KeyCode::Char('w')),
+            Some(ResponseViewerEvent::Notify(_))
+        ));
+        let rows = viewer.fit_to_pane(&lines, 0, 10);
+        assert_eq!(rows[0].to_string(), long[..width]);
+        assert_eq!(rows[1].to_string(), "{}");
+
+        press(&mut viewer, KeyCode::Char('l'));
+        press(&mut viewer, KeyCode::Right);
+        press(&mut viewer, KeyCode::Char('h'));
+        assert_eq!(
+            viewer.fit_to_pane(&lines, 0, 10)[0].to_string(),
+            long[1..=width]
+        );
+
+        // the end of the longest line is as far as it goes
+        press(&mut viewer, KeyCode::Char('$'));
+        let rows = viewer.fit_to_pane(&lines, 0, 10);
+        assert_eq!(rows[0].to_string(), long[long.len() - 1..]);
+        press(&mut viewer, KeyCode::Char('0'));
+        assert_eq!(viewer.body_scroll_x, 0);
+    }
+
+    #[test]
+    fn test_display_language_override_is_kept_on_the_request() {
+        highlighter::wait_for_grammar(PreviewLanguage::Json);
+        let colors = hac_colors::Colors::default();
+        let collection = serde_json::json!({
+            "info": { "name": "users", "description": null },
+            "requests": [{ "id": "list", "method": "GET", "name": "list", "uri": "/users" }],
+        });
+        let mut store = CollectionStore::default();
+        store.set_state(serde_json::from_value(collection).unwrap());
+        store.select_request("list");
+        let store = Rc::new(RefCell::new(store));
+        let mut viewer = ResponseViewer::new(&colors, store.clone(), None, Rect::new(0, 0, 80, 20));
+
+        // json sent as plain text is still told apart by its body
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("content-type", "text/plain".parse().unwrap());
+        let body = "{\"id\":1}";
+        let mut response = Response::error(String::default(), std::time::Instant::now());
+        response.is_error = false;
+        response.body = Some(body.into());
+        response.pretty_body = Some(TextObject::from("{\n  \"id\": 1\n}"));
+        response.headers = Some(headers);
+        viewer.update(Some(Rc::new(RefCell::new(response))));
+        assert_eq!(viewer.display, Some((PreviewLanguage::Json, false)));
+        assert_eq!(viewer.lines.len(), 3);
+        assert!(viewer.tree.is_some());
+
+        let press = |viewer: &mut ResponseViewer| {
+            viewer
+                .handle_key_event(KeyEvent::new(KeyCode::Char('D'), KeyModifiers::NONE))
+                .unwrap()
+        };
+        assert!(matches!(
+            press(&mut viewer),
+            Some(ResponseViewerEvent::Notify(message)) if message.eq("displaying the body as xml")
+        ));
+        // anything but json is shown as received, without pretty printing
+        assert_eq!(viewer.display, Some((PreviewLanguage::Xml, true)));
+        assert_eq!(viewer.lines.len(), 1);
+        assert!(viewer.tree.is_none());
+        let preview_language = || {
+            let request = store.borrow().get_selected_request().unwrap();
+            let language = request.read().unwrap().preview_language;
+            language
+        };
+        assert_eq!(preview_language(), Some(PreviewLanguage::Xml));
+
+        // cycling back to the detected language clears the override
+        for _ in 0..4 {
+            press(&mut viewer);
+        }
+        assert_eq!(viewer.display, Some((PreviewLanguage::Json, false)));
+        assert_eq!(preview_language(), None);
+    }
+
+    #[test]
+    fn test_breadcrumb_follows_the_cursor_and_captures_it() {
+        highlighter::wait_for_grammar(PreviewLanguage::Json);
+        let colors = hac_colors::Colors::default();
+        let collection = serde_json::json!({
+            "info": { "name": "users", "description": null },
+            "requests": [{ "id": "list", "method": "GET", "name": "list", "uri": "/users" }],
+        });
+        let mut store = CollectionStore::default();
+        store.set_state(serde_json::from_value(collection).unwrap());
+        store.select_request("list");
+        let store = Rc::new(RefCell::new(store));
+        let mut viewer = ResponseViewer::new(&colors, store.clone(), None, Rect::new(0, 0, 80, 20));
+
+        let body = serde_json::json!({
+            "data": { "items": [{ "id": 1 }, { "first name": "ané", "id": 2 }] },
+        });
+        let mut response = Response::error(String::default(), std::time::Instant::now());
+        response.is_error = false;
+        response.body = Some(body.to_string());
+        response.pretty_body = Some(TextObject::from(
+            serde_json::to_string_pretty(&body).unwrap().as_str(),
+        ));
+        viewer.update(Some(Rc::new(RefCell::new(response))));
+
+        let path = |viewer: &ResponseViewer| viewer.inspected_node().map(|node| node.path());
+        // the top line in view is what the breadcrumb points to
+        assert_eq!(path(&viewer), Some("$".into()));
+        viewer.pretty_scroll = 1;
+        assert_eq!(path(&viewer), Some("$.data".into()));
+        viewer.pretty_scroll = 7;
+        assert!(line_text(&viewer.lines[7])
+            .trim()
+            .starts_with("\"first name\""));
+        assert_eq!(path(&viewer), Some("$.data.items[1]['first name']".into()));
+        assert_eq!(
+            viewer.inspected_node().unwrap().value(&viewer.tree_source),
+            "ané"
+        );
+
+        // while selecting, the cursor is what counts
+        let press = |viewer: &mut ResponseViewer, c: char| {
+            viewer
+                .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap()
+        };
+        press(&mut viewer, 'v');
+        press(&mut viewer, 'j');
+        for _ in 0..12 {
+            press(&mut viewer, 'l');
+        }
+        assert_eq!(path(&viewer), Some("$.data.items[1].id".into()));
+        assert!(matches!(
+            press(&mut viewer, 'C'),
+            Some(ResponseViewerEvent::Notify(message))
+                if message.eq("capturing $.data.items[1].id as {{id}}")
+        ));
+        assert!(viewer.selection.is_none());
+
+        viewer.pretty_scroll = 7;
+        press(&mut viewer, 'C');
+        viewer.pretty_scroll = 8;
+        assert!(matches!(
+            press(&mut viewer, 'C'),
+            Some(ResponseViewerEvent::Notify(message))
+                if message.eq("$.data.items[1].id is already captured as id")
+        ));
+        let request = store.borrow().get_selected_request().unwrap();
+        let captures = request
+            .read()
+            .unwrap()
+            .captures
+            .clone()
+            .unwrap()
+            .iter()
+            .map(|capture| capture.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            captures,
+            vec![
+                "id = $.data.items[1].id".to_string(),
+                "first_name = $.data.items[1]['first name']".to_string(),
+            ]
+        );
+
+        // `y` waits for what to copy, anything else drops it
+        assert!(press(&mut viewer, 'y').is_none());
+        assert!(viewer.pending_yank);
+        assert!(press(&mut viewer, 'x').is_none());
+        assert!(!viewer.pending_yank);
+        press(&mut viewer, 'y');
+        assert!(press(&mut viewer, 'p').is_some());
+    }
+
+    #[test]
+    fn test_byte_offsets_and_breadcrumbs() {
+        let text = "{\r\n  \"é\": 1\r\n}";
+        assert_eq!(byte_offset(text, (0, 0)), Some(0));
+        assert_eq!(byte_offset(text, (1, 2)), Some(5));
+        assert_eq!(byte_offset(text, (1, 7)), Some(11));
+        // past the end of the line stays on it
+        assert_eq!(byte_offset(text, (1, 99)), Some(12));
+        assert_eq!(byte_offset(text, (2, 0)), Some(14));
+        assert_eq!(byte_offset(text, (3, 0)), None);
+
+        assert_eq!(breadcrumb("$.data.items", 20), "$.data.items");
+        assert_eq!(breadcrumb("$.data.items[3].name", 10), "…s[3].name");
+        assert_eq!(capture_name("first name=x"), "first_name_x");
+    }
+
+    #[test]
+    fn test_images_are_drawn_to_fit_the_pane() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let viewer = ResponseViewer::new(&colors, store.clone(), None, Rect::new(0, 0, 80, 10));
+        let preview = |width: u32, height: u32, pixel: fn(u32, u32) -> [u8; 4]| ImagePreview {
+            info: ImageInfo {
+                format: image::ImageFormat::Png,
+                width,
+                height,
+            },
+            size: 2048,
+            pixels: Ok(Rc::new(Pixels {
+                width,
+                height,
+                rgba: (0..height)
+                    .flat_map(|y| (0..width).flat_map(move |x| pixel(x, y)))
+                    .collect(),
+            })),
+        };
+        // red on top, white below, and a transparent corner
+        let pixel = |x: u32, y: u32| match (x, y) {
+            (0, 0) => [0, 0, 0, 0],
+            (_, 0..=1) => [255, 0, 0, 255],
+            _ => [255, 255, 255, 255],
+        };
+
+        let lines = viewer.image_lines(&preview(4, 4, pixel));
+        assert_eq!(lines[0].to_string(), "PNG image, 4x4 (2.0 KiB)");
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1].to_string(), "▀▀▀▀");
+        assert_eq!(lines[1].spans[1].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(lines[1].spans[1].style.bg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(lines[2].spans[1].style.fg, Some(Color::Rgb(255, 255, 255)));
+        assert!(lines[3].to_string().starts_with("press s to save it"));
+
+        // wide images are scaled down to the width left by the gutter
+        let lines = viewer.image_lines(&preview(1000, 10, pixel));
+        let width = usize::from(viewer.preview_layout.content_pane.width) - 2;
+        assert_eq!(lines[1].spans.len(), width);
+        assert!(lines.len() <= usize::from(viewer.preview_layout.content_pane.height));
+
+        let monochrome = hac_colors::Colors::monochrome();
+        let viewer = ResponseViewer::new(&monochrome, store, None, Rect::new(0, 0, 80, 10));
+        let lines = viewer.image_lines(&preview(4, 4, pixel));
+        assert_eq!(lines[1].to_string(), ".:::");
+        assert_eq!(lines[2].to_string(), "@@@@");
+
+        let lines = viewer.image_lines(&ImagePreview {
+            info: ImageInfo {
+                format: image::ImageFormat::Gif,
+                width: 640,
+                height: 480,
+            },
+            size: 1024,
+            pixels: Err(ImageError::UnsupportedFormat(image::ImageFormat::Gif)),
+        });
+        let lines = lines.iter().map(line_text).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "GIF image, 640x480 (1.0 KiB)",
+                "GIF images can't be previewed",
+                "press s to save it, the raw tab has a hex view",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_images_are_left_to_the_terminal_when_it_draws_them() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 30));
+        viewer.graphics = Some(Graphics {
+            protocol: graphics::Protocol::Kitty,
+            cell_width: 10,
+            cell_height: 20,
+        });
+        let preview = |width: u32, height: u32| ImagePreview {
+            info: ImageInfo {
+                format: image::ImageFormat::Jpeg,
+                width,
+                height,
+            },
+            size: 2048,
+            pixels: Ok(Rc::new(Pixels {
+                width,
+                height,
+                rgba: vec![255; (width * height * 4) as usize],
+            })),
+        };
+
+        // small images keep their size, taking whole cells
+        let small = preview(95, 50);
+        assert_eq!(
+            viewer.graphic_cells(small.pixels.as_ref().unwrap()),
+            Some((10, 3))
+        );
+        let lines = viewer.image_lines(&small);
+        let lines = lines.iter().map(line_text).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "JPEG image, 95x50 (2.0 KiB)",
+                "",
+                "",
+                "",
+                "press s to save it, the raw tab has a hex view",
+            ]
+        );
+
+        // big ones are scaled down to the pane, keeping their aspect
+        let pane = viewer.preview_layout.content_pane;
+        let big = preview(4000, 4000);
+        let (columns, rows) = viewer.graphic_cells(big.pixels.as_ref().unwrap()).unwrap();
+        assert_eq!(rows, pane.height - 2);
+        assert_eq!(u32::from(columns) * 10, u32::from(rows) * 20);
+    }
+
+    #[test]
+    fn test_connection_tab_highlights_expired_certificates() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+
+        let mut response = Response::error(String::new(), std::time::Instant::now());
+        response.version = Some(reqwest::Version::HTTP_2);
+        response.remote_addr = Some("10.0.0.7:443".parse().unwrap());
+        response.connection_reused = Some(true);
+        let text = |lines: Vec<Line>| {
+            lines
+                .iter()
+                .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect::<Vec<String>>()
+        };
+
+        // plain http only has the protocol, the address and the connection
+        let lines = text(viewer.connection_lines(&response));
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("HTTP/2"));
+        assert!(lines[1].ends_with("10.0.0.7:443"));
+        assert_eq!(lines[2], format!("{:<13}reused", "Connection"));
+
+        // new tls connections tell how they were set up
+        response.connection_reused = Some(false);
+        response.handshake = Some(Handshake {
+            version: "TLS 1.3",
+            took: std::time::Duration::from_millis(34),
+        });
+        let lines = text(viewer.connection_lines(&response));
+        assert_eq!(
+            lines[2],
+            format!("{:<13}new, TLS 1.3, 34ms handshake", "Connection")
+        );
+
+        response.certificate = Some(certificate::Certificate {
+            subject: "CN=example.com".into(),
+            issuer: "CN=Test CA".into(),
+            san: vec!["example.com".into(), "www.example.com".into()],
+            not_before: 0,
+            not_after: 86400,
+            fingerprint: "AB:CD".into(),
+        });
+        let lines = viewer.connection_lines(&response);
+        let not_after = lines
+            .iter()
+            .find(|line| {
+                line.spans
+                    .first()
+                    .is_some_and(|span| span.content.starts_with("Not after"))
+            })
+            .unwrap();
+        assert_eq!(not_after.spans[1].style.fg, Some(colors.normal.red));
+        assert!(not_after.spans[1].content.ends_with("(expired)"));
+        assert!(text(lines).contains(&format!("{:<13}www.example.com", "")));
+    }
+
+    #[test]
+    fn test_decompression_is_saved_with_the_request() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = store_with_request(make_request("list", "/users"));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+        let z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE);
+
+        let event = viewer.handle_key_event(z).unwrap();
+        assert!(matches!(
+            event,
+            Some(ResponseViewerEvent::RequestChanged(message))
+                if message.eq("keeping compressed bodies as they arrive")
+        ));
+        assert!(request.read().unwrap().disable_decompression);
+
+        let event = viewer.handle_key_event(z).unwrap();
+        assert!(matches!(
+            event,
+            Some(ResponseViewerEvent::RequestChanged(_))
+        ));
+        assert!(!request.read().unwrap().disable_decompression);
+    }
+
+    #[test]
+    fn test_fresh_connections_are_saved_with_the_request() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = store_with_request(make_request("list", "/users"));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+
+        let event = viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('F'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(matches!(
+            event,
+            Some(ResponseViewerEvent::RequestChanged(message))
+                if message.eq("sending on a fresh connection every time")
+        ));
+        assert!(request.read().unwrap().fresh_connection);
+    }
+
+    #[test]
+    fn test_http_version_is_saved_with_the_request() {
+        let colors = hac_colors::Colors::default();
+        let (store, request) = store_with_request(make_request("list", "/users"));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+        let h = KeyEvent::new(KeyCode::Char('H'), KeyModifiers::NONE);
+
+        let event = viewer.handle_key_event(h).unwrap();
+        assert!(matches!(
+            event,
+            Some(ResponseViewerEvent::RequestChanged(message))
+                if message.eq("sending over HTTP/1.1 only")
+        ));
+        assert_eq!(
+            request.read().unwrap().http_version,
+            Some(HttpVersion::Http1)
+        );
+
+        viewer.handle_key_event(h).unwrap();
+        let event = viewer.handle_key_event(h).unwrap();
+        assert!(matches!(
+            event,
+            Some(ResponseViewerEvent::RequestChanged(message))
+                if message.eq("negotiating the http version with the server")
+        ));
+        assert_eq!(request.read().unwrap().http_version, None);
+    }
+
+    #[test]
+    fn test_connection_tab_lists_every_attempt() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+
+        let mut response = Response::error(String::new(), std::time::Instant::now());
+        response.attempts = vec![
+            Attempt {
+                status: Some(reqwest::StatusCode::UNAUTHORIZED),
+                header: Some((
+                    "www-authenticate".into(),
+                    r#"Digest realm="api", nonce="abc""#.into(),
+                )),
+            },
+            Attempt {
+                status: Some(reqwest::StatusCode::OK),
+                header: Some(("authorization".into(), "Digest username=\"me\"".into())),
+            },
+        ];
+
+        let lines = viewer
+            .connection_lines(&response)
+            .into_iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "Attempts",
+                "1. 401 Unauthorized",
+                r#"   www-authenticate: Digest realm="api", nonce="abc""#,
+                "2. 200 OK",
+                r#"   authorization: Digest username="me""#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bytes_tab_hashes_the_body_once() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut response = Response::error(String::new(), std::time::Instant::now());
+        response.is_error = false;
+        response.body = Some("abc".into());
+        response.body_size = Some(3);
+        let response = Rc::new(RefCell::new(response));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+        viewer.update(Some(response.clone()));
+
+        let text = |viewer: &ResponseViewer| {
+            viewer
+                .bytes_lines(&response.borrow())
+                .into_iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(viewer.copy_hash(|hashes| &hashes.md5, "md5").is_none());
+
+        viewer.ensure_hashes();
+        assert!(text(&viewer).contains(&format!("{:<13}computing...", "SHA-256")));
+
+        for _ in 0..100 {
+            viewer.ensure_hashes();
+            if response.borrow().hashes.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(viewer.hashing.is_none());
+
+        let lines = text(&viewer);
+        assert_eq!(lines[0], format!("{:<13}3 bytes", "Received"));
+        assert_eq!(lines[1], format!("{:<13}no", "Transcoded"));
+        assert!(lines.contains(&format!(
+            "{:<13}ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "SHA-256"
+        )));
+        assert!(lines.contains(&format!("{:<13}900150983cd24fb0d6963f7d28e17f72", "MD5")));
+    }
+
+    #[test]
+    fn test_binary_bodies_are_saved_where_asked() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut response = Response::error(String::new(), std::time::Instant::now());
+        response.is_error = false;
+        response.binary_body = Some(vec![0, 159, 146, 150]);
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+        viewer.update(Some(Rc::new(RefCell::new(response))));
+
+        let event = viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(matches!(
+            event,
+            Some(ResponseViewerEvent::SaveBody(path)) if path.eq("./response.body")
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("body.bin");
+        let message = viewer.save_body(&path.to_string_lossy()).unwrap();
+        assert_eq!(message, format!("Saved the body to {}", path.display()));
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0, 159, 146, 150]);
+
+        // nothing is overwritten, and directories get the name from the url
+        viewer.save_body(&path.to_string_lossy()).unwrap();
+        assert!(dir.path().join("body.bin.1").exists());
+        viewer.save_body(&dir.path().to_string_lossy()).unwrap();
+        assert!(dir.path().join("response.body").exists());
+    }
+
+    #[test]
+    fn test_run_trend_marks_failed_runs() {
+        let colors = hac_colors::Colors::default();
+        let store = Rc::new(RefCell::new(CollectionStore::default()));
+        let mut viewer = ResponseViewer::new(&colors, store, None, Rect::new(0, 0, 80, 20));
+        let run = |millis: Option<u64>| Run {
+            duration: millis.map(std::time::Duration::from_millis),
+            at: SystemTime::UNIX_EPOCH,
+            reused: None,
+        };
+
+        // a single run has nothing to compare against
+        viewer.set_runs(vec![run(Some(30))]);
+        assert!(viewer.run_trend().is_empty());
+
+        viewer.set_runs(vec![run(Some(30)), run(None), run(Some(90))]);
+        let trend = viewer.run_trend();
+        assert_eq!(Line::from(trend.clone()).to_string(), "▁×█ 30/90/90ms");
+        assert_eq!(trend[1].style.fg, Some(colors.normal.red));
+    }
 }
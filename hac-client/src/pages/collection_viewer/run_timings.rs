@@ -0,0 +1,230 @@
+use crate::pages::collection_viewer::response_history::{self, Run, RunStats};
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::ops::{Add, Div, Sub};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// set of events `RunTimings` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RunTimingsEvent {
+    /// user pressed `Esc`, the popup should be hidden
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// popup listing every run behind the sparkline on the response summary,
+/// newest first
+#[derive(Debug)]
+pub struct RunTimings<'rt> {
+    colors: &'rt hac_colors::Colors,
+    runs: Vec<Run>,
+    scroll: usize,
+}
+
+impl<'rt> RunTimings<'rt> {
+    pub fn new(colors: &'rt hac_colors::Colors) -> Self {
+        RunTimings {
+            colors,
+            runs: vec![],
+            scroll: 0,
+        }
+    }
+
+    /// replaces the runs being shown, `runs` are expected oldest first like
+    /// the history keeps them
+    pub fn set_runs(&mut self, runs: Vec<Run>) {
+        self.runs = runs;
+        self.scroll = 0;
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let bars = response_history::sparkline(&self.runs);
+        let median = RunStats::new(&self.runs).map(|stats| stats.median);
+
+        self.runs
+            .iter()
+            .zip(bars)
+            .enumerate()
+            .rev()
+            .map(|(idx, (run, bar))| {
+                let number = format!(" #{:<3}", idx.add(1)).fg(self.colors.bright.black);
+                let at = format!("{}  ", format_time_of_day(run.at)).fg(self.colors.normal.white);
+                match (run.duration, bar) {
+                    (Some(duration), Some(bar)) => {
+                        let color = match median {
+                            Some(median) if duration.gt(&median.mul_f64(1.5)) => {
+                                self.colors.normal.yellow
+                            }
+                            _ => self.colors.normal.green,
+                        };
+                        let mut spans = vec![
+                            number,
+                            at,
+                            format!("{bar} ").fg(self.colors.normal.blue),
+                            format!("{}ms", duration.as_millis()).fg(color),
+                        ];
+                        match run.reused {
+                            Some(true) => spans.push("  reused".fg(self.colors.bright.black)),
+                            Some(false) => spans.push("  new".fg(self.colors.bright.black)),
+                            None => {}
+                        }
+                        Line::from(spans)
+                    }
+                    _ => Line::from(vec![number, at, "× failed".fg(self.colors.normal.red)]),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Renderable for RunTimings<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let popup = build_layout(size, self.runs.len());
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Run timings ".fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let [stats_pane, _, runs_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Length(1),
+            ])
+            .areas(inner);
+
+        let stats = match RunStats::new(&self.runs) {
+            Some(stats) => format!(
+                " min {}ms, median {}ms, max {}ms",
+                stats.min.as_millis(),
+                stats.median.as_millis(),
+                stats.max.as_millis()
+            ),
+            None => String::from(" every run failed"),
+        };
+        frame.render_widget(Line::from(stats.fg(self.colors.bright.black)), stats_pane);
+
+        let lines = self.lines();
+        self.scroll = self
+            .scroll
+            .min(lines.len().saturating_sub(runs_pane.height.into()));
+        frame.render_widget(
+            Paragraph::new(lines).scroll((self.scroll as u16, 0)),
+            runs_pane,
+        );
+
+        frame.render_widget(
+            Line::from("[j/k -> scroll] [esc -> close]".fg(self.colors.bright.black)).centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for RunTimings<'_> {
+    type Result = RunTimingsEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(RunTimingsEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') => {
+                return Ok(Some(RunTimingsEvent::Close))
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+/// runs only live as long as the application, so the time of the day is
+/// enough to tell them apart. There is no timezone database around, so it is
+/// shown in UTC
+fn format_time_of_day(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        % 86_400;
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+fn build_layout(size: Rect, runs: usize) -> Rect {
+    let width = u16::min(size.width.saturating_sub(4), 50);
+    // stats, a gap, the runs and the hint, plus the borders
+    let height = u16::min(size.height.saturating_sub(4), runs as u16 + 5);
+
+    Rect::new(
+        size.x.add(size.width.sub(width).div(2)),
+        size.y.add(size.height.sub(height).div(2)),
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_runs_are_listed_newest_first_with_failures() {
+        let colors = hac_colors::Colors::default();
+        let mut run_timings = RunTimings::new(&colors);
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(86_400 * 3 + secs);
+        run_timings.set_runs(vec![
+            Run {
+                duration: Some(Duration::from_millis(120)),
+                at: at(3_723),
+                reused: Some(true),
+            },
+            Run {
+                duration: None,
+                at: at(3_730),
+                reused: None,
+            },
+        ]);
+
+        let lines = run_timings
+            .lines()
+            .into_iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                " #2  01:02:10 UTC  × failed",
+                " #1  01:02:03 UTC  ▁ 120ms  reused"
+            ]
+        );
+    }
+}
@@ -0,0 +1,473 @@
+use hac_core::collection::types::{Request, RequestMethod};
+use hac_core::runner::{self, Pacing, RunEvent, RunOptions, RunResult, RunSummary};
+
+use crate::pages::overlay::make_overlay;
+use crate::pages::spinner::Spinner;
+use crate::pages::{Eventful, Renderable};
+
+use std::collections::BTreeMap;
+use std::ops::{Add, Div, Sub};
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::task::AbortHandle;
+
+/// set of events `RunnerPanel` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RunnerPanelEvent {
+    /// user pressed `Esc` or `q`, the panel should be hidden. Closing the
+    /// panel doesn't stop the run, results keep streaming in the background
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// displays the progress of a collection run, results are streamed into the
+/// panel as each request finishes instead of waiting for the whole run
+#[derive(Debug)]
+pub struct RunnerPanel<'rp> {
+    colors: &'rp hac_colors::Colors,
+    title: String,
+    total: usize,
+    /// every request of the run in order, to list the ones that were never
+    /// sent when the run stops early
+    planned: Vec<(RequestMethod, String)>,
+    results: Vec<RunResult>,
+    summary: Option<RunSummary>,
+    /// things about the run itself, like requests that couldn't run in
+    /// parallel
+    warnings: Vec<String>,
+    /// the last wait of the run and when it started, shown until it is over
+    pacing: Option<(Pacing, Instant)>,
+    event_rx: Option<UnboundedReceiver<RunEvent>>,
+    /// the run was aborted before it finished, eg: from the activity panel
+    cancelled: bool,
+    scroll: usize,
+}
+
+impl<'rp> RunnerPanel<'rp> {
+    pub fn new(colors: &'rp hac_colors::Colors) -> Self {
+        RunnerPanel {
+            colors,
+            title: String::default(),
+            total: 0,
+            planned: vec![],
+            results: vec![],
+            summary: None,
+            warnings: vec![],
+            pacing: None,
+            event_rx: None,
+            cancelled: false,
+            scroll: 0,
+        }
+    }
+
+    /// starts running every given request in order on a background task,
+    /// discarding the results of any previous run. Aborting the task stops
+    /// the run
+    pub fn start(
+        &mut self,
+        title: String,
+        requests: Vec<Request>,
+        variables: BTreeMap<String, String>,
+        options: RunOptions,
+    ) -> AbortHandle {
+        let (event_tx, event_rx) = unbounded_channel::<RunEvent>();
+
+        self.title = title;
+        self.total = requests.len();
+        self.planned = requests
+            .iter()
+            .map(|request| (request.method.clone(), request.name.clone()))
+            .collect();
+        self.results.clear();
+        self.summary = None;
+        self.warnings.clear();
+        self.pacing = None;
+        self.cancelled = false;
+        self.scroll = 0;
+        self.event_rx = Some(event_rx);
+
+        let handle = tokio::spawn(async move {
+            runner::run_requests(requests, variables, options, |event| {
+                // the panel may be gone if the user left the collection
+                // while running, in which case nobody cares about results
+                if event_tx.send(event).is_err() {
+                    tracing::debug!("runner panel dropped before the run finished");
+                }
+            })
+            .await;
+        });
+        handle.abort_handle()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.event_rx.is_some() && self.summary.is_none()
+    }
+
+    /// how the last run ended, if it did
+    pub fn summary(&self) -> Option<&RunSummary> {
+        self.summary.as_ref()
+    }
+
+    pub fn drain_events(&mut self) {
+        let Some(event_rx) = self.event_rx.as_mut() else {
+            return;
+        };
+
+        loop {
+            match event_rx.try_recv() {
+                Ok(RunEvent::Started { total }) => self.total = total,
+                Ok(RunEvent::Warning(warning)) => self.warnings.push(warning),
+                Ok(RunEvent::Waiting(pacing)) => self.pacing = Some((pacing, Instant::now())),
+                Ok(RunEvent::RequestFinished(result)) => self.results.push(result),
+                Ok(RunEvent::Finished(summary)) => {
+                    self.pacing = None;
+                    self.summary = Some(summary);
+                }
+                // the task went away without a summary, so it was aborted
+                Err(TryRecvError::Disconnected) if self.summary.is_none() => {
+                    self.pacing = None;
+                    self.cancelled = true;
+                    self.event_rx = None;
+                    return;
+                }
+                Err(_) => return,
+            }
+        }
+    }
+
+    fn make_result_line(&self, result: &RunResult) -> Line<'static> {
+        let (outcome, color) = match result.passed {
+            true => ("PASS", self.colors.normal.green),
+            false => ("FAIL", self.colors.normal.red),
+        };
+        let status = result
+            .status
+            .map(|status| status.to_string())
+            .unwrap_or("---".into());
+
+        let mut spans = vec![
+            Span::from(format!(" {} ", outcome)).fg(color).bold(),
+            Span::from(format!("{:<7}", result.method.to_string())).fg(self.colors.normal.yellow),
+            Span::from(format!("{} ", result.name)).fg(self.colors.normal.white),
+            Span::from(format!("{} {}ms", status, result.duration.as_millis()))
+                .fg(self.colors.bright.black),
+        ];
+
+        if !result.assertions.is_empty() {
+            let passed = result.assertions.iter().filter(|a| a.passed).count();
+            spans.push(
+                Span::from(format!(
+                    " {}/{} assertions",
+                    passed,
+                    result.assertions.len()
+                ))
+                .fg(color),
+            );
+        }
+
+        if let Some(error) = result.error.as_ref() {
+            spans.push(Span::from(format!(" {}", error)).fg(self.colors.normal.red));
+        }
+
+        Line::from(spans)
+    }
+
+    /// requests that were never sent, as the run stopped on a failure
+    fn skipped(&self) -> &[(RequestMethod, String)] {
+        match self.summary.as_ref() {
+            Some(summary) if summary.skipped.gt(&0) => {
+                self.planned.get(self.results.len()..).unwrap_or_default()
+            }
+            _ => &[],
+        }
+    }
+
+    fn make_skipped_line(&self, method: &RequestMethod, name: &str) -> Line<'static> {
+        Line::from(vec![
+            Span::from(" SKIP ").fg(self.colors.bright.black).bold(),
+            Span::from(format!("{:<7}", method.to_string())).fg(self.colors.bright.black),
+            Span::from(name.to_string()).fg(self.colors.bright.black),
+        ])
+    }
+
+    /// rows taken by the warnings once wrapped to `width`
+    fn warnings_height(&self, width: u16) -> u16 {
+        self.warnings
+            .iter()
+            .map(|warning| warning.chars().count().div_ceil(usize::from(width.max(1))) as u16)
+            .sum()
+    }
+
+    /// the pacing is only mentioned while the wait is not over yet
+    fn progress_label(&self) -> String {
+        let progress = format!("Running {}/{}", self.results.len(), self.total);
+        match self.pacing.as_ref() {
+            Some((pacing, started)) if started.elapsed().lt(&pacing.wait) => {
+                format!("{progress}, {pacing}")
+            }
+            _ => progress,
+        }
+    }
+
+    fn make_footer(&self) -> Line<'static> {
+        match self.summary.as_ref() {
+            Some(summary) => Line::from(vec![
+                format!("{} passed", summary.passed).fg(self.colors.normal.green),
+                ", ".fg(self.colors.bright.black),
+                format!("{} failed", summary.failed).fg(self.colors.normal.red),
+                ", ".fg(self.colors.bright.black),
+                format!("{} skipped", summary.skipped).fg(self.colors.bright.black),
+            ])
+            .centered(),
+            None if self.cancelled => Line::from(
+                format!(
+                    "cancelled after {} of {} requests",
+                    self.results.len(),
+                    self.total
+                )
+                .fg(self.colors.normal.yellow),
+            )
+            .centered(),
+            None => Spinner::default()
+                .with_label(self.progress_label().fg(self.colors.bright.black))
+                .with_style(Style::default().fg(self.colors.normal.red))
+                .into_centered_line(),
+        }
+    }
+}
+
+impl Renderable for RunnerPanel<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let popup = build_layout(size);
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Running {} ", self.title).fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let [warnings_pane, results_pane, footer_pane, hint_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(self.warnings_height(inner.width)),
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(inner);
+
+        let skipped = self
+            .skipped()
+            .iter()
+            .map(|(method, name)| self.make_skipped_line(method, name));
+        let lines = self
+            .results
+            .iter()
+            .map(|result| self.make_result_line(result))
+            .chain(skipped)
+            .skip(self.scroll)
+            .take(results_pane.height.into())
+            .collect::<Vec<_>>();
+
+        let warnings = self
+            .warnings
+            .iter()
+            .map(|warning| Line::from(warning.clone().fg(self.colors.normal.yellow)))
+            .collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(warnings).wrap(Wrap { trim: true }),
+            warnings_pane,
+        );
+        frame.render_widget(Paragraph::new(lines), results_pane);
+        frame.render_widget(self.make_footer(), footer_pane);
+        frame.render_widget(
+            Line::from("[j/k -> scroll] [esc -> close]".fg(self.colors.bright.black)).centered(),
+            hint_pane,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for RunnerPanel<'_> {
+    type Result = RunnerPanelEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(RunnerPanelEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(Some(RunnerPanelEvent::Close)),
+            KeyCode::Char('j') | KeyCode::Down => {
+                let rows = self.results.len().add(self.skipped().len());
+                self.scroll = usize::min(self.scroll + 1, rows.saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+fn build_layout(size: Rect) -> Rect {
+    let width = u16::min(size.width.saturating_sub(4), 80);
+    let height = u16::min(size.height.saturating_sub(4), 24);
+
+    Rect::new(
+        size.x + size.width.sub(width).div(2),
+        size.y + size.height.sub(height).div(2),
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hac_core::collection::types::RequestMethod;
+    use std::time::Duration;
+
+    #[test]
+    fn test_build_layout_is_centered_and_clamped() {
+        assert_eq!(
+            build_layout(Rect::new(0, 0, 100, 30)),
+            Rect::new(10, 3, 80, 24)
+        );
+        assert_eq!(
+            build_layout(Rect::new(0, 0, 60, 20)),
+            Rect::new(2, 2, 56, 16)
+        );
+    }
+
+    #[test]
+    fn test_scrolling_is_bounded_by_results() {
+        let colors = hac_colors::Colors::default();
+        let mut panel = RunnerPanel::new(&colors);
+        panel.results = (0..3)
+            .map(|idx| RunResult {
+                name: format!("request {}", idx),
+                method: RequestMethod::Get,
+                status: Some(200),
+                duration: Duration::from_millis(10),
+                passed: true,
+                error: None,
+                assertions: vec![],
+                captures: vec![],
+                size: None,
+            })
+            .collect();
+
+        for _ in 0..10 {
+            panel
+                .handle_key_event(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE))
+                .unwrap();
+        }
+        assert_eq!(panel.scroll, 2);
+
+        let event = panel
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(event, Some(RunnerPanelEvent::Close));
+    }
+
+    #[test]
+    fn test_requests_never_sent_are_listed_as_skipped() {
+        let colors = hac_colors::Colors::default();
+        let mut panel = RunnerPanel::new(&colors);
+        panel.planned = ["login", "create", "cleanup"]
+            .into_iter()
+            .map(|name| (RequestMethod::Post, name.to_string()))
+            .collect();
+        panel.results = vec![RunResult {
+            name: "login".into(),
+            method: RequestMethod::Post,
+            status: Some(500),
+            duration: Duration::from_millis(10),
+            passed: false,
+            error: None,
+            assertions: vec![],
+            captures: vec![],
+            size: None,
+        }];
+        // nothing is skipped while the run goes on
+        assert!(panel.skipped().is_empty());
+
+        panel.summary = Some(RunSummary {
+            passed: 0,
+            failed: 1,
+            skipped: 2,
+        });
+        let skipped = panel
+            .skipped()
+            .iter()
+            .map(|(method, name)| panel.make_skipped_line(method, name).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, vec![" SKIP POST   create", " SKIP POST   cleanup"]);
+
+        for _ in 0..10 {
+            panel
+                .handle_key_event(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE))
+                .unwrap();
+        }
+        assert_eq!(panel.scroll, 2);
+    }
+
+    #[test]
+    fn test_pacing_is_shown_while_waiting() {
+        let colors = hac_colors::Colors::default();
+        let mut panel = RunnerPanel::new(&colors);
+        panel.total = 60;
+        panel.pacing = Some((
+            Pacing {
+                wait: Duration::from_millis(800),
+                reason: runner::PacingReason::RateLimit,
+            },
+            Instant::now(),
+        ));
+
+        assert_eq!(
+            panel.progress_label(),
+            "Running 0/60, waiting 800ms for rate limit"
+        );
+
+        panel.pacing.as_mut().unwrap().1 -= Duration::from_secs(1);
+        assert_eq!(panel.progress_label(), "Running 0/60");
+    }
+
+    #[test]
+    fn test_runs_aborted_midway_are_cancelled() {
+        let colors = hac_colors::Colors::default();
+        let mut panel = RunnerPanel::new(&colors);
+        let (event_tx, event_rx) = unbounded_channel();
+        panel.event_rx = Some(event_rx);
+        assert!(panel.is_running());
+
+        event_tx.send(RunEvent::Started { total: 3 }).unwrap();
+        drop(event_tx);
+        panel.drain_events();
+
+        assert!(!panel.is_running());
+        assert!(panel.summary().is_none());
+        assert_eq!(
+            panel.make_footer().to_string(),
+            "cancelled after 0 of 3 requests"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use hac_core::secrets::MASK;
+
+use crate::pages::input::{self, Input};
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::ops::{Add, Div, Sub};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear};
+use ratatui::Frame;
+
+/// set of events `SecretPrompt` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SecretPromptEvent {
+    /// user entered a value for the secret
+    Submit(String),
+    /// user pressed `Esc`, the request should not be sent
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// asks for the value of a secret that has none yet, instead of sending the
+/// request with the placeholder in it. What is typed stays masked unless
+/// revealed with tab
+#[derive(Debug)]
+pub struct SecretPrompt<'sp> {
+    colors: &'sp hac_colors::Colors,
+    name: String,
+    value: String,
+    revealed: bool,
+}
+
+impl<'sp> SecretPrompt<'sp> {
+    pub fn new(colors: &'sp hac_colors::Colors) -> Self {
+        SecretPrompt {
+            colors,
+            name: String::default(),
+            value: String::default(),
+            revealed: false,
+        }
+    }
+
+    /// starts asking for the secret `name`, forgetting anything typed before
+    pub fn open(&mut self, name: String) {
+        self.name = name;
+        self.value.clear();
+        self.revealed = false;
+    }
+
+    fn displayed_value(&self) -> String {
+        match (self.revealed, self.value.is_empty()) {
+            (true, _) | (_, true) => self.value.clone(),
+            (false, false) => MASK.into(),
+        }
+    }
+}
+
+impl Renderable for SecretPrompt<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let width = u16::min(size.width.saturating_sub(4), 60);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(7).div(2)),
+            width,
+            u16::min(size.height, 7),
+        );
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Secret {} has no value ", self.name).fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let input_size = Rect::new(inner.x, inner.y, inner.width, u16::min(inner.height, 3));
+        let mut input = Input::new(self.colors, "Value".into())
+            .placeholder("kept on the secrets file, never on the collection".into());
+        input.focus();
+        let mut displayed = self.displayed_value();
+        frame.render_stateful_widget(input, input_size, &mut displayed);
+        frame.set_cursor(
+            input_size.x.add(displayed.chars().count().add(1) as u16),
+            input_size.y.add(1),
+        );
+
+        let hint_size = Rect::new(inner.x, inner.y.add(input_size.height), inner.width, 1);
+        let reveal = match self.revealed {
+            true => "hide",
+            false => "reveal",
+        };
+        frame.render_widget(
+            Line::from(
+                format!("[enter -> save and send] [tab -> {reveal}] [esc -> cancel]")
+                    .fg(self.colors.bright.black),
+            )
+            .centered(),
+            hint_size.clamp(inner),
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for SecretPrompt<'_> {
+    type Result = SecretPromptEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(SecretPromptEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(SecretPromptEvent::Close)),
+            // an empty secret would be sent as an empty string, which is
+            // what the prompt is here to avoid
+            KeyCode::Enter if !self.value.is_empty() => {
+                return Ok(Some(SecretPromptEvent::Submit(self.value.clone())));
+            }
+            KeyCode::Tab => self.revealed = !self.revealed,
+            KeyCode::Backspace => _ = self.value.pop(),
+            KeyCode::Char(c) => self.value.push(c),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        self.value.push_str(&input::single_line(&text));
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(prompt: &mut SecretPrompt, code: KeyCode) -> Option<SecretPromptEvent> {
+        prompt
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_values_are_masked_until_revealed() {
+        let colors = hac_colors::Colors::default();
+        let mut prompt = SecretPrompt::new(&colors);
+        prompt.open("token".into());
+
+        assert_eq!(press(&mut prompt, KeyCode::Enter), None);
+        for c in "hunter2".chars() {
+            press(&mut prompt, KeyCode::Char(c));
+        }
+        assert_eq!(prompt.displayed_value(), MASK);
+
+        press(&mut prompt, KeyCode::Tab);
+        assert_eq!(prompt.displayed_value(), "hunter2");
+
+        assert_eq!(
+            press(&mut prompt, KeyCode::Enter),
+            Some(SecretPromptEvent::Submit("hunter2".into()))
+        );
+
+        prompt.open("password".into());
+        assert_eq!(prompt.displayed_value(), "");
+        assert!(!prompt.revealed);
+    }
+}
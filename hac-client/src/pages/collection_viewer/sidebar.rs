@@ -6,6 +6,7 @@ mod edit_directory_form;
 mod edit_request_form;
 mod request_form;
 mod select_request_parent;
+mod select_request_template;
 
 use hac_core::collection::types::{Request, RequestKind, RequestMethod};
 
@@ -14,18 +15,20 @@ use super::sidebar::directory_form::{DirectoryForm, DirectoryFormEvent};
 use super::sidebar::directory_form::{DirectoryFormCreate, DirectoryFormEdit};
 use super::sidebar::request_form::{RequestForm, RequestFormEvent};
 use super::sidebar::request_form::{RequestFormCreate, RequestFormEdit};
-use crate::pages::collection_viewer::collection_store::{CollectionStore, CollectionStoreAction};
+use crate::pages::collection_viewer::collection_store::{
+    CollectionStore, CollectionStoreAction, ItemMove,
+};
 use crate::pages::collection_viewer::collection_viewer::{CollectionViewerOverlay, PaneFocus};
-use crate::pages::{Eventful, Renderable};
+use crate::pages::{input, Eventful, Renderable};
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::Rect;
-use ratatui::style::{Style, Styled, Stylize};
+use ratatui::style::{Color, Style, Styled, Stylize};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
@@ -36,6 +39,9 @@ pub enum SidebarEvent {
     /// user pressed `CreateRequest (n)` hotkey, which should notify the caller to open
     /// the create_request_form and properly handle the creation of a new request
     CreateRequest,
+    /// same as `CreateRequest`, but there are templates saved, so the caller
+    /// should also open the template picker on top of the form
+    CreateRequestFromTemplate,
     /// user pressed `Edit (e)` hotkey on a request, which should notify the caller to open
     /// the edit_request_form and properly handle the editing of the existing request
     EditRequest,
@@ -56,6 +62,9 @@ pub enum SidebarEvent {
     /// user pressed `DeleteItem (D)` hotkey, which should notify the caller to open the
     /// delete_item_prompt to ask the user for confirmation
     DeleteItem(String),
+    /// user pressed a hotkey that changes the collection, but it is read-only, so
+    /// the caller should tell how to get a copy that can be changed instead
+    ReadOnly,
     /// user pressed a hotkey to quit the application, so we bubble up so the caller
     /// can do a few things before bubbling the quit request further up
     Quit,
@@ -110,6 +119,19 @@ impl RequestFormVariant<'_> {
     }
 }
 
+/// how the collection in memory compares to its file, shown on the title
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncState {
+    #[default]
+    Clean,
+    /// there are edits that weren't written yet
+    Dirty,
+    /// the file was changed by something else while we had edits of our own
+    Conflicted,
+    /// the collection is read-only, edits stay in memory
+    ReadOnly,
+}
+
 #[derive(Debug)]
 pub struct Sidebar<'sbar> {
     colors: &'sbar hac_colors::Colors,
@@ -118,6 +140,9 @@ pub struct Sidebar<'sbar> {
     request_form: RequestFormVariant<'sbar>,
     directory_form: DirectoryFormVariant<'sbar>,
     delete_item_prompt: DeleteItemPrompt<'sbar>,
+    /// whether keys are typed into the filter instead of acting on the tree
+    filtering: bool,
+    sync_state: SyncState,
 }
 
 impl<'sbar> Sidebar<'sbar> {
@@ -136,6 +161,8 @@ impl<'sbar> Sidebar<'sbar> {
             ),
             delete_item_prompt: DeleteItemPrompt::new(colors, collection_store.clone()),
             lines: vec![],
+            filtering: false,
+            sync_state: SyncState::default(),
             collection_store,
         };
 
@@ -144,18 +171,95 @@ impl<'sbar> Sidebar<'sbar> {
         sidebar
     }
 
+    pub fn set_sync_state(&mut self, sync_state: SyncState) {
+        self.sync_state = sync_state;
+    }
+
     pub fn rebuild_tree_view(&mut self) {
         let mut collection_store = self.collection_store.borrow_mut();
+        let visible = collection_store.get_filtered_items().map(|items| {
+            items
+                .iter()
+                .map(|item| item.get_id())
+                .collect::<HashSet<_>>()
+        });
         self.lines = build_lines(
             collection_store.get_requests(),
             0,
             collection_store.get_selected_request(),
             collection_store.get_hovered_request(),
             collection_store.get_dirs_expanded().unwrap().clone(),
+            visible.as_ref(),
             self.colors,
         );
     }
 
+    fn set_filter(&mut self, filter: &str) {
+        self.collection_store
+            .borrow_mut()
+            .set_sidebar_filter(filter);
+        self.rebuild_tree_view();
+    }
+
+    fn handle_filter_key_event(&mut self, key_event: KeyEvent) -> Option<SidebarEvent> {
+        let mut filter = self.collection_store.borrow().get_sidebar_filter();
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.filtering = false;
+                self.set_filter("");
+            }
+            KeyCode::Enter => self.filtering = false,
+            KeyCode::Backspace => {
+                // erasing past the start leaves the filter, like on the
+                // dashboard
+                if filter.pop().is_none() {
+                    self.filtering = false;
+                }
+                self.set_filter(&filter);
+            }
+            KeyCode::Down | KeyCode::Up => {
+                let action = match key_event.code {
+                    KeyCode::Down => CollectionStoreAction::HoverNext,
+                    _ => CollectionStoreAction::HoverPrev,
+                };
+                self.collection_store.borrow_mut().dispatch(action);
+                self.rebuild_tree_view();
+            }
+            KeyCode::Char(c) => {
+                filter.push(c);
+                self.set_filter(&filter);
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn sync_glyph(&self) -> Line<'static> {
+        let glyph = match self.sync_state {
+            SyncState::Clean => "✓".fg(self.colors.normal.green),
+            SyncState::Dirty => "●".fg(self.colors.normal.yellow),
+            SyncState::Conflicted => "!".fg(self.colors.normal.red).bold(),
+            SyncState::ReadOnly => "🔒".fg(self.colors.normal.red),
+        };
+        Line::from(vec![" ".into(), glyph, " ".into()])
+    }
+
+    fn draw_filter(&self, frame: &mut Frame, size: Rect) {
+        let filter = self.collection_store.borrow().get_sidebar_filter();
+        let line = Line::from(vec![
+            "/".fg(self.colors.normal.magenta),
+            filter.clone().fg(self.colors.normal.white),
+        ]);
+        frame.render_widget(line, size);
+
+        if self.filtering {
+            let cursor = size.x + 1 + filter.chars().count() as u16;
+            frame.set_cursor(cursor.min(size.right().saturating_sub(1)), size.y);
+        }
+    }
+
     pub fn draw_overlay(
         &mut self,
         frame: &mut Frame,
@@ -168,7 +272,7 @@ impl<'sbar> Sidebar<'sbar> {
             CollectionViewerOverlay::EditRequest => {
                 self.request_form.inner().draw(frame, frame.size())?;
             }
-            CollectionViewerOverlay::SelectParentDir => {
+            CollectionViewerOverlay::SelectParentDir | CollectionViewerOverlay::SelectTemplate => {
                 self.request_form.inner().draw_overlay(frame, overlay)?;
             }
             CollectionViewerOverlay::CreateDirectory => {
@@ -202,11 +306,7 @@ impl<'sbar> Renderable for Sidebar<'sbar> {
 
         let mut requests_size = Rect::new(size.x + 1, size.y, size.width.saturating_sub(2), 1);
 
-        let block_border = match (is_focused, is_selected) {
-            (true, false) => Style::default().fg(self.colors.bright.blue),
-            (true, true) => Style::default().fg(self.colors.normal.red),
-            (false, _) => Style::default().fg(self.colors.bright.black),
-        };
+        let block_border = self.colors.pane_border(is_focused, is_selected);
 
         let block = Block::default()
             .borders(Borders::ALL)
@@ -214,14 +314,33 @@ impl<'sbar> Renderable for Sidebar<'sbar> {
                 "R".fg(self.colors.normal.red).bold(),
                 "equests".fg(self.colors.bright.black),
             ])
+            .title_top(self.sync_glyph().right_aligned())
             .border_style(block_border);
 
         frame.render_widget(block, size);
 
-        self.lines.clone().into_iter().for_each(|req| {
+        let has_filter = self.filtering
+            || !self
+                .collection_store
+                .borrow()
+                .get_sidebar_filter()
+                .is_empty();
+        // the filter takes the last row inside of the border
+        let filter_row = size.bottom().saturating_sub(2);
+        if has_filter && filter_row.gt(&size.y) {
+            self.draw_filter(
+                frame,
+                Rect::new(requests_size.x, filter_row, requests_size.width, 1),
+            );
+        }
+
+        for req in self.lines.clone() {
             requests_size.y += 1;
+            if has_filter && requests_size.y.ge(&filter_row) {
+                break;
+            }
             frame.render_widget(req, requests_size);
-        });
+        }
 
         Ok(())
     }
@@ -265,11 +384,11 @@ impl<'a> Eventful for Sidebar<'a> {
                     None => return Ok(None),
                 }
             }
-            CollectionViewerOverlay::SelectParentDir => {
+            CollectionViewerOverlay::SelectParentDir | CollectionViewerOverlay::SelectTemplate => {
                 let result = self.request_form.inner().handle_key_event(key_event)?;
                 assert!(
                     result.is_none(),
-                    "should never return an event when selecting parent dir"
+                    "should never return an event when selecting parent dir or template"
                 );
                 return Ok(None);
             }
@@ -366,6 +485,16 @@ impl<'a> Eventful for Sidebar<'a> {
             return Ok(Some(SidebarEvent::Quit));
         }
 
+        if self.filtering {
+            return Ok(self.handle_filter_key_event(key_event));
+        }
+
+        if let KeyCode::Char('n' | 'e' | 'D' | 'd' | 'J' | 'K' | '<' | '>') = key_event.code {
+            if self.collection_store.borrow().is_read_only() {
+                return Ok(Some(SidebarEvent::ReadOnly));
+            }
+        }
+
         let mut store = self.collection_store.borrow_mut();
 
         match key_event.code {
@@ -376,6 +505,8 @@ impl<'a> Eventful for Sidebar<'a> {
 
                 let request = store.find_hovered_request();
                 match request {
+                    // every directory is shown expanded while filtering
+                    RequestKind::Nested(_) if !store.get_sidebar_filter().is_empty() => {}
                     RequestKind::Nested(_) => {
                         store.dispatch(CollectionStoreAction::ToggleDirectory(request.get_id()));
                     }
@@ -385,17 +516,26 @@ impl<'a> Eventful for Sidebar<'a> {
                     }
                 }
             }
+            KeyCode::Char('/') => self.filtering = true,
             KeyCode::Char('j') | KeyCode::Down => store.dispatch(CollectionStoreAction::HoverNext),
             KeyCode::Char('k') | KeyCode::Up => store.dispatch(CollectionStoreAction::HoverPrev),
             KeyCode::Char('n') => {
+                let has_templates = !store.get_templates().is_empty();
+                drop(store);
                 self.request_form =
                     RequestFormVariant::Create(RequestForm::<RequestFormCreate>::new(
                         self.colors,
                         self.collection_store.clone(),
                     ));
+                if has_templates {
+                    return Ok(Some(SidebarEvent::CreateRequestFromTemplate));
+                }
                 return Ok(Some(SidebarEvent::CreateRequest));
             }
             KeyCode::Char('e') => {
+                if store.get_hovered_request().is_none() {
+                    return Ok(None);
+                }
                 let hovered_request = store.find_hovered_request();
                 drop(store);
                 match hovered_request {
@@ -425,6 +565,29 @@ impl<'a> Eventful for Sidebar<'a> {
                 }
             }
             KeyCode::Char('d') => return Ok(Some(SidebarEvent::CreateDirectory)),
+            KeyCode::Char(c @ ('J' | 'K' | '<' | '>')) => {
+                let item_move = match c {
+                    'J' => ItemMove::Down,
+                    'K' => ItemMove::Up,
+                    '<' => ItemMove::Unindent,
+                    _ => ItemMove::Indent,
+                };
+                let moved = store
+                    .get_hovered_request()
+                    .is_some_and(|item_id| store.move_item(&item_id, item_move));
+                drop(store);
+                self.rebuild_tree_view();
+
+                if moved {
+                    return Ok(Some(SidebarEvent::SyncCollection));
+                }
+                return Ok(None);
+            }
+            KeyCode::Esc if !store.get_sidebar_filter().is_empty() => {
+                drop(store);
+                self.set_filter("");
+                return Ok(None);
+            }
             KeyCode::Esc => return Ok(Some(SidebarEvent::RemoveSelection)),
             _ => {}
         }
@@ -434,14 +597,37 @@ impl<'a> Eventful for Sidebar<'a> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        let overlay = self.collection_store.borrow().peek_overlay();
+        match overlay {
+            CollectionViewerOverlay::CreateRequest | CollectionViewerOverlay::EditRequest => {
+                self.request_form.inner().handle_paste(text)?;
+            }
+            CollectionViewerOverlay::CreateDirectory | CollectionViewerOverlay::EditDirectory => {
+                self.directory_form.inner().handle_paste(text)?;
+            }
+            CollectionViewerOverlay::None if self.filtering => {
+                let mut filter = self.collection_store.borrow().get_sidebar_filter();
+                filter.push_str(&input::single_line(&text));
+                self.set_filter(&filter);
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
 }
 
+/// builds a line for every item on the tree. When `visible` is given only
+/// the items on it are built, and directories are shown expanded
 pub fn build_lines(
     requests: Option<Arc<RwLock<Vec<RequestKind>>>>,
     level: usize,
     selected_request: Option<Arc<RwLock<Request>>>,
     hovered_request: Option<String>,
     dirs_expanded: Rc<RefCell<HashMap<String, bool>>>,
+    visible: Option<&HashSet<String>>,
     colors: &hac_colors::Colors,
 ) -> Vec<Paragraph<'static>> {
     requests
@@ -454,19 +640,26 @@ pub fn build_lines(
                 let is_hovered = hovered_request
                     .as_ref()
                     .is_some_and(|id| id.eq(&item.get_id()));
-                let mut dirs = dirs_expanded.borrow_mut();
-                let is_expanded = dirs.entry(dir.id.to_string()).or_insert(false);
+                // every directory needs an entry, even the ones filtered out,
+                // as hovering looks them up
+                let is_expanded = *dirs_expanded
+                    .borrow_mut()
+                    .entry(dir.id.to_string())
+                    .or_insert(false);
+                if visible.is_some_and(|visible| !visible.contains(&dir.id)) {
+                    return vec![];
+                }
+                let is_expanded = is_expanded || visible.is_some();
 
                 let dir_style = match is_hovered {
-                    true => Style::default()
-                        .fg(colors.normal.white)
-                        .bg(colors.primary.hover)
+                    true => colors
+                        .highlight(colors.normal.white, colors.primary.hover)
                         .bold(),
                     false => Style::default().fg(colors.normal.white).bold(),
                 };
 
                 let gap = " ".repeat(level * 2);
-                let chevron = if *is_expanded { "v" } else { ">" };
+                let chevron = if is_expanded { "v" } else { ">" };
                 let line = vec![Paragraph::new(format!(
                     "{}{} {}/",
                     gap,
@@ -475,13 +668,14 @@ pub fn build_lines(
                 ))
                 .set_style(dir_style)];
 
-                let nested_lines = if *is_expanded {
+                let nested_lines = if is_expanded {
                     build_lines(
                         Some(dir.requests.clone()),
                         level + 1,
                         selected_request.clone(),
                         hovered_request.clone(),
                         dirs_expanded.clone(),
+                        visible,
                         colors,
                     )
                 } else {
@@ -490,6 +684,9 @@ pub fn build_lines(
                 line.into_iter().chain(nested_lines).collect::<Vec<_>>()
             }
             RequestKind::Single(req) => {
+                if visible.is_some_and(|visible| !visible.contains(&item.get_id())) {
+                    return vec![];
+                }
                 let gap = " ".repeat(level * 2);
                 let is_selected = selected_request.as_ref().is_some_and(|selected| {
                     selected.read().unwrap().id.eq(&req.read().unwrap().id)
@@ -499,24 +696,29 @@ pub fn build_lines(
                     .is_some_and(|id| id.eq(&item.get_id()));
 
                 let req_style = match (is_selected, is_hovered) {
-                    (true, true) => Style::default()
-                        .fg(colors.normal.yellow)
-                        .bg(colors.normal.blue),
-                    (true, _) => Style::default()
-                        .fg(colors.normal.white)
-                        .bg(colors.normal.blue),
-                    (_, true) => Style::default()
-                        .fg(colors.normal.white)
-                        .bg(colors.primary.hover),
+                    (true, true) => colors.highlight(colors.normal.yellow, colors.normal.blue),
+                    (true, _) => colors.marked(colors.normal.white, colors.normal.blue),
+                    (_, true) => colors.highlight(colors.normal.white, colors.primary.hover),
                     (false, false) => Style::default().fg(colors.normal.white),
                 };
 
-                let line: Line<'_> = vec![
+                let mut spans = vec![
                     Span::from(gap.clone()),
                     colored_method(req.read().unwrap().method.clone(), colors),
                     Span::from(format!(" {}", req.read().unwrap().name.clone())),
-                ]
-                .into();
+                ];
+                if req.read().unwrap().notes.is_some() {
+                    spans.push(" ✎".fg(colors.bright.black));
+                }
+                for tag in req.read().unwrap().tags.iter() {
+                    spans.push(" ".into());
+                    spans.push(
+                        format!(" {tag} ")
+                            .fg(colors.normal.black)
+                            .bg(tag_color(tag, colors)),
+                    );
+                }
+                let line: Line<'_> = spans.into();
 
                 vec![Paragraph::new(line).set_style(req_style)]
             }
@@ -524,6 +726,23 @@ pub fn build_lines(
         .collect()
 }
 
+/// every tag gets the same color wherever it shows up, without having to
+/// store it anywhere
+fn tag_color(tag: &str, colors: &hac_colors::Colors) -> Color {
+    let palette = [
+        colors.normal.red,
+        colors.normal.green,
+        colors.normal.yellow,
+        colors.normal.blue,
+        colors.normal.magenta,
+        colors.normal.orange,
+    ];
+    let hash = tag.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte.into())
+    });
+    palette[hash % palette.len()]
+}
+
 fn colored_method(method: RequestMethod, colors: &hac_colors::Colors) -> Span<'static> {
     match method {
         RequestMethod::Get => "GET   ".fg(colors.normal.green).bold(),
@@ -531,5 +750,6 @@ fn colored_method(method: RequestMethod, colors: &hac_colors::Colors) -> Span<'s
         RequestMethod::Put => "PUT   ".fg(colors.normal.yellow).bold(),
         RequestMethod::Patch => "PATCH ".fg(colors.normal.orange).bold(),
         RequestMethod::Delete => "DELETE".fg(colors.normal.red).bold(),
+        RequestMethod::Other(method) => format!("{method:<6}").fg(colors.normal.white).bold(),
     }
 }
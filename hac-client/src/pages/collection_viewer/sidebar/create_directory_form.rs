@@ -4,6 +4,7 @@ use super::directory_form::{DirectoryForm, DirectoryFormCreate, DirectoryFormEve
 use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::sidebar::DirectoryFormTrait;
+use crate::pages::input;
 use crate::pages::Eventful;
 
 use std::cell::RefCell;
@@ -80,4 +81,9 @@ impl Eventful for DirectoryForm<'_, DirectoryFormCreate> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        self.dir_name.push_str(&input::single_line(&text));
+        Ok(None)
+    }
 }
@@ -1,18 +1,20 @@
 use hac_core::collection::types::*;
+use hac_core::templates::RequestTemplate;
 
-use super::request_form::FormField;
 use super::request_form::RequestForm;
 use super::request_form::RequestFormCreate;
 use super::request_form::RequestFormEvent;
+use super::request_form::{known_tags, FormField};
 use super::select_request_parent::{SelectRequestParent, SelectRequestParentEvent};
+use super::select_request_template::{SelectRequestTemplate, SelectRequestTemplateEvent};
 use super::RequestFormTrait;
 use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::collection_viewer::CollectionViewerOverlay;
+use crate::pages::input;
 use crate::pages::{Eventful, Renderable};
 
 use std::cell::RefCell;
-use std::ops::Sub;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
@@ -26,8 +28,14 @@ impl<'rf> RequestFormTrait for RequestForm<'rf, RequestFormCreate> {
         frame: &mut Frame,
         overlay: CollectionViewerOverlay,
     ) -> anyhow::Result<()> {
-        if let CollectionViewerOverlay::SelectParentDir = overlay {
-            self.parent_selector.draw(frame, frame.size())?;
+        match overlay {
+            CollectionViewerOverlay::SelectParentDir => {
+                self.parent_selector.draw(frame, frame.size())?;
+            }
+            CollectionViewerOverlay::SelectTemplate => {
+                self.template_selector.draw(frame, frame.size())?;
+            }
+            _ => {}
         }
 
         Ok(())
@@ -40,15 +48,23 @@ impl<'rf> RequestForm<'rf, RequestFormCreate> {
         collection_store: Rc<RefCell<CollectionStore>>,
     ) -> Self {
         let logo_idx = rand::thread_rng().gen_range(0..LOGO_ASCII.len());
+        let known_tags = known_tags(&collection_store);
 
         RequestForm {
             colors,
             parent_selector: SelectRequestParent::new(colors, collection_store.clone()),
+            template_selector: SelectRequestTemplate::new(colors, collection_store.clone()),
+            template: None,
             collection_store,
             logo_idx,
             request_name: String::default(),
             request_method: RequestMethod::Get,
             parent_dir: None,
+            request_tags: String::default(),
+            known_tags,
+            request_resolve: String::default(),
+            resolve_error: None,
+            request_http_version: None,
             focused_field: FormField::Name,
             marker: std::marker::PhantomData,
             request: None,
@@ -57,6 +73,17 @@ impl<'rf> RequestForm<'rf, RequestFormCreate> {
     }
 }
 
+impl RequestForm<'_, RequestFormCreate> {
+    /// the method of the template is picked already, but it can still be
+    /// changed like on a blank request
+    fn use_template(&mut self, template: Option<RequestTemplate>) {
+        if let Some(template) = template.as_ref() {
+            self.request_method = template.method.clone();
+        }
+        self.template = template;
+    }
+}
+
 impl Eventful for RequestForm<'_, RequestFormCreate> {
     type Result = RequestFormEvent;
 
@@ -96,7 +123,32 @@ impl Eventful for RequestForm<'_, RequestFormCreate> {
             return Ok(None);
         }
 
+        if overlay.eq(&CollectionViewerOverlay::SelectTemplate) {
+            match self.template_selector.handle_key_event(key_event)? {
+                Some(SelectRequestTemplateEvent::Confirm(template)) => {
+                    self.use_template(template.map(|template| *template));
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                Some(SelectRequestTemplateEvent::Cancel) => {
+                    self.collection_store.borrow_mut().pop_overlay();
+                }
+                None => {}
+            }
+            return Ok(None);
+        }
+
+        if let (KeyCode::Char('t'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            let mut store = self.collection_store.borrow_mut();
+            if !store.get_templates().is_empty() {
+                store.push_overlay(CollectionViewerOverlay::SelectTemplate);
+            }
+            return Ok(None);
+        }
+
         if let KeyCode::Tab = key_event.code {
+            if self.complete_tag() {
+                return Ok(None);
+            }
             self.focused_field = self.focused_field.next();
             return Ok(None);
         }
@@ -112,6 +164,14 @@ impl Eventful for RequestForm<'_, RequestFormCreate> {
         }
 
         if let KeyCode::Enter = key_event.code {
+            if !self.has_valid_method() {
+                self.focused_field = FormField::Method;
+                return Ok(None);
+            }
+            if !self.check_resolve() {
+                return Ok(None);
+            }
+
             let store = self.collection_store.borrow_mut();
             let collection = store
                 .get_collection()
@@ -127,17 +187,27 @@ impl Eventful for RequestForm<'_, RequestFormCreate> {
                 self.request_name = String::from("unnamed request");
             }
 
-            let request = RequestKind::Single(Arc::new(RwLock::new(Request {
-                id: uuid::Uuid::new_v4().to_string(),
-                auth_method: None,
-                body: None,
-                body_type: None,
-                parent: self.parent_dir.as_ref().map(|(id, _)| id.clone()),
-                headers: None,
-                method: self.request_method.clone(),
-                name: self.request_name.clone(),
-                uri: String::default(),
-            })));
+            let parent = self.parent_dir.as_ref().map(|(id, _)| id.clone());
+            let request = match self.template.as_ref() {
+                Some(template) => Request {
+                    method: self.request_method.clone(),
+                    tags: parse_tags(&self.request_tags),
+                    resolve: self.resolve(),
+                    http_version: self.request_http_version,
+                    ..template.to_request(self.request_name.clone(), parent)
+                },
+                None => Request {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    tags: parse_tags(&self.request_tags),
+                    resolve: self.resolve(),
+                    http_version: self.request_http_version,
+                    parent,
+                    method: self.request_method.clone(),
+                    name: self.request_name.clone(),
+                    ..Default::default()
+                },
+            };
+            let request = RequestKind::Single(Arc::new(RwLock::new(request)));
 
             if let Some((dir_id, _)) = self.parent_dir.as_ref() {
                 if let RequestKind::Nested(dir) = requests
@@ -176,20 +246,14 @@ impl Eventful for RequestForm<'_, RequestFormCreate> {
                 }
                 _ => {}
             },
-            FormField::Method => match key_event.code {
-                KeyCode::Char(c @ '1'..='5') => {
-                    self.request_method = (c.to_digit(10).unwrap() as usize).sub(1).try_into()?;
-                }
-                KeyCode::Left => self.request_method = self.request_method.prev(),
-                KeyCode::Down => self.request_method = 4.try_into()?,
-                KeyCode::Up => self.request_method = 0.try_into()?,
-                KeyCode::Right => self.request_method = self.request_method.next(),
-                KeyCode::Char('h') => self.request_method = self.request_method.prev(),
-                KeyCode::Char('j') => self.request_method = 4.try_into()?,
-                KeyCode::Char('k') => self.request_method = 0.try_into()?,
-                KeyCode::Char('l') => self.request_method = self.request_method.next(),
+            FormField::Method => self.handle_method_key_event(key_event)?,
+            FormField::Tags => match key_event.code {
+                KeyCode::Char(c) => self.request_tags.push(c),
+                KeyCode::Backspace => _ = self.request_tags.pop(),
                 _ => {}
             },
+            FormField::Resolve => self.handle_resolve_key_event(key_event),
+            FormField::HttpVersion => self.handle_http_version_key_event(key_event),
             FormField::Parent => {
                 if let KeyCode::Char(' ') = key_event.code {
                     let mut store = self.collection_store.borrow_mut();
@@ -229,4 +293,17 @@ impl Eventful for RequestForm<'_, RequestFormCreate> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        match self.focused_field {
+            FormField::Name => self.request_name.push_str(&input::single_line(&text)),
+            FormField::Tags => self.request_tags.push_str(&input::single_line(&text)),
+            FormField::Resolve => {
+                self.request_resolve.push_str(&input::single_line(&text));
+                self.resolve_error = None;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
 }
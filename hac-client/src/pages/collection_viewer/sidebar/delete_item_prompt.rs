@@ -1,10 +1,13 @@
+use hac_core::collection::types::RequestKind;
+use hac_core::collection::Collection;
+
 use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::overlay::make_overlay;
 use crate::pages::{Eventful, Renderable};
 
 use std::cell::RefCell;
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul};
 use std::rc::Rc;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -42,6 +45,33 @@ impl<'dip> DeleteItemPrompt<'dip> {
     }
 }
 
+/// flows with a step running the item with `id`, or any request inside of it
+/// when it is a directory
+fn flows_using(collection: &Collection, id: &str) -> Vec<String> {
+    let directory = collection.requests.as_ref().and_then(|requests| {
+        requests.read().unwrap().iter().find_map(|item| match item {
+            RequestKind::Nested(dir) if dir.id.eq(id) => Some(dir.clone()),
+            _ => None,
+        })
+    });
+    let ids = match directory {
+        Some(dir) => dir
+            .flatten_requests()
+            .iter()
+            .map(|request| request.read().unwrap().id.clone())
+            .collect(),
+        None => vec![id.to_string()],
+    };
+
+    let mut flows = ids
+        .iter()
+        .flat_map(|id| collection.flows_using(id))
+        .collect::<Vec<_>>();
+    flows.sort();
+    flows.dedup();
+    flows
+}
+
 impl Renderable for DeleteItemPrompt<'_> {
     fn draw(&mut self, frame: &mut Frame, _: Rect) -> anyhow::Result<()> {
         make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
@@ -84,6 +114,18 @@ impl Renderable for DeleteItemPrompt<'_> {
             ]
         };
 
+        let flows = flows_using(&collection, &hovered_id);
+        if !flows.is_empty() {
+            lines.insert(
+                lines.len().saturating_sub(1),
+                Line::from(
+                    format!("Steps of {} will be broken", flows.join(", "))
+                        .fg(self.colors.normal.yellow),
+                )
+                .centered(),
+            );
+        }
+
         lines.push(
             Line::from("[Confirm: Enter] [Cancel: Esc]".fg(self.colors.bright.black)).centered(),
         );
@@ -93,14 +135,15 @@ impl Renderable for DeleteItemPrompt<'_> {
         let size = frame.size();
 
         let size = Rect::new(
-            size.width.div(2).sub(25),
+            size.width.div(2).saturating_sub(25),
             size.height
                 .div(2)
                 .saturating_sub(logo_size.div(2))
                 .saturating_sub(3),
             50,
             logo_size.add(7),
-        );
+        )
+        .clamp(frame.size());
 
         let logo_size = Rect::new(size.x, size.y, size.width, logo_size);
         let logo = logo
@@ -134,3 +177,30 @@ impl Eventful for DeleteItemPrompt<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flows_using_a_request_or_anything_in_a_directory() {
+        let collection: Collection = serde_json::from_value(serde_json::json!({
+            "info": { "name": "users", "description": null },
+            "requests": [
+                { "id": "login", "method": "POST", "name": "login", "uri": "/login" },
+                { "id": "admin", "name": "admin", "requests": [
+                    { "id": "ban", "method": "POST", "name": "ban", "uri": "/ban", "parent": "admin" },
+                ]},
+            ],
+            "flows": [
+                { "name": "smoke", "steps": ["login", "ban"] },
+                { "name": "auth", "steps": ["login"] },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(flows_using(&collection, "login"), vec!["auth", "smoke"]);
+        assert_eq!(flows_using(&collection, "admin"), vec!["smoke"]);
+        assert!(flows_using(&collection, "missing").is_empty());
+    }
+}
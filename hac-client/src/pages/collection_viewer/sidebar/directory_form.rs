@@ -5,7 +5,7 @@ use crate::pages::overlay::make_overlay;
 use crate::pages::Renderable;
 
 use std::cell::RefCell;
-use std::ops::{Add, Div, Sub};
+use std::ops::{Add, Div};
 use std::rc::Rc;
 
 use ratatui::layout::Rect;
@@ -63,14 +63,15 @@ impl<State> Renderable for DirectoryForm<'_, State> {
 
         let size = frame.size();
         let size = Rect::new(
-            size.width.div(2).sub(25),
+            size.width.div(2).saturating_sub(25),
             size.height
                 .div(2)
                 .saturating_sub(logo_size.div(2))
                 .saturating_sub(2),
             50,
             logo_size.add(4),
-        );
+        )
+        .clamp(frame.size());
 
         let logo = logo
             .iter()
@@ -1,18 +1,19 @@
 use hac_core::collection::types::*;
 
-use super::request_form::FormField;
 use super::request_form::RequestForm;
 use super::request_form::RequestFormEdit;
 use super::request_form::RequestFormEvent;
+use super::request_form::{known_tags, FormField};
 use super::select_request_parent::{SelectRequestParent, SelectRequestParentEvent};
+use super::select_request_template::SelectRequestTemplate;
 use super::RequestFormTrait;
 use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::collection_viewer::CollectionViewerOverlay;
+use crate::pages::input;
 use crate::pages::{Eventful, Renderable};
 
 use std::cell::RefCell;
-use std::ops::Sub;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
@@ -43,6 +44,16 @@ impl<'rf> RequestForm<'rf, RequestFormEdit> {
         let logo_idx = rand::thread_rng().gen_range(0..LOGO_ASCII.len());
         let request_method = request.read().unwrap().method.clone();
         let request_name = request.read().unwrap().name.clone();
+        let request_tags = request.read().unwrap().tags.join(" ");
+        let request_resolve = request
+            .read()
+            .unwrap()
+            .resolve
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let request_http_version = request.read().unwrap().http_version;
+        let known_tags = known_tags(&collection_store);
 
         let parent_dir = if request.read().unwrap().parent.is_some() {
             let store = collection_store.borrow();
@@ -71,11 +82,18 @@ impl<'rf> RequestForm<'rf, RequestFormEdit> {
         RequestForm {
             colors,
             parent_selector: SelectRequestParent::new(colors, collection_store.clone()),
+            template_selector: SelectRequestTemplate::new(colors, collection_store.clone()),
+            template: None,
             collection_store,
             logo_idx,
             request_name,
             request_method,
             parent_dir,
+            request_tags,
+            known_tags,
+            request_resolve,
+            resolve_error: None,
+            request_http_version,
             focused_field: FormField::Name,
             marker: std::marker::PhantomData,
             request: Some(request),
@@ -125,6 +143,9 @@ impl Eventful for RequestForm<'_, RequestFormEdit> {
         }
 
         if let KeyCode::Tab = key_event.code {
+            if self.complete_tag() {
+                return Ok(None);
+            }
             self.focused_field = self.focused_field.next();
             return Ok(None);
         }
@@ -135,12 +156,24 @@ impl Eventful for RequestForm<'_, RequestFormEdit> {
         }
 
         if let KeyCode::Enter = key_event.code {
+            if !self.has_valid_method() {
+                self.focused_field = FormField::Method;
+                return Ok(None);
+            }
+            if !self.check_resolve() {
+                return Ok(None);
+            }
+
+            let resolve = self.resolve();
             let request = self.request.as_mut().unwrap();
             let mut request = request.write().unwrap();
 
             request.name.clone_from(&self.request_name);
             request.method.clone_from(&self.request_method);
             request.parent = self.parent_dir.as_ref().map(|(id, _)| id.clone());
+            request.tags = parse_tags(&self.request_tags);
+            request.resolve = resolve;
+            request.http_version = self.request_http_version;
             let request_id = request.id.clone();
 
             drop(request);
@@ -208,20 +241,14 @@ impl Eventful for RequestForm<'_, RequestFormEdit> {
                 }
                 _ => {}
             },
-            FormField::Method => match key_event.code {
-                KeyCode::Char(c @ '1'..='5') => {
-                    self.request_method = (c.to_digit(10).unwrap() as usize).sub(1).try_into()?;
-                }
-                KeyCode::Left => self.request_method = self.request_method.prev(),
-                KeyCode::Down => self.request_method = 4.try_into()?,
-                KeyCode::Up => self.request_method = 0.try_into()?,
-                KeyCode::Right => self.request_method = self.request_method.next(),
-                KeyCode::Char('h') => self.request_method = self.request_method.prev(),
-                KeyCode::Char('j') => self.request_method = 4.try_into()?,
-                KeyCode::Char('k') => self.request_method = 0.try_into()?,
-                KeyCode::Char('l') => self.request_method = self.request_method.next(),
+            FormField::Method => self.handle_method_key_event(key_event)?,
+            FormField::Tags => match key_event.code {
+                KeyCode::Char(c) => self.request_tags.push(c),
+                KeyCode::Backspace => _ = self.request_tags.pop(),
                 _ => {}
             },
+            FormField::Resolve => self.handle_resolve_key_event(key_event),
+            FormField::HttpVersion => self.handle_http_version_key_event(key_event),
             FormField::Parent => {
                 if let KeyCode::Char(' ') = key_event.code {
                     let mut store = self.collection_store.borrow_mut();
@@ -261,4 +288,135 @@ impl Eventful for RequestForm<'_, RequestFormEdit> {
 
         Ok(None)
     }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        match self.focused_field {
+            FormField::Name => self.request_name.push_str(&input::single_line(&text)),
+            FormField::Tags => self.request_tags.push_str(&input::single_line(&text)),
+            FormField::Resolve => {
+                self.request_resolve.push_str(&input::single_line(&text));
+                self.resolve_error = None;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_editing_tags_with_completion() {
+        let collection: Collection = serde_json::from_value(serde_json::json!({
+            "info": { "name": "users", "description": null },
+            "requests": [
+                { "id": "login", "method": "POST", "name": "login", "uri": "/login", "tags": ["smoke"] },
+                { "id": "ban", "method": "POST", "name": "ban", "uri": "/ban", "tags": ["admin", "smoke"] },
+            ],
+        }))
+        .unwrap();
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        let request = store.get_request_by_id("login").unwrap();
+        let store = Rc::new(RefCell::new(store));
+        let colors = hac_colors::Colors::default();
+        let mut form = RequestForm::<RequestFormEdit>::new(&colors, store, request.clone());
+        assert_eq!(form.request_tags, "smoke");
+        assert_eq!(form.known_tags, vec!["admin", "smoke"]);
+
+        form.focused_field = FormField::Tags;
+        for c in " #Ad".chars() {
+            form.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        assert_eq!(form.tag_completion(), Some("min"));
+        form.handle_key_event(key(KeyCode::Tab)).unwrap();
+        assert_eq!(form.request_tags, "smoke #Admin ");
+        // tags typed already are not offered again
+        form.handle_key_event(key(KeyCode::Char('s'))).unwrap();
+        assert_eq!(form.tag_completion(), None);
+        form.handle_key_event(key(KeyCode::Backspace)).unwrap();
+        // with nothing to complete tab moves on
+        form.handle_key_event(key(KeyCode::Tab)).unwrap();
+        assert_eq!(form.focused_field, FormField::Resolve);
+
+        form.handle_key_event(key(KeyCode::Enter)).unwrap();
+        assert_eq!(request.read().unwrap().tags, vec!["smoke", "admin"]);
+    }
+
+    #[test]
+    fn test_overriding_the_address_of_a_request() {
+        let collection: Collection = serde_json::from_value(serde_json::json!({
+            "info": { "name": "users", "description": null },
+            "requests": [
+                { "id": "login", "method": "POST", "name": "login", "uri": "/login" },
+            ],
+        }))
+        .unwrap();
+        let mut store = CollectionStore::default();
+        store.set_state(collection);
+        let request = store.get_request_by_id("login").unwrap();
+        let store = Rc::new(RefCell::new(store));
+        let colors = hac_colors::Colors::default();
+        let mut form = RequestForm::<RequestFormEdit>::new(&colors, store, request.clone());
+        assert_eq!(form.request_resolve, "");
+
+        // refused until it is valid, keeping the form open on the field
+        form.focused_field = FormField::Resolve;
+        form.handle_paste("api.example.com:443".into()).unwrap();
+        assert!(form
+            .handle_key_event(key(KeyCode::Enter))
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            form.resolve_error.as_deref(),
+            Some("must be written as host:port:address")
+        );
+
+        form.handle_paste(":10.0.0.7".into()).unwrap();
+        assert_eq!(form.resolve_error, None);
+        form.focused_field = FormField::Name;
+        assert!(matches!(
+            form.handle_key_event(key(KeyCode::Enter)).unwrap(),
+            Some(RequestFormEvent::Confirm)
+        ));
+        assert_eq!(
+            request.read().unwrap().resolve,
+            Some("api.example.com:443:10.0.0.7".parse().unwrap())
+        );
+
+        let form = RequestForm::<RequestFormEdit>::new(
+            &colors,
+            Rc::new(RefCell::new(CollectionStore::default())),
+            request,
+        );
+        assert_eq!(form.request_resolve, "api.example.com:443:10.0.0.7");
+    }
+
+    #[test]
+    fn test_picking_the_http_version() {
+        let (store, request) = store_with_request(make_request("login", "/login"));
+        let colors = hac_colors::Colors::default();
+        let mut form = RequestForm::<RequestFormEdit>::new(&colors, store, request.clone());
+        assert_eq!(form.request_http_version, None);
+
+        form.focused_field = FormField::Resolve;
+        form.handle_key_event(key(KeyCode::Tab)).unwrap();
+        assert_eq!(form.focused_field, FormField::HttpVersion);
+        form.handle_key_event(key(KeyCode::Char(' '))).unwrap();
+        form.handle_key_event(key(KeyCode::Right)).unwrap();
+        assert_eq!(form.request_http_version, Some(HttpVersion::Http2));
+
+        form.handle_key_event(key(KeyCode::Enter)).unwrap();
+        assert_eq!(
+            request.read().unwrap().http_version,
+            Some(HttpVersion::Http2)
+        );
+    }
 }
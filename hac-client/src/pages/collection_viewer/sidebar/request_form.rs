@@ -1,8 +1,12 @@
-use hac_core::collection::types::{Request, RequestMethod};
+use hac_core::collection::types::{
+    is_token_char, parse_tags, HostOverride, HttpVersion, Request, RequestMethod,
+};
+use hac_core::templates::RequestTemplate;
 
 use crate::ascii::LOGO_ASCII;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
 use crate::pages::collection_viewer::sidebar::select_request_parent::SelectRequestParent;
+use crate::pages::collection_viewer::sidebar::select_request_template::SelectRequestTemplate;
 use crate::pages::input::Input;
 use crate::pages::overlay::make_overlay;
 use crate::pages::Renderable;
@@ -12,8 +16,9 @@ use std::ops::{Add, Div, Sub};
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::Stylize;
+use ratatui::style::{Style, Stylize};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
@@ -29,6 +34,9 @@ pub enum FormField {
     Name,
     Method,
     Parent,
+    Tags,
+    Resolve,
+    HttpVersion,
 }
 
 impl FormField {
@@ -36,15 +44,21 @@ impl FormField {
         match self {
             FormField::Name => FormField::Method,
             FormField::Method => FormField::Parent,
-            FormField::Parent => FormField::Name,
+            FormField::Parent => FormField::Tags,
+            FormField::Tags => FormField::Resolve,
+            FormField::Resolve => FormField::HttpVersion,
+            FormField::HttpVersion => FormField::Name,
         }
     }
 
     pub fn prev(&self) -> Self {
         match self {
-            FormField::Name => FormField::Parent,
+            FormField::Name => FormField::HttpVersion,
             FormField::Method => FormField::Name,
             FormField::Parent => FormField::Method,
+            FormField::Tags => FormField::Parent,
+            FormField::Resolve => FormField::Tags,
+            FormField::HttpVersion => FormField::Resolve,
         }
     }
 }
@@ -71,6 +85,17 @@ pub struct RequestForm<'rf, State = RequestFormCreate> {
     /// lifetimes or to Rc our way to hell, along with it we also store the name
     /// for displaying purposes
     pub parent_dir: Option<(String, String)>,
+    /// tags of the request as typed, separated by spaces or commas
+    pub request_tags: String,
+    /// tags already used on the collection, offered as completions
+    pub known_tags: Vec<String>,
+    /// a fixed address for the host of this request alone, typed as
+    /// `host:port:address`
+    pub request_resolve: String,
+    /// why the typed address override was refused
+    pub resolve_error: Option<String>,
+    /// `None` lets the server negotiate it
+    pub request_http_version: Option<HttpVersion>,
     /// which form field is currently focused, so we can direct interactions
     /// accordingly
     pub focused_field: FormField,
@@ -79,6 +104,10 @@ pub struct RequestForm<'rf, State = RequestFormCreate> {
     /// parent selector is the form used to select a parent for the current edited
     /// or created request
     pub parent_selector: SelectRequestParent<'rf>,
+    /// only used when creating a request, the template it starts from
+    pub template: Option<RequestTemplate>,
+    /// picks the template a new request starts from
+    pub template_selector: SelectRequestTemplate<'rf>,
     /// when the user tries to select a parent for a given request but there are
     /// no directories on the collection, we use this timer to show a message for
     /// a short duration, alerting the user
@@ -93,11 +122,140 @@ impl<'rf, State> RequestForm<'rf, State> {
         self.request_method = RequestMethod::Get;
         self.focused_field = FormField::Name;
         self.parent_dir = None;
+        self.request_tags = String::default();
+        self.request_resolve = String::default();
+        self.resolve_error = None;
+        self.request_http_version = None;
+        self.template = None;
+    }
+
+    /// the address override typed on the form, `None` when it is empty or
+    /// not valid
+    pub fn resolve(&self) -> Option<HostOverride> {
+        self.request_resolve.trim().parse().ok()
+    }
+
+    /// whether the typed address override can be saved. When it can't, the
+    /// field is focused with the reason below it
+    pub fn check_resolve(&mut self) -> bool {
+        let typed = self.request_resolve.trim();
+        if typed.is_empty() {
+            return true;
+        }
+        match typed.parse::<HostOverride>() {
+            Ok(_) => true,
+            Err(error) => {
+                self.resolve_error = Some(error);
+                self.focused_field = FormField::Resolve;
+                false
+            }
+        }
+    }
+
+    /// typing on the address override, which forgets why it was refused
+    pub fn handle_resolve_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char(c) => self.request_resolve.push(c),
+            KeyCode::Backspace => _ = self.request_resolve.pop(),
+            _ => return,
+        }
+        self.resolve_error = None;
+    }
+
+    /// space or the arrows go through the versions a request can be sent
+    /// with
+    pub fn handle_http_version_key_event(&mut self, key_event: KeyEvent) {
+        if let KeyCode::Char(' ' | 'h' | 'l') | KeyCode::Left | KeyCode::Right = key_event.code {
+            self.request_http_version = HttpVersion::cycle(self.request_http_version);
+        }
+    }
+
+    /// what is left to type of the first known tag starting with the tag
+    /// being typed, tags that were typed already are not offered again
+    pub fn tag_completion(&self) -> Option<&str> {
+        let (typed, partial) = self
+            .request_tags
+            .rsplit_once(|c: char| c.eq(&',') || c.is_whitespace())
+            .unwrap_or(("", &self.request_tags));
+        let partial = partial.trim_start_matches('#').to_lowercase();
+        if partial.is_empty() {
+            return None;
+        }
+
+        let typed = parse_tags(typed);
+        self.known_tags
+            .iter()
+            .filter(|tag| !typed.contains(tag))
+            .find(|tag| tag.starts_with(&partial) && tag.ne(&&partial))
+            .map(|tag| &tag[partial.len()..])
+    }
+
+    /// completes the tag being typed, returning whether there was anything
+    /// to complete, so tab can move to the next field otherwise
+    pub fn complete_tag(&mut self) -> bool {
+        if self.focused_field.ne(&FormField::Tags) {
+            return false;
+        }
+        let Some(completion) = self.tag_completion().map(String::from) else {
+            return false;
+        };
+        self.request_tags.push_str(&completion);
+        self.request_tags.push(' ');
+        true
     }
 
     pub fn set_no_parent_timer(&mut self) {
         self.no_available_parent_timer = Some(std::time::Instant::now());
     }
+
+    /// custom methods need a name before the form can be confirmed
+    pub fn has_valid_method(&self) -> bool {
+        match &self.request_method {
+            RequestMethod::Other(method) => RequestMethod::is_valid_token(method),
+            _ => true,
+        }
+    }
+
+    /// picks the method with the number keys or by moving around. With the
+    /// custom method selected, keys that are valid on a method name type it
+    /// instead, so only the arrows move away from it
+    pub fn handle_method_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<()> {
+        if let RequestMethod::Other(method) = &mut self.request_method {
+            match key_event.code {
+                KeyCode::Char(c) if is_token_char(c) => {
+                    method.push(c);
+                    return Ok(());
+                }
+                KeyCode::Backspace => {
+                    method.pop();
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        match key_event.code {
+            KeyCode::Char(c @ '1'..='6') => {
+                self.request_method = (c.to_digit(10).unwrap() as usize).sub(1).try_into()?;
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.request_method = self.request_method.prev(),
+            KeyCode::Down | KeyCode::Char('j') => self.request_method = 5.try_into()?,
+            KeyCode::Up | KeyCode::Char('k') => self.request_method = 0.try_into()?,
+            KeyCode::Right | KeyCode::Char('l') => self.request_method = self.request_method.next(),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// tags used on the collection of the store, for completing them
+pub fn known_tags(collection_store: &Rc<RefCell<CollectionStore>>) -> Vec<String> {
+    collection_store
+        .borrow()
+        .get_collection()
+        .map(|collection| collection.borrow().tags().into_iter().collect())
+        .unwrap_or_default()
 }
 
 impl<'rf, State> Renderable for RequestForm<'rf, State> {
@@ -107,25 +265,26 @@ impl<'rf, State> Renderable for RequestForm<'rf, State> {
         let mut logo = LOGO_ASCII[self.logo_idx];
         let mut logo_size = logo.len() as u16;
         // adding size of the form + spacing + hint
-        let total_size = logo_size.add(11).add(2);
+        let total_size = logo_size.add(20).add(2);
 
         let size = frame.size();
         let mut size = Rect::new(
-            size.width.div(2).sub(32),
+            size.width.div(2).saturating_sub(32),
             size.height
                 .div(2)
                 .saturating_sub(logo_size.div(2))
                 .saturating_sub(6),
             65,
-            logo_size.add(12),
+            logo_size.add(21),
         );
 
         if total_size.ge(&frame.size().height) {
             logo = &[];
             logo_size = 0;
-            size.height = 12;
-            size.y = frame.size().height.div(2).saturating_sub(5);
+            size.height = 21;
+            size.y = frame.size().height.div(2).saturating_sub(10);
         }
+        let size = size.clamp(frame.size());
 
         if !logo.is_empty() {
             let logo = logo
@@ -137,48 +296,67 @@ impl<'rf, State> Renderable for RequestForm<'rf, State> {
             frame.render_widget(Paragraph::new(logo), logo_size);
         }
 
-        let mut name_input = Input::new(self.colors, "Name".into());
+        let name_title = match self.template.as_ref() {
+            Some(template) => format!("Name (from {})", template.name),
+            None => "Name".into(),
+        };
+        let mut name_input = Input::new(self.colors, name_title);
         let method_title = Paragraph::new("Method".fg(self.colors.normal.white));
-        let hint =
-            "[Confirm: Enter] [Cancel: Esc] [Switch: Tab] [Select: Space] [Remove Parent: <C-p>]";
+        let mut tags_input = Input::new(self.colors, "Tags".into())
+            .placeholder("separated by spaces, eg: smoke admin".into());
+        let mut resolve_input = Input::new(self.colors, "Resolve".into())
+            .placeholder("connect elsewhere, eg: api.example.com:443:10.0.0.7".into());
+        let hint = match self.focused_field {
+            FormField::Tags => "[Confirm: Enter] [Cancel: Esc] [Complete or Switch: Tab]",
+            FormField::HttpVersion => "[Confirm: Enter] [Cancel: Esc] [Switch: Tab] [Change: Space]",
+            _ => "[Confirm: Enter] [Cancel: Esc] [Switch: Tab] [Select: Space] [Remove Parent: <C-p>]",
+        };
         let hint_size = hint.len() as u16;
         let hint = Paragraph::new(hint.fg(self.colors.bright.black)).centered();
 
         if self.focused_field.eq(&FormField::Name) {
             name_input.focus();
         }
+        if self.focused_field.eq(&FormField::Tags) {
+            tags_input.focus();
+        }
+        if self.focused_field.eq(&FormField::Resolve) {
+            resolve_input.focus();
+        }
 
         let name_size = Rect::new(size.x, size.y.add(logo_size).add(1), size.width, 3);
         let method_title_size = Rect::new(size.x, name_size.y.add(3), size.width, 1);
         let methods_size = Rect::new(size.x, method_title_size.y.add(1), size.width, 3);
         let parent_size = Rect::new(size.x, methods_size.y.add(3), size.width, 3);
+        let tags_size = Rect::new(size.x, parent_size.y.add(3), size.width, 3);
+        let resolve_size = Rect::new(size.x, tags_size.y.add(3), size.width, 3);
+        let http_version_size = Rect::new(size.x, resolve_size.y.add(3), size.width, 3);
         let hint_size = Rect::new(
             frame.size().width.div(2).saturating_sub(hint_size.div(2)),
-            parent_size.y.add(4),
+            http_version_size.y.add(4),
             hint_size,
             1,
         );
 
         let methods_items = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints((0..5).map(|_| Constraint::Length(13)))
+            .constraints((0..6).map(|_| Constraint::Ratio(1, 6)))
             .split(methods_size);
 
-        let parent_name = if self.parent_dir.is_none() {
-            format!(
+        let parent_name = match self.parent_dir.as_ref() {
+            None => format!(
                 "{}None{}",
                 " ".repeat(parent_size.width.div(2).sub(2).into()),
                 " ".repeat(parent_size.width.div(2).sub(2).into())
             )
-            .fg(self.colors.bright.black)
-        } else {
-            format!(
+            .fg(self.colors.bright.black),
+            Some((_, name)) => format!(
                 "{}{}{}",
                 " ".repeat(parent_size.width.div(2).sub(2).into()),
-                self.parent_dir.as_ref().unwrap().1.clone(),
+                name,
                 " ".repeat(parent_size.width.div(2).sub(2).into())
             )
-            .fg(self.colors.normal.white)
+            .fg(self.colors.normal.white),
         };
         let parent = Paragraph::new(parent_name).centered().block(
             Block::default()
@@ -191,30 +369,82 @@ impl<'rf, State> Renderable for RequestForm<'rf, State> {
                 }),
         );
 
-        for (idx, method) in RequestMethod::iter().enumerate() {
-            let border_color = match (&self.request_method, &self.focused_field) {
-                (m, FormField::Method) if m.eq(method) => self.colors.normal.red,
-                (m, _) if m.eq(method) => self.colors.bright.blue,
-                _ => self.colors.bright.black,
+        let http_version = match self.request_http_version {
+            Some(version) => version.to_string().fg(self.colors.normal.white),
+            None => "negotiated".fg(self.colors.bright.black),
+        };
+        let http_version = Paragraph::new(http_version).centered().block(
+            Block::default()
+                .title("HTTP version".fg(self.colors.normal.white))
+                .borders(Borders::ALL)
+                .fg(if self.focused_field.eq(&FormField::HttpVersion) {
+                    self.colors.normal.red
+                } else {
+                    self.colors.bright.black
+                }),
+        );
+
+        let custom_method = match &self.request_method {
+            RequestMethod::Other(method) => method.clone(),
+            _ => String::default(),
+        };
+        let methods = RequestMethod::iter()
+            .cloned()
+            .chain(std::iter::once(RequestMethod::Other(custom_method)));
+        for (idx, method) in methods.enumerate() {
+            let border_style = match (&self.request_method, &self.focused_field) {
+                (m, FormField::Method) if m.eq(&method) => {
+                    self.colors.focus_border(self.colors.normal.red)
+                }
+                (m, _) if m.eq(&method) => self.colors.focus_border(self.colors.bright.blue),
+                _ => Style::default().fg(self.colors.bright.black),
+            };
+            let name = match &method {
+                RequestMethod::Other(name) if name.is_empty() => {
+                    "OTHER".fg(self.colors.bright.black)
+                }
+                method => method.to_string().fg(self.colors.normal.white),
             };
             let method = Paragraph::new(Line::from(vec![
-                format!(" {}", idx.add(1)).fg(self.colors.bright.black),
-                format!(
-                    " {}{}",
-                    method,
-                    " ".repeat(10.sub(method.to_string().len()))
-                )
-                .fg(self.colors.normal.white),
+                format!(" {} ", idx.add(1)).fg(self.colors.bright.black),
+                name,
             ]))
-            .block(Block::default().borders(Borders::ALL).fg(border_color));
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style),
+            );
             frame.render_widget(method, methods_items[idx]);
         }
 
         frame.render_stateful_widget(name_input, name_size, &mut self.request_name);
         frame.render_widget(method_title, method_title_size);
         frame.render_widget(parent, parent_size);
+        frame.render_stateful_widget(tags_input, tags_size, &mut self.request_tags);
+        frame.render_stateful_widget(resolve_input, resolve_size, &mut self.request_resolve);
+        frame.render_widget(http_version, http_version_size);
+        if let Some(completion) = self
+            .tag_completion()
+            .filter(|_| self.focused_field.eq(&FormField::Tags))
+        {
+            let typed = self.request_tags.chars().count() as u16;
+            let completion_size = Rect::new(
+                tags_size.x.add(typed).add(1),
+                tags_size.y.add(1),
+                tags_size.width.saturating_sub(typed.add(2)),
+                1,
+            );
+            frame.render_widget(
+                Paragraph::new(completion.fg(self.colors.bright.black)),
+                completion_size,
+            );
+        }
 
-        if self
+        if let Some(error) = self.resolve_error.as_ref() {
+            let error = Paragraph::new(error.clone().fg(self.colors.normal.red)).centered();
+            let error_size = Rect::new(size.x, hint_size.y, size.width, 1);
+            frame.render_widget(error, error_size);
+        } else if self
             .no_available_parent_timer
             .is_some_and(|timer| timer.elapsed().as_secs().le(&3))
         {
@@ -244,6 +474,26 @@ impl<'rf, State> Renderable for RequestForm<'rf, State> {
             );
         }
 
+        if self.focused_field.eq(&FormField::Tags) {
+            frame.set_cursor(
+                tags_size
+                    .x
+                    .add(self.request_tags.chars().count() as u16)
+                    .add(1),
+                tags_size.y.add(1),
+            );
+        }
+
+        if self.focused_field.eq(&FormField::Resolve) {
+            frame.set_cursor(
+                resolve_size
+                    .x
+                    .add(self.request_resolve.chars().count() as u16)
+                    .add(1),
+                resolve_size.y.add(1),
+            );
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,172 @@
+use hac_core::templates::RequestTemplate;
+
+use crate::ascii::LOGO_ASCII;
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Mul, Sub};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rand::Rng;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SelectRequestTemplateEvent {
+    /// user picked what the request starts from, `None` being a blank request
+    Confirm(Option<Box<RequestTemplate>>),
+    /// user closed the picker, the request starts blank
+    Cancel,
+}
+
+/// lists the saved templates when creating a request, the first entry is
+/// always a blank request
+#[derive(Debug)]
+pub struct SelectRequestTemplate<'srt> {
+    colors: &'srt hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    logo_idx: usize,
+}
+
+impl<'srt> SelectRequestTemplate<'srt> {
+    pub fn new(
+        colors: &'srt hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        SelectRequestTemplate {
+            colors,
+            collection_store,
+            selected: 0,
+            logo_idx: rand::thread_rng().gen_range(0..LOGO_ASCII.len()),
+        }
+    }
+}
+
+impl Renderable for SelectRequestTemplate<'_> {
+    fn draw(&mut self, frame: &mut Frame, _: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let templates = self.collection_store.borrow().get_templates();
+
+        let mut logo = LOGO_ASCII[self.logo_idx];
+        let size = frame.size();
+        let mut logo_size = logo.len() as u16;
+
+        // if the logo makes the screen have 10 or less height, we hide it
+        if size.height.saturating_sub(logo_size).le(&10) {
+            logo = &[];
+            logo_size = 0;
+        }
+
+        let size = Rect::new(
+            size.width.div(2).saturating_sub(25),
+            size.y.add(4),
+            50,
+            size.height.saturating_sub(4),
+        );
+
+        if !logo.is_empty() {
+            let logo_size = Rect::new(size.x, size.y, size.width, logo_size);
+            let logo = logo
+                .iter()
+                .map(|line| Line::from(line.fg(self.colors.normal.red)).centered())
+                .collect::<Vec<_>>();
+
+            frame.render_widget(Paragraph::new(logo), logo_size);
+        }
+
+        let header = Paragraph::new("Start from a template".fg(self.colors.normal.yellow).bold());
+        let header_size = Rect::new(size.x, size.y.add(logo_size).add(1), size.width, 2);
+        frame.render_widget(header, header_size);
+
+        let entries = std::iter::once(Line::from("blank request".fg(self.colors.bright.black)))
+            .chain(templates.iter().map(|template| {
+                Line::from(vec![
+                    format!("{} ", template.method).fg(self.colors.normal.blue),
+                    template.name.clone().fg(self.colors.normal.white),
+                ])
+            }))
+            .collect::<Vec<_>>();
+
+        let list_y = header_size.y.add(2);
+        let amount_on_view = size.bottom().saturating_sub(list_y).div(2) as usize;
+        let scroll = self
+            .selected
+            .saturating_sub(amount_on_view.saturating_sub(1));
+
+        for (idx, entry) in entries
+            .into_iter()
+            .enumerate()
+            .skip(scroll)
+            .take(amount_on_view)
+        {
+            let entry = match idx.eq(&self.selected) {
+                true => Line::from(
+                    std::iter::once("> ".fg(self.colors.normal.red))
+                        .chain(
+                            entry
+                                .spans
+                                .into_iter()
+                                .map(|span| span.fg(self.colors.normal.red)),
+                        )
+                        .collect::<Vec<_>>(),
+                ),
+                false => Line::from(
+                    std::iter::once("  ".into())
+                        .chain(entry.spans)
+                        .collect::<Vec<_>>(),
+                ),
+            };
+            let entry_size = Rect::new(
+                size.x,
+                list_y.add(idx.sub(scroll).mul(2) as u16),
+                size.width,
+                1,
+            );
+            frame.render_widget(entry, entry_size);
+        }
+
+        Ok(())
+    }
+}
+
+impl Eventful for SelectRequestTemplate<'_> {
+    type Result = SelectRequestTemplateEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(SelectRequestTemplateEvent::Cancel));
+        }
+
+        let templates = self.collection_store.borrow().get_templates();
+
+        match key_event.code {
+            KeyCode::Enter => {
+                let template = self
+                    .selected
+                    .checked_sub(1)
+                    .and_then(|idx| templates.get(idx))
+                    .cloned()
+                    .map(Box::new);
+                return Ok(Some(SelectRequestTemplateEvent::Confirm(template)));
+            }
+            KeyCode::Esc => return Ok(Some(SelectRequestTemplateEvent::Cancel)),
+            KeyCode::Down | KeyCode::Tab | KeyCode::Char('j') => {
+                self.selected = usize::min(self.selected.add(1), templates.len());
+            }
+            KeyCode::Up | KeyCode::BackTab | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
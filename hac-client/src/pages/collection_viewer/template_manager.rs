@@ -0,0 +1,319 @@
+use hac_core::templates::RequestTemplate;
+
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+use crate::pages::input::{self, Input};
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Sub};
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear};
+use ratatui::Frame;
+
+/// set of events `TemplateManager` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TemplateManagerEvent {
+    /// user pressed `Esc` while browsing the templates
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// what the name being typed is for
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Naming {
+    /// saving the selected request as a new template
+    New,
+    /// renaming the template at the index
+    Rename(usize),
+}
+
+/// lists the request templates, where the selected request can be saved as
+/// one and templates renamed or deleted
+#[derive(Debug)]
+pub struct TemplateManager<'tm> {
+    colors: &'tm hac_colors::Colors,
+    collection_store: Rc<RefCell<CollectionStore>>,
+    selected: usize,
+    naming: Option<Naming>,
+    name: String,
+}
+
+impl<'tm> TemplateManager<'tm> {
+    pub fn new(
+        colors: &'tm hac_colors::Colors,
+        collection_store: Rc<RefCell<CollectionStore>>,
+    ) -> Self {
+        TemplateManager {
+            colors,
+            collection_store,
+            selected: 0,
+            naming: None,
+            name: String::default(),
+        }
+    }
+
+    /// the name starts as the one of the request or template, as it is
+    /// usually a good starting point
+    fn start_naming(&mut self, naming: Naming) {
+        let store = self.collection_store.borrow();
+        let name = match naming {
+            Naming::New => store
+                .get_selected_request()
+                .map(|request| request.read().unwrap().name.clone()),
+            Naming::Rename(idx) => store
+                .get_templates()
+                .get(idx)
+                .map(|template| template.name.clone()),
+        };
+        drop(store);
+
+        if let Some(name) = name {
+            self.name = name;
+            self.naming = Some(naming);
+        }
+    }
+
+    fn finish_naming(&mut self, naming: Naming) {
+        let name = self.name.trim().to_string();
+        let mut store = self.collection_store.borrow_mut();
+        match naming {
+            Naming::New => {
+                let Some(request) = store.get_selected_request() else {
+                    return;
+                };
+                let template =
+                    RequestTemplate::from_request(name.clone(), &request.read().unwrap());
+                store.save_template(template);
+                self.selected = store
+                    .get_templates()
+                    .iter()
+                    .position(|template| template.name.eq(&name))
+                    .unwrap_or_default();
+            }
+            Naming::Rename(idx) => store.rename_template(idx, name),
+        }
+        self.naming = None;
+    }
+
+    fn handle_naming_key_event(&mut self, key_event: KeyEvent, naming: Naming) {
+        match key_event.code {
+            KeyCode::Esc => self.naming = None,
+            KeyCode::Enter if !self.name.trim().is_empty() => self.finish_naming(naming),
+            KeyCode::Backspace => _ = self.name.pop(),
+            KeyCode::Char(c) => self.name.push(c),
+            _ => {}
+        }
+    }
+
+    fn template_line(&self, idx: usize, template: &RequestTemplate) -> Line<'static> {
+        let (chevron, name_fg) = match idx.eq(&self.selected) {
+            true => ("> ", self.colors.normal.red),
+            false => ("  ", self.colors.normal.white),
+        };
+        Line::from(vec![
+            chevron.fg(self.colors.normal.red),
+            format!("{} ", template.method).fg(self.colors.normal.blue),
+            template.name.clone().fg(name_fg),
+        ])
+    }
+}
+
+impl Renderable for TemplateManager<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let width = u16::min(size.width.saturating_sub(4), 60);
+        let height = u16::min(size.height, 16);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.sub(height).div(2)),
+            width,
+            height,
+        );
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Request templates ".fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let input_height = if self.naming.is_some() { 3 } else { 0 };
+        let list_height = inner.height.saturating_sub(input_height).saturating_sub(1);
+        let templates = self.collection_store.borrow().get_templates();
+
+        if templates.is_empty() {
+            let empty = Line::from(
+                "no templates yet, s saves the selected request as one"
+                    .fg(self.colors.bright.black),
+            )
+            .centered();
+            frame.render_widget(
+                empty,
+                Rect::new(inner.x, inner.y, inner.width, 1).clamp(inner),
+            );
+        }
+
+        let scroll = self
+            .selected
+            .saturating_sub(usize::from(list_height).saturating_sub(1));
+        for (row, (idx, template)) in templates
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(list_height.into())
+            .enumerate()
+        {
+            let row_size = Rect::new(inner.x, inner.y.add(row as u16), inner.width, 1);
+            frame.render_widget(self.template_line(idx, template), row_size);
+        }
+
+        if self.naming.is_some() {
+            let input_size = Rect::new(inner.x, inner.y.add(list_height), inner.width, 3);
+            let mut input = Input::new(self.colors, "Template name".into());
+            input.focus();
+            let mut name = self.name.clone();
+            frame.render_stateful_widget(input, input_size.clamp(inner), &mut name);
+            frame.set_cursor(
+                input_size.x.add(self.name.chars().count().add(1) as u16),
+                input_size.y.add(1),
+            );
+        }
+
+        let hint = match self.naming {
+            Some(_) => "[enter -> confirm] [esc -> cancel]",
+            None => "[s -> save request] [r -> rename] [d -> delete] [esc -> close]",
+        };
+        let hint_size = Rect::new(inner.x, inner.bottom().saturating_sub(1), inner.width, 1);
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_size,
+        );
+
+        Ok(())
+    }
+}
+
+impl Eventful for TemplateManager<'_> {
+    type Result = TemplateManagerEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(TemplateManagerEvent::Quit));
+        }
+
+        if let Some(naming) = self.naming {
+            self.handle_naming_key_event(key_event, naming);
+            return Ok(None);
+        }
+
+        let total = self.collection_store.borrow().get_templates().len();
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(TemplateManagerEvent::Close)),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = usize::min(self.selected.add(1), total.saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('s') => self.start_naming(Naming::New),
+            KeyCode::Char('r') => self.start_naming(Naming::Rename(self.selected)),
+            KeyCode::Char('d') if self.selected.lt(&total) => {
+                self.collection_store
+                    .borrow_mut()
+                    .remove_template(self.selected);
+                self.selected = self.selected.min(total.saturating_sub(2));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        if self.naming.is_some() {
+            self.name.push_str(&input::single_line(&text));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_request, store_with_request};
+    use hac_core::collection::types::*;
+
+    fn press(manager: &mut TemplateManager, code: KeyCode) -> Option<TemplateManagerEvent> {
+        manager
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    fn names(store: &Rc<RefCell<CollectionStore>>) -> Vec<String> {
+        store
+            .borrow()
+            .get_templates()
+            .into_iter()
+            .map(|template| template.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_saving_renaming_and_deleting_templates() {
+        let colors = hac_colors::Colors::default();
+        let request = Request {
+            method: RequestMethod::Post,
+            headers: Some(vec![HeaderMap {
+                pair: ("Accept".into(), "application/json".into()),
+                enabled: true,
+            }]),
+            auth_method: Some(AuthMethod::Bearer),
+            body: Some("{}".into()),
+            body_type: Some(BodyType::Json),
+            ..make_request("create user", "/users")
+        };
+        let (store, _) = store_with_request(request);
+        let mut manager = TemplateManager::new(&colors, store.clone());
+
+        press(&mut manager, KeyCode::Char('s'));
+        assert_eq!(manager.name, "create user");
+        press(&mut manager, KeyCode::Enter);
+        press(&mut manager, KeyCode::Char('s'));
+        manager.handle_paste(" (copy)".into()).unwrap();
+        press(&mut manager, KeyCode::Enter);
+        assert_eq!(names(&store), vec!["create user", "create user (copy)"]);
+        assert_eq!(manager.selected, 1);
+
+        let template = store.borrow().get_templates()[0].clone();
+        assert_eq!(template.method, RequestMethod::Post);
+        assert_eq!(template.auth_method, Some(AuthMethod::Bearer));
+
+        press(&mut manager, KeyCode::Char('r'));
+        for _ in 0.." (copy)".len() {
+            press(&mut manager, KeyCode::Backspace);
+        }
+        press(&mut manager, KeyCode::Char('!'));
+        press(&mut manager, KeyCode::Enter);
+        assert_eq!(names(&store), vec!["create user", "create user!"]);
+
+        press(&mut manager, KeyCode::Char('d'));
+        assert_eq!(names(&store), vec!["create user"]);
+        assert_eq!(manager.selected, 0);
+
+        // esc leaves naming before it closes the manager
+        press(&mut manager, KeyCode::Char('r'));
+        assert_eq!(press(&mut manager, KeyCode::Esc), None);
+        assert_eq!(
+            press(&mut manager, KeyCode::Esc),
+            Some(TemplateManagerEvent::Close)
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+/// requests whose panes are remembered, the ones left the longest ago are
+/// forgotten first
+const MAX_VIEW_STATES: usize = 50;
+
+/// how the panes were left on each request visited during the session, so
+/// switching back to one doesn't scroll everything back to the top. It
+/// lives on the viewer, which is rebuilt whenever a collection is opened
+#[derive(Debug)]
+pub struct ViewStates<T> {
+    /// most recently left last
+    states: VecDeque<(String, T)>,
+    capacity: usize,
+}
+
+impl<T> Default for ViewStates<T> {
+    fn default() -> Self {
+        ViewStates {
+            states: VecDeque::new(),
+            capacity: MAX_VIEW_STATES,
+        }
+    }
+}
+
+impl<T> ViewStates<T> {
+    /// keeps how the panes were left on `request_id`, replacing whatever
+    /// was kept for it before
+    pub fn insert(&mut self, request_id: String, state: T) {
+        self.states.retain(|(id, _)| id.ne(&request_id));
+        if self.states.len().ge(&self.capacity) {
+            self.states.pop_front();
+        }
+        self.states.push_back((request_id, state));
+    }
+
+    pub fn get(&self, request_id: &str) -> Option<&T> {
+        self.states
+            .iter()
+            .find(|(id, _)| id.eq(request_id))
+            .map(|(_, state)| state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_the_longest_left_requests_are_forgotten_first() {
+        let mut states = ViewStates {
+            states: VecDeque::new(),
+            capacity: 2,
+        };
+
+        states.insert("a".into(), 1);
+        states.insert("b".into(), 2);
+        // leaving `a` again makes it the most recent one
+        states.insert("a".into(), 3);
+        states.insert("c".into(), 4);
+
+        assert_eq!(states.get("a"), Some(&3));
+        assert_eq!(states.get("b"), None);
+        assert_eq!(states.get("c"), Some(&4));
+        assert_eq!(states.states.len(), 2);
+    }
+}
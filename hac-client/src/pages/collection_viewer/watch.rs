@@ -0,0 +1,248 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Add;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// how often the files of a watch are looked at, walking a directory on
+/// every tick would be wasteful
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// walking stops after this many entries, so watching a huge directory by
+/// mistake doesn't stall the application
+const MAX_WATCHED_ENTRIES: usize = 10_000;
+
+/// what makes a watched request be sent again
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchTrigger {
+    Every(Duration),
+    /// a file, or any file under a directory, was modified
+    Changes(PathBuf),
+}
+
+impl WatchTrigger {
+    /// reads what was typed on the watch prompt, the first choice takes a
+    /// number of seconds and the second a path
+    pub fn parse(typed: &str, choice: usize) -> Result<Self, String> {
+        let typed = typed.trim();
+        match choice {
+            0 => match typed.trim_end_matches('s').parse::<u64>() {
+                Ok(secs) if secs.gt(&0) => Ok(WatchTrigger::Every(Duration::from_secs(secs))),
+                _ => Err(format!("{typed} is not a whole number of seconds")),
+            },
+            _ => match Path::new(typed).exists() {
+                true => Ok(WatchTrigger::Changes(PathBuf::from(typed))),
+                false => Err(format!("{typed} doesn't exist")),
+            },
+        }
+    }
+}
+
+/// a request being sent over and over, like `watch curl` would. Nothing
+/// runs in the background, the viewer asks whether it is due on every tick,
+/// so dropping the watch is all it takes to stop it
+#[derive(Debug)]
+pub struct Watch {
+    pub request_id: String,
+    pub trigger: WatchTrigger,
+    next_check: Instant,
+    last_modified: Option<SystemTime>,
+    last_body: Option<u64>,
+    /// whether the last response had a different body than the one before
+    /// it, unknown until there are two of them
+    pub body_changed: Option<bool>,
+}
+
+impl Watch {
+    pub fn new(request_id: String, trigger: WatchTrigger, now: Instant) -> Self {
+        let (next_check, last_modified) = match &trigger {
+            WatchTrigger::Every(interval) => (now.add(*interval), None),
+            WatchTrigger::Changes(path) => (now.add(POLL_INTERVAL), latest_modification(path)),
+        };
+
+        Watch {
+            request_id,
+            trigger,
+            next_check,
+            last_modified,
+            last_body: None,
+            body_changed: None,
+        }
+    }
+
+    /// whether the request should be sent again, either the interval ran
+    /// out or something changed since the last time
+    pub fn is_due(&mut self, now: Instant) -> bool {
+        if now.lt(&self.next_check) {
+            return false;
+        }
+
+        match &self.trigger {
+            WatchTrigger::Every(interval) => {
+                self.next_check = now.add(*interval);
+                true
+            }
+            WatchTrigger::Changes(path) => {
+                self.next_check = now.add(POLL_INTERVAL);
+                let modified = latest_modification(path);
+                let changed = modified.ne(&self.last_modified);
+                self.last_modified = modified;
+                changed
+            }
+        }
+    }
+
+    /// keeps track of the body of every response, to tell whether it
+    /// changed since the previous one
+    pub fn record_body(&mut self, body: &[u8]) {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if let Some(last_body) = self.last_body {
+            self.body_changed = Some(last_body.ne(&hash));
+        }
+        self.last_body = Some(hash);
+    }
+
+    /// what is shown on the status bar, eg: `watching every 5s, next in 3s`
+    pub fn label(&self, now: Instant) -> String {
+        match &self.trigger {
+            WatchTrigger::Every(interval) => {
+                // rounded up, so it never reads `next in 0s` for a whole second
+                let remaining = self.next_check.saturating_duration_since(now);
+                let remaining = remaining.as_millis().div_ceil(1000);
+                format!(
+                    "watching every {}s, next in {remaining}s",
+                    interval.as_secs()
+                )
+            }
+            WatchTrigger::Changes(path) => {
+                format!("watching {} for changes", path.to_string_lossy())
+            }
+        }
+    }
+}
+
+/// the latest time `path` or anything under it was modified. Hidden files
+/// and directories are skipped, as `.git` changes all the time
+fn latest_modification(path: &Path) -> Option<SystemTime> {
+    let mut latest = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut pending = vec![path.to_path_buf()];
+    let mut seen = 0;
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            seen += 1;
+            if seen.gt(&MAX_WATCHED_ENTRIES) {
+                return latest;
+            }
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            }
+            latest = latest.max(metadata.modified().ok());
+        }
+    }
+
+    latest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_what_was_typed() {
+        assert_eq!(
+            WatchTrigger::parse(" 5s ", 0),
+            Ok(WatchTrigger::Every(Duration::from_secs(5)))
+        );
+        assert_eq!(
+            WatchTrigger::parse("0", 0),
+            Err("0 is not a whole number of seconds".into())
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        assert_eq!(
+            WatchTrigger::parse(&path, 1),
+            Ok(WatchTrigger::Changes(dir.path().to_path_buf()))
+        );
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        assert_eq!(
+            WatchTrigger::parse(&missing, 1),
+            Err(format!("{missing} doesn't exist"))
+        );
+    }
+
+    #[test]
+    fn test_intervals_count_down_to_the_next_send() {
+        let now = Instant::now();
+        let mut watch = Watch::new(
+            "list".into(),
+            WatchTrigger::Every(Duration::from_secs(5)),
+            now,
+        );
+
+        assert!(!watch.is_due(now.add(Duration::from_secs(2))));
+        assert_eq!(
+            watch.label(now.add(Duration::from_millis(2500))),
+            "watching every 5s, next in 3s"
+        );
+        assert!(watch.is_due(now.add(Duration::from_secs(5))));
+        // the interval starts over from the send
+        assert!(!watch.is_due(now.add(Duration::from_secs(6))));
+        assert!(watch.is_due(now.add(Duration::from_secs(10))));
+    }
+
+    #[test]
+    fn test_changes_under_a_directory_are_noticed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        let now = Instant::now();
+        let mut watch = Watch::new(
+            "list".into(),
+            WatchTrigger::Changes(dir.path().to_path_buf()),
+            now,
+        );
+
+        // nothing changed yet, and the files are not looked at too often
+        assert!(!watch.is_due(now));
+        assert!(!watch.is_due(now.add(POLL_INTERVAL)));
+
+        let file = std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("src/main.rs"))
+            .unwrap();
+        file.set_modified(SystemTime::now().add(Duration::from_secs(60)))
+            .unwrap();
+        assert!(!watch.is_due(now.add(POLL_INTERVAL)));
+        assert!(watch.is_due(now.add(POLL_INTERVAL * 2)));
+        assert!(!watch.is_due(now.add(POLL_INTERVAL * 3)));
+    }
+
+    #[test]
+    fn test_body_changes_between_responses() {
+        let mut watch = Watch::new(
+            "list".into(),
+            WatchTrigger::Every(Duration::from_secs(1)),
+            Instant::now(),
+        );
+
+        watch.record_body(b"{\"count\": 1}");
+        assert_eq!(watch.body_changed, None);
+        watch.record_body(b"{\"count\": 1}");
+        assert_eq!(watch.body_changed, Some(false));
+        watch.record_body(b"{\"count\": 2}");
+        assert_eq!(watch.body_changed, Some(true));
+    }
+}
@@ -1,30 +1,140 @@
-use ratatui::buffer::Buffer;
+use crate::pages::Overlay;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::Rect;
-use ratatui::style::{Style, Stylize};
-use ratatui::text::Line;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Widget, Wrap};
+use ratatui::Frame;
+
+/// a choice on a confirmation popup, `value` is what the caller gets back
+/// when the button is activated
+#[derive(Debug, Clone)]
+pub struct ConfirmButton<T> {
+    label: String,
+    accelerator: char,
+    color: Color,
+    value: T,
+}
+
+impl<T> ConfirmButton<T> {
+    /// `accelerator` is the key that activates the button directly, the
+    /// label is expected to hint it, eg: `(y)es`
+    pub fn new<S>(label: S, accelerator: char, color: Color, value: T) -> Self
+    where
+        S: Into<String>,
+    {
+        ConfirmButton {
+            label: label.into(),
+            accelerator,
+            color,
+            value,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfirmPopupEvent<T> {
+    /// one of the buttons was activated, holding its value
+    Confirm(T),
+    /// the popup was dismissed without choosing anything
+    Close,
+    Quit,
+}
 
-pub struct ConfirmPopup<'a> {
+/// asks the user a question and lets them pick one of the buttons, either
+/// by moving the focus with `h/l` or tab and pressing enter, or with the
+/// accelerator of a button
+#[derive(Debug)]
+pub struct ConfirmPopup<'a, T> {
     message: String,
+    /// lines shown as they are between the message and the buttons
+    details: Vec<String>,
     colors: &'a hac_colors::Colors,
+    buttons: Vec<ConfirmButton<T>>,
+    selected: usize,
 }
 
-impl<'a> ConfirmPopup<'a> {
-    pub fn new(message: String, colors: &'a hac_colors::Colors) -> Self {
-        ConfirmPopup { message, colors }
+impl<'a, T: Clone> ConfirmPopup<'a, T> {
+    pub fn new(
+        message: String,
+        colors: &'a hac_colors::Colors,
+        buttons: Vec<ConfirmButton<T>>,
+    ) -> Self {
+        ConfirmPopup {
+            message,
+            details: vec![],
+            colors,
+            buttons,
+            selected: 0,
+        }
+    }
+
+    /// moves the focus to the button at `idx`, out of bounds indexes are
+    /// ignored
+    pub fn with_selected(self, idx: usize) -> Self {
+        match idx.lt(&self.buttons.len()) {
+            true => ConfirmPopup {
+                selected: idx,
+                ..self
+            },
+            false => self,
+        }
+    }
+
+    /// lists `details` under the message, one per line, eg: the commands a
+    /// choice would run
+    pub fn with_details(self, details: Vec<String>) -> Self {
+        ConfirmPopup { details, ..self }
+    }
+
+    /// rows the details take on top of the message and the buttons, so the
+    /// popup can be made tall enough for them
+    pub fn details_height(&self) -> u16 {
+        match self.details.is_empty() {
+            true => 0,
+            false => self.details.len().saturating_add(1) as u16,
+        }
+    }
+
+    fn select_next(&mut self) {
+        if !self.buttons.is_empty() {
+            self.selected = (self.selected + 1) % self.buttons.len();
+        }
+    }
+
+    fn select_prev(&mut self) {
+        if !self.buttons.is_empty() {
+            self.selected = (self.selected + self.buttons.len() - 1) % self.buttons.len();
+        }
     }
 
     fn build_popup(&self) -> Paragraph<'_> {
-        let lines = vec![
-            self.message.clone().fg(self.colors.normal.yellow).into(),
-            "".into(),
-            Line::from(vec![
-                "(y)es".fg(self.colors.normal.green),
-                " ".into(),
-                "(n)o".fg(self.colors.normal.red),
-            ])
-            .centered(),
-        ];
+        let mut choices = vec![];
+        for (idx, button) in self.buttons.iter().enumerate() {
+            if idx.gt(&0) {
+                choices.push(Span::from(" "));
+            }
+            let label = match idx.eq(&self.selected) {
+                true => Span::styled(
+                    button.label.clone(),
+                    self.colors
+                        .highlight(self.colors.normal.black, button.color),
+                ),
+                false => button.label.clone().fg(button.color),
+            };
+            choices.push(label);
+        }
+
+        let mut lines = vec![self.message.clone().fg(self.colors.normal.yellow).into()];
+        if !self.details.is_empty() {
+            lines.push("".into());
+        }
+        for detail in self.details.iter() {
+            lines.push(detail.clone().fg(self.colors.normal.white).into());
+        }
+        lines.push("".into());
+        lines.push(Line::from(choices).centered());
         Paragraph::new(lines).wrap(Wrap { trim: true }).block(
             Block::default()
                 .borders(Borders::ALL)
@@ -35,14 +145,59 @@ impl<'a> ConfirmPopup<'a> {
     }
 }
 
-impl Widget for ConfirmPopup<'_> {
-    fn render(self, size: Rect, buf: &mut Buffer)
-    where
-        Self: Sized,
-    {
-        Clear.render(size, buf);
-        let popup = self.build_popup();
-        popup.render(size, buf);
+impl<'a> ConfirmPopup<'a, bool> {
+    /// the usual `(y)es (n)o` question, focusing `no` so an accidental enter
+    /// doesn't confirm anything
+    pub fn yes_no(message: String, colors: &'a hac_colors::Colors) -> Self {
+        let buttons = vec![
+            ConfirmButton::new("(y)es", 'y', colors.normal.green, true),
+            ConfirmButton::new("(n)o", 'n', colors.normal.red, false),
+        ];
+        ConfirmPopup::new(message, colors, buttons).with_selected(1)
+    }
+}
+
+impl<T: Clone> Overlay for ConfirmPopup<'_, T> {
+    type Result = ConfirmPopupEvent<T>;
+
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        Clear.render(size, frame.buffer_mut());
+        frame.render_widget(self.build_popup(), size);
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(ConfirmPopupEvent::Quit));
+        }
+
+        // accelerators go first, so a button can take `h` or `l` for itself.
+        // Chords like ctrl+y are never taken for a choice
+        let plain = matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        );
+        if let KeyCode::Char(c) = key_event.code {
+            let button = self.buttons.iter().find(|button| button.accelerator.eq(&c));
+            if let Some(button) = button.filter(|_| plain) {
+                return Ok(Some(ConfirmPopupEvent::Confirm(button.value.clone())));
+            }
+        }
+
+        match key_event.code {
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => self.select_prev(),
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => self.select_next(),
+            KeyCode::Enter => {
+                return Ok(self
+                    .buttons
+                    .get(self.selected)
+                    .map(|button| ConfirmPopupEvent::Confirm(button.value.clone())));
+            }
+            KeyCode::Esc => return Ok(Some(ConfirmPopupEvent::Close)),
+            _ => {}
+        }
+
+        Ok(None)
     }
 }
 
@@ -50,17 +205,21 @@ impl Widget for ConfirmPopup<'_> {
 mod tests {
     use super::*;
 
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
     #[test]
     fn test_build_with_correct_message() {
         let colors = hac_colors::Colors::default();
-        let popup = ConfirmPopup::new("my confirmation message".into(), &colors);
+        let popup = ConfirmPopup::yes_no("my confirmation message".into(), &colors);
         let lines = vec![
             "my confirmation message".fg(colors.normal.yellow).into(),
             "".into(),
             Line::from(vec![
                 "(y)es".fg(colors.normal.green),
                 " ".into(),
-                "(n)o".fg(colors.normal.red),
+                "(n)o".fg(colors.normal.black).bg(colors.normal.red),
             ])
             .centered(),
         ];
@@ -76,4 +235,127 @@ mod tests {
 
         assert_eq!(expected, content);
     }
+
+    #[test]
+    fn test_details_go_between_the_message_and_the_buttons() {
+        let colors = hac_colors::Colors::default();
+        let popup = ConfirmPopup::yes_no("run these?".into(), &colors)
+            .with_details(vec!["vault read token".into(), "tee -a log".into()]);
+
+        let lines = vec![
+            "run these?".fg(colors.normal.yellow).into(),
+            "".into(),
+            "vault read token".fg(colors.normal.white).into(),
+            "tee -a log".fg(colors.normal.white).into(),
+            "".into(),
+            Line::from(vec![
+                "(y)es".fg(colors.normal.green),
+                " ".into(),
+                "(n)o".fg(colors.normal.black).bg(colors.normal.red),
+            ])
+            .centered(),
+        ];
+        let expected = Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.bright.black))
+                .padding(Padding::new(2, 2, 1, 1))
+                .bg(colors.normal.black),
+        );
+
+        assert_eq!(popup.build_popup(), expected);
+        assert_eq!(popup.details_height(), 3);
+    }
+
+    #[test]
+    fn test_focus_moves_and_wraps_around() {
+        let colors = hac_colors::Colors::default();
+        let buttons = vec![
+            ConfirmButton::new("(y)es", 'y', colors.normal.green, 0),
+            ConfirmButton::new("(a)lways", 'a', colors.normal.blue, 1),
+            ConfirmButton::new("(n)o", 'n', colors.normal.red, 2),
+        ];
+        let mut popup = ConfirmPopup::new("send it?".into(), &colors, buttons);
+
+        popup.handle_key_event(key(KeyCode::Char('l'))).unwrap();
+        popup.handle_key_event(key(KeyCode::Tab)).unwrap();
+        popup.handle_key_event(key(KeyCode::Tab)).unwrap();
+        assert_eq!(popup.selected, 0);
+
+        popup.handle_key_event(key(KeyCode::Char('h'))).unwrap();
+        assert_eq!(popup.selected, 2);
+        popup.handle_key_event(key(KeyCode::BackTab)).unwrap();
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Enter)).unwrap(),
+            Some(ConfirmPopupEvent::Confirm(1))
+        );
+    }
+
+    #[test]
+    fn test_accelerators_and_dismissal() {
+        let colors = hac_colors::Colors::default();
+        let mut popup = ConfirmPopup::yes_no("delete it?".into(), &colors);
+
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Enter)).unwrap(),
+            Some(ConfirmPopupEvent::Confirm(false))
+        );
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Char('y'))).unwrap(),
+            Some(ConfirmPopupEvent::Confirm(true))
+        );
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Char('x'))).unwrap(),
+            None
+        );
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Esc)).unwrap(),
+            Some(ConfirmPopupEvent::Close)
+        );
+        assert_eq!(
+            popup
+                .handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+                .unwrap(),
+            Some(ConfirmPopupEvent::Quit)
+        );
+        assert_eq!(
+            popup
+                .handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            popup
+                .handle_key_event(KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_accelerators_win_over_moving_the_focus() {
+        let colors = hac_colors::Colors::default();
+        let buttons = vec![
+            ConfirmButton::new("(l)ater", 'l', colors.normal.blue, 0),
+            ConfirmButton::new("(n)ow", 'n', colors.normal.green, 1),
+        ];
+        let mut popup = ConfirmPopup::new("update?".into(), &colors, buttons);
+
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Char('l'))).unwrap(),
+            Some(ConfirmPopupEvent::Confirm(0))
+        );
+        // keys no button takes still move the focus
+        assert_eq!(
+            popup.handle_key_event(key(KeyCode::Char('h'))).unwrap(),
+            None
+        );
+        assert_eq!(popup.selected, 1);
+        assert_eq!(
+            popup
+                .handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::SHIFT))
+                .unwrap(),
+            Some(ConfirmPopupEvent::Confirm(1))
+        );
+    }
 }
@@ -1,7 +1,16 @@
+use hac_core::command::ErrorReport;
+
 use ratatui::buffer::Buffer;
-use ratatui::layout::{Constraint, Direction, Flex, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Flex, Layout, Rect};
 use ratatui::style::{Style, Stylize};
-use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Widget, Wrap};
+use ratatui::text::Line;
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, StatefulWidget, Widget};
+
+/// borders, padding, the confirmation line and the blank line above it take
+/// this many rows, the rest of the popup is for the message
+const CHROME_HEIGHT: u16 = 6;
+const CHROME_WIDTH: u16 = 6;
 
 #[derive(Debug, PartialEq)]
 pub struct ErrorPopupLayout {
@@ -9,122 +18,254 @@ pub struct ErrorPopupLayout {
     confirmation_pane: Rect,
 }
 
+#[derive(Debug, Default, PartialEq)]
+pub struct ErrorPopupState {
+    scroll: usize,
+    show_details: bool,
+}
+
+impl ErrorPopupState {
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+    }
+}
+
 pub struct ErrorPopup<'a> {
-    message: String,
+    report: &'a ErrorReport,
     colors: &'a hac_colors::Colors,
 }
 
 impl<'a> ErrorPopup<'a> {
-    pub fn new(message: String, colors: &'a hac_colors::Colors) -> Self {
-        ErrorPopup { message, colors }
+    pub fn new(report: &'a ErrorReport, colors: &'a hac_colors::Colors) -> Self {
+        ErrorPopup { report, colors }
     }
 
-    fn build_popup(&self) -> (Paragraph<'_>, Paragraph<'_>) {
-        let message = Paragraph::new(self.message.clone().fg(self.colors.normal.red))
-            .wrap(Wrap { trim: true });
+    /// every line of the popup content already wrapped to `width`, the
+    /// causes are only included when details are shown
+    fn build_lines(&self, width: u16, show_details: bool) -> Vec<Line<'static>> {
+        let mut lines = wrap_text(&self.report.message, width.into())
+            .into_iter()
+            .map(|line| Line::from(line.fg(self.colors.normal.red)))
+            .collect::<Vec<_>>();
+
+        if show_details && !self.report.causes.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("caused by:".fg(self.colors.bright.black)));
+            for cause in self.report.causes.iter() {
+                lines.extend(
+                    wrap_text(&format!("- {}", cause), width.into())
+                        .into_iter()
+                        .map(|line| Line::from(line.fg(self.colors.normal.yellow))),
+                );
+            }
+        }
+
+        lines
+    }
 
-        let confirmation = Paragraph::new("(O)k".fg(self.colors.normal.green).into_centered_line())
-            .wrap(Wrap { trim: true });
+    fn build_confirmation(&self, show_details: bool) -> Line<'static> {
+        let mut choices = vec!["(o)k".fg(self.colors.normal.green)];
+        if !self.report.causes.is_empty() {
+            let label = match show_details {
+                true => "(d)etails -",
+                false => "(d)etails +",
+            };
+            choices.push("  ".into());
+            choices.push(label.fg(self.colors.normal.yellow));
+        }
+        choices.push("  ".into());
+        choices.push("(y)ank".fg(self.colors.normal.blue));
 
-        (message, confirmation)
+        Line::from(choices).centered()
     }
 
-    fn layout(&self, size: &Rect) -> ErrorPopupLayout {
-        let size = Rect::new(
-            size.x + 2,
-            size.y + 2,
-            size.width.saturating_sub(4),
-            size.height.saturating_sub(4),
+    /// the popup grows with its content, up to the size it was given, and
+    /// stays vertically centered on it
+    fn layout(&self, size: &Rect, content_height: u16) -> (Rect, ErrorPopupLayout) {
+        let height = u16::min(content_height.saturating_add(CHROME_HEIGHT), size.height);
+        let popup = Rect::new(
+            size.x,
+            size.y + size.height.saturating_sub(height) / 2,
+            size.width,
+            height,
+        );
+
+        let inner = Rect::new(
+            popup.x + 3,
+            popup.y + 2,
+            popup.width.saturating_sub(CHROME_WIDTH),
+            popup.height.saturating_sub(4),
         );
 
         let [message_pane, confirmation_pane] = Layout::default()
             .direction(Direction::Vertical)
             .flex(Flex::SpaceBetween)
             .constraints([Constraint::Fill(1), Constraint::Length(1)])
-            .areas(size);
+            .areas(inner);
 
-        ErrorPopupLayout {
-            message_pane,
-            confirmation_pane,
-        }
+        (
+            popup,
+            ErrorPopupLayout {
+                message_pane,
+                confirmation_pane,
+            },
+        )
     }
 
-    fn build_container(&self) -> Block<'_> {
-        Block::default()
+    fn build_container(&self, scrollable: bool) -> Block<'_> {
+        let block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(self.colors.bright.black))
             .padding(Padding::new(2, 2, 1, 1))
-            .bg(self.colors.normal.black)
+            .bg(self.colors.normal.black);
+
+        match scrollable {
+            true => block.title(
+                Title::from(" j/k to scroll ".fg(self.colors.bright.black))
+                    .position(Position::Bottom)
+                    .alignment(Alignment::Right),
+            ),
+            false => block,
+        }
+    }
+}
+
+impl StatefulWidget for ErrorPopup<'_> {
+    type State = ErrorPopupState;
+
+    fn render(self, size: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let width = size.width.saturating_sub(CHROME_WIDTH);
+        let lines = self.build_lines(width, state.show_details);
+        let (popup, layout) = self.layout(&size, lines.len() as u16);
+
+        let visible = layout.message_pane.height as usize;
+        let scrollable = lines.len().gt(&visible);
+        state.scroll = usize::min(state.scroll, lines.len().saturating_sub(visible));
+
+        Clear.render(popup, buf);
+        self.build_container(scrollable).render(popup, buf);
+
+        let lines = lines
+            .into_iter()
+            .skip(state.scroll)
+            .take(visible)
+            .collect::<Vec<_>>();
+        Paragraph::new(lines).render(layout.message_pane, buf);
+        self.build_confirmation(state.show_details)
+            .render(layout.confirmation_pane, buf);
     }
 }
 
-impl Widget for ErrorPopup<'_> {
-    fn render(self, size: Rect, buf: &mut Buffer)
-    where
-        Self: Sized,
-    {
-        Clear.render(size, buf);
-        let layout = self.layout(&size);
-        let (message, confirmation) = self.build_popup();
-        let full_block = self.build_container();
-
-        full_block.render(size, buf);
-        message.render(layout.message_pane, buf);
-        confirmation.render(layout.confirmation_pane, buf);
+/// wraps the text on whitespace so no line is wider than `width`, words that
+/// don't fit a line by themselves are broken. Line breaks are kept
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = usize::max(width, 1);
+    let mut lines = vec![];
+
+    for paragraph in text.lines() {
+        let mut line = String::default();
+        for word in paragraph.split_whitespace() {
+            let line_len = line.chars().count();
+            let word_len = word.chars().count();
+
+            if line_len.gt(&0) && line_len + 1 + word_len <= width {
+                line.push(' ');
+                line.push_str(word);
+                continue;
+            }
+
+            if line_len.gt(&0) {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            let mut chars = word.chars().collect::<Vec<_>>();
+            while chars.len().gt(&width) {
+                lines.push(chars.drain(..width).collect());
+            }
+            line = chars.into_iter().collect();
+        }
+        lines.push(line);
     }
+
+    lines
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_build_with_correct_message() {
-        let colors = hac_colors::Colors::default();
-        let popup = ErrorPopup::new("my error message".into(), &colors);
-
-        let (message, confirmation) = popup.build_popup();
+    fn make_report() -> ErrorReport {
+        ErrorReport {
+            message: "failed to send request".into(),
+            causes: vec!["tls handshake failed".into()],
+        }
+    }
 
+    #[test]
+    fn test_wrap_text() {
         assert_eq!(
-            message,
-            Paragraph::new("my error message".fg(colors.normal.red)).wrap(Wrap { trim: true })
+            wrap_text("expected value at line 1 column 5", 12),
+            vec!["expected", "value at", "line 1", "column 5"]
         );
+        assert_eq!(wrap_text("first\nsecond", 20), vec!["first", "second"]);
+        assert_eq!(wrap_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
 
-        assert_eq!(
-            confirmation,
-            Paragraph::new("(O)k".fg(colors.normal.green).into_centered_line())
-                .wrap(Wrap { trim: true })
-        );
+    #[test]
+    fn test_details_are_toggled() {
+        let colors = hac_colors::Colors::default();
+        let report = make_report();
+        let popup = ErrorPopup::new(&report, &colors);
+
+        assert_eq!(popup.build_lines(40, false).len(), 1);
+        let lines = popup.build_lines(40, true);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3].to_string(), "- tls handshake failed");
     }
 
     #[test]
-    fn test_build_layout_correctly() {
+    fn test_popup_is_sized_by_its_content() {
         let colors = hac_colors::Colors::default();
-        let popup = ErrorPopup::new("my error message".into(), &colors);
-        let rect = Rect::new(0, 0, 10, 10);
-        let expected = ErrorPopupLayout {
-            message_pane: Rect::new(2, 2, 6, 5),
-            confirmation_pane: Rect::new(2, 7, 6, 1),
-        };
+        let report = make_report();
+        let popup = ErrorPopup::new(&report, &colors);
+        let rect = Rect::new(0, 0, 20, 20);
 
-        let layout = popup.layout(&rect);
+        let (popup_rect, layout) = popup.layout(&rect, 2);
+        assert_eq!(popup_rect, Rect::new(0, 6, 20, 8));
+        assert_eq!(
+            layout,
+            ErrorPopupLayout {
+                message_pane: Rect::new(3, 8, 14, 3),
+                confirmation_pane: Rect::new(3, 11, 14, 1),
+            }
+        );
 
-        assert_eq!(layout, expected);
+        let (popup_rect, _) = popup.layout(&rect, 100);
+        assert_eq!(popup_rect, rect);
     }
 
     #[test]
-    fn test_build_container_correctly() {
+    fn test_scroll_is_clamped_to_the_content() {
         let colors = hac_colors::Colors::default();
-        let popup = ErrorPopup::new("my error message".into(), &colors);
-
-        let expected = Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(colors.bright.black))
-            .padding(Padding::new(2, 2, 1, 1))
-            .bg(colors.normal.black);
+        let report = ErrorReport::from("word ".repeat(100));
+        let mut state = ErrorPopupState::default();
+        for _ in 0..200 {
+            state.scroll_down();
+        }
 
-        let block = popup.build_container();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 20, 12));
+        ErrorPopup::new(&report, &colors).render(buffer.area, &mut buffer, &mut state);
 
-        assert_eq!(expected, block);
+        // 100 words of 4 characters on 14 columns fit 3 per line, and 7 rows
+        // are visible at once
+        assert_eq!(state.scroll, 34 - 7);
     }
 }
@@ -0,0 +1,563 @@
+use hac_config::{Action, KeyAction};
+
+use crate::pages::overlay::make_overlay;
+use crate::pages::status_bar::Segment;
+use crate::pages::Overlay;
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph};
+use ratatui::Frame;
+
+/// a binding shown on the hint line at the bottom of a screen, like
+/// `[n -> new]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    keys: String,
+    label: String,
+    /// hints with lower priority are the first to go on narrow terminals
+    priority: u8,
+}
+
+impl Hint {
+    pub fn keys(&self) -> &str {
+        &self.keys
+    }
+
+    /// shows the current value of whatever the keys change next to the
+    /// label, eg: `[s -> sort: name]`
+    pub fn with_value(self, value: impl Display) -> Self {
+        Hint {
+            label: format!("{}: {value}", self.label),
+            ..self
+        }
+    }
+
+    pub fn segment(&self, color: Color) -> Segment {
+        Segment::left(self.to_string().fg(color)).with_priority(self.priority)
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} -> {}]", self.keys, self.label)
+    }
+}
+
+/// a group of keybindings shown under a title on the help overlay, the few
+/// that matter the most are also hinted at the bottom of the screen while
+/// the part of the screen they belong to is focused
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpSection {
+    title: String,
+    entries: Vec<(String, String)>,
+    hints: Vec<Hint>,
+}
+
+impl HelpSection {
+    pub fn new<S>(title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        HelpSection {
+            title: title.into(),
+            entries: vec![],
+            hints: vec![],
+        }
+    }
+
+    pub fn entry<K, D>(mut self, keys: K, description: D) -> Self
+    where
+        K: Into<String>,
+        D: Into<String>,
+    {
+        self.entries.push((keys.into(), description.into()));
+        self
+    }
+
+    pub fn hint<K, L>(mut self, keys: K, label: L, priority: u8) -> Self
+    where
+        K: Into<String>,
+        L: Into<String>,
+    {
+        self.hints.push(Hint {
+            keys: keys.into(),
+            label: label.into(),
+            priority,
+        });
+        self
+    }
+
+    /// hints whatever keys are bound to `action` on a section built from a
+    /// keymap, so rebinding it changes the hint too. Unbound actions are not
+    /// hinted at all
+    pub fn hint_action<L>(self, action: &Action, label: L, priority: u8) -> Self
+    where
+        L: Into<String>,
+    {
+        let description = describe_action(action);
+        let keys = self
+            .entries
+            .iter()
+            .filter(|(_, bound)| bound.eq(&description))
+            .map(|(keys, _)| keys.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        match keys.is_empty() {
+            true => self,
+            false => self.hint(keys, label, priority),
+        }
+    }
+
+    /// one entry for every binding on `keymap`, built from the keymap itself
+    /// so the help shows what is configured instead of the defaults
+    pub fn from_keymap<S>(title: S, keymap: &HashMap<String, KeyAction>) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut entries = vec![];
+        collect_bindings(keymap, String::default(), &mut entries);
+        entries.sort();
+
+        HelpSection {
+            title: title.into(),
+            entries,
+            hints: vec![],
+        }
+    }
+}
+
+fn collect_bindings(
+    keymap: &HashMap<String, KeyAction>,
+    prefix: String,
+    entries: &mut Vec<(String, String)>,
+) {
+    for (key, action) in keymap {
+        let keys = format!("{prefix}{}", display_key(key));
+        match action {
+            KeyAction::Simple(action) => entries.push((keys, describe_action(action))),
+            KeyAction::Multiple(actions) => entries.push((
+                keys,
+                actions
+                    .iter()
+                    .map(describe_action)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )),
+            KeyAction::Complex(keymap) => collect_bindings(keymap, keys, entries),
+        }
+    }
+}
+
+/// single characters are shown as they are, anything else, like `C-d` or
+/// `Esc`, is wrapped in angle brackets the way vim does it
+fn display_key(key: &str) -> String {
+    match key.chars().count() {
+        1 => key.to_string(),
+        _ => format!("<{key}>"),
+    }
+}
+
+/// turns the name of the action into words, `MoveToLineEnd` becomes
+/// `move to line end` and `InsertAtEOL` becomes `insert at eol`
+fn describe_action(action: &Action) -> String {
+    match action {
+        Action::EnterMode(mode) => format!("enter {} mode", mode.to_string().to_lowercase()),
+        Action::InsertChar(c) => format!("insert {c}"),
+        action => {
+            let name = format!("{action:?}");
+            let mut description = String::default();
+            let mut prev_lowercase = false;
+            for c in name.chars() {
+                if c.is_uppercase() && prev_lowercase {
+                    description.push(' ');
+                }
+                prev_lowercase = c.is_lowercase();
+                description.push(c.to_ascii_lowercase());
+            }
+            description
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HelpOverlayEvent {
+    Close,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum HelpLine<'s> {
+    Title(&'s str),
+    Entry(&'s str, &'s str),
+    Blank,
+}
+
+/// lists the keybindings of a screen grouped by section, splitting them in
+/// pages when they don't fit the popup
+#[derive(Debug)]
+pub struct HelpOverlay<'a> {
+    colors: &'a hac_colors::Colors,
+    sections: Vec<HelpSection>,
+    page: usize,
+    /// how many pages there were on the last draw, as that depends on the
+    /// size of the popup
+    total_pages: usize,
+}
+
+impl<'a> HelpOverlay<'a> {
+    pub fn new(colors: &'a hac_colors::Colors, sections: Vec<HelpSection>) -> Self {
+        HelpOverlay {
+            colors,
+            sections,
+            page: 0,
+            total_pages: 1,
+        }
+    }
+
+    /// the hints of the section under `title`, in the order they were given
+    pub fn hints(&self, title: &str) -> &[Hint] {
+        self.sections
+            .iter()
+            .find(|section| section.title.eq(title))
+            .map(|section| section.hints.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// goes back to the first page, so the overlay always opens at the top
+    pub fn reset(&mut self) {
+        self.page = 0;
+    }
+
+    fn lines(&self) -> Vec<HelpLine<'_>> {
+        let mut lines = vec![];
+        for (idx, section) in self.sections.iter().enumerate() {
+            if idx.gt(&0) {
+                lines.push(HelpLine::Blank);
+            }
+            lines.push(HelpLine::Title(&section.title));
+            lines.extend(
+                section
+                    .entries
+                    .iter()
+                    .map(|(keys, description)| HelpLine::Entry(keys, description)),
+            );
+        }
+        lines
+    }
+
+    /// splits the lines in pages of `height` lines, a section title is never
+    /// left alone at the bottom of a page and pages never start blank
+    fn paginate(&self, height: usize) -> Vec<Vec<HelpLine<'_>>> {
+        let height = usize::max(height, 1);
+        let mut pages = vec![];
+        let mut page = vec![];
+
+        for line in self.lines() {
+            let remaining = height.saturating_sub(page.len());
+            let needs_break = match line {
+                // the blank line, the title and at least one entry
+                HelpLine::Blank => remaining.lt(&3),
+                HelpLine::Title(_) => remaining.lt(&2),
+                HelpLine::Entry(..) => remaining.eq(&0),
+            };
+
+            if needs_break && !page.is_empty() {
+                pages.push(std::mem::take(&mut page));
+            }
+            if line.eq(&HelpLine::Blank) && page.is_empty() {
+                continue;
+            }
+            page.push(line);
+        }
+
+        if !page.is_empty() || pages.is_empty() {
+            pages.push(page);
+        }
+
+        pages
+    }
+
+    fn key_width(&self) -> usize {
+        self.sections
+            .iter()
+            .flat_map(|section| section.entries.iter())
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn build_line(&self, line: &HelpLine, key_width: usize) -> Line<'static> {
+        match line {
+            HelpLine::Title(title) => {
+                Line::from(title.to_string().fg(self.colors.normal.yellow).bold())
+            }
+            HelpLine::Entry(keys, description) => Line::from(vec![
+                format!("{keys:<key_width$}").fg(self.colors.bright.magenta),
+                format!("  - {description}").fg(self.colors.normal.white),
+            ]),
+            HelpLine::Blank => Line::default(),
+        }
+    }
+
+    fn build_footer(&self) -> Line<'static> {
+        let hint = match self.total_pages {
+            1 => String::from("press any key to go back"),
+            total => format!(
+                "page {}/{total} · h/l for more · any key to go back",
+                self.page + 1
+            ),
+        };
+        Line::from(hint.fg(self.colors.normal.magenta)).centered()
+    }
+}
+
+impl Overlay for HelpOverlay<'_> {
+    type Result = HelpOverlayEvent;
+
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .title(" help ".fg(self.colors.normal.white))
+            .title_bottom(
+                Line::from(format!(" hac v{} ", crate::VERSION).fg(self.colors.bright.black))
+                    .right_aligned(),
+            )
+            .padding(Padding::new(2, 2, 1, 0))
+            .bg(self.colors.normal.black);
+
+        let [content_pane, _, footer_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(block.inner(size));
+
+        let key_width = self.key_width();
+        let pages = self.paginate(content_pane.height.into());
+        let total_pages = pages.len();
+        let page = usize::min(self.page, total_pages - 1);
+        let lines = pages[page]
+            .iter()
+            .map(|line| self.build_line(line, key_width))
+            .collect::<Vec<_>>();
+
+        self.total_pages = total_pages;
+        self.page = page;
+        let footer = self.build_footer();
+
+        frame.render_widget(Clear, size);
+        frame.render_widget(block, size);
+        frame.render_widget(Paragraph::new(lines), content_pane);
+        frame.render_widget(footer, footer_pane);
+
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(HelpOverlayEvent::Quit));
+        }
+
+        if self.total_pages.gt(&1) {
+            match key_event.code {
+                KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => {
+                    self.page = usize::min(self.page + 1, self.total_pages - 1);
+                    return Ok(None);
+                }
+                KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => {
+                    self.page = self.page.saturating_sub(1);
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Some(HelpOverlayEvent::Close))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_sections() -> Vec<HelpSection> {
+        vec![
+            HelpSection::new("navigation")
+                .entry("j/<down>", "select item below")
+                .entry("k/<up>", "select item above"),
+            HelpSection::new("general")
+                .entry("?", "toggle this help window")
+                .entry("<C-c>", "quits the application"),
+        ]
+    }
+
+    fn lines_to_string(page: &[HelpLine]) -> Vec<String> {
+        page.iter()
+            .map(|line| match line {
+                HelpLine::Title(title) => title.to_string(),
+                HelpLine::Entry(keys, _) => keys.to_string(),
+                HelpLine::Blank => String::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_entries_are_aligned() {
+        let colors = hac_colors::Colors::default();
+        let help = HelpOverlay::new(&colors, make_sections());
+        let key_width = help.key_width();
+
+        let short = help.build_line(&HelpLine::Entry("?", "toggle this help window"), key_width);
+        let long = help.build_line(&HelpLine::Entry("j/<down>", "select item below"), key_width);
+        assert_eq!(short.to_string(), "?         - toggle this help window");
+        assert_eq!(long.to_string(), "j/<down>  - select item below");
+    }
+
+    #[test]
+    fn test_everything_fits_a_single_page() {
+        let colors = hac_colors::Colors::default();
+        let help = HelpOverlay::new(&colors, make_sections());
+
+        let pages = help.paginate(10);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(
+            lines_to_string(&pages[0]),
+            [
+                "navigation",
+                "j/<down>",
+                "k/<up>",
+                "",
+                "general",
+                "?",
+                "<C-c>"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_titles_are_never_left_alone() {
+        let colors = hac_colors::Colors::default();
+        let help = HelpOverlay::new(&colors, make_sections());
+
+        let pages = help.paginate(4);
+        assert_eq!(
+            pages.iter().map(|p| lines_to_string(p)).collect::<Vec<_>>(),
+            [
+                vec!["navigation", "j/<down>", "k/<up>"],
+                vec!["general", "?", "<C-c>"],
+            ]
+        );
+
+        let pages = help.paginate(2);
+        assert_eq!(pages.len(), 4);
+        assert!(pages.iter().all(|page| page.len().le(&2)));
+    }
+
+    #[test]
+    fn test_changing_pages() {
+        let colors = hac_colors::Colors::default();
+        let mut help = HelpOverlay::new(&colors, make_sections());
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(
+            help.handle_key_event(key(KeyCode::Char('l'))).unwrap(),
+            Some(HelpOverlayEvent::Close)
+        );
+
+        help.total_pages = 2;
+        assert_eq!(
+            help.handle_key_event(key(KeyCode::Char('l'))).unwrap(),
+            None
+        );
+        assert_eq!(help.handle_key_event(key(KeyCode::Right)).unwrap(), None);
+        assert_eq!(help.page, 1);
+        assert_eq!(
+            help.handle_key_event(key(KeyCode::Char('h'))).unwrap(),
+            None
+        );
+        assert_eq!(help.page, 0);
+        assert_eq!(
+            help.handle_key_event(key(KeyCode::Char('?'))).unwrap(),
+            Some(HelpOverlayEvent::Close)
+        );
+        assert_eq!(
+            help.handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+                .unwrap(),
+            Some(HelpOverlayEvent::Quit)
+        );
+    }
+
+    #[test]
+    fn test_sections_from_a_keymap() {
+        let config = hac_config::load_config();
+        let section = HelpSection::from_keymap("body editor", &config.editor_keys.normal);
+
+        let find = |keys: &str| {
+            section
+                .entries
+                .iter()
+                .find(|(k, _)| k.eq(keys))
+                .map(|(_, description)| description.as_str())
+        };
+        assert_eq!(find("gg"), Some("move to top"));
+        assert_eq!(find("dw"), Some("delete word"));
+        assert_eq!(find("<C-d>"), Some("page down"));
+        assert_eq!(find("o"), Some("insert line below, insert at eol"));
+        assert_eq!(find("i"), Some("enter insert mode"));
+    }
+
+    #[test]
+    fn test_hints_follow_the_keymap() {
+        let colors = hac_colors::Colors::default();
+        let mut config = hac_config::load_config();
+        config.editor_keys.normal.insert(
+            "a".into(),
+            KeyAction::Simple(Action::EnterMode(hac_config::EditorMode::Insert)),
+        );
+        config.editor_keys.normal.remove("u");
+
+        let help = HelpOverlay::new(
+            &colors,
+            vec![
+                HelpSection::new("list")
+                    .entry("n", "creates an item")
+                    .hint("n", "new", 2)
+                    .hint("s", "sort", 1),
+                HelpSection::from_keymap("body editor", &config.editor_keys.normal)
+                    .hint_action(
+                        &Action::EnterMode(hac_config::EditorMode::Insert),
+                        "insert",
+                        2,
+                    )
+                    .hint_action(&Action::Undo, "undo", 1),
+            ],
+        );
+
+        let hints = |title| {
+            help.hints(title)
+                .iter()
+                .map(Hint::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hints("list"), ["[n -> new]", "[s -> sort]"]);
+        assert_eq!(hints("body editor"), ["[a/i -> insert]"]);
+        assert!(hints("missing").is_empty());
+        assert_eq!(
+            help.hints("list")[1].clone().with_value("name").to_string(),
+            "[s -> sort: name]"
+        );
+    }
+}
@@ -37,7 +37,7 @@ impl<'a> Input<'a> {
 
     fn build_input(&self, value: String, size: Rect) -> Paragraph<'_> {
         let border_color = if self.focused {
-            Style::default().fg(self.colors.normal.red)
+            self.colors.focus_border(self.colors.normal.red)
         } else {
             Style::default().fg(self.colors.primary.hover)
         };
@@ -75,10 +75,26 @@ impl StatefulWidget for Input<'_> {
     }
 }
 
+/// pasted text for inputs that only hold a single line, every line break
+/// becomes a space so a trailing newline from the clipboard goes away
+pub fn single_line(text: &str) -> String {
+    text.lines().collect::<Vec<_>>().join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pastes_are_turned_into_a_single_line() {
+        assert_eq!(
+            single_line("http://localhost:3000\n"),
+            "http://localhost:3000"
+        );
+        assert_eq!(single_line("first\r\nsecond\nthird"), "first second third");
+        assert_eq!(single_line(""), "");
+    }
+
     #[test]
     fn test_build_input_with_placeholder_unfocused() {
         let colors = hac_colors::Colors::default();
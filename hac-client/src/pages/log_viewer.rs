@@ -0,0 +1,273 @@
+use crate::log_buffer::{LogBuffer, LogEntry};
+use crate::pages::overlay::make_overlay;
+use crate::pages::{Eventful, Renderable};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph};
+use ratatui::Frame;
+use tracing::Level;
+
+#[derive(Debug, PartialEq)]
+pub enum LogViewerEvent {
+    Close,
+    Quit,
+}
+
+/// shows the last log entries on top of whatever screen is open, so we don't
+/// have to tail the log file on another terminal
+#[derive(Debug)]
+pub struct LogViewer<'a> {
+    colors: &'a hac_colors::Colors,
+    buffer: LogBuffer,
+    log_path: String,
+    /// the most verbose level being shown
+    level: Level,
+    /// how many lines we are away from the bottom, staying at 0 follows new
+    /// entries as they come in
+    scroll: usize,
+    /// how many lines fit the popup on the last draw, used to clamp scrolling
+    page_height: usize,
+}
+
+impl<'a> LogViewer<'a> {
+    pub fn new(colors: &'a hac_colors::Colors, buffer: LogBuffer, log_path: String) -> Self {
+        LogViewer {
+            colors,
+            buffer,
+            log_path,
+            level: Level::DEBUG,
+            scroll: 0,
+            page_height: 0,
+        }
+    }
+
+    pub fn set_buffer(&mut self, buffer: LogBuffer) {
+        self.buffer = buffer;
+    }
+
+    /// goes back to the newest entries, so the viewer always opens following
+    /// the log
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+    }
+
+    fn next_level(&mut self) {
+        self.level = match self.level {
+            Level::ERROR => Level::WARN,
+            Level::WARN => Level::INFO,
+            Level::INFO => Level::DEBUG,
+            _ => Level::ERROR,
+        };
+        self.scroll = 0;
+    }
+
+    fn visible_entries(&self) -> Vec<LogEntry> {
+        self.buffer
+            .entries()
+            .into_iter()
+            .filter(|entry| entry.level.le(&self.level))
+            .collect()
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.visible_entries()
+            .len()
+            .saturating_sub(self.page_height)
+    }
+
+    fn level_color(&self, level: &Level) -> Color {
+        match *level {
+            Level::ERROR => self.colors.normal.red,
+            Level::WARN => self.colors.normal.yellow,
+            Level::INFO => self.colors.normal.green,
+            _ => self.colors.normal.blue,
+        }
+    }
+
+    fn build_line(&self, entry: &LogEntry) -> Line<'static> {
+        Line::from(vec![
+            format!("{:<5} ", entry.level).fg(self.level_color(&entry.level)),
+            format!("{}: ", entry.target).fg(self.colors.bright.black),
+            entry.message.clone().fg(self.colors.normal.white),
+        ])
+    }
+
+    fn build_footer(&self) -> Line<'static> {
+        let hint = format!(
+            "j/k scroll · g/G top/bottom · f level: {} · esc to go back",
+            self.level.as_str().to_lowercase()
+        );
+        Line::from(hint.fg(self.colors.normal.magenta)).centered()
+    }
+}
+
+impl Renderable for LogViewer<'_> {
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.primary.background, 0.2, frame);
+
+        let popup = Rect::new(
+            size.x.saturating_add(2),
+            size.y.saturating_add(1),
+            size.width.saturating_sub(4),
+            size.height.saturating_sub(2),
+        )
+        .clamp(size);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .title(format!(" logs · {} ", self.log_path).fg(self.colors.normal.white))
+            .padding(Padding::horizontal(1))
+            .bg(self.colors.normal.black);
+
+        let [content_pane, footer_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(block.inner(popup));
+
+        self.page_height = content_pane.height.into();
+        let entries = self.visible_entries();
+        self.scroll = usize::min(self.scroll, self.max_scroll());
+
+        let end = entries.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(self.page_height);
+        let lines = match entries.is_empty() {
+            true => vec![Line::from(
+                "nothing was logged yet".fg(self.colors.bright.black),
+            )],
+            false => entries[start..end]
+                .iter()
+                .map(|entry| self.build_line(entry))
+                .collect(),
+        };
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+        frame.render_widget(Paragraph::new(lines), content_pane);
+        frame.render_widget(self.build_footer(), footer_pane);
+
+        Ok(())
+    }
+}
+
+impl Eventful for LogViewer<'_> {
+    type Result = LogViewerEvent;
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(LogViewerEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.scroll = usize::min(self.scroll + 1, self.max_scroll())
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('g') => self.scroll = self.max_scroll(),
+            KeyCode::Char('G') => self.scroll = 0,
+            KeyCode::Char('f') => self.next_level(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(12) => {
+                return Ok(Some(LogViewerEvent::Close))
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn make_buffer() -> LogBuffer {
+        let buffer = LogBuffer::default();
+        for (idx, level) in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG]
+            .into_iter()
+            .cycle()
+            .take(40)
+            .enumerate()
+        {
+            buffer.push(LogEntry {
+                level,
+                target: String::from("hac_client"),
+                message: format!("entry {idx}"),
+            });
+        }
+        buffer
+    }
+
+    fn rendered(terminal: &Terminal<TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_follows_the_newest_entries_and_scrolls_back() {
+        let colors = hac_colors::Colors::default();
+        let mut viewer = LogViewer::new(&colors, make_buffer(), String::from("/tmp/hac.log"));
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+        let size = terminal.size().unwrap();
+
+        terminal.draw(|f| viewer.draw(f, size).unwrap()).unwrap();
+        let screen = rendered(&terminal);
+        assert!(screen.contains("/tmp/hac.log"));
+        assert!(screen.contains("entry 39"));
+        assert!(!screen.contains("entry 0 "));
+
+        viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE))
+            .unwrap();
+        terminal.draw(|f| viewer.draw(f, size).unwrap()).unwrap();
+        let screen = rendered(&terminal);
+        assert!(screen.contains("entry 0 "));
+        assert!(!screen.contains("entry 39"));
+    }
+
+    #[test]
+    fn test_cycling_the_level_filter() {
+        let colors = hac_colors::Colors::default();
+        let mut viewer = LogViewer::new(&colors, make_buffer(), String::from("/tmp/hac.log"));
+        let cycle = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE);
+
+        assert_eq!(viewer.visible_entries().len(), 40);
+
+        viewer.handle_key_event(cycle).unwrap();
+        let entries = viewer.visible_entries();
+        assert_eq!(entries.len(), 10);
+        assert!(entries.iter().all(|entry| entry.level.eq(&Level::ERROR)));
+
+        viewer.handle_key_event(cycle).unwrap();
+        assert_eq!(viewer.visible_entries().len(), 20);
+        viewer.handle_key_event(cycle).unwrap();
+        assert_eq!(viewer.visible_entries().len(), 30);
+        viewer.handle_key_event(cycle).unwrap();
+        assert_eq!(viewer.visible_entries().len(), 40);
+    }
+
+    #[test]
+    fn test_closing_and_quitting() {
+        let colors = hac_colors::Colors::default();
+        let mut viewer = LogViewer::new(&colors, LogBuffer::default(), String::new());
+
+        let close = viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(close, Some(LogViewerEvent::Close));
+
+        let quit = viewer
+            .handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(quit, Some(LogViewerEvent::Quit));
+    }
+}
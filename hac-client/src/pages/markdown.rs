@@ -0,0 +1,174 @@
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+
+/// renders the small subset of markdown that is useful on notes: headings,
+/// bullet lists, code fences, `**bold**` and `code` spans. Anything else is
+/// kept as plain text, so unknown syntax shows up as written instead of
+/// going missing
+pub fn render(text: &str, colors: &hac_colors::Colors) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+
+        if in_fence {
+            lines.push(Line::from(line.to_string().fg(colors.normal.green)));
+            continue;
+        }
+
+        if let Some(heading) = heading(trimmed) {
+            lines.push(Line::from(
+                heading.to_string().fg(colors.normal.yellow).bold(),
+            ));
+            continue;
+        }
+
+        let normal = Style::default().fg(colors.normal.white);
+        match bullet(trimmed) {
+            Some(item) => {
+                let indent = &line[..line.len() - trimmed.len()];
+                let mut spans = vec![Span::styled(
+                    format!("{indent}• "),
+                    Style::default().fg(colors.normal.magenta),
+                )];
+                spans.extend(inline(item, normal, colors));
+                lines.push(Line::from(spans));
+            }
+            None => lines.push(Line::from(inline(line, normal, colors))),
+        }
+    }
+
+    lines
+}
+
+fn heading(line: &str) -> Option<&str> {
+    let content = line.trim_start_matches('#');
+    let level = line.len() - content.len();
+    match (1..=6).contains(&level) && content.starts_with(' ') {
+        true => Some(content.trim()),
+        false => None,
+    }
+}
+
+fn bullet(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+}
+
+/// splits a line on `**bold**` and `code` spans, markers without a closing
+/// pair are left as they are
+fn inline(text: &str, normal: Style, colors: &hac_colors::Colors) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let (marker, style) = match rest {
+            _ if rest.starts_with("**") => ("**", normal.bold()),
+            _ if rest.starts_with('`') => ("`", Style::default().fg(colors.normal.green)),
+            _ => ("", normal),
+        };
+
+        let closed = (!marker.is_empty())
+            .then(|| rest[marker.len()..].find(marker))
+            .flatten()
+            .filter(|end| end.gt(&0));
+
+        match closed {
+            Some(end) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), normal));
+                }
+                let content = &rest[marker.len()..marker.len() + end];
+                spans.push(Span::styled(content.to_string(), style));
+                rest = &rest[marker.len() * 2 + end..];
+            }
+            None => {
+                let c = rest.chars().next().unwrap();
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(plain, normal));
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Modifier;
+
+    fn contents(line: &Line) -> Vec<String> {
+        line.spans
+            .iter()
+            .map(|span| span.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_bold_and_code_spans() {
+        let colors = hac_colors::Colors::default();
+        let lines = render("needs **admin** scope, send `x-api-key`", &colors);
+
+        assert_eq!(
+            contents(&lines[0]),
+            vec!["needs ", "admin", " scope, send ", "x-api-key"]
+        );
+        assert!(lines[0].spans[1]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert_eq!(lines[0].spans[3].style.fg, Some(colors.normal.green));
+    }
+
+    #[test]
+    fn test_headings_bullets_and_fences() {
+        let colors = hac_colors::Colors::default();
+        let text = "# Auth\n- first\n  * nested `code`\n```\n**raw**\n```\n#hashtag";
+        let lines = render(text, &colors);
+
+        assert_eq!(contents(&lines[0]), vec!["Auth"]);
+        assert_eq!(contents(&lines[1]), vec!["• ", "first"]);
+        assert_eq!(contents(&lines[2]), vec!["  • ", "nested ", "code"]);
+        assert_eq!(contents(&lines[3]), vec!["**raw**"]);
+        assert_eq!(contents(&lines[4]), vec!["#hashtag"]);
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn test_unknown_or_unbalanced_syntax_is_kept() {
+        let colors = hac_colors::Colors::default();
+        let text = "**unclosed and `open\n[link](http://x) ****  `` _em_\n\n";
+        let lines = render(text, &colors);
+
+        assert_eq!(
+            lines[0]
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect::<String>(),
+            "**unclosed and `open"
+        );
+        assert_eq!(
+            lines[1]
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect::<String>(),
+            "[link](http://x) ****  `` _em_"
+        );
+        assert_eq!(contents(&lines[2]), vec![""]);
+    }
+}
@@ -1,5 +1,5 @@
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Stylize};
+use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::Line;
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
@@ -23,6 +23,14 @@ pub fn make_overlay(colors: &hac_colors::Colors, color: Color, alpha: f32, frame
     let buffer = frame.buffer_mut();
     let cells = &mut buffer.content;
 
+    // there is nothing to blend without colors, dimming does the same job
+    if colors.monochrome {
+        cells.iter_mut().for_each(|cell| {
+            cell.set_style(Style::new().add_modifier(Modifier::DIM));
+        });
+        return;
+    }
+
     cells.iter_mut().for_each(|cell| {
         let cell_fg = cell.style().fg.unwrap_or(colors.normal.white);
         let cell_bg = cell.style().bg.unwrap_or(colors.primary.background);
@@ -0,0 +1,241 @@
+use crate::pages::input::{self, Input};
+use crate::pages::overlay::make_overlay;
+use crate::pages::Overlay;
+
+use std::ops::{Add, Div, Sub};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear};
+use ratatui::Frame;
+
+/// set of events `PathPrompt` can emit to its parent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PathPromptEvent {
+    /// user confirmed the path, along with the index of the selected choice
+    Submit { path: String, choice: usize },
+    /// user pressed `Esc`, nothing should happen
+    Close,
+    /// user pressed a hotkey to quit the application
+    Quit,
+}
+
+/// asks for a file path, used when importing or exporting files. It can
+/// also offer a few choices, eg: what to export, which are cycled with tab
+#[derive(Debug)]
+pub struct PathPrompt<'pp> {
+    colors: &'pp hac_colors::Colors,
+    title: String,
+    path: String,
+    /// labels of each choice, along with the path suggested for it
+    choices: Vec<(String, String)>,
+    choice: usize,
+    /// label and placeholder of the input, for prompts asking for more than
+    /// a path
+    input: (String, String),
+}
+
+impl<'pp> PathPrompt<'pp> {
+    pub fn new(colors: &'pp hac_colors::Colors) -> Self {
+        PathPrompt {
+            colors,
+            title: String::default(),
+            path: String::default(),
+            choices: vec![],
+            choice: 0,
+            input: default_input(),
+        }
+    }
+
+    /// starts asking for a path, forgetting anything typed before. The path
+    /// suggested for the first choice is typed in already
+    pub fn open(&mut self, title: String, choices: Vec<(String, String)>) {
+        self.title = title;
+        self.path = choices
+            .first()
+            .map(|(_, path)| path.clone())
+            .unwrap_or_default();
+        self.choices = choices;
+        self.choice = 0;
+        self.input = default_input();
+    }
+
+    /// types `path` in, for prompts with a suggestion but no choices
+    pub fn set_path(&mut self, path: String) {
+        self.path = path;
+    }
+
+    /// changes the label and placeholder of the input until the next `open`
+    pub fn set_input(&mut self, label: &str, placeholder: &str) {
+        self.input = (label.into(), placeholder.into());
+    }
+
+    pub fn choice(&self) -> usize {
+        self.choice
+    }
+
+    /// moves to the next choice, the suggested path follows along unless
+    /// the user already typed another one
+    fn next_choice(&mut self) {
+        let next = self.choice.add(1) % self.choices.len();
+        if self.path.eq(&self.choices[self.choice].1) {
+            self.path = self.choices[next].1.clone();
+        }
+        self.choice = next;
+    }
+
+    fn choices_line(&self) -> Line<'static> {
+        let spans = self
+            .choices
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, (choice, _))| {
+                let choice = match idx.eq(&self.choice) {
+                    true => format!("[{choice}]").fg(self.colors.normal.red).bold(),
+                    false => format!(" {choice} ").fg(self.colors.normal.white),
+                };
+                [choice, Span::from(" ")]
+            })
+            .collect::<Vec<_>>();
+        Line::from(spans).centered()
+    }
+}
+
+fn default_input() -> (String, String) {
+    ("Path".into(), "relative to where hac was started".into())
+}
+
+impl Overlay for PathPrompt<'_> {
+    type Result = PathPromptEvent;
+
+    fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        make_overlay(self.colors, self.colors.normal.black, 0.1, frame);
+
+        let height = match self.choices.is_empty() {
+            true => 6,
+            false => 7,
+        };
+        let width = u16::min(size.width.saturating_sub(4), 70);
+        let popup = Rect::new(
+            size.x.add(size.width.sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(height).div(2)),
+            width,
+            u16::min(size.height, height),
+        );
+        frame.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.title).fg(self.colors.normal.white))
+            .border_style(Style::default().fg(self.colors.bright.black))
+            .bg(self.colors.primary.background);
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let mut y = inner.y;
+        if !self.choices.is_empty() {
+            frame.render_widget(self.choices_line(), Rect::new(inner.x, y, inner.width, 1));
+            y = y.add(1);
+        }
+
+        let input_size = Rect::new(inner.x, y, inner.width, 3).clamp(inner);
+        let (label, placeholder) = self.input.clone();
+        let mut input = Input::new(self.colors, label).placeholder(placeholder);
+        input.focus();
+        let mut path = self.path.clone();
+        frame.render_stateful_widget(input, input_size, &mut path);
+        frame.set_cursor(
+            input_size.x.add(self.path.chars().count().add(1) as u16),
+            input_size.y.add(1),
+        );
+
+        let hint = match self.choices.is_empty() {
+            true => "[enter -> confirm] [esc -> cancel]",
+            false => "[enter -> confirm] [tab -> switch] [esc -> cancel]",
+        };
+        let hint_size = Rect::new(inner.x, input_size.bottom(), inner.width, 1);
+        frame.render_widget(
+            Line::from(hint.fg(self.colors.bright.black)).centered(),
+            hint_size.clamp(inner),
+        );
+
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> anyhow::Result<Option<Self::Result>> {
+        if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (key_event.code, key_event.modifiers) {
+            return Ok(Some(PathPromptEvent::Quit));
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return Ok(Some(PathPromptEvent::Close)),
+            KeyCode::Enter if !self.path.trim().is_empty() => {
+                return Ok(Some(PathPromptEvent::Submit {
+                    path: self.path.trim().to_string(),
+                    choice: self.choice,
+                }));
+            }
+            KeyCode::Tab if !self.choices.is_empty() => self.next_choice(),
+            KeyCode::Backspace => _ = self.path.pop(),
+            KeyCode::Char(c) => self.path.push(c),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste(&mut self, text: String) -> anyhow::Result<Option<Self::Result>> {
+        self.path.push_str(&input::single_line(&text));
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(prompt: &mut PathPrompt, code: KeyCode) -> Option<PathPromptEvent> {
+        prompt
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_submit_with_the_selected_choice() {
+        let colors = hac_colors::Colors::default();
+        let mut prompt = PathPrompt::new(&colors);
+        prompt.open(
+            "Export".into(),
+            vec![
+                ("request".into(), "./list users.http".into()),
+                ("collection".into(), "./users.http".into()),
+            ],
+        );
+        assert_eq!(prompt.path, "./list users.http");
+
+        press(&mut prompt, KeyCode::Tab);
+        assert_eq!(prompt.choice(), 1);
+        assert_eq!(prompt.path, "./users.http");
+
+        for _ in 0..".http".len() {
+            press(&mut prompt, KeyCode::Backspace);
+        }
+        prompt.handle_paste(".rest\n".into()).unwrap();
+        press(&mut prompt, KeyCode::Tab);
+        press(&mut prompt, KeyCode::Tab);
+        assert_eq!(
+            press(&mut prompt, KeyCode::Enter),
+            Some(PathPromptEvent::Submit {
+                path: "./users.rest".into(),
+                choice: 1,
+            })
+        );
+
+        prompt.open("Import".into(), vec![]);
+        assert_eq!(press(&mut prompt, KeyCode::Enter), None);
+        assert_eq!(press(&mut prompt, KeyCode::Tab), None);
+        assert_eq!(prompt.choice(), 0);
+    }
+}
@@ -0,0 +1,144 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::Widget;
+
+/// a thin vertical scrollbar drawn on the rightmost column of the area it is
+/// rendered into. Nothing is drawn when the content fits the viewport
+#[derive(Debug, Clone)]
+pub struct Scrollbar<'a> {
+    colors: &'a hac_colors::Colors,
+    content_len: usize,
+    viewport_len: usize,
+    offset: usize,
+}
+
+impl<'a> Scrollbar<'a> {
+    /// `content_len` and `viewport_len` are in the same unit the content
+    /// scrolls by, lines or rows, and `offset` is the first one in view
+    pub fn new(
+        colors: &'a hac_colors::Colors,
+        content_len: usize,
+        viewport_len: usize,
+        offset: usize,
+    ) -> Self {
+        Scrollbar {
+            colors,
+            content_len,
+            viewport_len,
+            offset,
+        }
+    }
+}
+
+impl Widget for Scrollbar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width.eq(&0) {
+            return;
+        }
+
+        let Some((start, len)) = thumb(
+            self.content_len,
+            self.viewport_len,
+            self.offset,
+            area.height,
+        ) else {
+            return;
+        };
+
+        let x = area.right().saturating_sub(1);
+        let track = Style::default().fg(self.colors.bright.black);
+        let thumb = Style::default().fg(self.colors.normal.red);
+        for row in 0..area.height {
+            let (symbol, style) = match row.ge(&start) && row.lt(&(start + len)) {
+                true => ("┃", thumb),
+                false => ("│", track),
+            };
+            buf.set_string(x, area.y + row, symbol, style);
+        }
+    }
+}
+
+/// where the thumb starts on a track `track` cells tall, and how tall it is.
+/// The thumb is never shorter than a cell, no matter how long the content
+/// is, and is `None` when everything fits the viewport
+pub fn thumb(
+    content_len: usize,
+    viewport_len: usize,
+    offset: usize,
+    track: u16,
+) -> Option<(u16, u16)> {
+    if content_len.le(&viewport_len) || track.eq(&0) {
+        return None;
+    }
+
+    let track = track as usize;
+    let len = (track * viewport_len / content_len).clamp(1, track);
+    let max_offset = content_len - viewport_len;
+    let start = (track - len) * offset.min(max_offset) / max_offset;
+
+    Some((start as u16, len as u16))
+}
+
+/// how far through the content the viewport is, from 0 to 100. `None` when
+/// there is nothing to scroll
+pub fn scroll_percent(content_len: usize, viewport_len: usize, offset: usize) -> Option<u16> {
+    if content_len.le(&viewport_len) {
+        return None;
+    }
+
+    let max_offset = content_len - viewport_len;
+    Some((offset.min(max_offset) * 100 / max_offset) as u16)
+}
+
+/// the scroll position as shown on status lines, eg: `(42%)`
+pub fn percent_label(content_len: usize, viewport_len: usize, offset: usize) -> Option<String> {
+    scroll_percent(content_len, viewport_len, offset).map(|percent| format!("({percent}%)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumb_placement() {
+        assert_eq!(thumb(10, 10, 0, 10), None);
+        assert_eq!(thumb(5, 10, 0, 10), None);
+
+        assert_eq!(thumb(20, 10, 0, 10), Some((0, 5)));
+        assert_eq!(thumb(20, 10, 10, 10), Some((5, 5)));
+        // scrolling past the end keeps the thumb at the bottom
+        assert_eq!(thumb(20, 10, 19, 10), Some((5, 5)));
+
+        // very long content still gets a visible thumb
+        assert_eq!(thumb(1_000_000, 10, 0, 10), Some((0, 1)));
+        assert_eq!(thumb(1_000_000, 10, 999_990, 10), Some((9, 1)));
+    }
+
+    #[test]
+    fn test_scroll_percent() {
+        assert_eq!(scroll_percent(10, 20, 0), None);
+        assert_eq!(scroll_percent(110, 10, 0), Some(0));
+        assert_eq!(scroll_percent(110, 10, 42), Some(42));
+        assert_eq!(scroll_percent(110, 10, 200), Some(100));
+        assert_eq!(percent_label(110, 10, 42).as_deref(), Some("(42%)"));
+    }
+
+    #[test]
+    fn test_rendering_on_the_right_edge() {
+        let colors = hac_colors::Colors::default();
+        let area = Rect::new(0, 0, 3, 4);
+        let mut buf = Buffer::empty(area);
+        Scrollbar::new(&colors, 8, 4, 4).render(area, &mut buf);
+
+        let column = (0..4)
+            .map(|y| buf.get(2, y).symbol().to_string())
+            .collect::<String>();
+        assert_eq!(column, "││┃┃");
+        assert_eq!(buf.get(1, 0).symbol(), " ");
+
+        let mut buf = Buffer::empty(area);
+        Scrollbar::new(&colors, 4, 4, 0).render(area, &mut buf);
+        assert_eq!(buf, Buffer::empty(area));
+    }
+}
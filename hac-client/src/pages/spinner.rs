@@ -9,7 +9,7 @@ use ratatui::widgets::Widget;
 
 #[derive(Debug, Clone)]
 pub struct Spinner {
-    step: usize,
+    step: Option<usize>,
     symbol_set: usize,
     spinner_style: Style,
     label: Option<Span<'static>>,
@@ -31,7 +31,7 @@ impl Spinner {
     /// Creates a new Spinner using a random symbol from the `DOTS` set
     pub fn new() -> Self {
         Spinner {
-            step: 0,
+            step: None,
             symbol_set: 0,
             spinner_style: Style::default(),
             label: None,
@@ -48,6 +48,17 @@ impl Spinner {
         }
     }
 
+    /// pins the spinner to a given frame, so it animates by advancing the
+    /// step instead of picking a random symbol on every draw
+    pub fn with_step(self, step: usize) -> Self {
+        Spinner {
+            step: Some(step),
+            symbol_set: self.symbol_set,
+            spinner_style: self.spinner_style,
+            label: self.label,
+        }
+    }
+
     /// adds a label to the spinner, which will be displayed at the right
     /// to the
     pub fn with_label<S>(self, label: S) -> Self
@@ -65,8 +76,7 @@ impl Spinner {
     /// converts the spinner into a ratatui line
     pub fn into_line(self) -> Line<'static> {
         let mut pieces = vec![];
-        let step = rand::thread_rng().gen_range(0..Spinner::SYMBOL_SET[self.symbol_set].len());
-        let symbol = Spinner::SYMBOL_SET[self.symbol_set][step];
+        let symbol = self.symbol();
         pieces.push(Span::styled(symbol.to_string(), self.spinner_style));
         pieces.push(" ".into());
         if let Some(label) = self.label {
@@ -75,6 +85,14 @@ impl Spinner {
         Line::from(pieces)
     }
 
+    fn symbol(&self) -> &'static str {
+        let symbols = Spinner::SYMBOL_SET[self.symbol_set];
+        let step = self
+            .step
+            .unwrap_or_else(|| rand::thread_rng().gen_range(0..symbols.len()));
+        symbols[step % symbols.len()]
+    }
+
     /// converts the spinner into a ratatui centered line
     pub fn into_centered_line(self) -> Line<'static> {
         self.into_line().centered()
@@ -90,8 +108,7 @@ impl Widget for Spinner {
             return;
         }
 
-        let step = rand::thread_rng().gen_range(0..Spinner::SYMBOL_SET[self.symbol_set].len());
-        let symbol = Spinner::SYMBOL_SET[self.symbol_set][step];
+        let symbol = self.symbol();
         let span = Span::styled(symbol.to_string(), self.spinner_style);
 
         buf.set_style(size, self.spinner_style);
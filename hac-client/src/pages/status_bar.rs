@@ -0,0 +1,181 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Widget;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentAlign {
+    Left,
+    Right,
+}
+
+/// a piece of the status bar, screens build their list of segments every
+/// frame and the bar decides which ones fit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    content: Line<'static>,
+    align: SegmentAlign,
+    /// segments with lower priority are dropped first when the terminal is
+    /// too narrow to show all of them
+    priority: u8,
+}
+
+impl Segment {
+    pub fn left(content: impl Into<Line<'static>>) -> Self {
+        Segment {
+            content: content.into(),
+            align: SegmentAlign::Left,
+            priority: 0,
+        }
+    }
+
+    pub fn right(content: impl Into<Line<'static>>) -> Self {
+        Segment {
+            content: content.into(),
+            align: SegmentAlign::Right,
+            priority: 0,
+        }
+    }
+
+    pub fn with_priority(self, priority: u8) -> Self {
+        Segment { priority, ..self }
+    }
+
+    fn width(&self) -> usize {
+        self.content.width()
+    }
+}
+
+/// shown by every screen while offline, so it is never left on by accident
+pub fn offline_badge(colors: &hac_colors::Colors) -> Segment {
+    let style = colors.highlight(colors.normal.black, colors.normal.red);
+    Segment::right(Span::styled(" OFFLINE ", style).bold()).with_priority(u8::MAX)
+}
+
+/// how much background work is going on, pointing to where it is listed
+pub fn activity_counter(colors: &hac_colors::Colors, active: usize) -> Segment {
+    Segment::right(format!("[F9 -> {active} active]").fg(colors.normal.blue)).with_priority(3)
+}
+
+/// the single line at the bottom of a screen, hints go on the left and state
+/// goes on the right. Segments on the same side are separated by a space
+#[derive(Debug, Default)]
+pub struct StatusBar {
+    segments: Vec<Segment>,
+}
+
+impl StatusBar {
+    pub fn new(segments: Vec<Segment>) -> Self {
+        StatusBar { segments }
+    }
+
+    /// drops the lowest priority segments until the rest fit in `width`, on a
+    /// tie the last segment goes first
+    fn fit(&self, width: usize) -> Vec<&Segment> {
+        let mut segments = self.segments.iter().collect::<Vec<_>>();
+
+        while total_width(&segments) > width {
+            let Some(idx) = segments
+                .iter()
+                .enumerate()
+                .rev()
+                .min_by_key(|(_, segment)| segment.priority)
+                .map(|(idx, _)| idx)
+            else {
+                break;
+            };
+            segments.remove(idx);
+        }
+
+        segments
+    }
+}
+
+fn side_width(segments: &[&Segment], align: SegmentAlign) -> usize {
+    let (count, width) = segments
+        .iter()
+        .filter(|segment| segment.align.eq(&align))
+        .fold((0, 0), |(count, width), segment| {
+            (count + 1, width + segment.width())
+        });
+    width + usize::saturating_sub(count, 1)
+}
+
+fn total_width(segments: &[&Segment]) -> usize {
+    let left = side_width(segments, SegmentAlign::Left);
+    let right = side_width(segments, SegmentAlign::Right);
+    match left.gt(&0) && right.gt(&0) {
+        true => left + right + 1,
+        false => left + right,
+    }
+}
+
+fn join(segments: &[&Segment], align: SegmentAlign) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = vec![];
+    for segment in segments.iter().filter(|segment| segment.align.eq(&align)) {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.extend(segment.content.spans.iter().cloned());
+    }
+    Line::from(spans)
+}
+
+impl Widget for StatusBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let segments = self.fit(area.width.into());
+
+        join(&segments, SegmentAlign::Left).render(area, buf);
+        join(&segments, SegmentAlign::Right)
+            .right_aligned()
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(status_bar: StatusBar, width: u16) -> String {
+        let area = Rect::new(0, 0, width, 1);
+        let mut buffer = Buffer::empty(area);
+        status_bar.render(area, &mut buffer);
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_left_and_right_segments() {
+        let status_bar = StatusBar::new(vec![
+            Segment::left("[? -> help]"),
+            Segment::right("dev"),
+            Segment::left("[q -> quit]"),
+        ]);
+
+        assert_eq!(render(status_bar, 30), "[? -> help] [q -> quit]    dev");
+    }
+
+    #[test]
+    fn test_lower_priorities_are_dropped_when_narrow() {
+        let segments = vec![
+            Segment::left("/filter").with_priority(3),
+            Segment::left("[a -> first]").with_priority(1),
+            Segment::left("[b -> second]").with_priority(1),
+            Segment::right("state").with_priority(2),
+        ];
+
+        assert_eq!(
+            render(StatusBar::new(segments.clone()), 40),
+            "/filter [a -> first] [b -> second] state"
+        );
+        assert_eq!(
+            render(StatusBar::new(segments.clone()), 30),
+            "/filter [a -> first]     state"
+        );
+        assert_eq!(
+            render(StatusBar::new(segments.clone()), 15),
+            "/filter   state"
+        );
+        assert_eq!(render(StatusBar::new(segments), 10), "/filter   ");
+    }
+}
@@ -20,24 +20,29 @@ impl<'a> TerminalTooSmall<'a> {
     }
 }
 
+/// smallest terminal every screen is laid out for, anything smaller shows
+/// `TerminalTooSmall` instead
+pub const MIN_WIDTH: u16 = 80;
+pub const MIN_HEIGHT: u16 = 22;
+
+pub fn is_too_small(size: Rect) -> bool {
+    size.width < MIN_WIDTH || size.height < MIN_HEIGHT
+}
+
 impl Renderable for TerminalTooSmall<'_> {
     fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
         let layout = build_layout(size);
 
-        let lines = Line::from("Terminal is too small:".bold().fg(self.colors.bright.black));
-        let curr_size = Line::from(vec![
-            "Width = ".bold().fg(self.colors.bright.black),
-            format!("{} ", size.width).bold().fg(self.colors.normal.red),
-            "Height = ".bold().fg(self.colors.bright.black),
-            format!("{}", size.height).bold().fg(self.colors.normal.red),
-        ]);
-        let empty = Line::from(" ");
-        let hint = Line::from("Minimum size needed:".bold().fg(self.colors.bright.black));
-        let min_size = Line::from("Width = 80 Height = 22".bold().fg(self.colors.bright.black));
-
-        let text = Paragraph::new(vec![lines, curr_size, empty, hint, min_size])
+        let message = Line::from(vec![
+            format!("terminal too small (need {MIN_WIDTH}x{MIN_HEIGHT}, have ")
+                .fg(self.colors.bright.black),
+            format!("{}x{}", size.width, size.height).fg(self.colors.normal.red),
+            ")".fg(self.colors.bright.black),
+        ])
+        .bold();
+
+        let text = Paragraph::new(message)
             .wrap(Wrap { trim: true })
-            .centered()
             .alignment(Alignment::Center);
 
         frame.render_widget(text, layout);
@@ -50,14 +55,53 @@ impl Renderable for TerminalTooSmall<'_> {
     fn resize(&mut self, _new_size: Rect) {}
 }
 
+/// the message wraps on narrow terminals, so it gets a few rows on the middle
+/// of the screen
 fn build_layout(size: Rect) -> Rect {
     Layout::default()
         .constraints([
             Constraint::Fill(1),
-            Constraint::Length(5),
+            Constraint::Length(3),
             Constraint::Fill(1),
         ])
         .direction(Direction::Vertical)
         .flex(Flex::Center)
         .split(size)[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn test_build_layout_with_degenerate_sizes() {
+        assert_eq!(build_layout(Rect::new(0, 0, 1, 1)).height, 1);
+        assert_eq!(
+            build_layout(Rect::new(0, 0, 200, 5)),
+            Rect::new(0, 1, 200, 3)
+        );
+    }
+
+    #[test]
+    fn test_message_shows_the_needed_and_current_size() {
+        let colors = hac_colors::Colors::default();
+        let mut page = TerminalTooSmall::new(&colors);
+        let mut terminal = Terminal::new(TestBackend::new(60, 8)).unwrap();
+
+        terminal
+            .draw(|frame| page.draw(frame, frame.size()).unwrap())
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let content = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(content.contains("terminal too small (need 80x22, have 60x8)"));
+    }
+}
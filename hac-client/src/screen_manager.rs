@@ -1,17 +1,41 @@
-use hac_core::{collection::Collection, command::Command};
+use hac_core::activity::Activity;
+use hac_core::app_state::{AppState, Session, SessionScreen};
+use hac_core::collection::{collection, Collection};
+use hac_core::command::Command;
+use hac_core::hooks::TrustedHooks;
+use hac_core::net::request_client::ClientOptions;
+use hac_core::secrets::Secrets;
+use hac_core::templates::Templates;
+use hac_core::url_history::UrlHistory;
 
 use crate::event_pool::Event;
+use crate::log_buffer::LogBuffer;
+use crate::pages::activity_panel::{ActivityPanel, ActivityPanelEvent};
 use crate::pages::collection_dashboard::CollectionDashboard;
 use crate::pages::collection_viewer::collection_store::CollectionStore;
-use crate::pages::collection_viewer::CollectionViewer;
-use crate::pages::terminal_too_small::TerminalTooSmall;
-use crate::pages::{Eventful, Renderable};
+use crate::pages::collection_viewer::{CollectionViewer, FinishedRequest, APP_TITLE};
+use crate::pages::confirm_popup::{ConfirmButton, ConfirmPopup, ConfirmPopupEvent};
+use crate::pages::log_viewer::{LogViewer, LogViewerEvent};
+use crate::pages::terminal_too_small::{self, TerminalTooSmall};
+use crate::pages::{Eventful, Overlay, Renderable};
+use crate::task_tracker::TaskTracker;
 
+use std::ops::{Add, Div};
 use std::{cell::RefCell, rc::Rc};
 
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{layout::Rect, Frame};
 use tokio::sync::mpsc::UnboundedSender;
 
+/// the answers to the confirmation shown when quitting would lose something
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum QuitChoice {
+    SaveAndQuit,
+    /// quits without writing the edits that weren't saved yet
+    QuitAnyway,
+    Cancel,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Screens {
     CollectionDashboard,
@@ -27,6 +51,19 @@ pub struct ScreenManager<'sm> {
     /// CollectionViewer is a option as we need a selected collection in order to build
     /// all the components inside
     collection_viewer: Option<CollectionViewer<'sm>>,
+    /// the log viewer is drawn on top of any screen, so it isn't one of the
+    /// screens we navigate between
+    log_viewer: LogViewer<'sm>,
+    show_logs: bool,
+    /// what is running on the background, also drawn on top of any screen
+    activity_panel: ActivityPanel<'sm>,
+    show_activity: bool,
+    /// shown on top of every screen when quitting would lose unsaved edits
+    /// or requests in flight
+    quit_prompt: Option<ConfirmPopup<'sm, QuitChoice>>,
+    /// the user chose to quit without saving, so nothing is written on
+    /// shutdown
+    discard_changes: bool,
 
     curr_screen: Screens,
     /// we keep track of the previous screen, as when the terminal_too_small screen
@@ -37,6 +74,17 @@ pub struct ScreenManager<'sm> {
     colors: &'sm hac_colors::Colors,
     config: &'sm hac_config::Config,
     dry_run: bool,
+    /// whether the last session should be reopened once the collections are
+    /// loaded, it only happens once
+    restore_session: bool,
+    /// whether to look for a newer release on startup
+    check_updates: bool,
+    /// every write to disk started by the screens, so quitting can wait on
+    /// them
+    tasks: TaskTracker,
+    /// requests, runs and imports the screens report, listed on the activity
+    /// panel
+    activity: Activity,
 
     collection_store: Rc<RefCell<CollectionStore>>,
 
@@ -53,21 +101,87 @@ impl<'sm> ScreenManager<'sm> {
         config: &'sm hac_config::Config,
         dry_run: bool,
     ) -> anyhow::Result<Self> {
+        let tasks = TaskTracker::default();
+        let activity = Activity::default();
+        let mut collection_list = CollectionDashboard::new(size, colors, collections, dry_run)?;
+        collection_list.set_task_tracker(tasks.clone());
+        collection_list.set_activity(activity.clone());
+        collection_list.set_client_options(ClientOptions::from_config(config));
+        let (data_dir, logfile) = hac_config::log_file();
+        let log_path = data_dir.join(logfile).to_string_lossy().to_string();
+        // dry runs can still be given secrets, templates, trusted hooks and
+        // remembered urls, they are just never written
+        let mut secrets = Secrets::load(hac_config::secrets_file());
+        let mut templates = Templates::load(hac_config::templates_file());
+        let mut trusted_hooks = TrustedHooks::load(hac_config::trusted_hooks_file());
+        let mut url_history = UrlHistory::load(hac_config::url_history_file());
+        if dry_run {
+            secrets.detach();
+            templates.detach();
+            trusted_hooks.detach();
+            url_history.detach();
+        }
+        let mut collection_store = CollectionStore::default();
+        collection_store.set_secrets(secrets);
+        collection_store.set_templates(templates);
+        collection_store.set_trusted_hooks(trusted_hooks);
+        collection_store.set_url_history(url_history);
+
         Ok(Self {
             curr_screen: Screens::CollectionDashboard,
             prev_screen: Screens::CollectionDashboard,
             collection_viewer: None,
             terminal_too_small: TerminalTooSmall::new(colors),
-            collection_list: CollectionDashboard::new(size, colors, collections, dry_run)?,
-            collection_store: Rc::new(RefCell::new(CollectionStore::default())),
+            log_viewer: LogViewer::new(colors, LogBuffer::default(), log_path),
+            show_logs: false,
+            activity_panel: ActivityPanel::new(colors, activity.clone()),
+            show_activity: false,
+            quit_prompt: None,
+            discard_changes: false,
+            collection_list,
+            collection_store: Rc::new(RefCell::new(collection_store)),
             size,
             colors,
             config,
             sender: None,
             dry_run,
+            restore_session: false,
+            check_updates: false,
+            tasks,
+            activity,
         })
     }
 
+    pub fn with_session_restore(self, restore_session: bool) -> Self {
+        ScreenManager {
+            restore_session,
+            ..self
+        }
+    }
+
+    pub fn with_update_check(self, check_updates: bool) -> Self {
+        ScreenManager {
+            check_updates,
+            ..self
+        }
+    }
+
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.set_offline(offline);
+        self
+    }
+
+    /// the store is shared with the viewer, which is who sends requests
+    fn set_offline(&mut self, offline: bool) {
+        self.collection_store.borrow_mut().set_offline(offline);
+        self.collection_list.set_offline(offline);
+    }
+
+    pub fn with_log_buffer(mut self, buffer: LogBuffer) -> Self {
+        self.log_viewer.set_buffer(buffer);
+        self
+    }
+
     fn restore_screen(&mut self) {
         std::mem::swap(&mut self.curr_screen, &mut self.prev_screen);
     }
@@ -80,47 +194,413 @@ impl<'sm> ScreenManager<'sm> {
         self.curr_screen = screen;
     }
 
+    /// reads every collection from disk on a blocking task, so the dashboard
+    /// can be drawn right away instead of waiting on a big, or slow, data
+    /// directory. The result comes back as a command
+    pub fn load_collections(&mut self, sender: UnboundedSender<Command>) {
+        self.collection_list
+            .set_app_state(AppState::load(hac_config::state_file()));
+        self.collection_list.set_loading();
+        tokio::task::spawn_blocking(move || {
+            let command = match collection::get_collections_from_config() {
+                Ok(collections) => Command::CollectionsLoaded(collections),
+                Err(e) => Command::Error(e.into()),
+            };
+            if sender.send(command).is_err() {
+                tracing::error!("failed to send loaded collections through channel");
+            }
+        });
+    }
+
+    /// looks for a newer release in the background. Nothing is shown when the
+    /// check fails, and nothing waits on it, so a slow or missing network
+    /// never gets in the way
+    pub fn check_for_updates(&mut self, sender: UnboundedSender<Command>) {
+        if !self.check_updates {
+            return;
+        }
+
+        tokio::spawn(async move {
+            match hac_core::updates::check_for_update(crate::VERSION).await {
+                Ok(Some(version)) => {
+                    _ = sender.send(Command::UpdateAvailable(version.to_string()));
+                }
+                Ok(None) => {}
+                Err(e) => tracing::debug!("update check failed: {e:#}"),
+            }
+        });
+    }
+
+    /// the request and environment are selected before the viewer is built,
+    /// so the editor starts with them loaded
+    fn open_collection(
+        &mut self,
+        collection: Collection,
+        request_id: Option<String>,
+        environment: Option<String>,
+    ) {
+        tracing::debug!("changing to api explorer: {}", collection.info.name);
+        // the viewer being replaced is closed for good
+        if let Some(viewer) = self.collection_viewer.take() {
+            viewer.forget_connections();
+        }
+        self.switch_screen(Screens::CollectionViewer);
+        self.collection_store.borrow_mut().set_state(collection);
+        if let Some(request_id) = request_id {
+            self.collection_store
+                .borrow_mut()
+                .select_request(&request_id);
+        }
+        if let Some(environment) = environment {
+            self.collection_store
+                .borrow_mut()
+                .select_environment(&environment);
+        }
+        let mut collection_viewer = CollectionViewer::new(
+            self.size,
+            self.collection_store.clone(),
+            self.colors,
+            self.config,
+            self.dry_run,
+        );
+        collection_viewer.set_task_tracker(self.tasks.clone());
+        collection_viewer.set_activity(self.activity.clone());
+        if let Some(orientation) = self.collection_list.layout() {
+            collection_viewer.set_orientation(orientation);
+        }
+        self.collection_viewer = Some(collection_viewer);
+        self.collection_viewer
+            .as_mut()
+            .unwrap()
+            .register_command_handler(
+                self.sender
+                    .as_ref()
+                    .expect("attempted to register the sender on collection_viewer but it was None")
+                    .clone(),
+            )
+            .ok();
+        self.save_session();
+    }
+
+    /// jumps straight into the collection the last session was left on, a
+    /// collection that no longer exists keeps us on the dashboard
+    fn restore_last_session(&mut self) {
+        let Some((collection, session)) = self.collection_list.last_session() else {
+            return;
+        };
+
+        tracing::debug!("restoring last session on: {:?}", session.collection);
+        self.open_collection(collection, session.request, session.environment);
+    }
+
+    /// saves whatever is still only in memory, returning the tracker of the
+    /// writes that are still running so the caller can wait on them
+    pub fn shutdown(&mut self) -> TaskTracker {
+        self.save_session();
+        if let Some(viewer) = self.collection_viewer.as_mut() {
+            if !self.discard_changes {
+                viewer.sync_before_quit();
+            }
+        }
+        self.tasks.clone()
+    }
+
+    /// the window title, about requests of the viewer when there is one
+    pub fn window_title(&self) -> String {
+        self.collection_viewer
+            .as_ref()
+            .map(CollectionViewer::window_title)
+            .unwrap_or(APP_TITLE.into())
+    }
+
+    /// a request of the viewer that finished since the last time this was
+    /// asked, for the terminal to tell about
+    pub fn take_finished_request(&mut self) -> Option<FinishedRequest> {
+        self.collection_viewer
+            .as_mut()
+            .and_then(CollectionViewer::take_finished_request)
+    }
+
+    /// every quit goes through here. When quitting would lose unsaved edits
+    /// or requests in flight we ask first, unless disabled on the config
+    fn confirm_quit(&mut self) -> Option<Command> {
+        if !self.config.confirm_quit {
+            return Some(Command::Quit);
+        }
+
+        let Some(summary) = self
+            .collection_viewer
+            .as_mut()
+            .and_then(|viewer| viewer.pending_work().summary())
+        else {
+            return Some(Command::Quit);
+        };
+        let buttons = vec![
+            ConfirmButton::new(
+                "(s)ave and quit",
+                's',
+                self.colors.normal.green,
+                QuitChoice::SaveAndQuit,
+            ),
+            ConfirmButton::new(
+                "quit (a)nyway",
+                'a',
+                self.colors.normal.red,
+                QuitChoice::QuitAnyway,
+            ),
+            ConfirmButton::new("(c)ancel", 'c', self.colors.normal.blue, QuitChoice::Cancel),
+        ];
+        let message = format!("Quitting now would lose {summary}");
+        self.quit_prompt = Some(ConfirmPopup::new(message, self.colors, buttons).with_selected(2));
+        None
+    }
+
+    /// pressing `<C-c>` again on the prompt quits, saving what can be saved
+    fn handle_quit_prompt_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> anyhow::Result<Option<Command>> {
+        let Some(prompt) = self.quit_prompt.as_mut() else {
+            return Ok(None);
+        };
+
+        match prompt.handle_key_event(key_event)? {
+            Some(ConfirmPopupEvent::Confirm(QuitChoice::SaveAndQuit))
+            | Some(ConfirmPopupEvent::Quit) => {
+                self.quit_prompt = None;
+                Ok(Some(Command::Quit))
+            }
+            Some(ConfirmPopupEvent::Confirm(QuitChoice::QuitAnyway)) => {
+                self.quit_prompt = None;
+                self.discard_changes = true;
+                Ok(Some(Command::Quit))
+            }
+            Some(ConfirmPopupEvent::Confirm(QuitChoice::Cancel))
+            | Some(ConfirmPopupEvent::Close) => {
+                self.quit_prompt = None;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// hands the event to the log viewer or the activity panel when one is
+    /// open, or to the screen being shown
+    fn route_event(&mut self, event: Option<Event>) -> anyhow::Result<Option<Command>> {
+        if self.curr_screen.ne(&Screens::TerminalTooSmall) {
+            if let Some(Event::Key(KeyEvent {
+                code: KeyCode::F(9),
+                ..
+            })) = event
+            {
+                if !self.show_activity && !self.show_logs {
+                    self.show_activity = true;
+                    return Ok(None);
+                }
+            }
+
+            if self.show_activity {
+                let Some(Event::Key(key_event)) = event else {
+                    return Ok(None);
+                };
+                return match self.activity_panel.handle_key_event(key_event)? {
+                    Some(ActivityPanelEvent::Close) => {
+                        self.show_activity = false;
+                        Ok(None)
+                    }
+                    Some(ActivityPanelEvent::Quit) => Ok(Some(Command::Quit)),
+                    None => Ok(None),
+                };
+            }
+
+            if let Some(Event::Key(KeyEvent {
+                code: KeyCode::F(12),
+                ..
+            })) = event
+            {
+                if !self.show_logs {
+                    self.log_viewer.reset();
+                    self.show_logs = true;
+                    return Ok(None);
+                }
+            }
+
+            if self.show_logs {
+                let Some(Event::Key(key_event)) = event else {
+                    return Ok(None);
+                };
+                return match self.log_viewer.handle_key_event(key_event)? {
+                    Some(LogViewerEvent::Close) => {
+                        self.show_logs = false;
+                        Ok(None)
+                    }
+                    Some(LogViewerEvent::Quit) => Ok(Some(Command::Quit)),
+                    None => Ok(None),
+                };
+            }
+        }
+
+        match self.curr_screen {
+            Screens::CollectionViewer => self
+                .collection_viewer
+                .as_mut()
+                .expect(
+                    "should never be able to switch to editor screen without having a collection",
+                )
+                .handle_event(event),
+            Screens::CollectionDashboard => self.collection_list.handle_event(event),
+            Screens::TerminalTooSmall => Ok(None),
+        }
+    }
+
+    fn draw_quit_prompt(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
+        let width = u16::min(size.width, 60);
+        let popup = Rect::new(
+            size.x.add(size.width.saturating_sub(width).div(2)),
+            size.y.add(size.height.saturating_sub(8).div(2)),
+            width,
+            u16::min(size.height, 8),
+        );
+
+        match self.quit_prompt.as_mut() {
+            Some(prompt) => prompt.draw(frame, popup),
+            None => Ok(()),
+        }
+    }
+
+    /// the screen the user is on, even while it is hidden behind the
+    /// terminal too small screen
+    fn visible_screen(&self) -> &Screens {
+        match self.curr_screen {
+            Screens::TerminalTooSmall => &self.prev_screen,
+            ref screen => screen,
+        }
+    }
+
+    /// the viewer with the given id, a viewer that was replaced by opening
+    /// another collection is gone, along with the results it was waiting on
+    fn viewer(&mut self, viewer_id: u64) -> Option<&mut CollectionViewer<'sm>> {
+        self.collection_viewer
+            .as_mut()
+            .filter(|viewer| viewer.id().eq(&viewer_id))
+    }
+
+    /// remembers where the user is, so the next run can start right there
+    pub fn save_session(&mut self) {
+        let session = match self.visible_screen() {
+            Screens::CollectionViewer => {
+                let store = self.collection_store.borrow();
+                Session {
+                    screen: SessionScreen::Editor,
+                    collection: store
+                        .get_collection()
+                        .map(|collection| collection.borrow().path.clone()),
+                    request: store
+                        .get_selected_request()
+                        .map(|request| request.read().unwrap().id.clone()),
+                    environment: store.get_active_environment(),
+                }
+            }
+            _ => Session::default(),
+        };
+
+        self.collection_list.set_session(session);
+    }
+
     // events can generate commands, which are sent back to the top level event loop through this
     // channel, and goes back down the chain of components as many components may be interested
     // in such command
     pub fn handle_command(&mut self, command: Command) {
+        if let Command::CreateCollection(collection) = &command {
+            self.collection_list.collection_created(collection.clone());
+        }
+
         match command {
-            Command::SelectCollection(collection) | Command::CreateCollection(collection) => {
-                tracing::debug!("changing to api explorer: {}", collection.info.name);
-                self.switch_screen(Screens::CollectionViewer);
-                self.collection_store.borrow_mut().set_state(collection);
-                self.collection_viewer = Some(CollectionViewer::new(
-                    self.size,
-                    self.collection_store.clone(),
-                    self.colors,
-                    self.config,
-                    self.dry_run,
-                ));
-                self.collection_viewer.as_mut().unwrap()
-                    .register_command_handler(
-                        self.sender
-                            .as_ref()
-                            .expect("attempted to register the sender on collection_viewer but it was None")
-                            .clone(),
-                    )
-                    .ok();
+            Command::SelectCollection(collection) => self.open_collection(collection, None, None),
+            // creating or importing happens on the background, another
+            // collection might have been opened in the meantime
+            Command::CreateCollection(collection) => match self.visible_screen() {
+                Screens::CollectionDashboard => self.open_collection(collection, None, None),
+                _ => tracing::debug!(
+                    "not opening {}, the dashboard was left while it was created",
+                    collection.info.name
+                ),
+            },
+            Command::OpenRequest {
+                collection,
+                request_id,
+            } => {
+                self.open_collection(collection, Some(request_id), None);
             }
-            Command::Error(msg) => {
-                self.collection_list.display_error(msg);
+            Command::CollectionsLoaded(collections) => {
+                self.collection_list.set_collections(collections);
+                if std::mem::take(&mut self.restore_session) {
+                    self.restore_last_session();
+                }
             }
-            _ => {}
+            Command::CollectionsImported {
+                collections,
+                skipped,
+            } => {
+                for collection in collections.iter() {
+                    self.collection_list.add_collection(collection.clone());
+                }
+                self.collection_list
+                    .display_import_summary(&collections, skipped);
+            }
+            Command::UpdateCollection(collection) => {
+                self.collection_list.update_collection(collection);
+            }
+            Command::Error(report) => {
+                self.collection_list.display_error(report);
+            }
+            Command::ResponseReceived {
+                viewer_id,
+                send_id,
+                request_id,
+                response,
+            } => match self.viewer(viewer_id) {
+                Some(viewer) => viewer.handle_response(send_id, request_id, *response),
+                None => tracing::debug!("dropping response of {request_id}, its viewer is gone"),
+            },
+            Command::ExternalEditFinished { viewer_id, result } => match self.viewer(viewer_id) {
+                Some(viewer) => viewer.handle_external_edit(result),
+                None => tracing::debug!("dropping external edit, its viewer is gone"),
+            },
+            Command::PreRequestHookFinished {
+                viewer_id,
+                send_id,
+                request_id,
+                result,
+            } => match self.viewer(viewer_id) {
+                Some(viewer) => viewer.handle_pre_request_hook(send_id, request_id, result),
+                None => tracing::debug!("dropping hook of {request_id}, its viewer is gone"),
+            },
+            Command::RequestProgress {
+                viewer_id,
+                send_id,
+                progress,
+            } => match self.viewer(viewer_id) {
+                Some(viewer) => viewer.handle_progress(send_id, progress),
+                None => tracing::debug!("dropping progress of send {send_id}, its viewer is gone"),
+            },
+            Command::UpdateAvailable(version) => {
+                self.collection_list.set_available_update(version);
+            }
+            Command::LayoutChanged(orientation) => {
+                self.collection_list.set_layout(orientation);
+            }
+            Command::OfflineChanged(offline) => self.set_offline(offline),
+            command => tracing::warn!("unhandled command: {command:?}"),
         }
     }
 }
 
 impl Renderable for ScreenManager<'_> {
     fn draw(&mut self, frame: &mut Frame, size: Rect) -> anyhow::Result<()> {
-        match (size.width < 80, size.height < 22) {
-            (true, _) => self.switch_screen(Screens::TerminalTooSmall),
-            (_, true) => self.switch_screen(Screens::TerminalTooSmall),
-            (false, false) if self.curr_screen.eq(&Screens::TerminalTooSmall) => {
-                self.restore_screen()
-            }
+        match terminal_too_small::is_too_small(size) {
+            true => self.switch_screen(Screens::TerminalTooSmall),
+            false if self.curr_screen.eq(&Screens::TerminalTooSmall) => self.restore_screen(),
             _ => {}
         }
 
@@ -136,6 +616,18 @@ impl Renderable for ScreenManager<'_> {
             Screens::TerminalTooSmall => self.terminal_too_small.draw(frame, frame.size())?,
         };
 
+        if self.show_logs && self.curr_screen.ne(&Screens::TerminalTooSmall) {
+            self.log_viewer.draw(frame, frame.size())?;
+        }
+
+        if self.show_activity && self.curr_screen.ne(&Screens::TerminalTooSmall) {
+            self.activity_panel.draw(frame, frame.size())?;
+        }
+
+        if self.curr_screen.ne(&Screens::TerminalTooSmall) {
+            self.draw_quit_prompt(frame, frame.size())?;
+        }
+
         Ok(())
     }
 
@@ -148,6 +640,7 @@ impl Renderable for ScreenManager<'_> {
 
     fn resize(&mut self, new_size: Rect) {
         self.size = new_size;
+        self.terminal_too_small.resize(new_size);
         self.collection_list.resize(new_size);
 
         if let Some(e) = self.collection_viewer.as_mut() {
@@ -157,7 +650,12 @@ impl Renderable for ScreenManager<'_> {
 
     fn handle_tick(&mut self) -> anyhow::Result<()> {
         // currently, only the editor cares about the ticks, used to determine
-        // when to sync changes in disk
+        // when to sync changes in disk. Nothing is synced while asking about
+        // quitting, as the user may choose not to save
+        if self.quit_prompt.is_some() {
+            return Ok(());
+        }
+
         if let Screens::CollectionViewer = &self.curr_screen {
             self.collection_viewer
                 .as_mut()
@@ -173,16 +671,16 @@ impl Eventful for ScreenManager<'_> {
     type Result = Command;
 
     fn handle_event(&mut self, event: Option<Event>) -> anyhow::Result<Option<Command>> {
-        match self.curr_screen {
-            Screens::CollectionViewer => self
-                .collection_viewer
-                .as_mut()
-                .expect(
-                    "should never be able to switch to editor screen without having a collection",
-                )
-                .handle_event(event),
-            Screens::CollectionDashboard => self.collection_list.handle_event(event),
-            Screens::TerminalTooSmall => Ok(None),
+        if self.quit_prompt.is_some() {
+            return match event {
+                Some(Event::Key(key_event)) => self.handle_quit_prompt_key_event(key_event),
+                _ => Ok(None),
+            };
+        }
+
+        match self.route_event(event)? {
+            Some(Command::Quit) => Ok(self.confirm_quit()),
+            command => Ok(command),
         }
     }
 }
@@ -190,7 +688,9 @@ impl Eventful for ScreenManager<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pages::collection_viewer::collection_viewer::CollectionViewerOverlay;
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use hac_core::activity::ActivityKind;
     use hac_core::collection::{self, types::*};
     use ratatui::{backend::TestBackend, Terminal};
     use std::{
@@ -277,6 +777,33 @@ mod tests {
         assert_eq!(sm.size, expected);
     }
 
+    #[test]
+    fn test_degenerate_sizes_do_not_panic() {
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let tiny = Rect::new(0, 0, 1, 1);
+        let mut sm =
+            ScreenManager::new(tiny, &colors, collections.clone(), &config, false).unwrap();
+        _ = sm.register_command_handler(tx.clone());
+        sm.handle_command(Command::SelectCollection(collections[0].clone()));
+
+        for size in [tiny, Rect::new(0, 0, 200, 5), Rect::new(0, 0, 5, 200)] {
+            let mut terminal = Terminal::new(TestBackend::new(size.width, size.height)).unwrap();
+            sm.resize(size);
+            sm.draw(&mut terminal.get_frame(), size).unwrap();
+            assert_eq!(sm.curr_screen, Screens::TerminalTooSmall);
+        }
+
+        let enough = Rect::new(0, 0, 80, 22);
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+        sm.resize(enough);
+        sm.draw(&mut terminal.get_frame(), enough).unwrap();
+        assert_eq!(sm.curr_screen, Screens::CollectionViewer);
+    }
+
     #[test]
     fn test_switch_to_explorer_on_select() {
         let initial = Rect::new(0, 0, 80, 22);
@@ -284,10 +811,10 @@ mod tests {
         let collection = Collection {
             info: Info {
                 name: String::from("any_name"),
-                description: None,
+                ..Default::default()
             },
             path: "any_path".into(),
-            requests: None,
+            ..Default::default()
         };
         let command = Command::SelectCollection(collection.clone());
         let (_guard, path) = setup_temp_collections(10);
@@ -302,6 +829,353 @@ mod tests {
         assert_eq!(sm.curr_screen, Screens::CollectionViewer);
     }
 
+    #[test]
+    fn test_help_opens_on_the_collection_viewer() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(1);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut sm =
+            ScreenManager::new(initial, &colors, collections.clone(), &config, false).unwrap();
+        _ = sm.register_command_handler(tx.clone());
+        sm.handle_command(Command::SelectCollection(collections[0].clone()));
+
+        let help = Event::Key(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        sm.handle_event(Some(help)).unwrap();
+        assert_eq!(
+            sm.collection_store.borrow().peek_overlay(),
+            CollectionViewerOverlay::Help
+        );
+
+        let any_key = Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        sm.handle_event(Some(any_key)).unwrap();
+        assert_eq!(
+            sm.collection_store.borrow().peek_overlay(),
+            CollectionViewerOverlay::None
+        );
+    }
+
+    #[test]
+    fn test_log_viewer_opens_on_top_of_any_screen() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(1);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let buffer = LogBuffer::default();
+        buffer.push(crate::log_buffer::LogEntry {
+            level: tracing::Level::WARN,
+            target: String::from("hac_client"),
+            message: String::from("something looks off"),
+        });
+        let mut sm = ScreenManager::new(initial, &colors, collections, &config, false)
+            .unwrap()
+            .with_log_buffer(buffer);
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+
+        let toggle = Event::Key(KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE));
+        sm.handle_event(Some(toggle.clone())).unwrap();
+        assert!(sm.show_logs);
+
+        // keys go to the log viewer instead of the dashboard below it
+        let filter = Event::Key(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE));
+        sm.handle_event(Some(filter)).unwrap();
+        assert!(sm.show_logs);
+
+        terminal.draw(|f| sm.draw(f, f.size()).unwrap()).unwrap();
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("something looks off"));
+
+        sm.handle_event(Some(toggle)).unwrap();
+        assert!(!sm.show_logs);
+        assert_eq!(sm.curr_screen, Screens::CollectionDashboard);
+    }
+
+    #[tokio::test]
+    async fn test_activity_panel_lists_imports() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(1);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let mut sm = ScreenManager::new(initial, &colors, collections, &config, false).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+
+        let id = sm
+            .activity
+            .start(ActivityKind::Import, "https://a.dev/api.http");
+        let task = tokio::spawn(tokio::time::sleep(std::time::Duration::from_secs(60)));
+        sm.activity.attach(id, task.abort_handle());
+
+        let toggle = Event::Key(KeyEvent::new(KeyCode::F(9), KeyModifiers::NONE));
+        sm.handle_event(Some(toggle.clone())).unwrap();
+        assert!(sm.show_activity);
+
+        // the log viewer can't be opened over it
+        let logs = Event::Key(KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE));
+        sm.handle_event(Some(logs)).unwrap();
+        assert!(!sm.show_logs);
+
+        terminal.draw(|f| sm.draw(f, f.size()).unwrap()).unwrap();
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("https://a.dev/api.http"));
+
+        let cancel = Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        sm.handle_event(Some(cancel)).unwrap();
+        assert!(task.await.unwrap_err().is_cancelled());
+
+        sm.handle_event(Some(toggle)).unwrap();
+        assert!(!sm.show_activity);
+        assert_eq!(sm.curr_screen, Screens::CollectionDashboard);
+    }
+
+    #[test]
+    fn test_responses_without_a_viewer_are_dropped() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let mut sm = ScreenManager::new(initial, &colors, vec![], &config, false).unwrap();
+        let response = hac_core::net::request_manager::Response::error(
+            String::from("closed"),
+            std::time::Instant::now(),
+        );
+
+        sm.handle_command(Command::ResponseReceived {
+            viewer_id: 0,
+            send_id: 0,
+            request_id: String::from("gone"),
+            response: Box::new(response),
+        });
+
+        assert!(sm.collection_viewer.is_none());
+        assert_eq!(sm.curr_screen, Screens::CollectionDashboard);
+    }
+
+    #[test]
+    fn test_quitting_with_unsaved_edits_asks_first() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("api.json"),
+            r#"{
+                "info": { "name": "api" },
+                "requests": [{ "id": "a", "method": "GET", "name": "list", "uri": "/users" }]
+            }"#,
+        )
+        .unwrap();
+        let collections =
+            collection::collection::get_collections(dir.path().to_string_lossy().to_string())
+                .unwrap();
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut sm =
+            ScreenManager::new(initial, &colors, collections.clone(), &config, true).unwrap();
+        _ = sm.register_command_handler(tx.clone());
+        let quit = || {
+            Some(Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL,
+            )))
+        };
+        let key = |c| {
+            Some(Event::Key(KeyEvent::new(
+                KeyCode::Char(c),
+                KeyModifiers::NONE,
+            )))
+        };
+
+        sm.handle_command(Command::SelectCollection(collections[0].clone()));
+        assert!(matches!(
+            sm.handle_event(quit()).unwrap(),
+            Some(Command::Quit)
+        ));
+
+        let collection = sm.collection_store.borrow().get_collection().unwrap();
+        let request = collection.borrow().flatten_requests()[0].clone();
+        request.write().unwrap().uri = String::from("/users/1");
+
+        assert!(sm.handle_event(quit()).unwrap().is_none());
+        let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+        terminal.draw(|f| sm.draw(f, f.size()).unwrap()).unwrap();
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("1 unsaved request"));
+
+        assert!(sm.handle_event(key('c')).unwrap().is_none());
+        assert!(sm.quit_prompt.is_none());
+
+        assert!(sm.handle_event(quit()).unwrap().is_none());
+        assert!(matches!(
+            sm.handle_event(key('a')).unwrap(),
+            Some(Command::Quit)
+        ));
+        assert!(sm.discard_changes);
+    }
+
+    #[test]
+    fn test_results_of_a_replaced_viewer_are_dropped() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let config = hac_config::load_config();
+        let dir = tempdir().unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(
+                dir.path().join(format!("{name}.json")),
+                format!(
+                    r#"{{
+                        "info": {{ "name": "{name}" }},
+                        "requests": [{{ "id": "list", "method": "POST", "name": "list", "uri": "/", "body": "{{}}" }}]
+                    }}"#
+                ),
+            )
+            .unwrap();
+        }
+        let mut collections =
+            collection::collection::get_collections(dir.path().to_string_lossy().to_string())
+                .unwrap();
+        collections.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut sm =
+            ScreenManager::new(initial, &colors, collections.clone(), &config, true).unwrap();
+        _ = sm.register_command_handler(tx.clone());
+        let mut drain = |sm: &mut ScreenManager| {
+            while let Ok(command) = rx.try_recv() {
+                sm.handle_command(command);
+            }
+        };
+        let body = |sm: &ScreenManager| {
+            let request = sm.collection_store.borrow().get_selected_request().unwrap();
+            let body = request.read().unwrap().body.clone();
+            body
+        };
+
+        // `a` is left while its editor and a new collection are still busy,
+        // and `b` is opened before their results arrive
+        tx.send(Command::SelectCollection(collections[0].clone()))
+            .unwrap();
+        drain(&mut sm);
+        let stale_viewer = sm.collection_viewer.as_ref().unwrap().id();
+        tx.send(Command::SelectCollection(collections[1].clone()))
+            .unwrap();
+        tx.send(Command::ExternalEditFinished {
+            viewer_id: stale_viewer,
+            result: Ok(String::from("{\"from\": \"a\"}")),
+        })
+        .unwrap();
+        tx.send(Command::CreateCollection(collections[2].clone()))
+            .unwrap();
+        drain(&mut sm);
+
+        let name = |sm: &ScreenManager| {
+            let collection = sm.collection_store.borrow().get_collection().unwrap();
+            let name = collection.borrow().info.name.clone();
+            name
+        };
+        assert_eq!(sm.curr_screen, Screens::CollectionViewer);
+        assert_eq!(name(&sm), "b");
+        assert_eq!(body(&sm), Some(String::from("{}")));
+
+        // the viewer showing `b` still gets its own results
+        let viewer_id = sm.collection_viewer.as_ref().unwrap().id();
+        tx.send(Command::ExternalEditFinished {
+            viewer_id,
+            result: Ok(String::from("{\"from\": \"b\"}")),
+        })
+        .unwrap();
+        drain(&mut sm);
+        assert_eq!(body(&sm), Some(String::from("{\"from\": \"b\"}")));
+    }
+
+    #[test]
+    fn test_restores_the_last_session() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut sm = ScreenManager::new(initial, &colors, vec![], &config, false)
+            .unwrap()
+            .with_session_restore(true);
+        _ = sm.register_command_handler(tx.clone());
+
+        let last = collections[1].path.clone();
+        sm.collection_list.set_session(Session {
+            screen: SessionScreen::Editor,
+            collection: Some(last.clone()),
+            request: Some("deleted_request".into()),
+            environment: None,
+        });
+        sm.handle_command(Command::CollectionsLoaded(collections));
+
+        assert_eq!(sm.curr_screen, Screens::CollectionViewer);
+        let collection = sm.collection_store.borrow().get_collection().unwrap();
+        assert_eq!(collection.borrow().path, last);
+    }
+
+    #[test]
+    fn test_stale_session_falls_back_to_the_dashboard() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(3);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let mut sm = ScreenManager::new(initial, &colors, vec![], &config, false)
+            .unwrap()
+            .with_session_restore(true);
+
+        sm.collection_list.set_session(Session {
+            screen: SessionScreen::Editor,
+            collection: Some("deleted_collection.json".into()),
+            request: None,
+            environment: None,
+        });
+        sm.handle_command(Command::CollectionsLoaded(collections));
+
+        assert_eq!(sm.curr_screen, Screens::CollectionDashboard);
+        assert!(!sm.restore_session);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_the_open_collection() {
+        let initial = Rect::new(0, 0, 80, 22);
+        let colors = hac_colors::Colors::default();
+        let (_guard, path) = setup_temp_collections(1);
+        let collections = collection::collection::get_collections(path).unwrap();
+        let config = hac_config::load_config();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Command>();
+        let mut sm = ScreenManager::new(initial, &colors, vec![], &config, false).unwrap();
+        _ = sm.register_command_handler(tx.clone());
+
+        let file = collections[0].path.clone();
+        sm.handle_command(Command::SelectCollection(collections[0].clone()));
+        std::fs::remove_file(&file).unwrap();
+
+        let tasks = sm.shutdown();
+        assert!(tasks.wait(std::time::Duration::from_secs(5)).await);
+        assert!(file.exists());
+    }
+
     #[test]
     fn test_register_command_sender_for_dashboard() {
         let initial = Rect::new(0, 0, 80, 22);
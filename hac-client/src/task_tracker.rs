@@ -0,0 +1,89 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// keeps the handles of the background tasks that write to disk, so quitting
+/// can wait on them instead of cutting a write in half. Clones share the same
+/// tasks
+#[derive(Debug, Clone, Default)]
+pub struct TaskTracker {
+    handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl TaskTracker {
+    /// returns a handle to abort the task with, for work that can be cancelled
+    pub fn spawn<F>(&self, task: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(task);
+        let abort = handle.abort_handle();
+        let mut handles = self.handles.lock().unwrap();
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+        abort
+    }
+
+    /// waits on every task spawned so far for at most `timeout`, whatever is
+    /// still running after that is aborted. Returns whether every task
+    /// finished in time
+    pub async fn wait(&self, timeout: Duration) -> bool {
+        let mut handles = std::mem::take(&mut *self.handles.lock().unwrap());
+
+        let finished = tokio::time::timeout(timeout, async {
+            for handle in handles.iter_mut() {
+                if let Err(e) = handle.await {
+                    tracing::error!("background task failed: {e:?}");
+                }
+            }
+        })
+        .await
+        .is_ok();
+
+        if !finished {
+            handles.iter().for_each(JoinHandle::abort);
+        }
+
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_slow_writes_finish_before_waiting_returns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        let tracker = TaskTracker::default();
+
+        let task_path = path.clone();
+        tracker.clone().spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tokio::fs::write(task_path, "{}").await.unwrap();
+        });
+
+        assert!(tracker.wait(Duration::from_secs(5)).await);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_tasks_past_the_timeout_are_aborted() {
+        let tracker = TaskTracker::default();
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let task_finished = finished.clone();
+        tracker.spawn(async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            task_finished.store(true, Ordering::SeqCst);
+        });
+
+        assert!(!tracker.wait(Duration::from_millis(50)).await);
+        assert!(!finished.load(Ordering::SeqCst));
+        assert!(tracker.wait(Duration::from_millis(50)).await);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::pages::collection_viewer::collection_store::CollectionStore;
+
+use hac_core::collection::types::{Collection, Info, Request, RequestKind};
+use hac_core::net::request_manager::Response;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+
+/// a GET request to `uri`, `name` is used as its id as well
+pub fn make_request(name: &str, uri: &str) -> Request {
+    Request {
+        id: name.into(),
+        name: name.into(),
+        uri: uri.into(),
+        ..Default::default()
+    }
+}
+
+/// a collection named `name` with `requests` at its top level
+pub fn make_collection(name: &str, requests: Vec<Request>) -> Collection {
+    let requests = requests
+        .into_iter()
+        .map(|request| RequestKind::Single(Arc::new(RwLock::new(request))))
+        .collect();
+
+    Collection {
+        info: Info {
+            name: name.into(),
+            ..Default::default()
+        },
+        requests: Some(Arc::new(RwLock::new(requests))),
+        ..Default::default()
+    }
+}
+
+/// a store holding `collection`, which has its first request selected
+pub fn make_store(collection: Collection) -> Rc<RefCell<CollectionStore>> {
+    let mut store = CollectionStore::default();
+    store.set_state(collection);
+    Rc::new(RefCell::new(store))
+}
+
+/// a store holding only `request`, along with the request the store has so
+/// editors can be checked against it
+pub fn store_with_request(
+    request: Request,
+) -> (Rc<RefCell<CollectionStore>>, Arc<RwLock<Request>>) {
+    let collection = make_collection("users", vec![request]);
+    let request = collection.flatten_requests().remove(0);
+    (make_store(collection), request)
+}
+
+pub fn make_response(status: u16) -> Response {
+    Response {
+        status: Some(reqwest::StatusCode::from_u16(status).unwrap()),
+        ..Default::default()
+    }
+}
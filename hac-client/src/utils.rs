@@ -1,8 +1,10 @@
 use hac_core::syntax::highlighter::HIGHLIGHTER;
+use hac_core::variables;
 
-use std::ops::Add;
+use std::collections::BTreeMap;
+use std::ops::{Add, Range};
 
-use ratatui::style::{Color, Stylize};
+use ratatui::style::{Color, Style, Stylize};
 use ratatui::text::{Line, Span};
 use tree_sitter::Tree;
 
@@ -145,6 +147,132 @@ pub fn build_syntax_highlighted_lines(
     styled_lines
 }
 
+/// restyles the `{{name}}` placeholders of `line` on top of the style they
+/// already had, spans are only split where a placeholder starts or ends.
+/// Placeholders broken across spans of different styles keep each of them
+pub fn highlight_placeholders(
+    line: Line<'static>,
+    variables: &BTreeMap<String, String>,
+    colors: &hac_colors::Colors,
+) -> Line<'static> {
+    let text = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect::<String>();
+    let placeholders = variables::placeholder_spans(&text)
+        .into_iter()
+        .map(|(range, name)| {
+            // builtins are generated when the request is sent
+            let color = match name.starts_with('$') || variables.contains_key(name) {
+                true => colors.primary.accent,
+                false => colors.normal.red,
+            };
+            (range, Style::default().fg(color))
+        })
+        .collect::<Vec<_>>();
+
+    restyle_ranges(line, &placeholders)
+}
+
+/// patches the style of each byte range of `line` on top of the style it
+/// already had, spans are only split where a range starts or ends
+pub fn restyle_ranges(line: Line<'static>, ranges: &[(Range<usize>, Style)]) -> Line<'static> {
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let mut spans = vec![];
+    let mut offset = 0;
+    for span in line.spans.iter() {
+        let (start, end) = (offset, offset.add(span.content.len()));
+        offset = end;
+
+        let mut cuts = vec![start, end];
+        for (range, _) in ranges.iter() {
+            cuts.extend(
+                [range.start, range.end]
+                    .into_iter()
+                    .filter(|cut| start.lt(cut) && end.gt(cut)),
+            );
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        for piece in cuts.windows(2) {
+            let style = ranges
+                .iter()
+                .find(|(range, _)| range.start.le(&piece[0]) && range.end.ge(&piece[1]))
+                .map(|(_, style)| span.style.patch(*style))
+                .unwrap_or(span.style);
+            let content = span.content[piece[0] - start..piece[1] - start].to_string();
+            spans.push(Span::styled(content, style));
+        }
+    }
+
+    Line { spans, ..line }
+}
+
+/// the `width` characters of `line` starting at column `skip`, every piece
+/// keeps the style it had
+pub fn slice_line(line: &Line<'static>, skip: usize, width: usize) -> Line<'static> {
+    let mut spans = vec![];
+    let mut offset = 0;
+    for span in line.spans.iter() {
+        let len = span.content.chars().count();
+        let (start, end) = (offset, offset.add(len));
+        offset = end;
+
+        let (from, to) = (skip.max(start), skip.add(width).min(end));
+        if from.lt(&to) {
+            let content = span
+                .content
+                .chars()
+                .skip(from - start)
+                .take(to - from)
+                .collect::<String>();
+            spans.push(Span::styled(content, span.style));
+        }
+    }
+
+    Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+/// soft wraps `line` into rows of `width` characters behind a two column
+/// gutter, where rows that continue the one above are marked. Empty lines
+/// still take a row, and the line break at the end never takes one
+pub fn wrap_line(
+    line: &Line<'static>,
+    width: usize,
+    colors: &hac_colors::Colors,
+) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let len = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect::<String>()
+        .trim_end_matches(is_endline)
+        .chars()
+        .count();
+
+    (0..len.div_ceil(width).max(1))
+        .map(|row| {
+            let gutter = match row {
+                0 => Span::from("  "),
+                _ => "↪ ".fg(colors.bright.black),
+            };
+            let mut wrapped = slice_line(line, row * width, width);
+            wrapped.spans.insert(0, gutter);
+            wrapped
+        })
+        .collect()
+}
+
 /// will try to apply a blending using multiply to two colors, based on a given alpha.
 ///
 /// It will apply the background over the foreground so we get a middleground color. This
@@ -248,3 +376,76 @@ macro_rules! impl_enum_iter {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_are_restyled_without_losing_other_styles() {
+        let colors = hac_colors::Colors::default();
+        let variables = BTreeMap::from([("host".to_string(), "localhost".to_string())]);
+        let line = Line::from(vec![
+            Span::from("\"{{host}}/").fg(colors.normal.green),
+            Span::from("{{id}}\"").fg(colors.normal.green).bold(),
+            Span::from(" {{$uuid}}"),
+        ]);
+
+        let line = highlight_placeholders(line, &variables, &colors);
+        let pieces = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style.fg))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pieces,
+            vec![
+                ("\"", Some(colors.normal.green)),
+                ("{{host}}", Some(colors.primary.accent)),
+                ("/", Some(colors.normal.green)),
+                ("{{id}}", Some(colors.normal.red)),
+                ("\"", Some(colors.normal.green)),
+                (" ", None),
+                ("{{$uuid}}", Some(colors.primary.accent)),
+            ]
+        );
+        // only the color changes, the rest of the style is kept
+        assert!(line.spans[3]
+            .style
+            .add_modifier
+            .contains(ratatui::style::Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_long_lines_wrap_keeping_their_styles() {
+        let colors = hac_colors::Colors::default();
+        let line = Line::from(vec![
+            Span::from("\"token\": ").fg(colors.normal.red),
+            Span::from("\"abcdef\"").fg(colors.normal.green),
+        ]);
+
+        let rows = wrap_line(&line, 8, &colors);
+        let texts = rows.iter().map(|row| row.to_string()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["  \"token\":", "↪  \"abcdef", "↪ \""]);
+        // the string starts midway through the second row and keeps its color
+        assert_eq!(rows[1].spans[2].content, "\"abcdef");
+        assert_eq!(rows[1].spans[2].style.fg, Some(colors.normal.green));
+
+        let empty = wrap_line(&Line::default(), 8, &colors);
+        assert_eq!(empty.len(), 1);
+        let exact = wrap_line(&Line::from("12345678\n"), 8, &colors);
+        assert_eq!(exact.len(), 1);
+    }
+
+    #[test]
+    fn test_slicing_a_line_sideways() {
+        let line = Line::from(vec![Span::from("héllo "), Span::from("world").bold()]);
+
+        assert_eq!(slice_line(&line, 3, 5).to_string(), "lo wo");
+        assert_eq!(slice_line(&line, 20, 5).to_string(), "");
+        assert!(slice_line(&line, 6, 5).spans[0]
+            .style
+            .add_modifier
+            .contains(ratatui::style::Modifier::BOLD));
+    }
+}
@@ -4,6 +4,7 @@ use hac_client::pages::{collection_dashboard::CollectionDashboard, Eventful, Ren
 
 use std::fs::{create_dir, File};
 use std::io::Write;
+use std::path::PathBuf;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{backend::TestBackend, layout::Rect, Frame, Terminal};
@@ -37,6 +38,16 @@ fn feed_keys(dashboard: &mut CollectionDashboard, events: &[KeyEvent]) {
     }
 }
 
+/// the collections directory as the onboarding screen shows it
+fn collections_dir() -> String {
+    let dir = hac_config::get_collections_dir();
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match home.and_then(|home| dir.strip_prefix(home).ok().map(PathBuf::from)) {
+        Some(relative) => format!("~/{}", relative.display()),
+        None => dir.display().to_string(),
+    }
+}
+
 fn get_rendered_from_buffer(frame: &mut Frame, size: Rect) -> Vec<String> {
     frame
         .buffer_mut()
@@ -55,10 +66,14 @@ fn test_draw_empty_message() {
     let mut frame = terminal.get_frame();
 
     let expected = [
-        "            █▖▐▌                ▝█  ▝█           ▟   ▀                          ",
-        "            █▜▟▌▟▀▙     ▟▀▙ ▟▀▙  █   █  ▟▀▙ ▟▀▙ ▝█▀ ▝█  ▟▀▙ █▀▙ ▟▀▀             ",
-        "            █ ▜▌█ █     █ ▄ █ █  █   █  █▀▀ █ ▄  █▗  █  █ █ █ █ ▝▀▙             ",
-        "            ▀ ▝▘▝▀▘     ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘  ▝▘ ▝▀▘ ▝▀▘ ▀ ▀ ▀▀▘             ",
+        "          │                      Welcome to hac!                     │          ",
+        "          │                                                          │          ",
+        "          │  collections group the requests you send to an api,      │          ",
+        "          │  and you don't have any yet.                             │          ",
+        "          │                                                          │          ",
+        "          │  n      -> create a new collection                       │          ",
+        "          │  i      -> import .http, insomnia or collection files    │          ",
+        "          │  collection files can also be copied into:               │          ",
     ];
 
     dashboard.draw(&mut frame, size).unwrap();
@@ -70,7 +85,7 @@ fn test_draw_empty_message() {
         .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
         .collect::<Vec<_>>();
 
-    let result = [&rendered[10], &rendered[11], &rendered[12], &rendered[13]];
+    let result = &rendered[9..17];
 
     assert_eq!(result, expected);
 }
@@ -86,10 +101,9 @@ fn test_draw_no_matches_message() {
     let mut frame = terminal.get_frame();
 
     let expected = [
-        "                    █▖▐▌                 ▟      ▜▌                              ",
-        "                    █▜▟▌▟▀▙     █▄█▖▝▀▙ ▝█▀ ▟▀▙ ▐▙▜▖▟▀▙ ▟▀▀                     ",
-        "                    █ ▜▌█ █     █▜▜▌▟▀█  █▗ █ ▄ ▐▌▐▌█▀▀ ▝▀▙                     ",
-        "                    ▀ ▝▘▝▀▘     ▀ ▝▘▝▀▝▘ ▝▘ ▝▀▘ ▀▘▝▘▝▀▘ ▀▀▘                     ",
+        "                       no collections match ‹non existing›                      ",
+        "                                                                                ",
+        "                         press <esc> to clear the filter                        ",
     ];
 
     feed_keys(
@@ -120,7 +134,7 @@ fn test_draw_no_matches_message() {
         .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
         .collect::<Vec<_>>();
 
-    let result = [&rendered[10], &rendered[11], &rendered[12], &rendered[13]];
+    let result = &rendered[12..15];
 
     assert_eq!(result, expected);
 }
@@ -136,7 +150,7 @@ fn draw_hint_text() {
     let mut frame = terminal.get_frame();
 
     let expected =
-        [" [h/j/k/l to move] [n -> new] [enter -> select item] [? -> help] [<C-c> -> quit]"];
+        [" [h/j/k/l -> move] [n -> new] [s -> sort: name] [? -> help] [<C-c> -> quit]     "];
 
     dashboard.draw(&mut frame, size).unwrap();
     let rendered = get_rendered_from_buffer(&mut frame, size);
@@ -155,7 +169,7 @@ fn draw_filter_prompt() {
     let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
     let mut frame = terminal.get_frame();
     let expected =
-        [" /any_filter                                                                    "];
+        [" /any_filter [enter -> apply] [esc -> clear]                                    "];
 
     feed_keys(
         &mut dashboard,
@@ -218,30 +232,33 @@ fn test_draw_error() {
     let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
     let mut frame = terminal.get_frame();
 
-    let expected = [
+    let mut expected = [
         "                                                                                ",
-        "  ▟▀▙     ▝█       ┌─────────────────────────────────────┐     ▟   ▀            ",
-        "  ▜▙  ▟▀▙  █  ▟▀▙ ▟│                                     │▟▀▙ ▝█▀ ▝█  ▟▀▙ █▀▙   ",
-        "  ▄▝█ █▀▀  █  █▀▀ █│ any_error_message                   │█ ▄  █▗  █  █ █ █ █   ",
-        "  ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝│                                     │▝▀▘  ▝▘ ▝▀▘ ▝▀▘ ▀ ▀   ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "            █▖▐▌   │                                     │                      ",
-        "            █▜▟▌▟▀▙│                                     │▙ █▀▙ ▟▀▀             ",
-        "            █ ▜▌█ █│                                     │█ █ █ ▝▀▙             ",
-        "            ▀ ▝▘▝▀▘│                                     │▘ ▀ ▀ ▀▀▘             ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "                   │                                     │                      ",
-        "                   │                (O)k                 │                      ",
-        "                   │                                     │                      ",
-        "                   └─────────────────────────────────────┘                      ",
+        "  ▟▀▙     ▝█           ▟                      ▝█  ▝█           ▟   ▀            ",
+        "  ▜▙  ▟▀▙  █  ▟▀▙ ▟▀▙ ▝█▀     ▝▀▙     ▟▀▙ ▟▀▙  █   █  ▟▀▙ ▟▀▙ ▝█▀ ▝█  ▟▀▙ █▀▙   ",
+        "  ▄▝█ █▀▀  █  █▀▀ █ ▄  █▗     ▟▀█     █ ▄ █ █  █   █  █▀▀ █ ▄  █▗  █  █ █ █ █   ",
+        "  ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘  ▝▘     ▝▀▝▘    ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘  ▝▘ ▝▀▘ ▝▀▘ ▀ ▀   ",
         "                                                                                ",
-    ];
+        "                                                                                ",
+        "          ╭────────┌─────────────────────────────────────┐───────────╮          ",
+        "          │        │                                     │           │          ",
+        "          │        │  any_error_message                  │           │          ",
+        "          │        │                                     │           │          ",
+        "          │  collec│            (o)k  (y)ank             │ api,      │          ",
+        "          │  and yo│                                     │           │          ",
+        "          │        └─────────────────────────────────────┘           │          ",
+        "          │  n      -> create a new collection                       │          ",
+        "          │  i      -> import .http, insomnia or collection files    │          ",
+        "          │  collection files can also be copied into:               │          ",
+        "",
+        "          │                                                          │          ",
+        "          ╰──────────────────────────────────────────────────────────╯          ",
+        "                                                                                ",
+        "                                                                                ",
+    ]
+    .map(String::from);
+    // where collections live depends on the platform and the environment
+    expected[17] = format!("          │  {:<56}│          ", collections_dir());
 
     dashboard.display_error("any_error_message".into());
     dashboard.draw(&mut frame, size).unwrap();
@@ -263,36 +280,87 @@ fn test_draw_help() {
     let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
     let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
     let mut frame = terminal.get_frame();
+    // the help shows which version is running on its bottom border
+    let version_row = format!(
+        "         ╰{:─>58}╯╯          ",
+        format!(" hac v{} ", hac_client::VERSION)
+    );
 
-    let expected = [
+    let mut expected = [
         "                                                                                ",
         "  ▟▀▙     ▝█           ▟                      ▝█  ▝█           ▟   ▀            ",
-        "  ▜▙  ▟▀▙  █  ▟▀▙ ▟▀▙ ▝█▀     ▝▀▙     ▟▀▙ ▟▀▙  █   █  ▟▀▙ ▟▀▙ ▝█▀ ▝█  ▟▀▙ █▀▙   ",
-        "  ▄▝█ █▀▀  █  █▀▀ █ ▄  █▗     ▟▀█     █ ▄ █ █  █   █  █▀▀ █ ▄  █▗  █  █ █ █ █   ",
-        "  ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘  ▝▘     ▝▀▝▘    ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘ ▝▀▘  ▝▘ ▝▀▘ ▝▀▘ ▀ ▀   ",
-        "                h/<left>    - select left item                                  ",
-        "                j/<down>    - select item below                                 ",
-        "                k/<up>      - select item above                                 ",
-        "                l/<right>   - select right item                                 ",
-        "                n/c         - creates a new collection                          ",
-        "            █▖▐▌d           - deletes the selected collection                   ",
-        "            █▜▟▌?           - toggle this help window█  ▟▀▙ █▀▙ ▟▀▀             ",
-        "            █ ▜▌enter       - select item under cursor  █ █ █ █ ▝▀▙             ",
-        "            ▀ ▝▘/           - enter filter mode  ▝▘ ▝▀▘ ▝▀▘ ▀ ▀ ▀▀▘             ",
-        "                <C-c>       - quits the application                             ",
-        "                                                                                ",
-        "                           press any key to go back                             ",
+        "  ▜▙  ▟▀▙╭ help ────────────────────────────────────────────────────╮ ▟▀▙ █▀▙   ",
+        "  ▄▝█ █▀▀│                                                          │ █ █ █ █   ",
+        "  ▝▀▘ ▝▀▘│  navigation                                              │ ▝▀▘ ▀ ▀   ",
+        "         │  h/<left>   - select left item                           │           ",
+        "         │  j/<down>   - select item below                          │           ",
+        "         │  k/<up>     - select item above                          │╮          ",
+        "         │  l/<right>  - select right item                          ││          ",
+        "         │  enter      - opens the selected collection              ││          ",
+        "         │  v          - shows the collection details               ││          ",
+        "         │                                                          ││          ",
+        "         │  collections                                             ││          ",
+        "         │  n/c        - creates a new collection                   ││          ",
+        "         │  i          - imports .http, insomnia or collection fil  ││          ",
+        "         │  r          - re-imports the collection from its url     ││          ",
+        "         │  E          - exports the collection to share it         ││          ",
+        "         │                                                          ││          ",
+        "         │       page 1/4 · h/l for more · any key to go back       ││          ",
+        "",
         "                                                                                ",
         "                                                                                ",
+    ]
+    .map(String::from);
+    expected[19] = version_row.clone();
+
+    feed_keys(
+        &mut dashboard,
+        &[KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE)],
+    );
+    dashboard.draw(&mut frame, size).unwrap();
+
+    let rendered = frame
+        .buffer_mut()
+        .content
+        .chunks(size.width.into())
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>();
+
+    assert_eq!(rendered, expected);
+
+    let mut expected = [
         "                                                                                ",
+        "  ▟▀▙     ▝█           ▟                      ▝█  ▝█           ▟   ▀            ",
+        "  ▜▙  ▟▀▙╭ help ────────────────────────────────────────────────────╮ ▟▀▙ █▀▙   ",
+        "  ▄▝█ █▀▀│                                                          │ █ █ █ █   ",
+        "  ▝▀▘ ▝▀▘│  e          - edits the selected collection              │ ▝▀▘ ▀ ▀   ",
+        "         │  d          - deletes the selected collection            │           ",
+        "         │  y          - duplicates the collection, the way to edi  │           ",
+        "         │  p          - pins or unpins the collection              │╮          ",
+        "         │                                                          ││          ",
+        "         │  marks                                                   ││          ",
+        "         │  <space>    - marks or unmarks the collection            ││          ",
+        "         │  d          - deletes every marked collection            ││          ",
+        "         │  E          - exports every marked collection to a dire  ││          ",
+        "         │  <esc>      - clears the marks                           ││          ",
+        "         │                                                          ││          ",
+        "         │  list                                                    ││          ",
+        "         │  s          - changes the sorting                        ││          ",
+        "         │                                                          ││          ",
+        "         │       page 2/4 · h/l for more · any key to go back       ││          ",
+        "",
         "                                                                                ",
         "                                                                                ",
-    ];
+    ]
+    .map(String::from);
+    expected[19] = version_row;
 
     feed_keys(
         &mut dashboard,
-        &[KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE)],
+        &[KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE)],
     );
+    let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+    let mut frame = terminal.get_frame();
     dashboard.draw(&mut frame, size).unwrap();
 
     let rendered = frame
@@ -376,11 +444,11 @@ fn test_draw_delete_prompt() {
         " ╭────────────────────────────────────╮╭────────────────────────────────────╮ ↑ ",
         " │test_collection_0┌─────────────────────────────────────┐                  │ █ ",
         " │test_description_│                                     │                  │ █ ",
-        " ╰─────────────────│  You really want to delete          │──────────────────╯ █ ",
+        " ╰───────────── 0 r│  You really want to delete          │quests · just now ╯ █ ",
         " ╭─────────────────│  collection test_collection_0?      │                    █ ",
         " │test_collection_2│                                     │                    █ ",
         " │test_description_│             (y)es (n)o              │                    █ ",
-        " ╰─────────────────│                                     │                    █ ",
+        " ╰───────────── 0 r│                                     │                    █ ",
         "                   └─────────────────────────────────────┘                    █ ",
         "                                                                              █ ",
         "                                                                              █ ",
@@ -429,11 +497,11 @@ fn test_draw_collections_list() {
         " ╭────────────────────────────────────╮╭────────────────────────────────────╮ ↑ ",
         " │test_collection_0                   ││test_collection_1                   │ █ ",
         " │test_description_0                  ││test_description_1                  │ █ ",
-        " ╰────────────────────────────────────╯╰────────────────────────────────────╯ █ ",
+        " ╰───────────── 0 requests · just now ╯╰───────────── 0 requests · just now ╯ █ ",
         " ╭────────────────────────────────────╮                                       █ ",
         " │test_collection_2                   │                                       █ ",
         " │test_description_2                  │                                       █ ",
-        " ╰────────────────────────────────────╯                                       █ ",
+        " ╰───────────── 0 requests · just now ╯                                       █ ",
         "                                                                              █ ",
         "                                                                              █ ",
         "                                                                              █ ",
@@ -441,7 +509,7 @@ fn test_draw_collections_list() {
         "                                                                              █ ",
         "                                                                              █ ",
         "                                                                              ↓ ",
-        " [h/j/k/l to move] [n -> new] [enter -> select item] [? -> help] [<C-c> -> quit]",
+        " [h/j/k/l -> move] [n -> new] [s -> sort: name] [? -> help] [<C-c> -> quit]     ",
     ];
 
     dashboard.draw(&mut frame, size).unwrap();
@@ -455,3 +523,28 @@ fn test_draw_collections_list() {
 
     assert_eq!(rendered, expected);
 }
+
+#[test]
+fn test_empty_message_follows_resize() {
+    let colors = hac_colors::Colors::default();
+    let size = Rect::new(0, 0, 80, 22);
+    let mut dashboard = CollectionDashboard::new(size, &colors, vec![], false).unwrap();
+    let new_size = Rect::new(0, 0, 100, 30);
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    let mut frame = terminal.get_frame();
+
+    dashboard.resize(new_size);
+    dashboard.draw(&mut frame, new_size).unwrap();
+
+    let rendered = get_rendered_from_buffer(&mut frame, new_size);
+    let welcome = rendered
+        .iter()
+        .position(|row| row.contains("Welcome to hac!"))
+        .unwrap();
+
+    assert_eq!(welcome, 13);
+    assert_eq!(
+        rendered[welcome],
+        "                    │                      Welcome to hac!                     │                    "
+    );
+}
@@ -0,0 +1,113 @@
+use hac_core::collection;
+use hac_core::collection::types::PreviewLanguage;
+use hac_core::syntax::highlighter::{self, Grammar, HIGHLIGHTER};
+
+use hac_client::pages::collection_dashboard::CollectionDashboard;
+use hac_client::pages::collection_viewer::{collection_store::CollectionStore, CollectionViewer};
+use hac_client::pages::Renderable;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs::{create_dir, write};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use ratatui::style::Color;
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+use tempfile::tempdir;
+
+static GRAMMARS_RELEASED: AtomicBool = AtomicBool::new(false);
+
+/// a loader that never finishes until the test lets it
+fn slow_loader(language: PreviewLanguage) -> Option<Grammar> {
+    while !GRAMMARS_RELEASED.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(5));
+    }
+    highlighter::load_grammar(language)
+}
+
+/// colors of the cells showing `needle` on the screen
+fn needle_colors(terminal: &Terminal<TestBackend>, needle: &str) -> HashSet<Color> {
+    let buffer = terminal.backend().buffer();
+    let len = needle.chars().count();
+    buffer
+        .content
+        .chunks(usize::from(buffer.area.width))
+        .find_map(|row| {
+            row.windows(len).find(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| cell.symbol())
+                    .collect::<String>()
+                    .eq(needle)
+            })
+        })
+        .expect("nothing on the screen shows the needle")
+        .iter()
+        .filter(|cell| !cell.symbol().trim().is_empty())
+        .map(|cell| cell.fg)
+        .collect()
+}
+
+#[test]
+fn test_screens_are_drawn_before_grammars_are_loaded() {
+    HIGHLIGHTER.write().unwrap().set_loader(slow_loader);
+
+    let tmp_data_dir = tempdir().unwrap();
+    let collections_dir = tmp_data_dir.path().join("collections");
+    create_dir(&collections_dir).unwrap();
+    write(
+        collections_dir.join("users.json"),
+        r#"{
+            "info": { "name": "users" },
+            "requests": [{
+                "id": "create",
+                "method": "POST",
+                "name": "create user",
+                "uri": "http://localhost:3000/users",
+                "body": "{\"name\": \"ada\"}",
+                "bodyType": "json"
+            }]
+        }"#,
+    )
+    .unwrap();
+    let collections = collection::collection::get_collections(&collections_dir).unwrap();
+
+    let colors = hac_colors::Colors::default();
+    let config = hac_config::load_config();
+    let size = Rect::new(0, 0, 80, 22);
+    let mut terminal = Terminal::new(TestBackend::new(80, 22)).unwrap();
+
+    let mut dashboard = CollectionDashboard::new(size, &colors, collections.clone(), true).unwrap();
+    terminal
+        .draw(|frame| dashboard.draw(frame, size).unwrap())
+        .unwrap();
+    assert!(!needle_colors(&terminal, "users").is_empty());
+
+    let mut store = CollectionStore::default();
+    store.set_state(collections[0].clone());
+    store.select_request("create");
+    let store = Rc::new(RefCell::new(store));
+    let mut viewer = CollectionViewer::new(size, store, &colors, &config, true);
+
+    // the body is there, just without highlighting
+    let plain = [colors.normal.white, Color::Reset];
+    terminal
+        .draw(|frame| viewer.draw(frame, size).unwrap())
+        .unwrap();
+    assert!(needle_colors(&terminal, r#"{"name": "ada"}"#)
+        .iter()
+        .all(|color| plain.contains(color)));
+    assert!(!HIGHLIGHTER.read().unwrap().is_loaded(PreviewLanguage::Json));
+
+    GRAMMARS_RELEASED.store(true, Ordering::SeqCst);
+    highlighter::wait_for_grammar(PreviewLanguage::Json);
+    terminal
+        .draw(|frame| viewer.draw(frame, size).unwrap())
+        .unwrap();
+    assert!(needle_colors(&terminal, r#"{"name": "ada"}"#)
+        .iter()
+        .any(|color| !plain.contains(color)));
+}
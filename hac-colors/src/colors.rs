@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 
 #[derive(Debug, PartialEq)]
 pub struct Colors {
@@ -8,6 +8,9 @@ pub struct Colors {
     pub normal: NormalColors,
     pub bright: BrightColors,
     pub tokens: HashMap<String, Style>,
+    /// every color is the terminal default, things are told apart with
+    /// bold, dim, reverse and underline instead
+    pub monochrome: bool,
 }
 
 impl Default for Colors {
@@ -17,6 +20,68 @@ impl Default for Colors {
             normal: Default::default(),
             bright: Default::default(),
             tokens: token_highlight(),
+            monochrome: false,
+        }
+    }
+}
+
+impl Colors {
+    /// palette for terminals without colors, or for when hues are hard to
+    /// tell apart. Used when `NO_COLOR` is set or with `--no-color`
+    pub fn monochrome() -> Self {
+        Colors {
+            primary: PrimaryColors {
+                foreground: Color::Reset,
+                background: Color::Reset,
+                accent: Color::Reset,
+                hover: Color::Reset,
+            },
+            normal: NormalColors::reset(),
+            bright: BrightColors::reset(),
+            tokens: token_attributes(),
+            monochrome: true,
+        }
+    }
+
+    /// border of a pane, the focused pane is told by its color, or by being
+    /// drawn in reverse video without colors. A selected pane is the one
+    /// keys are going to
+    pub fn pane_border(&self, focused: bool, selected: bool) -> Style {
+        match (self.monochrome, focused, selected) {
+            (false, true, false) => Style::new().fg(self.primary.accent),
+            (false, true, true) => Style::new().fg(self.normal.red),
+            (false, false, _) => Style::new().fg(self.bright.black),
+            (true, true, false) => Style::new().add_modifier(Modifier::REVERSED),
+            (true, true, true) => Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            (true, false, _) => Style::new().add_modifier(Modifier::DIM),
+        }
+    }
+
+    /// border of something focused that isn't a pane, eg: an input, drawn
+    /// in `color` or in reverse video without colors
+    pub fn focus_border(&self, color: Color) -> Style {
+        match self.monochrome {
+            true => Style::new().add_modifier(Modifier::REVERSED),
+            false => Style::new().fg(color),
+        }
+    }
+
+    /// the item under the cursor of a list or a focused button, drawn with
+    /// `fg` over `bg`, or in reverse video without colors
+    pub fn highlight(&self, fg: Color, bg: Color) -> Style {
+        match self.monochrome {
+            true => Style::new().add_modifier(Modifier::REVERSED),
+            false => Style::new().fg(fg).bg(bg),
+        }
+    }
+
+    /// an item that is marked without being under the cursor, eg: the open
+    /// request on the sidebar. Underlined without colors, as reverse video is
+    /// taken by the cursor
+    pub fn marked(&self, fg: Color, bg: Color) -> Style {
+        match self.monochrome {
+            true => Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            false => Style::new().fg(fg).bg(bg),
         }
     }
 }
@@ -74,6 +139,34 @@ fn token_highlight() -> HashMap<String, Style> {
     tokens
 }
 
+/// without colors the syntax is told apart by attributes, keys stand out the
+/// most and punctuation the least
+fn token_attributes() -> HashMap<String, Style> {
+    let mut tokens = HashMap::new();
+
+    tokens.insert("conceal".into(), Style::new().add_modifier(Modifier::DIM));
+    tokens.insert(
+        "boolean".into(),
+        Style::new().add_modifier(Modifier::UNDERLINED),
+    );
+    tokens.insert(
+        "number".into(),
+        Style::new().add_modifier(Modifier::UNDERLINED),
+    );
+    tokens.insert("property".into(), Style::new().add_modifier(Modifier::BOLD));
+    tokens.insert(
+        "punctuation.bracket".into(),
+        Style::new().add_modifier(Modifier::DIM),
+    );
+    tokens.insert(
+        "punctuation.delimiter".into(),
+        Style::new().add_modifier(Modifier::DIM),
+    );
+    tokens.insert("string".into(), Style::new().add_modifier(Modifier::ITALIC));
+
+    tokens
+}
+
 impl Default for PrimaryColors {
     fn default() -> Self {
         PrimaryColors {
@@ -100,6 +193,36 @@ impl Default for NormalColors {
     }
 }
 
+impl NormalColors {
+    fn reset() -> Self {
+        NormalColors {
+            black: Color::Reset,
+            red: Color::Reset,
+            green: Color::Reset,
+            yellow: Color::Reset,
+            blue: Color::Reset,
+            magenta: Color::Reset,
+            orange: Color::Reset,
+            white: Color::Reset,
+        }
+    }
+}
+
+impl BrightColors {
+    fn reset() -> Self {
+        BrightColors {
+            black: Color::Reset,
+            red: Color::Reset,
+            green: Color::Reset,
+            yellow: Color::Reset,
+            blue: Color::Reset,
+            magenta: Color::Reset,
+            orange: Color::Reset,
+            white: Color::Reset,
+        }
+    }
+}
+
 impl Default for BrightColors {
     fn default() -> Self {
         BrightColors {
@@ -114,3 +237,31 @@ impl Default for BrightColors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monochrome_tells_things_apart_without_colors() {
+        let colors = Colors::monochrome();
+        assert_eq!(colors.normal.red, Color::Reset);
+        assert_eq!(colors.tokens.len(), token_highlight().len());
+        assert!(colors.tokens.values().all(|style| style.fg.is_none()));
+
+        let focused = colors.pane_border(true, false);
+        let unfocused = colors.pane_border(false, false);
+        assert!(focused.add_modifier.contains(Modifier::REVERSED));
+        assert!(!unfocused.add_modifier.contains(Modifier::REVERSED));
+        assert_ne!(
+            colors.highlight(Color::Reset, Color::Reset),
+            colors.marked(Color::Reset, Color::Reset)
+        );
+
+        let colors = Colors::default();
+        assert_eq!(
+            colors.highlight(colors.normal.white, colors.normal.blue),
+            Style::new().fg(colors.normal.white).bg(colors.normal.blue)
+        );
+    }
+}
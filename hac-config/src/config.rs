@@ -1,8 +1,10 @@
 use crate::default_config::DEFAULT_CONFIG;
-use crate::{EditorMode, APP_NAME, CONFIG_ENV_VAR, CONFIG_FILE, XDG_DEFAULTS, XDG_ENV_VARS};
+use crate::paths;
+use crate::{EditorMode, TEMPLATES_FILE};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
 
@@ -47,11 +49,276 @@ pub enum Action {
     JumpToClosing,
     JumpToEmptyLineBelow,
     JumpToEmptyLineAbove,
+    /// opens the body on `$VISUAL` or `$EDITOR`
+    EditExternally,
+    /// shows the value of the placeholder under the cursor
+    ShowVariable,
+    /// soft wraps long lines instead of scrolling sideways
+    ToggleWrap,
+    /// sends the body as written, without substituting its placeholders
+    ToggleRawBody,
+    /// switches to the next body type, which changes how the body is encoded
+    /// and its content type
+    CycleBodyType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub editor_keys: Keys,
+    /// proxy used by every request, collections can override it. Supports
+    /// `http://`, `https://` and `socks5://` urls, credentials can be given
+    /// on the url itself
+    pub proxy: Option<String>,
+    /// asks for confirmation before sending `DELETE`, `PUT` or `PATCH`
+    /// requests to any of the protected hosts
+    #[serde(default)]
+    pub confirm_destructive: bool,
+    /// hosts where destructive requests need confirmation, `*` matches any
+    /// sequence of characters, so `*.prod.*` matches `api.prod.example.com`
+    #[serde(default)]
+    pub protected_hosts: Vec<String>,
+    /// reopens the collection and request that were open when the
+    /// application last closed, instead of starting on the dashboard
+    #[serde(default)]
+    pub restore_session: bool,
+    /// moves the focus to the response pane whenever a request is sent
+    #[serde(default = "focus_response_on_send")]
+    pub focus_response_on_send: bool,
+    /// resending with `<C-r>` while a request is in flight cancels it and
+    /// sends again, instead of being ignored
+    #[serde(default)]
+    pub resend_cancels_pending: bool,
+    /// seconds between sends suggested when watching a request
+    #[serde(default = "watch_interval_secs")]
+    pub watch_interval_secs: u64,
+    /// response bodies bigger than this many bytes are written to a temp
+    /// file as they arrive, and only their start is shown. `0` keeps every
+    /// body in memory
+    #[serde(default = "max_body_in_memory")]
+    pub max_body_in_memory: u64,
+    /// sending a request with a body bigger than this many bytes shows a
+    /// warning, the request is still sent. `0` never warns
+    #[serde(default = "max_request_body")]
+    pub max_request_body: u64,
+    /// pacing of collection runs started from the viewer
+    #[serde(default)]
+    pub runner: RunnerConfig,
+    /// what the terminal is told about requests, so a slow one finishing
+    /// is noticed from another window
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// looks for a newer release on startup and mentions it on the
+    /// dashboard, `--offline` skips it for a single run
+    #[serde(default = "check_updates")]
+    pub check_updates: bool,
+    /// never touches the network, sends fail right away and the update
+    /// check is skipped. `--offline` does the same for a single run, and
+    /// `O` toggles it while running
+    #[serde(default)]
+    pub offline: bool,
+    /// quitting with unsaved edits or requests in flight asks first,
+    /// disabling it quits right away like it used to
+    #[serde(default = "confirm_quit")]
+    pub confirm_quit: bool,
+    /// keys jumping to the requests opened last, the first key goes to the
+    /// request opened before the current one. Only `A-` and `C-` chords are
+    /// accepted, so they never get in the way of typing
+    #[serde(default = "recent_request_keys")]
+    pub recent_request_keys: Vec<String>,
+    /// draws without colors, telling things apart with attributes like bold
+    /// and reverse video instead. `NO_COLOR` and `--no-color` do the same
+    #[serde(default)]
+    pub no_color: bool,
+    /// how the request editor and the response pane are split, `L` on the
+    /// viewer cycles through them
+    #[serde(default)]
+    pub layout: LayoutOrientation,
+    /// headers sent with every request to the matching hosts, whatever the
+    /// collection. Collection and request headers with the same name take
+    /// precedence
+    #[serde(default)]
+    pub global_headers: Vec<HostHeaders>,
+    /// collections whose file is in any of these directories can't be
+    /// changed from here, like collections linked from a repository
+    /// checkout. `~` is the home directory
+    #[serde(default)]
+    pub read_only_dirs: Vec<PathBuf>,
+}
+
+/// headers for every host matching `host`, when more than one entry matches
+/// a host the later ones take precedence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostHeaders {
+    pub host: HostPattern,
+    pub headers: BTreeMap<String, String>,
+}
+
+/// a host name where `*` matches any sequence of characters, dots included,
+/// eg: `*.internal.corp`. Schemes, ports and paths are not part of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HostPattern(String);
+
+impl HostPattern {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for HostPattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        if pattern.is_empty() {
+            return Err("host pattern can't be empty".into());
+        }
+
+        let is_host_char =
+            |c: &char| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '*');
+        match pattern.chars().find(|c| !is_host_char(c)) {
+            Some(c) => Err(format!(
+                "invalid host pattern {pattern:?}, {c:?} is not allowed, only host names with `*` wildcards are"
+            )),
+            None => Ok(HostPattern(pattern)),
+        }
+    }
+}
+
+impl From<HostPattern> for String {
+    fn from(pattern: HostPattern) -> Self {
+        pattern.0
+    }
+}
+
+/// how the request editor and the response pane share the viewer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutOrientation {
+    /// side by side when the panes are wider than they are tall, stacked
+    /// otherwise. Picked again on every resize
+    #[default]
+    Auto,
+    /// side by side
+    Horizontal,
+    /// one above the other
+    Vertical,
+}
+
+impl LayoutOrientation {
+    pub fn next(&self) -> Self {
+        match self {
+            LayoutOrientation::Auto => LayoutOrientation::Horizontal,
+            LayoutOrientation::Horizontal => LayoutOrientation::Vertical,
+            LayoutOrientation::Vertical => LayoutOrientation::Auto,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LayoutOrientation::Auto => "auto",
+            LayoutOrientation::Horizontal => "horizontal",
+            LayoutOrientation::Vertical => "vertical",
+        }
+    }
+}
+
+/// the cli takes the same settings as flags, eg: `--max-rps`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunnerConfig {
+    /// milliseconds to wait between two requests
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// most requests sent on any second, bursts of up to this many requests
+    /// are allowed
+    pub max_rps: Option<f64>,
+    /// how many requests can be in flight at once. Requests using values
+    /// captured by earlier ones always wait for them
+    #[serde(default = "runner_parallel")]
+    pub parallel: usize,
+    /// a `429` with a `Retry-After` header is sent once more after waiting
+    /// for as long as the server asked
+    #[serde(default)]
+    pub respect_retry_after: bool,
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        RunnerConfig {
+            delay_ms: 0,
+            max_rps: None,
+            parallel: runner_parallel(),
+            respect_retry_after: false,
+        }
+    }
+}
+
+fn runner_parallel() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// shows the request in flight and how the last one went on the window
+    /// title, the title the terminal had is put back on exit
+    #[serde(default = "notify_title")]
+    pub title: bool,
+    /// rings the bell when a slow request finishes
+    #[serde(default)]
+    pub bell: bool,
+    /// sends a desktop notification through the terminal, as `OSC 9`, when
+    /// a slow request finishes
+    #[serde(default)]
+    pub desktop: bool,
+    /// requests taking longer than this many milliseconds are slow
+    #[serde(default = "notify_after_ms")]
+    pub after_ms: u64,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            title: notify_title(),
+            bell: false,
+            desktop: false,
+            after_ms: notify_after_ms(),
+        }
+    }
+}
+
+fn notify_title() -> bool {
+    true
+}
+
+fn notify_after_ms() -> u64 {
+    10_000
+}
+
+fn focus_response_on_send() -> bool {
+    true
+}
+
+fn watch_interval_secs() -> u64 {
+    5
+}
+
+fn check_updates() -> bool {
+    true
+}
+
+fn confirm_quit() -> bool {
+    true
+}
+
+fn recent_request_keys() -> Vec<String> {
+    (1..=9).map(|n| format!("A-{n}")).collect()
+}
+
+fn max_body_in_memory() -> u64 {
+    20 * 1024 * 1024
+}
+
+fn max_request_body() -> u64 {
+    5 * 1024 * 1024
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -85,43 +352,46 @@ where
     Ok(toml::from_str::<Config>(&config_file)?)
 }
 
-/// try to get the configuration path from `XDG_CONFIG_HOME` on unix or `LOCALAPPDATA` on windows
-/// if that fails, fallback to the default path specified on the specification, or `AppData\\Local`
-/// on windows
-/// if the above fails, we return None for the default configuration to be loaded
+/// where the config file is looked for, see `paths` for how it is told.
+/// Only `None` without a home directory, when the default config is loaded
 pub fn get_config_dir_path() -> Option<PathBuf> {
-    let config_path = std::env::var(CONFIG_ENV_VAR).ok().map(|config_path| {
-        tracing::debug!("loading config file from $HAC_CONFIG: {config_path:?}");
-        PathBuf::from(config_path).join(CONFIG_FILE)
-    });
-
-    if config_path.is_some() {
-        return config_path;
-    }
+    paths::config_file()
+}
 
-    let xdg_config_path = std::env::var(XDG_ENV_VARS[0]).ok().map(|config_path| {
-        tracing::debug!("loading config file from $XDG_CONFIG_HOME: {config_path}/hac/hac.toml");
-        Path::new(&config_path).join(APP_NAME).join(CONFIG_FILE)
-    });
+static READ_ONLY_DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new();
 
-    if xdg_config_path.is_some() {
-        return xdg_config_path;
+/// the `read_only_dirs` of the config being used, so any collection can tell
+/// whether it is read-only. Only the first call counts
+pub fn set_read_only_dirs(dirs: Vec<PathBuf>) {
+    if READ_ONLY_DIRS.set(dirs).is_err() {
+        tracing::warn!("read-only directories were already set, ignoring the new ones");
     }
+}
 
-    let xdg_config_path = dirs::home_dir().map(|home_path| {
-        tracing::debug!("loading config file from $HOME: {home_path:?}/.config/hac/hac.toml");
-        Path::new(&home_path)
-            .join(XDG_DEFAULTS[0])
-            .join(APP_NAME)
-            .join(CONFIG_FILE)
-    });
+/// whether `path` is in any of the directories given to `set_read_only_dirs`
+pub fn is_in_read_only_dir(path: &Path) -> bool {
+    is_within_any(
+        path,
+        READ_ONLY_DIRS.get().map(Vec::as_slice).unwrap_or_default(),
+        dirs::home_dir().as_deref(),
+    )
+}
 
-    if xdg_config_path.is_some() {
-        return xdg_config_path;
+/// links are followed, so a collection linked from a read-only directory
+/// is read-only as well
+fn is_within_any(path: &Path, dirs: &[PathBuf], home: Option<&Path>) -> bool {
+    if dirs.is_empty() {
+        return false;
     }
-
-    tracing::debug!("no config file found, loading default");
-    None
+    let resolve = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path = resolve(path);
+    dirs.iter().any(|dir| {
+        let dir = match (dir.strip_prefix("~"), home) {
+            (Ok(rest), Some(home)) => home.join(rest),
+            _ => dir.clone(),
+        };
+        path.starts_with(resolve(&dir))
+    })
 }
 
 fn load_default_config() -> Config {
@@ -132,6 +402,41 @@ pub fn default_as_str() -> &'static str {
     DEFAULT_CONFIG
 }
 
+/// the global headers of the config file, read on their own so a mistake on
+/// them can be told apart from the rest of the file
+#[derive(Deserialize)]
+struct GlobalHeadersSection {
+    #[serde(default)]
+    global_headers: Vec<HostHeaders>,
+}
+
+/// same as `load_config`, but fails on malformed global headers instead of
+/// falling back to the default config, as sending headers to hosts other
+/// than the intended ones is worse than not starting. The error tells the
+/// line they are on
+pub fn try_load_config() -> anyhow::Result<Config> {
+    if let Some(path) = get_config_dir_path() {
+        if paths::is_overridden_config(&path) && !path.exists() {
+            anyhow::bail!("the config file {} does not exist", path.display());
+        }
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            global_headers(&contents)
+                .map_err(|e| anyhow::anyhow!("invalid config file {}\n{e}", path.display()))?;
+        }
+    }
+
+    Ok(load_config())
+}
+
+/// files that are not valid toml at all keep falling back to the default
+/// config, only the global headers section is checked
+fn global_headers(contents: &str) -> Result<Vec<HostHeaders>, toml::de::Error> {
+    if contents.parse::<toml::Table>().is_err() {
+        return Ok(vec![]);
+    }
+    toml::from_str::<GlobalHeadersSection>(contents).map(|section| section.global_headers)
+}
+
 pub fn load_config() -> Config {
     let config = get_config_dir_path().and_then(|path| load_config_from_file(path).ok());
 
@@ -143,8 +448,87 @@ pub fn load_config() -> Config {
 }
 
 pub fn get_usual_path() -> PathBuf {
-    dirs::home_dir()
+    get_config_dir_path()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
         .expect("failed to get the home directory")
-        .join(XDG_DEFAULTS[0])
-        .join(APP_NAME)
+}
+
+/// request templates are shared by every collection, so they are kept next
+/// to the config file instead of on the data directory
+pub fn templates_file() -> PathBuf {
+    get_usual_path().join(TEMPLATES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_malformed_host_patterns_are_rejected_with_their_line() {
+        let contents = r#"
+[[global_headers]]
+host = "*"
+headers = { "User-Agent" = "my-team-tool" }
+
+[[global_headers]]
+host = "https://*.internal.corp"
+headers = { "X-Internal" = "yes" }
+"#;
+        let error = global_headers(contents).unwrap_err().to_string();
+        assert!(error.contains("line 7"), "{error}");
+        assert!(error.contains("invalid host pattern \"https://*.internal.corp\""));
+
+        let contents = contents.replace("https://", "");
+        let headers = global_headers(&contents).unwrap();
+        assert_eq!(headers[1].host.as_str(), "*.internal.corp");
+        assert_eq!(headers[0].headers["User-Agent"], "my-team-tool");
+
+        assert!(global_headers("[[global_headers]]\nhost = \"\"\nheaders = {}").is_err());
+        // broken files are not about the global headers
+        assert!(global_headers("proxy = ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_default_config_has_no_global_headers() {
+        assert!(load_default_config().global_headers.is_empty());
+    }
+
+    #[test]
+    fn test_collections_in_read_only_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("repo").join("api");
+        let collections = dir.path().join("collections");
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::create_dir_all(&collections).unwrap();
+        std::fs::write(shared.join("users.json"), "{}").unwrap();
+        std::fs::write(collections.join("mine.json"), "{}").unwrap();
+
+        let dirs = [PathBuf::from("~/repo")];
+        let home = Some(dir.path());
+        assert!(is_within_any(&shared.join("users.json"), &dirs, home));
+        assert!(!is_within_any(&collections.join("mine.json"), &dirs, home));
+        assert!(!is_within_any(&shared.join("users.json"), &[], home));
+
+        #[cfg(unix)]
+        {
+            let linked = collections.join("users.json");
+            std::os::unix::fs::symlink(shared.join("users.json"), &linked).unwrap();
+            assert!(is_within_any(&linked, &dirs, home));
+        }
+    }
+
+    #[test]
+    fn test_notifications_default_to_title_updates_only() {
+        assert_eq!(
+            load_default_config().notifications,
+            NotificationsConfig::default()
+        );
+
+        let contents = "[editor_keys.normal]\n[editor_keys.insert]\n[notifications]\nbell = true";
+        let config = toml::from_str::<Config>(contents).unwrap().notifications;
+        assert!(config.title);
+        assert!(config.bell);
+        assert!(!config.desktop);
+        assert_eq!(config.after_ms, 10_000);
+    }
 }
@@ -1,23 +1,20 @@
-use crate::{APP_NAME, COLLECTIONS_DIR, XDG_DEFAULTS, XDG_ENV_VARS};
+use crate::paths;
+use crate::{
+    APP_NAME, COLLECTIONS_DIR, SECRETS_FILE, STATE_FILE, TRASH_DIR, TRUSTED_HOOKS_FILE,
+    URL_HISTORY_FILE,
+};
 
 use std::path::PathBuf;
 
 pub fn get_data_dir() -> PathBuf {
-    let data_dir = std::env::var(XDG_ENV_VARS[1])
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from(XDG_DEFAULTS[1]));
-
-    dirs::home_dir()
-        .expect("failed to get the home directory")
-        .join(data_dir)
-        .join(APP_NAME)
+    paths::data_dir()
 }
 
 pub fn get_or_create_data_dir() -> PathBuf {
     let data_dir = get_data_dir();
 
     if !data_dir.exists() && !data_dir.is_dir() {
-        match std::fs::create_dir(&data_dir) {
+        match std::fs::create_dir_all(&data_dir) {
             // if we create the data dir, theres nothing to do
             Ok(_) => {}
             // if we fail to do so, panicking is adequate as we won't be able to properly run the
@@ -41,7 +38,7 @@ pub fn get_or_create_collections_dir() -> PathBuf {
     let collections_dir = get_collections_dir();
 
     if !collections_dir.exists() && !collections_dir.is_dir() {
-        match std::fs::create_dir(&collections_dir) {
+        match std::fs::create_dir_all(&collections_dir) {
             // if we create the collections dir, theres nothing to do
             Ok(_) => {}
             // if we fail to do so, panicking is adequate as we won't be able to properly run the
@@ -56,6 +53,34 @@ pub fn get_or_create_collections_dir() -> PathBuf {
     collections_dir
 }
 
+/// where things the application remembers between runs are stored, like the
+/// sorting of the dashboard
+pub fn state_file() -> PathBuf {
+    get_data_dir().join(STATE_FILE)
+}
+
+/// values of secret variables, kept out of the collection files so those
+/// can be shared
+pub fn secrets_file() -> PathBuf {
+    get_data_dir().join(SECRETS_FILE)
+}
+
+/// collections the user allowed to run hook commands
+pub fn trusted_hooks_file() -> PathBuf {
+    get_data_dir().join(TRUSTED_HOOKS_FILE)
+}
+
+/// urls sent from the uri bar, suggested back while typing
+pub fn url_history_file() -> PathBuf {
+    get_data_dir().join(URL_HISTORY_FILE)
+}
+
+/// copies of collection files replaced by an overwrite, so what was on disk
+/// can still be recovered
+pub fn trash_dir() -> PathBuf {
+    get_data_dir().join(TRASH_DIR)
+}
+
 pub fn log_file() -> (PathBuf, String) {
     (get_data_dir(), format!("{}.log", APP_NAME))
 }
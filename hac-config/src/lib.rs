@@ -1,12 +1,16 @@
 pub mod config;
 pub mod data;
 mod default_config;
+pub mod paths;
 
 pub use config::{
-    default_as_str, get_config_dir_path, get_usual_path, load_config, Action, Config, KeyAction,
+    default_as_str, get_config_dir_path, get_usual_path, is_in_read_only_dir, load_config,
+    set_read_only_dirs, templates_file, try_load_config, Action, Config, HostHeaders, HostPattern,
+    KeyAction, LayoutOrientation, NotificationsConfig, RunnerConfig,
 };
 pub use data::{
     get_collections_dir, get_or_create_collections_dir, get_or_create_data_dir, log_file,
+    secrets_file, state_file, trash_dir, trusted_hooks_file, url_history_file,
 };
 use serde::{Deserialize, Serialize};
 
@@ -17,19 +21,16 @@ pub enum EditorMode {
 }
 
 pub static APP_NAME: &str = "hac";
+/// what the config, data and cache directories are called, older releases
+/// named them after `APP_NAME`
+pub static DIR_NAME: &str = "httpretty";
 pub static COLLECTIONS_DIR: &str = "collections";
 pub static CONFIG_FILE: &str = "hac.toml";
+pub static STATE_FILE: &str = "state.json";
+pub static SECRETS_FILE: &str = "secrets.json";
+pub static TEMPLATES_FILE: &str = "templates.json";
+pub static TRUSTED_HOOKS_FILE: &str = "trusted_hooks.json";
+pub static URL_HISTORY_FILE: &str = "url_history.json";
 pub static THEMES_DIR: &str = "themes";
+pub static TRASH_DIR: &str = "trash";
 pub static CONFIG_ENV_VAR: &str = "HAC_CONFIG";
-
-#[cfg(unix)]
-static XDG_ENV_VARS: [&str; 2] = ["XDG_CONFIG_HOME", "XDG_DATA_HOME"];
-
-#[cfg(windows)]
-static XDG_ENV_VARS: [&str; 2] = ["LOCALAPPDATA", "LOCALAPPDATA"];
-
-#[cfg(unix)]
-static XDG_DEFAULTS: [&str; 2] = [".config", ".local/share"];
-
-#[cfg(windows)]
-static XDG_DEFAULTS: [&str; 2] = ["AppData\\Local", "AppData\\Local"];
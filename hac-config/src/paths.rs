@@ -0,0 +1,471 @@
+use crate::{APP_NAME, CONFIG_ENV_VAR, CONFIG_FILE, DIR_NAME};
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// locations given on the command line, they win over the environment so
+/// more than one profile can be used side by side
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Overrides {
+    pub config_file: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+}
+
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+/// has to be called before anything is read or written, as every path is
+/// resolved again when asked for. Only the first call counts
+pub fn set_overrides(overrides: Overrides) {
+    if OVERRIDES.set(overrides).is_err() {
+        tracing::warn!("path overrides were already set, ignoring the new ones");
+    }
+}
+
+pub fn overrides() -> Overrides {
+    OVERRIDES.get().cloned().unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Platform {
+    Linux,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Linux
+        }
+    }
+}
+
+/// where every file is read from and written to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paths {
+    /// the config file, which doesn't have to exist. Only missing when
+    /// there is no home directory to look on
+    pub config_file: Option<PathBuf>,
+    /// collections, history, state, secrets and logs
+    pub data_dir: PathBuf,
+    /// anything that can be thrown away and computed again
+    pub cache_dir: PathBuf,
+    /// directories of older releases still being used, as nothing was found
+    /// where they are looked for now
+    pub legacy: Vec<PathBuf>,
+}
+
+/// everything paths are resolved from, so tests don't depend on who runs
+/// them
+struct Env<'a> {
+    platform: Platform,
+    home: Option<PathBuf>,
+    var: &'a dyn Fn(&str) -> Option<String>,
+    overrides: &'a Overrides,
+}
+
+impl Env<'_> {
+    /// only asked for when nothing on the environment says where to go
+    fn home(&self) -> PathBuf {
+        self.home.clone().expect("failed to get the home directory")
+    }
+
+    /// `name` from the environment. Empty and relative values count as
+    /// unset, as the xdg spec says to ignore the relative ones
+    fn dir_var(&self, name: &str) -> Option<PathBuf> {
+        (self.var)(name)
+            .map(PathBuf::from)
+            .filter(|dir| dir.has_root())
+    }
+
+    fn resolve(&self) -> Paths {
+        let mut legacy = vec![];
+        let config_file = self.config_file(&mut legacy);
+        let data_dir = self.data_dir(&mut legacy);
+        let cache_dir = self.cache_dir();
+
+        Paths {
+            config_file,
+            data_dir,
+            cache_dir,
+            legacy,
+        }
+    }
+
+    fn config_file(&self, legacy: &mut Vec<PathBuf>) -> Option<PathBuf> {
+        if let Some(path) = self.overrides.config_file.clone() {
+            return Some(path);
+        }
+        if let Some(dir) = (self.var)(CONFIG_ENV_VAR).filter(|dir| !dir.is_empty()) {
+            return Some(PathBuf::from(dir).join(CONFIG_FILE));
+        }
+
+        let dir = match self.platform {
+            Platform::Windows => self.dir_var("APPDATA"),
+            _ => self.dir_var("XDG_CONFIG_HOME"),
+        };
+        let dir = match (dir, self.home.as_ref()) {
+            (Some(dir), _) => dir,
+            (None, Some(home)) => match self.platform {
+                Platform::Linux => home.join(".config"),
+                Platform::MacOs => home.join("Library").join("Preferences"),
+                Platform::Windows => home.join("AppData").join("Roaming"),
+            },
+            (None, None) => return None,
+        };
+        let path = dir.join(DIR_NAME).join(CONFIG_FILE);
+
+        let before = self
+            .before_rename("XDG_CONFIG_HOME", &[".config"])
+            .map(|dir| dir.join(CONFIG_FILE));
+        Some(self.or_legacy(path, before, legacy))
+    }
+
+    fn data_dir(&self, legacy: &mut Vec<PathBuf>) -> PathBuf {
+        if let Some(dir) = self.overrides.data_dir.clone() {
+            return dir;
+        }
+
+        let dir = match self.platform {
+            Platform::Windows => self.dir_var("LOCALAPPDATA"),
+            _ => self.dir_var("XDG_DATA_HOME"),
+        };
+        let path = dir
+            .unwrap_or_else(|| match self.platform {
+                Platform::Linux => self.home().join(".local").join("share"),
+                Platform::MacOs => self.home().join("Library").join("Application Support"),
+                Platform::Windows => self.home().join("AppData").join("Local"),
+            })
+            .join(DIR_NAME);
+
+        let before = self.before_rename("XDG_DATA_HOME", &[".local", "share"]);
+        self.or_legacy(path, before, legacy)
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        // a profile given on the command line keeps its cache to itself
+        if let Some(dir) = self.overrides.data_dir.as_ref() {
+            return dir.join("cache");
+        }
+
+        match self.platform {
+            Platform::Windows => self
+                .dir_var("LOCALAPPDATA")
+                .unwrap_or_else(|| self.home().join("AppData").join("Local"))
+                .join(DIR_NAME)
+                .join("cache"),
+            Platform::Linux | Platform::MacOs => self
+                .dir_var("XDG_CACHE_HOME")
+                .unwrap_or_else(|| match self.platform {
+                    Platform::MacOs => self.home().join("Library").join("Caches"),
+                    _ => self.home().join(".cache"),
+                })
+                .join(DIR_NAME),
+        }
+    }
+
+    /// the directory named `hac` releases before the rename kept their
+    /// files on. Windows had both the config and the data on
+    /// `%LOCALAPPDATA%`, everywhere else it was `xdg_var` or `xdg_default`
+    /// under the home, macos included
+    fn before_rename(&self, xdg_var: &str, xdg_default: &[&str]) -> Option<PathBuf> {
+        let dir = match self.platform {
+            Platform::Windows => self.dir_var("LOCALAPPDATA").or_else(|| {
+                self.home
+                    .as_ref()
+                    .map(|home| home.join("AppData").join("Local"))
+            }),
+            Platform::Linux | Platform::MacOs => self.dir_var(xdg_var).or_else(|| {
+                self.home.as_ref().map(|home| {
+                    xdg_default
+                        .iter()
+                        .fold(home.clone(), |dir, part| dir.join(part))
+                })
+            }),
+        };
+        dir.map(|dir| dir.join(APP_NAME))
+    }
+
+    /// keeps using where releases before the rename wrote to, see
+    /// `before_rename`, as long as nothing was written to `path` yet
+    fn or_legacy(
+        &self,
+        path: PathBuf,
+        before: Option<PathBuf>,
+        legacy: &mut Vec<PathBuf>,
+    ) -> PathBuf {
+        match before {
+            Some(before) if !path.exists() && before.exists() => {
+                legacy.push(before.clone());
+                before
+            }
+            _ => path,
+        }
+    }
+}
+
+/// runs `f` with the environment of the process and the overrides given on
+/// the command line
+fn with_env<T>(f: impl FnOnce(&Env) -> T) -> T {
+    let overrides = overrides();
+    let var = |name: &str| std::env::var(name).ok();
+    f(&Env {
+        platform: Platform::current(),
+        home: dirs::home_dir(),
+        var: &var,
+        overrides: &overrides,
+    })
+}
+
+pub fn paths() -> Paths {
+    with_env(|env| env.resolve())
+}
+
+pub fn data_dir() -> PathBuf {
+    with_env(|env| env.data_dir(&mut vec![]))
+}
+
+pub fn cache_dir() -> PathBuf {
+    with_env(|env| env.cache_dir())
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    with_env(|env| env.config_file(&mut vec![]))
+}
+
+/// tells on the log where everything is, and when an older location is
+/// still being used, so it can be moved by hand. Called once logging is
+/// set up, as the log itself lives on the data directory
+pub fn log_locations() {
+    let paths = paths();
+    tracing::info!(
+        config_file = ?paths.config_file,
+        data_dir = ?paths.data_dir,
+        cache_dir = ?paths.cache_dir,
+        "resolved locations"
+    );
+    for dir in paths.legacy {
+        tracing::warn!("reading from {dir:?}, where older releases kept their files");
+    }
+}
+
+/// whether `path` is the config file given on the command line, which
+/// must exist as falling back to the default config would hide a typo
+pub fn is_overridden_config(path: &Path) -> bool {
+    overrides()
+        .config_file
+        .is_some_and(|config_file| config_file.eq(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(
+        platform: Platform,
+        home: &Path,
+        vars: &[(&str, &str)],
+        overrides: &Overrides,
+    ) -> Paths {
+        let vars = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+        let var = |name: &str| vars.get(name).cloned();
+        Env {
+            platform,
+            home: Some(home.to_path_buf()),
+            var: &var,
+            overrides,
+        }
+        .resolve()
+    }
+
+    #[test]
+    fn test_linux_follows_xdg() {
+        let home = Path::new("/home/ada");
+        let paths = resolve(Platform::Linux, home, &[], &Overrides::default());
+        assert_eq!(
+            paths.config_file,
+            Some(PathBuf::from("/home/ada/.config/httpretty/hac.toml"))
+        );
+        assert_eq!(
+            paths.data_dir,
+            PathBuf::from("/home/ada/.local/share/httpretty")
+        );
+        assert_eq!(paths.cache_dir, PathBuf::from("/home/ada/.cache/httpretty"));
+
+        let vars = [
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_DATA_HOME", "/xdg/data"),
+            ("XDG_CACHE_HOME", "/xdg/cache"),
+        ];
+        let paths = resolve(Platform::Linux, home, &vars, &Overrides::default());
+        assert_eq!(
+            paths.config_file,
+            Some(PathBuf::from("/xdg/config/httpretty/hac.toml"))
+        );
+        assert_eq!(paths.data_dir, PathBuf::from("/xdg/data/httpretty"));
+        assert_eq!(paths.cache_dir, PathBuf::from("/xdg/cache/httpretty"));
+
+        // empty and relative values are unset, and $HAC_CONFIG wins over xdg
+        let vars = [
+            ("XDG_DATA_HOME", ""),
+            ("XDG_CACHE_HOME", "cache"),
+            ("HAC_CONFIG", "/profiles/work"),
+        ];
+        let paths = resolve(Platform::Linux, home, &vars, &Overrides::default());
+        assert_eq!(
+            paths.config_file,
+            Some(PathBuf::from("/profiles/work/hac.toml"))
+        );
+        assert_eq!(
+            paths.data_dir,
+            PathBuf::from("/home/ada/.local/share/httpretty")
+        );
+        assert_eq!(paths.cache_dir, PathBuf::from("/home/ada/.cache/httpretty"));
+    }
+
+    #[test]
+    fn test_xdg_needs_no_home() {
+        let vars = [
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_DATA_HOME", "/xdg/data"),
+            ("XDG_CACHE_HOME", "/xdg/cache"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let var = |name: &str| {
+            vars.iter()
+                .find(|(k, _)| k.eq(name))
+                .map(|(_, v)| v.clone())
+        };
+        let paths = Env {
+            platform: Platform::Linux,
+            home: None,
+            var: &var,
+            overrides: &Overrides::default(),
+        }
+        .resolve();
+
+        assert_eq!(
+            paths.config_file,
+            Some(PathBuf::from("/xdg/config/httpretty/hac.toml"))
+        );
+        assert_eq!(paths.data_dir, PathBuf::from("/xdg/data/httpretty"));
+        assert_eq!(paths.cache_dir, PathBuf::from("/xdg/cache/httpretty"));
+    }
+
+    #[test]
+    fn test_command_line_overrides_win() {
+        let overrides = Overrides {
+            config_file: Some("/profiles/work.toml".into()),
+            data_dir: Some("/profiles/work".into()),
+        };
+        let vars = [("HAC_CONFIG", "/elsewhere"), ("XDG_DATA_HOME", "/xdg/data")];
+
+        for platform in [Platform::Linux, Platform::MacOs, Platform::Windows] {
+            let paths = resolve(platform, Path::new("/home/ada"), &vars, &overrides);
+            assert_eq!(
+                paths.config_file,
+                Some(PathBuf::from("/profiles/work.toml"))
+            );
+            assert_eq!(paths.data_dir, PathBuf::from("/profiles/work"));
+            assert_eq!(paths.cache_dir, PathBuf::from("/profiles/work/cache"));
+        }
+    }
+
+    #[test]
+    fn test_platform_directories() {
+        let home = Path::new("/Users/ada");
+        let paths = resolve(Platform::MacOs, home, &[], &Overrides::default());
+        assert_eq!(
+            paths.config_file,
+            Some(PathBuf::from(
+                "/Users/ada/Library/Preferences/httpretty/hac.toml"
+            ))
+        );
+        assert_eq!(
+            paths.data_dir,
+            PathBuf::from("/Users/ada/Library/Application Support/httpretty")
+        );
+        assert_eq!(
+            paths.cache_dir,
+            PathBuf::from("/Users/ada/Library/Caches/httpretty")
+        );
+        assert!(paths.legacy.is_empty());
+
+        let vars = [
+            ("APPDATA", "/appdata/roaming"),
+            ("LOCALAPPDATA", "/appdata/local"),
+        ];
+        let paths = resolve(Platform::Windows, home, &vars, &Overrides::default());
+        assert_eq!(
+            paths.config_file,
+            Some(PathBuf::from("/appdata/roaming/httpretty/hac.toml"))
+        );
+        assert_eq!(paths.data_dir, PathBuf::from("/appdata/local/httpretty"));
+        assert_eq!(
+            paths.cache_dir,
+            PathBuf::from("/appdata/local/httpretty/cache")
+        );
+    }
+
+    #[test]
+    fn test_older_installations_are_read_in_place() {
+        let home = tempfile::tempdir().unwrap();
+        let before = home.path().join(".local/share/hac");
+        std::fs::create_dir_all(&before).unwrap();
+        std::fs::create_dir_all(home.path().join(".config/hac")).unwrap();
+        std::fs::write(home.path().join(".config/hac/hac.toml"), "").unwrap();
+
+        for platform in [Platform::Linux, Platform::MacOs] {
+            let paths = resolve(platform, home.path(), &[], &Overrides::default());
+            assert_eq!(paths.data_dir, before);
+            assert_eq!(
+                paths.config_file,
+                Some(home.path().join(".config/hac/hac.toml"))
+            );
+            assert_eq!(paths.legacy.len(), 2);
+        }
+
+        // once something is written to the new place it is used instead
+        let now = home.path().join(".local/share/httpretty");
+        std::fs::create_dir_all(&now).unwrap();
+        let paths = resolve(Platform::Linux, home.path(), &[], &Overrides::default());
+        assert_eq!(paths.data_dir, now);
+        assert_eq!(paths.legacy.len(), 1);
+
+        // windows never used the xdg directories
+        let paths = resolve(Platform::Windows, home.path(), &[], &Overrides::default());
+        assert!(paths.legacy.is_empty());
+    }
+
+    #[test]
+    fn test_older_installations_are_read_in_place_on_windows() {
+        let home = tempfile::tempdir().unwrap();
+        let local = home.path().join("AppData").join("Local");
+        let before = local.join("hac");
+        std::fs::create_dir_all(&before).unwrap();
+        std::fs::write(before.join("hac.toml"), "").unwrap();
+
+        // config and data both lived on the same directory
+        let paths = resolve(Platform::Windows, home.path(), &[], &Overrides::default());
+        assert_eq!(paths.config_file, Some(before.join("hac.toml")));
+        assert_eq!(paths.data_dir, before);
+        assert_eq!(paths.legacy.len(), 2);
+
+        // and `%LOCALAPPDATA%` wins over the home, like it did back then
+        let elsewhere = tempfile::tempdir().unwrap();
+        let moved = elsewhere.path().join("hac");
+        std::fs::create_dir_all(&moved).unwrap();
+        let vars = [("LOCALAPPDATA", elsewhere.path().to_str().unwrap())];
+        let paths = resolve(Platform::Windows, home.path(), &vars, &Overrides::default());
+        assert_eq!(paths.data_dir, moved);
+        assert_eq!(paths.legacy, vec![moved]);
+    }
+}
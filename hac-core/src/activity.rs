@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::task::AbortHandle;
+
+/// how long finished work stays listed, long enough to see how it ended
+pub const LINGER: Duration = Duration::from_secs(10);
+/// the most entries kept, finished ones are dropped first
+pub const MAX_ENTRIES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActivityKind {
+    Request,
+    /// a request whose body takes a while to arrive
+    Download,
+    Run,
+    Import,
+}
+
+impl std::fmt::Display for ActivityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityKind::Request => write!(f, "request"),
+            ActivityKind::Download => write!(f, "download"),
+            ActivityKind::Run => write!(f, "run"),
+            ActivityKind::Import => write!(f, "import"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActivityState {
+    Running,
+    Done(String),
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEntry {
+    pub id: u64,
+    pub kind: ActivityKind,
+    /// what is being worked on, eg: `GET /users`
+    pub label: String,
+    /// how far along it is, eg: how much of a body was downloaded
+    pub detail: Option<String>,
+    pub state: ActivityState,
+    pub started: Instant,
+    pub finished: Option<Instant>,
+    /// whether there is a task to abort
+    pub cancellable: bool,
+}
+
+impl ActivityEntry {
+    pub fn is_running(&self) -> bool {
+        self.state.eq(&ActivityState::Running)
+    }
+
+    /// for how long it ran, or has been running
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.finished
+            .unwrap_or(now)
+            .saturating_duration_since(self.started)
+    }
+}
+
+#[derive(Debug)]
+struct Tracked {
+    entry: ActivityEntry,
+    abort: Option<AbortHandle>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    next_id: u64,
+    tracked: VecDeque<Tracked>,
+}
+
+impl Registry {
+    fn get_mut(&mut self, id: u64) -> Option<&mut Tracked> {
+        self.tracked
+            .iter_mut()
+            .find(|tracked| tracked.entry.id.eq(&id))
+    }
+
+    /// tasks that ended without anyone telling how are only known to be done
+    fn reconcile(&mut self, now: Instant) {
+        for tracked in self.tracked.iter_mut() {
+            let ended = tracked.abort.as_ref().is_some_and(AbortHandle::is_finished);
+            if tracked.entry.is_running() && ended {
+                tracked.entry.state = ActivityState::Done(String::from("finished"));
+                tracked.entry.finished = Some(now);
+            }
+        }
+    }
+
+    fn prune(&mut self, now: Instant) {
+        self.tracked.retain(|tracked| {
+            tracked
+                .entry
+                .finished
+                .is_none_or(|finished| now.saturating_duration_since(finished).lt(&LINGER))
+        });
+
+        while self.tracked.len().gt(&MAX_ENTRIES) {
+            // running work only stops being listed when everything else is
+            // running as well, it keeps running either way
+            let oldest = self
+                .tracked
+                .iter()
+                .position(|tracked| !tracked.entry.is_running())
+                .unwrap_or_default();
+            self.tracked.remove(oldest);
+        }
+    }
+}
+
+/// what is happening on the background, like requests being sent, runs and
+/// imports. Whoever spawns the work reports it here, so it can be listed and
+/// cancelled from a single place. Clones share the same entries
+#[derive(Debug, Clone, Default)]
+pub struct Activity {
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl Activity {
+    /// lists new work as running, returning the id everything else takes
+    pub fn start(&self, kind: ActivityKind, label: impl Into<String>) -> u64 {
+        let mut registry = self.registry.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.tracked.push_back(Tracked {
+            entry: ActivityEntry {
+                id,
+                kind,
+                label: label.into(),
+                detail: None,
+                state: ActivityState::Running,
+                started: Instant::now(),
+                finished: None,
+                cancellable: false,
+            },
+            abort: None,
+        });
+        registry.prune(Instant::now());
+        id
+    }
+
+    /// the task doing the work, so it can be cancelled and so it is known
+    /// to be done once it ends
+    pub fn attach(&self, id: u64, abort: AbortHandle) {
+        if let Some(tracked) = self.registry.lock().unwrap().get_mut(id) {
+            tracked.entry.cancellable = true;
+            tracked.abort = Some(abort);
+        }
+    }
+
+    /// changes what the work is listed as, the same work can turn out to be
+    /// something else once it is underway
+    pub fn set_kind(&self, id: u64, kind: ActivityKind) {
+        if let Some(tracked) = self.registry.lock().unwrap().get_mut(id) {
+            tracked.entry.kind = kind;
+        }
+    }
+
+    pub fn progress(&self, id: u64, detail: impl Into<String>) {
+        if let Some(tracked) = self.registry.lock().unwrap().get_mut(id) {
+            tracked.entry.detail = Some(detail.into());
+        }
+    }
+
+    /// how the work ended, either way. Does nothing for cancelled work.
+    /// The task may be seen ending before its outcome is handled, so this
+    /// replaces the plain `finished` it was given then
+    pub fn finish(&self, id: u64, outcome: Result<String, String>) {
+        if let Some(tracked) = self.registry.lock().unwrap().get_mut(id) {
+            if tracked.entry.state.eq(&ActivityState::Cancelled) {
+                return;
+            }
+            tracked.entry.state = match outcome {
+                Ok(outcome) => ActivityState::Done(outcome),
+                Err(reason) => ActivityState::Failed(reason),
+            };
+            tracked.entry.finished = Some(Instant::now());
+        }
+    }
+
+    /// aborts the task of `id`, returning whether there was one running
+    pub fn cancel(&self, id: u64) -> bool {
+        let mut registry = self.registry.lock().unwrap();
+        registry.reconcile(Instant::now());
+        let Some(tracked) = registry.get_mut(id) else {
+            return false;
+        };
+        let Some(abort) = tracked
+            .abort
+            .as_ref()
+            .filter(|_| tracked.entry.is_running())
+        else {
+            return false;
+        };
+
+        abort.abort();
+        tracked.entry.state = ActivityState::Cancelled;
+        tracked.entry.finished = Some(Instant::now());
+        true
+    }
+
+    pub fn is_cancelled(&self, id: u64) -> bool {
+        self.registry
+            .lock()
+            .unwrap()
+            .get_mut(id)
+            .is_some_and(|tracked| tracked.entry.state.eq(&ActivityState::Cancelled))
+    }
+
+    /// everything listed as of `now`, oldest first
+    pub fn entries_at(&self, now: Instant) -> Vec<ActivityEntry> {
+        let mut registry = self.registry.lock().unwrap();
+        registry.reconcile(now);
+        registry.prune(now);
+        registry
+            .tracked
+            .iter()
+            .map(|tracked| tracked.entry.clone())
+            .collect()
+    }
+
+    pub fn entries(&self) -> Vec<ActivityEntry> {
+        self.entries_at(Instant::now())
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.entries()
+            .iter()
+            .filter(|entry| entry.is_running())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_work_is_listed_until_it_ages_out() {
+        let activity = Activity::default();
+        let send = activity.start(ActivityKind::Request, "GET /users");
+        let import = activity.start(ActivityKind::Import, "https://a.dev/api.http");
+        assert_eq!(activity.active_count(), 2);
+
+        activity.progress(send, "1.2 MB of 4 MB");
+        activity.set_kind(send, ActivityKind::Download);
+        activity.finish(send, Ok(String::from("200 OK")));
+        activity.finish(import, Err(String::from("401 Unauthorized")));
+
+        let entries = activity.entries();
+        assert_eq!(activity.active_count(), 0);
+        assert_eq!(entries[0].detail.as_deref(), Some("1.2 MB of 4 MB"));
+        assert_eq!(entries[0].kind, ActivityKind::Download);
+        assert_eq!(entries[0].state, ActivityState::Done("200 OK".into()));
+        assert_eq!(
+            entries[1].state,
+            ActivityState::Failed("401 Unauthorized".into())
+        );
+
+        let later = Instant::now() + LINGER;
+        assert!(activity.entries_at(later).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_aborts_the_task() {
+        let activity = Activity::default();
+        let id = activity.start(ActivityKind::Run, "users");
+        // nothing to abort until a task is attached
+        assert!(!activity.cancel(id));
+
+        let handle = tokio::spawn(tokio::time::sleep(Duration::from_secs(60)));
+        activity.attach(id, handle.abort_handle());
+        assert!(activity.cancel(id));
+        assert!(handle.await.unwrap_err().is_cancelled());
+        assert!(activity.is_cancelled(id));
+        assert!(!activity.cancel(id));
+        activity.finish(id, Ok(String::from("2 passed")));
+        assert!(activity.is_cancelled(id));
+
+        // tasks that end on their own are known to be done
+        let id = activity.start(ActivityKind::Request, "GET /me");
+        let handle = tokio::spawn(async {});
+        activity.attach(id, handle.abort_handle());
+        handle.await.unwrap();
+        assert_eq!(
+            activity.entries()[1].state,
+            ActivityState::Done("finished".into())
+        );
+        activity.finish(id, Ok(String::from("200 OK")));
+        assert_eq!(
+            activity.entries()[1].state,
+            ActivityState::Done("200 OK".into())
+        );
+    }
+
+    #[test]
+    fn test_the_registry_is_bounded() {
+        let activity = Activity::default();
+        let running = activity.start(ActivityKind::Run, "long run");
+        for idx in 0..MAX_ENTRIES * 2 {
+            let id = activity.start(ActivityKind::Request, format!("GET /{idx}"));
+            activity.finish(id, Ok(String::from("200 OK")));
+        }
+
+        let entries = activity.entries();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].id, running);
+        assert_eq!(
+            entries.last().unwrap().label,
+            format!("GET /{}", MAX_ENTRIES * 2 - 1)
+        );
+    }
+}
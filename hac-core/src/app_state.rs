@@ -0,0 +1,353 @@
+use crate::collection::Collection;
+
+use hac_config::LayoutOrientation;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// the orderings the dashboard can display collections in, ties are always
+/// broken by name so the order is deterministic
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionSort {
+    #[default]
+    Name,
+    RecentlyOpened,
+    RecentlyModified,
+}
+
+impl CollectionSort {
+    pub fn next(&self) -> Self {
+        match self {
+            CollectionSort::Name => CollectionSort::RecentlyOpened,
+            CollectionSort::RecentlyOpened => CollectionSort::RecentlyModified,
+            CollectionSort::RecentlyModified => CollectionSort::Name,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CollectionSort::Name => "name",
+            CollectionSort::RecentlyOpened => "opened",
+            CollectionSort::RecentlyModified => "modified",
+        }
+    }
+}
+
+/// the screens a session can be left on
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionScreen {
+    #[default]
+    Dashboard,
+    Editor,
+}
+
+/// where the user was when the application last closed, so it can be
+/// reopened right there when `restore_session` is enabled
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub screen: SessionScreen,
+    /// path of the collection file that was open
+    pub collection: Option<PathBuf>,
+    /// id of the request that was selected on the collection
+    pub request: Option<String>,
+    /// name of the environment that was active on the collection
+    pub environment: Option<String>,
+}
+
+impl Session {
+    /// the collection the session should reopen, if it was left on the
+    /// editor and its collection still exists
+    pub fn find_collection<'c>(&self, collections: &'c [Collection]) -> Option<&'c Collection> {
+        if self.screen.ne(&SessionScreen::Editor) {
+            return None;
+        }
+
+        let path = self.collection.as_ref()?;
+        collections
+            .iter()
+            .find(|collection| collection.path.eq(path))
+    }
+}
+
+/// things the application remembers between runs that don't belong to the
+/// config file nor to any collection
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppState {
+    #[serde(default)]
+    pub collection_sort: CollectionSort,
+    /// when each collection was last opened, in milliseconds since the unix
+    /// epoch, keyed by the path of the collection file
+    #[serde(default)]
+    pub last_opened: BTreeMap<PathBuf, u128>,
+    /// paths of the collections pinned to the top of the dashboard
+    #[serde(default)]
+    pub pinned: BTreeSet<PathBuf>,
+    #[serde(default)]
+    pub session: Session,
+    /// release whose update notice was dismissed, newer releases are still
+    /// mentioned
+    #[serde(default)]
+    pub dismissed_update: Option<String>,
+    /// orientation the viewer was last switched to with `L`, which wins
+    /// over the one on the config
+    #[serde(default)]
+    pub layout: Option<LayoutOrientation>,
+    /// where the state is saved to, a state without a path lives only in
+    /// memory
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl AppState {
+    /// reads the state from `path`, a missing or unreadable file gives the
+    /// default state, which is then saved to `path` on the next change
+    #[tracing::instrument]
+    pub fn load(path: PathBuf) -> Self {
+        let state = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str::<AppState>(&content) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    tracing::warn!("ignoring malformed state file: {e:?}");
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        AppState {
+            path: Some(path),
+            ..state
+        }
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        // a crash halfway through writing would lose every pin and sort
+        crate::fs::replace_file(path, serde_json::to_string_pretty(self)?.as_bytes(), false)?;
+        Ok(())
+    }
+
+    pub fn mark_opened(&mut self, collection: &Path) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        self.last_opened.insert(collection.to_path_buf(), now);
+    }
+
+    /// pins the collection when it isn't pinned and unpins it otherwise,
+    /// returns whether it ended up pinned
+    pub fn toggle_pin(&mut self, collection: &Path) -> bool {
+        match self.pinned.remove(collection) {
+            true => false,
+            false => self.pinned.insert(collection.to_path_buf()),
+        }
+    }
+
+    pub fn is_pinned(&self, collection: &Path) -> bool {
+        self.pinned.contains(collection)
+    }
+
+    pub fn is_update_dismissed(&self, version: &str) -> bool {
+        self.dismissed_update
+            .as_deref()
+            .is_some_and(|dismissed| dismissed.eq(version))
+    }
+
+    /// sorts the collections by the sorting on this state, pinned collections
+    /// come first and each group is sorted on its own
+    pub fn sort_collections(&self, collections: &mut [Collection]) {
+        match self.collection_sort {
+            CollectionSort::Name => collections.sort_by(|a, b| a.info.name.cmp(&b.info.name)),
+            CollectionSort::RecentlyOpened => collections.sort_by_cached_key(|collection| {
+                let opened = self.last_opened.get(&collection.path).copied();
+                (
+                    Reverse(opened.unwrap_or_default()),
+                    collection.info.name.clone(),
+                )
+            }),
+            // every file is stated once up front, never while comparing
+            CollectionSort::RecentlyModified => collections.sort_by_cached_key(|collection| {
+                (
+                    Reverse(modified_at(&collection.path)),
+                    collection.info.name.clone(),
+                )
+            }),
+        }
+
+        // sorting is stable, so this keeps the order inside each group
+        collections.sort_by_key(|collection| !self.is_pinned(&collection.path));
+    }
+}
+
+/// when the file was last written, in milliseconds. Files that can't be
+/// read sort as the oldest
+fn modified_at(path: &Path) -> u128 {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_millis())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn make_collection(name: &str, path: &str) -> Collection {
+        Collection {
+            path: path.into(),
+            ..test_utils::make_collection(name, vec![])
+        }
+    }
+
+    fn names(collections: &[Collection]) -> Vec<&str> {
+        collections.iter().map(|c| c.info.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_cycles_through_every_mode() {
+        let sort = CollectionSort::default();
+        assert_eq!(sort, CollectionSort::Name);
+        assert_eq!(sort.next(), CollectionSort::RecentlyOpened);
+        assert_eq!(sort.next().next(), CollectionSort::RecentlyModified);
+        assert_eq!(sort.next().next().next(), CollectionSort::Name);
+    }
+
+    #[test]
+    fn test_recently_opened_falls_back_to_name() {
+        let mut collections = vec![
+            make_collection("charlie", "c.json"),
+            make_collection("bravo", "b.json"),
+            make_collection("alpha", "a.json"),
+        ];
+        let mut state = AppState {
+            collection_sort: CollectionSort::RecentlyOpened,
+            ..Default::default()
+        };
+        state.last_opened.insert("c.json".into(), 10);
+
+        state.sort_collections(&mut collections);
+        assert_eq!(names(&collections), ["charlie", "alpha", "bravo"]);
+
+        state.collection_sort = CollectionSort::Name;
+        state.sort_collections(&mut collections);
+        assert_eq!(names(&collections), ["alpha", "bravo", "charlie"]);
+    }
+
+    #[test]
+    fn test_recently_modified_sorts_by_the_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        let now = SystemTime::now();
+        for (name, age) in [("a.json", 20), ("b.json", 10)] {
+            let file = std::fs::File::create(path(name)).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(age))
+                .unwrap();
+        }
+        let mut collections = vec![
+            make_collection("alpha", &path("a.json")),
+            make_collection("missing", &path("c.json")),
+            make_collection("bravo", &path("b.json")),
+        ];
+        let state = AppState {
+            collection_sort: CollectionSort::RecentlyModified,
+            ..Default::default()
+        };
+
+        state.sort_collections(&mut collections);
+        assert_eq!(names(&collections), ["bravo", "alpha", "missing"]);
+    }
+
+    #[test]
+    fn test_pinned_collections_come_first() {
+        let mut collections = vec![
+            make_collection("alpha", "a.json"),
+            make_collection("bravo", "b.json"),
+            make_collection("charlie", "c.json"),
+            make_collection("delta", "d.json"),
+        ];
+        let mut state = AppState::default();
+        assert!(state.toggle_pin(Path::new("d.json")));
+        assert!(state.toggle_pin(Path::new("b.json")));
+
+        state.sort_collections(&mut collections);
+        assert_eq!(names(&collections), ["bravo", "delta", "alpha", "charlie"]);
+
+        assert!(!state.toggle_pin(Path::new("d.json")));
+        state.sort_collections(&mut collections);
+        assert_eq!(names(&collections), ["bravo", "alpha", "charlie", "delta"]);
+    }
+
+    #[test]
+    fn test_session_only_reopens_existing_collections() {
+        let collections = vec![
+            make_collection("alpha", "a.json"),
+            make_collection("bravo", "b.json"),
+        ];
+        let mut session = Session {
+            screen: SessionScreen::Editor,
+            collection: Some("b.json".into()),
+            request: None,
+            environment: None,
+        };
+        let found = session.find_collection(&collections);
+        assert_eq!(found.map(|c| c.info.name.as_str()), Some("bravo"));
+
+        session.collection = Some("deleted.json".into());
+        assert!(session.find_collection(&collections).is_none());
+
+        session.collection = Some("a.json".into());
+        session.screen = SessionScreen::Dashboard;
+        assert!(session.find_collection(&collections).is_none());
+        assert!(Session::default().find_collection(&collections).is_none());
+    }
+
+    #[test]
+    fn test_state_roundtrips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        let mut state = AppState::load(path.clone());
+        assert_eq!(state.collection_sort, CollectionSort::Name);
+
+        state.collection_sort = CollectionSort::RecentlyModified;
+        state.mark_opened(Path::new("a.json"));
+        state.toggle_pin(Path::new("a.json"));
+        state.session = Session {
+            screen: SessionScreen::Editor,
+            collection: Some("a.json".into()),
+            request: Some("request_id".into()),
+            environment: Some("staging".into()),
+        };
+        state.dismissed_update = Some("0.3.0".into());
+        state.layout = Some(LayoutOrientation::Vertical);
+        state.save().unwrap();
+
+        assert!(!dir.path().join(".state.json.tmp").exists());
+
+        let loaded = AppState::load(path);
+        assert_eq!(loaded, state);
+        assert!(loaded.is_update_dismissed("0.3.0"));
+        assert!(!loaded.is_update_dismissed("0.4.0"));
+    }
+
+    #[test]
+    fn test_state_without_path_is_not_saved() {
+        let state = AppState::default();
+        assert!(state.save().is_ok());
+    }
+}
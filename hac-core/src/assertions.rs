@@ -0,0 +1,702 @@
+pub mod json_path;
+
+use crate::builtins;
+use crate::collection::types::{Assertion, Comparison, JsonCheck};
+use crate::net::request_manager::Response;
+use json_path::JsonPathError;
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+/// outcome of checking a single assertion against a response. `actual` holds
+/// what was found on the response so failures can show why they failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    pub passed: bool,
+    pub actual: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssertionParseError {
+    Empty,
+    /// the first word didn't match any kind of assertion
+    UnknownKind(String),
+    /// status must be a 3 digit code or a class like `2xx`
+    InvalidStatus(String),
+    UnknownOperator(String),
+    MissingOperator,
+    MissingValue,
+    InvalidLength(String),
+    JsonPath(JsonPathError),
+}
+
+impl std::fmt::Display for AssertionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssertionParseError::Empty => write!(f, "assertion is empty"),
+            AssertionParseError::UnknownKind(kind) => write!(
+                f,
+                "unknown assertion `{}`, expected `status`, `header`, `body` or a json path",
+                kind
+            ),
+            AssertionParseError::InvalidStatus(status) => write!(
+                f,
+                "invalid status `{}`, expected a code like `200` or a class like `2xx`",
+                status
+            ),
+            AssertionParseError::UnknownOperator(op) => write!(f, "unknown operator `{}`", op),
+            AssertionParseError::MissingOperator => write!(f, "expected an operator"),
+            AssertionParseError::MissingValue => write!(f, "expected a value"),
+            AssertionParseError::InvalidLength(len) => write!(f, "invalid length `{}`", len),
+            AssertionParseError::JsonPath(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AssertionParseError {}
+
+impl From<JsonPathError> for AssertionParseError {
+    fn from(value: JsonPathError) -> Self {
+        AssertionParseError::JsonPath(value)
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = AssertionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "==" => Ok(Comparison::Eq),
+            "!=" => Ok(Comparison::Ne),
+            ">" => Ok(Comparison::Gt),
+            ">=" => Ok(Comparison::Ge),
+            "<" => Ok(Comparison::Lt),
+            "<=" => Ok(Comparison::Le),
+            _ => Err(AssertionParseError::UnknownOperator(s.into())),
+        }
+    }
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Eq => f.write_str("=="),
+            Comparison::Ne => f.write_str("!="),
+            Comparison::Gt => f.write_str(">"),
+            Comparison::Ge => f.write_str(">="),
+            Comparison::Lt => f.write_str("<"),
+            Comparison::Le => f.write_str("<="),
+        }
+    }
+}
+
+impl Comparison {
+    pub fn compare(&self, lhs: usize, rhs: usize) -> bool {
+        match self {
+            Comparison::Eq => lhs.eq(&rhs),
+            Comparison::Ne => lhs.ne(&rhs),
+            Comparison::Gt => lhs.gt(&rhs),
+            Comparison::Ge => lhs.ge(&rhs),
+            Comparison::Lt => lhs.lt(&rhs),
+            Comparison::Le => lhs.le(&rhs),
+        }
+    }
+}
+
+/// assertions are written in a short textual form on the editor, this is
+/// the inverse of the `Display` implementation, so any displayed assertion
+/// can be parsed back into the same value
+///
+/// - `status 200` or `status 2xx`
+/// - `header content-type == application/json`
+/// - `header content-type contains json`
+/// - `body contains some text`
+/// - `$.data.id exists`
+/// - `$.data.name == hac`
+/// - `$.items length >= 1`
+impl FromStr for Assertion {
+    type Err = AssertionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, rest) = split_word(s);
+
+        match kind {
+            "" => Err(AssertionParseError::Empty),
+            "status" => parse_status(rest),
+            "header" => {
+                let (name, rest) = split_word(rest);
+                let (op, value) = split_word(rest);
+                if name.is_empty() || op.is_empty() {
+                    return Err(AssertionParseError::MissingOperator);
+                }
+                let value = non_empty_value(value)?;
+                match op {
+                    "==" => Ok(Assertion::HeaderEquals {
+                        name: name.into(),
+                        value,
+                    }),
+                    "contains" => Ok(Assertion::HeaderContains {
+                        name: name.into(),
+                        value,
+                    }),
+                    _ => Err(AssertionParseError::UnknownOperator(op.into())),
+                }
+            }
+            "body" => {
+                let (op, value) = split_word(rest);
+                match op {
+                    "contains" => Ok(Assertion::BodyContains {
+                        value: non_empty_value(value)?,
+                    }),
+                    "" => Err(AssertionParseError::MissingOperator),
+                    _ => Err(AssertionParseError::UnknownOperator(op.into())),
+                }
+            }
+            path if path.starts_with('$') => {
+                json_path::parse(path)?;
+                let (op, rest) = split_word(rest);
+                let check = match op {
+                    "exists" => JsonCheck::Exists,
+                    "==" => JsonCheck::Equals {
+                        value: non_empty_value(rest)?,
+                    },
+                    "length" => {
+                        let (comparison, value) = split_word(rest);
+                        if comparison.is_empty() {
+                            return Err(AssertionParseError::MissingOperator);
+                        }
+                        let comparison = comparison.parse::<Comparison>()?;
+                        let value = non_empty_value(value)?;
+                        let value = value
+                            .parse::<usize>()
+                            .map_err(|_| AssertionParseError::InvalidLength(value))?;
+                        JsonCheck::Length { comparison, value }
+                    }
+                    "" => return Err(AssertionParseError::MissingOperator),
+                    _ => return Err(AssertionParseError::UnknownOperator(op.into())),
+                };
+                Ok(Assertion::JsonPath {
+                    path: path.into(),
+                    check,
+                })
+            }
+            _ => Err(AssertionParseError::UnknownKind(kind.into())),
+        }
+    }
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assertion::Status { expected } => write!(f, "status {}", expected),
+            Assertion::StatusClass { class } => write!(f, "status {}xx", class),
+            Assertion::HeaderEquals { name, value } => {
+                write!(f, "header {} == {}", name, quoted(value))
+            }
+            Assertion::HeaderContains { name, value } => {
+                write!(f, "header {} contains {}", name, quoted(value))
+            }
+            Assertion::BodyContains { value } => write!(f, "body contains {}", quoted(value)),
+            Assertion::JsonPath { path, check } => match check {
+                JsonCheck::Exists => write!(f, "{} exists", path),
+                JsonCheck::Equals { value } => write!(f, "{} == {}", path, quoted(value)),
+                JsonCheck::Length { comparison, value } => {
+                    write!(f, "{} length {} {}", path, comparison, value)
+                }
+            },
+        }
+    }
+}
+
+/// wraps `value` in quotes when parsing it back would lose something, like
+/// leading whitespace or quotes of its own
+fn quoted(value: &str) -> String {
+    let is_quoted = value.len().gt(&1) && value.starts_with('"') && value.ends_with('"');
+    match is_quoted || value.trim().len().ne(&value.len()) {
+        true => format!("\"{value}\""),
+        false => value.to_string(),
+    }
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    match s.trim_start().split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (s.trim(), ""),
+    }
+}
+
+/// values may be wrapped in double quotes to make leading or trailing
+/// whitespace explicit, the quotes themselves are not part of the value
+fn non_empty_value(value: &str) -> Result<String, AssertionParseError> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+
+    match value.is_empty() {
+        true => Err(AssertionParseError::MissingValue),
+        false => Ok(value.into()),
+    }
+}
+
+fn parse_status(status: &str) -> Result<Assertion, AssertionParseError> {
+    let invalid = || AssertionParseError::InvalidStatus(status.into());
+
+    if status.is_empty() {
+        return Err(AssertionParseError::MissingValue);
+    }
+
+    if let Some(class) = status
+        .strip_suffix("xx")
+        .or_else(|| status.strip_suffix("XX"))
+    {
+        return match class.parse::<u16>() {
+            Ok(class @ 1..=5) => Ok(Assertion::StatusClass { class }),
+            _ => Err(invalid()),
+        };
+    }
+
+    match status.parse::<u16>() {
+        Ok(expected @ 100..=599) => Ok(Assertion::Status { expected }),
+        _ => Err(invalid()),
+    }
+}
+
+/// an assertion made from a response, `note` tells why it checks less than
+/// it could, eg: a value that changes on every response only has to exist
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaffoldedAssertion {
+    pub assertion: Assertion,
+    pub note: Option<String>,
+}
+
+/// assertions pinning down `response` as it is: its status, content type
+/// and the value at `path` when given. Every one of them passes against
+/// `response`
+pub fn scaffold(response: &Response, path: Option<&str>) -> Vec<ScaffoldedAssertion> {
+    let mut scaffolded = vec![];
+    let pinned = |assertion| ScaffoldedAssertion {
+        assertion,
+        note: None,
+    };
+
+    if let Some(status) = response.status {
+        scaffolded.push(pinned(Assertion::Status {
+            expected: status.as_u16(),
+        }));
+    }
+
+    if let Some(content_type) = header_value(response, "content-type").filter(|v| !v.is_empty()) {
+        scaffolded.push(pinned(Assertion::HeaderEquals {
+            name: "content-type".into(),
+            value: content_type,
+        }));
+    }
+
+    let value = path.zip(
+        response
+            .body
+            .as_ref()
+            .and_then(|body| serde_json::from_str::<Value>(body).ok()),
+    );
+    if let Some((path, body)) = value {
+        if let Ok(Some(value)) = json_path::query(&body, path) {
+            scaffolded.push(scaffold_json_value(path, value));
+        }
+    }
+
+    scaffolded
+}
+
+/// equality on the value, unless it looks generated or can't be written as
+/// an assertion value
+fn scaffold_json_value(path: &str, value: &Value) -> ScaffoldedAssertion {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    let (check, note) = match builtins::looks_generated(&text) {
+        Some(builtin) => (
+            JsonCheck::Exists,
+            Some(format!(
+                "looks like a {{{{{}{builtin}}}}}, only checked to exist",
+                builtins::PREFIX
+            )),
+        ),
+        None if text.is_empty() => (
+            JsonCheck::Length {
+                comparison: Comparison::Eq,
+                value: 0,
+            },
+            None,
+        ),
+        None => (JsonCheck::Equals { value: text }, None),
+    };
+
+    ScaffoldedAssertion {
+        assertion: Assertion::JsonPath {
+            path: path.into(),
+            check,
+        },
+        note,
+    }
+}
+
+/// checks every assertion against the response, keeping the order they
+/// were defined in
+pub fn evaluate_all(assertions: &[Assertion], response: &Response) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .map(|assertion| evaluate(assertion, response))
+        .collect()
+}
+
+pub fn evaluate(assertion: &Assertion, response: &Response) -> AssertionResult {
+    let (passed, actual) = match assertion {
+        Assertion::Status { expected } => match response.status {
+            Some(status) => (status.as_u16().eq(expected), status.as_u16().to_string()),
+            None => (false, "no status".into()),
+        },
+        Assertion::StatusClass { class } => match response.status {
+            Some(status) => (
+                status.as_u16().div_euclid(100).eq(class),
+                status.as_u16().to_string(),
+            ),
+            None => (false, "no status".into()),
+        },
+        Assertion::HeaderEquals { name, value } => match header_value(response, name) {
+            Some(header) => (header.eq(value), header),
+            None => (false, "header not present".into()),
+        },
+        Assertion::HeaderContains { name, value } => match header_value(response, name) {
+            Some(header) => (header.contains(value.as_str()), header),
+            None => (false, "header not present".into()),
+        },
+        Assertion::BodyContains { value } => match response.body.as_ref() {
+            Some(body) if body.contains(value.as_str()) => (true, "found in body".into()),
+            Some(_) => (false, "not found in body".into()),
+            None => (false, "empty body".into()),
+        },
+        Assertion::JsonPath { path, check } => evaluate_json_path(path, check, response),
+    };
+
+    AssertionResult {
+        assertion: assertion.clone(),
+        passed,
+        actual,
+    }
+}
+
+fn header_value(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.get(name))
+        .map(|value| value.to_str().unwrap_or_default().to_string())
+}
+
+fn evaluate_json_path(path: &str, check: &JsonCheck, response: &Response) -> (bool, String) {
+    let Some(body) = response
+        .body
+        .as_ref()
+        .and_then(|body| serde_json::from_str::<Value>(body).ok())
+    else {
+        return (false, "body is not valid json".into());
+    };
+
+    let value = match json_path::query(&body, path) {
+        Ok(Some(value)) => value,
+        Ok(None) => return (false, "not found".into()),
+        Err(e) => return (false, e.to_string()),
+    };
+
+    match check {
+        JsonCheck::Exists => (true, value.to_string()),
+        JsonCheck::Equals { value: expected } => {
+            let actual = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (actual.eq(expected), value.to_string())
+        }
+        JsonCheck::Length {
+            comparison,
+            value: expected,
+        } => {
+            let len = match value {
+                Value::Array(items) => items.len(),
+                Value::Object(map) => map.len(),
+                Value::String(s) => s.chars().count(),
+                _ => return (false, format!("{} has no length", value)),
+            };
+            (
+                comparison.compare(len, *expected),
+                format!("length {}", len),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    fn make_response(status: u16, body: &str) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "content-type",
+            HeaderValue::from_static("application/json; charset=utf-8"),
+        );
+
+        Response {
+            headers: Some(headers),
+            ..test_utils::make_response(status, body)
+        }
+    }
+
+    fn check(assertion: &str, response: &Response) -> AssertionResult {
+        evaluate(&assertion.parse::<Assertion>().unwrap(), response)
+    }
+
+    #[test]
+    fn test_parse_every_kind() {
+        let cases = [
+            ("status 200", Assertion::Status { expected: 200 }),
+            ("status 4xx", Assertion::StatusClass { class: 4 }),
+            (
+                "header content-type == application/json",
+                Assertion::HeaderEquals {
+                    name: "content-type".into(),
+                    value: "application/json".into(),
+                },
+            ),
+            (
+                "header x-id contains abc def",
+                Assertion::HeaderContains {
+                    name: "x-id".into(),
+                    value: "abc def".into(),
+                },
+            ),
+            (
+                r#"body contains " spaced ""#,
+                Assertion::BodyContains {
+                    value: " spaced ".into(),
+                },
+            ),
+            (
+                "$.data.id exists",
+                Assertion::JsonPath {
+                    path: "$.data.id".into(),
+                    check: JsonCheck::Exists,
+                },
+            ),
+            (
+                "$.items length >= 1",
+                Assertion::JsonPath {
+                    path: "$.items".into(),
+                    check: JsonCheck::Length {
+                        comparison: Comparison::Ge,
+                        value: 1,
+                    },
+                },
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<Assertion>(), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrips() {
+        let inputs = [
+            "status 201",
+            "status 2xx",
+            "header content-type == application/json",
+            "header content-type contains json",
+            "body contains hello world",
+            "$.data.items[0].id exists",
+            "$.data.name == hac",
+            "$.items length < 10",
+            "$.padded == \" hac \"",
+            "header etag == \"\"abc\"\"",
+        ];
+
+        for input in inputs {
+            let assertion = input.parse::<Assertion>().unwrap();
+            assert_eq!(assertion.to_string(), input);
+            assert_eq!(assertion.to_string().parse::<Assertion>(), Ok(assertion));
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Assertion>(), Err(AssertionParseError::Empty));
+        assert_eq!(
+            "cookie a == b".parse::<Assertion>(),
+            Err(AssertionParseError::UnknownKind("cookie".into()))
+        );
+        assert_eq!(
+            "status 99".parse::<Assertion>(),
+            Err(AssertionParseError::InvalidStatus("99".into()))
+        );
+        assert_eq!(
+            "status 9xx".parse::<Assertion>(),
+            Err(AssertionParseError::InvalidStatus("9xx".into()))
+        );
+        assert_eq!(
+            "header content-type".parse::<Assertion>(),
+            Err(AssertionParseError::MissingOperator)
+        );
+        assert_eq!(
+            "header content-type ~= json".parse::<Assertion>(),
+            Err(AssertionParseError::UnknownOperator("~=".into()))
+        );
+        assert_eq!(
+            "body contains".parse::<Assertion>(),
+            Err(AssertionParseError::MissingValue)
+        );
+        assert_eq!(
+            "$.items length >= many".parse::<Assertion>(),
+            Err(AssertionParseError::InvalidLength("many".into()))
+        );
+        assert_eq!(
+            "$.items[x] exists".parse::<Assertion>(),
+            Err(AssertionParseError::JsonPath(JsonPathError::InvalidIndex(
+                "x".into()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_status_assertions() {
+        let response = make_response(404, "");
+        assert!(check("status 404", &response).passed);
+        assert!(check("status 4xx", &response).passed);
+
+        let result = check("status 2xx", &response);
+        assert!(!result.passed);
+        assert_eq!(result.actual, "404");
+    }
+
+    #[test]
+    fn test_header_assertions_are_case_insensitive_on_names() {
+        let response = make_response(200, "");
+        assert!(check("header Content-Type contains json", &response).passed);
+        assert!(
+            check(
+                "header content-type == application/json; charset=utf-8",
+                &response
+            )
+            .passed
+        );
+
+        let result = check("header x-missing == a", &response);
+        assert!(!result.passed);
+        assert_eq!(result.actual, "header not present");
+    }
+
+    #[test]
+    fn test_body_and_json_assertions() {
+        let response = make_response(
+            200,
+            r#"{"data": {"id": 7, "name": "hac", "active": true}, "items": [1, 2]}"#,
+        );
+
+        assert!(check("body contains \"name\"", &response).passed);
+        assert!(check("$.data.id exists", &response).passed);
+        assert!(check("$.data.id == 7", &response).passed);
+        assert!(check("$.data.name == hac", &response).passed);
+        assert!(check("$.data.active == true", &response).passed);
+        assert!(check("$.items length == 2", &response).passed);
+        assert!(check("$.data length > 2", &response).passed);
+
+        let result = check("$.data.missing exists", &response);
+        assert!(!result.passed);
+        assert_eq!(result.actual, "not found");
+
+        let result = check("$.items length >= 3", &response);
+        assert!(!result.passed);
+        assert_eq!(result.actual, "length 2");
+
+        let result = check("$.data.id length == 1", &response);
+        assert!(!result.passed);
+        assert_eq!(result.actual, "7 has no length");
+    }
+
+    #[test]
+    fn test_json_assertions_on_non_json_body() {
+        let response = make_response(200, "<html></html>");
+        let result = check("$.data exists", &response);
+        assert!(!result.passed);
+        assert_eq!(result.actual, "body is not valid json");
+    }
+
+    #[test]
+    fn test_scaffolded_assertions_pass_against_their_response() {
+        let body = serde_json::json!({
+            "data": {
+                "id": "6f9619ff-8b86-4011-b42d-00c04fc964ff",
+                "name": "hac",
+                "tags": ["a", "b"],
+                "created_at": "2024-05-01T13:45:12.345Z",
+                "updated": 1714571112,
+                "count": 3,
+                "nickname": "",
+            },
+        });
+        let response = make_response(201, &body.to_string());
+
+        let scaffolded = scaffold(&response, Some("$.data.name"));
+        assert_eq!(
+            scaffolded
+                .iter()
+                .map(|scaffolded| scaffolded.assertion.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "status 201",
+                "header content-type == application/json; charset=utf-8",
+                "$.data.name == hac",
+            ]
+        );
+        assert!(scaffolded
+            .iter()
+            .all(|scaffolded| scaffolded.note.is_none()));
+
+        let cases = [
+            ("$.data.id", "$.data.id exists", true),
+            ("$.data.created_at", "$.data.created_at exists", true),
+            ("$.data.updated", "$.data.updated exists", true),
+            ("$.data.count", "$.data.count == 3", false),
+            ("$.data.tags", r#"$.data.tags == ["a","b"]"#, false),
+            ("$.data.nickname", "$.data.nickname length == 0", false),
+        ];
+        for (path, expected, is_noted) in cases {
+            let json = scaffold(&response, Some(path)).pop().unwrap();
+            assert_eq!(json.assertion.to_string(), expected);
+            assert_eq!(json.note.is_some(), is_noted, "{path}");
+            assert_eq!(
+                json.assertion.to_string().parse::<Assertion>(),
+                Ok(json.assertion.clone())
+            );
+        }
+        assert_eq!(
+            scaffold(&response, Some("$.data.id")).pop().unwrap().note,
+            Some("looks like a {{$uuid}}, only checked to exist".into())
+        );
+
+        for scaffolded in scaffold(&response, Some("$.data")) {
+            assert!(evaluate(&scaffolded.assertion, &response).passed);
+        }
+        // paths that are not on the body are left out
+        assert_eq!(scaffold(&response, Some("$.missing")).len(), 2);
+        assert_eq!(scaffold(&response, None).len(), 2);
+    }
+}
@@ -0,0 +1,315 @@
+use serde_json::Value;
+
+/// a single step on a json path, either an object key or an array index
+#[derive(Debug, PartialEq, Clone)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsonPathError {
+    /// paths must always start at the root, with `$`
+    MissingRoot,
+    /// a `.` was not followed by a key, like in `$.a..b` or `$.`
+    EmptyKey(usize),
+    /// a `[` was never closed
+    UnclosedBracket(usize),
+    /// what was inside the brackets is not a valid array index
+    InvalidIndex(String),
+    UnexpectedChar(char, usize),
+}
+
+impl std::fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonPathError::MissingRoot => write!(f, "json paths must start with `$`"),
+            JsonPathError::EmptyKey(pos) => write!(f, "expected a key at position {}", pos),
+            JsonPathError::UnclosedBracket(pos) => {
+                write!(f, "unclosed bracket at position {}", pos)
+            }
+            JsonPathError::InvalidIndex(index) => write!(f, "invalid array index `{}`", index),
+            JsonPathError::UnexpectedChar(c, pos) => {
+                write!(f, "unexpected `{}` at position {}", c, pos)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+/// parses a small subset of JSONPath into its segments. We only support dot
+/// separated keys and array indexes, eg: `$.data.items[0].id`, which covers
+/// most of what people check on API responses without pulling a full
+/// implementation. Keys that can't be written after a dot go in quoted
+/// brackets, like `$['user.name']`, with `\` escaping the quote
+pub fn parse(path: &str) -> Result<Vec<Segment>, JsonPathError> {
+    let path = path.trim();
+    let Some(rest) = path.strip_prefix('$') else {
+        return Err(JsonPathError::MissingRoot);
+    };
+
+    let chars = rest.chars().collect::<Vec<_>>();
+    let mut segments = vec![];
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        // positions are reported relative to the full path, which includes `$`
+        let pos = idx + 1;
+        match chars[idx] {
+            '.' => {
+                let key = chars[idx + 1..]
+                    .iter()
+                    .take_while(|c| !matches!(c, '.' | '['))
+                    .collect::<String>();
+
+                if key.is_empty() {
+                    return Err(JsonPathError::EmptyKey(pos + 1));
+                }
+
+                idx += key.chars().count() + 1;
+                segments.push(Segment::Key(key));
+            }
+            '[' if matches!(chars.get(idx + 1), Some('\'' | '"')) => {
+                let quote = chars[idx + 1];
+                let mut key = String::default();
+                let mut end = idx + 2;
+                loop {
+                    match chars.get(end) {
+                        None => return Err(JsonPathError::UnclosedBracket(pos)),
+                        Some('\\') if end + 1 < chars.len() => {
+                            key.push(chars[end + 1]);
+                            end += 2;
+                        }
+                        Some(c) if c.eq(&quote) => break,
+                        Some(c) => {
+                            key.push(*c);
+                            end += 1;
+                        }
+                    }
+                }
+
+                match chars.get(end + 1) {
+                    Some(']') => {}
+                    Some(c) => return Err(JsonPathError::UnexpectedChar(*c, end + 2)),
+                    None => return Err(JsonPathError::UnclosedBracket(pos)),
+                }
+
+                idx = end + 2;
+                segments.push(Segment::Key(key));
+            }
+            '[' => {
+                let Some(len) = chars[idx + 1..].iter().position(|c| c.eq(&']')) else {
+                    return Err(JsonPathError::UnclosedBracket(pos));
+                };
+
+                let index = chars[idx + 1..idx + 1 + len].iter().collect::<String>();
+                let index = index
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| JsonPathError::InvalidIndex(index))?;
+
+                idx += len + 2;
+                segments.push(Segment::Index(index));
+            }
+            c => return Err(JsonPathError::UnexpectedChar(c, pos)),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// writes segments back as a path `parse` understands. Keys are written
+/// after a dot when they can be, and in quoted brackets otherwise
+pub fn format(segments: &[Segment]) -> String {
+    let mut path = String::from("$");
+    for segment in segments {
+        match segment {
+            Segment::Index(index) => path.push_str(&format!("[{index}]")),
+            Segment::Key(key)
+                if !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-')) =>
+            {
+                path.push('.');
+                path.push_str(key);
+            }
+            Segment::Key(key) => {
+                let key = key.replace('\\', "\\\\").replace('\'', "\\'");
+                path.push_str(&format!("['{key}']"));
+            }
+        }
+    }
+    path
+}
+
+/// walks the value following the path, returning `None` when any segment
+/// doesn't exist, like a missing key or an index out of bounds
+pub fn query<'a>(value: &'a Value, path: &str) -> Result<Option<&'a Value>, JsonPathError> {
+    let segments = parse(path)?;
+
+    Ok(segments
+        .iter()
+        .try_fold(value, |value, segment| match (segment, value) {
+            (Segment::Key(key), Value::Object(map)) => map.get(key),
+            (Segment::Index(index), Value::Array(items)) => items.get(*index),
+            _ => None,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!({
+            "data": {
+                "id": 42,
+                "name": "hac",
+                "tags": ["api", "tui"],
+                "owner": null,
+            },
+            "items": [
+                { "id": 1, "nested": [[10, 20], [30]] },
+                { "id": 2, "nested": [] },
+            ],
+            "key2": "with number",
+            "empty": {},
+        })
+    }
+
+    #[test]
+    fn test_parse_root_only() {
+        assert_eq!(parse("$"), Ok(vec![]));
+        assert_eq!(parse("  $  "), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_keys_and_indexes() {
+        assert_eq!(
+            parse("$.data.items[0].id"),
+            Ok(vec![
+                Segment::Key("data".into()),
+                Segment::Key("items".into()),
+                Segment::Index(0),
+                Segment::Key("id".into()),
+            ])
+        );
+        assert_eq!(
+            parse("$[1][ 2 ]"),
+            Ok(vec![Segment::Index(1), Segment::Index(2)])
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_keys() {
+        assert_eq!(
+            parse(r#"$['user.name']["a]b"][0]['it\'s']"#),
+            Ok(vec![
+                Segment::Key("user.name".into()),
+                Segment::Key("a]b".into()),
+                Segment::Index(0),
+                Segment::Key("it's".into()),
+            ])
+        );
+        assert_eq!(parse("$['']"), Ok(vec![Segment::Key("".into())]));
+        assert_eq!(parse("$['a"), Err(JsonPathError::UnclosedBracket(1)));
+        assert_eq!(parse("$['a'"), Err(JsonPathError::UnclosedBracket(1)));
+        assert_eq!(parse("$['a'x]"), Err(JsonPathError::UnexpectedChar('x', 5)));
+    }
+
+    #[test]
+    fn test_formatted_paths_parse_back() {
+        let segments = vec![
+            Segment::Key("data".into()),
+            Segment::Key("items".into()),
+            Segment::Index(3),
+            Segment::Key("first name".into()),
+            Segment::Key(r"it's a \".into()),
+            Segment::Key("".into()),
+            Segment::Key("x-id_2".into()),
+        ];
+        let path = format(&segments);
+        assert_eq!(
+            path,
+            r"$.data.items[3]['first name']['it\'s a \\'][''].x-id_2"
+        );
+        assert_eq!(parse(&path), Ok(segments));
+        assert_eq!(format(&[]), "$");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("data.id"), Err(JsonPathError::MissingRoot));
+        assert_eq!(parse(""), Err(JsonPathError::MissingRoot));
+        assert_eq!(parse("$."), Err(JsonPathError::EmptyKey(2)));
+        assert_eq!(parse("$.a..b"), Err(JsonPathError::EmptyKey(4)));
+        assert_eq!(parse("$.a.[0]"), Err(JsonPathError::EmptyKey(4)));
+        assert_eq!(parse("$.a[0"), Err(JsonPathError::UnclosedBracket(3)));
+        assert_eq!(
+            parse("$.a[x]"),
+            Err(JsonPathError::InvalidIndex("x".into()))
+        );
+        assert_eq!(
+            parse("$.a[-1]"),
+            Err(JsonPathError::InvalidIndex("-1".into()))
+        );
+        assert_eq!(parse("$.a[]"), Err(JsonPathError::InvalidIndex("".into())));
+        assert_eq!(parse("$a"), Err(JsonPathError::UnexpectedChar('a', 1)));
+        assert_eq!(parse("$.a[0]b"), Err(JsonPathError::UnexpectedChar('b', 6)));
+    }
+
+    #[test]
+    fn test_query_nested_keys() {
+        let value = sample();
+        assert_eq!(query(&value, "$.data.id"), Ok(Some(&json!(42))));
+        assert_eq!(query(&value, "$.data.name"), Ok(Some(&json!("hac"))));
+        assert_eq!(query(&value, "$.key2"), Ok(Some(&json!("with number"))));
+        assert_eq!(query(&value, "$.empty"), Ok(Some(&json!({}))));
+    }
+
+    #[test]
+    fn test_query_root_returns_the_whole_value() {
+        let value = sample();
+        assert_eq!(query(&value, "$"), Ok(Some(&value)));
+    }
+
+    #[test]
+    fn test_query_array_indexes() {
+        let value = sample();
+        assert_eq!(query(&value, "$.data.tags[1]"), Ok(Some(&json!("tui"))));
+        assert_eq!(query(&value, "$.items[1].id"), Ok(Some(&json!(2))));
+        assert_eq!(
+            query(&value, "$.items[0].nested[0][1]"),
+            Ok(Some(&json!(20)))
+        );
+        assert_eq!(query(&json!([1, 2]), "$[0]"), Ok(Some(&json!(1))));
+    }
+
+    #[test]
+    fn test_query_null_is_found() {
+        let value = sample();
+        assert_eq!(query(&value, "$.data.owner"), Ok(Some(&Value::Null)));
+    }
+
+    #[test]
+    fn test_query_missing_values() {
+        let value = sample();
+        assert_eq!(query(&value, "$.data.missing"), Ok(None));
+        assert_eq!(query(&value, "$.items[5]"), Ok(None));
+        assert_eq!(query(&value, "$.items[1].nested[0]"), Ok(None));
+        // indexing into objects and keying into arrays never matches
+        assert_eq!(query(&value, "$.data[0]"), Ok(None));
+        assert_eq!(query(&value, "$.items.id"), Ok(None));
+        // nor does going deeper than a scalar
+        assert_eq!(query(&value, "$.data.id.value"), Ok(None));
+    }
+
+    #[test]
+    fn test_query_invalid_path() {
+        let value = sample();
+        assert_eq!(query(&value, "data"), Err(JsonPathError::MissingRoot));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::net::certificate;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+/// built-in variables start with this, eg: `{{$uuid}}`
+pub const PREFIX: char = '$';
+/// `$randomString` would happily allocate whatever it is asked for
+const MAX_RANDOM_STRING: usize = 4096;
+
+/// a variable that is not defined anywhere and is generated again every time
+/// it is used, eg: `{{$uuid}}`. Arguments follow the name separated by
+/// whitespace, eg: `{{$randomInt 1 10}}`
+#[derive(Debug)]
+pub struct Builtin {
+    pub name: &'static str,
+    /// how the builtin is written, as shown on the help
+    pub usage: &'static str,
+    pub description: &'static str,
+    generate: fn(&[&str]) -> Result<String, String>,
+}
+
+/// every builtin we know of, new ones only have to be added here
+pub const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "uuid",
+        usage: "{{$uuid}}",
+        description: "a random v4 uuid",
+        generate: uuid,
+    },
+    Builtin {
+        name: "timestamp",
+        usage: "{{$timestamp}}",
+        description: "seconds since the unix epoch",
+        generate: timestamp,
+    },
+    Builtin {
+        name: "isoTimestamp",
+        usage: "{{$isoTimestamp}}",
+        description: "the current time in ISO 8601, in UTC",
+        generate: iso_timestamp,
+    },
+    Builtin {
+        name: "randomInt",
+        usage: "{{$randomInt min max}}",
+        description: "a random integer between min and max, inclusive",
+        generate: random_int,
+    },
+    Builtin {
+        name: "randomString",
+        usage: "{{$randomString n}}",
+        description: "n random alphanumeric characters",
+        generate: random_string,
+    },
+];
+
+#[derive(Debug, PartialEq)]
+pub enum BuiltinError {
+    Unknown(String),
+    InvalidArguments { name: String, reason: String },
+}
+
+impl std::fmt::Display for BuiltinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuiltinError::Unknown(name) => {
+                let names = BUILTINS
+                    .iter()
+                    .map(|builtin| format!("{PREFIX}{}", builtin.name))
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "unknown built-in variable {PREFIX}{name}, available ones are: {}",
+                    names.join(", ")
+                )
+            }
+            BuiltinError::InvalidArguments { name, reason } => {
+                write!(f, "invalid arguments for {PREFIX}{name}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuiltinError {}
+
+/// whether the placeholder `expression` refers to a builtin
+pub fn is_builtin(expression: &str) -> bool {
+    expression.starts_with(PREFIX)
+}
+
+/// generates a fresh value for `expression`, which is what goes inside the
+/// braces, eg: `$randomInt 1 10`
+pub fn resolve(expression: &str) -> Result<String, BuiltinError> {
+    let mut parts = expression
+        .trim()
+        .trim_start_matches(PREFIX)
+        .split_whitespace();
+    let name = parts.next().unwrap_or_default();
+    let args = parts.collect::<Vec<_>>();
+
+    let builtin = BUILTINS
+        .iter()
+        .find(|builtin| builtin.name.eq(name))
+        .ok_or_else(|| BuiltinError::Unknown(name.into()))?;
+
+    (builtin.generate)(&args).map_err(|reason| BuiltinError::InvalidArguments {
+        name: name.into(),
+        reason,
+    })
+}
+
+/// the builtin `value` looks like it was made by, for values that change on
+/// every response like ids and timestamps. Only the shape is looked at, so
+/// `$uuid` matches any uuid, not only v4 ones
+pub fn looks_generated(value: &str) -> Option<&'static str> {
+    let bytes = value.as_bytes();
+    let is_digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+
+    let is_uuid = bytes.len().eq(&36)
+        && bytes.iter().enumerate().all(|(idx, b)| match idx {
+            8 | 13 | 18 | 23 => b.eq(&b'-'),
+            _ => b.is_ascii_hexdigit(),
+        });
+    // seconds or milliseconds since the epoch, from 2001 up to 2286
+    let is_timestamp = matches!(bytes.len(), 10 | 13) && is_digits(0..bytes.len());
+    // `2024-05-01T13:45:12`, followed by anything like fractions or offsets
+    let is_iso_timestamp = bytes.len().ge(&19)
+        && [0..4, 5..7, 8..10, 11..13, 14..16, 17..19]
+            .into_iter()
+            .all(is_digits)
+        && [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')]
+            .iter()
+            .all(|(idx, sep)| bytes[*idx].eq(sep));
+
+    match (is_uuid, is_timestamp, is_iso_timestamp) {
+        (true, _, _) => Some("uuid"),
+        (_, true, _) => Some("timestamp"),
+        (_, _, true) => Some("isoTimestamp"),
+        _ => None,
+    }
+}
+
+fn expect_args(args: &[&str], expected: usize) -> Result<(), String> {
+    match args.len().eq(&expected) {
+        true => Ok(()),
+        false => Err(format!("expected {expected} arguments, got {}", args.len())),
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse::<T>()
+        .map_err(|_| format!("{arg:?} is not a valid number"))
+}
+
+fn uuid(args: &[&str]) -> Result<String, String> {
+    expect_args(args, 0)?;
+    Ok(uuid::Uuid::new_v4().to_string())
+}
+
+fn now() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+fn timestamp(args: &[&str]) -> Result<String, String> {
+    expect_args(args, 0)?;
+    Ok(now().as_secs().to_string())
+}
+
+fn iso_timestamp(args: &[&str]) -> Result<String, String> {
+    expect_args(args, 0)?;
+    Ok(format_iso8601(now().as_millis() as i64))
+}
+
+fn random_int(args: &[&str]) -> Result<String, String> {
+    expect_args(args, 2)?;
+    let min = parse_arg::<i64>(args[0])?;
+    let max = parse_arg::<i64>(args[1])?;
+    if min.gt(&max) {
+        return Err(format!("min {min} is bigger than max {max}"));
+    }
+    Ok(rand::thread_rng().gen_range(min..=max).to_string())
+}
+
+fn random_string(args: &[&str]) -> Result<String, String> {
+    expect_args(args, 1)?;
+    let len = parse_arg::<usize>(args[0])?;
+    if len.gt(&MAX_RANDOM_STRING) {
+        return Err(format!("at most {MAX_RANDOM_STRING} characters, got {len}"));
+    }
+    Ok(rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect())
+}
+
+/// formats milliseconds since the unix epoch as `2024-05-01T13:45:12.345Z`
+fn format_iso8601(millis: i64) -> String {
+    let seconds = millis.div_euclid(1000);
+    let (year, month, day) = certificate::civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        millis.rem_euclid(1000)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_generates_fresh_values() {
+        let first = resolve("$uuid").unwrap();
+        assert_eq!(first.len(), 36);
+        assert_eq!(first.chars().nth(14), Some('4'));
+        assert_ne!(first, resolve("$uuid").unwrap());
+
+        for _ in 0..20 {
+            let value = resolve("$randomInt 3 5").unwrap().parse::<i64>().unwrap();
+            assert!((3..=5).contains(&value));
+        }
+        assert_eq!(resolve("$randomInt -2 -2"), Ok("-2".into()));
+
+        let value = resolve(" $randomString  12 ").unwrap();
+        assert_eq!(value.len(), 12);
+        assert!(value.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        assert!(resolve("$timestamp").unwrap().parse::<u64>().is_ok());
+        assert_eq!(resolve("$isoTimestamp").unwrap().len(), 24);
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown_names_and_bad_arguments() {
+        assert_eq!(resolve("$nope"), Err(BuiltinError::Unknown("nope".into())));
+        assert_eq!(
+            resolve("$uuid 4"),
+            Err(BuiltinError::InvalidArguments {
+                name: "uuid".into(),
+                reason: "expected 0 arguments, got 1".into()
+            })
+        );
+        assert!(resolve("$randomInt 10 1").is_err());
+        assert!(resolve("$randomInt one 2").is_err());
+        assert!(resolve("$randomString 100000").is_err());
+    }
+
+    #[test]
+    fn test_generated_looking_values() {
+        assert_eq!(looks_generated(&resolve("$uuid").unwrap()), Some("uuid"));
+        assert_eq!(
+            looks_generated("6F9619FF-8B86-D011-B42D-00C04FC964FF"),
+            Some("uuid")
+        );
+        assert_eq!(
+            looks_generated(&resolve("$timestamp").unwrap()),
+            Some("timestamp")
+        );
+        assert_eq!(looks_generated("1714571112345"), Some("timestamp"));
+        assert_eq!(
+            looks_generated(&resolve("$isoTimestamp").unwrap()),
+            Some("isoTimestamp")
+        );
+        assert_eq!(
+            looks_generated("2024-05-01T13:45:12+02:00"),
+            Some("isoTimestamp")
+        );
+
+        for value in [
+            "",
+            "42",
+            "hac",
+            "2024-05-01",
+            "6f9619ff-8b86-d011-b42d",
+            "12345678901",
+        ] {
+            assert_eq!(looks_generated(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_format_iso8601() {
+        assert_eq!(format_iso8601(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_iso8601(951_782_400_123), "2000-02-29T00:00:00.123Z");
+        assert_eq!(
+            format_iso8601(1_714_571_112_345),
+            "2024-05-01T13:45:12.345Z"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use crate::assertions::json_path;
+use crate::collection::types::{Capture, CaptureSource};
+use crate::net::request_manager::Response;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+/// outcome of a capture, `value` is `None` when the path or header wasn't
+/// found on the response. Unresolved captures are only a warning, they never
+/// fail a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureResult {
+    pub capture: Capture,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CaptureParseError {
+    /// captures are written as `name = source`
+    MissingName,
+    MissingSource,
+    /// the source is neither a json path or a `header <name>`
+    InvalidSource(String),
+    JsonPath(json_path::JsonPathError),
+}
+
+impl std::fmt::Display for CaptureParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureParseError::MissingName => {
+                write!(f, "expected a variable name, like `token = $.token`")
+            }
+            CaptureParseError::MissingSource => {
+                write!(f, "expected a json path or `header <name>` after `=`")
+            }
+            CaptureParseError::InvalidSource(source) => write!(
+                f,
+                "invalid source `{}`, expected a json path or `header <name>`",
+                source
+            ),
+            CaptureParseError::JsonPath(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CaptureParseError {}
+
+/// parses the textual form of a capture, `name = $.path` or
+/// `name = header <header name>`. Whether the capture is persisted is not
+/// part of the textual form, new captures are never persisted
+impl FromStr for Capture {
+    type Err = CaptureParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, source) = s.split_once('=').ok_or(CaptureParseError::MissingSource)?;
+        let (name, source) = (name.trim(), source.trim());
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(CaptureParseError::MissingName);
+        }
+
+        let source = match source {
+            "" => return Err(CaptureParseError::MissingSource),
+            path if path.starts_with('$') => {
+                json_path::parse(path).map_err(CaptureParseError::JsonPath)?;
+                CaptureSource::JsonPath { path: path.into() }
+            }
+            _ => match source.split_once(char::is_whitespace) {
+                Some(("header", header)) if !header.trim().is_empty() => CaptureSource::Header {
+                    name: header.trim().into(),
+                },
+                _ => return Err(CaptureParseError::InvalidSource(source.into())),
+            },
+        };
+
+        Ok(Capture {
+            name: name.into(),
+            source,
+            persist: false,
+        })
+    }
+}
+
+impl std::fmt::Display for Capture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            CaptureSource::JsonPath { path } => write!(f, "{} = {}", self.name, path),
+            CaptureSource::Header { name } => write!(f, "{} = header {}", self.name, name),
+        }
+    }
+}
+
+/// extracts every capture from the response, keeping the order they were
+/// defined in
+pub fn extract_all(captures: &[Capture], response: &Response) -> Vec<CaptureResult> {
+    // parse the body only once, instead of once per capture
+    let body = response
+        .body
+        .as_ref()
+        .and_then(|body| serde_json::from_str::<Value>(body).ok());
+
+    captures
+        .iter()
+        .map(|capture| {
+            let value = match &capture.source {
+                CaptureSource::JsonPath { path } => body
+                    .as_ref()
+                    .and_then(|body| json_path::query(body, path).ok().flatten())
+                    .map(|value| match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    }),
+                CaptureSource::Header { name } => response
+                    .headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(name))
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string()),
+            };
+
+            CaptureResult {
+                capture: capture.clone(),
+                value,
+            }
+        })
+        .collect()
+}
+
+/// writes every resolved capture into the variables, unresolved captures
+/// keep whatever value the variable had before
+pub fn apply(results: &[CaptureResult], variables: &mut BTreeMap<String, String>) {
+    for result in results {
+        if let Some(value) = result.value.as_ref() {
+            variables.insert(result.capture.name.clone(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    fn make_response(body: &str) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-session-id", HeaderValue::from_static("abc123"));
+
+        Response {
+            headers: Some(headers),
+            ..test_utils::make_response(200, body)
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        for input in ["token = $.data.token", "session = header x-session-id"] {
+            let capture = input.parse::<Capture>().unwrap();
+            assert_eq!(capture.to_string(), input);
+        }
+
+        assert_eq!(
+            "id=$.items[0].id".parse::<Capture>(),
+            Ok(Capture {
+                name: "id".into(),
+                source: CaptureSource::JsonPath {
+                    path: "$.items[0].id".into()
+                },
+                persist: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "token".parse::<Capture>(),
+            Err(CaptureParseError::MissingSource)
+        );
+        assert_eq!(
+            " = $.token".parse::<Capture>(),
+            Err(CaptureParseError::MissingName)
+        );
+        assert_eq!(
+            "my token = $.token".parse::<Capture>(),
+            Err(CaptureParseError::MissingName)
+        );
+        assert_eq!(
+            "token = ".parse::<Capture>(),
+            Err(CaptureParseError::MissingSource)
+        );
+        assert_eq!(
+            "token = body".parse::<Capture>(),
+            Err(CaptureParseError::InvalidSource("body".into()))
+        );
+        assert!(matches!(
+            "token = $.a[x]".parse::<Capture>(),
+            Err(CaptureParseError::JsonPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_from_body_and_headers() {
+        let response = make_response(r#"{"data": {"token": "secret", "id": 42}}"#);
+        let captures = [
+            "token = $.data.token".parse::<Capture>().unwrap(),
+            "id = $.data.id".parse::<Capture>().unwrap(),
+            "session = header X-Session-Id".parse::<Capture>().unwrap(),
+            "missing = $.data.missing".parse::<Capture>().unwrap(),
+        ];
+
+        let values = extract_all(&captures, &response)
+            .into_iter()
+            .map(|result| result.value)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            values,
+            vec![
+                Some("secret".into()),
+                Some("42".into()),
+                Some("abc123".into()),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_skips_unresolved_captures() {
+        let response = make_response("not json");
+        let captures = [
+            "token = $.token".parse::<Capture>().unwrap(),
+            "session = header x-session-id".parse::<Capture>().unwrap(),
+        ];
+        let results = extract_all(&captures, &response);
+
+        let mut variables = BTreeMap::from([("token".to_string(), "previous".to_string())]);
+        apply(&results, &mut variables);
+
+        assert_eq!(variables.get("token"), Some(&"previous".to_string()));
+        assert_eq!(variables.get("session"), Some(&"abc123".to_string()));
+    }
+}
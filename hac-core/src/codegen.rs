@@ -0,0 +1,355 @@
+use crate::collection::types::{BodyType, Request, RequestMethod};
+use crate::net;
+
+/// languages we can generate snippets for. Each target maps to a single
+/// generator function on this module, so adding a new one is a matter of
+/// adding a variant and its function
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    JavaScript,
+    Python,
+    Go,
+}
+
+impl Target {
+    pub fn iter() -> std::slice::Iter<'static, Target> {
+        [Target::JavaScript, Target::Python, Target::Go].iter()
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::JavaScript => f.write_str("JavaScript (fetch)"),
+            Target::Python => f.write_str("Python (requests)"),
+            Target::Go => f.write_str("Go (net/http)"),
+        }
+    }
+}
+
+/// generates a snippet for the given target. The request is expected to be
+/// already resolved, any `{{variable}}` left on it ends up on the snippet
+pub fn generate(target: Target, request: &Request) -> String {
+    match target {
+        Target::JavaScript => javascript_fetch(request),
+        Target::Python => python_requests(request),
+        Target::Go => go_net_http(request),
+    }
+}
+
+/// renders the request as a javascript `fetch` call
+pub fn javascript_fetch(request: &Request) -> String {
+    let mut snippet = format!("const response = await fetch({}, {{\n", quote(&request.uri));
+    snippet.push_str(&format!(
+        "  method: {},\n",
+        quote(&request.method.to_string())
+    ));
+
+    let headers = headers(request);
+    if !headers.is_empty() {
+        snippet.push_str("  headers: {\n");
+        for (name, value) in headers {
+            snippet.push_str(&format!("    {}: {},\n", quote(&name), quote(&value)));
+        }
+        snippet.push_str("  },\n");
+    }
+
+    if let Some(body) = body(request) {
+        snippet.push_str(&format!("  body: {},\n", quote(&body)));
+    }
+
+    snippet.push_str("});\n\nconsole.log(response.status);\nconsole.log(await response.text());\n");
+    snippet
+}
+
+/// renders the request as a python script using the `requests` package
+pub fn python_requests(request: &Request) -> String {
+    let mut snippet = String::from("import requests\n\n");
+    match &request.method {
+        // `requests` only has helpers for the standard methods
+        RequestMethod::Other(method) => {
+            snippet.push_str("response = requests.request(\n");
+            snippet.push_str(&format!("    {},\n", quote(method)));
+        }
+        method => snippet.push_str(&format!(
+            "response = requests.{}(\n",
+            method.to_string().to_lowercase()
+        )),
+    }
+    snippet.push_str(&format!("    {},\n", quote(&request.uri)));
+
+    let headers = headers(request);
+    if !headers.is_empty() {
+        snippet.push_str("    headers={\n");
+        for (name, value) in headers {
+            snippet.push_str(&format!("        {}: {},\n", quote(&name), quote(&value)));
+        }
+        snippet.push_str("    },\n");
+    }
+
+    if let Some(body) = body(request) {
+        snippet.push_str(&format!("    data={},\n", quote(&body)));
+    }
+
+    snippet.push_str(")\n\nprint(response.status_code)\nprint(response.text)\n");
+    snippet
+}
+
+/// renders the request as a go program using `net/http`
+pub fn go_net_http(request: &Request) -> String {
+    let body = body(request);
+
+    let mut snippet =
+        String::from("package main\n\nimport (\n\t\"fmt\"\n\t\"io\"\n\t\"net/http\"\n");
+    if body.is_some() {
+        snippet.push_str("\t\"strings\"\n");
+    }
+    snippet.push_str(")\n\nfunc main() {\n");
+
+    let reader = match body {
+        Some(body) => {
+            snippet.push_str(&format!("\tbody := strings.NewReader({})\n", quote(&body)));
+            "body"
+        }
+        None => "nil",
+    };
+    snippet.push_str(&format!(
+        "\treq, err := http.NewRequest({}, {}, {})\n",
+        quote(&request.method.to_string()),
+        quote(&request.uri),
+        reader
+    ));
+    snippet.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+
+    for (name, value) in headers(request) {
+        snippet.push_str(&format!(
+            "\treq.Header.Add({}, {})\n",
+            quote(&name),
+            quote(&value)
+        ));
+    }
+
+    snippet.push_str(concat!(
+        "\n\tres, err := http.DefaultClient.Do(req)\n",
+        "\tif err != nil {\n\t\tpanic(err)\n\t}\n",
+        "\tdefer res.Body.Close()\n\n",
+        "\tdata, err := io.ReadAll(res.Body)\n",
+        "\tif err != nil {\n\t\tpanic(err)\n\t}\n\n",
+        "\tfmt.Println(res.Status)\n",
+        "\tfmt.Println(string(data))\n",
+        "}\n",
+    ));
+    snippet
+}
+
+/// every enabled header in the order they were defined, followed by the
+/// computed headers that weren't overridden, just like the client sends them.
+///
+/// auth methods don't hold credentials yet, whatever authorization the
+/// request uses is already on its headers, so it is rendered just like them
+fn headers(request: &Request) -> Vec<(String, String)> {
+    request
+        .headers
+        .iter()
+        .flatten()
+        .filter(|header| header.enabled)
+        .map(|header| header.pair.clone())
+        .chain(
+            request
+                .computed_headers()
+                .into_iter()
+                .filter(|(name, _)| !request.has_header(name)),
+        )
+        .collect()
+}
+
+/// `GET` requests are never sent with a body, so we don't render it either.
+/// The body is encoded for its type like the client does, so it matches the
+/// computed content type. When it can't be, it is rendered as written
+fn body(request: &Request) -> Option<String> {
+    let body = request
+        .body
+        .as_deref()
+        .filter(|body| !body.is_empty() && request.method.ne(&RequestMethod::Get))?;
+    let body_type = request.body_type.as_ref().unwrap_or(&BodyType::Json);
+    Some(net::body::encode(body, body_type).unwrap_or_else(|_| body.into()))
+}
+
+/// quotes a string as a double quoted literal. JSON escapes are also valid
+/// escapes on every language we target, so we let serde handle quotes,
+/// backslashes and control characters for us
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).expect("serializing a string never fails")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::HeaderMap;
+    use crate::test_utils;
+
+    fn make_request(method: RequestMethod, body: Option<&str>) -> Request {
+        Request {
+            method,
+            headers: Some(vec![
+                HeaderMap {
+                    pair: ("Authorization".into(), "Bearer abc".into()),
+                    enabled: true,
+                },
+                HeaderMap {
+                    pair: ("X-Disabled".into(), "nope".into()),
+                    enabled: false,
+                },
+            ]),
+            body: body.map(String::from),
+            ..test_utils::make_request(
+                "create user",
+                "https://api.example.com/users?page=2&sort=name",
+            )
+        }
+    }
+
+    const BODY: &str = "{\n  \"name\": \"Jane \\\"JD\\\" Doe\",\n  \"bio\": \"C:\\\\temp\"\n}";
+
+    #[test]
+    fn test_javascript_fetch() {
+        let request = make_request(RequestMethod::Post, Some(BODY));
+
+        assert_eq!(
+            javascript_fetch(&request),
+            r#"const response = await fetch("https://api.example.com/users?page=2&sort=name", {
+  method: "POST",
+  headers: {
+    "Authorization": "Bearer abc",
+    "Content-Type": "application/json",
+  },
+  body: "{\n  \"name\": \"Jane \\\"JD\\\" Doe\",\n  \"bio\": \"C:\\\\temp\"\n}",
+});
+
+console.log(response.status);
+console.log(await response.text());
+"#
+        );
+    }
+
+    #[test]
+    fn test_python_requests() {
+        let request = make_request(RequestMethod::Post, Some(BODY));
+
+        assert_eq!(
+            python_requests(&request),
+            r#"import requests
+
+response = requests.post(
+    "https://api.example.com/users?page=2&sort=name",
+    headers={
+        "Authorization": "Bearer abc",
+        "Content-Type": "application/json",
+    },
+    data="{\n  \"name\": \"Jane \\\"JD\\\" Doe\",\n  \"bio\": \"C:\\\\temp\"\n}",
+)
+
+print(response.status_code)
+print(response.text)
+"#
+        );
+    }
+
+    #[test]
+    fn test_custom_methods_are_kept_verbatim() {
+        let request = make_request(RequestMethod::Other("PURGE".into()), None);
+
+        assert!(javascript_fetch(&request).contains("  method: \"PURGE\",\n"));
+        assert!(python_requests(&request).contains("requests.request(\n    \"PURGE\",\n"));
+        assert!(go_net_http(&request).contains("http.NewRequest(\"PURGE\""));
+    }
+
+    #[test]
+    fn test_go_net_http() {
+        let request = make_request(RequestMethod::Post, Some(BODY));
+
+        assert_eq!(
+            go_net_http(&request),
+            "package main
+
+import (
+\t\"fmt\"
+\t\"io\"
+\t\"net/http\"
+\t\"strings\"
+)
+
+func main() {
+\tbody := strings.NewReader(\"{\\n  \\\"name\\\": \\\"Jane \\\\\\\"JD\\\\\\\" Doe\\\",\\n  \\\"bio\\\": \\\"C:\\\\\\\\temp\\\"\\n}\")
+\treq, err := http.NewRequest(\"POST\", \"https://api.example.com/users?page=2&sort=name\", body)
+\tif err != nil {
+\t\tpanic(err)
+\t}
+\treq.Header.Add(\"Authorization\", \"Bearer abc\")
+\treq.Header.Add(\"Content-Type\", \"application/json\")
+
+\tres, err := http.DefaultClient.Do(req)
+\tif err != nil {
+\t\tpanic(err)
+\t}
+\tdefer res.Body.Close()
+
+\tdata, err := io.ReadAll(res.Body)
+\tif err != nil {
+\t\tpanic(err)
+\t}
+
+\tfmt.Println(res.Status)
+\tfmt.Println(string(data))
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_get_requests_have_no_body() {
+        let request = make_request(RequestMethod::Get, Some(BODY));
+
+        for target in Target::iter() {
+            let snippet = generate(*target, &request);
+            assert!(!snippet.contains("Jane"), "{target} rendered a body");
+            assert!(
+                !snippet.contains("Content-Type"),
+                "{target} added a content type"
+            );
+            assert!(snippet.contains("page=2&sort=name"));
+        }
+
+        assert!(go_net_http(&request)
+            .contains("\"https://api.example.com/users?page=2&sort=name\", nil)"));
+        assert!(!go_net_http(&request).contains("strings"));
+    }
+
+    #[test]
+    fn test_bodies_are_encoded_for_their_type() {
+        let mut request = make_request(RequestMethod::Post, Some("name=Jane Doe\nrole=admin"));
+        request.body_type = Some(BodyType::UrlEncoded);
+
+        for target in Target::iter() {
+            let snippet = generate(*target, &request);
+            assert!(snippet.contains("\"name=Jane+Doe&role=admin\""), "{target}");
+            assert!(
+                snippet.contains("application/x-www-form-urlencoded"),
+                "{target}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_existing_content_type_is_kept() {
+        let mut request = make_request(RequestMethod::Put, Some("a=1"));
+        request.headers = Some(vec![HeaderMap {
+            pair: ("content-type".into(), "text/plain".into()),
+            enabled: true,
+        }]);
+
+        let snippet = javascript_fetch(&request);
+        assert!(snippet.contains("\"content-type\": \"text/plain\""));
+        assert!(!snippet.contains("application/json"));
+    }
+}
@@ -1,5 +1,6 @@
 #[allow(clippy::module_inception)]
 pub mod collection;
 pub mod types;
+pub use errors::{format_names, CollectionError};
 pub use types::Collection;
 mod errors;
@@ -1,6 +1,6 @@
 use crate::collection::types::{Collection, Info};
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{self, UNIX_EPOCH};
 
 #[tracing::instrument(err)]
@@ -21,10 +21,7 @@ where
     for item in items.into_iter().flatten() {
         let file_name = item.file_name();
         let collection_name = collections_dir.as_ref().join(file_name);
-        let file = std::fs::read_to_string(&collection_name)?;
-        let mut collection: Collection = serde_json::from_str(&file)?;
-        collection.path = collection_name;
-        collections.push(collection);
+        collections.push(Collection::load(collection_name)?);
     }
 
     collections.sort_by(|a, b| a.info.name.cmp(&b.info.name));
@@ -43,23 +40,172 @@ pub fn create_from_form(name: String, description: String) -> Collection {
         name
     };
 
-    let collections_dir = hac_config::get_collections_dir();
-    let name_as_file_name = name.to_lowercase().replace(' ', "_");
-    let collection_name = collections_dir.join(name_as_file_name);
+    let path = path_for(&name);
 
     Collection {
         info: Info {
             name,
             description: Some(description),
+            ..Default::default()
         },
-        requests: None,
-        path: format!("{}.json", collection_name.to_string_lossy()).into(),
+        path,
+        ..Default::default()
     }
 }
 
+/// where the collection named `name` is stored on the collections directory
+pub fn path_for(name: &str) -> PathBuf {
+    let collections_dir = hac_config::get_collections_dir();
+    let name_as_file_name = name.to_lowercase().replace(' ', "_");
+    let collection_name = collections_dir.join(name_as_file_name);
+    format!("{}.json", collection_name.to_string_lossy()).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::collection::types::{
+        BodyType, Directory, HeaderMap, Request, RequestKind, RequestMethod,
+    };
+    use crate::test_utils;
+    use std::sync::{Arc, RwLock};
+
+    fn make_request(id: &str, parent: Option<&str>) -> RequestKind {
+        RequestKind::Single(Arc::new(RwLock::new(Request {
+            parent: parent.map(String::from),
+            ..test_utils::make_request(id, "")
+        })))
+    }
+
+    #[test]
+    fn test_requests_keep_their_authored_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut collection = create_from_form("ordered".into(), String::default());
+        collection.requests = Some(Arc::new(RwLock::new(vec![
+            make_request("zeta", None),
+            RequestKind::Nested(Directory {
+                id: "mid".into(),
+                name: "mid".into(),
+                requests: Arc::new(RwLock::new(vec![
+                    make_request("b", Some("mid")),
+                    make_request("a", Some("mid")),
+                ])),
+            }),
+            make_request("alpha", None),
+        ])));
+        std::fs::write(
+            dir.path().join("ordered.json"),
+            serde_json::to_string(&collection).unwrap(),
+        )
+        .unwrap();
+
+        let collections = get_collections(dir.path()).unwrap();
+        let requests = collections[0].requests.as_ref().unwrap().read().unwrap();
+        let ids = requests.iter().map(RequestKind::get_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["zeta", "mid", "alpha"]);
+
+        let RequestKind::Nested(mid) = &requests[1] else {
+            panic!("expected a directory");
+        };
+        let children = mid.requests.read().unwrap();
+        let ids = children.iter().map(RequestKind::get_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_custom_methods_are_stored_as_strings() {
+        let request = make_request("purge", None);
+        let RequestKind::Single(request) = request else {
+            unreachable!();
+        };
+        request.write().unwrap().method = RequestMethod::Other("PROPFIND".into());
+
+        let json = serde_json::to_value(&*request.read().unwrap()).unwrap();
+        assert_eq!(json["method"], "PROPFIND");
+
+        let loaded: Request = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(loaded.method, RequestMethod::Other("PROPFIND".into()));
+
+        let mut json = json;
+        json["method"] = "GET".into();
+        let loaded: Request = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(loaded.method, RequestMethod::Get);
+
+        json["method"] = "NOT A TOKEN".into();
+        assert!(serde_json::from_value::<Request>(json).is_err());
+    }
+
+    #[test]
+    fn test_host_overrides_are_optional() {
+        let json = r#"{
+            "info": { "name": "lb", "description": null },
+            "requests": null,
+            "environments": null,
+            "proxy": null,
+            "confirm_destructive": null,
+            "resolve": [{ "host": "api.example.com", "port": 443, "address": "10.0.0.7" }]
+        }"#;
+        let collection: Collection = serde_json::from_str(json).unwrap();
+        let resolve = collection.resolve.unwrap();
+        assert_eq!(
+            resolve[0].address,
+            "10.0.0.7".parse::<std::net::IpAddr>().unwrap()
+        );
+        assert!(resolve[0].matches("api.example.com", 443));
+
+        // collections written before overrides existed still load
+        let json = r#"{ "info": { "name": "old", "description": null } }"#;
+        let collection: Collection = serde_json::from_str(json).unwrap();
+        assert_eq!(collection.resolve, None);
+    }
+
+    #[test]
+    fn test_computed_headers_follow_the_body() {
+        let RequestKind::Single(request) = make_request("create", None) else {
+            unreachable!();
+        };
+        let mut request = request.read().unwrap().clone();
+        request.method = RequestMethod::Post;
+        assert!(request.computed_headers().is_empty());
+
+        request.body = Some(r#"{"name":"hac"}"#.into());
+        assert_eq!(
+            request.computed_headers(),
+            vec![("Content-Type".into(), "application/json".into())]
+        );
+        assert!(!request.has_header("content-type"));
+
+        request.headers = Some(vec![HeaderMap {
+            pair: ("content-type".into(), "application/vnd.api+json".into()),
+            enabled: false,
+        }]);
+        assert!(!request.has_header("Content-Type"));
+        request.headers.as_mut().unwrap()[0].enabled = true;
+        assert!(request.has_header("Content-Type"));
+
+        // switching the body type changes the content type along with it
+        request.body_type = Some(BodyType::UrlEncoded);
+        assert_eq!(
+            request.computed_headers(),
+            vec![(
+                "Content-Type".into(),
+                "application/x-www-form-urlencoded".into()
+            )]
+        );
+        request.body_type = Some(BodyType::Multipart);
+        let (_, content_type) = request.computed_headers().remove(0);
+        assert_eq!(
+            content_type,
+            format!(
+                "multipart/form-data; boundary={}",
+                crate::net::body::boundary(r#"{"name":"hac"}"#)
+            )
+        );
+
+        request.method = RequestMethod::Get;
+        assert!(request.computed_headers().is_empty());
+    }
+
     #[test]
     fn test_creating_from_form() {
         let collection = create_from_form("any valid name".into(), "any desctiption".into());
@@ -1,22 +1,89 @@
+/// something asked of a collection by name that it doesn't have, the names
+/// it does have are kept so they can be suggested
+#[derive(Debug, PartialEq)]
 pub enum CollectionError {
-    Unknown(String),
+    RequestNotFound {
+        collection: String,
+        request: String,
+        available: Vec<String>,
+    },
+    EnvironmentNotFound {
+        collection: String,
+        environment: String,
+        available: Vec<String>,
+    },
+    FlowNotFound {
+        collection: String,
+        flow: String,
+        available: Vec<String>,
+    },
+    /// the flow has steps whose request was deleted, numbered from 1
+    BrokenFlow { flow: String, steps: Vec<usize> },
 }
 
 impl std::fmt::Display for CollectionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            CollectionError::Unknown(msg) => write!(f, "{}", msg),
+            CollectionError::RequestNotFound {
+                collection,
+                request,
+                available,
+            } => write!(
+                f,
+                "request {request:?} not found on collection {collection:?}, available requests are:\n{}",
+                format_names(available)
+            ),
+            CollectionError::EnvironmentNotFound {
+                collection,
+                environment,
+                available,
+            } => write!(
+                f,
+                "environment {environment:?} not found on collection {collection:?}, available environments are:\n{}",
+                format_names(available)
+            ),
+            CollectionError::FlowNotFound {
+                collection,
+                flow,
+                available,
+            } => write!(
+                f,
+                "flow {flow:?} not found on collection {collection:?}, available flows are:\n{}",
+                format_names(available)
+            ),
+            CollectionError::BrokenFlow { flow, steps } => {
+                let numbers = steps
+                    .iter()
+                    .map(|step| step.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match steps.len() {
+                    1 => write!(
+                        f,
+                        "flow {flow:?} can't run, step {numbers} runs a request that no longer exists"
+                    ),
+                    _ => write!(
+                        f,
+                        "flow {flow:?} can't run, steps {numbers} run requests that no longer exist"
+                    ),
+                }
+            }
         }
     }
 }
 
-impl<E> From<E> for CollectionError
-where
-    E: Into<anyhow::Error>,
-{
-    fn from(err: E) -> Self {
-        let err: anyhow::Error = err.into();
-        let msg = err.to_string();
-        CollectionError::Unknown(msg)
+impl std::error::Error for CollectionError {}
+
+/// one indented name per line, for errors listing what could have been
+/// picked instead
+pub fn format_names(names: &[String]) -> String {
+    if names.is_empty() {
+        return "  (none)".into();
     }
+
+    names
+        .iter()
+        .map(|name| format!("  {}", name))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
@@ -1,17 +1,67 @@
+use crate::collection::CollectionError;
+use crate::hooks::Hooks;
+use crate::net::{body, uri};
+use crate::secrets::Secrets;
+use crate::variables::Layers;
+
+use std::collections::{BTreeMap, BTreeSet};
 use std::hash::Hash;
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::ops::Add;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 use serde::{Deserialize, Serialize};
 
 /// a collection is represented as a file on the file system and holds every
 /// request and metadata
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Collection {
     /// basic information about the collection such as name and description
     pub info: Info,
     /// maybe a vector of `RequestKind` that are part of the collection
     pub requests: Option<Arc<RwLock<Vec<RequestKind>>>>,
+    /// maybe a vector of environments, which are named sets of variables that
+    /// can be used on requests with the `{{variable}}` syntax
+    pub environments: Option<Vec<Environment>>,
+    /// variables shared by every environment, like an api version, an
+    /// environment variable with the same name takes precedence
+    pub variables: Option<BTreeMap<String, String>>,
+    /// proxy used by every request on this collection, overrides the one on
+    /// the config file and the environment variables
+    pub proxy: Option<String>,
+    /// overrides `confirm_destructive` from the config file for this
+    /// collection only
+    pub confirm_destructive: Option<bool>,
+    /// hosts that should resolve to a fixed address instead of going through
+    /// dns, like `curl --resolve`
+    pub resolve: Option<Vec<HostOverride>>,
+    /// sends every request over this unix socket, eg: `/var/run/docker.sock`,
+    /// the request uri still provides the path and the `Host` header
+    pub unix_socket: Option<PathBuf>,
+    /// prefixed to every request uri starting with `/`, so requests keep
+    /// only the path and switching hosts is done in a single place
+    pub base_url: Option<String>,
+    /// requests on the collection give up after this many milliseconds,
+    /// there is no limit when not set
+    pub timeout_ms: Option<u64>,
+    /// overrides `max_request_body` from the config file, `0` never warns
+    pub max_request_body: Option<u64>,
+    /// overrides `max_body_in_memory` from the config file, `0` keeps every
+    /// response body in memory
+    pub max_body_in_memory: Option<u64>,
+    /// headers sent with every request on the collection, a request header
+    /// with the same name takes precedence
+    pub default_headers: Option<Vec<HeaderMap>>,
+    /// commands run around every request on the collection, requests can
+    /// override either of them
+    pub hooks: Option<Hooks>,
+    /// named sequences of requests, ran one after the other like a scenario
+    pub flows: Option<Vec<Flow>>,
+    /// shared collections that shouldn't be changed from here, eg: one
+    /// committed to a repository. Its requests can still be sent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
     /// path is a virtual field used only during runtime to know where to
     /// sync the file, this will be the absolute path to the file on the
     /// users computer
@@ -19,6 +69,344 @@ pub struct Collection {
     pub path: PathBuf,
 }
 
+impl Collection {
+    /// reads the collection stored at `path`, this is all it takes to use a
+    /// collection without the terminal interface
+    ///
+    /// ```
+    /// use hac_core::collection::Collection;
+    /// use hac_core::secrets::Secrets;
+    /// use hac_core::variables;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("users.json");
+    /// # std::fs::write(&path, r#"{
+    /// #     "info": { "name": "users", "description": null },
+    /// #     "base_url": "https://{{host}}",
+    /// #     "requests": [{ "id": "list", "method": "GET", "name": "list users", "uri": "/users" }],
+    /// #     "environments": [{ "name": "local", "variables": { "host": "localhost:3000" } }]
+    /// # }"#)?;
+    /// let collection = Collection::load(&path)?;
+    /// let request = collection.request("list users")?;
+    /// let layers = collection.layers(Some("local"), &Secrets::default())?;
+    /// let request = variables::resolve_request(&request, &layers.merged())?;
+    /// assert_eq!(request.uri, "https://localhost:3000/users");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load<P>(path: P) -> anyhow::Result<Collection>
+    where
+        P: AsRef<Path>,
+    {
+        let file = std::fs::read_to_string(&path)?;
+        let mut collection: Collection = serde_json::from_str(&file)?;
+        collection.path = path.as_ref().to_path_buf();
+        Ok(collection)
+    }
+
+    /// whether the collection says so itself, or its file is in one of the
+    /// `read_only_dirs` of the config. Nothing should be written to it then
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || hac_config::is_in_read_only_dir(&self.path)
+    }
+
+    /// a copy of the request named `name` as runs send it, ready to have its
+    /// variables resolved
+    pub fn request(&self, name: &str) -> Result<Request, CollectionError> {
+        match self.find_request(name) {
+            Some(request) => Ok(self.runnable(&request.read().unwrap())),
+            None => Err(CollectionError::RequestNotFound {
+                collection: self.info.name.clone(),
+                request: name.into(),
+                available: self
+                    .flatten_requests()
+                    .iter()
+                    .map(|request| request.read().unwrap().name.clone())
+                    .collect(),
+            }),
+        }
+    }
+
+    /// the variables requests are resolved with on `environment`, or with
+    /// only the collection ones when there is none. The values of secrets
+    /// come from `secrets`, as they are never stored on the collection
+    pub fn layers(
+        &self,
+        environment: Option<&str>,
+        secrets: &Secrets,
+    ) -> Result<Layers, CollectionError> {
+        let environment = match environment {
+            None => BTreeMap::default(),
+            Some(name) => match self.find_environment(name) {
+                Some(environment) => secrets.variables_for(&Secrets::key(self), environment),
+                None => {
+                    return Err(CollectionError::EnvironmentNotFound {
+                        collection: self.info.name.clone(),
+                        environment: name.into(),
+                        available: self
+                            .environments
+                            .iter()
+                            .flatten()
+                            .map(|environment| environment.name.clone())
+                            .collect(),
+                    })
+                }
+            },
+        };
+
+        Ok(Layers {
+            collection: self.variables.clone().unwrap_or_default(),
+            environment,
+            request: BTreeMap::default(),
+        })
+    }
+
+    /// returns every request on the collection in the order they were authored,
+    /// walking directories depth-first
+    pub fn flatten_requests(&self) -> Vec<Arc<RwLock<Request>>> {
+        let mut requests = vec![];
+        if let Some(items) = self.requests.as_ref() {
+            flatten_into(&items.read().unwrap(), &mut requests);
+        }
+        requests
+    }
+
+    /// finds the first request matching the given name, searching nested
+    /// directories as well
+    pub fn find_request(&self, name: &str) -> Option<Arc<RwLock<Request>>> {
+        self.flatten_requests()
+            .into_iter()
+            .find(|req| req.read().unwrap().name.eq(name))
+    }
+
+    /// finds the first directory matching the given name, searching nested
+    /// directories as well
+    pub fn find_directory(&self, name: &str) -> Option<Directory> {
+        self.requests
+            .as_ref()
+            .and_then(|items| find_directory_in(&items.read().unwrap(), name))
+    }
+
+    /// names of the directories the request is in, outermost first. Empty
+    /// for requests at the root of the collection or that don't exist
+    pub fn directory_path(&self, request_id: &str) -> Vec<String> {
+        let mut path = vec![];
+        if let Some(items) = self.requests.as_ref() {
+            directory_path_in(&items.read().unwrap(), request_id, &mut path);
+        }
+        path
+    }
+
+    /// every tag used on the collection, sorted. Tags only exist on the
+    /// requests carrying them, so a tag is gone with its last request
+    pub fn tags(&self) -> BTreeSet<String> {
+        self.flatten_requests()
+            .iter()
+            .flat_map(|req| req.read().unwrap().tags.clone())
+            .collect()
+    }
+
+    pub fn find_flow(&self, name: &str) -> Option<&Flow> {
+        self.flows
+            .as_ref()
+            .and_then(|flows| flows.iter().find(|flow| flow.name.eq(name)))
+    }
+
+    /// the requests of the flow named `name` in the order they run, as runs
+    /// send them. A flow with steps whose request no longer exists can't run
+    pub fn flow(&self, name: &str) -> Result<Vec<Request>, CollectionError> {
+        let Some(flow) = self.find_flow(name) else {
+            return Err(CollectionError::FlowNotFound {
+                collection: self.info.name.clone(),
+                flow: name.into(),
+                available: self
+                    .flows
+                    .iter()
+                    .flatten()
+                    .map(|flow| flow.name.clone())
+                    .collect(),
+            });
+        };
+
+        let broken = self.broken_steps(flow);
+        if !broken.is_empty() {
+            return Err(CollectionError::BrokenFlow {
+                flow: flow.name.clone(),
+                steps: broken.iter().map(|step| step.add(1)).collect(),
+            });
+        }
+
+        Ok(flow
+            .steps
+            .iter()
+            .filter_map(|id| self.get_request(id))
+            .map(|request| self.runnable(&request.read().unwrap()))
+            .collect())
+    }
+
+    /// indexes of the steps of `flow` referencing a request that doesn't
+    /// exist anymore
+    pub fn broken_steps(&self, flow: &Flow) -> Vec<usize> {
+        let requests = self.flatten_requests();
+        flow.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| {
+                !requests
+                    .iter()
+                    .any(|request| request.read().unwrap().id.eq(*id))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// names of the flows with a step running the request with `id`
+    pub fn flows_using(&self, id: &str) -> Vec<String> {
+        self.flows
+            .iter()
+            .flatten()
+            .filter(|flow| flow.steps.iter().any(|step| step.eq(id)))
+            .map(|flow| flow.name.clone())
+            .collect()
+    }
+
+    /// finds the request with the given id, searching nested directories
+    pub fn get_request(&self, id: &str) -> Option<Arc<RwLock<Request>>> {
+        self.flatten_requests()
+            .into_iter()
+            .find(|req| req.read().unwrap().id.eq(id))
+    }
+
+    pub fn find_environment(&self, name: &str) -> Option<&Environment> {
+        self.environments
+            .as_ref()
+            .and_then(|envs| envs.iter().find(|env| env.name.eq(name)))
+    }
+
+    /// default headers that apply to `request`, those that are enabled and
+    /// not opted out of by the request
+    pub fn inherited_headers(&self, request: &Request) -> Vec<&HeaderMap> {
+        self.default_headers
+            .iter()
+            .flatten()
+            .filter(|header| header.enabled && !request.skips_default_header(&header.pair.0))
+            .collect()
+    }
+
+    /// the request as it should be sent, with the base url prefixed to
+    /// relative uris and the inherited headers the request doesn't override
+    /// appended to its own. Variables are resolved afterwards, so both the
+    /// base url and the default headers can use them
+    pub fn apply_defaults(&self, request: &Request) -> Request {
+        let mut request = request.clone();
+
+        if let Some(base_url) = self.base_url.as_ref() {
+            let path = request.uri.trim_start();
+            if path.starts_with('/') {
+                request.uri = uri::join(base_url, path);
+            }
+        }
+
+        let inherited = self
+            .inherited_headers(&request)
+            .into_iter()
+            .filter(|header| !request.has_header(&header.pair.0))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !inherited.is_empty() {
+            request
+                .headers
+                .get_or_insert_with(Vec::new)
+                .extend(inherited);
+        }
+
+        request
+    }
+
+    /// `request` the way runs send it, with the defaults of the collection
+    /// applied and the hooks it inherits set on it, so the runner doesn't
+    /// need the collection around
+    pub fn runnable(&self, request: &Request) -> Request {
+        Request {
+            hooks: Some(self.hooks_for(request)).filter(|hooks| !hooks.is_empty()),
+            ..self.apply_defaults(request)
+        }
+    }
+
+    /// the hooks `request` runs with, each kind set on the request takes
+    /// precedence over the one on the collection
+    pub fn hooks_for(&self, request: &Request) -> Hooks {
+        let collection = self.hooks.clone().unwrap_or_default();
+        let request = request.hooks.clone().unwrap_or_default();
+        Hooks {
+            pre_request: request.pre_request.or(collection.pre_request),
+            post_response: request.post_response.or(collection.post_response),
+        }
+    }
+}
+
+/// the tags typed on `text`, separated by commas or spaces. Tags are
+/// lowercased and the repeated ones are dropped, keeping the order they were
+/// typed in
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in text
+        .split(|c: char| c.eq(&',') || c.is_whitespace())
+        .map(|tag| tag.trim_start_matches('#').to_lowercase())
+        .filter(|tag| !tag.is_empty())
+    {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+fn find_directory_in(items: &[RequestKind], name: &str) -> Option<Directory> {
+    items.iter().find_map(|item| match item {
+        RequestKind::Single(_) => None,
+        RequestKind::Nested(dir) if dir.name.eq(name) => Some(dir.clone()),
+        RequestKind::Nested(dir) => find_directory_in(&dir.requests.read().unwrap(), name),
+    })
+}
+
+fn directory_path_in(items: &[RequestKind], request_id: &str, path: &mut Vec<String>) -> bool {
+    items.iter().any(|item| match item {
+        RequestKind::Single(req) => req.read().unwrap().id.eq(request_id),
+        RequestKind::Nested(dir) => {
+            path.push(dir.name.clone());
+            let found = directory_path_in(&dir.requests.read().unwrap(), request_id, path);
+            if !found {
+                path.pop();
+            }
+            found
+        }
+    })
+}
+
+fn flatten_into(items: &[RequestKind], requests: &mut Vec<Arc<RwLock<Request>>>) {
+    for item in items {
+        match item {
+            RequestKind::Single(req) => requests.push(Arc::clone(req)),
+            RequestKind::Nested(dir) => flatten_into(&dir.requests.read().unwrap(), requests),
+        }
+    }
+}
+
+/// an environment is a named set of variables, like `staging` or `production`,
+/// the active environment is used to resolve `{{variable}}` placeholders on
+/// requests right before they are sent
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Environment {
+    pub name: String,
+    pub variables: BTreeMap<String, String>,
+    /// names of the variables whose values are secret, their values are kept
+    /// on the secrets file instead of on the collection
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub secrets: BTreeSet<String>,
+}
+
 /// we store requests on a collection and on directories as a enum that could
 /// be either an request or a directory. This enables us to have nested
 /// directories, although we don't support that now and might not ever support.
@@ -64,21 +452,67 @@ impl RequestKind {
 /// represents name/value of a header, and wether it is enabled or not.
 ///
 /// disabled headers should not be sent on requests
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct HeaderMap {
     pub pair: (String, String),
     pub enabled: bool,
 }
 
-/// set of methods we currently support on HTTP requests
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
-#[serde(rename_all = "UPPERCASE")]
+/// set of methods we currently support on HTTP requests. They are stored as
+/// plain strings, so nonstandard methods round trip as they were typed
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(try_from = "String", into = "String")]
 pub enum RequestMethod {
+    #[default]
     Get,
     Post,
     Put,
     Patch,
     Delete,
+    /// any other method, like `PROPFIND` or `PURGE`, sent verbatim
+    Other(String),
+}
+
+impl RequestMethod {
+    /// whether `token` can be used as a method, following the `token` rule
+    /// of RFC 7230
+    pub fn is_valid_token(token: &str) -> bool {
+        !token.is_empty() && token.chars().all(is_token_char)
+    }
+}
+
+pub fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+impl std::str::FromStr for RequestMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "GET" => Ok(RequestMethod::Get),
+            "POST" => Ok(RequestMethod::Post),
+            "PUT" => Ok(RequestMethod::Put),
+            "PATCH" => Ok(RequestMethod::Patch),
+            "DELETE" => Ok(RequestMethod::Delete),
+            _ if RequestMethod::is_valid_token(value) => Ok(RequestMethod::Other(value.into())),
+            _ => anyhow::bail!("invalid request method {value:?}"),
+        }
+    }
+}
+
+impl TryFrom<String> for RequestMethod {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> anyhow::Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<RequestMethod> for String {
+    fn from(method: RequestMethod) -> Self {
+        method.to_string()
+    }
 }
 
 impl TryFrom<usize> for RequestMethod {
@@ -91,6 +525,7 @@ impl TryFrom<usize> for RequestMethod {
             2 => Ok(RequestMethod::Put),
             3 => Ok(RequestMethod::Patch),
             4 => Ok(RequestMethod::Delete),
+            5 => Ok(RequestMethod::Other(String::default())),
             _ => anyhow::bail!("invalid request method index"),
         }
     }
@@ -105,17 +540,19 @@ impl RequestMethod {
             RequestMethod::Post => RequestMethod::Put,
             RequestMethod::Put => RequestMethod::Patch,
             RequestMethod::Patch => RequestMethod::Delete,
-            RequestMethod::Delete => RequestMethod::Get,
+            RequestMethod::Delete => RequestMethod::Other(String::default()),
+            RequestMethod::Other(_) => RequestMethod::Get,
         }
     }
 
     pub fn prev(&self) -> Self {
         match self {
-            RequestMethod::Get => RequestMethod::Delete,
+            RequestMethod::Get => RequestMethod::Other(String::default()),
             RequestMethod::Post => RequestMethod::Get,
             RequestMethod::Put => RequestMethod::Post,
             RequestMethod::Patch => RequestMethod::Put,
             RequestMethod::Delete => RequestMethod::Patch,
+            RequestMethod::Other(_) => RequestMethod::Delete,
         }
     }
 }
@@ -128,6 +565,7 @@ impl std::fmt::Display for RequestMethod {
             Self::Put => f.write_str("PUT"),
             Self::Patch => f.write_str("PATCH"),
             Self::Delete => f.write_str("DELETE"),
+            Self::Other(method) => f.write_str(method),
         }
     }
 }
@@ -153,7 +591,7 @@ impl RequestMethod {
 /// This is how we store a request on the system, basically this stores all
 /// needed information about a request to be able to perform any actions we
 /// allow.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Request {
     /// we store an uuid on each request to be able to easily identify them
     /// as identifying by name is
@@ -181,19 +619,480 @@ pub struct Request {
     /// the type of the body to be used, like `application/json` or any other
     /// accepted body type
     pub body_type: Option<BodyType>,
+    /// checks that are run against the response after the request is sent,
+    /// these decide wether the request passed or failed on collection runs
+    pub assertions: Option<Vec<Assertion>>,
+    /// values extracted from the response into variables after a successful
+    /// send, so later requests can use them, eg: a token from a login request
+    pub captures: Option<Vec<Capture>>,
+    /// compressed responses are decompressed by default, disabling it keeps
+    /// the body exactly as it was received
+    #[serde(default)]
+    pub disable_decompression: bool,
+    /// sends the request on a brand new connection instead of reusing one
+    /// kept alive from earlier requests
+    #[serde(default)]
+    pub fresh_connection: bool,
+    /// the body is sent exactly as written, without substituting its
+    /// placeholders. The uri, headers and auth are still substituted
+    #[serde(default)]
+    pub raw_body: bool,
+    /// free text documenting the request for whoever opens the collection,
+    /// eg: required scopes or quirks of the payload
+    pub notes: Option<String>,
+    /// http version to send the request with, when not set http/2 is
+    /// offered and the server picks
+    pub http_version: Option<HttpVersion>,
+    /// language the response body is displayed as, instead of the one told
+    /// by its content type
+    pub preview_language: Option<PreviewLanguage>,
+    /// names of the collection default headers this request is sent
+    /// without
+    pub skip_default_headers: Option<Vec<String>>,
+    /// commands run before sending and after receiving this request
+    pub hooks: Option<Hooks>,
+    /// labels like `smoke` or `admin`, the sidebar and collection runs can
+    /// be narrowed down to the requests carrying one of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// a fixed address for this request alone, used over the ones of the
+    /// collection for the same host and port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolve: Option<HostOverride>,
+}
+
+impl Request {
+    /// tags are lowercased when set, so this ignores the case of `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own.eq_ignore_ascii_case(tag))
+    }
+
+    /// whether the request has an enabled header named `name`, header names
+    /// are case insensitive
+    pub fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .flatten()
+            .any(|header| header.enabled && header.pair.0.eq_ignore_ascii_case(name))
+    }
+
+    /// whether the collection default header named `name` is opted out of
+    pub fn skips_default_header(&self, name: &str) -> bool {
+        self.skip_default_headers
+            .iter()
+            .flatten()
+            .any(|skipped| skipped.eq_ignore_ascii_case(name))
+    }
+
+    /// opts out of the collection default header named `name`, or back in
+    /// when it was opted out already
+    pub fn toggle_default_header(&mut self, name: &str) {
+        let skipped = self.skip_default_headers.get_or_insert_with(Vec::new);
+        match skipped.iter().position(|s| s.eq_ignore_ascii_case(name)) {
+            Some(idx) => _ = skipped.remove(idx),
+            None => skipped.push(name.to_string()),
+        }
+        if skipped.is_empty() {
+            self.skip_default_headers = None;
+        }
+    }
+
+    /// headers that are added on their own based on the body, eg: the content
+    /// type for json bodies. An enabled header with the same name takes
+    /// precedence, so these are only sent when `has_header` is false
+    pub fn computed_headers(&self) -> Vec<(String, String)> {
+        // `GET` requests are never sent with a body
+        let has_body = self.body.as_deref().is_some_and(|body| !body.is_empty())
+            && self.method.ne(&RequestMethod::Get);
+        if !has_body {
+            return vec![];
+        }
+
+        let body_type = self.body_type.as_ref().unwrap_or(&BodyType::Json);
+        let content_type = match body_type {
+            BodyType::Multipart => format!(
+                "{}; boundary={}",
+                body_type.content_type(),
+                body::boundary(self.body.as_deref().unwrap_or_default())
+            ),
+            _ => body_type.content_type().into(),
+        };
+        vec![("Content-Type".into(), content_type)]
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum HttpVersion {
+    /// never attempts http/2, even when the server offers it
+    Http1,
+    /// fails the request when the server doesn't agree on http/2
+    Http2,
+}
+
+impl HttpVersion {
+    /// goes through negotiated, forced http/1.1 and forced http/2
+    pub fn cycle(version: Option<HttpVersion>) -> Option<HttpVersion> {
+        match version {
+            None => Some(HttpVersion::Http1),
+            Some(HttpVersion::Http1) => Some(HttpVersion::Http2),
+            Some(HttpVersion::Http2) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpVersion::Http1 => write!(f, "HTTP/1.1"),
+            HttpVersion::Http2 => write!(f, "HTTP/2"),
+        }
+    }
+}
+
+/// the languages a response body can be displayed as
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviewLanguage {
+    Json,
+    Xml,
+    Html,
+    Yaml,
+    Plain,
+}
+
+impl PreviewLanguage {
+    pub fn next(&self) -> Self {
+        match self {
+            PreviewLanguage::Json => PreviewLanguage::Xml,
+            PreviewLanguage::Xml => PreviewLanguage::Html,
+            PreviewLanguage::Html => PreviewLanguage::Yaml,
+            PreviewLanguage::Yaml => PreviewLanguage::Plain,
+            PreviewLanguage::Plain => PreviewLanguage::Json,
+        }
+    }
+
+    /// tells the language from the content type of the response, falling
+    /// back to the start of the body when the content type is missing or
+    /// doesn't say, eg: `text/plain`
+    pub fn detect(content_type: Option<&str>, body: &str) -> Self {
+        let content_type = content_type.unwrap_or_default().to_ascii_lowercase();
+        match content_type {
+            _ if content_type.contains("json") => PreviewLanguage::Json,
+            _ if content_type.contains("html") => PreviewLanguage::Html,
+            _ if content_type.contains("xml") => PreviewLanguage::Xml,
+            _ if content_type.contains("yaml") => PreviewLanguage::Yaml,
+            _ => {
+                let body = body.trim_start();
+                let starts_with = |prefix: &str| {
+                    body.get(..prefix.len())
+                        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+                };
+                match body.chars().next() {
+                    Some('{' | '[') => PreviewLanguage::Json,
+                    _ if starts_with("<!doctype html") || starts_with("<html") => {
+                        PreviewLanguage::Html
+                    }
+                    Some('<') => PreviewLanguage::Xml,
+                    _ => PreviewLanguage::Plain,
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PreviewLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreviewLanguage::Json => f.write_str("json"),
+            PreviewLanguage::Xml => f.write_str("xml"),
+            PreviewLanguage::Html => f.write_str("html"),
+            PreviewLanguage::Yaml => f.write_str("yaml"),
+            PreviewLanguage::Plain => f.write_str("plain"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum AuthMethod {
     Bearer,
     Basic,
+    /// signs the request with aws signature version 4, for api gateway and
+    /// s3 compatible services. Every field can use variables. The secret key
+    /// and the session token are never written to the collection file, they
+    /// are kept on the secrets file instead
+    AwsSigV4 {
+        access_key: String,
+        #[serde(serialize_with = "redacted")]
+        secret_key: String,
+        region: String,
+        service: String,
+        #[serde(serialize_with = "redacted_option")]
+        session_token: Option<String>,
+    },
+    /// answers the `401` digest challenge of the server and sends the
+    /// request again, both fields can use variables. Like the aws secret
+    /// key, the password is kept on the secrets file
+    Digest {
+        username: String,
+        #[serde(serialize_with = "redacted")]
+        password: String,
+    },
+}
+
+/// a field of an auth method, as the auth editor shows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthField {
+    /// what the field is called on the collection file
+    pub name: &'static str,
+    pub label: &'static str,
+    /// secret fields are masked on the editor and their values are kept on
+    /// the secrets file, never on the collection
+    pub secret: bool,
 }
 
-/// a collection of all available body types we support.
+impl AuthField {
+    const fn new(name: &'static str, label: &'static str, secret: bool) -> Self {
+        AuthField {
+            name,
+            label,
+            secret,
+        }
+    }
+}
+
+const AWS_SIGV4_FIELDS: &[AuthField] = &[
+    AuthField::new("access_key", "Access key", false),
+    AuthField::new("secret_key", "Secret key", true),
+    AuthField::new("region", "Region", false),
+    AuthField::new("service", "Service", false),
+    AuthField::new("session_token", "Session token", true),
+];
+
+const DIGEST_FIELDS: &[AuthField] = &[
+    AuthField::new("username", "Username", false),
+    AuthField::new("password", "Password", true),
+];
+
+impl AuthMethod {
+    /// every field of the method, in the order the editor shows them
+    pub fn fields(&self) -> &'static [AuthField] {
+        match self {
+            AuthMethod::AwsSigV4 { .. } => AWS_SIGV4_FIELDS,
+            AuthMethod::Digest { .. } => DIGEST_FIELDS,
+            AuthMethod::Bearer | AuthMethod::Basic => &[],
+        }
+    }
+
+    /// the value of the field called `name`, optional fields without a value
+    /// are empty
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let value = match (self, name) {
+            (AuthMethod::AwsSigV4 { access_key, .. }, "access_key") => access_key,
+            (AuthMethod::AwsSigV4 { secret_key, .. }, "secret_key") => secret_key,
+            (AuthMethod::AwsSigV4 { region, .. }, "region") => region,
+            (AuthMethod::AwsSigV4 { service, .. }, "service") => service,
+            (AuthMethod::AwsSigV4 { session_token, .. }, "session_token") => {
+                return Some(session_token.as_deref().unwrap_or_default())
+            }
+            (AuthMethod::Digest { username, .. }, "username") => username,
+            (AuthMethod::Digest { password, .. }, "password") => password,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// sets the field called `name`, clearing an optional field removes it.
+    /// Names the method doesn't have are ignored
+    pub fn set(&mut self, name: &str, value: String) {
+        let field = match (self, name) {
+            (AuthMethod::AwsSigV4 { access_key, .. }, "access_key") => access_key,
+            (AuthMethod::AwsSigV4 { secret_key, .. }, "secret_key") => secret_key,
+            (AuthMethod::AwsSigV4 { region, .. }, "region") => region,
+            (AuthMethod::AwsSigV4 { service, .. }, "service") => service,
+            (AuthMethod::AwsSigV4 { session_token, .. }, "session_token") => {
+                *session_token = Some(value).filter(|value| !value.is_empty());
+                return;
+            }
+            (AuthMethod::Digest { username, .. }, "username") => username,
+            (AuthMethod::Digest { password, .. }, "password") => password,
+            _ => return,
+        };
+        *field = value;
+    }
+
+    /// every value the method holds, so variables can be substituted in them
+    pub fn values_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            AuthMethod::AwsSigV4 {
+                access_key,
+                secret_key,
+                region,
+                service,
+                session_token,
+            } => [access_key, secret_key, region, service]
+                .into_iter()
+                .chain(session_token.as_mut())
+                .collect(),
+            AuthMethod::Digest { username, password } => vec![username, password],
+            AuthMethod::Bearer | AuthMethod::Basic => vec![],
+        }
+    }
+}
+
+fn redacted<S: serde::Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("")
+}
+
+fn redacted_option<S: serde::Serializer>(
+    _: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_none()
+}
+
+/// a collection of all available body types we support. The body is always
+/// written as text, the type decides how it is encoded when sent, see
+/// `net::body::encode`
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub enum BodyType {
     #[serde(rename = "json")]
     Json,
+    #[serde(rename = "text")]
+    Text,
+    /// written as `name=value` lines, sent as an url encoded form
+    #[serde(rename = "urlencoded")]
+    UrlEncoded,
+    /// written as `name=value` lines, each one sent as a text part
+    #[serde(rename = "multipart")]
+    Multipart,
+    /// the query, optionally followed by a `---` line and the variables as
+    /// a json object
+    #[serde(rename = "graphql")]
+    GraphQl,
+}
+
+impl BodyType {
+    /// multipart bodies also need their boundary, which depends on the body,
+    /// `Request::computed_headers` adds it
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            BodyType::Json | BodyType::GraphQl => "application/json",
+            BodyType::Text => "text/plain",
+            BodyType::UrlEncoded => "application/x-www-form-urlencoded",
+            BodyType::Multipart => "multipart/form-data",
+        }
+    }
+
+    /// used when the body is written to a file, so editors pick the right
+    /// syntax for it
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BodyType::Json => "json",
+            BodyType::GraphQl => "graphql",
+            BodyType::Text | BodyType::UrlEncoded | BodyType::Multipart => "txt",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            BodyType::Json => BodyType::Text,
+            BodyType::Text => BodyType::UrlEncoded,
+            BodyType::UrlEncoded => BodyType::Multipart,
+            BodyType::Multipart => BodyType::GraphQl,
+            BodyType::GraphQl => BodyType::Json,
+        }
+    }
+}
+
+impl std::fmt::Display for BodyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyType::Json => f.write_str("json"),
+            BodyType::Text => f.write_str("text"),
+            BodyType::UrlEncoded => f.write_str("url encoded form"),
+            BodyType::Multipart => f.write_str("multipart form"),
+            BodyType::GraphQl => f.write_str("graphql"),
+        }
+    }
+}
+
+/// a check performed against a response. Every variant can be written in a
+/// short textual form, which is what the editor shows, eg: `status 2xx` or
+/// `$.items length >= 1`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Assertion {
+    /// status code must be exactly the expected one
+    Status { expected: u16 },
+    /// status code must be within a class, `2` means any 2xx status
+    StatusClass { class: u16 },
+    /// header must be present and its value must be exactly `value`
+    HeaderEquals { name: String, value: String },
+    /// header must be present and its value must contain `value`
+    HeaderContains { name: String, value: String },
+    /// raw body must contain the given substring
+    BodyContains { value: String },
+    /// body must be valid json and the value at `path` must pass `check`
+    JsonPath { path: String, check: JsonCheck },
+}
+
+/// checks that can be performed on a value found by a json path
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum JsonCheck {
+    Exists,
+    /// strings are compared by their contents, any other value is compared
+    /// by its json representation, so `true` or `42` work as expected
+    Equals {
+        value: String,
+    },
+    /// only arrays, objects and strings have a length
+    Length {
+        comparison: Comparison,
+        value: usize,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum Comparison {
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+}
+
+/// a capture stores a value from a response into the variable `name`. Like
+/// assertions, captures have a short textual form, eg: `token = $.data.token`
+/// or `session = header x-session-id`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Capture {
+    pub name: String,
+    pub source: CaptureSource,
+    /// persisted captures are also written to the active environment on the
+    /// collection file, otherwise they only live until the app is closed
+    #[serde(default)]
+    pub persist: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "from", rename_all = "camelCase")]
+pub enum CaptureSource {
+    /// a value from the json body, using the same paths as assertions
+    JsonPath {
+        path: String,
+    },
+    Header {
+        name: String,
+    },
 }
 
 /// a directory can hold a vector of requests, which will be
@@ -211,11 +1110,402 @@ pub struct Directory {
     pub requests: Arc<RwLock<Vec<RequestKind>>>,
 }
 
+impl Directory {
+    /// returns every request inside the directory in the order they were
+    /// authored, walking nested directories depth-first
+    pub fn flatten_requests(&self) -> Vec<Arc<RwLock<Request>>> {
+        let mut requests = vec![];
+        flatten_into(&self.requests.read().unwrap(), &mut requests);
+        requests
+    }
+}
+
+/// an ordered subset of the requests of a collection, like logging in,
+/// creating something, checking it and cleaning up after. Captures of a
+/// step are used by the ones after it, and a failing step stops the flow
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Flow {
+    pub name: String,
+    /// ids of the requests to run, in order. Ids are used instead of names
+    /// so renaming a request doesn't break the flow
+    pub steps: Vec<String>,
+}
+
+/// makes requests to `host:port` connect to `address`. The request is still
+/// made to `host`, so the `Host` header, SNI and certificate checks are the
+/// same as without the override
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct HostOverride {
+    pub host: String,
+    pub port: u16,
+    pub address: IpAddr,
+}
+
+impl HostOverride {
+    /// hosts are case insensitive, so `API.example.com` also matches
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        self.port.eq(&port) && self.host.eq_ignore_ascii_case(host)
+    }
+}
+
+/// written like curl takes them, `host:port:address`, ipv6 addresses can be
+/// wrapped in brackets
+impl std::str::FromStr for HostOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, ':');
+        let (Some(host), Some(port), Some(address)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err("must be written as host:port:address".into());
+        };
+        if host.is_empty() {
+            return Err("the host can't be empty".into());
+        }
+        let port = port
+            .parse::<u16>()
+            .map_err(|_| format!("{port} is not a port"))?;
+        let address = address
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .map_err(|_| format!("{address} is not an ip address"))?;
+
+        Ok(HostOverride {
+            host: host.to_string(),
+            port,
+            address,
+        })
+    }
+}
+
+impl std::fmt::Display for HostOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.address {
+            IpAddr::V4(address) => write!(f, "{}:{}:{address}", self.host, self.port),
+            IpAddr::V6(address) => write!(f, "{}:{}:[{address}]", self.host, self.port),
+        }
+    }
+}
+
 /// basic information about a colleciton
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Info {
     /// name of the collection that will be displayed onscreen
     pub name: String,
     /// a optional description in case it is useful
     pub description: Option<String>,
+    /// url the collection was imported from, so it can be fetched again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection() -> Collection {
+        serde_json::from_str(
+            r#"{
+                "info": { "name": "api", "description": null },
+                "requests": [],
+                "base_url": "https://{{host}}/v1/",
+                "default_headers": [
+                    { "pair": ["Authorization", "Bearer {{token}}"], "enabled": true },
+                    { "pair": ["Accept", "application/json"], "enabled": true },
+                    { "pair": ["X-Debug", "1"], "enabled": false }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn request(uri: &str) -> Request {
+        serde_json::from_str(&format!(
+            r#"{{ "id": "a", "method": "GET", "name": "users", "uri": "{uri}" }}"#
+        ))
+        .unwrap()
+    }
+
+    fn header_names(request: &Request) -> Vec<&str> {
+        request
+            .headers
+            .iter()
+            .flatten()
+            .map(|header| header.pair.0.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_preview_language_is_detected_from_content_type_or_body() {
+        let cases = [
+            (Some("application/problem+json"), "", PreviewLanguage::Json),
+            (Some("text/html; charset=utf-8"), "", PreviewLanguage::Html),
+            (Some("application/xml"), "{}", PreviewLanguage::Xml),
+            (Some("application/x-yaml"), "", PreviewLanguage::Yaml),
+            (Some("text/plain"), "  [1, 2]", PreviewLanguage::Json),
+            (None, "<!DOCTYPE html><html></html>", PreviewLanguage::Html),
+            (
+                Some("application/octet-stream"),
+                "<?xml?>",
+                PreviewLanguage::Xml,
+            ),
+            (None, "ok", PreviewLanguage::Plain),
+        ];
+        for (content_type, body, expected) in cases {
+            assert_eq!(
+                PreviewLanguage::detect(content_type, body),
+                expected,
+                "{content_type:?} {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_base_url_is_only_prefixed_to_paths() {
+        let collection = collection();
+
+        let applied = collection.apply_defaults(&request("/users"));
+        assert_eq!(applied.uri, "https://{{host}}/v1/users");
+
+        let applied = collection.apply_defaults(&request("https://other.com/users"));
+        assert_eq!(applied.uri, "https://other.com/users");
+
+        let applied = collection.apply_defaults(&request(" //users"));
+        assert_eq!(applied.uri, "https://{{host}}/v1/users");
+    }
+
+    #[test]
+    fn test_request_headers_override_defaults() {
+        let collection = collection();
+        let mut request = request("/users");
+        request.headers = Some(vec![HeaderMap {
+            pair: ("accept".into(), "text/plain".into()),
+            enabled: true,
+        }]);
+
+        let applied = collection.apply_defaults(&request);
+        assert_eq!(header_names(&applied), vec!["accept", "Authorization"]);
+
+        request.toggle_default_header("authorization");
+        let applied = collection.apply_defaults(&request);
+        assert_eq!(header_names(&applied), vec!["accept"]);
+
+        request.toggle_default_header("Authorization");
+        assert_eq!(request.skip_default_headers, None);
+    }
+
+    #[test]
+    fn test_directory_path_of_nested_requests() {
+        let dir = |name: &str, items: Vec<RequestKind>| {
+            RequestKind::Nested(Directory {
+                id: name.into(),
+                name: name.into(),
+                requests: Arc::new(RwLock::new(items)),
+            })
+        };
+        let single = |id: &str| {
+            let mut request = request("/");
+            request.id = id.into();
+            RequestKind::Single(Arc::new(RwLock::new(request)))
+        };
+
+        let mut collection = collection();
+        collection.requests = Some(Arc::new(RwLock::new(vec![
+            dir(
+                "auth",
+                vec![dir("admin", vec![single("login")]), single("me")],
+            ),
+            single("health"),
+        ])));
+
+        assert_eq!(collection.directory_path("login"), vec!["auth", "admin"]);
+        assert_eq!(collection.directory_path("me"), vec!["auth"]);
+        assert!(collection.directory_path("health").is_empty());
+        assert!(collection.directory_path("missing").is_empty());
+    }
+
+    #[test]
+    fn test_requests_and_environments_are_looked_up_by_name() {
+        let mut collection = collection();
+        collection.requests = Some(Arc::new(RwLock::new(vec![RequestKind::Single(Arc::new(
+            RwLock::new(request("/users")),
+        ))])));
+        collection.variables = Some(BTreeMap::from([("host".into(), "example.com".into())]));
+        collection.environments = Some(vec![serde_json::from_str(
+            r#"{ "name": "local", "variables": { "host": "localhost" }, "secrets": ["token"] }"#,
+        )
+        .unwrap()]);
+
+        let request = collection.request("users").unwrap();
+        assert_eq!(request.uri, "https://{{host}}/v1/users");
+        assert_eq!(
+            collection.request("posts").unwrap_err().to_string(),
+            "request \"posts\" not found on collection \"api\", available requests are:\n  users"
+        );
+
+        let mut secrets = Secrets::default();
+        secrets.insert(
+            &Secrets::key(&collection),
+            "local",
+            "token",
+            "s3cr3t".into(),
+        );
+        let layers = collection.layers(Some("local"), &secrets).unwrap();
+        assert_eq!(layers.collection["host"], "example.com");
+        assert_eq!(layers.merged()["host"], "localhost");
+        assert_eq!(layers.merged()["token"], "s3cr3t");
+        assert!(collection
+            .layers(None, &secrets)
+            .unwrap()
+            .environment
+            .is_empty());
+        assert_eq!(
+            collection.layers(Some("prod"), &secrets).unwrap_err(),
+            CollectionError::EnvironmentNotFound {
+                collection: "api".into(),
+                environment: "prod".into(),
+                available: vec!["local".into()],
+            }
+        );
+    }
+    #[test]
+    fn test_tags_only_live_on_requests() {
+        assert_eq!(
+            parse_tags("Smoke, admin #smoke,,  deprecated"),
+            vec!["smoke", "admin", "deprecated"]
+        );
+        assert!(parse_tags(" , ").is_empty());
+
+        let tagged = |id: &str, tags: &str| {
+            let mut request = request("/");
+            request.id = id.into();
+            request.tags = parse_tags(tags);
+            RequestKind::Single(Arc::new(RwLock::new(request)))
+        };
+        let mut collection = collection();
+        collection.requests = Some(Arc::new(RwLock::new(vec![
+            tagged("a", "smoke admin"),
+            RequestKind::Nested(Directory {
+                id: "dir".into(),
+                name: "dir".into(),
+                requests: Arc::new(RwLock::new(vec![tagged("b", "smoke")])),
+            }),
+        ])));
+        assert_eq!(
+            collection.tags(),
+            BTreeSet::from(["admin".into(), "smoke".into()])
+        );
+        assert!(collection
+            .get_request("a")
+            .unwrap()
+            .read()
+            .unwrap()
+            .has_tag("ADMIN"));
+
+        // deleting the last request with a tag leaves nothing of it behind
+        collection
+            .requests
+            .as_ref()
+            .unwrap()
+            .write()
+            .unwrap()
+            .remove(0);
+        assert_eq!(collection.tags(), BTreeSet::from(["smoke".into()]));
+        let json = serde_json::to_string(&collection).unwrap();
+        assert!(!json.contains("admin"));
+        assert!(!serde_json::to_string(&request("/"))
+            .unwrap()
+            .contains("tags"));
+    }
+
+    #[test]
+    fn test_flows_run_their_steps_by_id() {
+        let mut collection = collection();
+        let make = |id: &str, name: &str| {
+            let mut request = request(&format!("/{name}"));
+            request.id = id.into();
+            request.name = name.into();
+            RequestKind::Single(Arc::new(RwLock::new(request)))
+        };
+        collection.requests = Some(Arc::new(RwLock::new(vec![
+            make("a", "login"),
+            make("b", "create"),
+            make("c", "cleanup"),
+        ])));
+        collection.flows = Some(vec![Flow {
+            name: "smoke".into(),
+            steps: vec!["a".into(), "c".into(), "b".into()],
+        }]);
+
+        let names = |requests: Vec<Request>| {
+            requests
+                .into_iter()
+                .map(|request| request.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(collection.flow("smoke").unwrap()),
+            vec!["login", "cleanup", "create"]
+        );
+        assert_eq!(collection.flows_using("c"), vec!["smoke"]);
+        assert_eq!(
+            collection.flow("nightly").unwrap_err().to_string(),
+            "flow \"nightly\" not found on collection \"api\", available flows are:\n  smoke"
+        );
+
+        // renaming keeps the flow working, deleting breaks the step
+        collection.get_request("b").unwrap().write().unwrap().name = "create user".into();
+        assert_eq!(
+            names(collection.flow("smoke").unwrap()),
+            vec!["login", "cleanup", "create user"]
+        );
+        collection
+            .requests
+            .as_ref()
+            .unwrap()
+            .write()
+            .unwrap()
+            .remove(2);
+        let flow = collection.find_flow("smoke").unwrap();
+        assert_eq!(collection.broken_steps(flow), vec![1]);
+        assert_eq!(
+            collection.flow("smoke").unwrap_err(),
+            CollectionError::BrokenFlow {
+                flow: "smoke".into(),
+                steps: vec![2],
+            }
+        );
+    }
+
+    #[test]
+    fn test_host_overrides_are_written_like_curl() {
+        let entry = "api.example.com:443:10.0.0.7"
+            .parse::<HostOverride>()
+            .unwrap();
+        assert_eq!(
+            entry,
+            HostOverride {
+                host: "api.example.com".into(),
+                port: 443,
+                address: "10.0.0.7".parse().unwrap(),
+            }
+        );
+        assert_eq!(entry.to_string(), "api.example.com:443:10.0.0.7");
+
+        let entry = "localhost:8080:[::1]".parse::<HostOverride>().unwrap();
+        assert_eq!(entry.address, "::1".parse::<IpAddr>().unwrap());
+        assert_eq!(entry.to_string(), "localhost:8080:[::1]");
+        // brackets are optional
+        assert_eq!("localhost:8080:::1".parse::<HostOverride>(), Ok(entry));
+
+        assert!("api.example.com:443".parse::<HostOverride>().is_err());
+        assert!("api.example.com:https:10.0.0.7"
+            .parse::<HostOverride>()
+            .is_err());
+        assert!("api.example.com:443:not-an-ip"
+            .parse::<HostOverride>()
+            .is_err());
+        assert!(":443:10.0.0.7".parse::<HostOverride>().is_err());
+    }
 }
@@ -1,9 +1,166 @@
 use crate::collection::Collection;
 
+use crate::net::download::Progress;
+use crate::net::request_manager::Response;
+use std::collections::BTreeMap;
+
 #[derive(Debug)]
 pub enum Command {
     Quit,
     SelectCollection(Collection),
-    Error(String),
+    Error(ErrorReport),
     CreateCollection(Collection),
+    /// an import created more than one collection, or had to leave out
+    /// things the file had, so the collections are listed instead of opened
+    CollectionsImported {
+        collections: Vec<Collection>,
+        skipped: Vec<String>,
+    },
+    /// an existing collection had its info edited and was written back to
+    /// disk
+    UpdateCollection(Collection),
+    /// opens the collection with the request with the given id already
+    /// selected, used by the search on the dashboard
+    OpenRequest {
+        collection: Collection,
+        request_id: String,
+    },
+    /// collections are read from disk on a background task when the
+    /// application starts, this delivers them once they are ready
+    CollectionsLoaded(Vec<Collection>),
+    /// the response of a request sent from the viewer. Every send gets its
+    /// own `send_id`, so the response of a send that was cancelled or
+    /// superseded by another one can be told apart and discarded
+    ResponseReceived {
+        /// the viewer that sent the request, results of a viewer that was
+        /// replaced by opening another collection are dropped
+        viewer_id: u64,
+        send_id: u64,
+        request_id: String,
+        response: Box<Response>,
+    },
+    /// how much of the response body of a send has arrived, sent a few
+    /// times per second while the body is downloading
+    RequestProgress {
+        viewer_id: u64,
+        send_id: u64,
+        progress: Progress,
+    },
+    /// hands the body of the selected request over to the user's editor,
+    /// the application suspends itself until the editor exits. `extension`
+    /// is the one of the temporary file the body is written to
+    EditExternally {
+        viewer_id: u64,
+        content: String,
+        extension: String,
+    },
+    /// what was on the file once the external editor exited, or why it
+    /// couldn't be read back
+    ExternalEditFinished {
+        viewer_id: u64,
+        result: Result<String, ErrorReport>,
+    },
+    /// the pre-request hook of a send exited, with the variables it printed
+    /// when it succeeded
+    PreRequestHookFinished {
+        viewer_id: u64,
+        send_id: u64,
+        request_id: String,
+        result: Result<BTreeMap<String, String>, ErrorReport>,
+    },
+    /// a release newer than the running version was published, only sent
+    /// when the update check succeeds
+    UpdateAvailable(String),
+    /// the viewer was switched to another layout orientation, which is
+    /// remembered for the next time the application runs
+    LayoutChanged(hac_config::LayoutOrientation),
+    /// offline mode was toggled on one of the screens, the other screens
+    /// follow it
+    OfflineChanged(bool),
+}
+
+/// an error to be displayed to the user, along with every error that caused
+/// it, outermost first
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ErrorReport {
+    pub message: String,
+    pub causes: Vec<String>,
+}
+
+impl ErrorReport {
+    pub fn new(error: &dyn std::error::Error) -> Self {
+        let mut causes = vec![];
+        let mut source = error.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+
+        ErrorReport {
+            message: error.to_string(),
+            causes,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ErrorReport {
+    fn from(error: anyhow::Error) -> Self {
+        let mut chain = error.chain().map(|cause| cause.to_string());
+        ErrorReport {
+            message: chain.next().unwrap_or_default(),
+            causes: chain.collect(),
+        }
+    }
+}
+
+impl From<String> for ErrorReport {
+    fn from(message: String) -> Self {
+        ErrorReport {
+            message,
+            causes: vec![],
+        }
+    }
+}
+
+impl From<&str> for ErrorReport {
+    fn from(message: &str) -> Self {
+        ErrorReport::from(message.to_string())
+    }
+}
+
+impl std::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)?;
+        if !self.causes.is_empty() {
+            f.write_str("\n\ncaused by:")?;
+            for cause in self.causes.iter() {
+                write!(f, "\n  {}", cause)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_keeps_the_error_chain() {
+        let error = anyhow::anyhow!("invalid peer certificate")
+            .context("tls handshake failed")
+            .context("failed to send request");
+
+        let report = ErrorReport::from(error);
+
+        assert_eq!(report.message, "failed to send request");
+        assert_eq!(
+            report.causes,
+            vec!["tls handshake failed", "invalid peer certificate"]
+        );
+        assert_eq!(
+            report.to_string(),
+            "failed to send request\n\ncaused by:\n  tls handshake failed\n  invalid peer certificate"
+        );
+    }
 }
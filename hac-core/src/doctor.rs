@@ -0,0 +1,299 @@
+use crate::collection::types::Collection;
+use crate::variables;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// something on the collections directory that keeps a collection from
+/// showing up, or a request from being sent as is
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Problem {
+    /// the file isn't a collection we can read, `line` and `column` point to
+    /// where reading it stopped
+    Unparsable {
+        file: PathBuf,
+        line: usize,
+        column: usize,
+        error: String,
+    },
+    /// more than a file holds a collection with this name, only one of them
+    /// can be told apart on the dashboard or by `hac run`
+    DuplicateName { name: String, files: Vec<PathBuf> },
+    /// placeholders of the request that have no value on the environment,
+    /// or on the collection variables when there are no environments
+    UnresolvedVariables {
+        file: PathBuf,
+        collection: String,
+        request: String,
+        environment: Option<String>,
+        names: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Unparsable {
+                file,
+                line,
+                column,
+                error,
+            } => write!(
+                f,
+                "{}: not a collection, at line {line} column {column}: {error}",
+                file.to_string_lossy()
+            ),
+            Problem::DuplicateName { name, files } => {
+                let files = files
+                    .iter()
+                    .map(|file| file.to_string_lossy())
+                    .collect::<Vec<_>>();
+                write!(f, "{name:?} is the name of {}", files.join(", "))
+            }
+            Problem::UnresolvedVariables {
+                collection,
+                request,
+                environment,
+                names,
+                ..
+            } => {
+                let names = names
+                    .iter()
+                    .map(|name| format!("{{{{{name}}}}}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match environment {
+                    Some(environment) => write!(
+                        f,
+                        "{collection} › {request}: {names} not set on {environment}"
+                    ),
+                    None => write!(f, "{collection} › {request}: {names} not set"),
+                }
+            }
+        }
+    }
+}
+
+/// what was found on a collections directory
+#[derive(Debug, Default)]
+pub struct Checkup {
+    /// collections that could be read
+    pub collections: Vec<Collection>,
+    pub problems: Vec<Problem>,
+}
+
+/// reads every file on `dir` the way the dashboard does, but keeps going
+/// past the ones that fail, so every problem is reported at once
+pub fn check_dir(dir: &Path) -> anyhow::Result<Checkup> {
+    let mut entries = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut checkup = Checkup::default();
+    for path in entries {
+        let file = std::fs::read_to_string(&path)?;
+        match serde_json::from_str::<Collection>(&file) {
+            Ok(mut collection) => {
+                collection.path = path;
+                checkup.collections.push(collection);
+            }
+            Err(e) => checkup.problems.push(Problem::Unparsable {
+                file: path,
+                line: e.line(),
+                column: e.column(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    checkup
+        .problems
+        .extend(duplicate_names(&checkup.collections));
+    for collection in checkup.collections.iter() {
+        checkup.problems.extend(check_collection(collection));
+    }
+
+    Ok(checkup)
+}
+
+fn duplicate_names(collections: &[Collection]) -> Vec<Problem> {
+    let mut by_name: BTreeMap<&str, Vec<PathBuf>> = BTreeMap::new();
+    for collection in collections {
+        by_name
+            .entry(&collection.info.name)
+            .or_default()
+            .push(collection.path.clone());
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, files)| files.len().gt(&1))
+        .map(|(name, files)| Problem::DuplicateName {
+            name: name.to_string(),
+            files,
+        })
+        .collect()
+}
+
+/// requests of `collection` using variables that no environment defines.
+/// Every environment is checked on its own, as any of them can be the
+/// active one. Secrets count as set, their values live elsewhere, and so do
+/// the names captured by any request of the collection
+pub fn check_collection(collection: &Collection) -> Vec<Problem> {
+    let captured = collection
+        .flatten_requests()
+        .iter()
+        .flat_map(|request| {
+            let request = request.read().unwrap();
+            request
+                .captures
+                .iter()
+                .flatten()
+                .map(|capture| capture.name.clone())
+                .collect::<Vec<_>>()
+        })
+        .collect::<BTreeSet<_>>();
+
+    let mut base = collection.variables.clone().unwrap_or_default();
+    base.extend(captured.into_iter().map(|name| (name, String::default())));
+
+    let environments = match collection.environments.as_ref() {
+        Some(environments) if !environments.is_empty() => environments
+            .iter()
+            .map(|environment| {
+                let mut variables = base.clone();
+                variables.extend(environment.variables.clone());
+                variables.extend(
+                    environment
+                        .secrets
+                        .iter()
+                        .map(|name| (name.clone(), String::default())),
+                );
+                (Some(environment.name.clone()), variables)
+            })
+            .collect::<Vec<_>>(),
+        _ => vec![(None, base)],
+    };
+
+    let mut problems = vec![];
+    for request in collection.flatten_requests() {
+        let request = collection.apply_defaults(&request.read().unwrap());
+        for (environment, variables) in environments.iter() {
+            let (_, names) = variables::resolve_request_partially(&request, variables);
+            if names.is_empty() {
+                continue;
+            }
+            problems.push(Problem::UnresolvedVariables {
+                file: collection.path.clone(),
+                collection: collection.info.name.clone(),
+                request: request.name.clone(),
+                environment: environment.clone(),
+                names,
+            });
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, file: &str, contents: &str) {
+        std::fs::write(dir.join(file), contents).unwrap();
+    }
+
+    fn collection_json(name: &str, uri: &str) -> String {
+        serde_json::json!({
+            "info": { "name": name, "description": null },
+            "requests": [{
+                "id": "list",
+                "method": "GET",
+                "name": "list users",
+                "uri": uri,
+            }],
+            "variables": { "version": "v1" },
+            "environments": [
+                { "name": "local", "variables": { "host": "localhost" } },
+                { "name": "staging", "variables": {}, "secrets": ["host"] },
+                { "name": "production", "variables": {} },
+            ],
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_every_problem_is_reported_at_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "broken.json",
+            "{\n  \"info\": {\n    \"name\": 1\n",
+        );
+        write(
+            dir.path(),
+            "users.json",
+            &collection_json("users", "http://{{host}}/{{version}}/users"),
+        );
+        write(
+            dir.path(),
+            "users_copy.json",
+            &collection_json("users", "http://localhost/users"),
+        );
+
+        let checkup = check_dir(dir.path()).unwrap();
+        assert_eq!(checkup.collections.len(), 2);
+
+        let problems = checkup
+            .problems
+            .iter()
+            .map(|problem| match problem {
+                Problem::Unparsable { file, line, .. } => {
+                    format!("{} {line}", file.file_name().unwrap().to_string_lossy())
+                }
+                problem => problem.to_string(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                "broken.json 3".to_string(),
+                format!(
+                    "\"users\" is the name of {}, {}",
+                    dir.path().join("users.json").to_string_lossy(),
+                    dir.path().join("users_copy.json").to_string_lossy()
+                ),
+                "users › list users: {{host}} not set on production".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_captures_and_builtins_count_as_set() {
+        let mut json: serde_json::Value =
+            serde_json::from_str(&collection_json("users", "http://x/{{token}}/{{$uuid}}"))
+                .unwrap();
+        json["environments"] = serde_json::Value::Null;
+        json["requests"][0]["captures"] = serde_json::json!([
+            { "name": "token", "source": { "from": "header", "name": "x-token" } }
+        ]);
+        let collection = serde_json::from_value::<Collection>(json).unwrap();
+        assert_eq!(check_collection(&collection), vec![]);
+
+        let mut json: serde_json::Value =
+            serde_json::from_str(&collection_json("users", "http://x/{{token}}")).unwrap();
+        json["environments"] = serde_json::Value::Null;
+        let collection = serde_json::from_value::<Collection>(json).unwrap();
+        assert_eq!(
+            check_collection(&collection)[0].to_string(),
+            "users › list users: {{token}} not set"
+        );
+    }
+}
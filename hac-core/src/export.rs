@@ -0,0 +1,2 @@
+pub mod collection_file;
+pub mod http_file;
@@ -0,0 +1,136 @@
+use crate::collection::Collection;
+use crate::secrets::Secrets;
+
+use serde_json::Value;
+
+/// the collection as a single json file to be handed to someone else, it is
+/// read back by `import::collection_file`. Secret values are never on the
+/// collection itself, so they are only written out when `secrets` is given
+pub fn to_json(
+    collection: &Collection,
+    secrets: Option<&Secrets>,
+) -> Result<String, serde_json::Error> {
+    let mut collection = collection.clone();
+    let key = Secrets::key(&collection);
+
+    if let Some(secrets) = secrets {
+        for environment in collection.environments.iter_mut().flatten() {
+            environment.variables = secrets.variables_for(&key, environment);
+        }
+    }
+
+    let mut json = serde_json::to_value(&collection)?;
+    if let (Some(secrets), Some(requests)) = (secrets, json.get_mut("requests")) {
+        add_auth_secrets(requests, secrets, &key);
+    }
+
+    serde_json::to_string_pretty(&json)
+}
+
+/// secret auth fields are blank once serialized, requests are shared with
+/// the collection so they can't be filled before, only on the json
+fn add_auth_secrets(value: &mut Value, secrets: &Secrets, key: &str) {
+    match value {
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| add_auth_secrets(item, secrets, key)),
+        Value::Object(object) => {
+            let stored = object
+                .get("id")
+                .and_then(Value::as_str)
+                .and_then(|id| secrets.auth_for(key, id));
+            // auth methods are written as `{ "AwsSigV4": { ...fields } }`
+            let fields = object
+                .get_mut("auth_method")
+                .and_then(Value::as_object_mut)
+                .and_then(|method| method.values_mut().next())
+                .and_then(Value::as_object_mut);
+            if let (Some(stored), Some(fields)) = (stored, fields) {
+                for (name, value) in stored {
+                    fields.insert(name.clone(), Value::String(value.clone()));
+                }
+            }
+
+            if let Some(requests) = object.get_mut("requests") {
+                add_auth_secrets(requests, secrets, key);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::{AuthMethod, Directory, Environment, Info, RequestKind};
+    use crate::test_utils;
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::sync::{Arc, RwLock};
+
+    #[test]
+    fn test_secrets_are_only_written_when_asked_to() {
+        let collection = Collection {
+            info: Info {
+                name: "api".into(),
+                ..Default::default()
+            },
+            environments: Some(vec![Environment {
+                name: "prod".into(),
+                variables: BTreeMap::from([("host".into(), "https://api.example.com".into())]),
+                secrets: BTreeSet::from(["token".into()]),
+            }]),
+            ..Default::default()
+        };
+        let mut secrets = Secrets::default();
+        secrets.insert(
+            &Secrets::key(&collection),
+            "prod",
+            "token",
+            "hunter2".into(),
+        );
+
+        let without = to_json(&collection, None).unwrap();
+        assert!(without.contains("https://api.example.com"));
+        assert!(!without.contains("hunter2"));
+
+        let with = to_json(&collection, Some(&secrets)).unwrap();
+        assert!(with.contains("hunter2"));
+        // the value goes along with its name, so it is moved back to the
+        // secrets once imported
+        assert!(with.contains("\"secrets\""));
+    }
+
+    #[test]
+    fn test_secret_auth_fields_are_only_written_when_asked_to() {
+        let mut request = test_utils::make_request("digest", "/digest");
+        request.auth_method = Some(AuthMethod::Digest {
+            username: "admin".into(),
+            password: "hunter2".into(),
+        });
+        let nested = RequestKind::Nested(Directory {
+            id: "dir".into(),
+            name: "dir".into(),
+            requests: Arc::new(RwLock::new(vec![RequestKind::Single(Arc::new(
+                RwLock::new(request),
+            ))])),
+        });
+        let mut collection = test_utils::make_collection("api", vec![]);
+        collection.requests = Some(Arc::new(RwLock::new(vec![nested])));
+        let mut secrets = Secrets::default();
+        secrets.take_from(&mut collection);
+
+        let without = to_json(&collection, None).unwrap();
+        assert!(without.contains("admin"));
+        assert!(!without.contains("hunter2"));
+
+        let with = to_json(&collection, Some(&secrets)).unwrap();
+        let imported: Collection = serde_json::from_str(&with).unwrap();
+        let request = imported.flatten_requests()[0].read().unwrap().clone();
+        assert_eq!(
+            request
+                .auth_method
+                .and_then(|auth| auth.get("password").map(String::from)),
+            Some("hunter2".into())
+        );
+    }
+}
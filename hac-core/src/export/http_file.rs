@@ -0,0 +1,170 @@
+use crate::collection::types::{Collection, Environment, Request};
+use crate::import::http_file::{RENAMED_BUILTINS, SEPARATOR};
+use crate::variables;
+
+/// writes every request on the collection as a `.http` file, directories
+/// are flattened in the order the requests were authored, with the
+/// collection base url and default headers applied. Variables of
+/// `environment` used by the requests are written as file variables, so the
+/// file works on its own
+pub fn collection(collection: &Collection, environment: Option<&Environment>) -> String {
+    let requests = collection
+        .flatten_requests()
+        .iter()
+        .map(|request| collection.apply_defaults(&request.read().unwrap()))
+        .collect::<Vec<_>>();
+    write(&requests, environment)
+}
+
+/// writes a single request as a `.http` file
+pub fn request(request: &Request, environment: Option<&Environment>) -> String {
+    write(std::slice::from_ref(request), environment)
+}
+
+fn write(requests: &[Request], environment: Option<&Environment>) -> String {
+    let mut content = String::new();
+
+    if let Some(environment) = environment {
+        let used = requests
+            .iter()
+            .flat_map(|request| variables::placeholders(&request_text(request)))
+            .collect::<Vec<_>>();
+        let mut wrote_any = false;
+        // secrets have no value on the environment, so they are never
+        // written out
+        for (name, value) in environment.variables.iter() {
+            if used.contains(name) {
+                content.push_str(&format!("@{name} = {value}\n"));
+                wrote_any = true;
+            }
+        }
+        if wrote_any {
+            content.push('\n');
+        }
+    }
+
+    let requests = requests.iter().map(write_request).collect::<Vec<_>>();
+    content.push_str(&requests.join("\n"));
+    content
+}
+
+fn write_request(request: &Request) -> String {
+    let mut content = format!("{SEPARATOR} {}\n", request.name);
+    content.push_str(&format!(
+        "{} {}\n",
+        request.method,
+        rename_builtins(&request.uri)
+    ));
+
+    // disabled headers are kept commented out, which is how they are read
+    // back as disabled
+    for header in request.headers.iter().flatten() {
+        let prefix = if header.enabled { "" } else { "# " };
+        content.push_str(&format!(
+            "{prefix}{}: {}\n",
+            header.pair.0,
+            rename_builtins(&header.pair.1)
+        ));
+    }
+
+    // other tools don't know about our computed headers, so they are
+    // written out like any other header
+    for (name, value) in request.computed_headers() {
+        if !request.has_header(&name) {
+            content.push_str(&format!("{name}: {value}\n"));
+        }
+    }
+
+    if let Some(body) = request.body.as_ref().filter(|body| !body.is_empty()) {
+        content.push('\n');
+        content.push_str(&rename_builtins(body));
+        content.push('\n');
+    }
+
+    content
+}
+
+/// everything on the request that may hold placeholders
+fn request_text(request: &Request) -> String {
+    let headers = request
+        .headers
+        .iter()
+        .flatten()
+        .map(|header| format!("{}: {}", header.pair.0, header.pair.1))
+        .collect::<Vec<_>>();
+    format!(
+        "{}\n{}\n{}",
+        request.uri,
+        headers.join("\n"),
+        request.body.as_deref().unwrap_or_default()
+    )
+}
+
+fn rename_builtins(text: &str) -> String {
+    RENAMED_BUILTINS
+        .iter()
+        .fold(text.to_string(), |text, (theirs, ours)| {
+            text.replace(ours, theirs)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::http_file::{self as import, ENVIRONMENT_NAME};
+
+    /// ids are generated on every import, so they are left out
+    fn without_ids(requests: &[Request]) -> Vec<Request> {
+        requests
+            .iter()
+            .map(|request| Request {
+                id: String::default(),
+                ..request.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        for fixture in [
+            include_str!("../import/fixtures/users.http"),
+            include_str!("../import/fixtures/single.http"),
+        ] {
+            let file = import::parse(fixture).unwrap();
+            let collection = import::to_collection(file, "fixture".into());
+            let environment = collection.find_environment(ENVIRONMENT_NAME);
+
+            assert_eq!(super::collection(&collection, environment), fixture);
+        }
+    }
+
+    #[test]
+    fn test_rest_client_files_survive_an_export() {
+        let file = import::parse(include_str!("../import/fixtures/rest_client.http")).unwrap();
+        let collection = import::to_collection(file, "rest client".into());
+        let environment = collection.find_environment(ENVIRONMENT_NAME);
+
+        let exported = super::collection(&collection, environment);
+        let imported = import::parse(&exported).unwrap();
+
+        let original = import::parse(include_str!("../import/fixtures/rest_client.http")).unwrap();
+        assert_eq!(imported.variables, original.variables);
+        assert_eq!(
+            without_ids(&imported.requests),
+            without_ids(&original.requests)
+        );
+    }
+
+    #[test]
+    fn test_single_request_only_writes_the_variables_it_uses() {
+        let file = import::parse(include_str!("../import/fixtures/users.http")).unwrap();
+        let collection = import::to_collection(file, "users".into());
+        let environment = collection.find_environment(ENVIRONMENT_NAME);
+        let request = collection.find_request("Health").unwrap();
+
+        assert_eq!(
+            super::request(&request.read().unwrap(), environment),
+            "@host = https://api.example.com\n\n### Health\nGET {{host}}/health\n"
+        );
+    }
+}
@@ -3,6 +3,10 @@ pub enum FsError {
     SerializationError(String),
     IOError(String),
     CollectionAlreadyExists(String),
+    /// the file being imported couldn't be understood
+    ImportError(String),
+    /// the named collection can't be changed, see `Collection::is_read_only`
+    ReadOnly(String),
     Unknown,
 }
 
@@ -15,6 +19,12 @@ impl std::fmt::Display for FsError {
             FsError::Unknown => write!(f, "unknown error"),
             FsError::SerializationError(msg) => write!(f, "{}", msg),
             FsError::IOError(msg) => write!(f, "{}", msg),
+            FsError::ImportError(msg) => write!(f, "{}", msg),
+            FsError::ReadOnly(name) => {
+                write!(f, "{} is read-only, duplicate it to make changes", name)
+            }
         }
     }
 }
+
+impl std::error::Error for FsError {}
@@ -1,7 +1,15 @@
-use crate::collection::{collection::create_from_form, Collection};
+use crate::collection::collection::{create_from_form, path_for};
+use crate::collection::Collection;
+use crate::export::collection_file as export_file;
 use crate::fs::error::FsError;
+use crate::import::{self, collection_file, http_file, insomnia, remote, ImportFormat};
+use crate::net::request_client::ClientOptions;
+use crate::secrets::Secrets;
 
-use std::path::Path;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[tracing::instrument(err, skip_all)]
 pub async fn delete_collection<P>(path: P) -> anyhow::Result<(), FsError>
@@ -24,7 +32,250 @@ pub async fn create_collection(
     dry_run: bool,
 ) -> anyhow::Result<Collection, FsError> {
     let collection = create_from_form(name, description);
+    write_new_collection(collection, dry_run).await
+}
+
+/// what an import created, along with whatever on the file couldn't be
+/// brought over
+#[derive(Debug, Default)]
+pub struct Imported {
+    pub collections: Vec<Collection>,
+    /// descriptions of what was left out, eg: `cookie jar "Default Jar"`
+    pub skipped: Vec<String>,
+}
+
+/// creates new collections from a file, either one written by
+/// `export_collection`, a `.http` file, which is named after the file, or an
+/// insomnia export, which has a collection for each of its workspaces. Which
+/// one it is is told by the content of the file.
+///
+/// Names already taken get an `(imported)` suffix instead of overwriting the
+/// existing collection. Secret values on the file are written as they are,
+/// `Secrets::take_from` moves them out
+#[tracing::instrument(err)]
+pub async fn import_collections(path: PathBuf, dry_run: bool) -> anyhow::Result<Imported, FsError> {
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| FsError::IOError(format!("failed to read {:?}: {}", path, e)))?;
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let imported = parse_import(&content, name)
+        .map_err(|e| FsError::ImportError(format!("failed to import {:?}: {}", path, e)))?;
+    write_imported(imported, dry_run).await
+}
+
+/// like `import_collections`, with the file fetched from `url`. The url is
+/// kept on the collections, so they can be fetched again later
+#[tracing::instrument(err, skip(options))]
+pub async fn import_from_url(
+    url: &str,
+    options: &ClientOptions,
+    dry_run: bool,
+) -> anyhow::Result<Imported, FsError> {
+    let imported = fetch_import(url, options).await?;
+    write_imported(imported, dry_run).await
+}
+
+/// fetches the collection again from the url it was imported from. With
+/// `replace` it is written over `collection`, keeping its name and file,
+/// otherwise it becomes a new collection next to it
+#[tracing::instrument(err, skip_all)]
+pub async fn reimport_collection(
+    collection: &Collection,
+    options: &ClientOptions,
+    replace: bool,
+    dry_run: bool,
+) -> anyhow::Result<Collection, FsError> {
+    let Some(url) = collection.info.source.as_deref() else {
+        return Err(FsError::ImportError(format!(
+            "{} was not imported from a url",
+            collection.info.name
+        )));
+    };
+    if replace && collection.is_read_only() {
+        return Err(FsError::ReadOnly(collection.info.name.clone()));
+    }
+
+    let imported = fetch_import(url, options).await?;
+    // an insomnia export may hold more than one workspace, the collection
+    // came from the one sharing its name, unless it was renamed since
+    let position = imported
+        .collections
+        .iter()
+        .position(|fresh| fresh.info.name.eq(&collection.info.name))
+        .unwrap_or_default();
+    let Some(mut fresh) = imported.collections.into_iter().nth(position) else {
+        return Err(FsError::ImportError(format!(
+            "{url} has no collection on it"
+        )));
+    };
+
+    if !replace {
+        fresh.info.name = unique_name(&collection.info.name);
+        fresh.path = path_for(&fresh.info.name);
+        return write_new_collection(fresh, dry_run).await;
+    }
+
+    fresh.info.name = collection.info.name.clone();
+    fresh.path = collection.path.clone();
+    if !dry_run {
+        sync_collection(fresh.clone()).await?;
+    }
+    Ok(fresh)
+}
+
+async fn fetch_import(url: &str, options: &ClientOptions) -> anyhow::Result<Imported, FsError> {
+    let content = remote::fetch(url, options)
+        .await
+        .map_err(FsError::ImportError)?;
+    let mut imported = parse_import(&content, remote::name_from_url(url))
+        .map_err(|e| FsError::ImportError(format!("failed to import {url}: {e}")))?;
+    for collection in imported.collections.iter_mut() {
+        collection.info.source = Some(url.trim().to_string());
+    }
+
+    Ok(imported)
+}
+
+/// reads the collections out of `content`, whichever format it is in.
+/// `name` is given to the ones that carry no name of their own
+fn parse_import(content: &str, name: String) -> Result<Imported, String> {
+    let imported = match import::detect_format(content) {
+        ImportFormat::HttpFile => {
+            let file = http_file::parse(content).map_err(|e| e.to_string())?;
+            Imported {
+                collections: vec![http_file::to_collection(file, name)],
+                skipped: vec![],
+            }
+        }
+        ImportFormat::Insomnia => {
+            let export = insomnia::parse(content).map_err(|e| e.to_string())?;
+            Imported {
+                collections: export.collections,
+                skipped: export.skipped,
+            }
+        }
+        ImportFormat::Collection => Imported {
+            collections: vec![collection_file::parse(content).map_err(|e| e.to_string())?],
+            skipped: vec![],
+        },
+    };
+
+    Ok(imported)
+}
+
+/// writes every imported collection as a new one, under a name that isn't
+/// taken yet
+async fn write_imported(imported: Imported, dry_run: bool) -> anyhow::Result<Imported, FsError> {
+    let mut written = vec![];
+    for mut collection in imported.collections {
+        collection.info.name = unique_name(&collection.info.name);
+        collection.path = path_for(&collection.info.name);
+        written.push(write_new_collection(collection, dry_run).await?);
+    }
+
+    Ok(Imported {
+        collections: written,
+        skipped: imported.skipped,
+    })
+}
+
+/// writes `collection` to `path` as a single file that can be imported back,
+/// secret values are only written when `include_secrets` is set
+#[tracing::instrument(err, skip(collection))]
+pub fn export_collection(
+    collection: &Collection,
+    path: &Path,
+    include_secrets: bool,
+) -> anyhow::Result<(), FsError> {
+    let secrets = include_secrets.then(|| Secrets::load(hac_config::secrets_file()));
+    let content = export_file::to_json(collection, secrets.as_ref())
+        .map_err(|e| FsError::SerializationError(e.to_string()))?;
+
+    std::fs::write(path, content)
+        .map_err(|e| FsError::IOError(format!("failed to export to {:?}: {}", path, e)))?;
+
+    tracing::debug!("successfully exported collection to: {:?}", path);
+    Ok(())
+}
+
+/// exports every collection to a file of its own under `dir`, named after
+/// the collection. Files already there are kept, a counter is added to the
+/// name instead. Returns the files that were written
+pub fn export_collections(
+    collections: &[&Collection],
+    dir: &Path,
+    include_secrets: bool,
+) -> anyhow::Result<Vec<PathBuf>, FsError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| FsError::IOError(format!("failed to create {:?}: {}", dir, e)))?;
+
+    let mut written: Vec<PathBuf> = vec![];
+    for collection in collections {
+        let stem = collection.info.name.replace(std::path::is_separator, "-");
+        let mut path = dir.join(format!("{stem}.json"));
+        // two collections can share a name, or an earlier export can be
+        // there, and neither should be replaced
+        let mut count = 2;
+        while path.exists() {
+            path = dir.join(format!("{stem}-{count}.json"));
+            count += 1;
+        }
+
+        export_collection(collection, &path, include_secrets)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// `name`, or `name (imported)` when a collection already uses it, with a
+/// counter added once that is taken as well
+fn unique_name(name: &str) -> String {
+    match path_for(name).exists() {
+        true => suffixed_name(name, "imported"),
+        false => name.to_string(),
+    }
+}
+
+/// `name (suffix)`, with a counter added while a collection uses it
+fn suffixed_name(name: &str, suffix: &str) -> String {
+    let mut candidate = format!("{name} ({suffix})");
+    let mut count = 2;
+    while path_for(&candidate).exists() {
+        candidate = format!("{name} ({suffix} {count})");
+        count += 1;
+    }
+    candidate
+}
+
+/// a copy of `collection` on the collections directory that can be
+/// changed, the way to edit a read-only collection without touching the
+/// original
+#[tracing::instrument(err, skip(collection))]
+pub async fn duplicate_collection(
+    collection: &Collection,
+    dry_run: bool,
+) -> anyhow::Result<Collection, FsError> {
+    // requests are shared between clones, so the copy is made out of what
+    // would be written instead
+    let contents = collection_contents(collection)?;
+    let mut copy: Collection =
+        serde_json::from_str(&contents).map_err(|e| FsError::SerializationError(e.to_string()))?;
 
+    copy.read_only = false;
+    copy.info.name = suffixed_name(&collection.info.name, "copy");
+    copy.path = path_for(&copy.info.name);
+    write_new_collection(copy, dry_run).await
+}
+
+async fn write_new_collection(
+    collection: Collection,
+    dry_run: bool,
+) -> anyhow::Result<Collection, FsError> {
     if collection.path.exists() {
         return Err(FsError::CollectionAlreadyExists(
             collection.path.to_string_lossy().to_string(),
@@ -34,11 +285,10 @@ pub async fn create_collection(
     let serialized_collection = serde_json::to_string(&collection)
         .map_err(|e| FsError::SerializationError(e.to_string()))?;
 
-    // if we are on a dry_run, we skip syncing
+    // if we are on a dry_run, we skip syncing. Imports can be cancelled
+    // midway, which must never leave half a collection behind
     if !dry_run {
-        tokio::fs::write(&collection.path, serialized_collection)
-            .await
-            .map_err(|e| FsError::IOError(format!("failed to write collection: {:?}", e)))?;
+        write_atomically(&collection.path, serialized_collection).await?;
     }
 
     tracing::debug!("successfully created new collection: {:?}", collection.path);
@@ -46,19 +296,363 @@ pub async fn create_collection(
 }
 
 pub async fn sync_collection(collection: Collection) -> anyhow::Result<(), FsError> {
-    let collection_str = serde_json::to_string(&collection)
-        .map_err(|e| FsError::SerializationError(e.to_string()))?;
+    let contents = collection_contents(&collection)?;
+    write_atomically(&collection.path, contents).await?;
+    tracing::debug!("synchronization of collection: {:?}", collection.path);
 
-    tokio::fs::write(&collection.path, collection_str)
-        .await
-        .map_err(|_| {
-            FsError::IOError(format!(
-                "failed to synchronize collection {:?}",
-                collection.path
-            ))
-        })?;
+    Ok(())
+}
 
-    tracing::debug!("synchronization of collection: {:?}", collection.path);
+/// the collection as it is written to its file
+pub fn collection_contents(collection: &Collection) -> anyhow::Result<String, FsError> {
+    serde_json::to_string(collection).map_err(|e| FsError::SerializationError(e.to_string()))
+}
+
+/// a fingerprint of a file's contents, to tell whether it was changed by
+/// something else since we last read or wrote it
+pub fn stamp(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// the stamp of the file at `path`, `None` when it can't be read
+pub fn file_stamp(path: &Path) -> Option<u64> {
+    std::fs::read(path).ok().map(|contents| stamp(&contents))
+}
+
+/// writes to a file next to `path` and renames it over, so the file is
+/// never left half written
+pub async fn write_atomically(path: &Path, contents: String) -> anyhow::Result<(), FsError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.tmp"));
+    let io_error = |e: std::io::Error| {
+        FsError::IOError(format!(
+            "failed to synchronize collection {:?}: {}",
+            path, e
+        ))
+    };
+
+    tokio::fs::write(&temp, contents).await.map_err(io_error)?;
+    if let Err(e) = tokio::fs::rename(&temp, path).await {
+        _ = tokio::fs::remove_file(&temp).await;
+        return Err(io_error(e));
+    }
+
+    Ok(())
+}
+
+/// blocking take on `write_atomically` for the small files saved next to
+/// the collections. `private` files are only ever readable by their owner,
+/// the temporary file is created that way before anything is written to it
+pub fn replace_file(path: &Path, contents: &[u8], private: bool) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.tmp"));
+    // a leftover from a crash could have been created with other permissions
+    _ = std::fs::remove_file(&temp);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+
+    let written = options.open(&temp).and_then(|mut file| {
+        std::io::Write::write_all(&mut file, contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| std::fs::rename(&temp, path)) {
+        _ = std::fs::remove_file(&temp);
+        return Err(e);
+    }
 
     Ok(())
 }
+
+/// copies the file at `path` into `trash_dir`, named after the file and when
+/// it was moved there. Returns where the copy went
+pub async fn backup_to_trash(path: &Path, trash_dir: &Path) -> anyhow::Result<PathBuf, FsError> {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+
+    let mut backup = trash_dir.join(format!("{stem}-{secs}.json"));
+    let mut count = 2;
+    while backup.exists() {
+        backup = trash_dir.join(format!("{stem}-{secs}-{count}.json"));
+        count += 1;
+    }
+
+    tokio::fs::create_dir_all(trash_dir)
+        .await
+        .map_err(|e| FsError::IOError(format!("failed to create {:?}: {}", trash_dir, e)))?;
+    tokio::fs::copy(path, &backup)
+        .await
+        .map_err(|e| FsError::IOError(format!("failed to back up {:?}: {}", path, e)))?;
+
+    tracing::debug!("backed up {:?} to {:?}", path, backup);
+    Ok(backup)
+}
+
+/// deletes the collection at `path`, keeping a copy of it on `trash_dir` so
+/// it can still be brought back by hand
+pub async fn trash_collection(path: &Path, trash_dir: &Path) -> anyhow::Result<(), FsError> {
+    backup_to_trash(path, trash_dir).await?;
+    delete_collection(path).await
+}
+
+/// the collection renamed as a conflicted copy, on a file next to its own
+/// that isn't taken yet
+pub fn conflicted_copy(collection: &Collection) -> Collection {
+    let mut copy = collection.clone();
+    let dir = collection
+        .path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let path_of = |name: &str| dir.join(format!("{}.json", name.to_lowercase().replace(' ', "_")));
+
+    let mut name = format!("{} (conflicted copy)", collection.info.name);
+    let mut count = 2;
+    while path_of(&name).exists() {
+        name = format!("{} (conflicted copy {count})", collection.info.name);
+        count += 1;
+    }
+
+    copy.path = path_of(&name);
+    copy.info.name = name;
+    copy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_collection(path: PathBuf) -> Collection {
+        let mut collection: Collection = serde_json::from_value(serde_json::json!({
+            "info": { "name": "My Api" },
+            "requests": [],
+        }))
+        .unwrap();
+        collection.path = path;
+        collection
+    }
+
+    #[tokio::test]
+    async fn test_writes_replace_the_whole_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("my_api.json");
+        std::fs::write(&path, "a much longer content than what replaces it").unwrap();
+
+        write_atomically(&path, "{}".into()).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        assert_eq!(file_stamp(&path), Some(stamp(b"{}")));
+        // nothing is left behind but the file itself
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert_eq!(file_stamp(&dir.path().join("missing.json")), None);
+    }
+
+    #[test]
+    fn test_replacing_files_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("secrets.json");
+
+        replace_file(&path, b"first", true).unwrap();
+        replace_file(&path, b"second", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backups_never_replace_each_other() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path().join("trash");
+        let path = dir.path().join("my_api.json");
+        std::fs::write(&path, "first").unwrap();
+
+        let first = backup_to_trash(&path, &trash).await.unwrap();
+        std::fs::write(&path, "second").unwrap();
+        let second = backup_to_trash(&path, &trash).await.unwrap();
+
+        assert_ne!(first, second);
+        assert!(first
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("my_api-"));
+        assert_eq!(std::fs::read_to_string(first).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(second).unwrap(), "second");
+    }
+
+    #[tokio::test]
+    async fn test_trashed_collections_are_kept_on_the_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path().join("trash");
+        let path = dir.path().join("my_api.json");
+        std::fs::write(&path, "{}").unwrap();
+
+        trash_collection(&path, &trash).await.unwrap();
+        assert!(!path.exists());
+        let trashed = std::fs::read_dir(&trash)
+            .unwrap()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(std::fs::read_to_string(trashed[0].path()).unwrap(), "{}");
+
+        // nothing is deleted when there is nothing to keep
+        assert!(trash_collection(&path, &trash).await.is_err());
+    }
+
+    #[test]
+    fn test_exporting_many_collections_to_a_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let export_dir = dir.path().join("shared");
+        let first = make_collection(dir.path().join("my_api.json"));
+        let mut second = make_collection(dir.path().join("my_api_2.json"));
+        second.info.name = "client/v2".into();
+
+        let written = export_collections(&[&first, &second, &first], &export_dir, false).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                export_dir.join("My Api.json"),
+                export_dir.join("client-v2.json"),
+                export_dir.join("My Api-2.json"),
+            ]
+        );
+        assert!(written.iter().all(|path| path.exists()));
+
+        std::fs::write(export_dir.join("client-v2.json"), "kept").unwrap();
+        let written = export_collections(&[&second], &export_dir, false).unwrap();
+        assert_eq!(written, vec![export_dir.join("client-v2-2.json")]);
+        assert_eq!(
+            std::fs::read_to_string(export_dir.join("client-v2.json")).unwrap(),
+            "kept"
+        );
+    }
+
+    /// answers every request with `body`, like a collection shared on a url
+    fn serve(body: &'static str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0; 1024];
+                _ = stream.read(&mut buf).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{addr}/shared/users.http")
+    }
+
+    #[tokio::test]
+    async fn test_reimporting_from_the_source() {
+        let url = serve("### list users\nGET http://a.dev/users\n");
+        let dir = tempfile::tempdir().unwrap();
+        let mut collection = make_collection(dir.path().join("my_api.json"));
+        let options = ClientOptions::default();
+
+        let error = reimport_collection(&collection, &options, true, true)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("was not imported from a url"));
+
+        collection.info.source = Some(url.clone());
+        let replaced = reimport_collection(&collection, &options, true, false)
+            .await
+            .unwrap();
+        assert_eq!(replaced.info.name, "My Api");
+        assert_eq!(replaced.path, collection.path);
+        assert_eq!(replaced.info.source.as_deref(), Some(url.as_str()));
+        assert_eq!(replaced.flatten_requests().len(), 1);
+        let written = std::fs::read_to_string(&collection.path).unwrap();
+        assert!(written.contains("list users"));
+
+        let copy = reimport_collection(&collection, &options, false, true)
+            .await
+            .unwrap();
+        assert!(copy.info.name.starts_with("My Api"));
+        assert_ne!(copy.path, collection.path);
+        assert_eq!(copy.info.source.as_deref(), Some(url.as_str()));
+
+        collection.read_only = true;
+        let error = reimport_collection(&collection, &options, true, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, FsError::ReadOnly(_)));
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_can_be_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut collection: Collection = serde_json::from_value(serde_json::json!({
+            "info": { "name": "Shared Api" },
+            "requests": [{ "id": "me", "method": "GET", "name": "me", "uri": "/me" }],
+            "read_only": true,
+        }))
+        .unwrap();
+        collection.path = dir.path().join("shared_api.json");
+        assert!(collection.is_read_only());
+
+        let copy = duplicate_collection(&collection, true).await.unwrap();
+        assert!(!copy.is_read_only());
+        assert!(copy.info.name.starts_with("Shared Api (copy"));
+        assert_ne!(copy.path, collection.path);
+        assert!(!collection_contents(&copy).unwrap().contains("read_only"));
+
+        copy.flatten_requests()[0].write().unwrap().name = String::from("changed");
+        assert_eq!(collection.flatten_requests()[0].read().unwrap().name, "me");
+    }
+
+    #[test]
+    fn test_conflicted_copies_are_named_after_the_collection() {
+        let dir = tempfile::tempdir().unwrap();
+        let collection = make_collection(dir.path().join("my_api.json"));
+
+        let copy = conflicted_copy(&collection);
+        assert_eq!(copy.info.name, "My Api (conflicted copy)");
+        assert_eq!(copy.path, dir.path().join("my_api_(conflicted_copy).json"));
+
+        std::fs::write(&copy.path, "").unwrap();
+        let copy = conflicted_copy(&collection);
+        assert_eq!(copy.info.name, "My Api (conflicted copy 2)");
+        assert_eq!(
+            copy.path,
+            dir.path().join("my_api_(conflicted_copy_2).json")
+        );
+    }
+}
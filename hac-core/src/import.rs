@@ -0,0 +1,52 @@
+pub mod collection_file;
+pub mod http_file;
+pub mod insomnia;
+pub mod remote;
+
+/// the kinds of files collections can be imported from
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImportFormat {
+    /// written by `export::collection_file`
+    Collection,
+    /// a `.http` file, as written for REST Client
+    HttpFile,
+    /// an export of insomnia, version 4
+    Insomnia,
+}
+
+/// tells which kind of file `content` is by looking at it, so users never
+/// have to say where the file came from. Anything that isn't json is read
+/// as a `.http` file
+pub fn detect_format(content: &str) -> ImportFormat {
+    if !content.trim_start().starts_with('{') {
+        return ImportFormat::HttpFile;
+    }
+
+    match insomnia::is_export(content) {
+        true => ImportFormat::Insomnia,
+        false => ImportFormat::Collection,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_are_detected_by_content() {
+        assert_eq!(
+            detect_format(include_str!("import/fixtures/users.http")),
+            ImportFormat::HttpFile
+        );
+        assert_eq!(
+            detect_format(include_str!("import/fixtures/insomnia_nested.json")),
+            ImportFormat::Insomnia
+        );
+        assert_eq!(
+            detect_format("\n  { \"info\": { \"name\": \"api\" } }"),
+            ImportFormat::Collection
+        );
+        // broken json is still reported as a broken collection
+        assert_eq!(detect_format("{ \"info\": "), ImportFormat::Collection);
+    }
+}
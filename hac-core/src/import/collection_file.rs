@@ -0,0 +1,83 @@
+use crate::collection::types::RequestKind;
+use crate::collection::Collection;
+
+/// reads a collection written by `export::collection_file`. Every request and
+/// directory gets a new id, so importing a collection twice, or next to the
+/// one it was exported from, never mixes them up
+pub fn parse(content: &str) -> Result<Collection, serde_json::Error> {
+    let collection: Collection = serde_json::from_str(content)?;
+    if let Some(items) = collection.requests.as_ref() {
+        regenerate_ids(&mut items.write().unwrap(), None);
+    }
+    Ok(collection)
+}
+
+fn regenerate_ids(items: &mut [RequestKind], parent: Option<&str>) {
+    for item in items.iter_mut() {
+        match item {
+            RequestKind::Single(request) => {
+                let mut request = request.write().unwrap();
+                request.id = uuid::Uuid::new_v4().to_string();
+                request.parent = parent.map(String::from);
+            }
+            RequestKind::Nested(dir) => {
+                dir.id = uuid::Uuid::new_v4().to_string();
+                regenerate_ids(&mut dir.requests.write().unwrap(), Some(&dir.id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::collection_file::to_json;
+
+    const COLLECTION: &str = r#"{
+        "info": { "name": "api", "description": "shared" },
+        "requests": [
+            { "id": "a", "method": "GET", "name": "health", "uri": "/health" },
+            {
+                "id": "dir",
+                "name": "users",
+                "requests": [
+                    { "id": "b", "method": "POST", "name": "create", "uri": "/users", "parent": "dir" }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_ids_are_regenerated_and_parents_follow() {
+        let collection = parse(COLLECTION).unwrap();
+        let items = collection.requests.as_ref().unwrap().read().unwrap();
+
+        let RequestKind::Single(health) = &items[0] else {
+            panic!("expected a request");
+        };
+        assert_ne!(health.read().unwrap().id, "a");
+        assert_eq!(health.read().unwrap().parent, None);
+
+        let RequestKind::Nested(dir) = &items[1] else {
+            panic!("expected a directory");
+        };
+        assert_ne!(dir.id, "dir");
+        let create = dir.flatten_requests()[0].read().unwrap().clone();
+        assert_ne!(create.id, "b");
+        assert_eq!(create.parent.as_deref(), Some(dir.id.as_str()));
+    }
+
+    #[test]
+    fn test_exported_collections_round_trip() {
+        let collection = parse(COLLECTION).unwrap();
+        let imported = parse(&to_json(&collection, None).unwrap()).unwrap();
+
+        assert_eq!(imported.info.description.as_deref(), Some("shared"));
+        let names = imported
+            .flatten_requests()
+            .iter()
+            .map(|request| request.read().unwrap().name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["health", "create"]);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::collection::collection::create_from_form;
+use crate::collection::types::{
+    BodyType, Collection, Environment, HeaderMap, Request, RequestKind, RequestMethod,
+};
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// requests on a file are separated by lines starting with this, anything
+/// after it on the line names the request
+pub const SEPARATOR: &str = "###";
+/// environment created for the file variables of an imported file
+pub const ENVIRONMENT_NAME: &str = "default";
+/// builtins REST Client writes differently from us, as `(theirs, ours)`
+pub const RENAMED_BUILTINS: &[(&str, &str)] = &[
+    ("{{$guid}}", "{{$uuid}}"),
+    ("{{$datetime iso8601}}", "{{$isoTimestamp}}"),
+];
+
+/// requests read from a `.http` file, as written for the REST Client
+/// extension of vscode and the http client of jetbrains editors
+#[derive(Debug, Default, PartialEq)]
+pub struct HttpFile {
+    /// file variables, written as `@name = value`. They are used the same
+    /// way environment variables are, so they become one
+    pub variables: BTreeMap<String, String>,
+    pub requests: Vec<Request>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HttpFileError {
+    /// lines are counted from 1, like editors do
+    InvalidLine {
+        line: usize,
+        reason: String,
+    },
+    NoRequests,
+}
+
+impl std::fmt::Display for HttpFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpFileError::InvalidLine { line, reason } => write!(f, "line {line}: {reason}"),
+            HttpFileError::NoRequests => f.write_str("the file has no requests"),
+        }
+    }
+}
+
+impl std::error::Error for HttpFileError {}
+
+/// where the parser is on the request being read
+#[derive(Debug, PartialEq)]
+enum Section {
+    /// before the request line, only comments and variables are expected
+    Preamble,
+    Headers,
+    Body,
+}
+
+/// a request being read, turned into a `Request` once its block ends
+#[derive(Debug, Default)]
+struct Draft {
+    title: Option<String>,
+    name: Option<String>,
+    method: Option<RequestMethod>,
+    uri: String,
+    headers: Vec<HeaderMap>,
+    body: Vec<String>,
+}
+
+impl Draft {
+    fn into_request(self) -> Option<Request> {
+        let method = self.method?;
+        let body = self.body.join("\n").trim_end().to_string();
+        let name = self
+            .name
+            .or(self.title)
+            .unwrap_or_else(|| format!("{} {}", method, self.uri));
+
+        Some(Request {
+            id: uuid::Uuid::new_v4().to_string(),
+            method,
+            name,
+            uri: rename_builtins(&self.uri),
+            headers: (!self.headers.is_empty()).then(|| {
+                self.headers
+                    .into_iter()
+                    .map(|header| HeaderMap {
+                        pair: (header.pair.0, rename_builtins(&header.pair.1)),
+                        enabled: header.enabled,
+                    })
+                    .collect()
+            }),
+            body_type: (!body.is_empty()).then_some(BodyType::Json),
+            body: (!body.is_empty()).then(|| rename_builtins(&body)),
+            ..Default::default()
+        })
+    }
+}
+
+/// reads every request on `content`. Comments, `# @name` annotations and
+/// file variables are understood, and `{{placeholders}}` are kept as they
+/// are so they resolve against our variables. Headers commented out with
+/// `#` right below the request line are imported as disabled headers
+pub fn parse(content: &str) -> Result<HttpFile, HttpFileError> {
+    let mut file = HttpFile::default();
+    let mut draft = Draft::default();
+    let mut section = Section::Preamble;
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+
+        if let Some(title) = trimmed.strip_prefix(SEPARATOR) {
+            file.requests
+                .extend(std::mem::take(&mut draft).into_request());
+            let title = title.trim_start_matches('#').trim();
+            draft.title = (!title.is_empty()).then(|| title.to_string());
+            section = Section::Preamble;
+            continue;
+        }
+
+        match section {
+            Section::Preamble => {
+                if trimmed.is_empty() {
+                    continue;
+                }
+
+                if let Some(comment) = comment(trimmed) {
+                    if let Some(name) = comment.strip_prefix("@name") {
+                        draft.name = Some(name.trim().to_string());
+                    }
+                    continue;
+                }
+
+                if let Some((name, value)) = file_variable(trimmed) {
+                    file.variables.insert(name, value);
+                    continue;
+                }
+
+                let (method, uri) =
+                    request_line(trimmed).map_err(|reason| HttpFileError::InvalidLine {
+                        line: idx + 1,
+                        reason,
+                    })?;
+                draft.method = Some(method);
+                draft.uri = uri;
+                section = Section::Headers;
+            }
+            Section::Headers => {
+                if trimmed.is_empty() {
+                    section = Section::Body;
+                    continue;
+                }
+
+                // long query strings may be split into lines starting with
+                // `?` or `&` right after the request line
+                if draft.headers.is_empty() && trimmed.starts_with(['?', '&']) {
+                    draft.uri.push_str(trimmed);
+                    continue;
+                }
+
+                let (enabled, header) = match comment(trimmed) {
+                    Some(header) => (false, header),
+                    None => (true, trimmed),
+                };
+                match header.split_once(':') {
+                    Some((name, value)) if !name.trim().is_empty() => {
+                        draft.headers.push(HeaderMap {
+                            pair: (name.trim().to_string(), value.trim().to_string()),
+                            enabled,
+                        })
+                    }
+                    // any other comment is just a comment
+                    _ if !enabled => {}
+                    _ => {
+                        return Err(HttpFileError::InvalidLine {
+                            line: idx + 1,
+                            reason: format!("expected a header like `Name: value`, got {line:?}"),
+                        })
+                    }
+                }
+            }
+            Section::Body => draft.body.push(line.to_string()),
+        }
+    }
+    file.requests.extend(draft.into_request());
+
+    match file.requests.is_empty() {
+        true => Err(HttpFileError::NoRequests),
+        false => Ok(file),
+    }
+}
+
+/// a new collection named `name` holding the requests of the file, its
+/// variables end up on an environment
+pub fn to_collection(file: HttpFile, name: String) -> Collection {
+    let mut collection = create_from_form(name, String::from("imported from a .http file"));
+    collection.requests = Some(Arc::new(RwLock::new(
+        file.requests
+            .into_iter()
+            .map(|request| RequestKind::Single(Arc::new(RwLock::new(request))))
+            .collect(),
+    )));
+
+    if !file.variables.is_empty() {
+        collection.environments = Some(vec![Environment {
+            name: ENVIRONMENT_NAME.into(),
+            variables: file.variables,
+            secrets: Default::default(),
+        }]);
+    }
+
+    collection
+}
+
+fn comment(line: &str) -> Option<&str> {
+    line.strip_prefix('#')
+        .or_else(|| line.strip_prefix("//"))
+        .map(str::trim)
+}
+
+fn file_variable(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.strip_prefix('@')?.split_once('=')?;
+    Some((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// `METHOD url HTTP/1.1`, where both the method and the version are
+/// optional. Without a method the request is a `GET`
+fn request_line(line: &str) -> Result<(RequestMethod, String), String> {
+    let mut parts = line.split_whitespace().collect::<Vec<_>>();
+    if parts.len().gt(&1) && parts.last().is_some_and(|part| part.starts_with("HTTP/")) {
+        parts.pop();
+    }
+
+    match parts.as_slice() {
+        [uri] => Ok((RequestMethod::Get, uri.to_string())),
+        [method, uri] => Ok((
+            method.to_uppercase().parse().map_err(|e| format!("{e}"))?,
+            uri.to_string(),
+        )),
+        _ => Err(format!(
+            "expected a request line like `GET https://example.com`, got {line:?}"
+        )),
+    }
+}
+
+fn rename_builtins(text: &str) -> String {
+    RENAMED_BUILTINS
+        .iter()
+        .fold(text.to_string(), |text, (theirs, ours)| {
+            text.replace(theirs, ours)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rest_client_file() {
+        let file = parse(include_str!("fixtures/rest_client.http")).unwrap();
+
+        assert_eq!(
+            file.variables,
+            BTreeMap::from([
+                ("host".to_string(), "https://api.example.com".to_string()),
+                ("token".to_string(), "abc123".to_string()),
+            ])
+        );
+
+        let summary = file
+            .requests
+            .iter()
+            .map(|req| (req.name.as_str(), req.method.to_string(), req.uri.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("listUsers", "GET".into(), "{{host}}/users?page=2&limit=10"),
+                ("Create a user", "POST".into(), "{{host}}/users"),
+                (
+                    "GET https://example.com/health",
+                    "GET".into(),
+                    "https://example.com/health"
+                ),
+                ("purge", "PURGE".into(), "{{host}}/cache"),
+            ]
+        );
+
+        let create = &file.requests[1];
+        assert_eq!(
+            create.headers.as_ref().unwrap(),
+            &vec![
+                HeaderMap {
+                    pair: ("Content-Type".into(), "application/json".into()),
+                    enabled: true,
+                },
+                HeaderMap {
+                    pair: ("X-Debug".into(), "1".into()),
+                    enabled: false,
+                },
+            ]
+        );
+        assert_eq!(
+            create.body.as_deref(),
+            Some("{\n  \"id\": \"{{$uuid}}\",\n  \"name\": \"hac\"\n}")
+        );
+        assert_eq!(create.body_type, Some(BodyType::Json));
+        assert_eq!(file.requests[0].body, None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("# just a comment\n"), Err(HttpFileError::NoRequests));
+        assert_eq!(
+            parse("GET https://example.com\nnot a header\n"),
+            Err(HttpFileError::InvalidLine {
+                line: 2,
+                reason: "expected a header like `Name: value`, got \"not a header\"".into()
+            })
+        );
+        assert!(parse("G(E)T https://example.com\n").is_err());
+    }
+
+    #[test]
+    fn test_to_collection_turns_variables_into_an_environment() {
+        let file = parse("@host = http://localhost\n\nGET {{host}}/users\n").unwrap();
+        let collection = to_collection(file, "users".into());
+
+        assert_eq!(collection.info.name, "users");
+        assert_eq!(collection.flatten_requests().len(), 1);
+        let environment = collection.find_environment(ENVIRONMENT_NAME).unwrap();
+        assert_eq!(
+            environment.variables.get("host").map(String::as_str),
+            Some("http://localhost")
+        );
+    }
+}
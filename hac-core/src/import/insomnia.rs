@@ -0,0 +1,676 @@
+use crate::collection::collection::create_from_form;
+use crate::collection::types::{
+    BodyType, Collection, Directory, Environment, HeaderMap, Request, RequestKind, RequestMethod,
+};
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// the only version of the export format we understand
+pub const EXPORT_FORMAT: u64 = 4;
+/// template tags of insomnia with a builtin of ours that does the same, as
+/// `(start of the tag, builtin)`
+const TRANSLATED_TAGS: &[(&str, &str)] = &[("uuid", "$uuid"), ("now 'iso-8601'", "$isoTimestamp")];
+
+/// what an insomnia export turned into, every workspace on it becomes a
+/// collection of its own
+#[derive(Debug, Default)]
+pub struct InsomniaImport {
+    pub collections: Vec<Collection>,
+    /// what on the file has no equivalent here and was left out, eg: cookie
+    /// jars or grpc requests, described so the user can recreate it by hand
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum InsomniaError {
+    Json(serde_json::Error),
+    UnsupportedFormat(u64),
+    NoWorkspaces,
+}
+
+impl std::fmt::Display for InsomniaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InsomniaError::Json(e) => write!(f, "{e}"),
+            InsomniaError::UnsupportedFormat(version) => write!(
+                f,
+                "only version {EXPORT_FORMAT} of insomnia exports is supported, got {version}"
+            ),
+            InsomniaError::NoWorkspaces => f.write_str("the export has no workspaces"),
+        }
+    }
+}
+
+impl std::error::Error for InsomniaError {}
+
+#[derive(Debug, Deserialize)]
+struct Export {
+    #[serde(rename = "__export_format")]
+    format: u64,
+    resources: Vec<Resource>,
+}
+
+/// every node of an export, which has a flat list of them pointing to their
+/// parents. Only the fields of the nodes we import are read
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Resource {
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(rename = "_type")]
+    kind: String,
+    parent_id: Option<String>,
+    #[serde(default)]
+    name: String,
+    description: Option<String>,
+    method: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    headers: Vec<Pair>,
+    #[serde(default)]
+    parameters: Vec<Pair>,
+    body: Option<Body>,
+    authentication: Option<Value>,
+    /// variables of environments
+    data: Option<serde_json::Map<String, Value>>,
+    /// variables of folders
+    environment: Option<serde_json::Map<String, Value>>,
+    meta_sort_key: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pair {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    disabled: bool,
+}
+
+impl Pair {
+    fn enabled(pairs: &[Pair]) -> impl Iterator<Item = &Pair> {
+        pairs
+            .iter()
+            .filter(|pair| !pair.disabled && !pair.name.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Body {
+    mime_type: Option<String>,
+    text: Option<String>,
+    #[serde(default)]
+    params: Vec<Pair>,
+}
+
+/// whether `content` looks like an insomnia export, without reading the
+/// whole of it
+pub fn is_export(content: &str) -> bool {
+    #[derive(Deserialize)]
+    struct Header {
+        #[serde(rename = "_type")]
+        kind: Option<String>,
+    }
+
+    serde_json::from_str::<Header>(content)
+        .is_ok_and(|header| header.kind.is_some_and(|kind| kind.eq("export")))
+}
+
+/// reads an insomnia export. Folders nested inside others are brought up to
+/// the top of the collection, named after every folder above them, as we
+/// only have a single level of directories
+pub fn parse(content: &str) -> Result<InsomniaImport, InsomniaError> {
+    let export: Export = serde_json::from_str(content).map_err(InsomniaError::Json)?;
+    if export.format.ne(&EXPORT_FORMAT) {
+        return Err(InsomniaError::UnsupportedFormat(export.format));
+    }
+
+    let mut resources = export.resources;
+    // insomnia lists nodes by their sort key, the sort is stable so nodes
+    // without one go last in the order of the file
+    resources.sort_by(|a, b| {
+        let key = |resource: &Resource| resource.meta_sort_key.unwrap_or(f64::MAX);
+        key(a).total_cmp(&key(b))
+    });
+
+    let mut import = InsomniaImport::default();
+    for workspace in resources.iter().filter(|r| r.kind.eq("workspace")) {
+        import
+            .collections
+            .push(to_collection(workspace, &resources, &mut import.skipped));
+    }
+    if import.collections.is_empty() {
+        return Err(InsomniaError::NoWorkspaces);
+    }
+
+    let imported = ["workspace", "request_group", "request", "environment"];
+    for resource in resources.iter() {
+        if !imported.contains(&resource.kind.as_str()) {
+            import.skipped.push(describe(resource));
+        }
+    }
+
+    Ok(import)
+}
+
+fn to_collection(
+    workspace: &Resource,
+    resources: &[Resource],
+    skipped: &mut Vec<String>,
+) -> Collection {
+    let description = workspace
+        .description
+        .clone()
+        .filter(|description| !description.is_empty())
+        .unwrap_or_else(|| String::from("imported from insomnia"));
+    let mut collection = create_from_form(workspace.name.clone(), description);
+
+    let mut items = vec![];
+    for child in children(resources, &workspace.id) {
+        match child.kind.as_str() {
+            "request" => items.extend(
+                to_request(child, None, skipped)
+                    .map(|request| RequestKind::Single(Arc::new(RwLock::new(request)))),
+            ),
+            "request_group" => {
+                let mut directories = vec![];
+                to_directories(child, None, resources, &mut directories, skipped);
+                items.extend(directories.into_iter().map(RequestKind::Nested));
+            }
+            _ => {}
+        }
+    }
+    collection.requests = (!items.is_empty()).then(|| Arc::new(RwLock::new(items)));
+    collection.environments = to_environments(workspace, resources);
+
+    collection
+}
+
+/// turns a folder into a directory, followed by one directory for each of
+/// the folders inside it
+fn to_directories(
+    group: &Resource,
+    parent_name: Option<&str>,
+    resources: &[Resource],
+    directories: &mut Vec<Directory>,
+    skipped: &mut Vec<String>,
+) {
+    let name = match parent_name {
+        Some(parent_name) => format!("{parent_name} / {}", group.name),
+        None => group.name.clone(),
+    };
+    if group
+        .environment
+        .as_ref()
+        .is_some_and(|vars| !vars.is_empty())
+    {
+        skipped.push(format!("variables of folder {name:?}"));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let mut requests = vec![];
+    let mut nested = vec![];
+    for child in children(resources, &group.id) {
+        match child.kind.as_str() {
+            "request" => requests.extend(
+                to_request(child, Some(&id), skipped)
+                    .map(|request| RequestKind::Single(Arc::new(RwLock::new(request)))),
+            ),
+            "request_group" => to_directories(child, Some(&name), resources, &mut nested, skipped),
+            _ => {}
+        }
+    }
+
+    directories.push(Directory {
+        id,
+        name,
+        requests: Arc::new(RwLock::new(requests)),
+    });
+    directories.extend(nested);
+}
+
+fn to_request(
+    resource: &Resource,
+    parent: Option<&str>,
+    skipped: &mut Vec<String>,
+) -> Option<Request> {
+    let name = resource.name.clone();
+    let method = resource.method.as_deref().unwrap_or("GET").to_uppercase();
+    let Ok(method) = method.parse::<RequestMethod>() else {
+        skipped.push(format!(
+            "request {name:?}, its method {method:?} is invalid"
+        ));
+        return None;
+    };
+
+    let mut tags = vec![];
+    let mut uri = translate(resource.url.as_deref().unwrap_or_default(), &mut tags);
+    let query = Pair::enabled(&resource.parameters)
+        .map(|pair| format!("{}={}", pair.name, translate(&pair.value, &mut tags)))
+        .collect::<Vec<_>>();
+    if !query.is_empty() {
+        uri.push(if uri.contains('?') { '&' } else { '?' });
+        uri.push_str(&query.join("&"));
+    }
+
+    let mut headers = resource
+        .headers
+        .iter()
+        .filter(|header| !header.name.is_empty())
+        .map(|header| HeaderMap {
+            pair: (header.name.clone(), translate(&header.value, &mut tags)),
+            enabled: !header.disabled,
+        })
+        .collect::<Vec<_>>();
+
+    match auth_header(resource, &mut tags) {
+        Ok(Some(header)) => {
+            if !headers
+                .iter()
+                .any(|h| h.pair.0.eq_ignore_ascii_case(&header.pair.0))
+            {
+                headers.push(header);
+            }
+        }
+        Ok(None) => {}
+        Err(kind) => skipped.push(format!("{kind} auth of request {name:?}")),
+    }
+
+    let body = match resource.body.as_ref() {
+        Some(body) => match body.mime_type.as_deref() {
+            Some("application/x-www-form-urlencoded") => Some(
+                Pair::enabled(&body.params)
+                    .map(|pair| format!("{}={}", pair.name, translate(&pair.value, &mut tags)))
+                    .collect::<Vec<_>>()
+                    .join("&"),
+            ),
+            Some("multipart/form-data") => {
+                skipped.push(format!("multipart body of request {name:?}"));
+                None
+            }
+            _ => match body.text.as_deref() {
+                Some(text) => Some(translate(text, &mut tags)),
+                None if body.mime_type.is_some() => {
+                    skipped.push(format!("file body of request {name:?}"));
+                    None
+                }
+                None => None,
+            },
+        },
+        None => None,
+    }
+    .filter(|body| !body.is_empty());
+
+    for tag in tags {
+        skipped.push(format!(
+            "template tag `{tag}` on request {name:?}, kept as is"
+        ));
+    }
+
+    Some(Request {
+        id: uuid::Uuid::new_v4().to_string(),
+        method,
+        name,
+        uri,
+        headers: (!headers.is_empty()).then_some(headers),
+        parent: parent.map(String::from),
+        body_type: body.as_ref().map(|_| BodyType::Json),
+        body,
+        notes: resource
+            .description
+            .clone()
+            .filter(|description| !description.is_empty()),
+        ..Default::default()
+    })
+}
+
+/// bearer auth becomes an `Authorization` header, as it is nothing more than
+/// that. Any other kind of auth is given back as an error to be reported
+fn auth_header(resource: &Resource, tags: &mut Vec<String>) -> Result<Option<HeaderMap>, String> {
+    let Some(auth) = resource.authentication.as_ref().and_then(Value::as_object) else {
+        return Ok(None);
+    };
+    let field = |name: &str| auth.get(name).and_then(Value::as_str).unwrap_or_default();
+
+    match field("type") {
+        "" | "none" => Ok(None),
+        "bearer" => {
+            let prefix = match field("prefix") {
+                "" => "Bearer",
+                prefix => prefix,
+            };
+            Ok(Some(HeaderMap {
+                pair: (
+                    String::from("Authorization"),
+                    translate(&format!("{prefix} {}", field("token")), tags),
+                ),
+                enabled: !auth
+                    .get("disabled")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            }))
+        }
+        kind => Err(kind.to_string()),
+    }
+}
+
+/// insomnia has a base environment with sub environments inheriting from
+/// it. Each sub environment becomes one of ours with the base variables
+/// merged under its own, the base one is only kept when it has no children
+fn to_environments(workspace: &Resource, resources: &[Resource]) -> Option<Vec<Environment>> {
+    let base = children(resources, &workspace.id).find(|r| r.kind.eq("environment"))?;
+    let mut base_variables = BTreeMap::new();
+    flatten_variables(base.data.as_ref(), None, &mut base_variables);
+
+    let mut environments = children(resources, &base.id)
+        .filter(|r| r.kind.eq("environment"))
+        .map(|environment| {
+            let mut variables = base_variables.clone();
+            flatten_variables(environment.data.as_ref(), None, &mut variables);
+            Environment {
+                name: environment.name.clone(),
+                variables,
+                secrets: Default::default(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if environments.is_empty() && !base_variables.is_empty() {
+        environments.push(Environment {
+            name: base.name.clone(),
+            variables: base_variables,
+            secrets: Default::default(),
+        });
+    }
+
+    (!environments.is_empty()).then_some(environments)
+}
+
+/// nested objects are reached with dots on insomnia, eg: `_.auth.token`, so
+/// their variables are named the same way
+fn flatten_variables(
+    data: Option<&serde_json::Map<String, Value>>,
+    prefix: Option<&str>,
+    variables: &mut BTreeMap<String, String>,
+) {
+    for (name, value) in data.into_iter().flatten() {
+        let name = match prefix {
+            Some(prefix) => format!("{prefix}.{name}"),
+            None => name.clone(),
+        };
+        match value {
+            Value::Object(object) => flatten_variables(Some(object), Some(&name), variables),
+            Value::String(value) => _ = variables.insert(name, translate(value, &mut vec![])),
+            Value::Null => _ = variables.insert(name, String::new()),
+            value => _ = variables.insert(name, value.to_string()),
+        }
+    }
+}
+
+/// rewrites `{{ _.name }}` placeholders as `{{name}}`, and template tags
+/// with a builtin doing the same as that builtin. Template tags we have
+/// nothing for are kept, and pushed into `unknown_tags`
+fn translate(text: &str, unknown_tags: &mut Vec<String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start..];
+
+        let (open, close) = match after {
+            _ if after.starts_with("{{") => ("{{", "}}"),
+            _ if after.starts_with("{%") => ("{%", "%}"),
+            _ => {
+                output.push('{');
+                rest = &after[1..];
+                continue;
+            }
+        };
+        let Some(end) = after[open.len()..].find(close) else {
+            output.push_str(after);
+            rest = "";
+            break;
+        };
+
+        let inner = after[open.len()..open.len() + end].trim();
+        match open {
+            "{{" => {
+                let name = inner.strip_prefix("_.").unwrap_or(inner);
+                output.push_str(&format!("{{{{{name}}}}}"));
+            }
+            _ => {
+                match TRANSLATED_TAGS
+                    .iter()
+                    .find(|(start, _)| inner.starts_with(start))
+                {
+                    Some((_, builtin)) => output.push_str(&format!("{{{{{builtin}}}}}")),
+                    None => {
+                        let original = &after[..open.len() + end + close.len()];
+                        if !unknown_tags.iter().any(|t| t.eq(original)) {
+                            unknown_tags.push(original.to_string());
+                        }
+                        output.push_str(original);
+                    }
+                }
+            }
+        }
+        rest = &after[open.len() + end + close.len()..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn children<'a>(resources: &'a [Resource], parent: &'a str) -> impl Iterator<Item = &'a Resource> {
+    resources
+        .iter()
+        .filter(move |resource| resource.parent_id.as_deref().eq(&Some(parent)))
+}
+
+/// names a node we don't import the way insomnia calls it
+fn describe(resource: &Resource) -> String {
+    let kind = match resource.kind.as_str() {
+        "cookie_jar" => "cookie jar",
+        "api_spec" => "api spec",
+        "grpc_request" => "grpc request",
+        "websocket_request" => "websocket request",
+        "unit_test_suite" => "test suite",
+        "unit_test" => "test",
+        "proto_file" => "proto file",
+        kind => kind,
+    };
+    match resource.name.is_empty() {
+        true => kind.to_string(),
+        false => format!("{kind} {:?}", resource.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[RequestKind]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match item {
+                RequestKind::Single(request) => request.read().unwrap().name.clone(),
+                RequestKind::Nested(dir) => format!("{}/", dir.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_workspace_export() {
+        let content = include_str!("fixtures/insomnia_nested.json");
+        assert!(is_export(content));
+        let import = parse(content).unwrap();
+
+        let shop = &import.collections[0];
+        assert_eq!(shop.info.name, "Shop API");
+        assert_eq!(
+            shop.info.description.as_deref(),
+            Some("storefront endpoints")
+        );
+
+        let items = shop.requests.as_ref().unwrap().read().unwrap();
+        assert_eq!(
+            names(&items),
+            vec!["Health", "Orders/", "Orders / Refunds/"]
+        );
+        let RequestKind::Nested(orders) = &items[1] else {
+            panic!("expected a directory");
+        };
+        let RequestKind::Nested(refunds) = &items[2] else {
+            panic!("expected a directory");
+        };
+        assert_eq!(
+            names(&orders.requests.read().unwrap()),
+            vec!["List orders", "Create order"]
+        );
+        assert_eq!(
+            names(&refunds.requests.read().unwrap()),
+            vec!["Refund order", "Upload receipt"]
+        );
+
+        let requests = shop.flatten_requests();
+        let request = |name: &str| {
+            requests
+                .iter()
+                .map(|request| request.read().unwrap().clone())
+                .find(|request| request.name.eq(name))
+                .unwrap()
+        };
+
+        assert_eq!(request("Health").uri, "{{base_url}}/health");
+        assert_eq!(request("Health").parent, None);
+
+        let list = request("List orders");
+        assert_eq!(list.uri, "{{base_url}}/orders?page=2&limit={{page_size}}");
+        assert_eq!(list.parent.as_deref(), Some(orders.id.as_str()));
+
+        let create = request("Create order");
+        assert_eq!(create.method, RequestMethod::Post);
+        assert_eq!(
+            create.body.as_deref(),
+            Some("{\n  \"cart\": \"{{cart_id}}\",\n  \"reference\": \"{{$uuid}}\"\n}")
+        );
+        assert_eq!(create.body_type, Some(BodyType::Json));
+        assert_eq!(
+            create.headers.as_ref().unwrap(),
+            &vec![
+                HeaderMap {
+                    pair: ("Content-Type".into(), "application/json".into()),
+                    enabled: true,
+                },
+                HeaderMap {
+                    pair: ("X-Debug".into(), "1".into()),
+                    enabled: false,
+                },
+                HeaderMap {
+                    pair: ("Authorization".into(), "Bearer {{auth.token}}".into()),
+                    enabled: true,
+                },
+            ]
+        );
+        assert_eq!(
+            create.notes.as_deref(),
+            Some("needs a cart id from **List carts**")
+        );
+
+        let refund = request("Refund order");
+        assert_eq!(
+            refund.body.as_deref(),
+            Some("amount=10&reason={{refund_reason}}")
+        );
+        assert_eq!(refund.parent.as_deref(), Some(refunds.id.as_str()));
+        assert_eq!(request("Upload receipt").body, None);
+
+        let environments = shop.environments.as_ref().unwrap();
+        assert_eq!(environments.len(), 2);
+        let staging = shop.find_environment("Staging").unwrap();
+        assert_eq!(
+            staging.variables,
+            BTreeMap::from([
+                ("auth.token".to_string(), "local-token".to_string()),
+                (
+                    "base_url".to_string(),
+                    "https://staging.shop.dev".to_string()
+                ),
+                ("page_size".to_string(), "20".to_string()),
+                (
+                    "refund_reason".to_string(),
+                    "{{base_url}}/reasons/duplicate".to_string()
+                ),
+            ])
+        );
+        assert_eq!(
+            shop.find_environment("Production").unwrap().variables["base_url"],
+            "https://shop.dev"
+        );
+
+        let auth = &import.collections[1];
+        assert_eq!(auth.info.name, "Auth");
+        assert_eq!(
+            auth.info.description.as_deref(),
+            Some("imported from insomnia")
+        );
+        assert_eq!(
+            names(&auth.requests.as_ref().unwrap().read().unwrap()),
+            vec!["Login"]
+        );
+        assert_eq!(auth.environments, None);
+
+        assert_eq!(
+            import.skipped,
+            vec![
+                "basic auth of request \"Refund order\"",
+                "multipart body of request \"Upload receipt\"",
+                "cookie jar \"Default Jar\"",
+                "api spec",
+                "grpc request \"Stock stream\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_placeholders_and_tags_are_translated() {
+        let mut tags = vec![];
+        assert_eq!(
+            translate(
+                "{{ _.host }}/{{id}}?at={% now 'iso-8601', '' %}&x={",
+                &mut tags
+            ),
+            "{{host}}/{{id}}?at={{$isoTimestamp}}&x={"
+        );
+        assert!(tags.is_empty());
+
+        let tag = "{% response 'body', 'req_1', 'b64::JC50b2tlbg==::46b', 'never' %}";
+        assert_eq!(
+            translate(&format!("Bearer {tag}"), &mut tags),
+            format!("Bearer {tag}")
+        );
+        assert_eq!(tags, vec![tag]);
+        assert_eq!(translate("{{ unterminated", &mut tags), "{{ unterminated");
+    }
+
+    #[test]
+    fn test_other_files_are_not_exports() {
+        assert!(!is_export(r#"{ "info": { "name": "api" } }"#));
+        assert!(!is_export("GET https://example.com"));
+        assert!(matches!(
+            parse(r#"{ "_type": "export", "__export_format": 3, "resources": [] }"#),
+            Err(InsomniaError::UnsupportedFormat(3))
+        ));
+        assert!(matches!(
+            parse(r#"{ "_type": "export", "__export_format": 4, "resources": [] }"#),
+            Err(InsomniaError::NoWorkspaces)
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::collection::types::{Request, RequestMethod};
+use crate::net::request_client::ClientOptions;
+use crate::net::send_request;
+
+/// how much of the body of a failed fetch is shown, enough to tell a login
+/// page from an error message
+const SNIPPET_LEN: usize = 160;
+
+/// whether what was given to import is a url instead of a path
+pub fn is_url(source: &str) -> bool {
+    let source = source.trim().to_lowercase();
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// the last segment of the url path without its extension, eg: `users` for
+/// `https://example.com/api/users.http?v=2`. Used to name the collection
+/// of formats that carry no name, like `.http` files
+pub fn name_from_url(url: &str) -> String {
+    let url = url.trim();
+    let without_query = url.split(['?', '#']).next().unwrap_or_default();
+    let without_scheme = without_query
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(without_query);
+
+    // a bare host has no extension to strip, `example.com` stays whole
+    let Some((_, segment)) = without_scheme.trim_end_matches('/').rsplit_once('/') else {
+        return without_scheme.trim_end_matches('/').to_string();
+    };
+    match segment.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => segment.to_string(),
+    }
+}
+
+/// downloads what is on `url` to be imported, going through the same
+/// client as requests do so proxies and offline mode are respected. Any
+/// status but a 2xx fails with the status and the start of the body, as
+/// urls behind a login tend to answer with a html page instead
+pub async fn fetch(url: &str, options: &ClientOptions) -> Result<String, String> {
+    let request = Request {
+        id: String::from("import"),
+        method: RequestMethod::Get,
+        name: String::from("import"),
+        uri: url.trim().to_string(),
+        ..Default::default()
+    };
+    let response = send_request(request, options.clone()).await;
+
+    let Some(status) = response.status else {
+        let cause = response.cause.unwrap_or_else(|| "no response".into());
+        return Err(format!("failed to fetch {url}: {cause}"));
+    };
+    let body = response.body.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!(
+            "fetching {url} answered {status}: {}",
+            snippet(&body)
+        ));
+    }
+    if response.binary_body.is_some() {
+        return Err(format!(
+            "{url} answered with a binary file, not a collection"
+        ));
+    }
+
+    Ok(body)
+}
+
+/// the start of `body` on a single line
+fn snippet(body: &str) -> String {
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    match body.chars().count().gt(&SNIPPET_LEN) {
+        true => format!("{}…", body.chars().take(SNIPPET_LEN).collect::<String>()),
+        false if body.is_empty() => String::from("the body is empty"),
+        false => body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    /// answers a single request with `status` and `body`
+    fn serve(status: &str, body: &str) -> String {
+        let response = format!(
+            "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w.eq(b"\r\n\r\n")) {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{addr}/shared/users.http")
+    }
+
+    #[test]
+    fn test_telling_urls_and_names_apart() {
+        assert!(is_url("https://example.com/api.json"));
+        assert!(is_url(" HTTP://example.com"));
+        assert!(!is_url("./https/api.json"));
+        assert!(!is_url("/tmp/api.json"));
+
+        assert_eq!(
+            name_from_url("https://example.com/api/users.http?v=2"),
+            "users"
+        );
+        assert_eq!(name_from_url("https://example.com/exports/team/"), "team");
+        assert_eq!(name_from_url("https://example.com"), "example.com");
+    }
+
+    #[tokio::test]
+    async fn test_fetching_what_to_import() {
+        let url = serve("200 OK", "GET http://a.dev/me\n");
+        assert_eq!(
+            fetch(&url, &ClientOptions::default()).await.unwrap(),
+            "GET http://a.dev/me\n"
+        );
+
+        let url = serve(
+            "401 Unauthorized",
+            "<html>\n  <title>Sign in to continue</title>\n</html>",
+        );
+        let error = fetch(&url, &ClientOptions::default()).await.unwrap_err();
+        assert!(error.contains("401 Unauthorized"));
+        assert!(error.ends_with("<html> <title>Sign in to continue</title> </html>"));
+
+        let offline = ClientOptions {
+            offline: true,
+            ..Default::default()
+        };
+        let error = fetch("http://127.0.0.1:9/a.json", &offline)
+            .await
+            .unwrap_err();
+        assert!(error.contains("offline mode"));
+    }
+
+    #[test]
+    fn test_long_bodies_are_cut() {
+        let body = "word ".repeat(100);
+        assert!(snippet(&body).ends_with('…'));
+        assert_eq!(snippet(&body).chars().count(), SNIPPET_LEN + 1);
+        assert_eq!(snippet("  \n"), "the body is empty");
+    }
+}
@@ -1,6 +1,27 @@
+pub mod activity;
+pub mod app_state;
+pub mod assertions;
+pub mod builtins;
+pub mod captures;
+pub mod codegen;
 pub mod collection;
 pub mod command;
+pub mod doctor;
+pub mod export;
 pub mod fs;
+pub mod header_suggestions;
+pub mod hooks;
+pub mod import;
 pub mod net;
+pub mod runner;
+pub mod safety;
+pub mod search;
+pub mod secrets;
 pub mod syntax;
+pub mod templates;
+#[cfg(test)]
+mod test_utils;
 pub mod text_object;
+pub mod updates;
+pub mod url_history;
+pub mod variables;
@@ -1,6 +1,22 @@
+pub mod body;
+pub mod certificate;
+pub mod charset;
+pub mod checksum;
+pub mod client_pool;
+pub mod decompression;
+pub mod digest;
+pub mod download;
+pub mod handshake;
+pub mod image;
+pub mod md5;
+pub mod proxy;
 pub mod request_client;
 pub mod request_manager;
 pub mod request_strategies;
 pub mod response_decoders;
+pub mod sigv4;
+pub mod unix_socket;
+pub mod uri;
+pub mod wire;
 
-pub use request_manager::handle_request;
+pub use request_manager::{handle_request, send_request};
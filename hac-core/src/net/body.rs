@@ -0,0 +1,139 @@
+use crate::collection::types::BodyType;
+use crate::net::md5;
+
+/// the body as it goes on the wire for `body_type`. Json and text are sent
+/// as written, forms are built from their `name=value` lines and graphql is
+/// wrapped into the json object servers expect
+pub fn encode(body: &str, body_type: &BodyType) -> Result<String, String> {
+    match body_type {
+        BodyType::Json | BodyType::Text => Ok(body.into()),
+        BodyType::UrlEncoded => Ok(form_fields(body)
+            .map(|(name, value)| format!("{}={}", form_encode(name), form_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&")),
+        BodyType::Multipart => {
+            let boundary = boundary(body);
+            let mut encoded = String::default();
+            for (name, value) in form_fields(body) {
+                let name = name.replace('"', "%22");
+                encoded.push_str(&format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+                ));
+            }
+            encoded.push_str(&format!("--{boundary}--\r\n"));
+            Ok(encoded)
+        }
+        BodyType::GraphQl => {
+            let (query, variables) = match body.split_once("\n---\n") {
+                Some((query, variables)) => (query, Some(variables)),
+                None => (body, None),
+            };
+            let mut encoded = serde_json::json!({ "query": query.trim() });
+            if let Some(variables) = variables.filter(|variables| !variables.trim().is_empty()) {
+                let variables = serde_json::from_str::<serde_json::Value>(variables)
+                    .ok()
+                    .filter(serde_json::Value::is_object)
+                    .ok_or("graphql variables must be a json object")?;
+                encoded["variables"] = variables;
+            }
+            Ok(encoded.to_string())
+        }
+    }
+}
+
+/// separates the parts of a multipart body. It comes from the body itself,
+/// so the content type and the encoded body agree wherever they are built,
+/// and a body can't contain its own hash
+pub fn boundary(body: &str) -> String {
+    let hash = md5::md5(body.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("hac-{hash}")
+}
+
+/// the `name=value` lines of a form body, blank lines and the ones starting
+/// with `#` are skipped, and a line without `=` is a field with no value
+fn form_fields(body: &str) -> impl Iterator<Item = (&str, &str)> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, value) = line.split_once('=').unwrap_or((line, ""));
+            (name.trim(), value.trim())
+        })
+}
+
+/// `application/x-www-form-urlencoded` escaping, spaces become `+`
+fn form_encode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'*' => {
+                (byte as char).to_string()
+            }
+            b' ' => "+".into(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORM: &str = "name=Jane Doe\n# ignored\n\nbio=a&b=c\nnewsletter\n";
+
+    #[test]
+    fn test_json_and_text_are_sent_as_written() {
+        let body = "{\"name\": \"{{name}}\"}\n";
+        assert_eq!(encode(body, &BodyType::Json).unwrap(), body);
+        assert_eq!(encode(body, &BodyType::Text).unwrap(), body);
+    }
+
+    #[test]
+    fn test_url_encoded_forms() {
+        assert_eq!(
+            encode(FORM, &BodyType::UrlEncoded).unwrap(),
+            "name=Jane+Doe&bio=a%26b%3Dc&newsletter="
+        );
+        assert_eq!(encode("", &BodyType::UrlEncoded).unwrap(), "");
+    }
+
+    #[test]
+    fn test_multipart_forms() {
+        let boundary = boundary(FORM);
+        assert!(boundary.starts_with("hac-"));
+        assert_ne!(boundary, super::boundary("name=John"));
+
+        assert_eq!(
+            encode(FORM, &BodyType::Multipart).unwrap(),
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nJane Doe\r\n\
+                 --{boundary}\r\nContent-Disposition: form-data; name=\"bio\"\r\n\r\na&b=c\r\n\
+                 --{boundary}\r\nContent-Disposition: form-data; name=\"newsletter\"\r\n\r\n\r\n\
+                 --{boundary}--\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_graphql_queries() {
+        let query = "query User($id: ID!) {\n  user(id: $id) { name }\n}";
+        let encoded = encode(query, &BodyType::GraphQl).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&encoded).unwrap(),
+            serde_json::json!({ "query": query })
+        );
+
+        let with_variables = format!("{query}\n---\n{{\"id\": \"42\"}}\n");
+        let encoded = encode(&with_variables, &BodyType::GraphQl).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&encoded).unwrap(),
+            serde_json::json!({ "query": query, "variables": { "id": "42" } })
+        );
+
+        let broken = format!("{query}\n---\n[1, 2]");
+        assert!(encode(&broken, &BodyType::GraphQl).is_err());
+    }
+}
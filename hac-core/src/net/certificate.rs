@@ -0,0 +1,428 @@
+use std::net::IpAddr;
+
+/// certificates closer than this to expiring are flagged, in seconds
+pub const EXPIRY_WARNING: i64 = 30 * 24 * 60 * 60;
+
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_OID: u8 = 0x06;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+const TAG_DNS_NAME: u8 = 0x82;
+const TAG_IP_ADDRESS: u8 = 0x87;
+
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+/// what the server presented during the tls handshake
+#[derive(Debug, Clone, PartialEq)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    /// dns names and ip addresses from the subject alternative names
+    pub san: Vec<String>,
+    /// unix timestamps, in seconds
+    pub not_before: i64,
+    pub not_after: i64,
+    /// sha-256 of the certificate, as colon separated hex
+    pub fingerprint: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Validity {
+    NotYetValid,
+    Expired,
+    ExpiresSoon,
+    Valid,
+}
+
+impl Certificate {
+    pub fn validity(&self, now: i64) -> Validity {
+        match now {
+            _ if now.lt(&self.not_before) => Validity::NotYetValid,
+            _ if now.gt(&self.not_after) => Validity::Expired,
+            _ if self.not_after - now < EXPIRY_WARNING => Validity::ExpiresSoon,
+            _ => Validity::Valid,
+        }
+    }
+}
+
+/// reads the fields we display out of a der encoded x509 certificate, `None`
+/// when the bytes are not shaped like one
+pub fn parse(der: &[u8]) -> Option<Certificate> {
+    let certificate = Der(der).expect(TAG_SEQUENCE)?;
+    let tbs = Der(certificate).expect(TAG_SEQUENCE)?;
+    let mut tbs = Der(tbs);
+
+    if tbs.peek().eq(&Some(TAG_VERSION)) {
+        tbs.next()?;
+    }
+    // serial number and signature algorithm
+    tbs.next()?;
+    tbs.next()?;
+    let issuer = tbs.expect(TAG_SEQUENCE)?;
+    let validity = tbs.expect(TAG_SEQUENCE)?;
+    let subject = tbs.expect(TAG_SEQUENCE)?;
+    // subject public key info
+    tbs.next()?;
+
+    let mut validity = Der(validity);
+    let not_before = parse_time(validity.next()?)?;
+    let not_after = parse_time(validity.next()?)?;
+
+    let mut san = vec![];
+    while let Some((tag, content)) = tbs.next() {
+        if tag.eq(&TAG_EXTENSIONS) {
+            san = subject_alt_names(content).unwrap_or_default();
+        }
+    }
+
+    Some(Certificate {
+        subject: format_name(subject),
+        issuer: format_name(issuer),
+        san,
+        not_before,
+        not_after,
+        fingerprint: fingerprint(der),
+    })
+}
+
+/// formats a unix timestamp as `2024-05-01 13:45 UTC`
+pub fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let seconds = timestamp.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// a cursor over a run of der values, only the subset x509 needs: single
+/// byte tags and definite lengths
+struct Der<'a>(&'a [u8]);
+
+impl<'a> Der<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.0.first().copied()
+    }
+
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.0.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+
+        let (len, rest) = match first {
+            len if len.lt(&0x80) => (len as usize, rest),
+            long => {
+                let count = (long & 0x7f) as usize;
+                if count.eq(&0) || count.gt(&4) || count.gt(&rest.len()) {
+                    return None;
+                }
+                let (len_bytes, rest) = rest.split_at(count);
+                let len = len_bytes
+                    .iter()
+                    .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+                (len, rest)
+            }
+        };
+
+        if len.gt(&rest.len()) {
+            return None;
+        }
+        let (content, rest) = rest.split_at(len);
+        self.0 = rest;
+        Some((tag, content))
+    }
+
+    fn expect(&mut self, tag: u8) -> Option<&'a [u8]> {
+        self.next()
+            .filter(|(found, _)| found.eq(&tag))
+            .map(|(_, content)| content)
+    }
+}
+
+/// names are written the way openssl prints them, eg: `CN=example.com, O=Acme`.
+/// Attributes without a short name are left out
+fn format_name(name: &[u8]) -> String {
+    let mut parts = vec![];
+    let mut rdns = Der(name);
+
+    while let Some(rdn) = rdns.expect(TAG_SET) {
+        let mut attributes = Der(rdn);
+        while let Some(attribute) = attributes.expect(TAG_SEQUENCE) {
+            let mut attribute = Der(attribute);
+            let (Some(oid), Some((_, value))) = (attribute.expect(TAG_OID), attribute.next())
+            else {
+                continue;
+            };
+            let short = match oid {
+                [0x55, 0x04, 0x03] => "CN",
+                [0x55, 0x04, 0x06] => "C",
+                [0x55, 0x04, 0x07] => "L",
+                [0x55, 0x04, 0x08] => "ST",
+                [0x55, 0x04, 0x0a] => "O",
+                [0x55, 0x04, 0x0b] => "OU",
+                _ => continue,
+            };
+            parts.push(format!("{}={}", short, String::from_utf8_lossy(value)));
+        }
+    }
+
+    parts.join(", ")
+}
+
+fn subject_alt_names(extensions: &[u8]) -> Option<Vec<String>> {
+    let mut extensions = Der(Der(extensions).expect(TAG_SEQUENCE)?);
+
+    while let Some(extension) = extensions.expect(TAG_SEQUENCE) {
+        let mut extension = Der(extension);
+        if extension.expect(TAG_OID).ne(&Some(OID_SUBJECT_ALT_NAME)) {
+            continue;
+        }
+
+        // the critical flag is optional and comes before the value
+        let value = loop {
+            match extension.next()? {
+                (TAG_OCTET_STRING, value) => break value,
+                _ => continue,
+            }
+        };
+
+        let mut names = Der(Der(value).expect(TAG_SEQUENCE)?);
+        let mut san = vec![];
+        while let Some((tag, name)) = names.next() {
+            match tag {
+                TAG_DNS_NAME => san.push(String::from_utf8_lossy(name).into_owned()),
+                TAG_IP_ADDRESS => match name.len() {
+                    4 => san.push(IpAddr::from(<[u8; 4]>::try_from(name).ok()?).to_string()),
+                    16 => san.push(IpAddr::from(<[u8; 16]>::try_from(name).ok()?).to_string()),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        return Some(san);
+    }
+
+    None
+}
+
+/// `YYMMDDHHMMSSZ` for utc times, `YYYYMMDDHHMMSSZ` for generalized ones
+fn parse_time((tag, value): (u8, &[u8])) -> Option<i64> {
+    let value = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        TAG_UTC_TIME if value.len().eq(&12) => {
+            let year = value[..2].parse::<i64>().ok()?;
+            // rfc 5280 maps two digit years to 1950..2049
+            (
+                if year.ge(&50) {
+                    1900 + year
+                } else {
+                    2000 + year
+                },
+                &value[2..],
+            )
+        }
+        TAG_GENERALIZED_TIME if value.len().eq(&14) => (value[..4].parse().ok()?, &value[4..]),
+        _ => return None,
+    };
+
+    let field = |at: usize| rest.get(at..at + 2)?.parse::<i64>().ok();
+    let (month, day) = (field(0)?, field(2)?);
+    let (hour, minute, second) = (field(4)?, field(6)?, field(8)?);
+
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// days since the unix epoch, from howard hinnant's `days_from_civil`
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month.le(&2) { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index.lt(&10) {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month.le(&2) { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match content.len() {
+            len if len.lt(&0x80) => out.push(len as u8),
+            len => {
+                out.push(0x82);
+                out.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn name(cn: &str, o: &str) -> Vec<u8> {
+        let attribute = |oid: u8, value: &str| {
+            let pair = [
+                tlv(TAG_OID, &[0x55, 0x04, oid]),
+                tlv(0x0c, value.as_bytes()),
+            ]
+            .concat();
+            tlv(TAG_SET, &tlv(TAG_SEQUENCE, &pair))
+        };
+        tlv(
+            TAG_SEQUENCE,
+            &[attribute(0x03, cn), attribute(0x0a, o)].concat(),
+        )
+    }
+
+    fn make_certificate() -> Vec<u8> {
+        let san = tlv(
+            TAG_SEQUENCE,
+            &[
+                tlv(TAG_DNS_NAME, b"example.com"),
+                tlv(TAG_DNS_NAME, b"*.example.com"),
+                tlv(TAG_IP_ADDRESS, &[10, 0, 0, 7]),
+            ]
+            .concat(),
+        );
+        let extension = tlv(
+            TAG_SEQUENCE,
+            &[
+                tlv(TAG_OID, OID_SUBJECT_ALT_NAME),
+                tlv(0x01, &[0x00]),
+                tlv(TAG_OCTET_STRING, &san),
+            ]
+            .concat(),
+        );
+
+        let tbs = [
+            tlv(TAG_VERSION, &tlv(0x02, &[0x02])),
+            tlv(0x02, &[0x01, 0x23]),
+            tlv(TAG_SEQUENCE, &tlv(TAG_OID, &[0x2a, 0x86, 0x48])),
+            name("Test CA", "Acme"),
+            tlv(
+                TAG_SEQUENCE,
+                &[
+                    tlv(TAG_UTC_TIME, b"240101000000Z"),
+                    tlv(TAG_GENERALIZED_TIME, b"20250301123000Z"),
+                ]
+                .concat(),
+            ),
+            name("example.com", "Acme"),
+            tlv(TAG_SEQUENCE, &tlv(0x03, &[0x00])),
+            tlv(TAG_EXTENSIONS, &tlv(TAG_SEQUENCE, &extension)),
+        ]
+        .concat();
+
+        tlv(
+            TAG_SEQUENCE,
+            &[
+                tlv(TAG_SEQUENCE, &tbs),
+                tlv(TAG_SEQUENCE, &tlv(TAG_OID, &[0x2a, 0x86, 0x48])),
+                tlv(0x03, &[0x00; 200]),
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn test_parse_reads_the_displayed_fields() {
+        let der = make_certificate();
+        let certificate = parse(&der).unwrap();
+
+        assert_eq!(certificate.subject, "CN=example.com, O=Acme");
+        assert_eq!(certificate.issuer, "CN=Test CA, O=Acme");
+        assert_eq!(
+            certificate.san,
+            vec!["example.com", "*.example.com", "10.0.0.7"]
+        );
+        assert_eq!(
+            format_timestamp(certificate.not_before),
+            "2024-01-01 00:00 UTC"
+        );
+        assert_eq!(
+            format_timestamp(certificate.not_after),
+            "2025-03-01 12:30 UTC"
+        );
+        assert_eq!(certificate.fingerprint.len(), 32 * 3 - 1);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert_eq!(parse(&[]), None);
+        assert_eq!(parse(b"not a certificate"), None);
+
+        let der = make_certificate();
+        assert_eq!(parse(&der[..der.len() / 2]), None);
+    }
+
+    #[test]
+    fn test_validity() {
+        let certificate = parse(&make_certificate()).unwrap();
+        let day = 24 * 60 * 60;
+
+        assert_eq!(
+            certificate.validity(certificate.not_before - 1),
+            Validity::NotYetValid
+        );
+        assert_eq!(
+            certificate.validity(certificate.not_after - 90 * day),
+            Validity::Valid
+        );
+        assert_eq!(
+            certificate.validity(certificate.not_after - 5 * day),
+            Validity::ExpiresSoon
+        );
+        assert_eq!(
+            certificate.validity(certificate.not_after + 1),
+            Validity::Expired
+        );
+    }
+
+    #[test]
+    fn test_timestamps_roundtrip_through_the_calendar() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(
+            parse_time((TAG_UTC_TIME, b"491231235959Z")),
+            Some(days_from_civil(2049, 12, 31) * 86400 + 86399)
+        );
+        assert_eq!(parse_time((TAG_UTC_TIME, b"2401010000Z")), None);
+    }
+}
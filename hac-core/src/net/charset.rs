@@ -0,0 +1,185 @@
+use encoding_rs::{Encoding, UTF_8};
+
+/// charset a response body was decoded with
+#[derive(Debug, PartialEq, Clone)]
+pub struct Charset {
+    pub name: String,
+    /// set when the body couldn't be decoded with the charset, and was shown
+    /// as lossy utf-8 instead
+    pub warning: Option<String>,
+}
+
+/// a response body turned into text whenever possible
+#[derive(Debug, PartialEq, Default)]
+pub struct DecodedBody {
+    pub text: Option<String>,
+    /// bodies that are not text at all, like images. Only happens when no
+    /// charset was given, as a declared one means the server says it is text
+    pub binary: Option<Vec<u8>>,
+    /// the bytes as they were received, kept only when the text differs
+    /// from them, so saving the body still writes the original bytes
+    pub raw: Option<Vec<u8>>,
+    pub charset: Option<Charset>,
+}
+
+/// reads the `charset` parameter of a `Content-Type` header value
+pub fn charset_from_content_type(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+            .filter(|value| !value.is_empty())
+    })
+}
+
+/// decodes `bytes` into utf-8 text. A byte order mark wins over the charset
+/// on the content type, as it can't be wrong about the bytes that follow.
+/// Unknown charsets and bytes that are invalid on their charset fall back to
+/// lossy utf-8, with a warning on the charset
+pub fn decode(bytes: Vec<u8>, content_type: Option<&str>) -> DecodedBody {
+    let declared = content_type.and_then(charset_from_content_type);
+
+    let (encoding, bom_len) = match Encoding::for_bom(&bytes) {
+        Some((encoding, bom_len)) => (Some(encoding), bom_len),
+        None => match declared {
+            Some(label) => match Encoding::for_label(label.as_bytes()) {
+                Some(encoding) => (Some(encoding), 0),
+                None => {
+                    let warning = format!("unknown charset {label}, shown as utf-8");
+                    return lossy(bytes, label.to_string(), warning);
+                }
+            },
+            None => (None, 0),
+        },
+    };
+
+    let Some(encoding) = encoding else {
+        return match String::from_utf8(bytes) {
+            Ok(text) if text.is_empty() => DecodedBody::default(),
+            Ok(text) => DecodedBody {
+                text: Some(text),
+                ..Default::default()
+            },
+            Err(e) => DecodedBody {
+                binary: Some(e.into_bytes()),
+                ..Default::default()
+            },
+        };
+    };
+
+    let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    if had_errors {
+        let warning = format!("body is not valid {}, shown as utf-8", encoding.name());
+        return lossy(bytes, encoding.name().to_string(), warning);
+    }
+
+    let text = text.into_owned();
+    let raw = (!encoding.eq(UTF_8) || bom_len.gt(&0)).then_some(bytes);
+    DecodedBody {
+        text: (!text.is_empty()).then_some(text),
+        binary: None,
+        raw,
+        charset: Some(Charset {
+            name: encoding.name().to_string(),
+            warning: None,
+        }),
+    }
+}
+
+fn lossy(bytes: Vec<u8>, name: String, warning: String) -> DecodedBody {
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    let raw = (!text.as_bytes().eq(&bytes)).then_some(bytes);
+
+    DecodedBody {
+        text: (!text.is_empty()).then_some(text),
+        binary: None,
+        raw,
+        charset: Some(Charset {
+            name,
+            warning: Some(warning),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charset_from_content_type() {
+        assert_eq!(
+            charset_from_content_type("text/html; charset=ISO-8859-1"),
+            Some("ISO-8859-1")
+        );
+        assert_eq!(
+            charset_from_content_type("text/plain;CHARSET=\"shift_jis\"; q=1"),
+            Some("shift_jis")
+        );
+        assert_eq!(charset_from_content_type("application/json"), None);
+        assert_eq!(charset_from_content_type("text/html; charset="), None);
+    }
+
+    #[test]
+    fn test_declared_charsets_are_transcoded() {
+        // "café" on latin-1
+        let decoded = decode(
+            vec![0x63, 0x61, 0x66, 0xe9],
+            Some("text/html; charset=ISO-8859-1"),
+        );
+        assert_eq!(decoded.text, Some("café".into()));
+        assert_eq!(decoded.raw, Some(vec![0x63, 0x61, 0x66, 0xe9]));
+        assert_eq!(decoded.charset.unwrap().name, "windows-1252");
+
+        // "日本" on shift_jis
+        let decoded = decode(
+            vec![0x93, 0xfa, 0x96, 0x7b],
+            Some("text/plain; charset=Shift_JIS"),
+        );
+        assert_eq!(decoded.text, Some("日本".into()));
+        assert!(decoded.charset.unwrap().warning.is_none());
+    }
+
+    #[test]
+    fn test_boms_win_over_the_declared_charset() {
+        let decoded = decode(
+            vec![0xff, 0xfe, 0x68, 0x00, 0x69, 0x00],
+            Some("text/plain; charset=utf-8"),
+        );
+        assert_eq!(decoded.text, Some("hi".into()));
+        assert_eq!(decoded.charset.unwrap().name, "UTF-16LE");
+
+        let decoded = decode(vec![0xef, 0xbb, 0xbf, 0x7b, 0x7d], None);
+        assert_eq!(decoded.text, Some("{}".into()));
+        assert!(decoded.raw.is_some());
+    }
+
+    #[test]
+    fn test_failures_fall_back_to_lossy_utf8() {
+        let decoded = decode(b"caf\xc3".to_vec(), Some("text/plain; charset=utf-8"));
+        assert_eq!(decoded.text, Some("caf\u{fffd}".into()));
+        assert_eq!(decoded.raw, Some(b"caf\xc3".to_vec()));
+        assert!(decoded.charset.unwrap().warning.is_some());
+
+        let decoded = decode(b"plain".to_vec(), Some("text/plain; charset=klingon"));
+        assert_eq!(decoded.text, Some("plain".into()));
+        assert_eq!(decoded.raw, None);
+        let charset = decoded.charset.unwrap();
+        assert_eq!(charset.name, "klingon");
+        assert!(charset.warning.unwrap().contains("unknown charset"));
+    }
+
+    #[test]
+    fn test_undeclared_bodies_are_utf8_or_binary() {
+        let decoded = decode(b"{\"ok\":true}".to_vec(), Some("application/json"));
+        assert_eq!(decoded.text, Some("{\"ok\":true}".into()));
+        assert_eq!(decoded.charset, None);
+        assert_eq!(decoded.raw, None);
+
+        let decoded = decode(vec![0x89, 0x50, 0x4e, 0x47], Some("image/png"));
+        assert_eq!(decoded.binary, Some(vec![0x89, 0x50, 0x4e, 0x47]));
+        assert_eq!(decoded.text, None);
+
+        assert_eq!(decode(vec![], None), DecodedBody::default());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::net::md5::Md5;
+use crate::net::request_manager::Response;
+
+use std::io::Read;
+use std::path::PathBuf;
+
+/// checksums of a response body, as lowercase hex
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyHashes {
+    pub sha256: String,
+    pub md5: String,
+}
+
+/// the bytes a response body is hashed from. That is the body before it was
+/// transcoded to utf-8, which for decompressed bodies are the decompressed
+/// bytes, and for bodies written to disk, the file
+#[derive(Debug, Clone, PartialEq)]
+pub enum HashSource {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+}
+
+impl HashSource {
+    pub fn of(response: &Response) -> Self {
+        if let Some(spilled) = response.spilled.as_ref() {
+            return HashSource::File(spilled.path.clone());
+        }
+
+        let bytes = response
+            .raw_body
+            .clone()
+            .or_else(|| response.binary_body.clone())
+            .or_else(|| response.body.clone().map(String::into_bytes))
+            .unwrap_or_default();
+        HashSource::Bytes(bytes)
+    }
+
+    /// files are read in chunks, so bodies of any size can be hashed without
+    /// reading them whole into memory
+    pub fn hash(&self) -> std::io::Result<BodyHashes> {
+        let mut hasher = Hasher::default();
+        match self {
+            HashSource::Bytes(bytes) => hasher.update(bytes),
+            HashSource::File(path) => {
+                let mut file = std::fs::File::open(path)?;
+                let mut buffer = vec![0; 64 * 1024];
+                loop {
+                    match file.read(&mut buffer)? {
+                        0 => break,
+                        read => hasher.update(&buffer[..read]),
+                    }
+                }
+            }
+        }
+        Ok(hasher.finish())
+    }
+}
+
+struct Hasher {
+    sha256: ring::digest::Context,
+    md5: Md5,
+}
+
+impl Default for Hasher {
+    fn default() -> Self {
+        Hasher {
+            sha256: ring::digest::Context::new(&ring::digest::SHA256),
+            md5: Md5::default(),
+        }
+    }
+}
+
+impl Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        self.sha256.update(bytes);
+        self.md5.update(bytes);
+    }
+
+    fn finish(self) -> BodyHashes {
+        BodyHashes {
+            sha256: hex(self.sha256.finish().as_ref()),
+            md5: hex(&self.md5.finish()),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_and_bytes_hash_the_same() {
+        let body = b"hello world".repeat(20_000);
+        let from_bytes = HashSource::Bytes(body.clone()).hash().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("body");
+        std::fs::write(&path, &body).unwrap();
+        let from_file = HashSource::File(path).hash().unwrap();
+
+        assert_eq!(from_bytes, from_file);
+
+        let empty = HashSource::Bytes(vec![]).hash().unwrap();
+        assert_eq!(
+            empty.sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(empty.md5, "d41d8cd98f00b204e9800998ecf8427e");
+    }
+}
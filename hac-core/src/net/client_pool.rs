@@ -0,0 +1,207 @@
+use crate::net::handshake::HandshakeLog;
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+lazy_static::lazy_static! {
+    /// clients shared by every request sent with the same settings, so their
+    /// connections are kept alive between sends
+    static ref POOL: Mutex<HashMap<PoolKey, Pooled>> = Mutex::new(HashMap::new());
+}
+
+/// clients kept at once, the one used the longest ago is dropped to make
+/// room for a new one
+const MAX_CLIENTS: usize = 16;
+/// connections remembered per client, far more than a client keeps open
+const MAX_SEEN: usize = 64;
+
+/// everything a client is built with. Requests only share a client, and
+/// therefore its connections, when all of it matches
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    /// collections never share connections with each other
+    pub collection: Option<PathBuf>,
+    pub proxy: Option<String>,
+    pub timeout: Option<Duration>,
+    pub http1_only: bool,
+    pub resolve: Option<(String, SocketAddr)>,
+}
+
+#[derive(Debug)]
+struct Pooled {
+    client: reqwest::Client,
+    handshakes: Arc<HandshakeLog>,
+    /// local addresses of the latest connections this client opened
+    seen: VecDeque<SocketAddr>,
+    last_used: Instant,
+}
+
+/// the client shared under `key` and the log of its tls handshakes. The
+/// client is built with `build` the first time it is asked for
+pub fn client<F>(
+    key: &PoolKey,
+    build: F,
+) -> Result<(reqwest::Client, Arc<HandshakeLog>), reqwest::Error>
+where
+    F: FnOnce(&Arc<HandshakeLog>) -> Result<reqwest::Client, reqwest::Error>,
+{
+    let mut pool = POOL.lock().expect("client pool lock was poisoned");
+    if let Some(pooled) = pool.get_mut(key) {
+        pooled.last_used = Instant::now();
+        return Ok((pooled.client.clone(), pooled.handshakes.clone()));
+    }
+
+    let handshakes = Arc::new(HandshakeLog::default());
+    let client = build(&handshakes)?;
+    make_room(&mut pool);
+    pool.insert(
+        key.clone(),
+        Pooled {
+            client: client.clone(),
+            handshakes: handshakes.clone(),
+            seen: VecDeque::new(),
+            last_used: Instant::now(),
+        },
+    );
+    Ok((client, handshakes))
+}
+
+/// records that a response arrived through the connection bound to
+/// `local_addr`, telling whether that connection was already used before.
+///
+/// a connection is told apart by its local port, which the os may hand out
+/// again once an idle connection is closed, so this can rarely say a brand
+/// new connection was reused
+pub fn track(key: &PoolKey, local_addr: SocketAddr) -> bool {
+    let mut pool = POOL.lock().expect("client pool lock was poisoned");
+    match pool.get_mut(key) {
+        Some(pooled) => !remember(&mut pooled.seen, local_addr),
+        None => false,
+    }
+}
+
+/// drops the client used the longest ago when the pool is full
+fn make_room(pool: &mut HashMap<PoolKey, Pooled>) {
+    if pool.len() < MAX_CLIENTS {
+        return;
+    }
+    let oldest = pool
+        .iter()
+        .min_by_key(|(_, pooled)| pooled.last_used)
+        .map(|(key, _)| key.clone());
+    if let Some(key) = oldest {
+        pool.remove(&key);
+    }
+}
+
+/// adds `local_addr` to the latest connections, returns whether it wasn't
+/// there already
+fn remember(seen: &mut VecDeque<SocketAddr>, local_addr: SocketAddr) -> bool {
+    if seen.contains(&local_addr) {
+        return false;
+    }
+    if seen.len() >= MAX_SEEN {
+        seen.pop_front();
+    }
+    seen.push_back(local_addr);
+    true
+}
+
+/// drops every shared client of `collection`, closing their idle
+/// connections. The next request builds a new client, so this is called
+/// when the collection is closed or its settings change
+pub fn forget(collection: &Path) {
+    let mut pool = POOL.lock().expect("client pool lock was poisoned");
+    pool.retain(|key, _| key.collection.as_deref().ne(&Some(collection)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(collection: &str) -> PoolKey {
+        PoolKey {
+            collection: Some(collection.into()),
+            proxy: None,
+            timeout: None,
+            http1_only: false,
+            resolve: None,
+        }
+    }
+
+    #[test]
+    fn test_connections_are_tracked_per_client() {
+        let key = key("tracked.json");
+        let other = PoolKey {
+            http1_only: true,
+            ..key.clone()
+        };
+        let addr: SocketAddr = "127.0.0.1:50123".parse().unwrap();
+
+        // nothing is tracked for clients that were never built
+        assert!(!track(&key, addr));
+
+        client(&key, |_| reqwest::Client::builder().build()).unwrap();
+        client(&other, |_| reqwest::Client::builder().build()).unwrap();
+        assert!(!track(&key, addr));
+        assert!(track(&key, addr));
+        assert!(!track(&other, addr));
+
+        forget(Path::new("tracked.json"));
+        client(&key, |_| reqwest::Client::builder().build()).unwrap();
+        assert!(!track(&key, addr));
+    }
+
+    #[test]
+    fn test_clients_are_built_once_per_key() {
+        let key = key("built_once.json");
+        let mut builds = 0;
+        for _ in 0..3 {
+            client(&key, |_| {
+                builds += 1;
+                reqwest::Client::builder().build()
+            })
+            .unwrap();
+        }
+        assert_eq!(builds, 1);
+    }
+
+    #[test]
+    fn test_the_pool_and_its_connections_are_bounded() {
+        let addr = |port: u16| SocketAddr::from(([127, 0, 0, 1], port));
+        let mut seen = VecDeque::new();
+        for port in 0..MAX_SEEN as u16 + 1 {
+            assert!(remember(&mut seen, addr(port)));
+        }
+        // the first connection was pushed out by the latest ones
+        assert_eq!(seen.len(), MAX_SEEN);
+        assert!(remember(&mut seen, addr(0)));
+        assert!(!remember(&mut seen, addr(2)));
+
+        let started = Instant::now();
+        let mut pool = HashMap::new();
+        for idx in 0..MAX_CLIENTS {
+            make_room(&mut pool);
+            pool.insert(
+                PoolKey {
+                    timeout: Some(Duration::from_secs(idx as u64)),
+                    ..key("bounded.json")
+                },
+                Pooled {
+                    client: reqwest::Client::new(),
+                    handshakes: Default::default(),
+                    seen: VecDeque::new(),
+                    last_used: started + Duration::from_secs(idx as u64),
+                },
+            );
+        }
+        make_room(&mut pool);
+        assert_eq!(pool.len(), MAX_CLIENTS - 1);
+        assert!(pool
+            .keys()
+            .all(|key| key.timeout.ne(&Some(Duration::from_secs(0)))));
+    }
+}
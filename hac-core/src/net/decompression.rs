@@ -0,0 +1,562 @@
+use std::io::{Read, Write};
+
+use miniz_oxide::inflate::stream::InflateState;
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+
+/// encodings we know how to decompress, this is also what we advertise on
+/// `Accept-Encoding` when the user didn't set it
+pub const ACCEPT_ENCODING: &str = "gzip, br";
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const GZIP_DEFLATE_METHOD: u8 = 8;
+const GZIP_HEADER_LEN: usize = 10;
+const GZIP_TRAILER_LEN: usize = 8;
+
+const FLAG_HCRC: u8 = 0b0000_0010;
+const FLAG_EXTRA: u8 = 0b0000_0100;
+const FLAG_NAME: u8 = 0b0000_1000;
+const FLAG_COMMENT: u8 = 0b0001_0000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+    Brotli,
+}
+
+impl ContentEncoding {
+    /// parses the value of a `Content-Encoding` header. Unknown encodings and
+    /// `identity` return `None`, as there is nothing we can do with them
+    pub fn from_header(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentEncoding::Gzip),
+            "deflate" => Some(ContentEncoding::Deflate),
+            "br" => Some(ContentEncoding::Brotli),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentEncoding::Gzip => f.write_str("gzip"),
+            ContentEncoding::Deflate => f.write_str("deflate"),
+            ContentEncoding::Brotli => f.write_str("br"),
+        }
+    }
+}
+
+/// information about a compressed response body
+#[derive(Debug, PartialEq, Clone)]
+pub struct Compression {
+    pub encoding: ContentEncoding,
+    /// size of the body as it was received
+    pub wire_size: u64,
+    /// wether the body was decompressed, requests can opt out of it to
+    /// inspect the compressed bytes
+    pub decompressed: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecompressionError {
+    /// the stream ended before the compressed data did
+    Truncated(ContentEncoding),
+    /// the stream isn't valid for its encoding, the reason is kept for
+    /// display only
+    Corrupt(ContentEncoding, String),
+    /// what came out of the stream couldn't be written, only happens when
+    /// decompressing as the body arrives
+    Write(String),
+    /// the body grew past the limit it was decompressed with, what came out
+    /// so far is dropped
+    OverLimit(u64),
+}
+
+impl std::fmt::Display for DecompressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecompressionError::Truncated(encoding) => write!(
+                f,
+                "failed to decompress the {} body, the response was truncated",
+                encoding
+            ),
+            DecompressionError::Corrupt(encoding, reason) => write!(
+                f,
+                "failed to decompress the {} body, the data is corrupt: {}",
+                encoding, reason
+            ),
+            DecompressionError::Write(reason) => {
+                write!(f, "failed to write the decompressed body: {}", reason)
+            }
+            DecompressionError::OverLimit(limit) => {
+                write!(f, "the decompressed body is bigger than {} bytes", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecompressionError {}
+
+pub fn decompress(encoding: ContentEncoding, bytes: &[u8]) -> Result<Vec<u8>, DecompressionError> {
+    decompress_within(encoding, bytes, None)
+}
+
+/// same as `decompress`, but gives up once the output passes `limit`. A few
+/// kilobytes on the wire can inflate into gigabytes, so the size a body was
+/// received with says nothing about how much memory it takes
+pub fn decompress_within(
+    encoding: ContentEncoding,
+    bytes: &[u8],
+    limit: Option<u64>,
+) -> Result<Vec<u8>, DecompressionError> {
+    let limit = limit.map(|limit| limit.min(usize::MAX as u64) as usize);
+    match encoding {
+        ContentEncoding::Gzip => decompress_gzip(bytes, limit),
+        ContentEncoding::Deflate => decompress_deflate(bytes, limit),
+        ContentEncoding::Brotli => decompress_brotli(bytes, limit),
+    }
+}
+
+/// gzip is a deflate stream wrapped by a header and a trailer, the header may
+/// carry a few optional fields that we need to skip to reach the data
+fn decompress_gzip(bytes: &[u8], limit: Option<usize>) -> Result<Vec<u8>, DecompressionError> {
+    let encoding = ContentEncoding::Gzip;
+    let truncated = || DecompressionError::Truncated(encoding);
+
+    let offset = gzip_header_len(bytes)?.ok_or_else(truncated)?;
+    let data = &bytes[offset..];
+    let decompressed =
+        miniz_oxide::inflate::decompress_to_vec_with_limit(data, limit.unwrap_or(usize::MAX))
+            .map_err(|e| inflate_error(encoding, e, limit))?;
+
+    // the trailer ends with the size of the uncompressed data, a stream that
+    // was cut on the trailer still inflates fine, so we check it here
+    let trailer = data
+        .len()
+        .checked_sub(GZIP_TRAILER_LEN)
+        .ok_or_else(truncated)?;
+    if !gzip_size_matches(&data[trailer..], decompressed.len() as u64) {
+        return Err(truncated());
+    }
+
+    Ok(decompressed)
+}
+
+/// where the deflate data starts on a gzip stream, `None` while the header
+/// is still incomplete
+fn gzip_header_len(bytes: &[u8]) -> Result<Option<usize>, DecompressionError> {
+    if bytes.len().lt(&GZIP_HEADER_LEN) {
+        return Ok(None);
+    }
+    if bytes[..2].ne(&GZIP_MAGIC) || bytes[2].ne(&GZIP_DEFLATE_METHOD) {
+        return Err(DecompressionError::Corrupt(
+            ContentEncoding::Gzip,
+            "invalid gzip header".into(),
+        ));
+    }
+
+    let flags = bytes[3];
+    let mut offset = GZIP_HEADER_LEN;
+
+    if flags & FLAG_EXTRA != 0 {
+        let Some(len) = bytes.get(offset..offset + 2) else {
+            return Ok(None);
+        };
+        offset += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FLAG_NAME, FLAG_COMMENT] {
+        if flags & flag != 0 {
+            // both fields are zero terminated strings
+            let Some(len) = bytes
+                .get(offset..)
+                .and_then(|rest| rest.iter().position(|byte| byte.eq(&0)))
+            else {
+                return Ok(None);
+            };
+            offset += len + 1;
+        }
+    }
+    if flags & FLAG_HCRC != 0 {
+        offset += 2;
+    }
+
+    Ok(offset.le(&bytes.len()).then_some(offset))
+}
+
+/// the last 4 bytes of the trailer are the size of the uncompressed data,
+/// modulo 2^32
+fn gzip_size_matches(trailer: &[u8], size: u64) -> bool {
+    match trailer {
+        [_, _, _, _, a, b, c, d] => u32::from_le_bytes([*a, *b, *c, *d]).eq(&(size as u32)),
+        _ => false,
+    }
+}
+
+/// `deflate` on HTTP is meant to be zlib wrapped, but some servers send raw
+/// deflate streams, so we accept both
+fn decompress_deflate(bytes: &[u8], limit: Option<usize>) -> Result<Vec<u8>, DecompressionError> {
+    use miniz_oxide::inflate::{self, TINFLStatus};
+
+    let encoding = ContentEncoding::Deflate;
+    let max = limit.unwrap_or(usize::MAX);
+    // a zlib stream that hit the limit is valid, trying it again as raw
+    // deflate would only turn that into a corrupt data error
+    match inflate::decompress_to_vec_zlib_with_limit(bytes, max) {
+        Err(e) if e.status.ne(&TINFLStatus::HasMoreOutput) => {
+            inflate::decompress_to_vec_with_limit(bytes, max)
+        }
+        result => result,
+    }
+    .map_err(|e| inflate_error(encoding, e, limit))
+}
+
+fn decompress_brotli(bytes: &[u8], limit: Option<usize>) -> Result<Vec<u8>, DecompressionError> {
+    let encoding = ContentEncoding::Brotli;
+    let mut decompressed = vec![];
+
+    // one byte past the limit is enough to know it was passed
+    let max = limit.map_or(u64::MAX, |limit| (limit as u64).saturating_add(1));
+    brotli_decompressor::Decompressor::new(bytes, 4096)
+        .take(max)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => DecompressionError::Truncated(encoding),
+            _ => DecompressionError::Corrupt(encoding, e.to_string()),
+        })?;
+
+    match limit {
+        Some(limit) if decompressed.len().gt(&limit) => {
+            Err(DecompressionError::OverLimit(limit as u64))
+        }
+        _ => Ok(decompressed),
+    }
+}
+
+/// decompresses a body chunk by chunk as it arrives, writing what comes out
+/// to `out` right away. Used for bodies too big to be kept in memory, which
+/// could never be decompressed as a whole
+pub struct StreamDecoder<W: Write> {
+    encoding: ContentEncoding,
+    inner: StreamInner<W>,
+}
+
+enum StreamInner<W: Write> {
+    Inflate(Inflater<W>),
+    Brotli(brotli_decompressor::DecompressorWriter<W>),
+}
+
+struct Inflater<W> {
+    out: W,
+    /// `None` until enough of the stream arrived to know how it is wrapped
+    state: Option<Box<InflateState>>,
+    /// what can't be inflated yet, the start of the stream while its header
+    /// is incomplete, or the gzip trailer once the data ended
+    pending: Vec<u8>,
+    written: u64,
+    ended: bool,
+}
+
+impl<W: Write> StreamDecoder<W> {
+    pub fn new(encoding: ContentEncoding, out: W) -> Self {
+        let inner = match encoding {
+            ContentEncoding::Brotli => {
+                StreamInner::Brotli(brotli_decompressor::DecompressorWriter::new(out, 4096))
+            }
+            ContentEncoding::Gzip | ContentEncoding::Deflate => StreamInner::Inflate(Inflater {
+                out,
+                state: None,
+                pending: vec![],
+                written: 0,
+                ended: false,
+            }),
+        };
+        StreamDecoder { encoding, inner }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), DecompressionError> {
+        let encoding = self.encoding;
+        let inflater = match &mut self.inner {
+            StreamInner::Brotli(writer) => {
+                return writer.write_all(chunk).map_err(|e| match e.kind() {
+                    std::io::ErrorKind::InvalidData => {
+                        DecompressionError::Corrupt(encoding, e.to_string())
+                    }
+                    _ => DecompressionError::Write(e.to_string()),
+                })
+            }
+            StreamInner::Inflate(inflater) => inflater,
+        };
+
+        if inflater.ended || inflater.state.is_some() {
+            return inflater.inflate(encoding, chunk);
+        }
+
+        inflater.pending.extend_from_slice(chunk);
+        let (format, offset) = match encoding {
+            ContentEncoding::Gzip => match gzip_header_len(&inflater.pending)? {
+                Some(offset) => (DataFormat::Raw, offset),
+                None => return Ok(()),
+            },
+            // same as when decompressing a whole body, both zlib wrapped and
+            // raw deflate streams are accepted
+            _ => match inflater.pending.as_slice() {
+                [cmf, flg, ..] if cmf & 0x0f == 8 && u16::from_be_bytes([*cmf, *flg]) % 31 == 0 => {
+                    (DataFormat::Zlib, 0)
+                }
+                [_, _, ..] => (DataFormat::Raw, 0),
+                _ => return Ok(()),
+            },
+        };
+
+        inflater.state = Some(InflateState::new_boxed(format));
+        let data = inflater.pending.split_off(offset);
+        inflater.pending.clear();
+        inflater.inflate(encoding, &data)
+    }
+
+    /// checks the stream ended where it should, giving back where it was
+    /// written to
+    pub fn finish(self) -> Result<W, DecompressionError> {
+        let encoding = self.encoding;
+        let truncated = || DecompressionError::Truncated(encoding);
+
+        match self.inner {
+            StreamInner::Brotli(mut writer) => {
+                writer.close().map_err(|_| truncated())?;
+                writer.into_inner().map_err(|_| truncated())
+            }
+            StreamInner::Inflate(inflater) => {
+                if !inflater.ended {
+                    return Err(truncated());
+                }
+                let trailer_ok = match encoding {
+                    ContentEncoding::Gzip => {
+                        gzip_size_matches(&inflater.pending[..], inflater.written)
+                    }
+                    _ => true,
+                };
+                match trailer_ok {
+                    true => Ok(inflater.out),
+                    false => Err(truncated()),
+                }
+            }
+        }
+    }
+}
+
+impl<W: Write> Inflater<W> {
+    fn inflate(
+        &mut self,
+        encoding: ContentEncoding,
+        mut input: &[u8],
+    ) -> Result<(), DecompressionError> {
+        if self.ended {
+            self.pending.extend_from_slice(input);
+            return Ok(());
+        }
+        let Some(state) = self.state.as_mut() else {
+            return Ok(());
+        };
+
+        let mut buffer = [0; 32 * 1024];
+        loop {
+            let result =
+                miniz_oxide::inflate::stream::inflate(state, input, &mut buffer, MZFlush::None);
+            input = &input[result.bytes_consumed..];
+            self.out
+                .write_all(&buffer[..result.bytes_written])
+                .map_err(|e| DecompressionError::Write(e.to_string()))?;
+            self.written += result.bytes_written as u64;
+
+            match result.status {
+                Ok(MZStatus::StreamEnd) => {
+                    self.ended = true;
+                    self.pending.extend_from_slice(input);
+                    return Ok(());
+                }
+                // no progress can be made until the next chunk arrives
+                Err(MZError::Buf) => return Ok(()),
+                Ok(_) if result.bytes_written.eq(&buffer.len()) => {}
+                Ok(_) if input.is_empty() || result.bytes_consumed.eq(&0) => return Ok(()),
+                Ok(_) => {}
+                Err(_) => {
+                    return Err(DecompressionError::Corrupt(
+                        encoding,
+                        "invalid deflate data".into(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+fn inflate_error(
+    encoding: ContentEncoding,
+    error: miniz_oxide::inflate::DecompressError,
+    limit: Option<usize>,
+) -> DecompressionError {
+    use miniz_oxide::inflate::TINFLStatus;
+
+    match error.status {
+        // only returned when the output would pass the limit
+        TINFLStatus::HasMoreOutput => {
+            DecompressionError::OverLimit(limit.unwrap_or(usize::MAX) as u64)
+        }
+        TINFLStatus::FailedCannotMakeProgress | TINFLStatus::NeedsMoreInput => {
+            DecompressionError::Truncated(encoding)
+        }
+        _ => DecompressionError::Corrupt(encoding, error.to_string().to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"message": "hello hello hello hello"}"#;
+
+    const GZIP: [u8; 42] = [
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 171, 86, 202, 77, 45, 46, 78, 76, 79, 85, 178, 82, 80,
+        202, 72, 205, 201, 201, 87, 192, 32, 149, 106, 1, 210, 207, 172, 80, 38, 0, 0, 0,
+    ];
+
+    const ZLIB: [u8; 30] = [
+        120, 156, 171, 86, 202, 77, 45, 46, 78, 76, 79, 85, 178, 82, 80, 202, 72, 205, 201, 201,
+        87, 192, 32, 149, 106, 1, 2, 31, 13, 112,
+    ];
+
+    #[test]
+    fn test_encoding_from_header() {
+        assert_eq!(
+            ContentEncoding::from_header(" GZIP "),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::from_header("x-gzip"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::from_header("br"),
+            Some(ContentEncoding::Brotli)
+        );
+        assert_eq!(ContentEncoding::from_header("identity"), None);
+        assert_eq!(ContentEncoding::from_header("zstd"), None);
+    }
+
+    #[test]
+    fn test_decompress_gzip() {
+        assert_eq!(decompress(ContentEncoding::Gzip, &GZIP).unwrap(), BODY);
+    }
+
+    #[test]
+    fn test_decompress_gzip_with_optional_fields() {
+        // same stream, but with a file name on the header
+        let mut bytes = GZIP.to_vec();
+        bytes[3] |= FLAG_NAME;
+        bytes.splice(10..10, b"body.json\0".iter().copied());
+
+        assert_eq!(decompress(ContentEncoding::Gzip, &bytes).unwrap(), BODY);
+    }
+
+    #[test]
+    fn test_decompress_deflate_with_and_without_zlib_wrapper() {
+        assert_eq!(decompress(ContentEncoding::Deflate, &ZLIB).unwrap(), BODY);
+
+        // a raw deflate stream is the zlib one without its 2 bytes header and
+        // 4 bytes checksum
+        let raw = &ZLIB[2..ZLIB.len() - 4];
+        assert_eq!(decompress(ContentEncoding::Deflate, raw).unwrap(), BODY);
+    }
+
+    #[test]
+    fn test_truncated_streams_are_errors() {
+        for len in [0, 5, 20, GZIP.len() - 2] {
+            assert_eq!(
+                decompress(ContentEncoding::Gzip, &GZIP[..len]),
+                Err(DecompressionError::Truncated(ContentEncoding::Gzip)),
+                "gzip truncated at {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrupt_streams_are_errors() {
+        assert!(matches!(
+            decompress(ContentEncoding::Gzip, BODY),
+            Err(DecompressionError::Corrupt(ContentEncoding::Gzip, _))
+        ));
+        assert!(decompress(ContentEncoding::Deflate, BODY).is_err());
+        assert!(decompress(ContentEncoding::Brotli, BODY).is_err());
+    }
+
+    #[test]
+    fn test_decompression_stops_at_the_limit() {
+        let zeros = vec![0; 1024 * 1024];
+        let zlib = miniz_oxide::deflate::compress_to_vec_zlib(&zeros, 6);
+        assert!(zlib.len().lt(&4096));
+
+        assert_eq!(
+            decompress_within(ContentEncoding::Deflate, &zlib, Some(4096)),
+            Err(DecompressionError::OverLimit(4096))
+        );
+        assert_eq!(
+            decompress_within(ContentEncoding::Deflate, &zlib, Some(zeros.len() as u64)).unwrap(),
+            zeros
+        );
+        assert_eq!(
+            decompress_within(ContentEncoding::Gzip, &GZIP, Some(8)),
+            Err(DecompressionError::OverLimit(8))
+        );
+        assert_eq!(
+            decompress_within(ContentEncoding::Gzip, &GZIP, Some(BODY.len() as u64)).unwrap(),
+            BODY
+        );
+    }
+
+    fn decompress_in_chunks(
+        encoding: ContentEncoding,
+        bytes: &[u8],
+        chunk_size: usize,
+    ) -> Result<Vec<u8>, DecompressionError> {
+        let mut decoder = StreamDecoder::new(encoding, vec![]);
+        for chunk in bytes.chunks(chunk_size) {
+            decoder.push(chunk)?;
+        }
+        decoder.finish()
+    }
+
+    #[test]
+    fn test_streams_are_decompressed_as_they_arrive() {
+        let mut named = GZIP.to_vec();
+        named[3] |= FLAG_NAME;
+        named.splice(10..10, b"body.json\0".iter().copied());
+        let raw = &ZLIB[2..ZLIB.len() - 4];
+
+        // the header alone may be split across chunks
+        for size in [1, 3, 64] {
+            let gzip = decompress_in_chunks(ContentEncoding::Gzip, &GZIP, size);
+            assert_eq!(gzip.unwrap(), BODY);
+            let named = decompress_in_chunks(ContentEncoding::Gzip, &named, size);
+            assert_eq!(named.unwrap(), BODY);
+            let zlib = decompress_in_chunks(ContentEncoding::Deflate, &ZLIB, size);
+            assert_eq!(zlib.unwrap(), BODY);
+            let raw = decompress_in_chunks(ContentEncoding::Deflate, raw, size);
+            assert_eq!(raw.unwrap(), BODY);
+        }
+
+        for len in [0, 5, 20, GZIP.len() - 2] {
+            assert_eq!(
+                decompress_in_chunks(ContentEncoding::Gzip, &GZIP[..len], 7),
+                Err(DecompressionError::Truncated(ContentEncoding::Gzip)),
+                "gzip truncated at {}",
+                len
+            );
+        }
+        assert!(matches!(
+            decompress_in_chunks(ContentEncoding::Gzip, BODY, 7),
+            Err(DecompressionError::Corrupt(ContentEncoding::Gzip, _))
+        ));
+    }
+}
@@ -0,0 +1,342 @@
+use crate::net::md5::md5;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::Rng;
+
+lazy_static::lazy_static! {
+    /// the last challenge of every origin, so later sends answer it right
+    /// away instead of being challenged again. Kept for as long as the
+    /// application runs
+    static ref CHALLENGES: Mutex<HashMap<String, Challenge>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl Algorithm {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Algorithm::Md5),
+            "MD5-SESS" => Some(Algorithm::Md5Sess),
+            "SHA-256" => Some(Algorithm::Sha256),
+            "SHA-256-SESS" => Some(Algorithm::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Md5Sess => "MD5-sess",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_session(&self) -> bool {
+        matches!(self, Algorithm::Md5Sess | Algorithm::Sha256Sess)
+    }
+
+    fn hash(&self, data: &str) -> String {
+        match self {
+            Algorithm::Md5 | Algorithm::Md5Sess => hex(&md5(data.as_bytes())),
+            Algorithm::Sha256 | Algorithm::Sha256Sess => {
+                hex(ring::digest::digest(&ring::digest::SHA256, data.as_bytes()).as_ref())
+            }
+        }
+    }
+}
+
+/// what the server sent on `WWW-Authenticate`, along with how many times
+/// its nonce was used
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub algorithm: Algorithm,
+    /// whether the server accepts `qop=auth`, challenges without qop are
+    /// answered the way rfc 2069 describes
+    pub qop_auth: bool,
+    pub nonce_count: u32,
+}
+
+impl Challenge {
+    /// parses a single `Digest ...` challenge, `None` for other schemes or
+    /// challenges we can't answer, like those only offering `auth-int`
+    pub fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let params = parse_params(params);
+        let get = |name: &str| params.get(name).cloned();
+        let algorithm = match get("algorithm") {
+            Some(algorithm) => Algorithm::parse(&algorithm)?,
+            None => Algorithm::Md5,
+        };
+        let qop_auth = match get("qop") {
+            Some(qop) => match qop.split(',').any(|qop| qop.trim().eq("auth")) {
+                true => true,
+                false => return None,
+            },
+            None => false,
+        };
+
+        Some(Challenge {
+            realm: get("realm").unwrap_or_default(),
+            nonce: get("nonce")?,
+            opaque: get("opaque"),
+            algorithm,
+            qop_auth,
+            nonce_count: 0,
+        })
+    }
+
+    /// the `Authorization` header answering the challenge, every answer uses
+    /// the nonce once more
+    pub fn authorize(&mut self, username: &str, password: &str, method: &str, uri: &str) -> String {
+        self.nonce_count += 1;
+        let cnonce = hex(&rand::thread_rng().gen::<[u8; 16]>());
+        self.answer(username, password, method, uri, &cnonce)
+    }
+
+    fn answer(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        cnonce: &str,
+    ) -> String {
+        let nc = format!("{:08x}", self.nonce_count);
+        let response = self.response(username, password, method, uri, &nc, cnonce);
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", uri=\"{}\", algorithm={}, nonce=\"{}\"",
+            username,
+            self.realm,
+            uri,
+            self.algorithm.name(),
+            self.nonce
+        );
+        if self.qop_auth {
+            header.push_str(&format!(", nc={nc}, cnonce=\"{cnonce}\", qop=auth"));
+        }
+        header.push_str(&format!(", response=\"{response}\""));
+        if let Some(opaque) = self.opaque.as_ref() {
+            header.push_str(&format!(", opaque=\"{opaque}\""));
+        }
+        header
+    }
+
+    fn response(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        nc: &str,
+        cnonce: &str,
+    ) -> String {
+        let hash = |data: String| self.algorithm.hash(&data);
+
+        let mut ha1 = hash(format!("{username}:{}:{password}", self.realm));
+        if self.algorithm.is_session() {
+            ha1 = hash(format!("{ha1}:{}:{cnonce}", self.nonce));
+        }
+        let ha2 = hash(format!("{method}:{uri}"));
+
+        match self.qop_auth {
+            true => hash(format!("{ha1}:{}:{nc}:{cnonce}:auth:{ha2}", self.nonce)),
+            false => hash(format!("{ha1}:{}:{ha2}", self.nonce)),
+        }
+    }
+}
+
+/// picks the challenge to answer among every `WWW-Authenticate` header,
+/// along with the header it came from. SHA-256 is preferred when the server
+/// offers it along with MD5
+pub fn pick_challenge<'a>(headers: impl Iterator<Item = &'a str>) -> Option<(Challenge, &'a str)> {
+    let mut challenges = headers
+        .filter_map(|header| Challenge::parse(header).map(|challenge| (challenge, header)))
+        .collect::<Vec<_>>();
+    challenges.sort_by_key(|(challenge, _)| match challenge.algorithm {
+        Algorithm::Sha256 | Algorithm::Sha256Sess => 0,
+        Algorithm::Md5 | Algorithm::Md5Sess => 1,
+    });
+    challenges.into_iter().next()
+}
+
+/// answers the last challenge seen from `origin`, when there is one
+pub fn authorize_cached(
+    origin: &str,
+    username: &str,
+    password: &str,
+    method: &str,
+    uri: &str,
+) -> Option<String> {
+    let mut challenges = CHALLENGES.lock().unwrap();
+    let challenge = challenges.get_mut(origin)?;
+    Some(challenge.authorize(username, password, method, uri))
+}
+
+/// answers a new challenge from `origin`, keeping it for the next sends
+pub fn authorize_new(
+    origin: &str,
+    mut challenge: Challenge,
+    username: &str,
+    password: &str,
+    method: &str,
+    uri: &str,
+) -> String {
+    let header = challenge.authorize(username, password, method, uri);
+    CHALLENGES
+        .lock()
+        .unwrap()
+        .insert(origin.to_string(), challenge);
+    header
+}
+
+/// `key=value` pairs of a challenge, values may be quoted and have commas in
+/// them
+fn parse_params(input: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut rest = input.trim();
+
+    while let Some((key, after_key)) = rest.split_once('=') {
+        let key = key
+            .trim()
+            .trim_start_matches(',')
+            .trim()
+            .to_ascii_lowercase();
+        let after_key = after_key.trim_start();
+
+        let (value, remaining) = match after_key.strip_prefix('"') {
+            Some(quoted) => {
+                let mut value = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((idx, c)) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            end = idx + 1;
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                (value, &quoted[end..])
+            }
+            None => match after_key.split_once(',') {
+                Some((value, remaining)) => (value.trim().to_string(), remaining),
+                None => (after_key.trim().to_string(), ""),
+            },
+        };
+
+        params.insert(key, value);
+        rest = remaining.trim_start().trim_start_matches(',');
+    }
+
+    params
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc_7616_example() {
+        let header = r#"Digest
+            realm="http-auth@example.org",
+            qop="auth, auth-int",
+            algorithm=SHA-256,
+            nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+            opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut challenge = Challenge::parse(&header).unwrap();
+        assert_eq!(challenge.realm, "http-auth@example.org");
+        assert_eq!(challenge.algorithm, Algorithm::Sha256);
+        assert!(challenge.qop_auth);
+        challenge.nonce_count = 1;
+
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+        let answer = |challenge: &Challenge| {
+            challenge.answer("Mufasa", "Circle of Life", "GET", "/dir/index.html", cnonce)
+        };
+        assert!(answer(&challenge).contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+
+        challenge.algorithm = Algorithm::Md5;
+        let header = answer(&challenge);
+        assert!(header.contains("response=\"8ca523f5e9506fed4657c9700eebdbec\""));
+        assert!(header.contains(
+            "nc=00000001, cnonce=\"f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ\", qop=auth"
+        ));
+        assert!(header.ends_with("opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\""));
+    }
+
+    #[test]
+    fn test_rfc_2617_example() {
+        let mut challenge = Challenge::parse(
+            r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+        )
+        .unwrap();
+        challenge.nonce_count = 1;
+        let header = challenge.answer(
+            "Mufasa",
+            "Circle Of Life",
+            "GET",
+            "/dir/index.html",
+            "0a4f113b",
+        );
+        assert!(header.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+    }
+
+    #[test]
+    fn test_nonce_count_increments_on_every_answer() {
+        let mut challenge =
+            Challenge::parse(r#"Digest realm="api", nonce="abc", qop="auth""#).unwrap();
+        assert!(challenge
+            .authorize("user", "pass", "GET", "/")
+            .contains("nc=00000001"));
+        assert!(challenge
+            .authorize("user", "pass", "GET", "/")
+            .contains("nc=00000002"));
+    }
+
+    #[test]
+    fn test_picking_challenges() {
+        let headers = [
+            r#"Basic realm="api""#,
+            r#"Digest realm="api", nonce="a", algorithm=MD5, qop="auth""#,
+            r#"Digest realm="api", nonce="b", algorithm=SHA-256, qop="auth""#,
+        ];
+        let (challenge, header) = pick_challenge(headers.into_iter()).unwrap();
+        assert_eq!(challenge.nonce, "b");
+        assert_eq!(header, headers[2]);
+
+        // only auth-int is offered, which would need the body hashed
+        assert_eq!(
+            pick_challenge([r#"Digest realm="api", nonce="a", qop="auth-int""#].into_iter()),
+            None
+        );
+        assert_eq!(pick_challenge([r#"Basic realm="api""#].into_iter()), None);
+    }
+}
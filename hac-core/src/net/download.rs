@@ -0,0 +1,473 @@
+use crate::net::decompression::{ContentEncoding, StreamDecoder};
+
+use std::io::Write;
+use std::ops::Sub;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// progress is reported at most this often, big downloads arrive in many
+/// small chunks and each report is a command on the channel
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// how much of a spilled body is kept in memory for the viewer
+pub const PREVIEW_SIZE: usize = 64 * 1024;
+
+pub type ProgressFn = Arc<dyn Fn(Progress) + Send + Sync>;
+
+/// how far along a response body is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub received: u64,
+    /// from `Content-Length`, when the server sent one
+    pub total: Option<u64>,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// bytes per second since the body started arriving
+    pub fn rate(&self) -> u64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs.gt(&0.0) => (self.received as f64 / secs) as u64,
+            _ => 0,
+        }
+    }
+
+    pub fn percent(&self) -> Option<u64> {
+        self.total
+            .filter(|total| total.gt(&0))
+            .map(|total| (self.received * 100 / total).min(100))
+    }
+}
+
+/// how response bodies are read
+#[derive(Clone, Default)]
+pub struct Download {
+    /// bodies bigger than this are written to a temp file instead of being
+    /// kept in memory, `None` never spills
+    pub max_in_memory: Option<u64>,
+    pub on_progress: Option<ProgressFn>,
+}
+
+impl std::fmt::Debug for Download {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Download")
+            .field("max_in_memory", &self.max_in_memory)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+/// a body that was too big to keep in memory. The file is removed once this
+/// is dropped, which also covers downloads cancelled midway, as aborting the
+/// request drops it along with everything else
+#[derive(Debug, PartialEq)]
+pub struct SpilledBody {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+impl SpilledBody {
+    /// the name is random and only we can read the file, bodies may hold
+    /// anything and the temp dir is shared with everyone else
+    fn create() -> std::io::Result<(Self, std::fs::File)> {
+        let (file, path) = tempfile::Builder::new()
+            .prefix("hac-")
+            .suffix(".body")
+            .tempfile()?
+            .keep()
+            .map_err(|e| e.error)?;
+        Ok((SpilledBody { path, size: 0 }, file))
+    }
+
+    /// copies the whole body into `dir` as `name`. Existing files are never
+    /// overwritten, a number is appended to the name instead, eg: `big.iso.1`
+    pub fn save_into(&self, dir: &Path, name: &str) -> std::io::Result<PathBuf> {
+        let (destination, mut file) = create_unique(dir, name)?;
+        std::io::copy(&mut std::fs::File::open(&self.path)?, &mut file)?;
+        Ok(destination)
+    }
+}
+
+/// writes a body kept in memory into `dir` as `name`, with the same naming
+/// as `SpilledBody::save_into`
+pub fn save_bytes_into(dir: &Path, name: &str, bytes: &[u8]) -> std::io::Result<PathBuf> {
+    let (destination, mut file) = create_unique(dir, name)?;
+    file.write_all(bytes)?;
+    Ok(destination)
+}
+
+fn create_unique(dir: &Path, name: &str) -> std::io::Result<(PathBuf, std::fs::File)> {
+    let mut attempt = 0;
+    loop {
+        let destination = match attempt {
+            0 => dir.join(name),
+            n => dir.join(format!("{name}.{n}")),
+        };
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&destination)
+        {
+            Ok(file) => return Ok((destination, file)),
+            Err(e) if e.kind().eq(&std::io::ErrorKind::AlreadyExists) => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// formats a byte count using binary units, eg: `12.1 KiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if bytes.lt(&1024) {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size.ge(&1024.0) && unit.lt(&UNITS.len().sub(1)) {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// the name a downloaded body is saved with, taken from the last segment of
+/// the url path like `curl -O` does
+pub fn file_name_for(uri: &str) -> String {
+    reqwest::Url::parse(uri)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()?
+                .rfind(|segment| !segment.is_empty())
+                .map(String::from)
+        })
+        .filter(|name| !name.starts_with('.'))
+        .unwrap_or_else(|| "response.body".into())
+}
+
+impl Drop for SpilledBody {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("failed to remove {}: {e}", self.path.display());
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Body {
+    Memory(Vec<u8>),
+    /// only the start of the body is kept, the rest lives on the file
+    Spilled {
+        preview: Vec<u8>,
+        file: SpilledBody,
+        /// size of the body as it was received, the file holds it already
+        /// decompressed when asked to
+        wire_size: u64,
+    },
+}
+
+/// a spilled body on its way to the file, its start is kept as the preview
+struct Spill {
+    file: std::fs::File,
+    preview: Vec<u8>,
+    size: u64,
+}
+
+impl Write for Spill {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(buf)?;
+        let missing = PREVIEW_SIZE.saturating_sub(self.preview.len());
+        self.preview.extend_from_slice(&buf[..missing.min(written)]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// spilled bodies are written as they were received, or decompressed on
+/// the way to the file
+enum Sink {
+    Raw(Spill),
+    Decompressed(StreamDecoder<Spill>),
+}
+
+impl Sink {
+    fn new(spill: Spill, decompress: Option<ContentEncoding>) -> Self {
+        match decompress {
+            Some(encoding) => Sink::Decompressed(StreamDecoder::new(encoding, spill)),
+            None => Sink::Raw(spill),
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) -> Result<(), String> {
+        match self {
+            Sink::Raw(spill) => spill.write_all(chunk).map_err(spill_error),
+            Sink::Decompressed(decoder) => decoder.push(chunk).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn finish(self) -> Result<Spill, String> {
+        let mut spill = match self {
+            Sink::Raw(spill) => spill,
+            Sink::Decompressed(decoder) => decoder.finish().map_err(|e| e.to_string())?,
+        };
+        spill.flush().map_err(spill_error)?;
+        Ok(spill)
+    }
+}
+
+/// reads the body chunk by chunk, reporting progress as it goes. Bodies
+/// kept in memory are given back as received, spilled ones are decompressed
+/// with `decompress` as they arrive, as they could never be as a whole
+pub async fn read_body(
+    response: &mut reqwest::Response,
+    download: &Download,
+    decompress: Option<ContentEncoding>,
+) -> Result<Body, String> {
+    let started = Instant::now();
+    let total = response.content_length();
+    let mut last_report = started;
+    let mut received = 0u64;
+    let mut bytes = vec![];
+    let mut spilled: Option<(SpilledBody, Sink)> = None;
+
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        received += chunk.len() as u64;
+
+        let over_limit = download.max_in_memory.is_some_and(|max| received.gt(&max));
+        if spilled.is_none() && over_limit {
+            let (file, mut sink) = start_spill(download, decompress)?;
+            sink.push(&std::mem::take(&mut bytes))?;
+            spilled = Some((file, sink));
+        }
+
+        match spilled.as_mut() {
+            Some((_, sink)) => sink.push(&chunk)?,
+            None => bytes.extend_from_slice(&chunk),
+        }
+
+        if let Some(on_progress) = download.on_progress.as_ref() {
+            if last_report.elapsed().ge(&PROGRESS_INTERVAL) {
+                last_report = Instant::now();
+                on_progress(Progress {
+                    received,
+                    total,
+                    elapsed: started.elapsed(),
+                });
+            }
+        }
+    }
+
+    match spilled {
+        Some((file, sink)) => finish_spill(file, sink, received),
+        None => Ok(Body::Memory(bytes)),
+    }
+}
+
+/// spills a body that was small enough to be kept in memory as it arrived,
+/// but grew past `max_in_memory` once decompressed
+pub fn spill_decompressed(
+    bytes: &[u8],
+    download: &Download,
+    encoding: ContentEncoding,
+) -> Result<Body, String> {
+    let (file, mut sink) = start_spill(download, Some(encoding))?;
+    sink.push(bytes)?;
+    finish_spill(file, sink, bytes.len() as u64)
+}
+
+fn start_spill(
+    download: &Download,
+    decompress: Option<ContentEncoding>,
+) -> Result<(SpilledBody, Sink), String> {
+    let (file, handle) = SpilledBody::create().map_err(spill_error)?;
+    tracing::warn!(
+        "response body passed {}, writing it to {}",
+        format_size(download.max_in_memory.unwrap_or_default()),
+        file.path.display()
+    );
+    let spill = Spill {
+        file: handle,
+        preview: vec![],
+        size: 0,
+    };
+    Ok((file, Sink::new(spill, decompress)))
+}
+
+fn finish_spill(mut file: SpilledBody, sink: Sink, wire_size: u64) -> Result<Body, String> {
+    let Spill {
+        mut preview, size, ..
+    } = sink.finish()?;
+    file.size = size;
+    // a character cut in half by the preview would look like the whole body
+    // is not valid utf-8
+    if let Err(e) = std::str::from_utf8(&preview) {
+        if e.error_len().is_none() {
+            preview.truncate(e.valid_up_to());
+        }
+    }
+    Ok(Body::Spilled {
+        preview,
+        file,
+        wire_size,
+    })
+}
+
+fn spill_error(e: std::io::Error) -> String {
+    format!("failed to write the body to a temporary file: {e}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn make_response(body: Vec<u8>) -> reqwest::Response {
+        let response = hyper::Response::builder()
+            .header("content-length", body.len())
+            .body(body)
+            .unwrap();
+        reqwest::Response::from(response)
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(12390), "12.1 KiB");
+        assert_eq!(format_size(151_859), "148.3 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn test_file_name_for() {
+        assert_eq!(
+            file_name_for("https://example.com/files/big.iso"),
+            "big.iso"
+        );
+        assert_eq!(file_name_for("https://example.com/files/?page=2"), "files");
+        assert_eq!(file_name_for("https://example.com"), "response.body");
+        assert_eq!(file_name_for("https://example.com/.."), "response.body");
+        assert_eq!(file_name_for("not a url"), "response.body");
+    }
+
+    #[test]
+    fn test_progress_rate_and_percent() {
+        let progress = Progress {
+            received: 512,
+            total: Some(2048),
+            elapsed: Duration::from_millis(500),
+        };
+        assert_eq!(progress.rate(), 1024);
+        assert_eq!(progress.percent(), Some(25));
+
+        let unknown = Progress {
+            total: None,
+            elapsed: Duration::ZERO,
+            ..progress
+        };
+        assert_eq!(unknown.rate(), 0);
+        assert_eq!(unknown.percent(), None);
+    }
+
+    #[tokio::test]
+    async fn test_small_bodies_stay_in_memory() {
+        let download = Download {
+            max_in_memory: Some(1024),
+            on_progress: None,
+        };
+        let mut response = make_response(b"{}".to_vec());
+
+        let body = read_body(&mut response, &download, None).await.unwrap();
+        assert_eq!(body, Body::Memory(b"{}".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_big_bodies_spill_to_a_temp_file() {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let sink = reports.clone();
+        let download = Download {
+            max_in_memory: Some(1024),
+            on_progress: Some(Arc::new(move |progress| {
+                sink.lock().unwrap().push(progress)
+            })),
+        };
+        let content = (0..PREVIEW_SIZE * 2).map(|i| i as u8).collect::<Vec<_>>();
+        let mut response = make_response(content.clone());
+
+        let Body::Spilled {
+            preview,
+            file,
+            wire_size,
+        } = read_body(&mut response, &download, None).await.unwrap()
+        else {
+            panic!("body should have been spilled");
+        };
+        assert_eq!(preview, content[..PREVIEW_SIZE]);
+        assert_eq!(wire_size, content.len() as u64);
+        assert_eq!(file.size, content.len() as u64);
+        assert_eq!(std::fs::read(&file.path).unwrap(), content);
+
+        let dir = tempfile::tempdir().unwrap();
+        let saved = file.save_into(dir.path(), "big.bin").unwrap();
+        assert_eq!(saved, dir.path().join("big.bin"));
+        assert_eq!(std::fs::read(&saved).unwrap(), content);
+        let saved = file.save_into(dir.path(), "big.bin").unwrap();
+        assert_eq!(saved, dir.path().join("big.bin.1"));
+        let saved = save_bytes_into(dir.path(), "big.bin", b"small").unwrap();
+        assert_eq!(saved, dir.path().join("big.bin.2"));
+        assert_eq!(std::fs::read(&saved).unwrap(), b"small");
+
+        // the body arrives as a single chunk, before any report is due
+        assert!(reports.lock().unwrap().is_empty());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&file.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let path = file.path.clone();
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_spilled_bodies_are_decompressed_as_they_arrive() {
+        let download = Download {
+            max_in_memory: Some(16),
+            on_progress: None,
+        };
+        // `{"message": "hello hello hello hello"}` gzipped
+        let gzip = vec![
+            31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 171, 86, 202, 77, 45, 46, 78, 76, 79, 85, 178, 82, 80,
+            202, 72, 205, 201, 201, 87, 192, 32, 149, 106, 1, 210, 207, 172, 80, 38, 0, 0, 0,
+        ];
+        let body = br#"{"message": "hello hello hello hello"}"#;
+
+        let mut response = make_response(gzip.clone());
+        let Body::Spilled {
+            preview,
+            file,
+            wire_size,
+        } = read_body(&mut response, &download, Some(ContentEncoding::Gzip))
+            .await
+            .unwrap()
+        else {
+            panic!("body should have been spilled");
+        };
+        assert_eq!(preview, body);
+        assert_eq!(wire_size, gzip.len() as u64);
+        assert_eq!(file.size, body.len() as u64);
+        assert_eq!(std::fs::read(&file.path).unwrap(), body);
+
+        let mut response = make_response(gzip[..20].to_vec());
+        let truncated = read_body(&mut response, &download, Some(ContentEncoding::Gzip)).await;
+        assert!(truncated.unwrap_err().contains("truncated"));
+    }
+}
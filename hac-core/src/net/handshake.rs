@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rustls::client::{
+    ClientSessionMemoryCache, ClientSessionStore, Resumption, Tls12ClientSessionValue,
+    Tls13ClientSessionValue,
+};
+use rustls::pki_types::ServerName;
+use rustls::{KeyLog, NamedGroup, RootCertStore};
+
+lazy_static::lazy_static! {
+    /// loading the system certificates reads a bunch of files, so it is
+    /// only done once. `None` when none of them could be loaded
+    static ref ROOTS: Option<Arc<RootCertStore>> = native_roots();
+}
+
+/// how a new tls connection was set up, inserted into the response
+/// extensions of the first request sent through it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Handshake {
+    /// eg: `TLS 1.3`
+    pub version: &'static str,
+    /// from the client hello until the keys were agreed on
+    pub took: Duration,
+}
+
+/// records the tls handshakes of a client, as reqwest doesn't tell how they
+/// went. rustls asks the session store for a ticket when it writes the
+/// client hello, and hands the traffic secrets to the key log once the
+/// keys are agreed, so together they tell when a handshake started and
+/// ended. The secrets themselves are never kept.
+///
+/// handshakes of a client are assumed not to overlap, which holds when
+/// requests are sent one at a time
+#[derive(Debug)]
+pub struct HandshakeLog {
+    sessions: ClientSessionMemoryCache,
+    started: Mutex<Option<Instant>>,
+    finished: Mutex<Option<Handshake>>,
+}
+
+impl Default for HandshakeLog {
+    fn default() -> Self {
+        HandshakeLog {
+            // the size rustls uses by default
+            sessions: ClientSessionMemoryCache::new(256),
+            started: Mutex::new(None),
+            finished: Mutex::new(None),
+        }
+    }
+}
+
+impl HandshakeLog {
+    /// the handshake that finished last, if nobody took it yet
+    pub fn take(&self) -> Option<Handshake> {
+        self.finished
+            .lock()
+            .expect("handshake log lock was poisoned")
+            .take()
+    }
+
+    fn start(&self) {
+        *self
+            .started
+            .lock()
+            .expect("handshake log lock was poisoned") = Some(Instant::now());
+    }
+
+    fn finish(&self, version: &'static str) {
+        let Some(started) = self
+            .started
+            .lock()
+            .expect("handshake log lock was poisoned")
+            .take()
+        else {
+            return;
+        };
+        *self
+            .finished
+            .lock()
+            .expect("handshake log lock was poisoned") = Some(Handshake {
+            version,
+            took: started.elapsed(),
+        });
+    }
+}
+
+impl KeyLog for HandshakeLog {
+    /// tls 1.2 has a single secret, for tls 1.3 the one for application
+    /// data is only derived after the server finished its part
+    fn will_log(&self, label: &str) -> bool {
+        matches!(label, "CLIENT_RANDOM" | "CLIENT_TRAFFIC_SECRET_0")
+    }
+
+    fn log(&self, label: &str, _: &[u8], _: &[u8]) {
+        match label {
+            "CLIENT_RANDOM" => self.finish("TLS 1.2"),
+            "CLIENT_TRAFFIC_SECRET_0" => self.finish("TLS 1.3"),
+            _ => {}
+        }
+    }
+}
+
+impl ClientSessionStore for HandshakeLog {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.sessions.set_kx_hint(server_name, group);
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.sessions.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.sessions.set_tls12_session(server_name, value);
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.sessions.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.sessions.remove_tls12_session(server_name);
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.sessions.insert_tls13_ticket(server_name, value);
+    }
+
+    /// the first thing asked when writing the client hello, even when
+    /// there is no ticket to resume
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        self.start();
+        self.sessions.take_tls13_ticket(server_name)
+    }
+}
+
+/// the tls config reqwest would build by itself, but with `log` recording
+/// its handshakes. `None` when there are no system certificates to verify
+/// servers against, reqwest then reports that on its own
+pub fn tls_config(log: &Arc<HandshakeLog>, http1_only: bool) -> Option<rustls::ClientConfig> {
+    let roots = ROOTS.clone()?;
+    let mut config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = match http1_only {
+        true => vec![b"http/1.1".to_vec()],
+        false => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+    };
+    config.resumption = Resumption::store(log.clone());
+    config.key_log = log.clone();
+    Some(config)
+}
+
+/// system stores often carry a few certificates that can't be parsed, those
+/// are skipped like reqwest does
+fn native_roots() -> Option<Arc<RootCertStore>> {
+    let certs = match rustls_native_certs::load_native_certs() {
+        Ok(certs) => certs,
+        Err(e) => {
+            tracing::debug!("failed to load the system certificates: {e}");
+            return None;
+        }
+    };
+
+    let mut roots = RootCertStore::empty();
+    let (added, _) = roots.add_parsable_certificates(certs);
+    match added {
+        0 => None,
+        _ => Some(Arc::new(roots)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshakes_are_timed_from_the_client_hello() {
+        let log = HandshakeLog::default();
+        let server_name = ServerName::try_from("example.com").unwrap();
+
+        // secrets logged without a handshake starting are not ours
+        log.log("CLIENT_TRAFFIC_SECRET_0", &[], &[]);
+        assert_eq!(log.take(), None);
+
+        assert!(log.take_tls13_ticket(&server_name).is_none());
+        assert!(!log.will_log("CLIENT_HANDSHAKE_TRAFFIC_SECRET"));
+        assert!(log.will_log("CLIENT_TRAFFIC_SECRET_0"));
+        log.log("CLIENT_TRAFFIC_SECRET_0", &[], &[]);
+        assert_eq!(
+            log.take().map(|handshake| handshake.version),
+            Some("TLS 1.3")
+        );
+        // it is only told once
+        assert_eq!(log.take(), None);
+
+        log.take_tls13_ticket(&server_name);
+        log.log("CLIENT_RANDOM", &[], &[]);
+        assert_eq!(
+            log.take().map(|handshake| handshake.version),
+            Some("TLS 1.2")
+        );
+    }
+}
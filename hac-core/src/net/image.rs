@@ -0,0 +1,542 @@
+/// images bigger than this many pixels are not decoded, a preview doesn't
+/// need them and the memory it would take could be huge
+const MAX_PIXELS: u64 = 40_000_000;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl std::fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageFormat::Png => f.write_str("PNG"),
+            ImageFormat::Jpeg => f.write_str("JPEG"),
+            ImageFormat::Gif => f.write_str("GIF"),
+        }
+    }
+}
+
+/// what can be told of an image without decoding it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageInfo {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// a decoded image, every pixel as red, green, blue and alpha, row by row
+#[derive(Debug, PartialEq)]
+pub struct Pixels {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Pixels {
+    pub fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.rgba[start..start + 4]);
+        pixel
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ImageError {
+    /// only pngs and jpegs are decoded, other formats are only told apart
+    UnsupportedFormat(ImageFormat),
+    Unsupported(String),
+    TooBig {
+        width: u32,
+        height: u32,
+    },
+    Malformed(String),
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::UnsupportedFormat(format) => {
+                write!(f, "{format} images can't be previewed")
+            }
+            ImageError::Unsupported(what) => write!(f, "{what} can't be previewed"),
+            ImageError::TooBig { width, height } => {
+                write!(f, "{width}x{height} is too big to preview")
+            }
+            ImageError::Malformed(why) => write!(f, "the image is malformed, {why}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// tells what image `bytes` holds by its first bytes, along with its size.
+/// The content type is never trusted, as servers get it wrong often enough
+pub fn probe(bytes: &[u8]) -> Option<ImageInfo> {
+    if bytes.starts_with(PNG_SIGNATURE) {
+        // the header chunk always comes first, right after the signature
+        let header = bytes.get(8..24).filter(|header| header[4..8].eq(b"IHDR"))?;
+        return Some(ImageInfo {
+            format: ImageFormat::Png,
+            width: be_u32(&header[8..12]),
+            height: be_u32(&header[12..16]),
+        });
+    }
+
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        let size = bytes.get(6..10)?;
+        return Some(ImageInfo {
+            format: ImageFormat::Gif,
+            width: u16::from_le_bytes([size[0], size[1]]).into(),
+            height: u16::from_le_bytes([size[2], size[3]]).into(),
+        });
+    }
+
+    if bytes.starts_with(&[0xff, 0xd8]) {
+        return probe_jpeg(bytes);
+    }
+
+    None
+}
+
+/// walks the segments of a jpeg until the frame header, which holds the size
+fn probe_jpeg(bytes: &[u8]) -> Option<ImageInfo> {
+    let mut idx = 2;
+    loop {
+        let marker = *bytes.get(idx..idx + 2).filter(|m| m[0].eq(&0xff))?.get(1)?;
+        match marker {
+            // fill bytes before a marker
+            0xff => idx += 1,
+            // markers without a length
+            0x01 | 0xd0..=0xd7 => idx += 2,
+            // start of frame, except for the ones that are other things
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let frame = bytes.get(idx + 5..idx + 9)?;
+                return Some(ImageInfo {
+                    format: ImageFormat::Jpeg,
+                    width: u16::from_be_bytes([frame[2], frame[3]]).into(),
+                    height: u16::from_be_bytes([frame[0], frame[1]]).into(),
+                });
+            }
+            _ => {
+                let length = bytes.get(idx + 2..idx + 4)?;
+                idx += 2 + usize::from(u16::from_be_bytes([length[0], length[1]]));
+            }
+        }
+    }
+}
+
+/// decodes `bytes` into pixels, pngs without interlacing and jpegs are
+/// supported
+pub fn decode(bytes: &[u8]) -> Result<Pixels, ImageError> {
+    let info = probe(bytes).ok_or_else(|| ImageError::Malformed("unknown format".into()))?;
+    if u64::from(info.width) * u64::from(info.height) > MAX_PIXELS {
+        return Err(ImageError::TooBig {
+            width: info.width,
+            height: info.height,
+        });
+    }
+    match info.format {
+        ImageFormat::Png => decode_png(bytes, info),
+        ImageFormat::Jpeg => decode_jpeg(bytes),
+        format => Err(ImageError::UnsupportedFormat(format)),
+    }
+}
+
+fn decode_jpeg(bytes: &[u8]) -> Result<Pixels, ImageError> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    let data = decoder
+        .decode()
+        .map_err(|e| ImageError::Malformed(e.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| ImageError::Malformed("there is no frame header".into()))?;
+
+    let rgba = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => data.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        // big endian, the most significant byte is enough for a preview
+        jpeg_decoder::PixelFormat::L16 => data
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .flat_map(|&[l, _]| [l, l, l, 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::RGB24 => data
+            .as_chunks::<3>()
+            .0
+            .iter()
+            .flat_map(|&[r, g, b]| [r, g, b, 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => data
+            .as_chunks::<4>()
+            .0
+            .iter()
+            .flat_map(|&[c, m, y, k]| {
+                let ink = |c: u8| ((255 - u16::from(c)) * (255 - u16::from(k)) / 255) as u8;
+                [ink(c), ink(m), ink(y), 255]
+            })
+            .collect(),
+    };
+
+    Ok(Pixels {
+        width: info.width.into(),
+        height: info.height.into(),
+        rgba,
+    })
+}
+
+struct PngHeader {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl PngHeader {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * usize::from(self.bit_depth)
+    }
+}
+
+fn decode_png(bytes: &[u8], info: ImageInfo) -> Result<Pixels, ImageError> {
+    let malformed = |why: &str| ImageError::Malformed(why.into());
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut data = vec![];
+    let mut idx = PNG_SIGNATURE.len();
+    while let Some(length) = bytes.get(idx..idx + 4) {
+        let length = be_u32(length) as usize;
+        let kind = bytes
+            .get(idx + 4..idx + 8)
+            .ok_or_else(|| malformed("a chunk is cut short"))?;
+        let chunk = bytes
+            .get(idx + 8..idx + 8 + length)
+            .ok_or_else(|| malformed("a chunk is cut short"))?;
+        match kind {
+            b"IHDR" if chunk.len().ge(&13) => {
+                if chunk[12].ne(&0) {
+                    return Err(ImageError::Unsupported("interlaced pngs".into()));
+                }
+                header = Some(PngHeader {
+                    width: info.width as usize,
+                    height: info.height as usize,
+                    bit_depth: chunk[8],
+                    color_type: chunk[9],
+                });
+            }
+            b"PLTE" => palette = chunk,
+            b"tRNS" => transparency = chunk,
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+        // the length, the kind and the checksum around the chunk
+        idx += length + 12;
+    }
+
+    let header = header.ok_or_else(|| malformed("there is no header"))?;
+    let supported = match header.color_type {
+        0 => [1, 2, 4, 8, 16].contains(&header.bit_depth),
+        3 => [1, 2, 4, 8].contains(&header.bit_depth),
+        2 | 4 | 6 => [8, 16].contains(&header.bit_depth),
+        _ => false,
+    };
+    if !supported {
+        return Err(malformed("the color type or bit depth is invalid"));
+    }
+
+    let data = miniz_oxide::inflate::decompress_to_vec_zlib(&data)
+        .map_err(|_| malformed("the pixel data can't be inflated"))?;
+    let scanlines = unfilter(&header, &data)?;
+
+    let mut rgba = Vec::with_capacity(header.width * header.height * 4);
+    for line in scanlines.chunks(stride(&header)) {
+        for x in 0..header.width {
+            rgba.extend(pixel_at(&header, line, x, palette, transparency));
+        }
+    }
+
+    Ok(Pixels {
+        width: info.width,
+        height: info.height,
+        rgba,
+    })
+}
+
+/// bytes on each row of the image, not counting the filter byte
+fn stride(header: &PngHeader) -> usize {
+    (header.width * header.bits_per_pixel()).div_ceil(8)
+}
+
+/// reverts the filter each row was written with, returning the rows back to
+/// back without their filter byte
+fn unfilter(header: &PngHeader, data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    let stride = stride(header);
+    // filters work on whole bytes, even when a pixel takes less than one
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    if data.len() < (stride + 1) * header.height {
+        return Err(ImageError::Malformed("the pixel data is cut short".into()));
+    }
+
+    let mut out = vec![0u8; stride * header.height];
+    for y in 0..header.height {
+        let row = &data[y * (stride + 1)..(y + 1) * (stride + 1)];
+        let (filter, row) = (row[0], &row[1..]);
+        let (done, current) = out.split_at_mut(y * stride);
+        let previous = match y {
+            0 => None,
+            _ => Some(&done[(y - 1) * stride..]),
+        };
+        let current = &mut current[..stride];
+
+        for x in 0..stride {
+            let left = match x >= bpp {
+                true => current[x - bpp],
+                false => 0,
+            };
+            let up = previous.map_or(0, |previous| previous[x]);
+            let up_left = match (previous, x >= bpp) {
+                (Some(previous), true) => previous[x - bpp],
+                _ => 0,
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(ImageError::Malformed(format!("unknown filter {filter}"))),
+            };
+            current[x] = row[x].wrapping_add(predicted);
+        }
+    }
+
+    Ok(out)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let distance_left = (estimate - i16::from(left)).abs();
+    let distance_up = (estimate - i16::from(up)).abs();
+    let distance_up_left = (estimate - i16::from(up_left)).abs();
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// the sample `idx` of a row, scaled to 8 bits. Samples of 16 bits keep
+/// their most significant byte
+fn sample(header: &PngHeader, line: &[u8], idx: usize) -> u8 {
+    match header.bit_depth {
+        16 => line[idx * 2],
+        8 => line[idx],
+        depth => {
+            let bit = idx * usize::from(depth);
+            let shift = 8 - usize::from(depth) - bit % 8;
+            let mask = (1u8 << depth) - 1;
+            let value = (line[bit / 8] >> shift) & mask;
+            match header.color_type {
+                // palette indexes are not scaled
+                3 => value,
+                _ => value * (255 / mask),
+            }
+        }
+    }
+}
+
+fn pixel_at(
+    header: &PngHeader,
+    line: &[u8],
+    x: usize,
+    palette: &[u8],
+    transparency: &[u8],
+) -> [u8; 4] {
+    let channels = header.channels();
+    let at = |channel: usize| sample(header, line, x * channels + channel);
+    match header.color_type {
+        0 => {
+            let gray = at(0);
+            [gray, gray, gray, 255]
+        }
+        2 => [at(0), at(1), at(2), 255],
+        3 => {
+            let idx = usize::from(at(0));
+            let color = palette.get(idx * 3..idx * 3 + 3).unwrap_or(&[0, 0, 0]);
+            let alpha = transparency.get(idx).copied().unwrap_or(255);
+            [color[0], color[1], color[2], alpha]
+        }
+        4 => {
+            let gray = at(0);
+            [gray, gray, gray, at(1)]
+        }
+        _ => [at(0), at(1), at(2), at(3)],
+    }
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// writes a png of `width` by `height` with the given raw rows, each one
+    /// already starting with its filter byte. Checksums are left as zeroes
+    /// as they are never checked
+    fn png(width: u32, height: u32, bit_depth: u8, color_type: u8, rows: &[u8]) -> Vec<u8> {
+        let chunk = |kind: &[u8], data: &[u8]| {
+            let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+            chunk.extend_from_slice(kind);
+            chunk.extend_from_slice(data);
+            chunk.extend_from_slice(&[0, 0, 0, 0]);
+            chunk
+        };
+
+        let mut header = width.to_be_bytes().to_vec();
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        bytes.extend(chunk(b"IHDR", &header));
+        bytes.extend(chunk(
+            b"IDAT",
+            &miniz_oxide::deflate::compress_to_vec_zlib(rows, 6),
+        ));
+        bytes.extend(chunk(b"IEND", &[]));
+        bytes
+    }
+
+    #[test]
+    fn test_probing_tells_format_and_size() {
+        let bytes = png(3, 2, 8, 2, &[0; 20]);
+        assert_eq!(
+            probe(&bytes),
+            Some(ImageInfo {
+                format: ImageFormat::Png,
+                width: 3,
+                height: 2
+            })
+        );
+
+        let gif = b"GIF89a\x40\x01\xf0\x00rest";
+        assert_eq!(
+            probe(gif),
+            Some(ImageInfo {
+                format: ImageFormat::Gif,
+                width: 320,
+                height: 240
+            })
+        );
+
+        // an app segment, then the frame header of a 640x480 image
+        let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00];
+        jpeg.extend([0xff, 0xc0, 0x00, 0x11, 0x08, 0x01, 0xe0, 0x02, 0x80]);
+        assert_eq!(
+            probe(&jpeg),
+            Some(ImageInfo {
+                format: ImageFormat::Jpeg,
+                width: 640,
+                height: 480
+            })
+        );
+        assert_eq!(
+            decode(gif),
+            Err(ImageError::UnsupportedFormat(ImageFormat::Gif))
+        );
+
+        assert_eq!(probe(b"{\"not\": \"an image\"}"), None);
+    }
+
+    #[test]
+    fn test_decoding_reverts_every_filter() {
+        // the first row written with the sub filter, the second with up and
+        // the third with paeth, which ends up repeating the second
+        let rows = [
+            1, 255, 0, 0, 0, 255, 0, // sub: red, then yellow as a delta
+            2, 0, 0, 255, 255, 255, 255, // up: added to the row above
+            4, 0, 0, 0, 0, 0, 0, // paeth: predicts the row above
+        ];
+        let pixels = decode(&png(2, 3, 8, 2, &rows)).unwrap();
+        assert_eq!(pixels.get(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixels.get(1, 0), [255, 255, 0, 255]);
+        assert_eq!(pixels.get(0, 1), [255, 0, 255, 255]);
+        assert_eq!(pixels.get(1, 1), [254, 254, 255, 255]);
+        assert_eq!(pixels.get(0, 2), pixels.get(0, 1));
+        assert_eq!(pixels.get(1, 2), pixels.get(1, 1));
+    }
+
+    #[test]
+    fn test_decoding_small_bit_depths() {
+        // a 1 bit grayscale row: black, white, white, black
+        let pixels = decode(&png(4, 1, 1, 0, &[0, 0b0110_0000])).unwrap();
+        let grays = (0..4).map(|x| pixels.get(x, 0)[0]).collect::<Vec<_>>();
+        assert_eq!(grays, vec![0, 255, 255, 0]);
+
+        let mut interlaced = png(1, 1, 8, 0, &[0, 0]);
+        // the interlace flag is the last byte of the header
+        interlaced[28] = 1;
+        assert_eq!(
+            decode(&interlaced),
+            Err(ImageError::Unsupported("interlaced pngs".into()))
+        );
+    }
+
+    #[test]
+    fn test_decoding_jpegs() {
+        // left half red, right half blue, in blocks so the edge is exact
+        let (width, height) = (16u16, 8u16);
+        let rgb = (0..height)
+            .flat_map(|_| (0..width).map(|x| if x < 8 { [255, 0, 0] } else { [0, 0, 255] }))
+            .flatten()
+            .collect::<Vec<u8>>();
+        let mut bytes = vec![];
+        jpeg_encoder::Encoder::new(&mut bytes, 100)
+            .encode(&rgb, width, height, jpeg_encoder::ColorType::Rgb)
+            .unwrap();
+
+        let pixels = decode(&bytes).unwrap();
+        assert_eq!((pixels.width, pixels.height), (16, 8));
+        // lossy, but never far off
+        let close = |[r, g, b, a]: [u8; 4], [er, eg, eb]: [u8; 3]| {
+            [r.abs_diff(er), g.abs_diff(eg), b.abs_diff(eb)]
+                .iter()
+                .all(|diff| *diff < 16)
+                && a.eq(&255)
+        };
+        assert!(close(pixels.get(2, 4), [255, 0, 0]));
+        assert!(close(pixels.get(13, 4), [0, 0, 255]));
+
+        let gray = [128u8; 64];
+        let mut bytes = vec![];
+        jpeg_encoder::Encoder::new(&mut bytes, 100)
+            .encode(&gray, 8, 8, jpeg_encoder::ColorType::Luma)
+            .unwrap();
+        assert!(close(decode(&bytes).unwrap().get(3, 3), [128, 128, 128]));
+
+        assert!(matches!(
+            decode(&bytes[..bytes.len() / 2]),
+            Err(ImageError::Malformed(_))
+        ));
+    }
+}
@@ -1,16 +1,29 @@
-use crate::collection::types::{BodyType, Request};
+use crate::collection::types::Request;
+use crate::command::Command;
+use crate::net::certificate::Certificate;
+use crate::net::charset::Charset;
+use crate::net::checksum::BodyHashes;
+use crate::net::decompression::Compression;
+use crate::net::download::{ProgressFn, SpilledBody};
+use crate::net::handshake::Handshake;
+use crate::net::request_client::ClientOptions;
 use crate::net::request_strategies::{http_strategy::HttpResponse, RequestStrategy};
 use crate::text_object::{Readonly, TextObject};
 
+use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use reqwest::header::{HeaderMap, HeaderValue};
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub struct Response {
     pub body: Option<String>,
+    /// bodies that are not valid utf-8, like images or compressed bytes, are
+    /// kept here instead of on `body`
+    pub binary_body: Option<Vec<u8>>,
     pub pretty_body: Option<TextObject<Readonly>>,
     pub headers: Option<HeaderMap<HeaderValue>>,
     pub duration: Duration,
@@ -20,8 +33,78 @@ pub struct Response {
     pub size: Option<u64>,
     pub is_error: bool,
     pub cause: Option<String>,
+    /// proxy the request went through, with its password redacted
+    pub proxy: Option<String>,
+    /// set when the body was sent with a `Content-Encoding` we understand
+    pub compression: Option<Compression>,
+    /// set when the body declared a charset or started with a byte order mark
+    pub charset: Option<Charset>,
+    /// the body exactly as received, when it had to be transcoded to utf-8
+    pub raw_body: Option<Vec<u8>>,
+    /// address the request connected to, which is the proxy when going
+    /// through one
+    pub remote_addr: Option<SocketAddr>,
+    /// http version the server agreed on
+    pub version: Option<reqwest::Version>,
+    /// leaf certificate presented by the server, only set on https
+    pub certificate: Option<Certificate>,
+    /// whether the response came through a connection an earlier request
+    /// had already opened, `None` when it can't be told
+    pub connection_reused: Option<bool>,
+    /// how the tls connection was set up, only known on new connections
+    pub handshake: Option<Handshake>,
+    /// set when the body was too big to keep in memory, `body` and
+    /// `binary_body` then only hold its start
+    pub spilled: Option<SpilledBody>,
+    /// values built-in variables like `{{$uuid}}` got on the request, set
+    /// by whoever resolved it as the response itself knows nothing of them
+    pub generated: Vec<(String, String)>,
+    /// every exchange the send took, oldest first, when it took more than
+    /// one, eg: a digest challenge and its answer
+    pub attempts: Vec<Attempt>,
+    /// checksums of the body, computed the first time they are asked for
+    /// and kept with the response, as hashing a big body takes a while
+    pub hashes: Option<Result<BodyHashes, String>>,
 }
 
+/// one of the exchanges of a send that took more than one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub status: Option<reqwest::StatusCode>,
+    /// the header that drove the handshake on this exchange, as
+    /// `(name, value)`, eg: the `WWW-Authenticate` challenge of a `401`
+    pub header: Option<(String, String)>,
+}
+
+/// the version as it is usually written, eg: `HTTP/2`
+pub fn version_name(version: reqwest::Version) -> &'static str {
+    match version {
+        reqwest::Version::HTTP_09 => "HTTP/0.9",
+        reqwest::Version::HTTP_10 => "HTTP/1.0",
+        reqwest::Version::HTTP_11 => "HTTP/1.1",
+        reqwest::Version::HTTP_2 => "HTTP/2",
+        reqwest::Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP",
+    }
+}
+
+impl Response {
+    /// a response for a request that failed before we could read a response,
+    /// the cause is displayed to the user as is
+    pub fn error(cause: String, start: std::time::Instant) -> Self {
+        Response {
+            is_error: true,
+            cause: Some(cause),
+            duration: start.elapsed(),
+            ..Default::default()
+        }
+    }
+}
+
+/// why a send made while offline failed, distinct from any network error
+/// so it is never mistaken for the server being down
+pub const OFFLINE_CAUSE: &str = "offline mode: the request was not sent";
+
 pub struct RequestManager;
 
 impl RequestManager {
@@ -57,21 +140,118 @@ impl From<&str> for ContentType {
     }
 }
 
+/// sends the request using the strategy that matches its body type and waits
+/// for the response. This is shared by the TUI and the headless runner so both
+/// go through the exact same network path
+///
+/// ```no_run
+/// use hac_core::collection::Collection;
+/// use hac_core::net::request_client::ClientOptions;
+/// use hac_core::net::send_request;
+/// use hac_core::secrets::Secrets;
+/// use hac_core::variables;
+///
+/// # async fn check() -> anyhow::Result<()> {
+/// let collection = Collection::load("users.json")?;
+/// let layers = collection.layers(Some("local"), &Secrets::load(hac_config::secrets_file()))?;
+/// let request = variables::resolve_request(&collection.request("list users")?, &layers.merged())?;
+/// let options = ClientOptions::new(&hac_config::load_config(), &collection);
+/// // nothing here is tied to a terminal, so checks can run on their own tasks
+/// let response = tokio::spawn(send_request(request, options)).await?;
+/// println!("{:?} in {:?}", response.status, response.duration);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn send_request(request: Request, options: ClientOptions) -> Response {
+    send_request_with_progress(request, options, None).await
+}
+
+/// like `send_request`, calling `on_progress` as the response body arrives
+pub async fn send_request_with_progress(
+    request: Request,
+    options: ClientOptions,
+    on_progress: Option<ProgressFn>,
+) -> Response {
+    if options.offline {
+        return Response::error(OFFLINE_CAUSE.into(), Instant::now());
+    }
+
+    let strategy = HttpResponse {
+        options,
+        on_progress,
+    };
+    // every body type goes over http, they only differ on how the body is
+    // encoded, which the client takes care of
+    RequestManager::handle(strategy, request).await
+}
+
+/// sends the request on a background task, the response is sent through
+/// `command_tx` once it arrives, tagged with `viewer_id` and `send_id`.
+/// Aborting the returned handle cancels the request, in which case nothing
+/// is ever sent
 #[tracing::instrument(skip_all)]
-pub fn handle_request(request: &Arc<RwLock<Request>>, response_tx: UnboundedSender<Response>) {
+pub fn handle_request(
+    request: &Arc<RwLock<Request>>,
+    viewer_id: u64,
+    send_id: u64,
+    options: ClientOptions,
+    command_tx: UnboundedSender<Command>,
+) -> JoinHandle<()> {
     let request = request.read().unwrap().clone();
+    let progress_tx = command_tx.clone();
+    let on_progress: ProgressFn = Arc::new(move |progress| {
+        _ = progress_tx.send(Command::RequestProgress {
+            viewer_id,
+            send_id,
+            progress,
+        });
+    });
+
     tokio::spawn(async move {
-        let response = match request.body_type.as_ref() {
-            // if we dont have a body type, this is a GET request, so we use HTTP strategy
-            None => RequestManager::handle(HttpResponse, request).await,
-            Some(body_type) => match body_type {
-                BodyType::Json => RequestManager::handle(HttpResponse, request).await,
-            },
+        let request_id = request.id.clone();
+        let response = send_request_with_progress(request, options, Some(on_progress)).await;
+        let command = Command::ResponseReceived {
+            viewer_id,
+            send_id,
+            request_id,
+            response: Box::new(response),
         };
 
-        response_tx
-            .send(response)
-            .is_err()
-            .then(|| std::process::abort());
-    });
+        // nobody is listening when the application is shutting down, so the
+        // response has nowhere to go
+        if command_tx.send(command).is_err() {
+            tracing::debug!("request finished after its receiver was dropped");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::RequestMethod;
+
+    #[tokio::test]
+    async fn test_nothing_is_sent_while_offline() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let request = Request {
+            id: "id".into(),
+            method: RequestMethod::Get,
+            name: "users".into(),
+            uri: format!("http://{}/users", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        let options = ClientOptions {
+            offline: true,
+            ..Default::default()
+        };
+
+        let response = send_request(request, options).await;
+        assert!(response.is_error);
+        assert_eq!(response.cause.as_deref(), Some(OFFLINE_CAUSE));
+        assert_eq!(
+            listener.accept().unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock
+        );
+    }
 }
@@ -1,153 +1,300 @@
-use crate::collection::types::{Request, RequestMethod};
-use crate::net::request_client::RequestClient;
-use crate::net::request_manager::Response;
+use crate::collection::types::{AuthMethod, HttpVersion, Request};
+use crate::net::digest;
+use crate::net::download::{Download, ProgressFn};
+use crate::net::handshake::Handshake;
+use crate::net::request_client::{ClientOptions, ConnectionReused, RequestClient};
+use crate::net::request_manager::{version_name, Attempt, Response};
 use crate::net::request_strategies::RequestStrategy;
 use crate::net::response_decoders::{decoder_from_headers, ResponseDecoder};
+use crate::net::unix_socket;
 
-pub struct HttpResponse;
+use reqwest::header::{AUTHORIZATION, WWW_AUTHENTICATE};
 
-impl RequestStrategy for HttpResponse {
-    async fn handle(&self, request: Request) -> Response {
-        let client = RequestClient::default();
-
-        match request.method {
-            RequestMethod::Get => self.handle_get_request(client, request).await,
-            RequestMethod::Post => self.handle_post_request(client, request).await,
-            RequestMethod::Put => self.handle_put_request(client, request).await,
-            RequestMethod::Patch => self.handle_patch_request(client, request).await,
-            RequestMethod::Delete => self.handle_delete_request(client, request).await,
-        }
-    }
+pub struct HttpResponse {
+    pub options: ClientOptions,
+    /// called as the response body arrives
+    pub on_progress: Option<ProgressFn>,
 }
 
-impl HttpResponse {
-    async fn handle_get_request(&self, client: RequestClient, request: Request) -> Response {
-        let now = std::time::Instant::now();
-        match client.get(&request).send().await {
-            Ok(response) => {
-                let decoder = decoder_from_headers(response.headers());
-                decoder.decode(response, now).await
+impl RequestStrategy for HttpResponse {
+    async fn handle(&self, request: Request) -> Response {
+        let client = match RequestClient::new(&request, &self.options) {
+            Ok(client) => client,
+            Err(e) => {
+                return Response::error(
+                    format!("failed to configure the client: {}", e),
+                    std::time::Instant::now(),
+                )
             }
-            Err(e) => Response {
-                is_error: true,
-                cause: Some(e.to_string()),
-                body: None,
-                pretty_body: None,
-                body_size: None,
-                size: None,
-                headers_size: None,
-                status: None,
-                headers: None,
-                duration: now.elapsed(),
-            },
-        }
-    }
+        };
 
-    async fn handle_post_request(&self, client: RequestClient, request: Request) -> Response {
-        let now = std::time::Instant::now();
-        match client
-            .post(&request)
-            .json(&request.body.unwrap_or_default())
-            .send()
+        let start = std::time::Instant::now();
+        let http_version = request.http_version;
+        let uri = request.uri.clone();
+        let mut attempts = vec![];
+        let mut response = match self
+            .send_authenticated(&client, &request, &mut attempts)
             .await
         {
             Ok(response) => {
-                let decoder = decoder_from_headers(response.headers());
-                decoder.decode(response, now).await
+                let reused = response
+                    .extensions()
+                    .get::<ConnectionReused>()
+                    .map(|reused| reused.0);
+                let handshake = response.extensions().get::<Handshake>().copied();
+                let mut response = self
+                    .decode(response, start, !request.disable_decompression)
+                    .await;
+                response.connection_reused = reused;
+                response.handshake = handshake;
+                response
             }
-            Err(e) => Response {
-                is_error: true,
-                cause: Some(e.to_string()),
-                body: None,
-                pretty_body: None,
-                body_size: None,
-                size: None,
-                headers_size: None,
-                status: None,
-                headers: None,
-                duration: now.elapsed(),
-            },
+            Err(e) => Response::error(e, start),
+        };
+        response.attempts = attempts;
+
+        let mismatch = http_version.and_then(|version| version_mismatch(version, &uri, &response));
+        if let Some(cause) = mismatch {
+            response = Response::error(cause, start);
         }
+
+        response.proxy = client.proxy().map(String::from);
+        response
+    }
+}
+
+impl HttpResponse {
+    async fn decode(
+        &self,
+        response: reqwest::Response,
+        start: std::time::Instant,
+        decompress: bool,
+    ) -> Response {
+        let download = Download {
+            max_in_memory: self.options.max_in_memory,
+            on_progress: self.on_progress.clone(),
+        };
+        let decoder = decoder_from_headers(response.headers());
+        decoder.decode(response, start, decompress, &download).await
     }
 
-    async fn handle_put_request(&self, client: RequestClient, request: Request) -> Response {
-        let now = std::time::Instant::now();
-        match client
-            .put(&request)
-            .json(&request.body.unwrap_or_default())
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let decoder = decoder_from_headers(response.headers());
-                decoder.decode(response, now).await
-            }
-            Err(e) => Response {
-                is_error: true,
-                cause: Some(e.to_string()),
-                body: None,
-                pretty_body: None,
-                body_size: None,
-                size: None,
-                headers_size: None,
-                status: None,
-                headers: None,
-                duration: now.elapsed(),
-            },
+    /// sends the request, answering the digest challenge of the server when
+    /// the request uses digest auth. A challenge already seen from the same
+    /// origin is answered right away, the server is only asked for a new one
+    /// once it rejects the answer, eg: when the nonce went stale
+    async fn send_authenticated(
+        &self,
+        client: &RequestClient,
+        request: &Request,
+        attempts: &mut Vec<Attempt>,
+    ) -> Result<reqwest::Response, String> {
+        let request_builder = client.builder(request)?;
+        let (Some(AuthMethod::Digest { username, password }), Ok(url)) = (
+            request.auth_method.as_ref(),
+            reqwest::Url::parse(&request.uri),
+        ) else {
+            return self.send(client, request_builder).await;
+        };
+
+        let origin = url.origin().ascii_serialization();
+        let method = request.method.to_string();
+        let uri = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+
+        let request_builder =
+            match digest::authorize_cached(&origin, username, password, &method, &uri) {
+                Some(authorization) => request_builder.header(AUTHORIZATION, authorization),
+                None => request_builder,
+            };
+        let response = self.send(client, request_builder).await?;
+        if response.status().ne(&reqwest::StatusCode::UNAUTHORIZED) {
+            return Ok(response);
         }
+
+        let challenges = response.headers().get_all(WWW_AUTHENTICATE);
+        let Some((challenge, header)) =
+            digest::pick_challenge(challenges.iter().filter_map(|value| value.to_str().ok()))
+        else {
+            // nothing we can answer, the 401 is the response
+            return Ok(response);
+        };
+        attempts.push(Attempt {
+            status: Some(response.status()),
+            header: Some((WWW_AUTHENTICATE.to_string(), header.to_string())),
+        });
+
+        let authorization =
+            digest::authorize_new(&origin, challenge, username, password, &method, &uri);
+        let request_builder = client
+            .builder(request)?
+            .header(AUTHORIZATION, &authorization);
+        let response = self.send(client, request_builder).await?;
+        attempts.push(Attempt {
+            status: Some(response.status()),
+            header: Some((AUTHORIZATION.to_string(), authorization)),
+        });
+
+        Ok(response)
     }
 
-    async fn handle_patch_request(&self, client: RequestClient, request: Request) -> Response {
-        let now = std::time::Instant::now();
-        match client
-            .patch(&request)
-            .json(&request.body.unwrap_or_default())
-            .send()
+    async fn send(
+        &self,
+        client: &RequestClient,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, String> {
+        let Some(path) = self.options.unix_socket.as_deref() else {
+            let mut response = request_builder
+                .send()
+                .await
+                .map_err(|e| describe_error(client, e))?;
+            client.track_connection(&mut response);
+            return Ok(response);
+        };
+
+        let request = request_builder.build().map_err(|e| e.to_string())?;
+        unix_socket::send(request, path)
             .await
-        {
-            Ok(response) => {
-                let decoder = decoder_from_headers(response.headers());
-                decoder.decode(response, now).await
-            }
-            Err(e) => Response {
-                is_error: true,
-                cause: Some(e.to_string()),
-                body: None,
-                pretty_body: None,
-                body_size: None,
-                size: None,
-                headers_size: None,
-                status: None,
-                headers: None,
-                duration: now.elapsed(),
-            },
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// servers without http/2 quietly fall back to http/1.1 during alpn, so
+/// when http/2 is forced the version is checked once the response arrives.
+/// Forcing http/1.1 can't fail this way, as nothing else is ever offered
+fn version_mismatch(requested: HttpVersion, uri: &str, response: &Response) -> Option<String> {
+    let negotiated = match requested {
+        HttpVersion::Http1 => return None,
+        HttpVersion::Http2 => response
+            .version
+            .filter(|version| version.ne(&reqwest::Version::HTTP_2))?,
+    };
+
+    let negotiated = version_name(negotiated);
+    match uri.starts_with("http://") {
+        true => Some(format!(
+            "requested {requested}, but the server answered with {negotiated}, HTTP/2 is only negotiated over https"
+        )),
+        false => Some(format!(
+            "requested {requested}, but the server only offered {negotiated}"
+        )),
+    }
+}
+
+/// connection errors through a proxy may come from either the proxy or the
+/// server, so we always mention the proxy to make it clear which hop failed
+fn describe_error(client: &RequestClient, error: reqwest::Error) -> String {
+    match client.proxy() {
+        Some(proxy) => format!("{} (via proxy {})", error, proxy),
+        None => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::RequestMethod;
+    use std::io::{Read, Write};
+
+    fn response_with(version: reqwest::Version) -> Response {
+        Response {
+            is_error: false,
+            version: Some(version),
+            ..Response::error(String::new(), std::time::Instant::now())
         }
     }
 
-    async fn handle_delete_request(&self, client: RequestClient, request: Request) -> Response {
-        let now = std::time::Instant::now();
-        match client
-            .delete(&request)
-            .json(&request.body.unwrap_or_default())
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let decoder = decoder_from_headers(response.headers());
-                decoder.decode(response, now).await
+    #[test]
+    fn test_forced_http2_names_the_offered_version() {
+        let http1 = response_with(reqwest::Version::HTTP_11);
+        assert_eq!(
+            version_mismatch(HttpVersion::Http2, "https://example.com", &http1).as_deref(),
+            Some("requested HTTP/2, but the server only offered HTTP/1.1")
+        );
+        assert!(
+            version_mismatch(HttpVersion::Http2, "http://example.com", &http1)
+                .unwrap()
+                .contains("only negotiated over https")
+        );
+        assert_eq!(
+            version_mismatch(HttpVersion::Http1, "https://example.com", &http1),
+            None
+        );
+
+        let http2 = response_with(reqwest::Version::HTTP_2);
+        assert_eq!(
+            version_mismatch(HttpVersion::Http2, "https://example.com", &http2),
+            None
+        );
+
+        let failed = Response::error("timed out".into(), std::time::Instant::now());
+        assert_eq!(
+            version_mismatch(HttpVersion::Http2, "https://example.com", &failed),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_digest_challenges_are_answered_and_reused() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut received = vec![];
+            for idx in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w.eq(b"\r\n\r\n")) {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let response = match idx {
+                    0 => "HTTP/1.1 401 Unauthorized\r\nwww-authenticate: Digest realm=\"api\", nonce=\"abc\", qop=\"auth\"\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    _ => "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+                received.push(String::from_utf8(request).unwrap().to_lowercase());
             }
-            Err(e) => Response {
-                is_error: true,
-                cause: Some(e.to_string()),
-                body: None,
-                pretty_body: None,
-                body_size: None,
-                size: None,
-                headers_size: None,
-                status: None,
-                headers: None,
-                duration: now.elapsed(),
-            },
-        }
+            received
+        });
+
+        let request = Request {
+            id: "id".into(),
+            method: RequestMethod::Get,
+            name: "legacy".into(),
+            uri: format!("http://{addr}/users?page=1"),
+            auth_method: Some(AuthMethod::Digest {
+                username: "user".into(),
+                password: "pass".into(),
+            }),
+            ..Default::default()
+        };
+        let strategy = HttpResponse {
+            options: ClientOptions::default(),
+            on_progress: None,
+        };
+
+        let response = strategy.handle(request.clone()).await;
+        assert_eq!(response.status, Some(reqwest::StatusCode::OK));
+        let statuses = response
+            .attempts
+            .iter()
+            .map(|attempt| attempt.status.unwrap().as_u16())
+            .collect::<Vec<_>>();
+        assert_eq!(statuses, vec![401, 200]);
+        let (name, value) = response.attempts[1].header.clone().unwrap();
+        assert_eq!(name, "authorization");
+        assert!(value.contains("uri=\"/users?page=1\""));
+
+        // the challenge is answered right away from then on
+        let response = strategy.handle(request).await;
+        assert_eq!(response.status, Some(reqwest::StatusCode::OK));
+        assert!(response.attempts.is_empty());
+
+        let received = server.join().unwrap();
+        assert!(!received[0].contains("authorization:"));
+        assert!(received[1].contains("nc=00000001"));
+        assert!(received[2].contains("nc=00000002"));
     }
 }
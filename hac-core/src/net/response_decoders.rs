@@ -1,5 +1,6 @@
 mod json_decoder;
 
+use crate::net::download::Download;
 use crate::net::request_manager::{ContentType, Response};
 use crate::net::response_decoders::json_decoder::JsonDecoder;
 
@@ -13,6 +14,8 @@ pub trait ResponseDecoder {
         &self,
         response: reqwest::Response,
         start: Instant,
+        decompress: bool,
+        download: &Download,
     ) -> impl Future<Output = Response> + Send;
 }
 
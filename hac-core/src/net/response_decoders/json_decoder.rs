@@ -1,3 +1,7 @@
+use crate::net::certificate;
+use crate::net::charset;
+use crate::net::decompression::{self, Compression, ContentEncoding, DecompressionError};
+use crate::net::download::{self, Body, Download};
 use crate::net::{request_manager::Response, response_decoders::ResponseDecoder};
 use crate::text_object::TextObject;
 
@@ -6,32 +10,109 @@ use std::{ops::Add, time::Instant};
 pub struct JsonDecoder;
 
 impl ResponseDecoder for JsonDecoder {
-    async fn decode(&self, response: reqwest::Response, start: Instant) -> Response {
-        let duration = start.elapsed();
+    async fn decode(
+        &self,
+        mut response: reqwest::Response,
+        start: Instant,
+        decompress: bool,
+        download: &Download,
+    ) -> Response {
         let headers = Some(response.headers().to_owned());
         let status = Some(response.status());
+        let remote_addr = response.remote_addr();
+        let version = Some(response.version());
+        let certificate = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .and_then(certificate::parse);
         let headers_size: u64 = response
             .headers()
             .iter()
             .map(|(k, v)| k.as_str().len().add(v.as_bytes().len()).add(4) as u64)
             .sum();
+        let encoding = response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(ContentEncoding::from_header);
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
 
-        let mut body: Option<String> = None;
-        let mut pretty_body = None;
-
-        if response.content_length().is_some_and(|len| len.gt(&0)) {
-            if let Ok(body_str) = response.text().await {
-                let pretty_body_str = jsonxf::pretty_print(&body_str).unwrap_or_default();
-                pretty_body = Some(TextObject::from(&pretty_body_str));
-                body = Some(body_str);
+        let decode = encoding.filter(|_| decompress);
+        let (bytes, spilled, wire_size) =
+            match download::read_body(&mut response, download, decode).await {
+                Ok(Body::Memory(bytes)) => {
+                    let wire_size = bytes.len() as u64;
+                    (bytes, None, wire_size)
+                }
+                Ok(Body::Spilled {
+                    preview,
+                    file,
+                    wire_size,
+                }) => (preview, Some(file), wire_size),
+                Err(e) => return Response::error(e, start),
             };
-        }
+        let duration = start.elapsed();
 
-        let body_size = body.as_ref().map(|body| body.len()).unwrap_or_default() as u64;
+        let mut compression = encoding.map(|encoding| Compression {
+            encoding,
+            wire_size,
+            decompressed: decompress,
+        });
+
+        // spilled bodies were decompressed on their way to the file already.
+        // The ones kept in memory are decompressed up to the same limit, past
+        // it they are spilled too. A body that can't be decompressed is kept
+        // as received, so the status and headers are still there to tell
+        // what went wrong
+        let mut cause = None;
+        let (bytes, spilled) = match decode.filter(|_| spilled.is_none()) {
+            Some(encoding) => {
+                let limit = download.max_in_memory;
+                let decompressed = match decompression::decompress_within(encoding, &bytes, limit) {
+                    Err(DecompressionError::OverLimit(_)) => {
+                        download::spill_decompressed(&bytes, download, encoding)
+                    }
+                    result => result.map(Body::Memory).map_err(|e| e.to_string()),
+                };
+                match decompressed {
+                    Ok(Body::Memory(decompressed)) => (decompressed, None),
+                    Ok(Body::Spilled { preview, file, .. }) => (preview, Some(file)),
+                    Err(e) => {
+                        cause = Some(e);
+                        if let Some(compression) = compression.as_mut() {
+                            compression.decompressed = false;
+                        }
+                        (bytes, None)
+                    }
+                }
+            }
+            None => (bytes, spilled),
+        };
+
+        let body_size = match spilled.as_ref() {
+            Some(file) => file.size,
+            None => bytes.len() as u64,
+        };
         let size = headers_size.add(body_size);
 
+        // transcoded before anything else looks at the body, so the
+        // highlighter and the viewer only ever see utf-8
+        let decoded = charset::decode(bytes, content_type.as_deref());
+        let body = decoded.text;
+
+        let pretty_body = body.as_ref().map(|body| {
+            let pretty_body_str = jsonxf::pretty_print(body).unwrap_or_default();
+            TextObject::from(&pretty_body_str)
+        });
+
         Response {
             body,
+            binary_body: decoded.binary,
             pretty_body,
             headers,
             duration,
@@ -39,8 +120,87 @@ impl ResponseDecoder for JsonDecoder {
             size: Some(size),
             headers_size: Some(headers_size),
             body_size: Some(body_size),
-            cause: None,
-            is_error: false,
+            compression,
+            charset: decoded.charset,
+            raw_body: decoded.raw,
+            remote_addr,
+            version,
+            certificate,
+            spilled,
+            cause,
+            ..Default::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_corrupt_bodies_are_kept_as_received() {
+        let body = b"\x1f\x8b\x08\x00not really gzip".to_vec();
+        let response = hyper::Response::builder()
+            .status(200)
+            .header("content-encoding", "gzip")
+            .header("content-type", "application/json")
+            .body(body.clone())
+            .unwrap();
+
+        let response = JsonDecoder
+            .decode(
+                reqwest::Response::from(response),
+                Instant::now(),
+                true,
+                &Download::default(),
+            )
+            .await;
+
+        assert!(!response.is_error);
+        assert_eq!(response.status, Some(reqwest::StatusCode::OK));
+        assert!(response.headers.is_some());
+        assert!(response
+            .cause
+            .is_some_and(|cause| cause.starts_with("failed to decompress the gzip body")));
+        assert_eq!(response.binary_body, Some(body));
+        assert!(!response.compression.unwrap().decompressed);
+    }
+
+    #[tokio::test]
+    async fn test_bodies_that_inflate_past_the_limit_are_spilled() {
+        // 8MiB of zeros gzipped, a few kilobytes on the wire
+        let content = vec![0; 8 * 1024 * 1024];
+        let deflated = miniz_oxide::deflate::compress_to_vec(&content, 6);
+        let mut body = vec![31, 139, 8, 0, 0, 0, 0, 0, 2, 255];
+        body.extend(deflated);
+        body.extend(0u32.to_le_bytes());
+        body.extend((content.len() as u32).to_le_bytes());
+
+        let response = hyper::Response::builder()
+            .status(200)
+            .header("content-encoding", "gzip")
+            .body(body.clone())
+            .unwrap();
+        let download = Download {
+            max_in_memory: Some(64 * 1024),
+            on_progress: None,
+        };
+        assert!((body.len() as u64).lt(&download.max_in_memory.unwrap()));
+
+        let response = JsonDecoder
+            .decode(
+                reqwest::Response::from(response),
+                Instant::now(),
+                true,
+                &download,
+            )
+            .await;
+
+        assert!(response.cause.is_none());
+        let file = response.spilled.expect("body should have been spilled");
+        assert_eq!(file.size, content.len() as u64);
+        assert_eq!(response.body_size, Some(content.len() as u64));
+        assert_eq!(response.body.unwrap().len(), download::PREVIEW_SIZE);
+        assert_eq!(response.compression.unwrap().wire_size, body.len() as u64);
+    }
+}
@@ -0,0 +1,606 @@
+use crate::net::certificate;
+
+use std::time::SystemTime;
+
+use reqwest::header::{HeaderName, HeaderValue};
+
+pub const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+/// sent as the payload hash when the body is streamed and can't be hashed
+/// before sending
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// how the path of the url is written on the canonical request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathEncoding {
+    /// s3 signs the path as it is, encoded once
+    Once,
+    /// every other service signs the path as it is sent, which is already
+    /// encoded, encoded again after removing its dot segments and empty
+    /// ones
+    Twice,
+}
+
+impl PathEncoding {
+    pub fn for_service(service: &str) -> Self {
+        match service {
+            "s3" => PathEncoding::Once,
+            _ => PathEncoding::Twice,
+        }
+    }
+}
+
+/// everything needed to sign a request besides the request itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct SigningParams<'a> {
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+    pub region: &'a str,
+    pub service: &'a str,
+    pub session_token: Option<&'a str>,
+    /// as in the `x-amz-date` header, eg: `20150830T123600Z`
+    pub timestamp: String,
+}
+
+impl SigningParams<'_> {
+    fn date(&self) -> &str {
+        self.timestamp.get(..8).unwrap_or_default()
+    }
+
+    fn scope(&self) -> String {
+        format!(
+            "{}/{}/{}/aws4_request",
+            self.date(),
+            self.region,
+            self.service
+        )
+    }
+}
+
+/// formats `time` as sigv4 expects it on `x-amz-date`, eg: `20150830T123600Z`
+pub fn amz_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let (year, month, day) = certificate::civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// adds `x-amz-date`, the payload hash, the session token when there is one
+/// and the `Authorization` header to `request`. Only the host, the content
+/// type and the `x-amz-*` headers are signed, so a proxy adding headers of
+/// its own doesn't break the signature
+pub fn sign(request: &mut reqwest::Request, params: &SigningParams) -> Result<(), String> {
+    let payload_hash = match request.body() {
+        None => sha256_hex(b""),
+        Some(body) => match body.as_bytes() {
+            Some(bytes) => sha256_hex(bytes),
+            None => UNSIGNED_PAYLOAD.into(),
+        },
+    };
+
+    let headers = request.headers_mut();
+    headers.insert("x-amz-date", header_value(&params.timestamp)?);
+    headers.insert("x-amz-content-sha256", header_value(&payload_hash)?);
+    if let Some(token) = params.session_token {
+        headers.insert("x-amz-security-token", header_value(token)?);
+    }
+
+    // the host is only added while connecting, but it is always signed
+    let mut signed = vec![];
+    if !request.headers().contains_key(reqwest::header::HOST) {
+        if let Some(host) = host(request.url()) {
+            signed.push(("host".to_string(), host));
+        }
+    }
+    signed.extend(
+        request
+            .headers()
+            .iter()
+            .filter(|(name, _)| is_signed(name))
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            }),
+    );
+
+    let authorization = authorization(
+        request.method().as_str(),
+        request.url(),
+        &signed,
+        &payload_hash,
+        params,
+    );
+    request.headers_mut().insert(
+        HeaderName::from_static("authorization"),
+        header_value(&authorization)?,
+    );
+
+    Ok(())
+}
+
+/// the value of the `Authorization` header for a request with `headers`,
+/// which must include the host
+pub fn authorization(
+    method: &str,
+    url: &reqwest::Url,
+    headers: &[(String, String)],
+    payload_hash: &str,
+    params: &SigningParams,
+) -> String {
+    let path_encoding = PathEncoding::for_service(params.service);
+    let (canonical, signed_headers) =
+        canonical_request(method, url, headers, payload_hash, path_encoding);
+    let signature = signature(params, &string_to_sign(params, &canonical));
+    format!(
+        "{ALGORITHM} Credential={}/{}, SignedHeaders={signed_headers}, Signature={signature}",
+        params.access_key,
+        params.scope()
+    )
+}
+
+/// the canonical form of the request, and the names of the headers that
+/// were signed, as `host;x-amz-date`
+pub fn canonical_request(
+    method: &str,
+    url: &reqwest::Url,
+    headers: &[(String, String)],
+    payload_hash: &str,
+    path_encoding: PathEncoding,
+) -> (String, String) {
+    let mut query = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key), uri_encode(&value)))
+        .collect::<Vec<_>>();
+    query.sort();
+    let query = query
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    // headers with the same name are signed once, with their values joined
+    let mut canonical_headers: Vec<(String, String)> = vec![];
+    let mut sorted = headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), normalize_value(value)))
+        .collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, value) in sorted {
+        match canonical_headers.last_mut() {
+            Some((last, values)) if name.eq(last) => {
+                values.push(',');
+                values.push_str(&value);
+            }
+            _ => canonical_headers.push((name, value)),
+        }
+    }
+
+    let signed_headers = canonical_headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers = canonical_headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect::<String>();
+
+    let path = canonical_path(url.path(), path_encoding);
+
+    let canonical = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, path, query, canonical_headers, signed_headers, payload_hash
+    );
+    (canonical, signed_headers)
+}
+
+/// the path as the canonical request has it, the slashes between segments
+/// are never encoded
+fn canonical_path(path: &str, path_encoding: PathEncoding) -> String {
+    let path = match path_encoding {
+        PathEncoding::Once => path
+            .split('/')
+            .map(|segment| uri_encode(&percent_decode(segment)))
+            .collect::<Vec<_>>()
+            .join("/"),
+        PathEncoding::Twice => remove_dot_segments(path)
+            .split('/')
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/"),
+    };
+    match path.starts_with('/') {
+        true => path,
+        false => format!("/{path}"),
+    }
+}
+
+/// drops the `.` and empty segments and goes back one on `..`, keeping the
+/// trailing slash, so `//a/./b/../` is `/a/`
+fn remove_dot_segments(path: &str) -> String {
+    let mut segments: Vec<&str> = vec![];
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => _ = segments.pop(),
+            segment => segments.push(segment),
+        }
+    }
+
+    let trailing = ["/", "/.", "/.."].iter().any(|end| path.ends_with(end));
+    match (segments.is_empty(), trailing) {
+        (true, _) => "/".into(),
+        (false, true) => format!("/{}/", segments.join("/")),
+        (false, false) => format!("/{}", segments.join("/")),
+    }
+}
+
+/// headers that are part of the signature. An authorization header already
+/// on the request is replaced by the signature, so it can't be one of them
+fn is_signed(name: &HeaderName) -> bool {
+    name.eq(&reqwest::header::HOST)
+        || name.eq(&reqwest::header::CONTENT_TYPE)
+        || name.as_str().starts_with("x-amz-")
+}
+
+fn string_to_sign(params: &SigningParams, canonical_request: &str) -> String {
+    format!(
+        "{ALGORITHM}\n{}\n{}\n{}",
+        params.timestamp,
+        params.scope(),
+        sha256_hex(canonical_request.as_bytes())
+    )
+}
+
+/// the key is derived from the secret for the date, region and service of
+/// the request, so it is never used directly
+pub fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    [date, region, service, "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{secret_key}").into_bytes(), |key, part| {
+            hmac(&key, part.as_bytes())
+        })
+}
+
+fn signature(params: &SigningParams, string_to_sign: &str) -> String {
+    let key = signing_key(
+        params.secret_key,
+        params.date(),
+        params.region,
+        params.service,
+    );
+    hex(&hmac(&key, string_to_sign.as_bytes()))
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    ring::hmac::sign(&key, data).as_ref().to_vec()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex(ring::digest::digest(&ring::digest::SHA256, data).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// percent encodes everything but the unreserved characters, spaces
+/// included, which is stricter than what urls usually need
+fn uri_encode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// the bytes of `input` with its `%XX` escapes decoded, escapes that aren't
+/// valid are kept as they are
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = bytes
+            .get(idx + 1..idx + 3)
+            .filter(|_| bytes[idx].eq(&b'%'))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// trims the value and collapses runs of spaces into a single one
+fn normalize_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn host(url: &reqwest::Url) -> Option<String> {
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+fn header_value(value: &str) -> Result<HeaderValue, String> {
+    HeaderValue::from_str(value).map_err(|e| format!("invalid signing header {value:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn example_params(service: &'static str) -> SigningParams<'static> {
+        SigningParams {
+            access_key: "AKIDEXAMPLE",
+            secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            region: "us-east-1",
+            service,
+            session_token: None,
+            timestamp: "20150830T123600Z".into(),
+        }
+    }
+
+    fn suite_headers(extra: &[(&str, &str)]) -> Vec<(String, String)> {
+        [
+            ("Host", "example.amazonaws.com"),
+            ("X-Amz-Date", "20150830T123600Z"),
+        ]
+        .iter()
+        .chain(extra)
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_signing_key_derivation() {
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_iam_list_users_example() {
+        let params = example_params("iam");
+        let url =
+            reqwest::Url::parse("https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08")
+                .unwrap();
+        let headers = [
+            (
+                "Content-Type",
+                "application/x-www-form-urlencoded; charset=utf-8",
+            ),
+            ("Host", "iam.amazonaws.com"),
+            ("X-Amz-Date", "20150830T123600Z"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        let (canonical, signed) =
+            canonical_request("GET", &url, &headers, EMPTY_HASH, PathEncoding::Twice);
+        assert_eq!(signed, "content-type;host;x-amz-date");
+        assert_eq!(
+            sha256_hex(canonical.as_bytes()),
+            "f536975d06c0309214f805bb90ccff089219ecd68b2577efef23edd43b7e1a59"
+        );
+        assert_eq!(
+            authorization("GET", &url, &headers, EMPTY_HASH, &params),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+
+    #[test]
+    fn test_suite_vectors() {
+        let params = example_params("service");
+        let cases = [
+            (
+                "GET",
+                "https://example.amazonaws.com/",
+                suite_headers(&[]),
+                "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+            ),
+            (
+                "POST",
+                "https://example.amazonaws.com/",
+                suite_headers(&[]),
+                "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b",
+            ),
+            (
+                "GET",
+                "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+                suite_headers(&[]),
+                "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500",
+            ),
+            // get-slash, get-slash-dot-slash, get-relative and
+            // get-relative-relative are all signed as the root
+            (
+                "GET",
+                "https://example.amazonaws.com//",
+                suite_headers(&[]),
+                "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+            ),
+            (
+                "GET",
+                "https://example.amazonaws.com/./",
+                suite_headers(&[]),
+                "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+            ),
+            (
+                "GET",
+                "https://example.amazonaws.com/example/..",
+                suite_headers(&[]),
+                "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+            ),
+            (
+                "GET",
+                "https://example.amazonaws.com/example1/example2/../..",
+                suite_headers(&[]),
+                "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+            ),
+            // get-slashes
+            (
+                "GET",
+                "https://example.amazonaws.com//example//",
+                suite_headers(&[]),
+                "9a624bd73a37c9a373b5312afbebe7a714a789de108f0bdfe846570885f57e84",
+            ),
+            // get-slash-pointless-dot
+            (
+                "GET",
+                "https://example.amazonaws.com/./example",
+                suite_headers(&[]),
+                "ef75d96142cf21edca26f06005da7988e4f8dc83a165a80865db7089db637ec5",
+            ),
+            // get-unreserved
+            (
+                "GET",
+                "https://example.amazonaws.com/-._~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+                suite_headers(&[]),
+                "07ef7494c76fa4850883e2b006601f940f8a34d404d0cfa977f52a65bbf5f24f",
+            ),
+        ];
+
+        for (method, url, headers, expected) in cases {
+            let url = reqwest::Url::parse(url).unwrap();
+            let authorization = authorization(method, &url, &headers, EMPTY_HASH, &params);
+            assert!(
+                authorization.ends_with(&format!("Signature={expected}")),
+                "{method} {url}: {authorization}"
+            );
+        }
+    }
+
+    /// the suite signs get-space and get-utf8 with their path encoded once,
+    /// which is what s3 does. Other services encode what is sent again
+    #[test]
+    fn test_paths_are_encoded_per_service() {
+        let params = example_params("service");
+        let sign_once = |url: &str| {
+            let url = reqwest::Url::parse(url).unwrap();
+            let (canonical, _) = canonical_request(
+                "GET",
+                &url,
+                &suite_headers(&[]),
+                EMPTY_HASH,
+                PathEncoding::Once,
+            );
+            (
+                canonical.lines().nth(1).unwrap().to_string(),
+                signature(&params, &string_to_sign(&params, &canonical)),
+            )
+        };
+
+        assert_eq!(
+            sign_once("https://example.amazonaws.com/example space/"),
+            (
+                "/example%20space/".into(),
+                "652487583200325589f1fba4c7e578f72c47cb61beeca81406b39ddec1366741".into()
+            )
+        );
+        assert_eq!(
+            sign_once("https://example.amazonaws.com/ሴ"),
+            (
+                "/%E1%88%B4".into(),
+                "8318018e0b0f223aa2bbf98705b62bb787dc9c0e678f255a891fd03141be5d85".into()
+            )
+        );
+
+        let paths = [
+            ("/example space/", "/example%2520space/", "/example%20space/"),
+            ("/ሴ", "/%25E1%2588%25B4", "/%E1%88%B4"),
+            // double-url-encode and double-encode-path
+            (
+                "/2015-03-31/functions/arn%3Aaws%3Alambda%3Aus-west-2%3A892717189312%3Afunction%3Amy-rusty-fun/invocations",
+                "/2015-03-31/functions/arn%253Aaws%253Alambda%253Aus-west-2%253A892717189312%253Afunction%253Amy-rusty-fun/invocations",
+                "/2015-03-31/functions/arn%3Aaws%3Alambda%3Aus-west-2%3A892717189312%3Afunction%3Amy-rusty-fun/invocations",
+            ),
+            (
+                "/test/@connections/JBDvjfGEIAMCERw%3D",
+                "/test/%40connections/JBDvjfGEIAMCERw%253D",
+                "/test/%40connections/JBDvjfGEIAMCERw%3D",
+            ),
+            // s3 keys are signed as they are
+            ("/a//b//", "/a/b/", "/a//b//"),
+        ];
+        for (path, twice, once) in paths {
+            let url = reqwest::Url::parse("https://example.amazonaws.com").unwrap();
+            let url = url.join(path).unwrap();
+            assert_eq!(canonical_path(url.path(), PathEncoding::Twice), twice);
+            assert_eq!(canonical_path(url.path(), PathEncoding::Once), once);
+        }
+    }
+
+    #[test]
+    fn test_amz_date() {
+        let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1440938160);
+        assert_eq!(amz_date(time), "20150830T123600Z");
+    }
+
+    #[test]
+    fn test_signing_a_built_request() {
+        let client = reqwest::Client::new();
+        let mut request = client
+            .post("https://example.amazonaws.com:8443/items")
+            .header("Content-Type", "application/json")
+            .header("Via", "1.1 proxy")
+            .header("Authorization", "Bearer old")
+            .body(r#"{"id":1}"#)
+            .build()
+            .unwrap();
+        let params = SigningParams {
+            session_token: Some("token"),
+            ..example_params("execute-api")
+        };
+
+        sign(&mut request, &params).unwrap();
+
+        let header = |name: &str| request.headers().get(name).unwrap().to_str().unwrap();
+        assert_eq!(header("x-amz-date"), "20150830T123600Z");
+        assert_eq!(
+            header("x-amz-content-sha256"),
+            sha256_hex(br#"{"id":1}"#).as_str()
+        );
+        assert_eq!(header("x-amz-security-token"), "token");
+        assert!(header("authorization").contains(
+            "SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date;x-amz-security-token,"
+        ));
+    }
+}
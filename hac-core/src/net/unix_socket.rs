@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum UnixSocketError {
+    NotFound(PathBuf),
+    /// something exists on the path, but it is not a socket
+    NotASocket(PathBuf),
+    /// unix sockets only exist on unix
+    Unsupported,
+    /// the socket refused the connection or the exchange failed midway, the
+    /// reason is kept for display only
+    Connection(PathBuf, String),
+}
+
+impl std::fmt::Display for UnixSocketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnixSocketError::NotFound(path) => {
+                write!(f, "unix socket {} does not exist", path.display())
+            }
+            UnixSocketError::NotASocket(path) => {
+                write!(f, "{} is not a unix socket", path.display())
+            }
+            UnixSocketError::Unsupported => {
+                write!(f, "unix sockets are not supported on this platform")
+            }
+            UnixSocketError::Connection(path, reason) => {
+                write!(
+                    f,
+                    "failed to talk to unix socket {}: {}",
+                    path.display(),
+                    reason
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnixSocketError {}
+
+/// makes sure `path` is a socket before connecting to it, so a typo on the
+/// path doesn't show up as a cryptic io error
+#[cfg(unix)]
+pub fn check(path: &Path) -> Result<(), UnixSocketError> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => Ok(()),
+        Ok(_) => Err(UnixSocketError::NotASocket(path.to_path_buf())),
+        Err(_) => Err(UnixSocketError::NotFound(path.to_path_buf())),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn check(_path: &Path) -> Result<(), UnixSocketError> {
+    Err(UnixSocketError::Unsupported)
+}
+
+/// sends `request` over the socket at `path`. The url still provides the
+/// path, query and `Host` header, but the connection is always plain http,
+/// as nothing speaks tls over a local socket
+#[cfg(unix)]
+pub async fn send(
+    request: reqwest::Request,
+    path: &Path,
+) -> Result<reqwest::Response, UnixSocketError> {
+    use http_body_util::BodyExt;
+    use hyper_util::rt::TokioIo;
+
+    check(path)?;
+    let failed =
+        |e: &dyn std::fmt::Display| UnixSocketError::Connection(path.into(), e.to_string());
+
+    let host = request.url().host_str().map(String::from);
+    let mut request = hyper::Request::<reqwest::Body>::try_from(request).map_err(|e| failed(&e))?;
+
+    // over a socket there is no proxy to read an absolute uri, servers
+    // expect only the path
+    let origin = request
+        .uri()
+        .path_and_query()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "/".into());
+    *request.uri_mut() = origin.parse().map_err(|e| failed(&e))?;
+    if let Some(host) = host.filter(|_| !request.headers().contains_key(hyper::header::HOST)) {
+        let host = host.parse().map_err(|e| failed(&e))?;
+        request.headers_mut().insert(hyper::header::HOST, host);
+    }
+
+    let stream = tokio::net::UnixStream::connect(path)
+        .await
+        .map_err(|e| failed(&e))?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(|e| failed(&e))?;
+    // the connection only makes progress while polled, errors surface on
+    // `send_request` so the result here can be ignored
+    tokio::spawn(connection);
+
+    let response = sender.send_request(request).await.map_err(|e| failed(&e))?;
+    let (parts, body) = response.into_parts();
+    let body = body.collect().await.map_err(|e| failed(&e))?.to_bytes();
+
+    Ok(hyper::Response::from_parts(parts, body).into())
+}
+
+#[cfg(not(unix))]
+pub async fn send(
+    _request: reqwest::Request,
+    _path: &Path,
+) -> Result<reqwest::Response, UnixSocketError> {
+    Err(UnixSocketError::Unsupported)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_check_explains_bad_paths() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = dir.path().join("missing.sock");
+        assert!(matches!(check(&missing), Err(UnixSocketError::NotFound(_))));
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(matches!(check(&file), Err(UnixSocketError::NotASocket(_))));
+
+        let socket = dir.path().join("ok.sock");
+        let _listener = UnixListener::bind(&socket).unwrap();
+        assert!(check(&socket).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_requests_use_the_url_path_and_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("docker.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = vec![];
+            let mut buf = [0; 1024];
+            while !received.windows(4).any(|w| w.eq(b"\r\n\r\n")) {
+                let read = stream.read(&mut buf).unwrap();
+                received.extend_from_slice(&buf[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n[]")
+                .unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let request = reqwest::Client::new()
+            .get("http://docker/containers/json?all=1")
+            .build()
+            .unwrap();
+        let response = send(request, &socket).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "[]");
+
+        let received = server.join().unwrap().to_lowercase();
+        assert!(received.starts_with("get /containers/json?all=1 http/1.1\r\n"));
+        assert!(received.contains("host: docker\r\n"));
+    }
+}
@@ -0,0 +1,244 @@
+use std::ops::{Add, Range, Sub};
+
+#[derive(Debug, PartialEq)]
+pub enum UriError {
+    /// a character like a newline or a tab in the middle of the uri, with
+    /// the position it is at, counted in characters from 1
+    ControlCharacter { character: char, position: usize },
+}
+
+impl std::fmt::Display for UriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UriError::ControlCharacter {
+                character,
+                position,
+            } => {
+                let name = match character {
+                    '\n' => "a newline".to_string(),
+                    '\r' => "a carriage return".to_string(),
+                    '\t' => "a tab".to_string(),
+                    c => format!("the control character U+{:04X}", *c as u32),
+                };
+                write!(f, "URL contains {name} at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UriError {}
+
+/// a uri cleaned up to be sent
+#[derive(Debug, PartialEq)]
+pub struct Normalized {
+    pub uri: String,
+    /// things worth telling that don't stop the uri from being sent
+    pub warnings: Vec<String>,
+}
+
+/// trims the whitespace around `uri`, usually left over from pasting it,
+/// and refuses control characters inside it, as they would either fail to
+/// parse or go out percent encoded. Spaces are sent percent encoded, which
+/// is rarely what was meant, so they are warned about
+pub fn normalize(uri: &str) -> Result<Normalized, UriError> {
+    let uri = uri.trim();
+    if let Some((idx, character)) = uri.chars().enumerate().find(|(_, c)| c.is_control()) {
+        return Err(UriError::ControlCharacter {
+            character,
+            position: idx + 1,
+        });
+    }
+
+    let mut warnings = vec![];
+    match uri.matches(' ').count() {
+        0 => {}
+        1 => warnings.push("URL has a space, it is sent as %20".into()),
+        spaces => warnings.push(format!("URL has {spaces} spaces, they are sent as %20")),
+    }
+
+    Ok(Normalized {
+        uri: uri.to_string(),
+        warnings,
+    })
+}
+
+/// puts `path` after `base` with a single slash between them, however many
+/// either side had
+pub fn join(base: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// byte ranges of the http and https urls on `text`. A url ends at
+/// whitespace, quotes, angle brackets or a backslash, so json strings and
+/// html attributes give just the url. Punctuation at the end is left out,
+/// as are closing brackets without an opening one on the url, so a url
+/// ending a sentence or in parentheses doesn't take them along
+pub fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let lowercase = text.to_ascii_lowercase();
+    let mut urls = vec![];
+    let mut from = 0;
+
+    while let Some(found) = lowercase[from..].find("http") {
+        let start = from.add(found);
+        let rest = &lowercase[start..];
+        let scheme = match (rest.starts_with("https://"), rest.starts_with("http://")) {
+            (true, _) => "https://".len(),
+            (_, true) => "http://".len(),
+            _ => {
+                from = start.add("http".len());
+                continue;
+            }
+        };
+
+        let len = text[start..]
+            .find(|c: char| c.is_whitespace() || c.is_control() || "\"'<>`\\".contains(c))
+            .unwrap_or(text.len().sub(start));
+        let end = start.add(trim_url_end(&text[start..start.add(len)]));
+        // the scheme inside a word, like `xhttp://`, is not the start of a url
+        let inside_word = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric());
+
+        if !inside_word && end.gt(&start.add(scheme)) {
+            urls.push(start..end);
+        }
+        from = start.add(len.max(scheme));
+    }
+
+    urls
+}
+
+/// length of `url` without the punctuation and unbalanced closing brackets
+/// at its end
+fn trim_url_end(url: &str) -> usize {
+    let mut url = url;
+    while let Some(last) = url.chars().next_back() {
+        let unbalanced = |open: char| url.matches(last).count().gt(&url.matches(open).count());
+        let trim = match last {
+            '.' | ',' | ';' | ':' | '!' | '?' => true,
+            ')' => unbalanced('('),
+            ']' => unbalanced('['),
+            '}' => unbalanced('{'),
+            _ => false,
+        };
+        if !trim {
+            break;
+        }
+        url = &url[..url.len().sub(last.len_utf8())];
+    }
+    url.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_surrounding_whitespace_is_trimmed() {
+        let normalized = normalize("  https://api.example.com/users\n").unwrap();
+        assert_eq!(normalized.uri, "https://api.example.com/users");
+        assert!(normalized.warnings.is_empty());
+
+        let normalized = normalize("\thttps://api.example.com/a b c\r\n").unwrap();
+        assert_eq!(normalized.uri, "https://api.example.com/a b c");
+        assert_eq!(
+            normalized.warnings,
+            vec!["URL has 2 spaces, they are sent as %20".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_control_characters_inside_are_refused() {
+        let error = normalize(" https://api.example.com/users\n?page=2").unwrap_err();
+        assert_eq!(
+            error,
+            UriError::ControlCharacter {
+                character: '\n',
+                position: 30
+            }
+        );
+        assert_eq!(error.to_string(), "URL contains a newline at position 30");
+
+        let error = normalize("https://é.dev/\u{7}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "URL contains the control character U+0007 at position 15"
+        );
+    }
+
+    #[test]
+    fn test_joining_keeps_a_single_slash() {
+        let cases = [
+            ("https://api.example.com", "/users"),
+            ("https://api.example.com/", "/users"),
+            ("https://api.example.com//", "//users"),
+            ("https://api.example.com", "users"),
+        ];
+        for (base, path) in cases {
+            assert_eq!(join(base, path), "https://api.example.com/users");
+        }
+
+        assert_eq!(
+            join("{{host}}/v1/", "/users?next=/a"),
+            "{{host}}/v1/users?next=/a"
+        );
+        assert_eq!(
+            join("https://api.example.com/", "/"),
+            "https://api.example.com/"
+        );
+    }
+
+    #[test]
+    fn test_finding_urls_in_text() {
+        let found = |text: &str| {
+            find_urls(text)
+                .into_iter()
+                .map(|range| text[range].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            found(r#"  "next": "https://api.example.com/users?page=2","#),
+            vec!["https://api.example.com/users?page=2"]
+        );
+        assert_eq!(
+            found("see http://a.dev/docs. Or (https://b.dev/x), then https://c.dev!"),
+            vec!["http://a.dev/docs", "https://b.dev/x", "https://c.dev"]
+        );
+        assert_eq!(
+            found("[wiki](https://en.wikipedia.org/wiki/Rust_(language))"),
+            vec!["https://en.wikipedia.org/wiki/Rust_(language)"]
+        );
+        assert_eq!(
+            found(r#"<a href='HTTPS://a.dev/é?q=1'>{"u":"https://{{host}}/v1"}</a>"#),
+            vec!["HTTPS://a.dev/é?q=1", "https://{{host}}/v1"]
+        );
+        assert_eq!(
+            found(r#""escaped \"https://a.dev/x\" quotes""#),
+            vec!["https://a.dev/x"]
+        );
+    }
+
+    #[test]
+    fn test_things_that_are_not_urls() {
+        let cases = [
+            "",
+            "http",
+            "https://",
+            "http://.",
+            "xhttps://a.dev",
+            "ftp://a.dev",
+            "https:/a.dev",
+            "https:\\/\\/a.dev",
+            "httpie is a client",
+        ];
+        for text in cases {
+            assert!(find_urls(text).is_empty(), "{text:?}");
+        }
+    }
+}
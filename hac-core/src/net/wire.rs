@@ -0,0 +1,251 @@
+use crate::collection::types::Request;
+use crate::net::request_client::{ClientOptions, RequestClient};
+
+/// stands in for urls that can't be parsed yet, usually because a variable
+/// on them is unresolved, so the rest of the request can still be built
+const STAND_IN_URL: &str = "http://unresolved.invalid/";
+
+/// a request as it goes on the wire, built by the same client that sends it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WireRequest {
+    pub method: String,
+    pub url: String,
+    /// in the order they are sent, `Host` included
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl WireRequest {
+    pub fn new(request: &Request, options: &ClientOptions) -> Result<Self, String> {
+        let url = reqwest::Url::parse(&request.uri).ok();
+        let buildable = match url {
+            Some(_) => request.clone(),
+            None => Request {
+                uri: STAND_IN_URL.into(),
+                ..request.clone()
+            },
+        };
+
+        let client = RequestClient::new(&buildable, options).map_err(|e| e.to_string())?;
+        let built = client
+            .builder(&buildable)?
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        // the http library adds the host while connecting, it is the first
+        // header to go out
+        let mut headers = vec![];
+        if let Some(host) = url.as_ref().and_then(host_header) {
+            headers.push(("Host".into(), host));
+        }
+        headers.extend(built.headers().iter().map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into(),
+            )
+        }));
+
+        Ok(WireRequest {
+            method: built.method().to_string(),
+            // placeholders would come out percent encoded, leaving them as
+            // written keeps them recognizable
+            url: match url {
+                Some(url) if !has_placeholder(&request.uri) => url.to_string(),
+                _ => request.uri.clone(),
+            },
+            headers,
+            body: built
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| String::from_utf8_lossy(bytes).into()),
+        })
+    }
+
+    /// the request as a raw http/1.1 message, like it would be written to
+    /// the connection
+    pub fn to_http(&self) -> String {
+        let target = reqwest::Url::parse(&self.url)
+            .ok()
+            .filter(|_| !has_placeholder(&self.url))
+            .map(|url| match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            })
+            .unwrap_or_else(|| self.url.clone());
+
+        let mut message = format!("{} {} HTTP/1.1\r\n", self.method, target);
+        for (name, value) in self.headers.iter() {
+            message.push_str(&format!("{name}: {value}\r\n"));
+        }
+        message.push_str("\r\n");
+        if let Some(body) = self.body.as_ref() {
+            message.push_str(body);
+        }
+        message
+    }
+}
+
+fn has_placeholder(uri: &str) -> bool {
+    uri.contains("{{")
+}
+
+fn host_header(url: &reqwest::Url) -> Option<String> {
+    let host = url.host_str()?;
+    match url.port() {
+        Some(port) => Some(format!("{host}:{port}")),
+        None => Some(host.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::{AuthMethod, BodyType, HeaderMap, RequestMethod};
+    use crate::net::body;
+    use crate::test_utils;
+
+    fn make_request(method: RequestMethod, uri: &str, body: Option<&str>) -> Request {
+        Request {
+            method,
+            headers: Some(vec![HeaderMap {
+                pair: ("Authorization".into(), "Bearer abc".into()),
+                enabled: true,
+            }]),
+            body: body.map(String::from),
+            ..test_utils::make_request("users", uri)
+        }
+    }
+
+    #[test]
+    fn test_bodies_go_out_encoded_for_their_type() {
+        let form = "name=Jane Doe\nrole=admin";
+        let cases = [
+            (
+                BodyType::Text,
+                "text/plain",
+                "name=Jane Doe\nrole=admin".to_string(),
+            ),
+            (
+                BodyType::UrlEncoded,
+                "application/x-www-form-urlencoded",
+                "name=Jane+Doe&role=admin".to_string(),
+            ),
+            (
+                BodyType::Multipart,
+                "multipart/form-data; boundary=",
+                format!("--{}\r\n", body::boundary(form)),
+            ),
+            (
+                BodyType::GraphQl,
+                "application/json",
+                r#"{"query":"name=Jane Doe\nrole=admin"}"#.to_string(),
+            ),
+        ];
+
+        for (body_type, content_type, sent) in cases {
+            let mut request =
+                make_request(RequestMethod::Post, "http://localhost/users", Some(form));
+            request.body_type = Some(body_type.clone());
+            let wire = WireRequest::new(&request, &ClientOptions::default()).unwrap();
+
+            let header = wire
+                .headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+                .map(|(_, value)| value.clone())
+                .unwrap();
+            assert!(header.starts_with(content_type), "{body_type}: {header}");
+            assert!(
+                wire.body.as_deref().unwrap().starts_with(&sent),
+                "{body_type}"
+            );
+        }
+
+        // the boundary on the header is the one separating the parts
+        let mut request = make_request(RequestMethod::Post, "http://localhost/users", Some(form));
+        request.body_type = Some(BodyType::Multipart);
+        let wire = WireRequest::new(&request, &ClientOptions::default()).unwrap();
+        let boundary = body::boundary(form);
+        assert!(wire
+            .headers
+            .iter()
+            .any(|(_, value)| value.ends_with(&format!("boundary={boundary}"))));
+        assert!(wire.body.unwrap().ends_with(&format!("--{boundary}--\r\n")));
+    }
+
+    #[test]
+    fn test_wire_request_matches_what_is_sent() {
+        let request = make_request(
+            RequestMethod::Post,
+            "http://localhost:3000/users?page=2",
+            Some(r#"{"name":"hac"}"#),
+        );
+        let wire = WireRequest::new(&request, &ClientOptions::default()).unwrap();
+
+        assert_eq!(wire.method, "POST");
+        assert_eq!(wire.url, "http://localhost:3000/users?page=2");
+        let names = wire
+            .headers
+            .iter()
+            .map(|(name, _)| name.to_lowercase())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["host", "authorization", "content-type", "accept-encoding"]
+        );
+        assert_eq!(wire.headers[0].1, "localhost:3000");
+        // bodies are sent as json strings
+        assert_eq!(wire.body.as_deref(), Some(r#""{\"name\":\"hac\"}""#));
+
+        let http = wire.to_http();
+        assert!(http.starts_with("POST /users?page=2 HTTP/1.1\r\nHost: localhost:3000\r\n"));
+        assert!(http.ends_with("\r\n\r\n\"{\\\"name\\\":\\\"hac\\\"}\""));
+    }
+
+    #[test]
+    fn test_signed_requests_show_the_signature_headers() {
+        let mut request = make_request(
+            RequestMethod::Get,
+            "https://example.execute-api.us-east-1.amazonaws.com/items",
+            None,
+        );
+        request.auth_method = Some(AuthMethod::AwsSigV4 {
+            access_key: "AKIDEXAMPLE".into(),
+            secret_key: "secret".into(),
+            region: "us-east-1".into(),
+            service: "execute-api".into(),
+            session_token: None,
+        });
+        let wire = WireRequest::new(&request, &ClientOptions::default()).unwrap();
+
+        let header = |name: &str| {
+            wire.headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+                .unwrap()
+        };
+        assert_eq!(header("x-amz-date").len(), "20150830T123600Z".len());
+        assert!(header("authorization").starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(header("authorization").contains(
+            "/us-east-1/execute-api/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date,"
+        ));
+    }
+
+    #[test]
+    fn test_unparsable_urls_still_build_the_rest() {
+        let request = make_request(RequestMethod::Get, "{{base_url}}/users", None);
+        let wire = WireRequest::new(&request, &ClientOptions::default()).unwrap();
+
+        assert_eq!(wire.url, "{{base_url}}/users");
+        assert_eq!(wire.headers[0].0.to_lowercase(), "authorization");
+        assert_eq!(wire.body, None);
+        assert!(wire
+            .to_http()
+            .starts_with("GET {{base_url}}/users HTTP/1.1\r\n"));
+
+        let request = make_request(RequestMethod::Get, "http://localhost/{{tenant}}", None);
+        let wire = WireRequest::new(&request, &ClientOptions::default()).unwrap();
+        assert_eq!(wire.url, "http://localhost/{{tenant}}");
+    }
+}
@@ -0,0 +1,818 @@
+use crate::assertions::{self, AssertionResult};
+use crate::captures::{self, CaptureResult};
+use crate::collection::types::{Request, RequestMethod};
+use crate::hooks;
+use crate::net::request_client::ClientOptions;
+use crate::net::send_request;
+use crate::net::uri;
+use crate::variables;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// options that control how a sequence of requests is executed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOptions {
+    /// how long to wait between two requests, useful for rate-limited APIs
+    pub delay: Duration,
+    /// stops the run on the first failing request, the remaining requests are
+    /// reported as skipped
+    pub bail: bool,
+    /// wether a 4xx response counts as a failure
+    pub fail_on_4xx: bool,
+    /// most requests sent on any second
+    pub max_rps: Option<f64>,
+    /// how many requests can be in flight at once, requests using values
+    /// captured by the ones in flight wait for them regardless
+    pub parallel: usize,
+    /// sends a request answered with `429` once more, after waiting for
+    /// what its `Retry-After` header asked
+    pub respect_retry_after: bool,
+    /// settings used to send every request of the run
+    pub client: ClientOptions,
+    /// runs the hooks set on the requests, which is only meant for
+    /// collections whose hooks were trusted. Otherwise the requests that have
+    /// hooks are pointed out as they run without them
+    pub run_hooks: bool,
+}
+
+impl RunOptions {
+    /// pacing from the config file, the rest are the defaults
+    pub fn new(config: &hac_config::RunnerConfig, client: ClientOptions) -> Self {
+        RunOptions {
+            delay: Duration::from_millis(config.delay_ms),
+            max_rps: config.max_rps,
+            parallel: config.parallel,
+            respect_retry_after: config.respect_retry_after,
+            client,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            delay: Duration::ZERO,
+            bail: false,
+            fail_on_4xx: true,
+            max_rps: None,
+            parallel: 1,
+            respect_retry_after: false,
+            client: ClientOptions::default(),
+            run_hooks: false,
+        }
+    }
+}
+
+/// outcome of a single request during a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub name: String,
+    pub method: RequestMethod,
+    pub status: Option<u16>,
+    pub duration: Duration,
+    pub passed: bool,
+    /// set when the request couldn't be sent at all, eg: unresolved variables
+    /// or a network error
+    pub error: Option<String>,
+    /// results of every assertion defined on the request, when there are
+    /// any, they decide wether the request passed instead of the status
+    pub assertions: Vec<AssertionResult>,
+    /// values captured from the response, these are already applied to the
+    /// variables used by the following requests of the run
+    pub captures: Vec<CaptureResult>,
+    /// bytes of the response, headers included
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl RunSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed.eq(&0)
+    }
+}
+
+/// why the run is holding off on sending requests
+#[derive(Debug, Clone, PartialEq)]
+pub enum PacingReason {
+    /// the configured delay between two requests
+    Delay,
+    /// the configured maximum requests per second was reached
+    RateLimit,
+    /// the server answered the request with `429` and asked for a wait
+    RetryAfter(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pacing {
+    pub wait: Duration,
+    pub reason: PacingReason,
+}
+
+impl std::fmt::Display for Pacing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let wait = self.wait.as_millis();
+        match &self.reason {
+            PacingReason::Delay => write!(f, "waiting {wait}ms between requests"),
+            PacingReason::RateLimit => write!(f, "waiting {wait}ms for rate limit"),
+            PacingReason::RetryAfter(name) => {
+                write!(f, "waiting {wait}ms to retry {name}, as the server asked")
+            }
+        }
+    }
+}
+
+/// events emitted while a run is in progress, so callers can display results
+/// as they come instead of waiting for the whole run to finish
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunEvent {
+    Started {
+        total: usize,
+    },
+    /// something the user should know about how the run goes, eg: requests
+    /// that can't run in parallel
+    Warning(String),
+    /// the run is about to wait before sending a request
+    Waiting(Pacing),
+    RequestFinished(RunResult),
+    Finished(RunSummary),
+}
+
+/// token bucket holding up to `rps` tokens, refilled at `rps` tokens per
+/// second. Every request takes a token, waiting for one when there are none
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    rps: f64,
+    tokens: f64,
+    refilled_at: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(rps: f64) -> Self {
+        let rps = rps.max(f64::MIN_POSITIVE);
+        RateLimiter {
+            rps,
+            tokens: rps.max(1.0),
+            refilled_at: None,
+        }
+    }
+
+    /// takes a token, returning how long to wait before it can be used.
+    /// Tokens taken while there are none are owed, so later calls wait for
+    /// them to be paid first
+    pub fn acquire(&mut self, now: Instant) -> Duration {
+        if let Some(refilled_at) = self.refilled_at {
+            let elapsed = now.saturating_duration_since(refilled_at).as_secs_f64();
+            self.tokens = f64::min(self.tokens + elapsed * self.rps, self.rps.max(1.0));
+        }
+        self.refilled_at = Some(now);
+        self.tokens -= 1.0;
+
+        match self.tokens < 0.0 {
+            true => Duration::from_secs_f64(-self.tokens / self.rps),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+/// how long a `429` response asked to wait before trying again, only the
+/// delay in seconds form of `Retry-After` is understood
+pub fn retry_after(status: Option<u16>, headers: Option<&HeaderMap>) -> Option<Duration> {
+    if status.ne(&Some(429)) {
+        return None;
+    }
+
+    headers?
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// names of the requests that use a value captured by an earlier request,
+/// these are never sent before the request they depend on finishes
+pub fn dependent_requests(requests: &[Request]) -> Vec<String> {
+    let mut captured: Vec<String> = vec![];
+    let mut dependent = vec![];
+
+    for request in requests {
+        if uses_any(request, &captured) {
+            dependent.push(request.name.clone());
+        }
+        captured.extend(capture_names(request));
+    }
+
+    dependent
+}
+
+fn capture_names(request: &Request) -> Vec<String> {
+    request
+        .captures
+        .iter()
+        .flatten()
+        .map(|capture| capture.name.clone())
+        .collect()
+}
+
+/// placeholders on a raw body are sent as written, so they don't depend on
+/// anything
+fn uses_any(request: &Request, names: &[String]) -> bool {
+    if names.is_empty() {
+        return false;
+    }
+
+    let headers = request
+        .headers
+        .iter()
+        .flatten()
+        .flat_map(|header| [header.pair.0.as_str(), header.pair.1.as_str()]);
+    std::iter::once(request.uri.as_str())
+        .chain(headers)
+        .chain(request.body.as_deref().filter(|_| !request.raw_body))
+        .flat_map(variables::placeholders)
+        .any(|placeholder| names.contains(&placeholder))
+}
+
+/// 2xx and 3xx are always passing statuses, 4xx are only passing when
+/// `fail_on_4xx` is disabled, anything else, including having no status at
+/// all, is a failure
+pub fn is_passing_status(status: Option<u16>, fail_on_4xx: bool) -> bool {
+    match status {
+        Some(200..=399) => true,
+        Some(400..=499) => !fail_on_4xx,
+        _ => false,
+    }
+}
+
+/// sends every request in order, reporting progress through `on_event`.
+/// Requests are expected to be in the order they should run, which for
+/// collections is the authored order, depth-first.
+///
+/// captures of each request are applied to the variables before the next
+/// request is sent, which is what allows chaining requests. With `parallel`
+/// above one, requests using a captured value wait for every request in
+/// flight before being sent. Results are always reported in order
+pub async fn run_requests<F>(
+    requests: Vec<Request>,
+    mut variables: BTreeMap<String, String>,
+    options: RunOptions,
+    mut on_event: F,
+) -> RunSummary
+where
+    F: FnMut(RunEvent),
+{
+    let total = requests.len();
+    let parallel = options.parallel.max(1);
+    let mut summary = RunSummary::default();
+    on_event(RunEvent::Started { total });
+
+    let hooked = requests
+        .iter()
+        .filter(|request| {
+            request
+                .hooks
+                .as_ref()
+                .is_some_and(|hooks| !hooks.is_empty())
+        })
+        .map(|request| request.name.clone())
+        .collect::<Vec<_>>();
+    if !options.run_hooks && !hooked.is_empty() {
+        on_event(RunEvent::Warning(format!(
+            "skipped the hooks of {}, the hooks of the collection aren't trusted",
+            hooked.join(", ")
+        )));
+    }
+
+    if parallel.gt(&1) {
+        let dependent = dependent_requests(&requests);
+        if !dependent.is_empty() {
+            on_event(RunEvent::Warning(format!(
+                "{} use values captured by earlier requests, they run after them instead of in parallel",
+                dependent.join(", ")
+            )));
+        }
+    }
+
+    // requests in flight report their own waits through this channel
+    let (pacing_tx, mut pacing_rx) = unbounded_channel::<RunEvent>();
+    let mut limiter = options.max_rps.map(RateLimiter::new);
+    let mut in_flight = VecDeque::<InFlight>::new();
+    let mut finished = 0;
+    let mut bailed = false;
+
+    for (idx, request) in requests.into_iter().enumerate() {
+        let depends = in_flight
+            .iter()
+            .any(|sent| uses_any(&request, &sent.captures));
+        while in_flight.len().ge(&parallel) || (depends && !in_flight.is_empty()) {
+            let sent = in_flight.pop_front().expect("checked to not be empty");
+            let result = forward_while(sent.wait(), &mut pacing_rx, &mut on_event).await;
+            finished += 1;
+            bailed = record(
+                result,
+                &mut variables,
+                &mut summary,
+                &options,
+                &mut on_event,
+            );
+            if bailed {
+                break;
+            }
+        }
+        if bailed {
+            break;
+        }
+
+        if idx.gt(&0) && !options.delay.is_zero() {
+            on_event(RunEvent::Waiting(Pacing {
+                wait: options.delay,
+                reason: PacingReason::Delay,
+            }));
+            forward_while(
+                tokio::time::sleep(options.delay),
+                &mut pacing_rx,
+                &mut on_event,
+            )
+            .await;
+        }
+
+        if let Some(wait) = limiter
+            .as_mut()
+            .map(|limiter| limiter.acquire(Instant::now()))
+        {
+            if !wait.is_zero() {
+                on_event(RunEvent::Waiting(Pacing {
+                    wait,
+                    reason: PacingReason::RateLimit,
+                }));
+                forward_while(tokio::time::sleep(wait), &mut pacing_rx, &mut on_event).await;
+            }
+        }
+
+        in_flight.push_back(InFlight::spawn(
+            request,
+            variables.clone(),
+            options.clone(),
+            pacing_tx.clone(),
+        ));
+    }
+
+    while let Some(sent) = in_flight.pop_front() {
+        if bailed {
+            sent.handle.abort();
+            continue;
+        }
+        let result = forward_while(sent.wait(), &mut pacing_rx, &mut on_event).await;
+        finished += 1;
+        bailed = record(
+            result,
+            &mut variables,
+            &mut summary,
+            &options,
+            &mut on_event,
+        );
+    }
+
+    // requests still in flight when bailing are dropped along with the ones
+    // never sent
+    if bailed {
+        summary.skipped = total.saturating_sub(finished);
+    }
+
+    on_event(RunEvent::Finished(summary.clone()));
+    summary
+}
+
+/// a request sent on its own task, along with what it captures so requests
+/// depending on it know to wait
+struct InFlight {
+    name: String,
+    method: RequestMethod,
+    captures: Vec<String>,
+    handle: JoinHandle<RunResult>,
+}
+
+impl InFlight {
+    fn spawn(
+        request: Request,
+        variables: BTreeMap<String, String>,
+        options: RunOptions,
+        pacing_tx: UnboundedSender<RunEvent>,
+    ) -> Self {
+        InFlight {
+            name: request.name.clone(),
+            method: request.method.clone(),
+            captures: capture_names(&request),
+            handle: tokio::spawn(async move {
+                run_single(request, &variables, &options, &pacing_tx).await
+            }),
+        }
+    }
+
+    async fn wait(self) -> RunResult {
+        match self.handle.await {
+            Ok(result) => result,
+            Err(e) => RunResult {
+                name: self.name,
+                method: self.method,
+                status: None,
+                duration: Duration::ZERO,
+                passed: false,
+                error: Some(e.to_string()),
+                assertions: vec![],
+                captures: vec![],
+                size: None,
+            },
+        }
+    }
+}
+
+/// applies the result to the run, returning wether the run should stop
+fn record<F>(
+    result: RunResult,
+    variables: &mut BTreeMap<String, String>,
+    summary: &mut RunSummary,
+    options: &RunOptions,
+    on_event: &mut F,
+) -> bool
+where
+    F: FnMut(RunEvent),
+{
+    captures::apply(&result.captures, variables);
+
+    match result.passed {
+        true => summary.passed += 1,
+        false => summary.failed += 1,
+    }
+
+    let should_bail = options.bail && !result.passed;
+    on_event(RunEvent::RequestFinished(result));
+    should_bail
+}
+
+/// waits on `future` while passing along the events sent by requests in
+/// flight, so their waits are reported as they happen
+async fn forward_while<T, F>(
+    future: impl Future<Output = T>,
+    pacing_rx: &mut UnboundedReceiver<RunEvent>,
+    on_event: &mut F,
+) -> T
+where
+    F: FnMut(RunEvent),
+{
+    tokio::pin!(future);
+    loop {
+        // events are taken first, so a request that waited before finishing
+        // has it reported before its result
+        tokio::select! {
+            biased;
+            Some(event) = pacing_rx.recv() => on_event(event),
+            output = &mut future => return output,
+        }
+    }
+}
+
+async fn run_single(
+    request: Request,
+    variables: &BTreeMap<String, String>,
+    options: &RunOptions,
+    pacing_tx: &UnboundedSender<RunEvent>,
+) -> RunResult {
+    let name = request.name.clone();
+    let method = request.method.clone();
+
+    // the pre-request hook prints variables the request is resolved with
+    let request_hooks = request
+        .hooks
+        .clone()
+        .filter(|_| options.run_hooks)
+        .unwrap_or_default();
+    let with_hook = match request_hooks.pre_request.as_ref() {
+        Some(hook) => hooks::run_pre_request(hook, &request).await.map(|printed| {
+            let mut variables = variables.clone();
+            variables.extend(printed);
+            variables
+        }),
+        None => Ok(variables.clone()),
+    };
+
+    let prepared = with_hook
+        .map_err(|e| format!("{e:#}"))
+        .and_then(|variables| {
+            variables::resolve_request(&request, &variables).map_err(|e| e.to_string())
+        })
+        .and_then(|mut request| {
+            let normalized = uri::normalize(&request.uri).map_err(|e| e.to_string())?;
+            for warning in normalized.warnings {
+                _ = pacing_tx.send(RunEvent::Warning(format!("{name}: {warning}")));
+            }
+            request.uri = normalized.uri;
+            if let Some(oversized) = options.client.oversized_body(&request) {
+                _ = pacing_tx.send(RunEvent::Warning(format!("{name}: {oversized}")));
+            }
+            Ok(request)
+        });
+    let request = match prepared {
+        Ok(request) => request,
+        Err(error) => {
+            return RunResult {
+                name,
+                method,
+                status: None,
+                duration: Duration::ZERO,
+                passed: false,
+                error: Some(error),
+                assertions: vec![],
+                captures: vec![],
+                size: None,
+            }
+        }
+    };
+
+    let assertions = request.assertions.clone().unwrap_or_default();
+    let captures = request.captures.clone().unwrap_or_default();
+    let mut response = send_request(request.clone(), options.client.clone()).await;
+
+    let wait = retry_after(
+        response.status.map(|status| status.as_u16()),
+        response.headers.as_ref(),
+    );
+    if let Some(wait) = wait.filter(|_| options.respect_retry_after) {
+        _ = pacing_tx.send(RunEvent::Waiting(Pacing {
+            wait,
+            reason: PacingReason::RetryAfter(name.clone()),
+        }));
+        tokio::time::sleep(wait).await;
+        response = send_request(request.clone(), options.client.clone()).await;
+    }
+
+    // the response is already judged by the time the hook runs, so a
+    // failing hook is only warned about
+    if let Some(hook) = request_hooks.post_response.as_ref() {
+        let summary = hooks::response_summary(&request, &response);
+        if let Err(e) = hooks::run_post_response(hook, &request, &summary).await {
+            _ = pacing_tx.send(RunEvent::Warning(format!("{name}: {e:#}")));
+        }
+    }
+
+    let status = response.status.map(|status| status.as_u16());
+    let results = assertions::evaluate_all(&assertions, &response);
+
+    // nothing can be captured from a request that failed to be sent
+    let captures = match response.is_error {
+        true => vec![],
+        false => captures::extract_all(&captures, &response),
+    };
+
+    let passed = match results.is_empty() {
+        true => is_passing_status(status, options.fail_on_4xx),
+        false => results.iter().all(|result| result.passed),
+    };
+
+    RunResult {
+        name,
+        method,
+        status,
+        duration: response.duration,
+        passed: !response.is_error && passed,
+        error: response.cause,
+        assertions: results,
+        captures,
+        size: response.size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_request;
+
+    #[test]
+    fn test_passing_status_by_class() {
+        assert!(is_passing_status(Some(200), true));
+        assert!(is_passing_status(Some(304), true));
+        assert!(!is_passing_status(Some(404), true));
+        assert!(is_passing_status(Some(404), false));
+        assert!(!is_passing_status(Some(500), false));
+        assert!(!is_passing_status(None, false));
+    }
+
+    #[tokio::test]
+    async fn test_bail_skips_remaining_requests() {
+        // unresolved variables fail before any network access happens
+        let requests = vec![
+            make_request("first", "{{missing}}/a"),
+            make_request("second", "{{missing}}/b"),
+            make_request("third", "{{missing}}/c"),
+        ];
+        let options = RunOptions {
+            bail: true,
+            ..Default::default()
+        };
+
+        let mut events = vec![];
+        let summary =
+            run_requests(requests, BTreeMap::default(), options, |e| events.push(e)).await;
+
+        assert_eq!(
+            summary,
+            RunSummary {
+                passed: 0,
+                failed: 1,
+                skipped: 2
+            }
+        );
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], RunEvent::Started { total: 3 });
+        assert!(matches!(events[2], RunEvent::Finished(_)));
+    }
+
+    #[tokio::test]
+    async fn test_results_keep_the_given_order() {
+        let requests = vec![
+            make_request("first", "{{missing}}/a"),
+            make_request("second", "{{missing}}/b"),
+        ];
+
+        let mut names = vec![];
+        let summary = run_requests(
+            requests,
+            BTreeMap::default(),
+            RunOptions::default(),
+            |event| {
+                if let RunEvent::RequestFinished(result) = event {
+                    names.push(result.name);
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(names, vec!["first", "second"]);
+        assert_eq!(summary.failed, 2);
+        assert!(!summary.is_success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hooks_only_run_when_asked_to() {
+        let request = Request {
+            hooks: Some(hooks::Hooks {
+                pre_request: Some(hooks::Hook {
+                    command: "echo 'vault is sealed' >&2; exit 1".into(),
+                    timeout_ms: None,
+                }),
+                post_response: None,
+            }),
+            ..make_request("users", "{{missing}}/users")
+        };
+
+        let mut events = vec![];
+        run_requests(
+            vec![request.clone()],
+            BTreeMap::default(),
+            RunOptions::default(),
+            |event| events.push(event),
+        )
+        .await;
+        assert_eq!(
+            events[1],
+            RunEvent::Warning(
+                "skipped the hooks of users, the hooks of the collection aren't trusted".into()
+            )
+        );
+
+        let mut errors = vec![];
+        let options = RunOptions {
+            run_hooks: true,
+            ..Default::default()
+        };
+        run_requests(vec![request], BTreeMap::default(), options, |event| {
+            if let RunEvent::RequestFinished(result) = event {
+                errors.push(result.error.unwrap_or_default());
+            }
+        })
+        .await;
+        assert!(errors[0].contains("vault is sealed"));
+    }
+
+    #[test]
+    fn test_rate_limiter_allows_bursts_then_waits() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(2.0);
+
+        assert_eq!(limiter.acquire(start), Duration::ZERO);
+        assert_eq!(limiter.acquire(start), Duration::ZERO);
+        assert_eq!(limiter.acquire(start), Duration::from_millis(500));
+        // the owed token is paid before a new one is available
+        assert_eq!(limiter.acquire(start), Duration::from_secs(1));
+        assert_eq!(
+            limiter.acquire(start + Duration::from_secs(2)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_retry_after_is_only_read_from_429s() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "2".parse().unwrap());
+
+        assert_eq!(
+            retry_after(Some(429), Some(&headers)),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(retry_after(Some(503), Some(&headers)), None);
+        assert_eq!(retry_after(Some(429), None), None);
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(Some(429), Some(&headers)), None);
+    }
+
+    #[test]
+    fn test_dependent_requests_use_captured_values() {
+        let mut login = make_request("login", "{{host}}/login");
+        login.captures = Some(vec!["token = $.token".parse().unwrap()]);
+        let mut me = make_request("me", "{{host}}/me");
+        me.headers = Some(vec![crate::collection::types::HeaderMap {
+            pair: ("Authorization".into(), "Bearer {{token}}".into()),
+            enabled: true,
+        }]);
+        let health = make_request("health", "{{host}}/health");
+
+        assert_eq!(dependent_requests(&[login, me, health]), vec!["me"]);
+    }
+
+    #[test]
+    fn test_raw_bodies_dont_depend_on_captured_values() {
+        let mut login = make_request("login", "{{host}}/login");
+        login.captures = Some(vec!["token = $.token".parse().unwrap()]);
+        let mut template = make_request("template", "{{host}}/templates");
+        template.body = Some(r#"{"greeting": "hi {{token}}"}"#.into());
+        template.raw_body = true;
+        let mut escaped = make_request("escaped", "{{host}}/templates");
+        escaped.body = Some(r#"{"greeting": "hi \{{token}}"}"#.into());
+
+        assert!(dependent_requests(&[login.clone(), template.clone(), escaped]).is_empty());
+
+        // the uri is still substituted on a raw request
+        template.uri = "{{host}}/templates/{{token}}".into();
+        assert_eq!(dependent_requests(&[login, template]), vec!["template"]);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_runs_report_in_order_and_pace() {
+        let mut first = make_request("first", "{{missing}}/a");
+        first.captures = Some(vec!["id = $.id".parse().unwrap()]);
+        let requests = vec![
+            first,
+            make_request("second", "{{missing}}/{{id}}"),
+            make_request("third", "{{missing}}/c"),
+        ];
+        let options = RunOptions {
+            parallel: 3,
+            delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+
+        let mut events = vec![];
+        run_requests(requests, BTreeMap::default(), options, |e| events.push(e)).await;
+
+        assert!(
+            matches!(&events[1], RunEvent::Warning(warning) if warning.starts_with("second use"))
+        );
+        let names = events
+            .iter()
+            .filter_map(|event| match event {
+                RunEvent::RequestFinished(result) => Some(result.name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["first", "second", "third"]);
+
+        let waits = events
+            .iter()
+            .filter_map(|event| match event {
+                RunEvent::Waiting(pacing) => Some(pacing.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(waits, vec!["waiting 1ms between requests"; 2]);
+    }
+}
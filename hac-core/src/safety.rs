@@ -0,0 +1,155 @@
+use crate::collection::types::{Collection, Request, RequestMethod};
+
+/// decides which requests need confirmation before being sent. Only methods
+/// that change data on the server are guarded, and only when they target one
+/// of the protected hosts
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SafetyOptions {
+    pub confirm_destructive: bool,
+    pub protected_hosts: Vec<String>,
+}
+
+impl SafetyOptions {
+    /// the collection can turn the confirmation on or off, the protected
+    /// hosts always come from the config file
+    pub fn new(config: &hac_config::Config, collection: &Collection) -> Self {
+        SafetyOptions {
+            confirm_destructive: collection
+                .confirm_destructive
+                .unwrap_or(config.confirm_destructive),
+            protected_hosts: config.protected_hosts.clone(),
+        }
+    }
+
+    /// returns the host the request targets when it needs confirmation. The
+    /// request is expected to be resolved already, as variables usually hold
+    /// the host
+    pub fn protected_host(&self, request: &Request) -> Option<String> {
+        if !self.confirm_destructive || !is_destructive(&request.method) {
+            return None;
+        }
+
+        let url = reqwest::Url::parse(&request.uri).ok()?;
+        let host = url.host_str()?;
+
+        self.protected_hosts
+            .iter()
+            .any(|pattern| glob_match(pattern.trim(), host))
+            .then(|| host.to_string())
+    }
+}
+
+pub fn is_destructive(method: &RequestMethod) -> bool {
+    matches!(
+        method,
+        RequestMethod::Delete | RequestMethod::Put | RequestMethod::Patch
+    )
+}
+
+/// matches a host against a pattern where `*` matches any sequence of
+/// characters, dots included. Hosts are case insensitive
+pub(crate) fn glob_match(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase().chars().collect::<Vec<_>>();
+    let host = host.to_ascii_lowercase().chars().collect::<Vec<_>>();
+
+    // classic wildcard matching, we remember the last star we've seen so we
+    // can backtrack to it when the rest of the pattern fails to match
+    let (mut p, mut h) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+
+    while h < host.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                last_star = Some((p, h));
+                p += 1;
+            }
+            Some(c) if c.eq(&host[h]) => {
+                p += 1;
+                h += 1;
+            }
+            _ => match last_star {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    h = matched + 1;
+                    last_star = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| c.eq(&'*'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn make_request(method: RequestMethod, uri: &str) -> Request {
+        Request {
+            method,
+            ..test_utils::make_request("delete user", uri)
+        }
+    }
+
+    fn make_options() -> SafetyOptions {
+        SafetyOptions {
+            confirm_destructive: true,
+            protected_hosts: vec!["*.prod.*".into(), "billing.example.com".into()],
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.prod.*", "api.prod.example.com"));
+        assert!(glob_match("*.prod.*", "API.PROD.example.com"));
+        assert!(glob_match("*", "localhost"));
+        assert!(glob_match("api.*.com", "api.eu.prod.com"));
+        assert!(glob_match("localhost", "localhost"));
+        assert!(!glob_match("*.prod.*", "prod.example.com"));
+        assert!(!glob_match("*.prod.*", "api.staging.example.com"));
+        assert!(!glob_match("localhost", "localhost.example.com"));
+    }
+
+    #[test]
+    fn test_only_destructive_methods_need_confirmation() {
+        let options = make_options();
+        let uri = "https://api.prod.example.com/users/1";
+
+        for method in [
+            RequestMethod::Delete,
+            RequestMethod::Put,
+            RequestMethod::Patch,
+        ] {
+            assert_eq!(
+                options.protected_host(&make_request(method, uri)),
+                Some("api.prod.example.com".into())
+            );
+        }
+        assert_eq!(
+            options.protected_host(&make_request(RequestMethod::Get, uri)),
+            None
+        );
+        assert_eq!(
+            options.protected_host(&make_request(RequestMethod::Post, uri)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unprotected_hosts_and_disabled_options() {
+        let options = make_options();
+        let request = make_request(RequestMethod::Delete, "http://localhost:3000/users/1");
+        assert_eq!(options.protected_host(&request), None);
+
+        let request = make_request(RequestMethod::Delete, "https://billing.example.com/x");
+        assert!(options.protected_host(&request).is_some());
+
+        let options = SafetyOptions {
+            confirm_destructive: false,
+            ..make_options()
+        };
+        assert_eq!(options.protected_host(&request), None);
+    }
+}
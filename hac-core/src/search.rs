@@ -0,0 +1,213 @@
+use crate::collection::types::{Request, RequestKind};
+use crate::collection::Collection;
+
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// a single collection can't take more than this many results, so a big one
+/// doesn't push every other collection out of the list
+pub const MAX_MATCHES_PER_COLLECTION: usize = 10;
+
+/// which part of the request matched the query
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchField {
+    Name,
+    Uri,
+    /// holds the name of the header whose value matched
+    Header(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub collection_name: String,
+    pub collection_path: PathBuf,
+    /// names of the directories the request lives in, outermost first
+    pub folders: Vec<String>,
+    pub request_id: String,
+    pub request_name: String,
+    pub field: MatchField,
+    /// the text that matched, and the byte range of the match on it
+    pub text: String,
+    pub range: Range<usize>,
+}
+
+/// looks for `query` on the name, uri and header values of every request of
+/// every collection, ignoring ascii case. Each request shows up once, for the
+/// first of its fields that matched
+pub fn search(collections: &[Collection], query: &str) -> Vec<SearchMatch> {
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let query = query.to_ascii_lowercase();
+    let mut matches = vec![];
+
+    for collection in collections {
+        let Some(requests) = collection.requests.as_ref() else {
+            continue;
+        };
+
+        let mut found = vec![];
+        walk(
+            &requests.read().unwrap(),
+            &mut vec![],
+            &mut |folders, request| {
+                if found.len().ge(&MAX_MATCHES_PER_COLLECTION) {
+                    return;
+                }
+                if let Some((field, text, range)) = match_request(request, &query) {
+                    found.push(SearchMatch {
+                        collection_name: collection.info.name.clone(),
+                        collection_path: collection.path.clone(),
+                        folders: folders.to_vec(),
+                        request_id: request.id.clone(),
+                        request_name: request.name.clone(),
+                        field,
+                        text,
+                        range,
+                    });
+                }
+            },
+        );
+
+        matches.extend(found);
+    }
+
+    matches
+}
+
+/// whether `query` shows up anywhere on `text`, ignoring ascii case. The
+/// dashboard and sidebar filters both go through here so they narrow things
+/// down the same way
+pub fn matches(text: &str, query: &str) -> bool {
+    find(text, &query.to_ascii_lowercase()).is_some()
+}
+
+fn walk<F>(requests: &[RequestKind], folders: &mut Vec<String>, visit: &mut F)
+where
+    F: FnMut(&[String], &Request),
+{
+    for kind in requests {
+        match kind {
+            RequestKind::Single(request) => visit(folders, &request.read().unwrap()),
+            RequestKind::Nested(dir) => {
+                folders.push(dir.name.clone());
+                walk(&dir.requests.read().unwrap(), folders, visit);
+                folders.pop();
+            }
+        }
+    }
+}
+
+/// `query` is expected to be lowercase already
+fn match_request(request: &Request, query: &str) -> Option<(MatchField, String, Range<usize>)> {
+    if let Some(range) = find(&request.name, query) {
+        return Some((MatchField::Name, request.name.clone(), range));
+    }
+
+    if let Some(range) = find(&request.uri, query) {
+        return Some((MatchField::Uri, request.uri.clone(), range));
+    }
+
+    request.headers.iter().flatten().find_map(|header| {
+        let (name, value) = &header.pair;
+        find(value, query).map(|range| (MatchField::Header(name.clone()), value.clone(), range))
+    })
+}
+
+/// ascii lowercasing keeps every byte where it was, so the range found on the
+/// lowercase text is valid on the original one
+fn find(text: &str, query: &str) -> Option<Range<usize>> {
+    text.to_ascii_lowercase()
+        .find(query)
+        .map(|start| start..start + query.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::{Directory, HeaderMap};
+    use crate::test_utils;
+    use std::sync::{Arc, RwLock};
+
+    fn make_request(name: &str, uri: &str, headers: &[(&str, &str)]) -> RequestKind {
+        RequestKind::Single(Arc::new(RwLock::new(Request {
+            headers: Some(
+                headers
+                    .iter()
+                    .map(|(name, value)| HeaderMap {
+                        pair: (name.to_string(), value.to_string()),
+                        enabled: true,
+                    })
+                    .collect(),
+            ),
+            ..test_utils::make_request(name, uri)
+        })))
+    }
+
+    fn make_collection(name: &str, requests: Vec<RequestKind>) -> Collection {
+        Collection {
+            requests: Some(Arc::new(RwLock::new(requests))),
+            path: format!("{name}.json").into(),
+            ..test_utils::make_collection(name, vec![])
+        }
+    }
+
+    #[test]
+    fn test_matches_names_uris_and_headers() {
+        let collections = vec![make_collection(
+            "users",
+            vec![
+                make_request("List Users", "http://localhost/users", &[]),
+                RequestKind::Nested(Directory {
+                    id: "admin".into(),
+                    name: "admin".into(),
+                    requests: Arc::new(RwLock::new(vec![
+                        make_request("ban", "http://localhost/ban", &[("X-Tenant", "ACME")]),
+                        make_request("unrelated", "http://localhost/other", &[]),
+                    ])),
+                }),
+            ],
+        )];
+
+        let matches = search(&collections, "users");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].field, MatchField::Name);
+        assert_eq!(matches[0].range, 5..10);
+
+        let matches = search(&collections, "acme");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].folders, ["admin"]);
+        assert_eq!(matches[0].field, MatchField::Header("X-Tenant".into()));
+        assert_eq!(&matches[0].text[matches[0].range.clone()], "ACME");
+
+        let matches = search(&collections, "localhost/ban");
+        assert_eq!(matches[0].field, MatchField::Uri);
+        assert_eq!(matches[0].request_id, "ban");
+
+        assert!(search(&collections, "").is_empty());
+        assert!(search(&collections, "nothing").is_empty());
+    }
+
+    #[test]
+    fn test_matches_are_capped_per_collection() {
+        let many = (0..30)
+            .map(|i| make_request(&format!("request {i}"), "http://localhost", &[]))
+            .collect();
+        let collections = vec![
+            make_collection("big", many),
+            make_collection("small", vec![make_request("request", "http://x", &[])]),
+        ];
+
+        let matches = search(&collections, "request");
+        assert_eq!(matches.len(), MAX_MATCHES_PER_COLLECTION + 1);
+        assert_eq!(matches.last().unwrap().collection_name, "small");
+    }
+
+    #[test]
+    fn test_matches_ignores_case() {
+        assert!(matches("List Users", "list u"));
+        assert!(matches("anything", ""));
+        assert!(!matches("users", "posts"));
+    }
+}
@@ -0,0 +1,426 @@
+use crate::captures::{self, CaptureResult};
+use crate::collection::types::Environment;
+use crate::collection::Collection;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// shown in place of secret values
+pub const MASK: &str = "••••••";
+
+/// values of the variables marked as secret on environments. They are kept
+/// away from collection files, so collections can be committed and shared
+/// without leaking tokens, and looked up by collection, environment and
+/// variable name. Collections are told apart by `Secrets::key`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Secrets {
+    /// values of the secret fields of auth methods, by collection, request
+    /// id and field name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    auth: BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>,
+    #[serde(flatten)]
+    values: BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>,
+    /// where the secrets are saved to, secrets without a path live only in
+    /// memory
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Secrets {
+    /// reads the secrets from `path`, a missing or unreadable file gives no
+    /// secrets at all, which are then saved to `path` on the next change
+    #[tracing::instrument]
+    pub fn load(path: PathBuf) -> Self {
+        let secrets = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str::<Secrets>(&content) {
+                Ok(secrets) => Some(secrets),
+                Err(e) => {
+                    tracing::warn!("ignoring malformed secrets file: {e:?}");
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Secrets {
+            path: Some(path),
+            ..secrets
+        }
+    }
+
+    /// stops saving to disk, changes from now on are only kept in memory
+    pub fn detach(&mut self) {
+        self.path = None;
+    }
+
+    /// the file is only readable by its owner, as it holds credentials
+    #[tracing::instrument(skip(self), err)]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_string_pretty(self)?;
+        crate::fs::replace_file(path, contents.as_bytes(), true)?;
+        Ok(())
+    }
+
+    /// what the secrets of `collection` are filed under. Names are not
+    /// unique across directories, so it is where the collection lives. Only
+    /// the directory is resolved, as the file may not be written yet
+    pub fn key(collection: &Collection) -> String {
+        let path = &collection.path;
+        let resolved = path
+            .parent()
+            .and_then(|dir| std::fs::canonicalize(dir).ok())
+            .zip(path.file_name())
+            .map(|(dir, name)| dir.join(name));
+
+        resolved
+            .as_deref()
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub fn get(&self, collection: &str, environment: &str, name: &str) -> Option<&str> {
+        self.values
+            .get(collection)?
+            .get(environment)?
+            .get(name)
+            .map(String::as_str)
+    }
+
+    pub fn insert(&mut self, collection: &str, environment: &str, name: &str, value: String) {
+        self.values
+            .entry(collection.into())
+            .or_default()
+            .entry(environment.into())
+            .or_default()
+            .insert(name.into(), value);
+    }
+
+    pub fn remove(&mut self, collection: &str, environment: &str, name: &str) -> Option<String> {
+        self.values
+            .get_mut(collection)?
+            .get_mut(environment)?
+            .remove(name)
+    }
+
+    /// the variables of `environment` with the values of its secrets added,
+    /// secrets without a value are left out so they show up as unresolved
+    pub fn variables_for(
+        &self,
+        collection: &str,
+        environment: &Environment,
+    ) -> BTreeMap<String, String> {
+        let mut variables = environment.variables.clone();
+        for name in environment.secrets.iter() {
+            match self.get(collection, &environment.name, name) {
+                Some(value) => _ = variables.insert(name.clone(), value.into()),
+                None => _ = variables.remove(name),
+            }
+        }
+        variables
+    }
+
+    /// writes persisted captures into `environment`, captures into secret
+    /// names are kept here instead so they never end up on the collection.
+    /// Returns whether any secret changed, so they can be saved
+    pub fn apply_captures(
+        &mut self,
+        collection: &str,
+        environment: &mut Environment,
+        persisted: &[CaptureResult],
+    ) -> bool {
+        let (secret, plain): (Vec<_>, Vec<_>) = persisted
+            .iter()
+            .cloned()
+            .partition(|result| environment.secrets.contains(&result.capture.name));
+        captures::apply(&plain, &mut environment.variables);
+
+        let mut values = BTreeMap::default();
+        captures::apply(&secret, &mut values);
+        for (name, value) in values.iter() {
+            self.insert(collection, &environment.name, name, value.clone());
+        }
+        !values.is_empty()
+    }
+
+    /// moves the values of secret variables that were written straight into
+    /// the collection over to the secrets, so the next time the collection
+    /// is saved they are gone from it. Secret auth fields written on the
+    /// file are moved too, and the empty ones are filled back in from here.
+    /// Returns whether anything was moved
+    pub fn take_from(&mut self, collection: &mut Collection) -> bool {
+        let key = Self::key(collection);
+        let mut moved = false;
+        for environment in collection.environments.iter_mut().flatten() {
+            for name in environment.secrets.iter() {
+                if let Some(value) = environment.variables.remove(name) {
+                    self.insert(&key, &environment.name, name, value);
+                    moved = true;
+                }
+            }
+        }
+
+        for (id, fields) in secret_auth_fields(collection) {
+            let stored = self.auth.entry(key.clone()).or_default().entry(id);
+            let stored = stored.or_default();
+            for (name, value) in fields {
+                moved |= stored.insert(name, value.clone()).as_ref() != Some(&value);
+            }
+        }
+        self.fill_auth(collection);
+        moved
+    }
+
+    /// the stored secret auth fields of `request`, by field name
+    pub fn auth_for(&self, collection: &str, request: &str) -> Option<&BTreeMap<String, String>> {
+        self.auth.get(collection)?.get(request)
+    }
+
+    /// writes the values of secret auth fields into the requests of
+    /// `collection` that have them empty, as they are left out of its file
+    pub fn fill_auth(&self, collection: &Collection) {
+        let Some(requests) = self.auth.get(&Self::key(collection)) else {
+            return;
+        };
+
+        for request in collection.flatten_requests() {
+            let mut request = request.write().unwrap();
+            let Some(stored) = requests.get(&request.id) else {
+                continue;
+            };
+            let Some(auth) = request.auth_method.as_mut() else {
+                continue;
+            };
+            for field in auth.fields().iter().filter(|field| field.secret) {
+                if let (Some(""), Some(value)) = (auth.get(field.name), stored.get(field.name)) {
+                    auth.set(field.name, value.clone());
+                }
+            }
+        }
+    }
+
+    /// replaces the stored auth secrets of `collection` with what its requests
+    /// hold now, which drops those of deleted requests and cleared fields.
+    /// Called right before the collection is written, returns whether
+    /// anything changed so the secrets can be saved
+    pub fn keep_auth(&mut self, collection: &Collection) -> bool {
+        let key = Self::key(collection);
+        let current = secret_auth_fields(collection);
+        if self.auth.get(&key).unwrap_or(&BTreeMap::default()) == &current {
+            return false;
+        }
+
+        match current.is_empty() {
+            true => _ = self.auth.remove(&key),
+            false => _ = self.auth.insert(key, current),
+        }
+        true
+    }
+}
+
+/// the non empty secret auth fields of every request on `collection`, by
+/// request id and field name
+fn secret_auth_fields(collection: &Collection) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut requests = BTreeMap::default();
+    for request in collection.flatten_requests() {
+        let request = request.read().unwrap();
+        let Some(auth) = request.auth_method.as_ref() else {
+            continue;
+        };
+        let fields = auth
+            .fields()
+            .iter()
+            .filter(|field| field.secret)
+            .filter_map(|field| Some((field.name.to_string(), auth.get(field.name)?)))
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| (name, value.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        if !fields.is_empty() {
+            requests.insert(request.id.clone(), fields);
+        }
+    }
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::AuthMethod;
+    use crate::test_utils;
+    use std::collections::BTreeSet;
+
+    fn make_collection() -> Collection {
+        Collection {
+            environments: Some(vec![Environment {
+                name: "prod".into(),
+                variables: BTreeMap::from([
+                    ("base_url".into(), "https://api.example.com".into()),
+                    ("token".into(), "hunter2".into()),
+                ]),
+                secrets: BTreeSet::from(["token".into(), "password".into()]),
+            }]),
+            path: "/collections/api.json".into(),
+            ..test_utils::make_collection("api", vec![])
+        }
+    }
+
+    #[test]
+    fn test_secrets_are_taken_out_of_the_collection() {
+        let mut collection = make_collection();
+        let mut secrets = Secrets::default();
+
+        assert!(secrets.take_from(&mut collection));
+        assert!(!secrets.take_from(&mut collection));
+
+        let environment = &collection.environments.as_ref().unwrap()[0];
+        let key = Secrets::key(&collection);
+        assert!(!environment.variables.contains_key("token"));
+        assert_eq!(secrets.get(&key, "prod", "token"), Some("hunter2"));
+
+        let json = serde_json::to_string(&collection).unwrap();
+        assert!(!json.contains("hunter2"));
+        assert!(json.contains("\"token\""));
+
+        // the password has no value yet, so it must show up as unresolved
+        let variables = secrets.variables_for(&key, environment);
+        assert_eq!(variables.get("token").map(String::as_str), Some("hunter2"));
+        assert!(!variables.contains_key("password"));
+        assert_eq!(variables.len(), 2);
+    }
+
+    #[test]
+    fn test_captured_secrets_stay_out_of_the_environment() {
+        let mut collection = make_collection();
+        let mut secrets = Secrets::default();
+        let key = Secrets::key(&collection);
+        let environment = &mut collection.environments.as_mut().unwrap()[0];
+
+        let persisted = ["token = $.token", "user = $.user"]
+            .iter()
+            .zip(["rotated", "admin"])
+            .map(|(capture, value)| CaptureResult {
+                capture: capture.parse().unwrap(),
+                value: Some(value.into()),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(secrets.apply_captures(&key, environment, &persisted));
+        assert_eq!(secrets.get(&key, "prod", "token"), Some("rotated"));
+        assert_eq!(
+            environment.variables.get("token").map(String::as_str),
+            Some("hunter2")
+        );
+        assert_eq!(
+            environment.variables.get("user").map(String::as_str),
+            Some("admin")
+        );
+
+        assert!(!secrets.apply_captures(&key, environment, &persisted[1..]));
+    }
+
+    #[test]
+    fn test_collections_with_the_same_name_keep_their_own_secrets() {
+        let mut secrets = Secrets::default();
+        let mut other = make_collection();
+        other.path = "/elsewhere/api.json".into();
+        assert!(secrets.take_from(&mut make_collection()));
+
+        let environment = &other.environments.as_ref().unwrap()[0];
+        let variables = secrets.variables_for(&Secrets::key(&other), environment);
+        assert!(!variables.contains_key("token"));
+    }
+
+    #[test]
+    fn test_secret_auth_fields_live_on_the_secrets() {
+        let mut request = test_utils::make_request("signed", "/signed");
+        request.auth_method = Some(AuthMethod::AwsSigV4 {
+            access_key: "AKIDEXAMPLE".into(),
+            secret_key: "wJalrXUtnFEMI".into(),
+            region: "us-east-1".into(),
+            service: "execute-api".into(),
+            session_token: Some("FwoGZXIvYXdz".into()),
+        });
+        let mut collection = Collection {
+            path: "/collections/api.json".into(),
+            ..test_utils::make_collection("api", vec![request])
+        };
+        let mut secrets = Secrets::default();
+
+        assert!(secrets.take_from(&mut collection));
+        assert!(!secrets.keep_auth(&collection));
+        let json = serde_json::to_string(&collection).unwrap();
+        assert!(json.contains("AKIDEXAMPLE"));
+        assert!(!json.contains("wJalrXUtnFEMI"));
+        assert!(!json.contains("FwoGZXIvYXdz"));
+
+        // the path isn't written on the file, it is where it was read from
+        let mut loaded: Collection = serde_json::from_str(&json).unwrap();
+        loaded.path = collection.path.clone();
+        assert!(!secrets.take_from(&mut loaded));
+        let signed = loaded.flatten_requests()[0].clone();
+        let auth = signed.read().unwrap().auth_method.clone().unwrap();
+        assert_eq!(
+            auth,
+            collection.flatten_requests()[0]
+                .read()
+                .unwrap()
+                .auth_method
+                .clone()
+                .unwrap()
+        );
+
+        // clearing a field on the editor must not bring it back on the next load
+        signed
+            .write()
+            .unwrap()
+            .auth_method
+            .as_mut()
+            .unwrap()
+            .set("session_token", String::default());
+        assert!(secrets.keep_auth(&loaded));
+        let mut reloaded: Collection = serde_json::from_str(&json).unwrap();
+        reloaded.path = collection.path.clone();
+        secrets.take_from(&mut reloaded);
+        let auth = reloaded.flatten_requests()[0]
+            .read()
+            .unwrap()
+            .auth_method
+            .clone();
+        assert_eq!(auth.unwrap().get("session_token"), Some(""));
+    }
+
+    #[test]
+    fn test_secrets_are_saved_and_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.json");
+
+        let mut secrets = Secrets::load(path.clone());
+        secrets.insert("api", "prod", "token", "hunter2".into());
+        secrets.save().unwrap();
+
+        let loaded = Secrets::load(path.clone());
+        assert_eq!(loaded.get("api", "prod", "token"), Some("hunter2"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let mut detached = loaded;
+        detached.detach();
+        detached.insert("api", "prod", "token", "changed".into());
+        detached.save().unwrap();
+        assert_eq!(
+            Secrets::load(path).get("api", "prod", "token"),
+            Some("hunter2")
+        );
+    }
+}
@@ -1 +1,2 @@
 pub mod highlighter;
+pub mod json_tree;
@@ -1,18 +1,62 @@
+use crate::collection::types::PreviewLanguage;
+
 use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
-use std::sync::RwLock;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Instant;
 
 use lazy_static::lazy_static;
-use ratatui::style::Style;
-use tree_sitter::{Parser, Query, QueryCursor, Tree};
+use tree_sitter::{Language, Parser, Query, QueryCursor, QueryError, Tree};
 
 lazy_static! {
     pub static ref HIGHLIGHTER: RwLock<Highlighter> = RwLock::new(Highlighter::default());
 }
 
+/// a grammar along with its highlight query, compiling the query is what
+/// makes loading one slow
+pub struct Grammar {
+    language: Language,
+    query: Query,
+}
+
+impl Grammar {
+    pub fn new(language: Language, highlights: &str) -> Result<Self, QueryError> {
+        let query = Query::new(&language, highlights)?;
+        Ok(Grammar { language, query })
+    }
+}
+
+/// loads the grammar of a language, `None` for the languages we don't
+/// have one for. It runs on a thread of its own, so it can take its time
+pub type GrammarLoader = fn(PreviewLanguage) -> Option<Grammar>;
+
+pub fn load_grammar(language: PreviewLanguage) -> Option<Grammar> {
+    match language {
+        PreviewLanguage::Json => Some(
+            Grammar::new(
+                tree_sitter_json::language(),
+                include_str!("queries/json/highlights.scm"),
+            )
+            .expect("failed to load json query"),
+        ),
+        _ => None,
+    }
+}
+
+/// the grammar of a language once its loader is done, shared with the
+/// thread loading it
+type GrammarSlot = Arc<OnceLock<Option<Grammar>>>;
+
+/// nothing is loaded up front, each grammar starts loading on a thread of
+/// its own the first time its language is parsed. Until it is done there
+/// are no trees, so everything is drawn as plain text, and as the loading
+/// never holds the lock, drawing never waits on it
 pub struct Highlighter {
     parser: Parser,
-    query: Query,
+    /// the language `parser` is set to
+    parser_language: Option<PreviewLanguage>,
+    grammars: HashMap<PreviewLanguage, GrammarSlot>,
+    loader: GrammarLoader,
 }
 
 impl Debug for Highlighter {
@@ -21,56 +65,111 @@ impl Debug for Highlighter {
     }
 }
 
+/// a highlighted range of the buffer, `style` is whatever the caller maps
+/// the capture names to, so the core never depends on how it is drawn
 #[derive(Debug, PartialEq)]
-pub struct ColorInfo {
+pub struct ColorInfo<S> {
     pub start: usize,
     pub end: usize,
-    pub style: Style,
+    pub style: S,
 }
 
 impl Default for Highlighter {
     fn default() -> Self {
-        let mut parser = Parser::new();
-        let json_language = include_str!("queries/json/highlights.scm");
-        let query = Query::new(&tree_sitter_json::language(), json_language)
-            .expect("failed to load json query");
-
-        parser
-            .set_language(&tree_sitter_json::language())
-            .expect("error loading json grammar");
-
-        Highlighter { parser, query }
+        Highlighter {
+            parser: Parser::new(),
+            parser_language: None,
+            grammars: HashMap::default(),
+            loader: load_grammar,
+        }
     }
 }
 
+/// blocks until the grammar of `language` is loaded, loading it right away
+/// if it wasn't already. Only for callers that can't go on without it,
+/// never the draw loop
+pub fn wait_for_grammar(language: PreviewLanguage) {
+    let (slot, loader) = {
+        let mut highlighter = HIGHLIGHTER.write().unwrap();
+        (highlighter.slot(language), highlighter.loader)
+    };
+    slot.get_or_init(|| loader(language));
+}
+
 impl Highlighter {
+    /// replaces how grammars are loaded, only affecting the languages that
+    /// weren't asked for yet
+    pub fn set_loader(&mut self, loader: GrammarLoader) {
+        self.loader = loader;
+    }
+
+    /// whether the grammar of `language` is done loading, `false` for
+    /// languages that weren't asked for yet
+    pub fn is_loaded(&self, language: PreviewLanguage) -> bool {
+        self.grammars
+            .get(&language)
+            .is_some_and(|slot| slot.get().is_some())
+    }
+
+    /// the slot of `language`, starting to load its grammar the first time
+    /// it is asked for
+    fn slot(&mut self, language: PreviewLanguage) -> GrammarSlot {
+        let loader = self.loader;
+        self.grammars
+            .entry(language)
+            .or_insert_with(|| {
+                let slot = GrammarSlot::default();
+                let loading = slot.clone();
+                std::thread::spawn(move || {
+                    let started = Instant::now();
+                    loading.get_or_init(|| loader(language));
+                    tracing::debug!("loaded the {language} grammar in {:?}", started.elapsed());
+                });
+                slot
+            })
+            .clone()
+    }
+
+    /// parses `buffer` as json, `None` while its grammar is still loading
     pub fn parse(&mut self, buffer: &str) -> Option<Tree> {
+        let slot = self.slot(PreviewLanguage::Json);
+        let grammar = slot.get()?.as_ref()?;
+        if self.parser_language.ne(&Some(PreviewLanguage::Json)) {
+            self.parser.set_language(&grammar.language).ok()?;
+            self.parser_language = Some(PreviewLanguage::Json);
+        }
         self.parser.parse(buffer, None)
     }
 
-    pub fn apply(
+    pub fn apply<S: Clone>(
         &self,
         buffer: &str,
         tree: Option<&Tree>,
-        tokens: &HashMap<String, Style>,
-    ) -> VecDeque<ColorInfo> {
+        tokens: &HashMap<String, S>,
+    ) -> VecDeque<ColorInfo<S>> {
         let mut colors = VecDeque::new();
 
-        if let Some(tree) = tree {
+        let grammar = self
+            .grammars
+            .get(&PreviewLanguage::Json)
+            .and_then(|slot| slot.get())
+            .and_then(|grammar| grammar.as_ref());
+
+        if let Some((tree, grammar)) = tree.zip(grammar) {
             let mut cursor = QueryCursor::new();
-            let matches = cursor.matches(&self.query, tree.root_node(), buffer.as_bytes());
+            let matches = cursor.matches(&grammar.query, tree.root_node(), buffer.as_bytes());
 
             for m in matches {
                 for cap in m.captures {
                     let node = cap.node;
                     let start = node.start_byte();
                     let end = node.end_byte();
-                    let capture_name = self.query.capture_names()[cap.index as usize];
+                    let capture_name = grammar.query.capture_names()[cap.index as usize];
                     if let Some(style) = tokens.get(capture_name) {
                         colors.push_back(ColorInfo {
                             start,
                             end,
-                            style: *style,
+                            style: style.clone(),
                         });
                     }
                 }
@@ -98,3 +197,42 @@ impl Highlighter {
         indent_level.saturating_sub(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    static RELEASED: AtomicBool = AtomicBool::new(false);
+
+    fn held_loader(language: PreviewLanguage) -> Option<Grammar> {
+        while !RELEASED.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        load_grammar(language)
+    }
+
+    #[test]
+    fn test_grammars_load_in_the_background() {
+        let mut highlighter = Highlighter::default();
+        highlighter.set_loader(held_loader);
+        let tokens = HashMap::from([("string".to_string(), ())]);
+        assert!(!highlighter.is_loaded(PreviewLanguage::Json));
+
+        // nothing to highlight with while the grammar loads
+        assert!(highlighter.parse(r#"{"a": "b"}"#).is_none());
+        assert!(!highlighter.is_loaded(PreviewLanguage::Json));
+        assert!(highlighter.apply(r#"{"a": "b"}"#, None, &tokens).is_empty());
+
+        RELEASED.store(true, Ordering::SeqCst);
+        while !highlighter.is_loaded(PreviewLanguage::Json) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let tree = highlighter.parse(r#"{"a": "b"}"#);
+        assert!(tree.is_some());
+        assert!(!highlighter
+            .apply(r#"{"a": "b"}"#, tree.as_ref(), &tokens)
+            .is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::assertions::json_path::{self, Segment};
+
+use std::ops::Range;
+
+use tree_sitter::{Node, Tree};
+
+/// a value of a parsed json document, found by where it is on the text
+#[derive(Debug, PartialEq)]
+pub struct JsonNode {
+    pub path: Vec<Segment>,
+    /// where the value is on the text, quotes included for strings
+    pub range: Range<usize>,
+    is_string: bool,
+}
+
+impl JsonNode {
+    /// the path to the value, as `$.data.items[3].name`
+    pub fn path(&self) -> String {
+        json_path::format(&self.path)
+    }
+
+    /// the text of the value, strings are unquoted and unescaped while
+    /// anything else is kept as written
+    pub fn value(&self, source: &str) -> String {
+        let text = &source[self.range.clone()];
+        match self.is_string {
+            true => unescape(text),
+            false => text.to_string(),
+        }
+    }
+}
+
+/// the innermost value at `byte` on `source`. Object keys and the colon after
+/// them belong to the value of the pair, while brackets, braces and commas
+/// belong to the array or object they are part of
+pub fn node_at(tree: &Tree, source: &str, byte: usize) -> Option<JsonNode> {
+    let root = tree.root_node();
+    let byte = byte.min(source.len());
+    let mut node = root.descendant_for_byte_range(byte, byte)?;
+
+    loop {
+        if node.kind().eq("pair") {
+            node = node.child_by_field_name("value")?;
+            break;
+        }
+        let Some(parent) = node.parent() else {
+            // on the document itself, outside of its only value
+            node = named_values(node).into_iter().next()?;
+            break;
+        };
+        match parent.kind() {
+            "pair" => {
+                node = parent.child_by_field_name("value")?;
+                break;
+            }
+            "array" | "document" if node.is_named() && node.kind().ne("comment") => break,
+            _ => node = parent,
+        }
+    }
+
+    let mut path = vec![];
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "pair" => {
+                let key = parent.child_by_field_name("key")?;
+                let key = key.utf8_text(source.as_bytes()).ok()?;
+                path.push(Segment::Key(unescape(key)));
+            }
+            "array" => {
+                let index = named_values(parent)
+                    .iter()
+                    .position(|item| item.id().eq(&current.id()))?;
+                path.push(Segment::Index(index));
+            }
+            _ => {}
+        }
+        current = parent;
+    }
+    path.reverse();
+
+    Some(JsonNode {
+        path,
+        range: node.byte_range(),
+        is_string: node.kind().eq("string"),
+    })
+}
+
+/// children of an array or a document that are values, comments are
+/// allowed by the grammar but don't count for indexes
+fn named_values(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|child| child.kind().ne("comment"))
+        .collect()
+}
+
+/// turns a quoted json string into its contents, falling back to just
+/// dropping the quotes when it isn't valid, like an unterminated string
+fn unescape(text: &str) -> String {
+    serde_json::from_str::<String>(text).unwrap_or_else(|_| text.trim_matches('"').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Parser;
+
+    fn parse(source: &str) -> Tree {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_json::language()).unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
+    fn path_at(source: &str, needle: &str) -> Option<String> {
+        let tree = parse(source);
+        let byte = source.find(needle).unwrap();
+        node_at(&tree, source, byte).map(|node| node.path())
+    }
+
+    #[test]
+    fn test_paths_follow_keys_and_indexes() {
+        let source = serde_json::to_string_pretty(&serde_json::json!({
+            "data": {
+                "items": [
+                    { "id": 1 },
+                    { "id": 2, "attributes": { "name": "hac", "tags": ["a", "b"] } },
+                ],
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            path_at(&source, "\"hac\""),
+            Some("$.data.items[1].attributes.name".into())
+        );
+        // the key and the colon point to the same value
+        assert_eq!(
+            path_at(&source, "\"name\""),
+            Some("$.data.items[1].attributes.name".into())
+        );
+        assert_eq!(
+            path_at(&source, ": \"hac\""),
+            Some("$.data.items[1].attributes.name".into())
+        );
+        assert_eq!(
+            path_at(&source, "\"b\""),
+            Some("$.data.items[1].attributes.tags[1]".into())
+        );
+        assert_eq!(path_at(&source, "1"), Some("$.data.items[0].id".into()));
+        // brackets and commas belong to their array
+        assert_eq!(path_at(&source, "[\n"), Some("$.data.items".into()));
+        assert_eq!(path_at(&source, "{"), Some("$".into()));
+    }
+
+    #[test]
+    fn test_escaped_keys_and_values() {
+        let source = r#"{ "a.b": { "say \"hi\"": "line\nbreak" }, "x": [true, null] }"#;
+        let tree = parse(source);
+
+        let node = node_at(&tree, source, source.find("line").unwrap()).unwrap();
+        assert_eq!(node.path(), r#"$['a.b']['say "hi"']"#);
+        assert_eq!(node.value(source), "line\nbreak");
+        assert_eq!(&source[node.range.clone()], r#""line\nbreak""#);
+        // the path can be used to query the same value back
+        let value = serde_json::from_str(source).unwrap();
+        assert_eq!(
+            json_path::query(&value, &node.path()),
+            Ok(Some(&serde_json::json!("line\nbreak")))
+        );
+
+        let node = node_at(&tree, source, source.find("null").unwrap()).unwrap();
+        assert_eq!(node.path(), "$.x[1]");
+        assert_eq!(node.value(source), "null");
+
+        let node = node_at(&tree, source, source.find("{ \"say").unwrap()).unwrap();
+        assert_eq!(node.path(), "$['a.b']");
+        assert_eq!(node.value(source), r#"{ "say \"hi\"": "line\nbreak" }"#);
+    }
+
+    #[test]
+    fn test_offsets_outside_of_any_value() {
+        let source = "  [1, 2]  ";
+        let tree = parse(source);
+        assert_eq!(node_at(&tree, source, 0).unwrap().path(), "$");
+        assert_eq!(node_at(&tree, source, 100).unwrap().path(), "$");
+        assert_eq!(node_at(&tree, source, 6).unwrap().path(), "$[1]");
+
+        let source = "";
+        assert_eq!(node_at(&parse(source), source, 0), None);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::collection::types::{AuthMethod, BodyType, HeaderMap, Request, RequestMethod};
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// the shape of a request saved to create new ones from, eg: a json `POST`
+/// with the usual headers. Templates are shared by every collection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestTemplate {
+    pub name: String,
+    pub method: RequestMethod,
+    pub headers: Option<Vec<HeaderMap>>,
+    pub auth_method: Option<AuthMethod>,
+    pub body: Option<String>,
+    #[serde(rename = "bodyType")]
+    pub body_type: Option<BodyType>,
+}
+
+impl RequestTemplate {
+    /// keeps what is usually repeated between requests, the uri, name and
+    /// anything tied to a single request like assertions are left out
+    pub fn from_request(name: String, request: &Request) -> Self {
+        RequestTemplate {
+            name,
+            method: request.method.clone(),
+            headers: request.headers.clone(),
+            auth_method: request.auth_method.clone(),
+            body: request.body.clone(),
+            body_type: request.body_type.clone(),
+        }
+    }
+
+    /// a brand new request with everything copied from the template, so
+    /// changing one never changes the other
+    pub fn to_request(&self, name: String, parent: Option<String>) -> Request {
+        Request {
+            id: uuid::Uuid::new_v4().to_string(),
+            method: self.method.clone(),
+            name,
+            headers: self.headers.clone(),
+            auth_method: self.auth_method.clone(),
+            parent,
+            body: self.body.clone(),
+            body_type: self.body_type.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// every saved template, kept on a file next to the config file
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Templates {
+    templates: Vec<RequestTemplate>,
+    /// where the templates are saved to, templates without a path live only
+    /// in memory
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Templates {
+    /// reads the templates from `path`, a missing or unreadable file gives
+    /// no templates, which are then saved to `path` on the next change
+    #[tracing::instrument]
+    pub fn load(path: PathBuf) -> Self {
+        let templates = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(
+                |content| match serde_json::from_str::<Templates>(&content) {
+                    Ok(templates) => Some(templates),
+                    Err(e) => {
+                        tracing::warn!("ignoring malformed templates file: {e:?}");
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+
+        Templates {
+            path: Some(path),
+            ..templates
+        }
+    }
+
+    /// stops saving to disk, changes from now on are only kept in memory
+    pub fn detach(&mut self) {
+        self.path = None;
+    }
+
+    /// the config directory may not exist yet when no config file was ever
+    /// written, so it is created along the way
+    #[tracing::instrument(skip(self), err)]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn list(&self) -> &[RequestTemplate] {
+        &self.templates
+    }
+
+    /// saving a template with the name of an existing one replaces it
+    pub fn add(&mut self, template: RequestTemplate) {
+        match self
+            .templates
+            .iter_mut()
+            .find(|t| t.name.eq(&template.name))
+        {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    pub fn rename(&mut self, idx: usize, name: String) {
+        if let Some(template) = self.templates.get_mut(idx) {
+            template.name = name;
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx.lt(&self.templates.len()) {
+            self.templates.remove(idx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_post() -> Request {
+        serde_json::from_str(
+            r#"{
+                "id": "a",
+                "method": "POST",
+                "name": "create user",
+                "uri": "{{host}}/users",
+                "headers": [{ "pair": ["Accept", "application/json"], "enabled": true }],
+                "auth_method": "Bearer",
+                "body": "{\"name\": \"\"}",
+                "bodyType": "json",
+                "notes": "only admins"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_requests_from_templates_are_independent() {
+        let template = RequestTemplate::from_request("json post".into(), &json_post());
+        let mut request = template.to_request("create post".into(), Some("dir".into()));
+
+        assert_ne!(request.id, "a");
+        assert_eq!(request.name, "create post");
+        assert_eq!(request.uri, "");
+        assert_eq!(request.method, RequestMethod::Post);
+        assert_eq!(request.auth_method, Some(AuthMethod::Bearer));
+        assert_eq!(request.body.as_deref(), Some("{\"name\": \"\"}"));
+        assert_eq!(request.notes, None);
+        assert_eq!(request.parent.as_deref(), Some("dir"));
+
+        request.headers.as_mut().unwrap()[0].pair.1 = "text/plain".into();
+        assert_eq!(
+            template.headers.as_ref().unwrap()[0].pair.1,
+            "application/json"
+        );
+    }
+
+    #[test]
+    fn test_templates_are_saved_and_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        // the directory is created on save when it doesn't exist yet
+        let path = dir.path().join("hac").join("templates.json");
+
+        let mut templates = Templates::load(path.clone());
+        templates.add(RequestTemplate::from_request(
+            "json post".into(),
+            &json_post(),
+        ));
+        templates.add(RequestTemplate::from_request("other".into(), &json_post()));
+        templates.add(RequestTemplate::from_request(
+            "json post".into(),
+            &json_post(),
+        ));
+        templates.rename(1, "renamed".into());
+        templates.save().unwrap();
+
+        let loaded = Templates::load(path.clone());
+        let names = loaded
+            .list()
+            .iter()
+            .map(|template| template.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["json post", "renamed"]);
+
+        let mut loaded = loaded;
+        loaded.remove(0);
+        loaded.remove(5);
+        assert_eq!(loaded.list().len(), 1);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::collection::types::{Collection, Info, Request, RequestKind};
+use crate::net::request_manager::Response;
+
+use std::sync::{Arc, RwLock};
+
+/// a GET request to `uri`, `name` is used as its id as well
+pub fn make_request(name: &str, uri: &str) -> Request {
+    Request {
+        id: name.into(),
+        name: name.into(),
+        uri: uri.into(),
+        ..Default::default()
+    }
+}
+
+/// a collection named `name` with `requests` at its top level
+pub fn make_collection(name: &str, requests: Vec<Request>) -> Collection {
+    let requests = requests
+        .into_iter()
+        .map(|request| RequestKind::Single(Arc::new(RwLock::new(request))))
+        .collect();
+
+    Collection {
+        info: Info {
+            name: name.into(),
+            ..Default::default()
+        },
+        requests: Some(Arc::new(RwLock::new(requests))),
+        ..Default::default()
+    }
+}
+
+pub fn make_response(status: u16, body: &str) -> Response {
+    Response {
+        status: Some(reqwest::StatusCode::from_u16(status).unwrap()),
+        body: Some(body.into()),
+        ..Default::default()
+    }
+}
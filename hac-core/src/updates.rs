@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+/// where the latest published release is looked up
+pub const RELEASES_URL: &str = "https://api.github.com/repos/wllfaria/hac/releases/latest";
+
+/// the check runs on every startup, so it gives up quickly on slow networks
+pub const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// asks github for the latest release and returns its version when it is
+/// newer than `current`
+pub async fn check_for_update(current: &str) -> anyhow::Result<Option<semver::Version>> {
+    let release = reqwest::Client::builder()
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        // github refuses requests without an user agent
+        .user_agent(format!("hac/{current}"))
+        .build()?
+        .get(RELEASES_URL)
+        .header("accept", "application/vnd.github+json")
+        .send()
+        .await
+        .context("failed to reach the releases api")?
+        .error_for_status()?
+        .json::<Release>()
+        .await
+        .context("failed to read the latest release")?;
+
+    newer_version(current, &release.tag_name)
+}
+
+/// `latest` is a release tag, which may be prefixed with a `v`. Pre-releases
+/// are never offered
+pub fn newer_version(current: &str, latest: &str) -> anyhow::Result<Option<semver::Version>> {
+    let current = semver::Version::parse(current)
+        .with_context(|| format!("`{current}` is not a valid version"))?;
+    let latest = latest.trim().trim_start_matches('v');
+    let latest = semver::Version::parse(latest)
+        .with_context(|| format!("`{latest}` is not a valid version"))?;
+
+    Ok((latest.pre.is_empty() && latest.gt(&current)).then_some(latest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_newer_releases_are_offered() {
+        let newer = newer_version("0.2.0", "v0.3.0").unwrap();
+        assert_eq!(newer, Some(semver::Version::new(0, 3, 0)));
+        assert_eq!(
+            newer_version("0.2.0", "0.10.1").unwrap(),
+            Some(semver::Version::new(0, 10, 1))
+        );
+
+        assert_eq!(newer_version("0.2.0", "v0.2.0").unwrap(), None);
+        assert_eq!(newer_version("0.2.0", "v0.1.9").unwrap(), None);
+        assert_eq!(newer_version("0.2.0", "v0.3.0-rc.1").unwrap(), None);
+        assert!(newer_version("0.2.0", "nightly").is_err());
+    }
+}
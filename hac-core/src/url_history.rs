@@ -0,0 +1,259 @@
+use std::cmp::Reverse;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// the least used urls are forgotten past this many
+pub const MAX_ENTRIES: usize = 300;
+
+/// query parameters whose name contains any of these are dropped before a
+/// url is remembered, as their values are usually credentials
+const SENSITIVE_PARAMS: [&str; 6] = ["token", "key", "secret", "password", "signature", "auth"];
+
+const HOUR_MS: u128 = 60 * 60 * 1000;
+const DAY_MS: u128 = 24 * HOUR_MS;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlEntry {
+    pub url: String,
+    /// path of the collection the url was sent from
+    pub collection: PathBuf,
+    pub uses: u32,
+    /// unix timestamp in milliseconds
+    pub last_used: u128,
+}
+
+impl UrlEntry {
+    /// how often the url is used, weighted by how recently
+    fn frecency(&self, now: u128) -> u128 {
+        let weight = match now.saturating_sub(self.last_used) {
+            age if age < 4 * HOUR_MS => 100,
+            age if age < DAY_MS => 70,
+            age if age < 7 * DAY_MS => 50,
+            age if age < 30 * DAY_MS => 30,
+            _ => 10,
+        };
+        u128::from(self.uses) * weight
+    }
+}
+
+/// urls sent from the uri bar, offered back as suggestions while typing
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlHistory {
+    entries: Vec<UrlEntry>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl UrlHistory {
+    /// reads the history from `path`, a missing or unreadable file starts
+    /// with nothing remembered
+    #[tracing::instrument]
+    pub fn load(path: PathBuf) -> Self {
+        let history = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(
+                |content| match serde_json::from_str::<UrlHistory>(&content) {
+                    Ok(history) => Some(history),
+                    Err(e) => {
+                        tracing::warn!("ignoring malformed url history file: {e:?}");
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+
+        UrlHistory {
+            path: Some(path),
+            ..history
+        }
+    }
+
+    /// stops saving to disk, changes from now on are only kept in memory
+    pub fn detach(&mut self) {
+        self.path = None;
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[UrlEntry] {
+        &self.entries
+    }
+
+    /// remembers `url` as sent from `collection`. Sensitive query parameters
+    /// are dropped first, and urls with any of the `secrets` values in them
+    /// are not remembered at all
+    pub fn record(&mut self, collection: PathBuf, url: &str, secrets: &[String]) {
+        let url = strip_sensitive_params(url.trim());
+        if url.is_empty()
+            || secrets
+                .iter()
+                .any(|secret| !secret.is_empty() && url.contains(secret.as_str()))
+        {
+            return;
+        }
+
+        let now = now();
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.url.eq(&url) && entry.collection.eq(&collection))
+        {
+            Some(entry) => {
+                entry.uses = entry.uses.saturating_add(1);
+                entry.last_used = now;
+            }
+            None => self.entries.push(UrlEntry {
+                url,
+                collection,
+                uses: 1,
+                last_used: now,
+            }),
+        }
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries
+                .sort_by_key(|entry| Reverse((entry.frecency(now), entry.last_used)));
+            self.entries.truncate(MAX_ENTRIES);
+        }
+    }
+
+    /// remembered urls starting with `typed`, best ranked first. Only those
+    /// sent from `collection` are given, unless it is `None`
+    pub fn suggestions(&self, typed: &str, collection: Option<&PathBuf>) -> Vec<String> {
+        self.suggestions_at(typed, collection, now())
+    }
+
+    fn suggestions_at(&self, typed: &str, collection: Option<&PathBuf>, now: u128) -> Vec<String> {
+        let typed = typed.to_lowercase();
+        let mut matches = self
+            .entries
+            .iter()
+            .filter(|entry| match collection {
+                Some(path) => entry.collection.eq(path),
+                None => true,
+            })
+            .filter(|entry| entry.url.to_lowercase().starts_with(&typed))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|entry| Reverse((entry.frecency(now), entry.last_used)));
+
+        let mut urls: Vec<String> = vec![];
+        for entry in matches {
+            if !urls.contains(&entry.url) {
+                urls.push(entry.url.clone());
+            }
+        }
+        urls
+    }
+}
+
+/// removes query parameters that look like credentials, eg: `?api_key=`
+pub fn strip_sensitive_params(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let (query, fragment) = match query.split_once('#') {
+        Some((query, fragment)) => (query, Some(fragment)),
+        None => (query, None),
+    };
+
+    let kept = query
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split('=').next().unwrap_or_default().to_lowercase();
+            !name.is_empty() && !SENSITIVE_PARAMS.iter().any(|param| name.contains(param))
+        })
+        .collect::<Vec<_>>();
+
+    let mut url = base.to_string();
+    if !kept.is_empty() {
+        url.push('?');
+        url.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+fn now() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitive_params_are_stripped() {
+        assert_eq!(
+            strip_sensitive_params("{{host}}/users?page=2&api_key=abc&Access_Token=x#top"),
+            "{{host}}/users?page=2#top"
+        );
+        assert_eq!(
+            strip_sensitive_params("https://api.dev/login?token=abc"),
+            "https://api.dev/login"
+        );
+        assert_eq!(strip_sensitive_params("/users"), "/users");
+    }
+
+    #[test]
+    fn test_recording_and_ranking_urls() {
+        let api = PathBuf::from("api.json");
+        let other = PathBuf::from("other.json");
+        let mut history = UrlHistory::default();
+
+        history.record(api.clone(), "/users?token=abc", &[]);
+        history.record(api.clone(), "/users/1", &[]);
+        history.record(api.clone(), "/users/1", &[]);
+        history.record(other.clone(), "/users/2", &[]);
+        history.record(api.clone(), "/users/hunter2", &["hunter2".into()]);
+        assert_eq!(history.entries().len(), 3);
+
+        assert_eq!(
+            history.suggestions("/USERS", Some(&api)),
+            vec!["/users/1", "/users"]
+        );
+        let global = history.suggestions("/users", None);
+        assert_eq!(global.len(), 3);
+        assert!(global.contains(&"/users/2".to_string()));
+
+        // recency outweighs a handful of old uses
+        let now = now();
+        history.entries[0].last_used = now;
+        history.entries[1].last_used = now - 40 * DAY_MS;
+        assert_eq!(
+            history.suggestions_at("/users", Some(&api), now),
+            vec!["/users", "/users/1"]
+        );
+    }
+
+    #[test]
+    fn test_history_is_pruned() {
+        let mut history = UrlHistory::default();
+        history.record("api.json".into(), "/kept", &[]);
+        history.record("api.json".into(), "/kept", &[]);
+        for idx in 0..MAX_ENTRIES {
+            history.record("api.json".into(), &format!("/{idx}"), &[]);
+        }
+
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert!(history.entries().iter().any(|entry| entry.url.eq("/kept")));
+    }
+}
@@ -0,0 +1,658 @@
+use crate::builtins::{self, BuiltinError};
+use crate::collection::types::{AuthMethod, Request};
+
+use std::collections::BTreeMap;
+use std::ops::{Add, Range, Sub};
+
+const OPENING_DELIMITER: &str = "{{";
+const CLOSING_DELIMITER: &str = "}}";
+/// written right before the opening braces, as `\{{`, sends them as they
+/// are instead of starting a placeholder
+const ESCAPE: char = '\\';
+
+#[derive(Debug, PartialEq)]
+pub enum VariableError {
+    /// one or more placeholders had no matching variable, the names are
+    /// stored in the order they first appeared
+    Unresolved(Vec<String>),
+    /// a built-in variable like `{{$uuid}}` couldn't be generated, either
+    /// because it doesn't exist or its arguments are wrong
+    Builtin(BuiltinError),
+}
+
+impl std::fmt::Display for VariableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableError::Unresolved(names) => {
+                write!(f, "unresolved variables: {}", names.join(", "))
+            }
+            VariableError::Builtin(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for VariableError {}
+
+/// where the value of a variable comes from, every layer shadows the ones
+/// before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// shared by every environment of the collection
+    Collection,
+    /// the active environment, along with what was captured into it
+    Environment,
+    /// one-off values for a single send, eg: printed by a pre-request hook
+    Request,
+}
+
+impl std::fmt::Display for Layer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Layer::Collection => write!(f, "collection"),
+            Layer::Environment => write!(f, "environment"),
+            Layer::Request => write!(f, "request"),
+        }
+    }
+}
+
+/// the variables a request is resolved with, split by where they come from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layers {
+    pub collection: BTreeMap<String, String>,
+    pub environment: BTreeMap<String, String>,
+    pub request: BTreeMap<String, String>,
+}
+
+impl Layers {
+    /// every variable with the value of the topmost layer that has it
+    pub fn merged(&self) -> BTreeMap<String, String> {
+        let mut variables = self.collection.clone();
+        variables.extend(self.environment.clone());
+        variables.extend(self.request.clone());
+        variables
+    }
+
+    /// the layer `name` gets its value from, if any has it
+    pub fn layer_of(&self, name: &str) -> Option<Layer> {
+        [
+            (Layer::Request, &self.request),
+            (Layer::Environment, &self.environment),
+            (Layer::Collection, &self.collection),
+        ]
+        .into_iter()
+        .find(|(_, variables)| variables.contains_key(name))
+        .map(|(layer, _)| layer)
+    }
+}
+
+/// the value every built-in variable got on a resolution, in the order they
+/// appear, as `($randomInt 1 10, 7)`
+pub type Generated = Vec<(String, String)>;
+
+/// replaces every `{{name}}` placeholder on `input` with the value of the
+/// matching variable. Whitespace inside the braces is ignored, so `{{ name }}`
+/// is the same as `{{name}}`. Unterminated placeholders are kept as is.
+/// Placeholders starting with `$` are built-in variables, generated anew on
+/// every use. A backslash before the braces escapes them, `\{{name}}` is
+/// sent as `{{name}}`
+pub fn substitute(
+    input: &str,
+    variables: &BTreeMap<String, String>,
+) -> Result<String, VariableError> {
+    let mut resolution = Resolution::new(variables);
+    let output = resolution.substitute(input);
+    resolution.finish().map(|_| output)
+}
+
+/// returns a copy of the request with every placeholder on the uri, headers
+/// and body replaced by its variable value. Unresolved names are collected
+/// from every field so they can all be reported at once. Raw bodies are
+/// left exactly as written
+pub fn resolve_request(
+    request: &Request,
+    variables: &BTreeMap<String, String>,
+) -> Result<Request, VariableError> {
+    resolve_request_with_generated(request, variables).map(|(request, _)| request)
+}
+
+/// same as `resolve_request`, also returning what the built-in variables
+/// generated, so a response can be told apart by the ids it was sent with
+pub fn resolve_request_with_generated(
+    request: &Request,
+    variables: &BTreeMap<String, String>,
+) -> Result<(Request, Generated), VariableError> {
+    let mut resolution = Resolution::new(variables);
+    let request = resolution.resolve_request(request);
+    resolution.finish().map(|generated| (request, generated))
+}
+
+/// resolves what can be resolved on the request, leaving the placeholders
+/// that couldn't in place instead of failing. Returns them along with the
+/// request, so they can be pointed out, eg: on a preview
+pub fn resolve_request_partially(
+    request: &Request,
+    variables: &BTreeMap<String, String>,
+) -> (Request, Vec<String>) {
+    let mut resolution = Resolution::new(variables);
+    let request = resolution.resolve_request(request);
+    let mut unresolved = resolution.unresolved;
+    unresolved.extend(resolution.failed);
+    (request, unresolved)
+}
+
+/// the layer of every variable `request` uses, in the order they first
+/// appear. Built-ins and unresolved names are left out
+pub fn sources(request: &Request, layers: &Layers) -> Vec<(String, Layer)> {
+    let variables = layers.merged();
+    let mut resolution = Resolution::new(&variables);
+    resolution.resolve_request(request);
+    resolution
+        .used
+        .into_iter()
+        .filter_map(|name| layers.layer_of(&name).map(|layer| (name, layer)))
+        .collect()
+}
+
+/// names of every placeholder on `input`, in the order they first appear
+pub fn placeholders(input: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for (_, name) in placeholder_spans(input) {
+        if !names.iter().any(|n| n.eq(name)) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// every placeholder on `input` along with the bytes it takes, braces
+/// included, eg: `0..8` and `name` for `{{name}}`. Escaped braces are not
+/// placeholders
+pub fn placeholder_spans(input: &str) -> Vec<(Range<usize>, &str)> {
+    let mut spans = vec![];
+    let mut offset = 0;
+
+    while let Some((start, escaped)) = find_opening(&input[offset..]) {
+        let start = offset + start;
+        let after_opening = start + OPENING_DELIMITER.len();
+        if escaped {
+            offset = after_opening;
+            continue;
+        }
+        let Some(end) = input[after_opening..].find(CLOSING_DELIMITER) else {
+            break;
+        };
+
+        let end = after_opening + end;
+        let name = input[after_opening..end].trim();
+        offset = end + CLOSING_DELIMITER.len();
+        spans.push((start..offset, name));
+    }
+
+    spans
+}
+
+/// name of the placeholder `col` is on, counted in characters
+pub fn placeholder_at(input: &str, col: usize) -> Option<&str> {
+    let (byte, _) = input.char_indices().nth(col)?;
+    placeholder_spans(input)
+        .into_iter()
+        .find(|(range, _)| range.contains(&byte))
+        .map(|(_, name)| name)
+}
+
+/// where the next opening braces are on `input`, and whether they are
+/// escaped
+fn find_opening(input: &str) -> Option<(usize, bool)> {
+    let start = input.find(OPENING_DELIMITER)?;
+    Some((start, input[..start].ends_with(ESCAPE)))
+}
+
+/// state of substituting the placeholders of one or many inputs
+struct Resolution<'a> {
+    variables: &'a BTreeMap<String, String>,
+    unresolved: Vec<String>,
+    /// only the first failing builtin is reported, the others most likely
+    /// fail for the same reason
+    invalid: Option<BuiltinError>,
+    /// every builtin that couldn't be generated, as written
+    failed: Vec<String>,
+    generated: Generated,
+    /// every variable that was found, in the order they first appear
+    used: Vec<String>,
+}
+
+impl<'a> Resolution<'a> {
+    fn new(variables: &'a BTreeMap<String, String>) -> Self {
+        Resolution {
+            variables,
+            unresolved: vec![],
+            invalid: None,
+            failed: vec![],
+            generated: vec![],
+            used: vec![],
+        }
+    }
+
+    fn finish(self) -> Result<Generated, VariableError> {
+        if let Some(e) = self.invalid {
+            return Err(VariableError::Builtin(e));
+        }
+
+        match self.unresolved.is_empty() {
+            true => Ok(self.generated),
+            false => Err(VariableError::Unresolved(self.unresolved)),
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> Option<String> {
+        if !builtins::is_builtin(name) {
+            let value = self.variables.get(name).cloned();
+            if value.is_some() && !self.used.iter().any(|n| n.eq(name)) {
+                self.used.push(name.to_string());
+            }
+            return value;
+        }
+
+        match builtins::resolve(name) {
+            Ok(value) => {
+                self.generated.push((name.to_string(), value.clone()));
+                Some(value)
+            }
+            Err(e) => {
+                self.invalid.get_or_insert(e);
+                if !self.failed.iter().any(|n| n.eq(name)) {
+                    self.failed.push(name.to_string());
+                }
+                None
+            }
+        }
+    }
+
+    fn resolve_request(&mut self, request: &Request) -> Request {
+        let mut request = request.clone();
+
+        request.uri = self.substitute(&request.uri);
+
+        if let Some(headers) = request.headers.as_mut() {
+            for header in headers.iter_mut().filter(|header| header.enabled) {
+                header.pair.0 = self.substitute(&header.pair.0);
+                header.pair.1 = self.substitute(&header.pair.1);
+            }
+        }
+
+        // raw bodies are sent as written, escapes included
+        if let (Some(body), false) = (request.body.as_mut(), request.raw_body) {
+            *body = self.substitute(body);
+        }
+
+        let auth_fields = request
+            .auth_method
+            .as_mut()
+            .map(AuthMethod::values_mut)
+            .unwrap_or_default();
+        for field in auth_fields {
+            *field = self.substitute(field);
+        }
+
+        request
+    }
+
+    fn substitute(&mut self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some((start, escaped)) = find_opening(rest) {
+            if escaped {
+                output.push_str(&rest[..start.sub(ESCAPE.len_utf8())]);
+                output.push_str(OPENING_DELIMITER);
+                rest = &rest[start.add(OPENING_DELIMITER.len())..];
+                continue;
+            }
+
+            output.push_str(&rest[..start]);
+            let after_opening = &rest[start + OPENING_DELIMITER.len()..];
+
+            let Some(end) = after_opening.find(CLOSING_DELIMITER) else {
+                output.push_str(&rest[start..]);
+                rest = "";
+                break;
+            };
+
+            let name = after_opening[..end].trim();
+            match self.lookup(name) {
+                Some(value) => output.push_str(&value),
+                None => {
+                    // builtins that failed are reported on their own
+                    let is_reported = self.unresolved.iter().any(|n| n.eq(name));
+                    if !builtins::is_builtin(name) && !is_reported {
+                        self.unresolved.push(name.to_string());
+                    }
+                    output.push_str(
+                        &rest[start
+                            ..start + OPENING_DELIMITER.len() + end + CLOSING_DELIMITER.len()],
+                    );
+                }
+            }
+
+            rest = &after_opening[end + CLOSING_DELIMITER.len()..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::types::{HeaderMap, RequestMethod};
+
+    fn variables() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (
+                "base_url".to_string(),
+                "https://api.example.com".to_string(),
+            ),
+            ("token".to_string(), "secret".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_substitute_replaces_known_variables() {
+        let result = substitute("{{base_url}}/users?t={{ token }}", &variables());
+        assert_eq!(result, Ok("https://api.example.com/users?t=secret".into()));
+    }
+
+    #[test]
+    fn test_substitute_reports_every_unresolved_variable_once() {
+        let result = substitute(
+            "{{missing}}/{{base_url}}/{{other}}/{{missing}}",
+            &variables(),
+        );
+        assert_eq!(
+            result,
+            Err(VariableError::Unresolved(vec![
+                "missing".into(),
+                "other".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            placeholders("{{base_url}}/{{ id }}?t={{base_url}}&x={{oops"),
+            vec!["base_url".to_string(), "id".to_string()]
+        );
+        assert!(placeholders("no placeholders").is_empty());
+    }
+
+    #[test]
+    fn test_placeholder_spans_and_lookup_by_column() {
+        let input = "é/{{ id }}/{{base_url}}{{oops";
+        assert_eq!(
+            placeholder_spans(input),
+            vec![(3..11, "id"), (12..24, "base_url")]
+        );
+
+        // columns are characters, `é` takes two bytes
+        assert_eq!(placeholder_at(input, 1), None);
+        assert_eq!(placeholder_at(input, 2), Some("id"));
+        assert_eq!(placeholder_at(input, 9), Some("id"));
+        assert_eq!(placeholder_at(input, 10), None);
+        assert_eq!(placeholder_at(input, 11), Some("base_url"));
+        assert_eq!(placeholder_at(input, 25), None);
+        assert_eq!(placeholder_at(input, 99), None);
+    }
+
+    #[test]
+    fn test_escaped_braces_next_to_placeholders() {
+        let result = substitute(r"\{{token}}{{token}}", &variables());
+        assert_eq!(result, Ok("{{token}}secret".into()));
+        let result = substitute(r"{{token}}\{{token}}", &variables());
+        assert_eq!(result, Ok("secret{{token}}".into()));
+        let result = substitute(r"\{{{{token}}}}", &variables());
+        assert_eq!(result, Ok("{{secret}}".into()));
+        // escaped names are never looked up, so they can't be unresolved
+        let result = substitute(r"\{{missing}} and \{{$nope}}", &variables());
+        assert_eq!(result, Ok("{{missing}} and {{$nope}}".into()));
+        // only the backslash right before the braces is dropped
+        let result = substitute(r#"{"path": "C:\\{{token}}"}"#, &variables());
+        assert_eq!(result, Ok(r#"{"path": "C:\{{token}}"}"#.into()));
+
+        let input = r"\{{a}}{{b}}\{{c}}";
+        assert_eq!(placeholders(input), vec!["b".to_string()]);
+        assert_eq!(placeholder_spans(input), vec![(6..11, "b")]);
+        assert_eq!(placeholder_at(input, 2), None);
+        assert_eq!(placeholder_at(input, 7), Some("b"));
+    }
+
+    #[test]
+    fn test_substitute_keeps_unterminated_placeholders() {
+        let result = substitute("{{base_url}}/{{oops", &variables());
+        assert_eq!(result, Ok("https://api.example.com/{{oops".into()));
+    }
+
+    #[test]
+    fn test_substitute_generates_builtins_on_every_use() {
+        let result = substitute("{{$uuid}}/{{ $uuid }}", &variables()).unwrap();
+        let (first, second) = result.split_once('/').unwrap();
+        assert_eq!(first.len(), 36);
+        assert_ne!(first, second);
+
+        let result = substitute("{{$randomInt 7 7}} {{base_url}}", &variables());
+        assert_eq!(result, Ok("7 https://api.example.com".into()));
+    }
+
+    #[test]
+    fn test_substitute_fails_on_unknown_builtins() {
+        let result = substitute("{{$uuidd}}/{{missing}}", &variables());
+        assert_eq!(
+            result,
+            Err(VariableError::Builtin(BuiltinError::Unknown(
+                "uuidd".into()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_resolve_request_partially_keeps_unresolved_placeholders() {
+        let request = Request {
+            id: "id".into(),
+            method: RequestMethod::Get,
+            name: "users".into(),
+            uri: "{{base_url}}/{{tenant}}/users?id={{$nope}}".into(),
+            ..Default::default()
+        };
+
+        let (resolved, unresolved) = resolve_request_partially(&request, &variables());
+        assert_eq!(
+            resolved.uri,
+            "https://api.example.com/{{tenant}}/users?id={{$nope}}"
+        );
+        assert_eq!(unresolved, vec!["tenant".to_string(), "$nope".to_string()]);
+    }
+
+    fn layers() -> Layers {
+        Layers {
+            collection: BTreeMap::from([
+                ("version".to_string(), "v2".to_string()),
+                ("tenant".to_string(), "shared".to_string()),
+                ("token".to_string(), "collection".to_string()),
+            ]),
+            environment: BTreeMap::from([
+                (
+                    "base_url".to_string(),
+                    "https://api.example.com".to_string(),
+                ),
+                ("tenant".to_string(), "acme".to_string()),
+                ("token".to_string(), "environment".to_string()),
+            ]),
+            request: BTreeMap::from([("token".to_string(), "hook".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_environment_shadows_collection_and_request_shadows_both() {
+        let layers = layers();
+        let result = substitute(
+            "{{base_url}}/{{version}}/{{tenant}}?t={{token}}",
+            &layers.merged(),
+        );
+        assert_eq!(result, Ok("https://api.example.com/v2/acme?t=hook".into()));
+
+        assert_eq!(layers.layer_of("version"), Some(Layer::Collection));
+        assert_eq!(layers.layer_of("tenant"), Some(Layer::Environment));
+        assert_eq!(layers.layer_of("token"), Some(Layer::Request));
+        assert_eq!(layers.layer_of("missing"), None);
+
+        // without the one-off value the environment wins again
+        let layers = Layers {
+            request: BTreeMap::default(),
+            ..layers
+        };
+        assert_eq!(
+            substitute("{{token}}", &layers.merged()),
+            Ok("environment".into())
+        );
+    }
+
+    #[test]
+    fn test_variables_missing_from_every_layer_are_unresolved() {
+        let result = substitute("{{version}}/{{region}}/{{$uuid}}", &layers().merged());
+        assert_eq!(
+            result,
+            Err(VariableError::Unresolved(vec!["region".into()]))
+        );
+        // an empty layer doesn't hide the ones below it
+        let layers = Layers {
+            environment: BTreeMap::default(),
+            ..layers()
+        };
+        assert_eq!(
+            substitute("{{tenant}}", &layers.merged()),
+            Ok("shared".into())
+        );
+    }
+
+    #[test]
+    fn test_sources_tell_the_layer_of_every_variable_used() {
+        let request = Request {
+            id: "id".into(),
+            method: RequestMethod::Get,
+            name: "users".into(),
+            uri: "{{base_url}}/{{version}}/{{ tenant }}/{{region}}?id={{$uuid}}".into(),
+            headers: Some(vec![HeaderMap {
+                pair: ("Authorization".into(), "Bearer {{token}}".into()),
+                enabled: true,
+            }]),
+            body: Some("{{version}}".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sources(&request, &layers()),
+            vec![
+                ("base_url".to_string(), Layer::Environment),
+                ("version".to_string(), Layer::Collection),
+                ("tenant".to_string(), Layer::Environment),
+                ("token".to_string(), Layer::Request),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_request_substitutes_every_field() {
+        let request = Request {
+            id: "id".into(),
+            method: RequestMethod::Post,
+            name: "login".into(),
+            uri: "{{base_url}}/login".into(),
+            headers: Some(vec![
+                HeaderMap {
+                    pair: ("Authorization".into(), "Bearer {{token}}".into()),
+                    enabled: true,
+                },
+                HeaderMap {
+                    pair: ("X-Ignored".into(), "{{not_checked}}".into()),
+                    enabled: false,
+                },
+            ]),
+            auth_method: Some(AuthMethod::AwsSigV4 {
+                access_key: "AKIDEXAMPLE".into(),
+                secret_key: "{{token}}".into(),
+                region: "us-east-1".into(),
+                service: "execute-api".into(),
+                session_token: None,
+            }),
+            body: Some(r#"{"token": "{{token}}"}"#.into()),
+            ..Default::default()
+        };
+
+        let resolved = resolve_request(&request, &variables()).unwrap();
+
+        assert_eq!(resolved.uri, "https://api.example.com/login");
+        assert_eq!(
+            resolved.headers.as_ref().unwrap()[0].pair.1,
+            "Bearer secret"
+        );
+        assert_eq!(resolved.body.as_deref(), Some(r#"{"token": "secret"}"#));
+        let Some(AuthMethod::AwsSigV4 { secret_key, .. }) = resolved.auth_method else {
+            panic!("expected the auth method to be kept");
+        };
+        assert_eq!(secret_key, "secret");
+    }
+
+    #[test]
+    fn test_raw_bodies_are_sent_as_written() {
+        let request = Request {
+            id: "id".into(),
+            method: RequestMethod::Post,
+            name: "template".into(),
+            uri: "{{base_url}}/templates/\\{{id}}".into(),
+            headers: Some(vec![HeaderMap {
+                pair: ("Authorization".into(), "Bearer {{token}}".into()),
+                enabled: true,
+            }]),
+            body: Some(r#"{"text": "hi {{name}}", "raw": "\{{token}}"}"#.into()),
+            raw_body: true,
+            ..Default::default()
+        };
+
+        // `name` is only on the body, so nothing is missing
+        let resolved = resolve_request(&request, &variables()).unwrap();
+        assert_eq!(resolved.uri, "https://api.example.com/templates/{{id}}");
+        assert_eq!(
+            resolved.headers.as_ref().unwrap()[0].pair.1,
+            "Bearer secret"
+        );
+        assert_eq!(resolved.body, request.body);
+        let (preview, unresolved) = resolve_request_partially(&request, &variables());
+        assert_eq!(preview, resolved);
+        assert!(unresolved.is_empty());
+
+        // a value captured into the environment is only used by the headers
+        let mut layers = layers();
+        layers.request = BTreeMap::default();
+        layers
+            .environment
+            .insert("name".to_string(), "captured".to_string());
+        assert_eq!(
+            sources(&request, &layers),
+            vec![
+                ("base_url".to_string(), Layer::Environment),
+                ("token".to_string(), Layer::Environment),
+            ]
+        );
+
+        let request = Request {
+            raw_body: false,
+            ..request
+        };
+        let resolved = resolve_request(&request, &layers.merged()).unwrap();
+        assert_eq!(
+            resolved.body.as_deref(),
+            Some(r#"{"text": "hi captured", "raw": "{{token}}"}"#)
+        );
+    }
+}